@@ -0,0 +1,13 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The costs of the calls between Rust and Java, run with `cargo bench`, the cdylib must be built first
+
+include!(concat!(env!("OUT_DIR"), "/jaffi_benches.rs"));
+
+criterion::criterion_group!(benches, jaffi_benches);
+criterion::criterion_main!(benches);
@@ -6,7 +6,7 @@ use std::{
     process::Command,
 };
 
-use jaffi::Jaffi;
+use jaffi::{ExportNames, Jaffi, PendingExceptionChecks, Prelude};
 
 fn class_path() -> PathBuf {
     PathBuf::from(std::env::var("OUT_DIR").expect("OUT_DIR not set")).join("java/classes")
@@ -35,14 +35,13 @@ fn find_files(mut search_paths: Vec<Cow<'_, Path>>, extension: &str) -> Vec<Path
                 e if e.is_dir() => {
                     search_paths.push(path.into());
                 }
-                e if e.is_file() => {
-                    if path
+                e if e.is_file()
+                    && path
                         .extension()
                         .map(|ext| ext == extension)
-                        .unwrap_or(false)
-                    {
-                        java_files.push(path);
-                    }
+                        .unwrap_or(false) =>
+                {
+                    java_files.push(path);
                 }
                 _ => (),
             }
@@ -52,6 +51,23 @@ fn find_files(mut search_paths: Vec<Cow<'_, Path>>, extension: &str) -> Vec<Path
     java_files
 }
 
+/// A `javac` reading the sources as UTF-8, and writing the files of the non-ASCII class names in a UTF-8 locale
+fn javac() -> Command {
+    let mut cmd = Command::new("javac");
+    cmd.arg("-encoding").arg("UTF-8");
+
+    // the locale of the first of these that is set is used, javac writes the file names in its encoding
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|locale| !locale.is_empty()))
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    if cfg!(unix) && !locale.contains("utf-8") && !locale.contains("utf8") {
+        cmd.env("LC_ALL", "C.UTF-8");
+    }
+    cmd
+}
+
 fn compile_java() {
     let java_files = find_java_files()
         .into_iter()
@@ -69,8 +85,10 @@ fn compile_java() {
     std::io::stderr().write_all(&output.stdout).unwrap();
     std::io::stderr().write_all(&output.stderr).unwrap();
 
-    let mut cmd = Command::new("javac");
-    cmd.arg("-d")
+    // the names of the parameters are used by the builders of constructors
+    let mut cmd = javac();
+    cmd.arg("-parameters")
+        .arg("-d")
         .arg(&class_path)
         .arg("-h")
         .arg(class_path)
@@ -90,6 +108,31 @@ fn compile_java() {
     eprintln!("successfully compiled java");
 }
 
+/// Checks that the generated loader compiles, with valid javadoc
+fn compile_loader(output_dir: &Path) {
+    let class_path = class_path();
+
+    let mut cmd = javac();
+    cmd.arg("-Xdoclint:all/protected")
+        .arg("-Werror")
+        .arg("-cp")
+        .arg(&class_path)
+        .arg("-d")
+        .arg(&class_path)
+        .arg(output_dir.join("net/bluejekyll/NativeLoader.java"));
+
+    eprintln!("javac: {cmd:?}");
+
+    let output = cmd.output().expect("Failed to execute command");
+
+    std::io::stderr().write_all(&output.stdout).unwrap();
+    std::io::stderr().write_all(&output.stderr).unwrap();
+
+    if !output.status.success() {
+        panic!("javac failed for the loader");
+    }
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
     // only need this if you need to compile the java, this is needed for the integration tests...
     compile_java();
@@ -101,26 +144,138 @@ fn main() -> Result<(), Box<dyn Error>> {
         Cow::from("net.bluejekyll.NativeArrays"),
         Cow::from("net.bluejekyll.RustKeywords"),
         Cow::from("net.bluejekyll.Exceptions"),
+        Cow::from("net.bluejekyll.NativeEnums"),
+        Cow::from("net.bluejekyll.NativeOptionals"),
+        Cow::from("net.bluejekyll.NativeTime"),
+        Cow::from("net.bluejekyll.NativeUuids"),
+        Cow::from("net.bluejekyll.NativeBigNums"),
+        Cow::from("net.bluejekyll.NativeStreams"),
+        Cow::from("net.bluejekyll.NativeConstants"),
+        Cow::from("net.bluejekyll.NativeFutures"),
+        Cow::from("net.bluejekyll.NativeFrames"),
+        Cow::from("net.bluejekyll.NativeMonitors"),
+        Cow::from("net.bluejekyll.AbstractNatives"),
+        Cow::from("net.bluejekyll.NativeCounter"),
+        Cow::from("net.bluejekyll.NativeContext"),
+        Cow::from("net.bluejekyll.NativeBeans"),
+        Cow::from("net.bluejekyll.NativeRecords"),
+        Cow::from("net.bluejekyll.NativeSealed"),
+        Cow::from("net.bluejekyll.NativeFallbacks"),
+        Cow::from("net.bluejekyll.NativeHooks"),
+        Cow::from("net.bluejekyll.NativeVarargs"),
+        Cow::from("net.bluejekyll.NativeBuffers"),
+        Cow::from("net.bluejekyll.aggregate.NativeAdder"),
+        Cow::from("net.bluejekyll.aggregate.NativeGreeter"),
+        Cow::from("net.bluejekyll.NativeOverrides"),
+        Cow::from("net.bluejekyll.NativeBuilders"),
+        Cow::from("net.bluejekyll.NativeBridges"),
+        Cow::from("net.bluejekyll.NativeNames"),
+        Cow::from("net.bluejekyll.NativeNames$Inner$Ünïcode"),
+        Cow::from("net.bluejekyll.NativeNames$𝒜"),
+        Cow::from("net.bluejekyll.NativeAnnotations"),
+        Cow::from("net.bluejekyll.NativeDispatch"),
+    ];
+    let classes_to_wrap = vec![
+        Cow::from("net.bluejekyll.ParentClass"),
+        Cow::from("net.bluejekyll.Named"),
+        Cow::from("net.bluejekyll.Shape"),
+        Cow::from("net.bluejekyll.Notifier"),
+        Cow::from("net.bluejekyll.Connection"),
+        Cow::from("net.bluejekyll.Legacy"),
+        Cow::from("net.bluejekyll.Document"),
+        Cow::from("net.bluejekyll.Draft"),
+        Cow::from("net.bluejekyll.Doctor"),
+        Cow::from("net.bluejekyll.Titled"),
+        Cow::from("net.bluejekyll.DispatchCallback"),
     ];
-    let classes_to_wrap = vec![Cow::from("net.bluejekyll.ParentClass")];
     let output_dir = PathBuf::from(std::env::var("OUT_DIR").expect("OUT_DIR not set"));
     let output_file = Cow::from(Path::new("generated_jaffi.rs"));
 
     let jaffi = Jaffi::builder()
         .output_dir(&output_dir)
         .output_filename(&output_file)
+        .header_filename(Path::new("generated_jaffi.h"))
+        .report_filename(Path::new("jaffi_report.json"))
+        .bench_filename(Path::new("jaffi_benches.rs"))
+        .bench_bindings(Cow::from("jaffi_integration_tests::net_bluejekyll"))
+        .loader_class(Cow::from("net.bluejekyll.NativeLoader"))
         .native_classes(classes)
         .classes_to_wrap(classes_to_wrap)
         .classpath(vec![Cow::from(class_path)])
+        .panic_exception(Cow::from("net.bluejekyll.NativePanicError"))
+        .panic_message_fn(Cow::from("crate::panic_message"))
+        .user_on_load_fn(Cow::from("crate::on_load"))
+        .user_on_unload_fn(Cow::from("crate::on_unload"))
+        .dispatcher((
+            Cow::from("crate::Command"),
+            Cow::from("crate::handle_command"),
+        ))
+        .native_enter_fn(Cow::from("crate::on_native_enter"))
+        .native_exit_fn(Cow::from("crate::on_native_exit"))
+        .on_load_name(Cow::from("jaffi_on_load"))
+        .on_unload_name(Cow::from("jaffi_on_unload"))
+        .borrowed_string_args(true)
+        .time_conversions(true)
+        .uuid_conversions(true)
+        .bignum_conversions(true)
+        .local_frames(true)
+        .cached_method_ids(true)
+        .scratch_buffers(true)
+        .interned_string_args(true)
+        .tracing_spans(true)
+        .async_wrappers(true)
+        .identity_methods(true)
+        .test_harness(true)
+        .class_registry(true)
+        .validate_args(true)
+        .registered_impls(vec![Cow::from("net.bluejekyll.NativeCounter")])
+        .context_impls(vec![Cow::from("net.bluejekyll.NativeContext")])
+        .java_fallback_impls(vec![Cow::from("net.bluejekyll.NativeFallbacks")])
+        .cancel_handles(vec![Cow::from("net.bluejekyll.NativeFutures")])
+        .aggregate_packages(vec![Cow::from("net.bluejekyll.aggregate")])
+        .overrides_file(Path::new("overrides.toml"))
+        .local_capacities(vec![(Cow::from("net.bluejekyll.NativeArrays"), 64)])
+        .export_names(ExportNames::Both)
+        .pending_exception_checks(PendingExceptionChecks::Debug)
+        .bean_classes(vec![Cow::from("net.bluejekyll.Person")])
+        .listener_interfaces(vec![Cow::from("net.bluejekyll.CountListener")])
+        .package_private_classes(vec![Cow::from("net.bluejekyll.Ledger")])
+        .bean_serde(true)
+        .package_modules(true)
+        .prelude(Prelude::default())
+        .buffer_classes(vec![(
+            Cow::from("net.bluejekyll.Particle"),
+            Cow::from("crate::Particle"),
+        )])
+        .bridges(vec![(
+            Cow::from("net.bluejekyll.Money"),
+            Cow::from("crate::Money"),
+        )])
+        .late_bound_classes(vec![(
+            Cow::from("net.bluejekyll.LateBound"),
+            vec![
+                Cow::from("<init>(I)V"),
+                Cow::from("twice()I"),
+                Cow::from("static version()Ljava/lang/String;"),
+            ],
+        )])
         .build();
 
-    jaffi.generate()?;
+    let report = jaffi.generate()?;
+    for entry in report.entries() {
+        eprintln!("{entry}");
+    }
+    compile_loader(&output_dir);
 
     // let's format the file to help with debugging build issues
-    let jaffi_file = output_dir.join(output_file);
+    let jaffi_file = output_dir.join(&output_file);
 
     let mut cmd = Command::new("rustfmt");
-    cmd.arg("--emit").arg("files").arg(jaffi_file);
+    cmd.arg("--edition")
+        .arg("2021")
+        .arg("--emit")
+        .arg("files")
+        .arg(jaffi_file);
 
     eprintln!("cargo fmt: {cmd:?}");
     let output = cmd.output();
@@ -135,5 +290,8 @@ fn main() -> Result<(), Box<dyn Error>> {
         }
     }
 
+    // the formatted file is still up to date, only the tokens are compared
+    jaffi.check()?;
+
     Ok(())
 }
@@ -1,23 +1,145 @@
+use std::{
+    any::Any,
+    borrow::Cow,
+    future::Future,
+    io::{Read, Write},
+    pin::pin,
+    sync::{
+        atomic::{AtomicI32, AtomicI64, Ordering},
+        Arc,
+    },
+    task::{Context, Poll, Wake, Waker},
+    thread::{self, Thread},
+    time::{Duration, SystemTime},
+};
+
 use jaffi_support::{
-    jni::{objects::JObject, JNIEnv},
-    Error,
+    bignum::{BigDecimal, BigInt},
+    cancel,
+    context::CallCtx,
+    exceptions::{get_panic_message, IllegalStateException},
+    frame,
+    future::{self, BoxFuture},
+    io::{JavaInputStream, JavaOutputStream},
+    jni::{
+        objects::{JObject, JValue},
+        sys::jint,
+        JNIEnv, JavaVM,
+    },
+    string::{Interned, JavaString},
+    uuid::Uuid,
+    Cancellation, Error, JavaBridge,
 };
 use net_bluejekyll::NetBluejekyllNativeStrings;
 
 use crate::net_bluejekyll::*;
 
-mod net_bluejekyll {
+/// Public for the compile tests of the generated types
+pub mod net_bluejekyll {
     #![allow(
         dead_code,
         clippy::unused_unit,
         clippy::needless_lifetimes,
         clippy::let_unit_value,
-        clippy::let_and_return
+        clippy::let_and_return,
+        clippy::wrong_self_convention
     )]
 
     include!(concat!(env!("OUT_DIR"), "/generated_jaffi.rs"));
 }
 
+/// Payload of the panics with an error code, see `panic_message`
+struct NativeErrorCode(i32);
+
+/// Converts panics to the message of the `NativePanicError`, configured in the `build.rs`
+///
+/// The messages of string panics are kept, the `NativeErrorCode` payloads are described.
+fn panic_message(payload: &(dyn Any + Send)) -> Cow<'_, str> {
+    match payload.downcast_ref::<NativeErrorCode>() {
+        Some(NativeErrorCode(code)) => format!("native error code {code}").into(),
+        None => get_panic_message(payload),
+    }
+}
+
+/// The generated load hook is renamed in the `build.rs`, this tests calling it from our own `JNI_OnLoad`
+#[no_mangle]
+pub extern "system" fn JNI_OnLoad(vm: JavaVM, _reserved: *const std::ffi::c_void) -> jint {
+    eprintln!("JNI_OnLoad: calling jaffi_on_load");
+    net_bluejekyll::jaffi_on_load(vm)
+}
+
+/// The generated unload hook is renamed with the load hook, it releases the global references
+#[no_mangle]
+pub extern "system" fn JNI_OnUnload(vm: JavaVM, _reserved: *const std::ffi::c_void) {
+    eprintln!("JNI_OnUnload: calling jaffi_on_unload");
+    net_bluejekyll::jaffi_on_unload(vm)
+}
+
+/// Called from `JNI_OnLoad`, configured in the `build.rs`
+fn on_load(vm: &JavaVM) {
+    let version = vm
+        .get_env()
+        .and_then(|env| env.get_version())
+        .map(i32::from)
+        .unwrap_or_default();
+    eprintln!("on_load: JNI version {version:#x}");
+
+    // each future gets its own thread, a real application would use its async runtime
+    future::set_executor(|future: BoxFuture<()>| {
+        thread::spawn(move || block_on(future));
+    });
+
+    // the library can be loaded more than once in a JVM, e.g. by different class loaders
+    net_bluejekyll::register_native_counter_rs(Box::new(NativeCounterRsImpl::default())).ok();
+}
+
+/// Called from `JNI_OnUnload`, configured in the `build.rs`
+fn on_unload(_vm: &JavaVM) {
+    eprintln!("on_unload");
+    future::clear_executor();
+}
+
+/// Calls of `NativeHooks` entered, counted by `on_native_enter`
+static HOOKS_ENTERED: AtomicI64 = AtomicI64::new(0);
+/// Calls of `NativeHooks` exited, counted by `on_native_exit`
+static HOOKS_EXITED: AtomicI64 = AtomicI64::new(0);
+
+/// Called on entering every native function, configured in the `build.rs`
+fn on_native_enter(class: &'static str, _method: &'static str) {
+    if class == "net.bluejekyll.NativeHooks" {
+        HOOKS_ENTERED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// Called on leaving every native function, configured in the `build.rs`
+fn on_native_exit(class: &'static str, _method: &'static str, _elapsed: Duration) {
+    if class == "net.bluejekyll.NativeHooks" {
+        HOOKS_EXITED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// A minimal executor for the tests, polls the future on the current thread
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        thread::park();
+    }
+}
+
 struct NativePrimitivesRsImpl<'j> {
     env: JNIEnv<'j>,
 }
@@ -62,6 +184,29 @@ impl<'j> net_bluejekyll::NativePrimitivesRs<'j> for NativePrimitivesRsImpl<'j> {
         ret
     }
 
+    fn add_boxed_native(
+        &self,
+        this: NetBluejekyllNativePrimitives<'j>,
+        arg0: Option<i32>,
+        arg1: Option<i64>,
+    ) -> Option<i32> {
+        let ret = this.add_boxed(self.env, arg0, arg1);
+        println!("add_boxed_native: got result from java: {ret:?}");
+        ret.map(|ret| ret as i32)
+    }
+
+    fn not_boxed(
+        &self,
+        _this: NetBluejekyllNativePrimitives<'j>,
+        arg0: Option<bool>,
+    ) -> Option<bool> {
+        arg0.map(|b| !b)
+    }
+
+    fn echo_char(&self, _this: NetBluejekyllNativePrimitives<'j>, arg0: char) -> char {
+        arg0
+    }
+
     fn print_hello_native(&self, this: NetBluejekyllNativePrimitives<'j>) {
         println!("print_hello_native: calling print_hello");
         this.print_hello(self.env)
@@ -114,29 +259,67 @@ impl<'j> net_bluejekyll::NativeStringsRs<'j> for NativeStringsRsImpl<'j> {
     fn ctor(
         &self,
         _class: NetBluejekyllNativeStringsClass<'j>,
-        arg0: String,
+        arg0: JavaString<'j>,
     ) -> NetBluejekyllNativeStrings<'j> {
         println!("ctor: {arg0}");
         NetBluejekyllNativeStrings::new_1net_bluejekyll_native_strings_ljava_lang_string_2(
-            self.env, arg0,
+            self.env,
+            arg0.to_string(),
         )
     }
 
-    fn eat_string(&self, _this: NetBluejekyllNativeStrings<'j>, arg0: String) {
+    fn via_factory(&self, _this: NetBluejekyllNativeStrings<'j>, arg0: JavaString<'j>) -> String {
+        // calls back into `ctor` through Java
+        let strings = NetBluejekyllNativeStrings::new_from_ctor(self.env, arg0.to_string());
+        strings.return_string(self.env, "")
+    }
+
+    fn eat_string(&self, _this: NetBluejekyllNativeStrings<'j>, arg0: JavaString<'j>) {
         println!("eatString ate: {arg0}");
     }
 
-    fn tie_off_string(&self, _this: NetBluejekyllNativeStrings<'j>, arg0: String) -> String {
+    fn tie_off_string(
+        &self,
+        _this: NetBluejekyllNativeStrings<'j>,
+        arg0: JavaString<'j>,
+    ) -> String {
         println!("tieOffString got: {arg0}");
-        arg0
+        arg0.to_string()
     }
 
-    fn return_string_native(&self, this: NetBluejekyllNativeStrings<'j>, append: String) -> String {
-        let ret = this.return_string(self.env, append);
+    fn return_string_native(
+        &self,
+        this: NetBluejekyllNativeStrings<'j>,
+        append: JavaString<'j>,
+    ) -> String {
+        let ret = this.return_string(self.env, append.to_string());
         println!("returnStringNative got: {ret}");
 
         ret
     }
+
+    fn return_interned_native(&self, this: NetBluejekyllNativeStrings<'j>) -> String {
+        // the second call uses the cached String
+        let first = this.return_string(self.env, Interned(", interned"));
+        let second = this.return_string(self.env, Interned(", interned"));
+        assert_eq!(first, second);
+
+        first
+    }
+
+    fn tie_off_char_sequence(&self, _this: NetBluejekyllNativeStrings<'j>, seq: String) -> String {
+        println!("tieOffCharSequence got: {seq}");
+        seq
+    }
+
+    fn append_to_builder(
+        &self,
+        _this: NetBluejekyllNativeStrings<'j>,
+        builder: String,
+        append: String,
+    ) -> String {
+        builder + &append
+    }
 }
 
 pub(crate) struct NativeArraysRsImpl<'j> {
@@ -200,6 +383,47 @@ impl<'j> net_bluejekyll::NativeArraysRs<'j> for NativeArraysRsImpl<'j> {
 
         bytes
     }
+
+    fn sum_java_bytes_native(
+        &self,
+        class: net_bluejekyll::NetBluejekyllNativeArraysClass<'j>,
+    ) -> i32 {
+        class.sum_java_bytes_bytes(self.env, &[1, 2, 3])
+    }
+
+    fn children(
+        &self,
+        this: net_bluejekyll::NetBluejekyllNativeArrays<'j>,
+        count: i32,
+    ) -> jaffi_support::arrays::JavaObjectArray<'j, net_bluejekyll::NetBluejekyllNativeArrays<'j>>
+    {
+        let name = this.get_name(self.env);
+        let children = (0..count).map(|i| {
+            NetBluejekyllNativeArrays::new_1net_bluejekyll_native_arrays_ljava_lang_string_2(
+                self.env,
+                format!("{name}.{i}"),
+            )
+        });
+
+        jaffi_support::arrays::JavaObjectArray::new(self.env, children)
+            .expect("could not create array")
+    }
+
+    fn join_names(
+        &self,
+        _class: net_bluejekyll::NetBluejekyllNativeArraysClass<'j>,
+        arrays: jaffi_support::arrays::JavaObjectArray<
+            'j,
+            net_bluejekyll::NetBluejekyllNativeArrays<'j>,
+        >,
+    ) -> String {
+        let arrays = arrays.to_vec(self.env).expect("could not read array");
+        arrays
+            .iter()
+            .map(|arrays| arrays.get_name(self.env))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
 }
 
 struct RustKeywordsRsImpl<'j> {
@@ -338,11 +562,11 @@ impl<'j> ExceptionsRs<'j> for ExceptionsRsImpl<'j> {
     fn throws_something_ljava_lang_string_2(
         &self,
         _this: NetBluejekyllExceptions<'j>,
-        msg: String,
+        msg: JavaString<'j>,
     ) -> Result<(), Error<SomethingExceptionErr>> {
         Err(Error::new(
             SomethingExceptionErr::SomethingException(SomethingException),
-            msg,
+            msg.to_string(),
         ))
     }
 
@@ -354,15 +578,944 @@ impl<'j> ExceptionsRs<'j> for ExceptionsRsImpl<'j> {
             .i_always_throw(self.env)
             .expect_err("error expected here");
 
-        #[allow(irrefutable_let_patterns)]
-        if let SomethingExceptionErr::SomethingException(SomethingException) = ex.throwable() {
-            net_bluejekyll::NetBluejekyllSomethingException::from(JObject::from(ex.exception()))
-        } else {
-            panic!("expected SomethingException")
+        match ex.throwable() {
+            SomethingExceptionErr::SomethingException(SomethingException) => {
+                let exception: NetBluejekyllSomethingException<'j> =
+                    ex.object().expect("not a SomethingException");
+                let detail = exception.get_detail(self.env);
+                println!("catchesSomething: {detail}");
+                assert_eq!(detail, "detail: iAlwaysThrow");
+
+                exception
+            }
         }
     }
 
     fn panics_are_runtime_exceptions(&self, _this: NetBluejekyllExceptions<'j>) {
         panic!("{}", "Panics are safe".to_string());
     }
+
+    fn panics_with_error_code(&self, _this: NetBluejekyllExceptions<'j>, code: i32) {
+        std::panic::panic_any(NativeErrorCode(code));
+    }
+
+    fn read_file(
+        &self,
+        _this: NetBluejekyllExceptions<'j>,
+        path: JavaString<'j>,
+    ) -> Result<String, Error<IOExceptionErr>> {
+        Ok(std::fs::read_to_string(&*path)?)
+    }
+}
+
+struct NativeEnumsRsImpl<'j> {
+    env: JNIEnv<'j>,
+}
+
+impl<'j> NativeEnumsRs<'j> for NativeEnumsRsImpl<'j> {
+    fn from_env(env: JNIEnv<'j>) -> Self {
+        Self { env }
+    }
+
+    fn round_trip(
+        &self,
+        _class: NetBluejekyllNativeEnumsClass<'j>,
+        arg0: net::bluejekyll::Fruit<'j>,
+    ) -> net::bluejekyll::Fruit<'j> {
+        // the short names in the package modules, see `package_modules` in the `build.rs`
+        let fruit = arg0
+            .rust_enum(self.env)
+            .expect("could not get ordinal")
+            .expect("unknown fruit");
+        println!("roundTrip: {fruit:?}");
+
+        net::bluejekyll::Fruit::from_rust_enum(self.env, fruit).expect("could not get the constant")
+    }
+
+    fn describe_native(&self, this: NetBluejekyllNativeEnums<'j>) -> String {
+        let description = this.describe(self.env, NetBluejekyllFruitEnum::Banana, "CHERRY");
+        println!("describeNative: {description}");
+
+        description
+    }
+}
+
+struct NativeOptionalsRsImpl<'j> {
+    env: JNIEnv<'j>,
+}
+
+impl<'j> NativeOptionalsRs<'j> for NativeOptionalsRsImpl<'j> {
+    fn from_env(env: JNIEnv<'j>) -> Self {
+        Self { env }
+    }
+
+    fn upper_native(
+        &self,
+        _this: NetBluejekyllNativeOptionals<'j>,
+        arg0: Option<String>,
+    ) -> Option<String> {
+        arg0.map(|s| s.to_uppercase())
+    }
+
+    fn length_native(
+        &self,
+        this: NetBluejekyllNativeOptionals<'j>,
+        arg0: Option<String>,
+    ) -> Option<Option<i32>> {
+        this.length(self.env, arg0)
+    }
+}
+
+struct NativeTimeRsImpl<'j> {
+    env: JNIEnv<'j>,
+}
+
+impl<'j> NativeTimeRs<'j> for NativeTimeRsImpl<'j> {
+    fn from_env(env: JNIEnv<'j>) -> Self {
+        Self { env }
+    }
+
+    fn add_native(
+        &self,
+        _this: NetBluejekyllNativeTime<'j>,
+        arg0: SystemTime,
+        arg1: Duration,
+    ) -> SystemTime {
+        arg0 + arg1
+    }
+
+    fn to_date_native(&self, _this: NetBluejekyllNativeTime<'j>, arg0: SystemTime) -> SystemTime {
+        arg0
+    }
+
+    fn between_native(
+        &self,
+        this: NetBluejekyllNativeTime<'j>,
+        arg0: SystemTime,
+        arg1: SystemTime,
+    ) -> Duration {
+        this.between(self.env, arg0, arg1)
+    }
+}
+
+struct NativeUuidsRsImpl<'j> {
+    _env: JNIEnv<'j>,
+}
+
+impl<'j> NativeUuidsRs<'j> for NativeUuidsRsImpl<'j> {
+    fn from_env(env: JNIEnv<'j>) -> Self {
+        Self { _env: env }
+    }
+
+    fn to_string_native(&self, _this: NetBluejekyllNativeUuids<'j>, arg0: Uuid) -> String {
+        arg0.to_string()
+    }
+
+    fn parse_native(&self, _this: NetBluejekyllNativeUuids<'j>, arg0: JavaString<'j>) -> Uuid {
+        Uuid::parse_str(&arg0).expect("invalid uuid")
+    }
+
+    fn maybe_nil_native(&self, _this: NetBluejekyllNativeUuids<'j>, arg0: Uuid) -> Option<Uuid> {
+        (!arg0.is_nil()).then_some(arg0)
+    }
+}
+
+struct NativeBigNumsRsImpl<'j> {
+    env: JNIEnv<'j>,
+}
+
+impl<'j> NativeBigNumsRs<'j> for NativeBigNumsRsImpl<'j> {
+    fn from_env(env: JNIEnv<'j>) -> Self {
+        Self { env }
+    }
+
+    fn multiply_native(
+        &self,
+        _this: NetBluejekyllNativeBigNums<'j>,
+        arg0: BigInt,
+        arg1: BigInt,
+    ) -> BigInt {
+        arg0 * arg1
+    }
+
+    fn add_native(
+        &self,
+        _this: NetBluejekyllNativeBigNums<'j>,
+        arg0: BigDecimal,
+        arg1: BigDecimal,
+    ) -> BigDecimal {
+        arg0 + arg1
+    }
+
+    fn negate_native(&self, this: NetBluejekyllNativeBigNums<'j>, arg0: BigDecimal) -> BigDecimal {
+        this.negate(self.env, arg0)
+    }
+}
+
+struct NativeStreamsRsImpl<'j> {
+    _env: JNIEnv<'j>,
+}
+
+impl<'j> NativeStreamsRs<'j> for NativeStreamsRsImpl<'j> {
+    fn from_env(env: JNIEnv<'j>) -> Self {
+        Self { _env: env }
+    }
+
+    fn copy_native(
+        &self,
+        _this: NetBluejekyllNativeStreams<'j>,
+        mut arg0: JavaInputStream<'j>,
+        mut arg1: JavaOutputStream<'j>,
+    ) {
+        std::io::copy(&mut arg0, &mut arg1).expect("failed to copy");
+        arg1.flush().expect("failed to flush");
+    }
+
+    fn read_error_native(
+        &self,
+        _this: NetBluejekyllNativeStreams<'j>,
+        mut arg0: JavaInputStream<'j>,
+    ) -> String {
+        let mut buf = Vec::new();
+        arg0.read_to_end(&mut buf)
+            .expect_err("read should have failed")
+            .to_string()
+    }
+}
+
+struct NativeConstantsRsImpl<'j> {
+    env: JNIEnv<'j>,
+}
+
+impl<'j> NativeConstantsRs<'j> for NativeConstantsRsImpl<'j> {
+    fn from_env(env: JNIEnv<'j>) -> Self {
+        Self { env }
+    }
+
+    fn describe_native(&self, _this: NetBluejekyllNativeConstants<'j>) -> String {
+        type Constants<'j> = NetBluejekyllNativeConstantsClass<'j>;
+
+        // Java prints floats with at least one decimal
+        format!(
+            "{},{},{},{},{},{},{:?},{},{}",
+            Constants::FLAG,
+            Constants::BYTE,
+            Constants::CHAR,
+            Constants::SHORT,
+            Constants::VISIBLE,
+            Constants::LONG,
+            Constants::FLOAT,
+            Constants::DOUBLE.is_nan(),
+            Constants::NAME
+        )
+    }
+
+    fn twice_raw_native(&self, this: NetBluejekyllNativeConstants<'j>, arg0: i32) -> i32 {
+        type Wrapper<'j> = NetBluejekyllNativeConstants<'j>;
+
+        let class = self
+            .env
+            .find_class(Wrapper::JAVA_CLASS_DESC)
+            .expect("couldn't find the class");
+        assert!(self
+            .env
+            .is_instance_of(*this, class)
+            .expect("couldn't check the instance"));
+
+        self.env
+            .call_method(
+                *this,
+                Wrapper::TWICE_NAME,
+                Wrapper::TWICE_SIG,
+                &[JValue::Int(arg0)],
+            )
+            .and_then(|v| v.i())
+            .expect("couldn't call twice")
+    }
+}
+
+struct NativeFuturesRsImpl<'j> {
+    _env: JNIEnv<'j>,
+}
+
+impl<'j> NativeFuturesRs<'j> for NativeFuturesRsImpl<'j> {
+    fn from_env(env: JNIEnv<'j>) -> Self {
+        Self { _env: env }
+    }
+
+    fn greet_native(
+        &self,
+        _this: NetBluejekyllNativeFutures<'j>,
+        arg0: JavaString<'j>,
+    ) -> BoxFuture<String> {
+        // JavaString borrows from the JVM, take ownership before the future is spawned
+        let name = arg0.to_string();
+        Box::pin(async move { format!("Hello, {name}") })
+    }
+
+    fn add_native(
+        &self,
+        _this: NetBluejekyllNativeFutures<'j>,
+        arg0: i32,
+        arg1: i32,
+    ) -> BoxFuture<Option<i32>> {
+        Box::pin(async move { Some(arg0 + arg1) })
+    }
+
+    fn panic_native(&self, _this: NetBluejekyllNativeFutures<'j>) -> BoxFuture<()> {
+        Box::pin(async move { panic!("the future panicked") })
+    }
+
+    fn new_cancellation(&self, _this: NetBluejekyllNativeFutures<'j>) -> i64 {
+        Cancellation::new().register()
+    }
+
+    fn until_cancelled(
+        &self,
+        _this: NetBluejekyllNativeFutures<'j>,
+        arg0: i64,
+    ) -> BoxFuture<Option<i64>> {
+        let cancellation = cancel::get(arg0).expect("unknown handle");
+        Box::pin(async move {
+            cancellation.cancelled().await;
+            cancel::release(arg0);
+            Some(arg0)
+        })
+    }
+}
+
+/// Implements all the native classes of `net.bluejekyll.aggregate`
+struct NetBluejekyllAggregateRsImpl<'j> {
+    _env: JNIEnv<'j>,
+}
+
+impl<'j> NetBluejekyllAggregateRs<'j> for NetBluejekyllAggregateRsImpl<'j> {
+    fn from_env(env: JNIEnv<'j>) -> Self {
+        Self { _env: env }
+    }
+
+    fn native_adder_add(
+        &self,
+        _this: NetBluejekyllAggregateNativeAdder<'j>,
+        arg0: i32,
+        arg1: i32,
+    ) -> i32 {
+        arg0 + arg1
+    }
+
+    fn native_adder_twice(
+        &self,
+        _class: NetBluejekyllAggregateNativeAdderClass<'j>,
+        arg0: i64,
+    ) -> i64 {
+        arg0 * 2
+    }
+
+    fn native_greeter_greet(
+        &self,
+        _this: NetBluejekyllAggregateNativeGreeter<'j>,
+        arg0: JavaString<'j>,
+    ) -> String {
+        format!("Hello, {arg0}")
+    }
+}
+
+/// The methods are generated as changed by `overrides.toml`
+struct NativeOverridesRsImpl<'j> {
+    env: JNIEnv<'j>,
+}
+
+impl<'j> NativeOverridesRs<'j> for NativeOverridesRsImpl<'j> {
+    fn from_env(env: JNIEnv<'j>) -> Self {
+        Self { env }
+    }
+
+    fn hello(&self, _this: NetBluejekyllNativeOverrides<'j>, arg0: Option<String>) -> String {
+        format!("Hello, {}", arg0.as_deref().unwrap_or("nobody"))
+    }
+
+    fn parse_number(
+        &self,
+        _this: NetBluejekyllNativeOverrides<'j>,
+        arg0: JavaString<'j>,
+    ) -> Result<i32, Error<RuntimeExceptionErr>> {
+        arg0.parse().map_err(|_| {
+            Error::new(
+                RuntimeExceptionErr::RuntimeException(RuntimeException),
+                format!("not a number: {arg0}"),
+            )
+        })
+    }
+
+    fn sum_bytes(&self, _this: NetBluejekyllNativeOverrides<'j>, arg0: Vec<u8>) -> i32 {
+        arg0.iter().map(|b| *b as i32).sum()
+    }
+
+    fn call_wrappers(&self, this: NetBluejekyllNativeOverrides<'j>) -> String {
+        let joined = this.join(self.env, None::<String>, "second");
+        let caught = this
+            .fail(self.env)
+            .expect_err("fail always throws")
+            .downcast::<IllegalStateException>()
+            .is_ok();
+
+        format!("{joined}, caught: {caught}")
+    }
+}
+
+struct NativeAnnotationsRsImpl<'j> {
+    env: JNIEnv<'j>,
+}
+
+impl<'j> NativeAnnotationsRs<'j> for NativeAnnotationsRsImpl<'j> {
+    fn from_env(env: JNIEnv<'j>) -> Self {
+        Self { env }
+    }
+
+    fn hello(&self, _this: NetBluejekyllNativeAnnotations<'j>, arg0: Option<String>) -> String {
+        format!("Hello, {}", arg0.as_deref().unwrap_or("nobody"))
+    }
+
+    fn call_wrappers(&self, this: NetBluejekyllNativeAnnotations<'j>) -> String {
+        this.join(self.env, None::<String>, "second")
+    }
+}
+
+struct NativeBuildersRsImpl<'j> {
+    env: JNIEnv<'j>,
+}
+
+impl<'j> NativeBuildersRs<'j> for NativeBuildersRsImpl<'j> {
+    fn from_env(env: JNIEnv<'j>) -> Self {
+        Self { env }
+    }
+
+    fn connect(
+        &self,
+        _class: NetBluejekyllNativeBuildersClass<'j>,
+        arg0: JavaString<'j>,
+    ) -> NetBluejekyllConnection<'j> {
+        // the arguments can be set in any order
+        NetBluejekyllConnectionBuilder::new()
+            .flavor(NetBluejekyllFruitEnum::Cherry)
+            .port(8443)
+            .host(arg0.to_string())
+            .timeout_millis(500)
+            .secure(true)
+            .build(self.env)
+    }
+}
+
+/// A Rust domain type bridged to `net.bluejekyll.Money`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Money {
+    cents: i64,
+    currency: String,
+}
+
+impl<'j> JavaBridge<'j, NetBluejekyllMoney<'j>> for Money {
+    fn from_java(env: JNIEnv<'j>, java: NetBluejekyllMoney<'j>) -> Self {
+        Self {
+            cents: java.get_cents(env),
+            currency: java.get_currency(env),
+        }
+    }
+
+    fn to_java(self, env: JNIEnv<'j>) -> NetBluejekyllMoney<'j> {
+        NetBluejekyllMoney::new_1net_bluejekyll_money(env, self.cents, self.currency)
+    }
+}
+
+struct NativeBridgesRsImpl;
+
+impl<'j> NativeBridgesRs<'j> for NativeBridgesRsImpl {
+    fn from_env(_env: JNIEnv<'j>) -> Self {
+        Self
+    }
+
+    fn add(&self, _class: NetBluejekyllNativeBridgesClass<'j>, arg0: Money, arg1: Money) -> Money {
+        assert_eq!(arg0.currency, arg1.currency);
+
+        Money {
+            cents: arg0.cents + arg1.cents,
+            currency: arg0.currency,
+        }
+    }
+
+    fn non_zero(&self, _class: NetBluejekyllNativeBridgesClass<'j>, arg0: Money) -> Option<Money> {
+        (arg0.cents != 0).then_some(arg0)
+    }
+}
+
+struct NativeNamesRsImpl;
+
+/// the methods are named for their JNI names, e.g. `add_1one` for `add_one`
+impl<'j> NativeNamesRs<'j> for NativeNamesRsImpl {
+    fn from_env(_env: JNIEnv<'j>) -> Self {
+        Self
+    }
+
+    fn add_1one(&self, _class: NetBluejekyllNativeNamesClass<'j>, arg0: i32) -> i32 {
+        arg0 + 1
+    }
+
+    fn dollar_00024sign(&self, _class: NetBluejekyllNativeNamesClass<'j>, arg0: i32) -> i32 {
+        -arg0
+    }
+
+    fn gr_000fc_000dfe(
+        &self,
+        _class: NetBluejekyllNativeNamesClass<'j>,
+        arg0: JavaString<'j>,
+    ) -> String {
+        format!("Grüße, {arg0}")
+    }
+
+    fn r_0d835_0dc9cdd(
+        &self,
+        _class: NetBluejekyllNativeNamesClass<'j>,
+        arg0: i32,
+        arg1: i32,
+    ) -> i32 {
+        arg0 + arg1
+    }
+}
+
+struct NativeNamesInner000dcn000efcodeRsImpl;
+
+impl<'j> NativeNamesInner000dcn000efcodeRs<'j> for NativeNamesInner000dcn000efcodeRsImpl {
+    fn from_env(_env: JNIEnv<'j>) -> Self {
+        Self
+    }
+
+    fn twice(
+        &self,
+        _class: NetBluejekyllNativeNamesInner000dcn000efcodeClass<'j>,
+        arg0: i32,
+    ) -> i32 {
+        arg0 * 2
+    }
+}
+
+struct NativeNames0d8350dc9cRsImpl;
+
+impl<'j> NativeNames0d8350dc9cRs<'j> for NativeNames0d8350dc9cRsImpl {
+    fn from_env(_env: JNIEnv<'j>) -> Self {
+        Self
+    }
+
+    fn plus(&self, _this: NetBluejekyllNativeNames0d8350dc9c<'j>, arg0: i32) -> i32 {
+        arg0 + 1
+    }
+}
+
+struct NativeFramesRsImpl<'j> {
+    env: JNIEnv<'j>,
+}
+
+impl<'j> NativeFramesRs<'j> for NativeFramesRsImpl<'j> {
+    fn from_env(env: JNIEnv<'j>) -> Self {
+        Self { env }
+    }
+
+    fn sum_lengths_native(&self, this: NetBluejekyllNativeFrames<'j>, arg0: i32) -> i32 {
+        // the wrappers are generated with local_frames, the argument Strings are released on each call
+        (0..arg0)
+            .map(|_| this.length(self.env, "hello".to_string()))
+            .sum()
+    }
+
+    fn last_twice_native(&self, this: NetBluejekyllNativeFrames<'j>, arg0: i32) -> String {
+        // the returned String is converted inside the frame, so its reference is released too
+        let mut last = String::new();
+        for i in 0..arg0 {
+            last = frame::with_frame(self.env, frame::DEFAULT_CAPACITY, || {
+                this.twice(self.env, i.to_string())
+            });
+        }
+
+        last
+    }
+}
+
+struct NativeMonitorsRsImpl<'j> {
+    env: JNIEnv<'j>,
+}
+
+impl<'j> NativeMonitorsRs<'j> for NativeMonitorsRsImpl<'j> {
+    fn from_env(env: JNIEnv<'j>) -> Self {
+        Self { env }
+    }
+
+    fn increment_native(&self, this: NetBluejekyllNativeMonitors<'j>, arg0: i32) {
+        for _ in 0..arg0 {
+            let _guard = this.lock(self.env).expect("couldn't lock the monitor");
+            let count = this.get_count(self.env);
+            this.set_count(self.env, count + 1);
+        }
+    }
+
+    fn locked_native(&self, this: NetBluejekyllNativeMonitors<'j>) -> bool {
+        let _guard = this.lock(self.env).expect("couldn't lock the monitor");
+        this.is_locked(self.env)
+    }
+}
+
+struct AbstractNativesRsImpl<'j> {
+    env: JNIEnv<'j>,
+}
+
+impl<'j> AbstractNativesRs<'j> for AbstractNativesRsImpl<'j> {
+    fn from_env(env: JNIEnv<'j>) -> Self {
+        Self { env }
+    }
+
+    fn add_to_base_native(&self, this: NetBluejekyllAbstractNatives<'j>, arg0: i32) -> i32 {
+        // base is abstract, this calls the implementation of the subclass
+        this.base(self.env) + arg0
+    }
+
+    fn describe_native(
+        &self,
+        _class: NetBluejekyllAbstractNativesClass<'j>,
+        arg0: NetBluejekyllNamed<'j>,
+    ) -> String {
+        // prefix is a static method of the interface, called through the Static trait
+        let prefix = arg0.prefix(self.env);
+        format!("{prefix}{}", arg0.name(self.env))
+    }
+}
+
+/// Registered in `on_load`, the count is kept across calls
+#[derive(Default)]
+struct NativeCounterRsImpl {
+    count: AtomicI32,
+}
+
+impl NativeCounterRs for NativeCounterRsImpl {
+    fn next_native<'j>(
+        &self,
+        _env: JNIEnv<'j>,
+        _class: NetBluejekyllNativeCounterClass<'j>,
+    ) -> i32 {
+        self.count.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    fn label_native<'j>(
+        &self,
+        _env: JNIEnv<'j>,
+        _this: NetBluejekyllNativeCounter<'j>,
+        arg0: JavaString<'j>,
+    ) -> String {
+        format!("{arg0}: {}", self.count.load(Ordering::SeqCst))
+    }
+}
+
+struct NativeContextRsImpl;
+
+impl<'j> NativeContextRs<'j> for NativeContextRsImpl {
+    fn from_env(_env: JNIEnv<'j>) -> Self {
+        Self
+    }
+
+    fn greet_native(
+        &self,
+        ctx: CallCtx<'j, NetBluejekyllNativeContext<'j>>,
+        arg0: JavaString<'j>,
+    ) -> String {
+        let prefix = ctx.this().prefix(ctx.env());
+        format!("{prefix}{arg0}")
+    }
+
+    fn checked_native(
+        &self,
+        ctx: CallCtx<'j, NetBluejekyllNativeContextClass<'j>>,
+        arg0: i32,
+    ) -> i32 {
+        if arg0 < 0 {
+            ctx.throw(
+                "java/lang/IllegalArgumentException",
+                format!("negative value: {arg0}"),
+            )
+            .expect("couldn't throw");
+        }
+
+        arg0
+    }
+}
+
+struct NativeBeansRsImpl<'j> {
+    env: JNIEnv<'j>,
+}
+
+impl<'j> NativeBeansRs<'j> for NativeBeansRsImpl<'j> {
+    fn from_env(env: JNIEnv<'j>) -> Self {
+        Self { env }
+    }
+
+    fn birthday_native(
+        &self,
+        _class: NetBluejekyllNativeBeansClass<'j>,
+        arg0: NetBluejekyllPerson<'j>,
+    ) -> NetBluejekyllPerson<'j> {
+        let mut person =
+            NetBluejekyllPersonBean::from_java(self.env, arg0).expect("couldn't read the Person");
+        person.age += 1;
+        person
+            .to_java(self.env)
+            .expect("couldn't create the Person")
+    }
+
+    fn to_json_native(
+        &self,
+        _class: NetBluejekyllNativeBeansClass<'j>,
+        arg0: NetBluejekyllPerson<'j>,
+    ) -> String {
+        let person =
+            NetBluejekyllPersonBean::from_java(self.env, arg0).expect("couldn't read the Person");
+        serde_json::to_string(&person).expect("couldn't serialize the Person")
+    }
+
+    fn from_json_native(
+        &self,
+        _class: NetBluejekyllNativeBeansClass<'j>,
+        arg0: JavaString<'j>,
+    ) -> NetBluejekyllPerson<'j> {
+        let person: NetBluejekyllPersonBean =
+            serde_json::from_str(arg0.as_ref()).expect("couldn't deserialize the Person");
+        person
+            .to_java(self.env)
+            .expect("couldn't create the Person")
+    }
+}
+
+struct NativeRecordsRsImpl<'j> {
+    env: JNIEnv<'j>,
+}
+
+impl<'j> NativeRecordsRs<'j> for NativeRecordsRsImpl<'j> {
+    fn from_env(env: JNIEnv<'j>) -> Self {
+        Self { env }
+    }
+
+    fn translate_native(
+        &self,
+        _class: NetBluejekyllNativeRecordsClass<'j>,
+        arg0: NetBluejekyllPoint<'j>,
+        arg1: i32,
+        arg2: i32,
+    ) -> NetBluejekyllPoint<'j> {
+        let mut point =
+            NetBluejekyllPointRecord::from_java(self.env, arg0).expect("couldn't read the Point");
+        point.x += arg1;
+        point.y += arg2;
+        point.to_java(self.env).expect("couldn't create the Point")
+    }
+
+    fn to_json_native(
+        &self,
+        _class: NetBluejekyllNativeRecordsClass<'j>,
+        arg0: NetBluejekyllPoint<'j>,
+    ) -> String {
+        let point =
+            NetBluejekyllPointRecord::from_java(self.env, arg0).expect("couldn't read the Point");
+        serde_json::to_string(&point).expect("couldn't serialize the Point")
+    }
+}
+
+struct NativeSealedRsImpl<'j> {
+    env: JNIEnv<'j>,
+}
+
+impl<'j> NativeSealedRs<'j> for NativeSealedRsImpl<'j> {
+    fn from_env(env: JNIEnv<'j>) -> Self {
+        Self { env }
+    }
+
+    fn describe_native(
+        &self,
+        _class: NetBluejekyllNativeSealedClass<'j>,
+        arg0: NetBluejekyllShape<'j>,
+    ) -> String {
+        let env = self.env;
+        let Ok(shape) = arg0.classify(env) else {
+            return "null".to_string();
+        };
+
+        match shape {
+            NetBluejekyllShapeSealed::Circle(circle) => {
+                format!("circle with radius {}", circle.radius(env))
+            }
+            NetBluejekyllShapeSealed::Square(square) => {
+                format!("square with side {}", square.side(env))
+            }
+            NetBluejekyllShapeSealed::Triangle(triangle) => {
+                format!("triangle with area {}", triangle.area(env))
+            }
+        }
+    }
+}
+
+struct NativeFallbacksRsImpl<'j> {
+    env: JNIEnv<'j>,
+}
+
+impl<'j> NativeFallbacksRs<'j> for NativeFallbacksRsImpl<'j> {
+    fn from_env(env: JNIEnv<'j>) -> Self {
+        Self { env }
+    }
+
+    fn jni_env(&self) -> JNIEnv<'j> {
+        self.env
+    }
+
+    // offset_native and describe_native are left to the Java fallbacks
+    fn square_native(&self, _this: NetBluejekyllNativeFallbacks<'j>, arg0: i32) -> i32 {
+        arg0 * arg0
+    }
+}
+
+struct NativeHooksRsImpl;
+
+impl<'j> NativeHooksRs<'j> for NativeHooksRsImpl {
+    fn from_env(_env: JNIEnv<'j>) -> Self {
+        Self
+    }
+
+    fn entered_calls(&self, _class: NetBluejekyllNativeHooksClass<'j>) -> i64 {
+        HOOKS_ENTERED.load(Ordering::SeqCst)
+    }
+
+    fn exited_calls(&self, _class: NetBluejekyllNativeHooksClass<'j>) -> i64 {
+        HOOKS_EXITED.load(Ordering::SeqCst)
+    }
+}
+
+struct NativeVarargsRsImpl<'j> {
+    env: JNIEnv<'j>,
+}
+
+impl<'j> NativeVarargsRs<'j> for NativeVarargsRsImpl<'j> {
+    fn from_env(env: JNIEnv<'j>) -> Self {
+        Self { env }
+    }
+
+    fn sum_native(
+        &self,
+        class: NetBluejekyllNativeVarargsClass<'j>,
+        arg0: i32,
+        arg1: i32,
+        arg2: i32,
+    ) -> i32 {
+        class.sum_varargs(self.env, [arg0, arg1, arg2])
+    }
+
+    fn join_native(
+        &self,
+        this: NetBluejekyllNativeVarargs<'j>,
+        arg0: JavaString<'j>,
+        arg1: JavaString<'j>,
+    ) -> String {
+        this.join_varargs(
+            self.env,
+            ", ".to_string(),
+            [arg0.to_string(), arg1.to_string()],
+        )
+    }
+
+    fn count_nulls_native(&self, this: NetBluejekyllNativeVarargs<'j>) -> i32 {
+        this.count_nulls_varargs(self.env, [*this, JObject::null()])
+    }
+}
+
+/// The layout of the direct buffer of `net.bluejekyll.Particle`
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Particle {
+    x: f64,
+    y: f64,
+    vx: f64,
+    vy: f64,
+}
+
+unsafe impl jaffi_support::buffer::BufferStruct for Particle {}
+
+struct NativeBuffersRsImpl<'j> {
+    env: JNIEnv<'j>,
+}
+
+impl<'j> NativeBuffersRs<'j> for NativeBuffersRsImpl<'j> {
+    fn from_env(env: JNIEnv<'j>) -> Self {
+        Self { env }
+    }
+
+    fn step(
+        &self,
+        _class: NetBluejekyllNativeBuffersClass<'j>,
+        particle: NetBluejekyllParticle<'j>,
+        dt: f64,
+    ) {
+        // Safety: the particles of the tests aren't shared between threads, and this is the only view of the buffer
+        let mut particle =
+            unsafe { particle.buffer_view_mut(self.env) }.expect("particle buffer is not viewable");
+        particle.x += particle.vx * dt;
+        particle.y += particle.vy * dt;
+    }
+
+    fn speed_squared(
+        &self,
+        _class: NetBluejekyllNativeBuffersClass<'j>,
+        particle: NetBluejekyllParticle<'j>,
+    ) -> f64 {
+        // Safety: the particles of the tests aren't shared between threads, and there is no mutable view of the buffer
+        let particle =
+            unsafe { particle.buffer_view(self.env) }.expect("particle buffer is not viewable");
+        particle.vx * particle.vx + particle.vy * particle.vy
+    }
+}
+
+/// The commands sent from the native methods to the dispatcher, configured in the `build.rs`
+pub enum Command {
+    /// The request of `NativeDispatch.request`, the result is posted to the callback
+    Request {
+        input: String,
+        callback: NetBluejekyllDispatchCallbackGlobal,
+    },
+}
+
+/// Handles the commands on the worker thread of the dispatcher
+fn handle_command(env: JNIEnv<'_>, command: Command) {
+    match command {
+        Command::Request { input, callback } => {
+            if let Ok(Some(callback)) = callback.local(env) {
+                callback.on_result(env, input.to_uppercase());
+            }
+        }
+    }
+}
+
+struct NativeDispatchRsImpl<'j> {
+    env: JNIEnv<'j>,
+}
+
+impl<'j> NativeDispatchRs<'j> for NativeDispatchRsImpl<'j> {
+    fn from_env(env: JNIEnv<'j>) -> Self {
+        Self { env }
+    }
+
+    fn request(
+        &self,
+        _class: NetBluejekyllNativeDispatchClass<'j>,
+        input: JavaString<'j>,
+        callback: NetBluejekyllDispatchCallback<'j>,
+    ) {
+        let callback = callback
+            .register_global(self.env)
+            .expect("couldn't register the callback");
+        net_bluejekyll::dispatch(Command::Request {
+            input: input.to_string(),
+            callback,
+        })
+        .unwrap_or_else(|_| panic!("the dispatcher isn't running"));
+    }
 }
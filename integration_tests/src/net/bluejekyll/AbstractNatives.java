@@ -0,0 +1,12 @@
+package net.bluejekyll;
+
+// native methods declared on an abstract class, called on instances of the subclass
+public abstract class AbstractNatives {
+    public abstract int base();
+
+    // returns base() + value, base is dispatched to the subclass
+    public native int addToBaseNative(int value);
+
+    // returns the prefix and name of the Named interface
+    public static native String describeNative(Named named);
+}
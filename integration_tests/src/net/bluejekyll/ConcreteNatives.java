@@ -0,0 +1,14 @@
+package net.bluejekyll;
+
+public class ConcreteNatives extends AbstractNatives {
+    private final int base;
+
+    public ConcreteNatives(int base) {
+        this.base = base;
+    }
+
+    @Override
+    public int base() {
+        return base;
+    }
+}
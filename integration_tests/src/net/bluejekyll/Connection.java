@@ -0,0 +1,23 @@
+package net.bluejekyll;
+
+// constructed from Rust with the generated NetBluejekyllConnectionBuilder
+public class Connection {
+    private final String host;
+    private final int port;
+    private final boolean secure;
+    private final long timeoutMillis;
+    private final Fruit flavor;
+
+    public Connection(String host, int port, boolean secure, long timeoutMillis, Fruit flavor) {
+        this.host = host;
+        this.port = port;
+        this.secure = secure;
+        this.timeoutMillis = timeoutMillis;
+        this.flavor = flavor;
+    }
+
+    public String describe() {
+        return (this.secure ? "https://" : "http://") + this.host + ":" + this.port + " in " + this.timeoutMillis
+                + "ms with " + this.flavor;
+    }
+}
@@ -0,0 +1,6 @@
+package net.bluejekyll;
+
+// implemented by Rust closures with from_fn
+public interface CountListener {
+    String onCount(String name, int count);
+}
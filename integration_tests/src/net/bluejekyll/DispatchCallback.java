@@ -0,0 +1,6 @@
+package net.bluejekyll;
+
+// the results of NativeDispatch are posted to this from the worker thread of the dispatcher
+public interface DispatchCallback {
+    void onResult(String result);
+}
@@ -0,0 +1,15 @@
+package net.bluejekyll;
+
+public class Doctor implements Titled {
+    public Doctor() {
+    }
+
+    public String name() {
+        return "Who";
+    }
+
+    @Override
+    public String farewell() {
+        return "allons-y";
+    }
+}
@@ -0,0 +1,14 @@
+package net.bluejekyll;
+
+public class Document {
+    public Document() {
+    }
+
+    public Document copy() {
+        return new Document();
+    }
+
+    public String kind() {
+        return "document";
+    }
+}
@@ -0,0 +1,17 @@
+package net.bluejekyll;
+
+// the covariant override of copy has a bridge returning Document
+public class Draft extends Document {
+    public Draft() {
+    }
+
+    @Override
+    public Draft copy() {
+        return new Draft();
+    }
+
+    @Override
+    public String kind() {
+        return "draft";
+    }
+}
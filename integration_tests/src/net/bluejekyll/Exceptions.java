@@ -9,6 +9,10 @@ public class Exceptions {
 
     public native void panicsAreRuntimeExceptions();
 
+    public native void panicsWithErrorCode(int code);
+
+    public native String readFile(String path) throws java.io.IOException;
+
     public void iAlwaysThrow() throws SomethingException {
         throw new SomethingException("iAlwaysThrow");
     }
@@ -0,0 +1,7 @@
+package net.bluejekyll;
+
+public enum Fruit {
+    APPLE,
+    BANANA,
+    CHERRY,
+}
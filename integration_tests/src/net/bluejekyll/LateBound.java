@@ -0,0 +1,18 @@
+package net.bluejekyll;
+
+// wrapped from the methods declared in the build script, as if the class were only available at run time
+public class LateBound {
+    private final int value;
+
+    public LateBound(int value) {
+        this.value = value;
+    }
+
+    public int twice() {
+        return this.value * 2;
+    }
+
+    public static String version() {
+        return "1.0";
+    }
+}
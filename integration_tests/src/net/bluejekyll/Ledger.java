@@ -0,0 +1,28 @@
+package net.bluejekyll;
+
+// the package-private methods are wrapped with package_private_classes
+public class Ledger {
+    private int balance;
+
+    public Ledger() {
+    }
+
+    public int balance() {
+        return balance;
+    }
+
+    void deposit(int amount) {
+        balance += amount;
+    }
+
+    static String currency() {
+        return "EUR";
+    }
+
+    protected void audit() {
+    }
+
+    private void reset() {
+        balance = 0;
+    }
+}
@@ -0,0 +1,12 @@
+package net.bluejekyll;
+
+/** @deprecated the wrapper is deprecated as well */
+@Deprecated
+public class Legacy {
+    public Legacy() {
+    }
+
+    public static int twice(int val) {
+        return val * 2;
+    }
+}
@@ -0,0 +1,20 @@
+package net.bluejekyll;
+
+// bridged to the Rust struct crate::Money, which the native methods take and return
+public class Money {
+    private final long cents;
+    private final String currency;
+
+    public Money(long cents, String currency) {
+        this.cents = cents;
+        this.currency = currency;
+    }
+
+    public long getCents() {
+        return this.cents;
+    }
+
+    public String getCurrency() {
+        return this.currency;
+    }
+}
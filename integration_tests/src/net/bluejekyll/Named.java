@@ -0,0 +1,9 @@
+package net.bluejekyll;
+
+public interface Named {
+    String name();
+
+    static String prefix() {
+        return "named: ";
+    }
+}
@@ -0,0 +1,21 @@
+package net.bluejekyll;
+
+import net.bluejekyll.annotations.JaffiNullable;
+import net.bluejekyll.annotations.JaffiRename;
+import net.bluejekyll.annotations.JaffiSkip;
+
+// the generation of these methods is changed by the annotations, like the overrides of NativeOverrides
+public class NativeAnnotations {
+    @JaffiRename("hello")
+    public native String nativeGreet(@JaffiNullable String name);
+
+    public native String callWrappers();
+
+    public String join(@JaffiNullable String first, String second) {
+        return first == null ? second : first + second;
+    }
+
+    @JaffiSkip
+    public void skipped() {
+    }
+}
@@ -1,6 +1,20 @@
 package net.bluejekyll;
 
 public class NativeArrays {
+    private final String name;
+
+    public NativeArrays() {
+        this("root");
+    }
+
+    public NativeArrays(String name) {
+        this.name = name;
+    }
+
+    public String getName() {
+        return this.name;
+    }
+
     // get bytes of len length
     public static native void sendBytes(byte[] bytes);
 
@@ -13,4 +27,21 @@ public class NativeArrays {
     public byte[] newJavaBytes() {
         return java.util.HexFormat.of().parseHex("CAFEBABE");
     }
+
+    public static int sumJavaBytes(byte[] bytes) {
+        int sum = 0;
+        for (byte b : bytes) {
+            sum += b;
+        }
+        return sum;
+    }
+
+    // calls sumJavaBytes with a slice
+    public static native int sumJavaBytesNative();
+
+    // arrays of the declaring class, built from an iterator of wrappers in Rust
+    public native NativeArrays[] children(int count);
+
+    // reads the elements of the array in Rust
+    public static native String joinNames(NativeArrays[] arrays);
 }
@@ -0,0 +1,12 @@
+package net.bluejekyll;
+
+public class NativeBeans {
+    // returns a new Person, a year older, through the Rust mirror
+    public static native Person birthdayNative(Person person);
+
+    // serializes the Rust mirror of the Person to JSON
+    public static native String toJsonNative(Person person);
+
+    // deserializes the Rust mirror from JSON
+    public static native Person fromJsonNative(String json);
+}
@@ -0,0 +1,18 @@
+package net.bluejekyll;
+
+import java.math.BigDecimal;
+import java.math.BigInteger;
+
+public class NativeBigNums {
+    // BigIntegers and BigDecimals are num-bigint and bigdecimal in Rust
+    public native BigInteger multiplyNative(BigInteger a, BigInteger b);
+
+    public native BigDecimal addNative(BigDecimal a, BigDecimal b);
+
+    // a native method that internally calls the object method negate
+    public native BigDecimal negateNative(BigDecimal a);
+
+    public BigDecimal negate(BigDecimal a) {
+        return a.negate();
+    }
+}
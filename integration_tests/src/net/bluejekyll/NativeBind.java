@@ -0,0 +1,8 @@
+package net.bluejekyll;
+
+// bound with jaffi_macros::bind! in tests/bind.rs, not in the build.rs
+public class NativeBind {
+    public static native int addNative(int a, int b);
+
+    public native String greetNative(String name);
+}
@@ -0,0 +1,9 @@
+package net.bluejekyll;
+
+import java.util.Optional;
+
+public class NativeBridges {
+    public static native Money add(Money a, Money b);
+
+    public static native Optional<Money> nonZero(Money money);
+}
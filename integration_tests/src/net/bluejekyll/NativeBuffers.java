@@ -0,0 +1,9 @@
+package net.bluejekyll;
+
+public class NativeBuffers {
+    // moves the particle by its velocity, written to the buffer in Rust
+    public static native void step(Particle particle, double dt);
+
+    // reads the velocity from the buffer in Rust
+    public static native double speedSquared(Particle particle);
+}
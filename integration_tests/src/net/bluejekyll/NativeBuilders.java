@@ -0,0 +1,5 @@
+package net.bluejekyll;
+
+public class NativeBuilders {
+    public static native Connection connect(String host);
+}
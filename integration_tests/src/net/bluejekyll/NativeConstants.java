@@ -0,0 +1,34 @@
+package net.bluejekyll;
+
+public class NativeConstants {
+    // static final constants are consts on the Class wrapper in Rust
+    public static final boolean FLAG = true;
+    public static final byte BYTE = -8;
+    public static final char CHAR = 'λ';
+    public static final short SHORT = 1_000;
+    public static final int VISIBLE = 0x0000_0004;
+    public static final long LONG = Long.MIN_VALUE;
+    public static final float FLOAT = 1.5f;
+    public static final double DOUBLE = Double.NaN;
+    public static final String NAME = "jaffi \"constants\"";
+
+    // not constants
+    public static final Object OBJECT = new Object();
+    public final int notStatic = 1;
+    static final int NOT_PUBLIC = 2;
+
+    // returns all the constants formatted by Rust
+    public native String describeNative();
+
+    public int twice(int value) {
+        return value * 2;
+    }
+
+    // calls twice with jni directly, using the descriptor consts of the wrapper
+    public native int twiceRawNative(int value);
+
+    static String describe() {
+        return FLAG + "," + BYTE + "," + CHAR + "," + SHORT + "," + VISIBLE + "," + LONG + "," + FLOAT + ","
+                + Double.isNaN(DOUBLE) + "," + NAME;
+    }
+}
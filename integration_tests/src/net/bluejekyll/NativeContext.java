@@ -0,0 +1,14 @@
+package net.bluejekyll;
+
+// the Rust implementation gets a CallCtx in place of this and class
+public class NativeContext {
+    public String prefix() {
+        return "Hello, ";
+    }
+
+    // returns prefix() followed by the name
+    public native String greetNative(String name);
+
+    // returns the value, throws an IllegalArgumentException for negative values
+    public static native int checkedNative(int value);
+}
@@ -0,0 +1,10 @@
+package net.bluejekyll;
+
+// the Rust implementation is registered at load, and keeps its state across calls
+public class NativeCounter {
+    // increments the count shared by all calls
+    public static native int nextNative();
+
+    // returns the label followed by the current count
+    public native String labelNative(String label);
+}
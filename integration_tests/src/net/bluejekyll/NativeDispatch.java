@@ -0,0 +1,6 @@
+package net.bluejekyll;
+
+public class NativeDispatch {
+    // returns right away, the request is handled by the dispatcher, which calls back with the result
+    public static native void request(String input, DispatchCallback callback);
+}
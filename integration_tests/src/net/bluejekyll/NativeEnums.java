@@ -0,0 +1,13 @@
+package net.bluejekyll;
+
+public class NativeEnums {
+    // Rust converts the enum to the Rust type and back to Java
+    public static native Fruit roundTrip(Fruit fruit);
+
+    // Rust passes enums to the Java method `describe`
+    public native String describeNative();
+
+    public String describe(Fruit first, Fruit second) {
+        return first.name() + ":" + first.ordinal() + "," + second.name() + ":" + second.ordinal();
+    }
+}
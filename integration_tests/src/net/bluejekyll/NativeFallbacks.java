@@ -0,0 +1,28 @@
+package net.bluejekyll;
+
+// native methods with Java fallbacks, only squareNative is implemented in Rust
+public class NativeFallbacks {
+    private final int offset;
+
+    public NativeFallbacks(int offset) {
+        this.offset = offset;
+    }
+
+    public native int squareNative(int x);
+
+    public int square(int x) {
+        return x * x;
+    }
+
+    public native int offsetNative(int x);
+
+    public int offset(int x) {
+        return x + this.offset;
+    }
+
+    public static native String describeNative(int x);
+
+    public static String describe(int x) {
+        return "java " + x;
+    }
+}
@@ -0,0 +1,18 @@
+package net.bluejekyll;
+
+public class NativeFrames {
+    // wrappers called in loops from Rust
+    public int length(String value) {
+        return value.length();
+    }
+
+    public String twice(String value) {
+        return value + value;
+    }
+
+    // calls length in a loop, each call runs in a local frame
+    public native int sumLengthsNative(int times);
+
+    // calls twice in a loop, using with_frame from Rust
+    public native String lastTwiceNative(int times);
+}
@@ -0,0 +1,21 @@
+package net.bluejekyll;
+
+import java.util.concurrent.CompletableFuture;
+
+public class NativeFutures {
+    // CompletableFutures are completed from Rust futures
+    public native CompletableFuture<String> greetNative(String name);
+
+    public native CompletableFuture<Integer> addNative(int a, int b);
+
+    public native CompletableFuture<Void> panicNative();
+
+    // the handle of a Rust Cancellation, cancelled with cancel
+    public native long newCancellation();
+
+    // completes with the handle once it's cancelled, the handle is released
+    public native CompletableFuture<Long> untilCancelled(long handle);
+
+    // implemented by jaffi with the cancel_handles option
+    public static native boolean cancel(long handle);
+}
@@ -0,0 +1,8 @@
+package net.bluejekyll;
+
+// the calls of these are counted by the enter and exit hooks
+public class NativeHooks {
+    public static native long enteredCalls();
+
+    public static native long exitedCalls();
+}
@@ -0,0 +1,24 @@
+package net.bluejekyll;
+
+public class NativeMonitors {
+    private int count = 0;
+
+    // not synchronized, the native method holds the monitor while calling these
+    public int getCount() {
+        return count;
+    }
+
+    public void setCount(int count) {
+        this.count = count;
+    }
+
+    public boolean isLocked() {
+        return Thread.holdsLock(this);
+    }
+
+    // increments the count with getCount and setCount while holding the monitor
+    public native void incrementNative(int times);
+
+    // returns isLocked while holding the monitor
+    public native boolean lockedNative();
+}
@@ -0,0 +1,21 @@
+package net.bluejekyll;
+
+// '_', '$' and unicode in the names of native classes and methods, as in the class files of e.g. Kotlin and Scala
+public class NativeNames {
+    public static native int add_one(int value);
+
+    public static native int dollar$sign(int value);
+
+    public static native String grüße(String name);
+
+    // outside of the basic multilingual plane, escaped as a surrogate pair
+    public static native int 𝒜dd(int a, int b);
+
+    public static class Inner$Ünïcode {
+        public static native int twice(int value);
+    }
+
+    public static class 𝒜 {
+        public native int plus(int value);
+    }
+}
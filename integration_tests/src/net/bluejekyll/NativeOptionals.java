@@ -0,0 +1,15 @@
+package net.bluejekyll;
+
+import java.util.Optional;
+
+public class NativeOptionals {
+    // Optionals are Options in Rust
+    public native Optional<String> upperNative(Optional<String> str);
+
+    // a native method that internally calls the object method length
+    public native Optional<Integer> lengthNative(Optional<String> str);
+
+    public Optional<Integer> length(Optional<String> str) {
+        return str.map(String::length);
+    }
+}
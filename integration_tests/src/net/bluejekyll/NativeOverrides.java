@@ -0,0 +1,23 @@
+package net.bluejekyll;
+
+// the generation of these methods is changed by overrides.toml
+public class NativeOverrides {
+    public native String nativeGreet(String name);
+
+    public native int parseNumber(String number);
+
+    public native int sumBytes(byte[] bytes);
+
+    public native String callWrappers();
+
+    public String join(String first, String second) {
+        return first == null ? second : first + second;
+    }
+
+    public int fail() {
+        throw new IllegalStateException("always fails");
+    }
+
+    public void skipped() {
+    }
+}
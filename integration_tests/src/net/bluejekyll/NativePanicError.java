@@ -0,0 +1,7 @@
+package net.bluejekyll;
+
+public class NativePanicError extends RuntimeException {
+    public NativePanicError(String msg) {
+        super(msg);
+    }
+}
@@ -43,4 +43,20 @@ public class NativePrimitives extends ParentClass {
     }
 
     public native Unsupported2 unsupportedReturnNative();
-}
\ No newline at end of file
+
+    // boxed primitives are Options in Rust, null is None
+    public native Integer addBoxedNative(Integer arg1, Long arg2);
+
+    public Long addBoxed(Integer arg1, Long arg2) {
+        if (arg1 == null || arg2 == null) {
+            return null;
+        }
+
+        return (long) arg1 + arg2;
+    }
+
+    public native Boolean notBoxed(Boolean arg);
+
+    // surrogates are replaced with U+FFFD
+    public native char echoChar(char ch);
+}
@@ -0,0 +1,9 @@
+package net.bluejekyll;
+
+public class NativeRecords {
+    // returns a new Point, moved by dx and dy, through the Rust mirror
+    public static native Point translateNative(Point point, int dx, int dy);
+
+    // serializes the Rust mirror of the Point to JSON
+    public static native String toJsonNative(Point point);
+}
@@ -0,0 +1,6 @@
+package net.bluejekyll;
+
+public class NativeSealed {
+    // describes the Shape by matching over the sealed hierarchy in Rust
+    public static native String describeNative(Shape shape);
+}
@@ -0,0 +1,12 @@
+package net.bluejekyll;
+
+import java.io.InputStream;
+import java.io.OutputStream;
+
+public class NativeStreams {
+    // InputStreams are std::io::Read and OutputStreams std::io::Write in Rust
+    public native void copyNative(InputStream in, OutputStream out);
+
+    // returns the message of the io::Error from reading
+    public native String readErrorNative(InputStream in);
+}
@@ -14,6 +14,9 @@ public class NativeStrings {
 
     public static native NativeStrings ctor(String s);
 
+    // creates the object from Rust with the ctor factory
+    public native String viaFactory(String s);
+
     // Test passing a string to Rust
     public native void eatString(String str);
 
@@ -22,6 +25,14 @@ public class NativeStrings {
 
     public native String returnStringNative(String append);
 
+    // calls returnString with an interned constant
+    public native String returnInternedNative();
+
+    // CharSequence and StringBuilder are converted to Rust Strings
+    public native CharSequence tieOffCharSequence(CharSequence seq);
+
+    public native StringBuilder appendToBuilder(StringBuilder builder, CharSequence append);
+
     // Return a String from Java to Rust
     public String returnString(String append) {
         return message + append;
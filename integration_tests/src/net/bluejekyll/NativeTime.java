@@ -0,0 +1,19 @@
+package net.bluejekyll;
+
+import java.time.Duration;
+import java.time.Instant;
+import java.util.Date;
+
+public class NativeTime {
+    // Instants and Durations are std::time types in Rust
+    public native Instant addNative(Instant instant, Duration duration);
+
+    public native Date toDateNative(Instant instant);
+
+    // a native method that internally calls the object method between
+    public native Duration betweenNative(Instant start, Instant end);
+
+    public Duration between(Instant start, Instant end) {
+        return Duration.between(start, end);
+    }
+}
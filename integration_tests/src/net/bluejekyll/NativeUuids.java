@@ -0,0 +1,13 @@
+package net.bluejekyll;
+
+import java.util.Optional;
+import java.util.UUID;
+
+public class NativeUuids {
+    // UUIDs are uuid::Uuid in Rust
+    public native String toStringNative(UUID uuid);
+
+    public native UUID parseNative(String uuid);
+
+    public native Optional<UUID> maybeNilNative(UUID uuid);
+}
@@ -0,0 +1,32 @@
+package net.bluejekyll;
+
+// the natives call the varargs methods through the generated _varargs wrappers
+public class NativeVarargs {
+    public static int sum(int... values) {
+        int sum = 0;
+        for (int value : values) {
+            sum += value;
+        }
+        return sum;
+    }
+
+    public String join(String separator, String... parts) {
+        return String.join(separator, parts);
+    }
+
+    public static int countNulls(Object... objects) {
+        int nulls = 0;
+        for (Object object : objects) {
+            if (object == null) {
+                nulls++;
+            }
+        }
+        return nulls;
+    }
+
+    public static native int sumNative(int a, int b, int c);
+
+    public native String joinNative(String a, String b);
+
+    public native int countNullsNative();
+}
@@ -0,0 +1,29 @@
+package net.bluejekyll;
+
+import java.util.ArrayList;
+import java.util.List;
+
+public class Notifier {
+    private final List<CountListener> listeners = new ArrayList<>();
+
+    public void addListener(CountListener listener) {
+        this.listeners.add(listener);
+    }
+
+    public void removeListener(CountListener listener) {
+        this.listeners.remove(listener);
+    }
+
+    public int listenerCount() {
+        return this.listeners.size();
+    }
+
+    // the results of the listeners, joined with commas
+    public String fire(String name, int count) {
+        List<String> results = new ArrayList<>();
+        for (CountListener listener : this.listeners) {
+            results.add(listener.onCount(name, count));
+        }
+        return String.join(",", results);
+    }
+}
@@ -4,4 +4,10 @@ public class ParentClass {
     public final int call_dad(int val) {
         return val;
     }
+
+    /** @deprecated use {@link #call_dad(int)} */
+    @Deprecated(since = "0.2", forRemoval = true)
+    public final int call_grandpa(int val) {
+        return val;
+    }
 }
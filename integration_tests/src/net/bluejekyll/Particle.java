@@ -0,0 +1,22 @@
+package net.bluejekyll;
+
+import java.nio.ByteBuffer;
+import java.nio.ByteOrder;
+
+// backed by a direct buffer, Rust views it as the #[repr(C)] struct crate::Particle
+public class Particle {
+    // x, y, vx and vy, as doubles
+    private final ByteBuffer buffer = ByteBuffer.allocateDirect(32).order(ByteOrder.nativeOrder());
+
+    public Particle(double x, double y, double vx, double vy) {
+        this.buffer.putDouble(0, x).putDouble(8, y).putDouble(16, vx).putDouble(24, vy);
+    }
+
+    public double getX() {
+        return this.buffer.getDouble(0);
+    }
+
+    public double getY() {
+        return this.buffer.getDouble(8);
+    }
+}
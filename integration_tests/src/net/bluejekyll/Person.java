@@ -0,0 +1,49 @@
+package net.bluejekyll;
+
+// a bean, Rust gets a plain struct mirror of its properties
+public class Person {
+    private String firstName;
+    private int age;
+    private boolean active;
+    private Double score;
+
+    public Person() {
+    }
+
+    public String getFirstName() {
+        return this.firstName;
+    }
+
+    public void setFirstName(String firstName) {
+        this.firstName = firstName;
+    }
+
+    public int getAge() {
+        return this.age;
+    }
+
+    public void setAge(int age) {
+        this.age = age;
+    }
+
+    public boolean isActive() {
+        return this.active;
+    }
+
+    public void setActive(boolean active) {
+        this.active = active;
+    }
+
+    public Double getScore() {
+        return this.score;
+    }
+
+    public void setScore(Double score) {
+        this.score = score;
+    }
+
+    // read only, not a property of the mirror
+    public String getDescription() {
+        return this.firstName + " (" + this.age + ")";
+    }
+}
@@ -0,0 +1,5 @@
+package net.bluejekyll;
+
+// a record, Rust gets a plain struct mirror of its components
+public record Point(int x, int y, String label) {
+}
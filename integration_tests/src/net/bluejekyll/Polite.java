@@ -0,0 +1,11 @@
+package net.bluejekyll;
+
+public interface Polite {
+    default String greet(String other) {
+        return "hello " + other;
+    }
+
+    default String farewell() {
+        return "goodbye";
+    }
+}
@@ -0,0 +1,28 @@
+package net.bluejekyll;
+
+// a sealed hierarchy, Rust gets an enum of the permitted subclasses
+public sealed interface Shape permits Shape.Circle, Shape.Square, Triangle {
+    double area();
+
+    public record Circle(double radius) implements Shape {
+        public double area() {
+            return Math.PI * this.radius * this.radius;
+        }
+    }
+
+    public final class Square implements Shape {
+        private final double side;
+
+        public Square(double side) {
+            this.side = side;
+        }
+
+        public double side() {
+            return this.side;
+        }
+
+        public double area() {
+            return this.side * this.side;
+        }
+    }
+}
@@ -16,4 +16,8 @@ public class SomethingException extends Exception {
     public SomethingException(Throwable cause) {
         super(cause);
     }
-}
\ No newline at end of file
+
+    public String getDetail() {
+        return "detail: " + this.getMessage();
+    }
+}
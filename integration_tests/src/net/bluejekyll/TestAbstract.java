@@ -0,0 +1,26 @@
+package net.bluejekyll;
+
+public class TestAbstract {
+    static void runTests() {
+        System.out.println(">>>> Running " + TestAbstract.class.getName());
+        TestAbstract.testAddToBase();
+        TestAbstract.testDescribe();
+        System.out.println("<<<< " + TestAbstract.class.getName() + " tests succeeded");
+    }
+
+    static void testAddToBase() {
+        AbstractNatives natives = new ConcreteNatives(40);
+
+        int got = natives.addToBaseNative(2);
+        if (got != 42) {
+            throw new RuntimeException("Expected 42 got " + got);
+        }
+    }
+
+    static void testDescribe() {
+        String got = AbstractNatives.describeNative(() -> "lambda");
+        if (!got.equals("named: lambda")) {
+            throw new RuntimeException("Expected named: lambda got " + got);
+        }
+    }
+}
@@ -0,0 +1,35 @@
+package net.bluejekyll;
+
+import net.bluejekyll.aggregate.NativeAdder;
+import net.bluejekyll.aggregate.NativeGreeter;
+
+public class TestAggregate {
+    static void runTests() {
+        System.out.println(">>>> Running " + TestAggregate.class.getName());
+        TestAggregate.testAdd();
+        TestAggregate.testTwice();
+        TestAggregate.testGreet();
+        System.out.println("<<<< " + TestAggregate.class.getName() + " tests succeeded");
+    }
+
+    static void testAdd() {
+        int got = new NativeAdder().add(40, 2);
+        if (got != 42) {
+            throw new RuntimeException("Expected 42 got " + got);
+        }
+    }
+
+    static void testTwice() {
+        long got = NativeAdder.twice(21L);
+        if (got != 42L) {
+            throw new RuntimeException("Expected 42 got " + got);
+        }
+    }
+
+    static void testGreet() {
+        String got = new NativeGreeter().greet("Java");
+        if (!got.equals("Hello, Java")) {
+            throw new RuntimeException("Expected Hello, Java got " + got);
+        }
+    }
+}
@@ -0,0 +1,31 @@
+package net.bluejekyll;
+
+public class TestAnnotations {
+    static void runTests() {
+        System.out.println(">>>> Running " + TestAnnotations.class.getName());
+        TestAnnotations.testRenamedNullable();
+        TestAnnotations.testWrappers();
+        System.out.println("<<<< " + TestAnnotations.class.getName() + " tests succeeded");
+    }
+
+    static void testRenamedNullable() {
+        NativeAnnotations annotations = new NativeAnnotations();
+
+        String got = annotations.nativeGreet("Java");
+        if (!got.equals("Hello, Java")) {
+            throw new RuntimeException("Expected Hello, Java got " + got);
+        }
+
+        got = annotations.nativeGreet(null);
+        if (!got.equals("Hello, nobody")) {
+            throw new RuntimeException("Expected Hello, nobody got " + got);
+        }
+    }
+
+    static void testWrappers() {
+        String got = new NativeAnnotations().callWrappers();
+        if (!got.equals("second")) {
+            throw new RuntimeException("Expected second got " + got);
+        }
+    }
+}
@@ -7,6 +7,8 @@ public class TestArrays {
         TestArrays.testGetBytes();
         TestArrays.testNewBytes();
         TestArrays.testNewBytesJava();
+        TestArrays.testSumBytesJava();
+        TestArrays.testChildren();
         System.out.println("<<<< " + TestStrings.class.getName() + " tests succeeded");
     }
 
@@ -43,4 +45,21 @@ public class TestArrays {
             throw new RuntimeException("Expected " + expect + " got " + got);
         }
     }
+
+    static void testSumBytesJava() {
+        int got = NativeArrays.sumJavaBytesNative();
+
+        if (got != 6) {
+            throw new RuntimeException("Expected 6 got " + got);
+        }
+    }
+
+    static void testChildren() {
+        NativeArrays[] children = new NativeArrays("parent").children(2);
+
+        String got = NativeArrays.joinNames(children);
+        if (!got.equals("parent.0,parent.1")) {
+            throw new RuntimeException("Expected parent.0,parent.1 got " + got);
+        }
+    }
 }
@@ -0,0 +1,61 @@
+package net.bluejekyll;
+
+public class TestBeans {
+    static void runTests() {
+        System.out.println(">>>> Running " + TestBeans.class.getName());
+        TestBeans.testBirthday();
+        TestBeans.testNulls();
+        TestBeans.testToJson();
+        TestBeans.testFromJson();
+        System.out.println("<<<< " + TestBeans.class.getName() + " tests succeeded");
+    }
+
+    static Person person() {
+        Person person = new Person();
+        person.setFirstName("Ferris");
+        person.setAge(7);
+        person.setActive(true);
+        person.setScore(9.5);
+        return person;
+    }
+
+    static void testBirthday() {
+        Person person = person();
+        Person older = NativeBeans.birthdayNative(person);
+
+        if (older == person) {
+            throw new RuntimeException("Expected a new Person");
+        }
+        if (older.getAge() != 8 || person.getAge() != 7) {
+            throw new RuntimeException("Expected 8 got " + older.getAge());
+        }
+        if (!"Ferris".equals(older.getFirstName()) || !older.isActive() || older.getScore() != 9.5) {
+            throw new RuntimeException("Expected the other properties to be copied, got " + older.getDescription());
+        }
+    }
+
+    static void testNulls() {
+        Person person = new Person();
+        Person older = NativeBeans.birthdayNative(person);
+
+        if (older.getFirstName() != null || older.getScore() != null) {
+            throw new RuntimeException("Expected null properties");
+        }
+    }
+
+    static void testToJson() {
+        String json = NativeBeans.toJsonNative(person());
+        String expected = "{\"firstName\":\"Ferris\",\"age\":7,\"active\":true,\"score\":9.5}";
+        if (!json.equals(expected)) {
+            throw new RuntimeException("Expected " + expected + " got " + json);
+        }
+    }
+
+    static void testFromJson() {
+        Person person = NativeBeans.fromJsonNative("{\"firstName\":\"Corro\",\"age\":3,\"active\":false,\"score\":null}");
+        if (!"Corro".equals(person.getFirstName()) || person.getAge() != 3 || person.isActive()
+                || person.getScore() != null) {
+            throw new RuntimeException("Expected Corro (3) got " + person.getDescription());
+        }
+    }
+}
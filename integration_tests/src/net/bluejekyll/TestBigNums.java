@@ -0,0 +1,46 @@
+package net.bluejekyll;
+
+import java.math.BigDecimal;
+import java.math.BigInteger;
+
+public class TestBigNums {
+    static void runTests() {
+        System.out.println(">>>> Running " + TestBigNums.class.getName());
+        TestBigNums.testMultiply();
+        TestBigNums.testAdd();
+        TestBigNums.testNegate();
+        System.out.println("<<<< " + TestBigNums.class.getName() + " tests succeeded");
+    }
+
+    static void testMultiply() {
+        NativeBigNums bigNums = new NativeBigNums();
+
+        BigInteger a = new BigInteger("-123456789012345678901234567890");
+        BigInteger b = new BigInteger("987654321098765432109876543210");
+        BigInteger got = bigNums.multiplyNative(a, b);
+        if (!got.equals(a.multiply(b))) {
+            throw new RuntimeException("Expected " + a.multiply(b) + " got " + got);
+        }
+    }
+
+    static void testAdd() {
+        NativeBigNums bigNums = new NativeBigNums();
+
+        BigDecimal a = new BigDecimal("12345678901234567890.0001");
+        BigDecimal b = new BigDecimal("-0.00000000000000000042");
+        BigDecimal got = bigNums.addNative(a, b);
+        if (got.compareTo(a.add(b)) != 0) {
+            throw new RuntimeException("Expected " + a.add(b) + " got " + got);
+        }
+    }
+
+    static void testNegate() {
+        NativeBigNums bigNums = new NativeBigNums();
+
+        BigDecimal a = new BigDecimal("1E+10");
+        BigDecimal got = bigNums.negateNative(a);
+        if (!got.equals(a.negate())) {
+            throw new RuntimeException("Expected " + a.negate() + " got " + got);
+        }
+    }
+}
@@ -0,0 +1,31 @@
+package net.bluejekyll;
+
+import java.util.Optional;
+
+public class TestBridges {
+    static void runTests() {
+        System.out.println(">>>> Running " + TestBridges.class.getName());
+        TestBridges.testAdd();
+        TestBridges.testOptional();
+        System.out.println("<<<< " + TestBridges.class.getName() + " tests succeeded");
+    }
+
+    static void testAdd() {
+        Money got = NativeBridges.add(new Money(150, "EUR"), new Money(275, "EUR"));
+        if (got.getCents() != 425 || !got.getCurrency().equals("EUR")) {
+            throw new RuntimeException("Expected 425 EUR got " + got.getCents() + " " + got.getCurrency());
+        }
+    }
+
+    static void testOptional() {
+        Optional<Money> got = NativeBridges.nonZero(new Money(0, "USD"));
+        if (got.isPresent()) {
+            throw new RuntimeException("Expected empty got " + got.get().getCents());
+        }
+
+        got = NativeBridges.nonZero(new Money(1, "USD"));
+        if (got.get().getCents() != 1) {
+            throw new RuntimeException("Expected 1 got " + got.get().getCents());
+        }
+    }
+}
@@ -0,0 +1,26 @@
+package net.bluejekyll;
+
+public class TestBuffers {
+    static void runTests() {
+        System.out.println(">>>> Running " + TestBuffers.class.getName());
+        TestBuffers.testStep();
+        TestBuffers.testSpeedSquared();
+        System.out.println("<<<< " + TestBuffers.class.getName() + " tests succeeded");
+    }
+
+    static void testStep() {
+        Particle particle = new Particle(1.0, 2.0, 3.0, 4.0);
+        NativeBuffers.step(particle, 0.5);
+
+        if (particle.getX() != 2.5 || particle.getY() != 4.0) {
+            throw new RuntimeException("Expected (2.5, 4.0) got (" + particle.getX() + ", " + particle.getY() + ")");
+        }
+    }
+
+    static void testSpeedSquared() {
+        double got = NativeBuffers.speedSquared(new Particle(0.0, 0.0, 3.0, 4.0));
+        if (got != 25.0) {
+            throw new RuntimeException("Expected 25.0 got " + got);
+        }
+    }
+}
@@ -0,0 +1,17 @@
+package net.bluejekyll;
+
+public class TestBuilders {
+    static void runTests() {
+        System.out.println(">>>> Running " + TestBuilders.class.getName());
+        TestBuilders.testBuilder();
+        System.out.println("<<<< " + TestBuilders.class.getName() + " tests succeeded");
+    }
+
+    static void testBuilder() {
+        String got = NativeBuilders.connect("localhost").describe();
+        String expected = "https://localhost:8443 in 500ms with CHERRY";
+        if (!got.equals(expected)) {
+            throw new RuntimeException("Expected " + expected + " got " + got);
+        }
+    }
+}
@@ -0,0 +1,29 @@
+package net.bluejekyll;
+
+public class TestConstants {
+    static void runTests() {
+        System.out.println(">>>> Running " + TestConstants.class.getName());
+        TestConstants.testDescribe();
+        TestConstants.testTwiceRaw();
+        System.out.println("<<<< " + TestConstants.class.getName() + " tests succeeded");
+    }
+
+    static void testDescribe() {
+        NativeConstants constants = new NativeConstants();
+
+        String got = constants.describeNative();
+        String expected = NativeConstants.describe();
+        if (!got.equals(expected)) {
+            throw new RuntimeException("Expected " + expected + " got " + got);
+        }
+    }
+
+    static void testTwiceRaw() {
+        NativeConstants constants = new NativeConstants();
+
+        int got = constants.twiceRawNative(21);
+        if (got != 42) {
+            throw new RuntimeException("Expected 42 got " + got);
+        }
+    }
+}
@@ -0,0 +1,38 @@
+package net.bluejekyll;
+
+public class TestContext {
+    static void runTests() {
+        System.out.println(">>>> Running " + TestContext.class.getName());
+        TestContext.testGreet();
+        TestContext.testChecked();
+        TestContext.testCheckedThrows();
+        System.out.println("<<<< " + TestContext.class.getName() + " tests succeeded");
+    }
+
+    static void testGreet() {
+        String got = new NativeContext().greetNative("jaffi");
+        if (!got.equals("Hello, jaffi")) {
+            throw new RuntimeException("Expected Hello, jaffi got " + got);
+        }
+    }
+
+    static void testChecked() {
+        int got = NativeContext.checkedNative(42);
+        if (got != 42) {
+            throw new RuntimeException("Expected 42 got " + got);
+        }
+    }
+
+    static void testCheckedThrows() {
+        try {
+            NativeContext.checkedNative(-1);
+        } catch (IllegalArgumentException e) {
+            if (!e.getMessage().equals("negative value: -1")) {
+                throw new RuntimeException("Unexpected message: " + e.getMessage());
+            }
+            return;
+        }
+
+        throw new RuntimeException("Expected an IllegalArgumentException");
+    }
+}
@@ -0,0 +1,28 @@
+package net.bluejekyll;
+
+public class TestCounter {
+    static void runTests() {
+        System.out.println(">>>> Running " + TestCounter.class.getName());
+        TestCounter.testNext();
+        TestCounter.testLabel();
+        System.out.println("<<<< " + TestCounter.class.getName() + " tests succeeded");
+    }
+
+    static void testNext() {
+        int first = NativeCounter.nextNative();
+        int second = NativeCounter.nextNative();
+        if (second != first + 1) {
+            throw new RuntimeException("Expected " + (first + 1) + " got " + second);
+        }
+    }
+
+    static void testLabel() {
+        int count = NativeCounter.nextNative();
+
+        String got = new NativeCounter().labelNative("count");
+        String expected = "count: " + count;
+        if (!got.equals(expected)) {
+            throw new RuntimeException("Expected " + expected + " got " + got);
+        }
+    }
+}
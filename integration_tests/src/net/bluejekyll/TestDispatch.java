@@ -0,0 +1,44 @@
+package net.bluejekyll;
+
+import java.util.concurrent.CountDownLatch;
+import java.util.concurrent.TimeUnit;
+import java.util.concurrent.atomic.AtomicReference;
+
+public class TestDispatch {
+    static void runTests() {
+        System.out.println(">>>> Running " + TestDispatch.class.getName());
+        TestDispatch.testRoundTrip();
+        System.out.println("<<<< " + TestDispatch.class.getName() + " tests succeeded");
+    }
+
+    static void testRoundTrip() {
+        CountDownLatch done = new CountDownLatch(2);
+        AtomicReference<String> results = new AtomicReference<>("");
+        AtomicReference<Thread> thread = new AtomicReference<>();
+        DispatchCallback callback = result -> {
+            results.updateAndGet(previous -> previous + result + ";");
+            thread.set(Thread.currentThread());
+            done.countDown();
+        };
+
+        NativeDispatch.request("first", callback);
+        NativeDispatch.request("second", callback);
+
+        try {
+            if (!done.await(10, TimeUnit.SECONDS)) {
+                throw new RuntimeException("the dispatcher didn't call back");
+            }
+        } catch (InterruptedException e) {
+            throw new RuntimeException(e);
+        }
+
+        // the commands are handled in order, on the worker thread
+        String expected = "FIRST;SECOND;";
+        if (!expected.equals(results.get())) {
+            throw new RuntimeException("expected " + expected + " got " + results.get());
+        }
+        if (thread.get() == Thread.currentThread()) {
+            throw new RuntimeException("expected the callback on the worker thread");
+        }
+    }
+}
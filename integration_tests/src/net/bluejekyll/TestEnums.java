@@ -0,0 +1,30 @@
+package net.bluejekyll;
+
+public class TestEnums {
+    static void runTests() {
+        System.out.println(">>>> Running " + TestEnums.class.getName());
+        TestEnums.testRoundTrip();
+        TestEnums.testDescribe();
+        System.out.println("<<<< " + TestEnums.class.getName() + " tests succeeded");
+    }
+
+    static void testRoundTrip() {
+        for (Fruit fruit : Fruit.values()) {
+            Fruit got = NativeEnums.roundTrip(fruit);
+
+            if (got != fruit) {
+                throw new RuntimeException("Expected " + fruit + " got " + got);
+            }
+        }
+    }
+
+    static void testDescribe() {
+        String expected = "BANANA:1,CHERRY:2";
+        NativeEnums enums = new NativeEnums();
+        String got = enums.describeNative();
+
+        if (!expected.equals(got)) {
+            throw new RuntimeException("Expected " + expected + " got " + got);
+        }
+    }
+}
@@ -7,6 +7,8 @@ public class TestExceptions {
         TestExceptions.testThrowsSomethingMsg();
         TestExceptions.testCatchesSomething();
         TestExceptions.testPanicsAreRuntimeExceptions();
+        TestExceptions.testPanicExceptionIsConfigurable();
+        TestExceptions.testIoErrorsAreIOExceptions();
         System.out.println("<<<< " + TestExceptions.class.getName() + " tests succeeded");
     }
 
@@ -82,4 +84,44 @@ public class TestExceptions {
 
         System.out.println("caught exception: " + exception);
     }
-}
\ No newline at end of file
+
+    public static void testPanicExceptionIsConfigurable() {
+        Exceptions exceptions = new Exceptions();
+
+        NativePanicError exception;
+        try {
+            exceptions.panicsWithErrorCode(7);
+            exception = null;
+        } catch (NativePanicError e) {
+            exception = e;
+        }
+
+        if (exception == null) {
+            throw new RuntimeException("no NativePanicError caught");
+        }
+
+        if (!exception.getMessage().startsWith("panic 'native error code 7'")) {
+            throw new RuntimeException("expected msg: 'panic 'native error code 7'' got: '" + exception.getMessage() + "'");
+        }
+
+        System.out.println("caught exception: " + exception);
+    }
+
+    public static void testIoErrorsAreIOExceptions() {
+        Exceptions exceptions = new Exceptions();
+
+        java.io.IOException exception;
+        try {
+            exceptions.readFile("/this/file/does/not/exist");
+            exception = null;
+        } catch (java.io.IOException e) {
+            exception = e;
+        }
+
+        if (exception == null) {
+            throw new RuntimeException("no exception caught");
+        }
+
+        System.out.println("caught exception: " + exception);
+    }
+}
@@ -0,0 +1,32 @@
+package net.bluejekyll;
+
+public class TestFallbacks {
+    static void runTests() {
+        System.out.println(">>>> Running " + TestFallbacks.class.getName());
+        TestFallbacks.testImplemented();
+        TestFallbacks.testFallback();
+        TestFallbacks.testStaticFallback();
+        System.out.println("<<<< " + TestFallbacks.class.getName() + " tests succeeded");
+    }
+
+    static void testImplemented() {
+        int got = new NativeFallbacks(0).squareNative(7);
+        if (got != 49) {
+            throw new RuntimeException("Expected 49 got " + got);
+        }
+    }
+
+    static void testFallback() {
+        int got = new NativeFallbacks(10).offsetNative(5);
+        if (got != 15) {
+            throw new RuntimeException("Expected 15 got " + got);
+        }
+    }
+
+    static void testStaticFallback() {
+        String got = NativeFallbacks.describeNative(3);
+        if (!got.equals("java 3")) {
+            throw new RuntimeException("Expected java 3 got " + got);
+        }
+    }
+}
@@ -0,0 +1,31 @@
+package net.bluejekyll;
+
+public class TestFrames {
+    static void runTests() {
+        System.out.println(">>>> Running " + TestFrames.class.getName());
+        TestFrames.testSumLengths();
+        TestFrames.testLastTwice();
+        System.out.println("<<<< " + TestFrames.class.getName() + " tests succeeded");
+    }
+
+    // far more local references than the default capacity of a native method
+    static final int TIMES = 100_000;
+
+    static void testSumLengths() {
+        NativeFrames frames = new NativeFrames();
+
+        int got = frames.sumLengthsNative(TIMES);
+        if (got != TIMES * 5) {
+            throw new RuntimeException("Expected " + TIMES * 5 + " got " + got);
+        }
+    }
+
+    static void testLastTwice() {
+        NativeFrames frames = new NativeFrames();
+
+        String got = frames.lastTwiceNative(TIMES);
+        if (!got.equals("99999" + "99999")) {
+            throw new RuntimeException("Expected 9999999999 got " + got);
+        }
+    }
+}
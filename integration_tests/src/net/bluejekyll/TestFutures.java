@@ -0,0 +1,77 @@
+package net.bluejekyll;
+
+import java.util.concurrent.CompletableFuture;
+import java.util.concurrent.ExecutionException;
+import java.util.concurrent.TimeUnit;
+
+public class TestFutures {
+    static void runTests() {
+        System.out.println(">>>> Running " + TestFutures.class.getName());
+        try {
+            TestFutures.testGreet();
+            TestFutures.testAdd();
+            TestFutures.testPanic();
+            TestFutures.testCancel();
+        } catch (Exception e) {
+            throw new RuntimeException("Future failed", e);
+        }
+        System.out.println("<<<< " + TestFutures.class.getName() + " tests succeeded");
+    }
+
+    static void testGreet() throws Exception {
+        NativeFutures futures = new NativeFutures();
+
+        String got = futures.greetNative("Java").get(5, TimeUnit.SECONDS);
+        if (!got.equals("Hello, Java")) {
+            throw new RuntimeException("Expected Hello, Java got " + got);
+        }
+    }
+
+    static void testAdd() throws Exception {
+        NativeFutures futures = new NativeFutures();
+
+        Integer got = futures.addNative(40, 2).get(5, TimeUnit.SECONDS);
+        if (got != 42) {
+            throw new RuntimeException("Expected 42 got " + got);
+        }
+    }
+
+    static void testPanic() throws Exception {
+        NativeFutures futures = new NativeFutures();
+
+        CompletableFuture<Void> future = futures.panicNative();
+        try {
+            future.get(5, TimeUnit.SECONDS);
+            throw new RuntimeException("Expected the future to fail");
+        } catch (ExecutionException e) {
+            String msg = e.getCause().getMessage();
+            if (!msg.contains("the future panicked")) {
+                throw new RuntimeException("Expected a panic got " + msg);
+            }
+        }
+    }
+
+    static void testCancel() throws Exception {
+        NativeFutures futures = new NativeFutures();
+
+        long handle = futures.newCancellation();
+        CompletableFuture<Long> future = futures.untilCancelled(handle);
+        Thread.sleep(50);
+        if (future.isDone()) {
+            throw new RuntimeException("Expected the future to wait for the cancellation");
+        }
+
+        if (!NativeFutures.cancel(handle)) {
+            throw new RuntimeException("Expected the handle to be registered");
+        }
+        Long got = future.get(5, TimeUnit.SECONDS);
+        if (got != handle) {
+            throw new RuntimeException("Expected " + handle + " got " + got);
+        }
+
+        // released by the future
+        if (NativeFutures.cancel(handle)) {
+            throw new RuntimeException("Expected the handle to be released");
+        }
+    }
+}
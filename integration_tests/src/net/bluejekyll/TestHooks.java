@@ -0,0 +1,26 @@
+package net.bluejekyll;
+
+public class TestHooks {
+    static void runTests() {
+        System.out.println(">>>> Running " + TestHooks.class.getName());
+        TestHooks.testEnter();
+        TestHooks.testExit();
+        System.out.println("<<<< " + TestHooks.class.getName() + " tests succeeded");
+    }
+
+    static void testEnter() {
+        long first = NativeHooks.enteredCalls();
+        long second = NativeHooks.enteredCalls();
+        if (second - first != 1) {
+            throw new RuntimeException("Expected 1 more enter got " + (second - first));
+        }
+    }
+
+    static void testExit() {
+        long first = NativeHooks.exitedCalls();
+        long second = NativeHooks.exitedCalls();
+        if (second - first != 1) {
+            throw new RuntimeException("Expected 1 more exit got " + (second - first));
+        }
+    }
+}
@@ -0,0 +1,52 @@
+package net.bluejekyll;
+
+import java.util.ArrayList;
+import java.util.List;
+
+public class TestMonitors {
+    static void runTests() {
+        System.out.println(">>>> Running " + TestMonitors.class.getName());
+        TestMonitors.testLocked();
+        TestMonitors.testIncrement();
+        System.out.println("<<<< " + TestMonitors.class.getName() + " tests succeeded");
+    }
+
+    static void testLocked() {
+        NativeMonitors monitors = new NativeMonitors();
+
+        if (!monitors.lockedNative()) {
+            throw new RuntimeException("Expected the monitor to be held");
+        }
+        if (monitors.isLocked()) {
+            throw new RuntimeException("Expected the monitor to be released");
+        }
+    }
+
+    static void testIncrement() {
+        final NativeMonitors monitors = new NativeMonitors();
+        final int threads = 4;
+        final int times = 10_000;
+
+        List<Thread> running = new ArrayList<>();
+        for (int i = 0; i < threads; i++) {
+            Thread thread = new Thread(() -> monitors.incrementNative(times));
+            thread.start();
+            running.add(thread);
+        }
+
+        for (Thread thread : running) {
+            try {
+                thread.join();
+            } catch (InterruptedException e) {
+                throw new RuntimeException(e);
+            }
+        }
+
+        // synchronized in Java shares the monitor with Rust
+        synchronized (monitors) {
+            if (monitors.getCount() != threads * times) {
+                throw new RuntimeException("Expected " + threads * times + " got " + monitors.getCount());
+            }
+        }
+    }
+}
@@ -0,0 +1,53 @@
+package net.bluejekyll;
+
+public class TestNames {
+    static void runTests() {
+        System.out.println(">>>> Running " + TestNames.class.getName());
+        TestNames.testUnderscore();
+        TestNames.testDollar();
+        TestNames.testUnicode();
+        TestNames.testSurrogatePair();
+        TestNames.testNestedClasses();
+        System.out.println("<<<< " + TestNames.class.getName() + " tests succeeded");
+    }
+
+    static void testUnderscore() {
+        int got = NativeNames.add_one(1);
+        if (got != 2) {
+            throw new RuntimeException("Expected 2 got " + got);
+        }
+    }
+
+    static void testDollar() {
+        int got = NativeNames.dollar$sign(2);
+        if (got != -2) {
+            throw new RuntimeException("Expected -2 got " + got);
+        }
+    }
+
+    static void testUnicode() {
+        String got = NativeNames.grüße("Jürgen");
+        if (!got.equals("Grüße, Jürgen")) {
+            throw new RuntimeException("Expected Grüße, Jürgen got " + got);
+        }
+    }
+
+    static void testSurrogatePair() {
+        int got = NativeNames.𝒜dd(2, 3);
+        if (got != 5) {
+            throw new RuntimeException("Expected 5 got " + got);
+        }
+    }
+
+    static void testNestedClasses() {
+        int got = NativeNames.Inner$Ünïcode.twice(21);
+        if (got != 42) {
+            throw new RuntimeException("Expected 42 got " + got);
+        }
+
+        got = new NativeNames.𝒜().plus(41);
+        if (got != 42) {
+            throw new RuntimeException("Expected 42 got " + got);
+        }
+    }
+}
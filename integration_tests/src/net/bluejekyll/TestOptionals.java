@@ -0,0 +1,40 @@
+package net.bluejekyll;
+
+import java.util.Optional;
+
+public class TestOptionals {
+    static void runTests() {
+        System.out.println(">>>> Running " + TestOptionals.class.getName());
+        TestOptionals.testUpper();
+        TestOptionals.testLength();
+        System.out.println("<<<< " + TestOptionals.class.getName() + " tests succeeded");
+    }
+
+    static void testUpper() {
+        NativeOptionals optionals = new NativeOptionals();
+
+        Optional<String> got = optionals.upperNative(Optional.of("rust"));
+        if (!got.equals(Optional.of("RUST"))) {
+            throw new RuntimeException("Expected Optional[RUST] got " + got);
+        }
+
+        got = optionals.upperNative(Optional.empty());
+        if (got.isPresent()) {
+            throw new RuntimeException("Expected empty got " + got);
+        }
+    }
+
+    static void testLength() {
+        NativeOptionals optionals = new NativeOptionals();
+
+        Optional<Integer> got = optionals.lengthNative(Optional.of("rust"));
+        if (!got.equals(Optional.of(4))) {
+            throw new RuntimeException("Expected Optional[4] got " + got);
+        }
+
+        got = optionals.lengthNative(Optional.empty());
+        if (got.isPresent()) {
+            throw new RuntimeException("Expected empty got " + got);
+        }
+    }
+}
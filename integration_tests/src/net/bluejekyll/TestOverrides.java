@@ -0,0 +1,60 @@
+package net.bluejekyll;
+
+public class TestOverrides {
+    static void runTests() {
+        System.out.println(">>>> Running " + TestOverrides.class.getName());
+        TestOverrides.testRenamedNullable();
+        TestOverrides.testResult();
+        TestOverrides.testArrays();
+        TestOverrides.testWrappers();
+        System.out.println("<<<< " + TestOverrides.class.getName() + " tests succeeded");
+    }
+
+    static void testRenamedNullable() {
+        NativeOverrides overrides = new NativeOverrides();
+
+        String got = overrides.nativeGreet("Java");
+        if (!got.equals("Hello, Java")) {
+            throw new RuntimeException("Expected Hello, Java got " + got);
+        }
+
+        got = overrides.nativeGreet(null);
+        if (!got.equals("Hello, nobody")) {
+            throw new RuntimeException("Expected Hello, nobody got " + got);
+        }
+    }
+
+    static void testResult() {
+        NativeOverrides overrides = new NativeOverrides();
+
+        int got = overrides.parseNumber("42");
+        if (got != 42) {
+            throw new RuntimeException("Expected 42 got " + got);
+        }
+
+        try {
+            overrides.parseNumber("forty two");
+        } catch (RuntimeException e) {
+            if (!e.getMessage().startsWith("not a number")) {
+                throw new RuntimeException("Unexpected message: " + e.getMessage());
+            }
+            return;
+        }
+
+        throw new RuntimeException("Expected a RuntimeException");
+    }
+
+    static void testArrays() {
+        int got = new NativeOverrides().sumBytes(new byte[] { 1, 2, 3 });
+        if (got != 6) {
+            throw new RuntimeException("Expected 6 got " + got);
+        }
+    }
+
+    static void testWrappers() {
+        String got = new NativeOverrides().callWrappers();
+        if (!got.equals("second, caught: true")) {
+            throw new RuntimeException("Expected second, caught: true got " + got);
+        }
+    }
+}
@@ -10,6 +10,9 @@ public class TestPrimitives {
         test_add_values_native();
         test_print_hello();
         test_call_dad();
+        test_add_boxed();
+        test_not_boxed();
+        test_echo_char();
         System.out.println("<<<< " + TestPrimitives.class.getName() + " tests succeeded");
     }
 
@@ -71,4 +74,44 @@ public class TestPrimitives {
             throw new RuntimeException("Expected " + expected + " got " + got);
         }
     }
+
+    static void test_add_boxed() {
+        NativePrimitives obj = new NativePrimitives();
+        Integer got = obj.addBoxedNative(7, 35L);
+
+        if (got == null || got != 42) {
+            throw new RuntimeException("Expected 42 got " + got);
+        }
+
+        got = obj.addBoxedNative(null, 35L);
+        if (got != null) {
+            throw new RuntimeException("Expected null got " + got);
+        }
+    }
+
+    static void test_not_boxed() {
+        NativePrimitives obj = new NativePrimitives();
+
+        if (obj.notBoxed(true) != Boolean.FALSE) {
+            throw new RuntimeException("Expected false");
+        }
+
+        if (obj.notBoxed(null) != null) {
+            throw new RuntimeException("Expected null");
+        }
+    }
+
+    static void test_echo_char() {
+        NativePrimitives obj = new NativePrimitives();
+
+        char ret = obj.echoChar('a');
+        if (ret != 'a') {
+            throw new RuntimeException("Expected a, got: " + ret);
+        }
+
+        ret = obj.echoChar('\uD83D');
+        if (ret != '\uFFFD') {
+            throw new RuntimeException("Expected U+FFFD, got: " + (int) ret);
+        }
+    }
 }
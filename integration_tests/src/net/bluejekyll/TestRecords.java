@@ -0,0 +1,35 @@
+package net.bluejekyll;
+
+public class TestRecords {
+    static void runTests() {
+        System.out.println(">>>> Running " + TestRecords.class.getName());
+        TestRecords.testTranslate();
+        TestRecords.testNullLabel();
+        TestRecords.testToJson();
+        System.out.println("<<<< " + TestRecords.class.getName() + " tests succeeded");
+    }
+
+    static void testTranslate() {
+        Point got = NativeRecords.translateNative(new Point(1, 2, "a"), 10, 20);
+        Point expected = new Point(11, 22, "a");
+        if (!got.equals(expected)) {
+            throw new RuntimeException("Expected " + expected + " got " + got);
+        }
+    }
+
+    static void testNullLabel() {
+        Point got = NativeRecords.translateNative(new Point(1, 2, null), -1, -2);
+        Point expected = new Point(0, 0, null);
+        if (!got.equals(expected)) {
+            throw new RuntimeException("Expected " + expected + " got " + got);
+        }
+    }
+
+    static void testToJson() {
+        String json = NativeRecords.toJsonNative(new Point(3, 4, "b"));
+        String expected = "{\"x\":3,\"y\":4,\"label\":\"b\"}";
+        if (!json.equals(expected)) {
+            throw new RuntimeException("Expected " + expected + " got " + json);
+        }
+    }
+}
@@ -19,6 +19,33 @@ public class TestRunner {
         TestStrings.runTests();
         TestArrays.runTests();
         TestExceptions.runTests();
+        TestEnums.runTests();
+        TestOptionals.runTests();
+        TestTime.runTests();
+        TestUuids.runTests();
+        TestBigNums.runTests();
+        TestStreams.runTests();
+        TestConstants.runTests();
+        TestFutures.runTests();
+        TestFrames.runTests();
+        TestMonitors.runTests();
+        TestAbstract.runTests();
+        TestCounter.runTests();
+        TestContext.runTests();
+        TestBeans.runTests();
+        TestRecords.runTests();
+        TestSealed.runTests();
+        TestFallbacks.runTests();
+        TestHooks.runTests();
+        TestVarargs.runTests();
+        TestBuffers.runTests();
+        TestAggregate.runTests();
+        TestOverrides.runTests();
+        TestBuilders.runTests();
+        TestBridges.runTests();
+        TestNames.runTests();
+        TestAnnotations.runTests();
+        TestDispatch.runTests();
         System.out.println("All tests succeeded");
     }
 
@@ -0,0 +1,26 @@
+package net.bluejekyll;
+
+public class TestSealed {
+    static void runTests() {
+        System.out.println(">>>> Running " + TestSealed.class.getName());
+        TestSealed.testDescribe(new Shape.Circle(2.0), "circle with radius 2");
+        TestSealed.testDescribe(new Shape.Square(3.0), "square with side 3");
+        TestSealed.testDescribe(new Triangle(4.0, 5.0), "triangle with area 10");
+        TestSealed.testNull();
+        System.out.println("<<<< " + TestSealed.class.getName() + " tests succeeded");
+    }
+
+    static void testDescribe(Shape shape, String expected) {
+        String got = NativeSealed.describeNative(shape);
+        if (!got.equals(expected)) {
+            throw new RuntimeException("Expected " + expected + " got " + got);
+        }
+    }
+
+    static void testNull() {
+        String got = NativeSealed.describeNative(null);
+        if (!got.equals("null")) {
+            throw new RuntimeException("Expected null got " + got);
+        }
+    }
+}
@@ -0,0 +1,53 @@
+package net.bluejekyll;
+
+import java.io.ByteArrayInputStream;
+import java.io.ByteArrayOutputStream;
+import java.io.IOException;
+import java.io.InputStream;
+import java.util.Arrays;
+import java.util.Random;
+
+public class TestStreams {
+    static void runTests() {
+        System.out.println(">>>> Running " + TestStreams.class.getName());
+        TestStreams.testCopy();
+        TestStreams.testReadError();
+        System.out.println("<<<< " + TestStreams.class.getName() + " tests succeeded");
+    }
+
+    static void testCopy() {
+        NativeStreams streams = new NativeStreams();
+
+        // larger than the buffers used for copying
+        byte[] bytes = new byte[100_000];
+        new Random(42).nextBytes(bytes);
+
+        ByteArrayOutputStream out = new ByteArrayOutputStream();
+        streams.copyNative(new ByteArrayInputStream(bytes), out);
+
+        if (!Arrays.equals(bytes, out.toByteArray())) {
+            throw new RuntimeException("Expected " + bytes.length + " bytes got " + out.size());
+        }
+    }
+
+    static void testReadError() {
+        NativeStreams streams = new NativeStreams();
+
+        InputStream failing = new InputStream() {
+            @Override
+            public int read() throws IOException {
+                throw new IOException("read failed");
+            }
+
+            @Override
+            public int read(byte[] b, int off, int len) throws IOException {
+                throw new IOException("read failed");
+            }
+        };
+
+        String got = streams.readErrorNative(failing);
+        if (!got.equals("java.io.IOException: read failed")) {
+            throw new RuntimeException("Expected java.io.IOException: read failed got " + got);
+        }
+    }
+}
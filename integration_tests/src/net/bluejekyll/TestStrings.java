@@ -7,6 +7,11 @@ public class TestStrings {
         TestStrings.testTieOffString();
         TestStrings.testReturnStringFromJava();
         TestStrings.testConstructor();
+        TestStrings.testViaFactory();
+        TestStrings.testReturnInterned();
+        TestStrings.testTieOffCharSequence();
+        TestStrings.testAppendToBuilder();
+        TestStrings.testNullArgument();
         System.out.println("<<<< " + TestStrings.class.getName() + " tests succeeded");
     }
 
@@ -44,4 +49,57 @@ public class TestStrings {
             throw new RuntimeException("expected " + expected + " got " + got);
         }
     }
+
+    static void testViaFactory() {
+        String expected = "made by the factory";
+        String got = new NativeStrings().viaFactory(expected);
+
+        if (!expected.equals(got)) {
+            throw new RuntimeException("expected " + expected + " got " + got);
+        }
+    }
+
+    static void testReturnInterned() {
+        String expected = "message, interned";
+        String got = new NativeStrings("message").returnInternedNative();
+
+        if (!expected.equals(got)) {
+            throw new RuntimeException("expected " + expected + " got " + got);
+        }
+    }
+
+    static void testTieOffCharSequence() {
+        String expected = "a sequence of i❤🦀";
+        NativeStrings strings = new NativeStrings();
+        CharSequence got = strings.tieOffCharSequence(new StringBuilder(expected));
+
+        if (!expected.equals(got.toString())) {
+            throw new RuntimeException("expected " + expected + " got " + got);
+        }
+    }
+
+    static void testAppendToBuilder() {
+        String expected = "built with i❤🦀";
+        NativeStrings strings = new NativeStrings();
+        StringBuilder got = strings.appendToBuilder(new StringBuilder("built with "), "i❤🦀");
+
+        if (!expected.equals(got.toString())) {
+            throw new RuntimeException("expected " + expected + " got " + got);
+        }
+    }
+
+    // the debug build validates the arguments, a null String is rejected before it's converted
+    static void testNullArgument() {
+        NativeStrings strings = new NativeStrings();
+        try {
+            strings.tieOffString(null);
+        } catch (IllegalArgumentException e) {
+            if (!e.getMessage().contains("of net.bluejekyll.NativeStrings.tieOffString(Ljava/lang/String;)Ljava/lang/String; is null")) {
+                throw new RuntimeException("unexpected message " + e.getMessage());
+            }
+            return;
+        }
+
+        throw new RuntimeException("expected an IllegalArgumentException");
+    }
 }
@@ -0,0 +1,54 @@
+package net.bluejekyll;
+
+import java.time.Duration;
+import java.time.Instant;
+import java.util.Date;
+
+public class TestTime {
+    static void runTests() {
+        System.out.println(">>>> Running " + TestTime.class.getName());
+        TestTime.testAdd();
+        TestTime.testToDate();
+        TestTime.testBetween();
+        System.out.println("<<<< " + TestTime.class.getName() + " tests succeeded");
+    }
+
+    static void testAdd() {
+        NativeTime time = new NativeTime();
+
+        Instant start = Instant.ofEpochSecond(1_000_000_000L, 500);
+        Duration duration = Duration.ofSeconds(30, 999_999_600);
+        Instant got = time.addNative(start, duration);
+        if (!got.equals(start.plus(duration))) {
+            throw new RuntimeException("Expected " + start.plus(duration) + " got " + got);
+        }
+
+        // before the epoch
+        start = Instant.ofEpochSecond(-10, 1);
+        got = time.addNative(start, Duration.ofSeconds(2));
+        if (!got.equals(Instant.ofEpochSecond(-8, 1))) {
+            throw new RuntimeException("Expected " + Instant.ofEpochSecond(-8, 1) + " got " + got);
+        }
+    }
+
+    static void testToDate() {
+        NativeTime time = new NativeTime();
+
+        Instant instant = Instant.ofEpochMilli(1_234_567_890_123L);
+        Date got = time.toDateNative(instant);
+        if (!got.equals(Date.from(instant))) {
+            throw new RuntimeException("Expected " + Date.from(instant) + " got " + got);
+        }
+    }
+
+    static void testBetween() {
+        NativeTime time = new NativeTime();
+
+        Instant start = Instant.ofEpochSecond(100);
+        Instant end = Instant.ofEpochSecond(160, 42);
+        Duration got = time.betweenNative(start, end);
+        if (!got.equals(Duration.ofSeconds(60, 42))) {
+            throw new RuntimeException("Expected PT60.000000042S got " + got);
+        }
+    }
+}
@@ -0,0 +1,50 @@
+package net.bluejekyll;
+
+import java.util.Optional;
+import java.util.UUID;
+
+public class TestUuids {
+    static void runTests() {
+        System.out.println(">>>> Running " + TestUuids.class.getName());
+        TestUuids.testToString();
+        TestUuids.testParse();
+        TestUuids.testOptional();
+        System.out.println("<<<< " + TestUuids.class.getName() + " tests succeeded");
+    }
+
+    static void testToString() {
+        NativeUuids uuids = new NativeUuids();
+
+        UUID uuid = UUID.randomUUID();
+        String got = uuids.toStringNative(uuid);
+        if (!got.equals(uuid.toString())) {
+            throw new RuntimeException("Expected " + uuid + " got " + got);
+        }
+    }
+
+    static void testParse() {
+        NativeUuids uuids = new NativeUuids();
+
+        // the high bits are set to check the sign of the longs
+        String uuid = "f81d4fae-7dec-11d0-a765-00a0c91e6bf6";
+        UUID got = uuids.parseNative(uuid);
+        if (!got.equals(UUID.fromString(uuid))) {
+            throw new RuntimeException("Expected " + uuid + " got " + got);
+        }
+    }
+
+    static void testOptional() {
+        NativeUuids uuids = new NativeUuids();
+
+        Optional<UUID> got = uuids.maybeNilNative(new UUID(0, 0));
+        if (got.isPresent()) {
+            throw new RuntimeException("Expected empty got " + got);
+        }
+
+        UUID uuid = UUID.randomUUID();
+        got = uuids.maybeNilNative(uuid);
+        if (!got.equals(Optional.of(uuid))) {
+            throw new RuntimeException("Expected " + uuid + " got " + got);
+        }
+    }
+}
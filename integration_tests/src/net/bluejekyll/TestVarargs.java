@@ -0,0 +1,32 @@
+package net.bluejekyll;
+
+public class TestVarargs {
+    static void runTests() {
+        System.out.println(">>>> Running " + TestVarargs.class.getName());
+        TestVarargs.testSum();
+        TestVarargs.testJoin();
+        TestVarargs.testCountNulls();
+        System.out.println("<<<< " + TestVarargs.class.getName() + " tests succeeded");
+    }
+
+    static void testSum() {
+        int got = NativeVarargs.sumNative(1, 2, 3);
+        if (got != 6) {
+            throw new RuntimeException("Expected 6 got " + got);
+        }
+    }
+
+    static void testJoin() {
+        String got = new NativeVarargs().joinNative("a", "b");
+        if (!got.equals("a, b")) {
+            throw new RuntimeException("Expected a, b got " + got);
+        }
+    }
+
+    static void testCountNulls() {
+        int got = new NativeVarargs().countNullsNative();
+        if (got != 1) {
+            throw new RuntimeException("Expected 1 got " + got);
+        }
+    }
+}
@@ -0,0 +1,8 @@
+package net.bluejekyll;
+
+// the default methods of interfaces are wrapped on the classes implementing them
+public interface Titled extends Named, Polite {
+    default String title() {
+        return "Dr. " + this.name();
+    }
+}
@@ -0,0 +1,15 @@
+package net.bluejekyll;
+
+public final class Triangle implements Shape {
+    private final double base;
+    private final double height;
+
+    public Triangle(double base, double height) {
+        this.base = base;
+        this.height = height;
+    }
+
+    public double area() {
+        return this.base * this.height / 2;
+    }
+}
@@ -0,0 +1,8 @@
+package net.bluejekyll.aggregate;
+
+// implemented with the trait of the package, NetBluejekyllAggregateRs
+public class NativeAdder {
+    public native int add(int a, int b);
+
+    public static native long twice(long value);
+}
@@ -0,0 +1,6 @@
+package net.bluejekyll.aggregate;
+
+// implemented with the trait of the package, NetBluejekyllAggregateRs
+public class NativeGreeter {
+    public native String greet(String name);
+}
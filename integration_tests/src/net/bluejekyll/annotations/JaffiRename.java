@@ -0,0 +1,14 @@
+package net.bluejekyll.annotations;
+
+import java.lang.annotation.ElementType;
+import java.lang.annotation.Retention;
+import java.lang.annotation.RetentionPolicy;
+import java.lang.annotation.Target;
+
+/** The Rust name of the method */
+@Retention(RetentionPolicy.RUNTIME)
+@Target(ElementType.METHOD)
+public @interface JaffiRename {
+    /** the Rust name */
+    String value();
+}
@@ -0,0 +1,9 @@
+package net.bluejekyll.annotations;
+
+import java.lang.annotation.ElementType;
+import java.lang.annotation.Target;
+
+/** Nothing is generated for the method, the default retention keeps it in the class file */
+@Target({ ElementType.METHOD, ElementType.CONSTRUCTOR })
+public @interface JaffiSkip {
+}
@@ -0,0 +1,44 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The bindings of `NativeBind` are generated with the macro, from the classes compiled by the `build.rs`.
+
+use jaffi_support::jni::JNIEnv;
+
+use crate::bindings::*;
+
+mod bindings {
+    #![allow(dead_code, non_snake_case)]
+
+    jaffi_macros::bind!(
+        classpath = "${OUT_DIR}/java/classes",
+        native = ["net.bluejekyll.NativeBind"],
+    );
+}
+
+struct NativeBindRsImpl;
+
+impl<'j> NativeBindRs<'j> for NativeBindRsImpl {
+    fn from_env(_env: JNIEnv<'j>) -> Self {
+        Self
+    }
+
+    fn add_native(&self, _class: NetBluejekyllNativeBindClass<'j>, arg0: i32, arg1: i32) -> i32 {
+        arg0 + arg1
+    }
+
+    fn greet_native(&self, _this: NetBluejekyllNativeBind<'j>, arg0: String) -> String {
+        format!("hello {arg0}")
+    }
+}
+
+#[test]
+fn test_bind_exports() {
+    let add = bindings::Java_net_bluejekyll_NativeBind_addNative as *const () as usize;
+    let greet = bindings::Java_net_bluejekyll_NativeBind_greetNative as *const () as usize;
+    assert_ne!(add, greet);
+}
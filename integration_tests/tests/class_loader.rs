@@ -0,0 +1,101 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The class loader of the lookups, in its own test binary as the loader is global to the process.
+
+use jaffi_support::{
+    cache::ClassCache,
+    class_loader,
+    jni::{
+        objects::{JClass, JObject, JValue},
+        JNIEnv,
+    },
+    testing::with_env,
+};
+
+/// The classes compiled by the build script
+const CLASSPATH: &[&str] = &[concat!(env!("OUT_DIR"), "/java/classes")];
+
+/// A class loader of the classes directory, without a parent, so that it defines its own copy of each class
+fn isolated_loader<'j>(env: JNIEnv<'j>) -> JObject<'j> {
+    let dir = env.new_string(CLASSPATH[0]).unwrap();
+    let file = env
+        .new_object("java/io/File", "(Ljava/lang/String;)V", &[dir.into()])
+        .unwrap();
+    let url = env
+        .call_method(file, "toURI", "()Ljava/net/URI;", &[])
+        .and_then(|uri| uri.l())
+        .and_then(|uri| env.call_method(uri, "toURL", "()Ljava/net/URL;", &[]))
+        .and_then(|url| url.l())
+        .unwrap();
+    let urls = env.new_object_array(1, "java/net/URL", url).unwrap();
+
+    env.new_object(
+        "java/net/URLClassLoader",
+        "([Ljava/net/URL;Ljava/lang/ClassLoader;)V",
+        &[JValue::Object(urls.into()), JValue::Object(JObject::null())],
+    )
+    .unwrap()
+}
+
+fn class_loader_of<'j>(env: JNIEnv<'j>, class: JClass<'j>) -> JObject<'j> {
+    env.call_method(class, "getClassLoader", "()Ljava/lang/ClassLoader;", &[])
+        .and_then(|loader| loader.l())
+        .unwrap()
+}
+
+#[test]
+fn test_class_loader() {
+    static DOCTOR: ClassCache = ClassCache::new("net/bluejekyll/Doctor");
+
+    with_env(CLASSPATH, |env| {
+        // without a loader the classes are found with `FindClass`, i.e. the system class loader of the attached thread
+        let system = env.find_class("net/bluejekyll/Doctor").unwrap();
+        let found = class_loader::find_class(env, "net/bluejekyll/Doctor").unwrap();
+        assert!(env.is_same_object(*found, *system).unwrap());
+
+        // the loader that is set is used for the lookups, and by the class caches
+        let loader = isolated_loader(env);
+        class_loader::set(env, loader).unwrap();
+        let found = class_loader::find_class(env, "net/bluejekyll/Doctor").unwrap();
+        assert!(!env.is_same_object(*found, *system).unwrap());
+        assert!(env
+            .is_same_object(class_loader_of(env, found), loader)
+            .unwrap());
+        let cached = DOCTOR.get(env).unwrap();
+        assert!(env.is_same_object(*cached, *found).unwrap());
+
+        // the bootstrap classes are found through the parents of the loader, and arrays with `FindClass`
+        let string = class_loader::find_class(env, "java/lang/String").unwrap();
+        assert!(env
+            .is_same_object(*string, *env.find_class("java/lang/String").unwrap())
+            .unwrap());
+        assert!(class_loader::find_class(env, "[Lnet/bluejekyll/Doctor;").is_ok());
+
+        // a missing class is the ClassNotFoundException of `loadClass`
+        assert!(class_loader::find_class(env, "net/bluejekyll/Missing").is_err());
+        let exception = env.exception_occurred().unwrap();
+        env.exception_clear().unwrap();
+        assert!(env
+            .is_instance_of(exception, "java/lang/ClassNotFoundException")
+            .unwrap());
+
+        // the loader of a class replaces the earlier one, the cached classes are kept
+        class_loader::capture(env, "net/bluejekyll/Doctor").unwrap();
+        let found = class_loader::find_class(env, "net/bluejekyll/Doctor").unwrap();
+        assert!(env.is_same_object(*found, *system).unwrap());
+        let cached = DOCTOR.get(env).unwrap();
+        assert!(env
+            .is_same_object(class_loader_of(env, cached), loader)
+            .unwrap());
+
+        // the bootstrap loader, null, resets the lookups to `FindClass`
+        class_loader::set(env, JObject::null()).unwrap();
+        let found = class_loader::find_class(env, "net/bluejekyll/Doctor").unwrap();
+        assert!(env.is_same_object(*found, *system).unwrap());
+    });
+}
@@ -0,0 +1,16 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The API of the wrappers generated with the options of the `build.rs`, which is only checked by the compiler.
+
+#[test]
+fn test_generated_api() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/generated_api.rs");
+    t.compile_fail("tests/ui/deprecated_wrapper.rs");
+    t.compile_fail("tests/ui/private_methods_are_not_wrapped.rs");
+}
@@ -0,0 +1,24 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The `build.rs` exports both names, the JVM links the short names so the long names are only checked here.
+
+use jaffi_integration_tests::net_bluejekyll;
+
+#[test]
+fn test_both_names_exported() {
+    let short = net_bluejekyll::Java_net_bluejekyll_NativeCounter_nextNative as *const () as usize;
+    let long = net_bluejekyll::Java_net_bluejekyll_NativeCounter_nextNative__ as *const () as usize;
+    assert_ne!(short, long);
+
+    // overloaded methods only have the long names
+    let one =
+        net_bluejekyll::Java_net_bluejekyll_NativePrimitives_voidLong__J as *const () as usize;
+    let two =
+        net_bluejekyll::Java_net_bluejekyll_NativePrimitives_voidLong__JI as *const () as usize;
+    assert_ne!(one, two);
+}
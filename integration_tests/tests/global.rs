@@ -0,0 +1,105 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The shutdown of the global references, in its own test binary as it releases the references of all the tests in the process.
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use jaffi_integration_tests::net_bluejekyll::{NetBluejekyllDoctor, NET_BLUEJEKYLL_DOCTOR_CLASS};
+use jaffi_support::{
+    global,
+    jni::{errors::Error as JniError, objects::JObject, JNIEnv},
+    listener::Listener,
+    testing::with_env,
+};
+
+/// The classes compiled by the build script
+const CLASSPATH: &[&str] = &[concat!(env!("OUT_DIR"), "/java/classes")];
+
+/// A listener counting its calls
+fn counting_listener(env: JNIEnv<'_>, calls: &Arc<AtomicUsize>) -> Listener {
+    let calls = Arc::clone(calls);
+    Listener::new(env, "java/lang/Runnable", move |_env, _args| {
+        calls.fetch_add(1, Ordering::Relaxed);
+        Ok(JObject::null())
+    })
+    .expect("no listener")
+}
+
+/// The `InvocationHandler` of the proxy
+fn invocation_handler<'j>(env: JNIEnv<'j>, proxy: JObject<'j>) -> JObject<'j> {
+    env.call_static_method(
+        "java/lang/reflect/Proxy",
+        "getInvocationHandler",
+        "(Ljava/lang/Object;)Ljava/lang/reflect/InvocationHandler;",
+        &[proxy.into()],
+    )
+    .and_then(|handler| handler.l())
+    .expect("not a proxy")
+}
+
+#[test]
+fn test_shutdown() {
+    with_env(CLASSPATH, |env| {
+        global::startup();
+
+        let doctor = NetBluejekyllDoctor::new_1net_bluejekyll_doctor(env);
+        let global = doctor.register_global(env).expect("no global");
+        let first_calls = Arc::new(AtomicUsize::new(0));
+        let first = counting_listener(env, &first_calls);
+        let first_proxy: JObject<'_> = first.get(env).expect("no proxy");
+        assert!(global.local(env).unwrap().is_some());
+        // the listener also registers its handler class
+        assert!(global::live_count() >= 3);
+
+        // e.g. from the generated `JNI_OnUnload`
+        global::shutdown();
+        assert_eq!(global::live_count(), 0);
+        assert!(global.local(env).unwrap().is_none());
+        assert!(matches!(
+            first.get::<JObject<'_>>(env),
+            Err(JniError::NullPtr(_))
+        ));
+        assert!(doctor.register_global(env).is_err());
+
+        // the cached classes are released too, and looked up on each call until the next startup
+        let class = NET_BLUEJEKYLL_DOCTOR_CLASS.get(env).expect("no Doctor");
+        assert!(env.is_instance_of(*doctor, class).unwrap());
+        assert_eq!(global::live_count(), 0);
+
+        // until the library is loaded again
+        global::startup();
+        let global = doctor.register_global(env).expect("no global");
+        assert!(global.local(env).unwrap().is_some());
+        drop(global);
+
+        // the handler class is defined and registered again, in a new class loader
+        let second_calls = Arc::new(AtomicUsize::new(0));
+        let second = counting_listener(env, &second_calls);
+        let second_proxy: JObject<'_> = second.get(env).expect("no proxy");
+        let first_handler = env
+            .get_object_class(invocation_handler(env, first_proxy))
+            .unwrap();
+        let second_handler = env
+            .get_object_class(invocation_handler(env, second_proxy))
+            .unwrap();
+        assert!(!env.is_same_object(first_handler, second_handler).unwrap());
+
+        // both handler classes call their listener
+        for proxy in [first_proxy, second_proxy] {
+            env.call_method(proxy, "run", "()V", &[]).expect("not run");
+        }
+        assert_eq!(first_calls.load(Ordering::Relaxed), 1);
+        assert_eq!(second_calls.load(Ordering::Relaxed), 1);
+
+        drop(second);
+        assert_eq!(global::live_count(), 1, "only the handler class");
+    });
+}
@@ -0,0 +1,60 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The generated header is compared to the headers from `javac -h` in the `build.rs`.
+
+use std::{fs, path::Path};
+
+/// The `JNIEXPORT ...;` declarations, with the whitespace normalized
+fn declarations(header: &str) -> Vec<String> {
+    header
+        .split("JNIEXPORT")
+        .skip(1)
+        .map(|decl| {
+            let decl = decl.split_once(';').expect("declaration without ';'").0;
+            format!(
+                "JNIEXPORT {};",
+                decl.split_whitespace().collect::<Vec<_>>().join(" ")
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_header_matches_javac() {
+    let out_dir = Path::new(env!("OUT_DIR"));
+    let generated = fs::read_to_string(out_dir.join("generated_jaffi.h")).expect("no header");
+    let generated = declarations(&generated);
+
+    let mut checked = 0;
+    for entry in fs::read_dir(out_dir.join("java/classes")).expect("no javac headers") {
+        let path = entry.expect("bad entry").path();
+        if path.extension().is_none_or(|ext| ext != "h") {
+            continue;
+        }
+
+        // bound with the macro in tests/bind.rs, not generated by the build.rs
+        if path
+            .file_name()
+            .is_some_and(|name| name == "net_bluejekyll_NativeBind.h")
+        {
+            continue;
+        }
+
+        let javac = fs::read_to_string(&path).expect("failed to read javac header");
+        for decl in declarations(&javac) {
+            assert!(
+                generated.contains(&decl),
+                "{decl} from {} is not in the generated header",
+                path.display()
+            );
+            checked += 1;
+        }
+    }
+
+    assert!(checked > 0, "no javac declarations found");
+}
@@ -0,0 +1,85 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The log records of the generation, in its own test binary as the logger is global to the process.
+
+use std::{borrow::Cow, path::Path, sync::Mutex};
+
+use jaffi::Jaffi;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+/// Keeps the level, the target and the message of each record
+struct Records(Mutex<Vec<(Level, String, String)>>);
+
+impl Records {
+    fn take(&self) -> Vec<(Level, String, String)> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+impl Log for Records {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        self.0.lock().unwrap().push((
+            record.level(),
+            record.target().to_string(),
+            record.args().to_string(),
+        ));
+    }
+
+    fn flush(&self) {}
+}
+
+static RECORDS: Records = Records(Mutex::new(Vec::new()));
+
+#[test]
+fn test_log_level() {
+    log::set_logger(&RECORDS).expect("a logger is already set");
+    log::set_max_level(LevelFilter::Trace);
+
+    let class_path = Path::new(env!("OUT_DIR")).join("java/classes");
+    let generate = |log_level: LevelFilter| {
+        Jaffi::builder()
+            .native_classes(vec![Cow::from("net.bluejekyll.NativeStrings")])
+            .classpath(vec![Cow::from(class_path.clone())])
+            .log_level(log_level)
+            .build()
+            .generate_class_tokens()
+            .expect("failed to generate");
+        RECORDS.take()
+    };
+
+    // a summary, and each class with the time it took
+    let records = generate(LevelFilter::Debug);
+    assert!(records.iter().all(|(_, target, _)| target == "jaffi"));
+    assert!(records.iter().all(|(level, _, _)| *level <= Level::Debug));
+    assert!(records.iter().any(|(level, _, message)| {
+        *level == Level::Debug
+            && message.starts_with("native class net/bluejekyll/NativeStrings, version ")
+    }));
+    let summary = records
+        .iter()
+        .filter(|(level, _, _)| *level == Level::Info)
+        .map(|(_, _, message)| message)
+        .collect::<Vec<_>>();
+    assert_eq!(summary.len(), 1, "{summary:?}");
+    assert!(summary[0].starts_with("generated 1 native classes and "));
+
+    // and the methods read from each class
+    let records = generate(LevelFilter::Trace);
+    assert!(records.iter().any(|(level, _, message)| {
+        *level == Level::Trace && message.starts_with("methods of net/bluejekyll/NativeStrings: ")
+    }));
+
+    // the default, `Info`, only logs the summary, and `Off` nothing while the logger takes all the levels
+    let records = generate(LevelFilter::Info);
+    assert!(records.iter().all(|(level, _, _)| *level <= Level::Info));
+    assert!(generate(LevelFilter::Off).is_empty());
+}
@@ -0,0 +1,109 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The model of the classes compiled by the `build.rs`, as consumed by external tools.
+
+use std::{borrow::Cow, path::Path};
+
+use jaffi::{
+    model::{JavaType, Method, NativeClass, Visitor, Wrapper},
+    Jaffi,
+};
+
+#[derive(Default)]
+struct Audit {
+    native_classes: Vec<String>,
+    wrappers: Vec<String>,
+    native_methods: Vec<String>,
+}
+
+impl Visitor for Audit {
+    fn visit_native_class(&mut self, class: &NativeClass) {
+        self.native_classes.push(class.class.clone());
+    }
+
+    fn visit_wrapper(&mut self, wrapper: &Wrapper) {
+        self.wrappers.push(wrapper.class.clone());
+    }
+
+    fn visit_method(&mut self, class: &str, method: &Method) {
+        if method.is_native {
+            self.native_methods.push(format!("{class}#{}", method.name));
+        }
+    }
+}
+
+#[test]
+fn test_model() {
+    let class_path = Path::new(env!("OUT_DIR")).join("java/classes");
+    let jaffi = Jaffi::builder()
+        .native_classes(vec![Cow::from("net.bluejekyll.NativeStrings")])
+        .classes_to_wrap(vec![Cow::from("net.bluejekyll.ParentClass")])
+        .classpath(vec![Cow::from(class_path)])
+        .build();
+
+    let model = jaffi.model().expect("failed to read the model");
+    let native = &model.native_classes[0];
+    assert_eq!(native.class, "net/bluejekyll/NativeStrings");
+    assert_eq!(native.trait_name, "NativeStringsRs");
+
+    let eat_string = native
+        .methods
+        .iter()
+        .find(|method| method.name == "eatString")
+        .expect("no eatString");
+    assert_eq!(eat_string.rust_name, "eat_string");
+    assert_eq!(eat_string.descriptor, "(Ljava/lang/String;)V");
+    assert_eq!(
+        eat_string.arguments[0].ty,
+        JavaType::Object("java/lang/String".to_string())
+    );
+    assert_eq!(eat_string.result, None);
+    assert_eq!(
+        eat_string.export_names[0],
+        "Java_net_bluejekyll_NativeStrings_eatString"
+    );
+
+    let mut audit = Audit::default();
+    model.visit(&mut audit);
+    assert_eq!(audit.native_classes, ["net/bluejekyll/NativeStrings"]);
+    assert!(audit
+        .wrappers
+        .contains(&"net/bluejekyll/ParentClass".to_string()));
+    assert!(audit
+        .native_methods
+        .contains(&"net/bluejekyll/NativeStrings#eatString".to_string()));
+    assert!(audit
+        .native_methods
+        .iter()
+        .all(|method| method.starts_with("net/bluejekyll/NativeStrings#")));
+}
+
+#[test]
+fn test_covariant_bridges() {
+    let class_path = Path::new(env!("OUT_DIR")).join("java/classes");
+    let jaffi = Jaffi::builder()
+        .native_classes(vec![])
+        .classes_to_wrap(vec![Cow::from("net.bluejekyll.Draft")])
+        .classpath(vec![Cow::from(class_path)])
+        .build();
+
+    let model = jaffi.model().expect("failed to read the model");
+    let draft = model
+        .wrappers
+        .iter()
+        .find(|wrapper| wrapper.class == "net/bluejekyll/Draft")
+        .expect("no Draft");
+    // only the override is the bridge
+    let bridges = draft
+        .methods
+        .iter()
+        .filter(|method| method.is_bridge)
+        .map(|method| method.rust_name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(bridges, ["copy_bridge"]);
+}
@@ -0,0 +1,52 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The panic hook of the generated `JNI_OnLoad`, in its own test binary as the hook is global to the process.
+
+use jaffi_support::{
+    exceptions::{catch_panic_and_throw, register_panic_hook},
+    jni::objects::JString,
+    testing::with_env,
+    JavaVoid,
+};
+
+/// The classes compiled by the build script
+const CLASSPATH: &[&str] = &[concat!(env!("OUT_DIR"), "/java/classes")];
+
+#[test]
+fn test_panic_hook_backtrace() {
+    // read on the first capture of a backtrace
+    std::env::set_var("RUST_LIB_BACKTRACE", "1");
+
+    with_env(CLASSPATH, |env| {
+        register_panic_hook(env.get_java_vm().unwrap());
+        let _: JavaVoid = catch_panic_and_throw(env, || panic!("boom"));
+        drop(std::panic::take_hook());
+
+        // thrown by the hook, with the location and the backtrace, the exception isn't replaced by `catch_panic_and_throw`
+        let exception = env.exception_occurred().unwrap();
+        env.exception_clear().unwrap();
+        assert!(env
+            .is_instance_of(exception, "java/lang/RuntimeException")
+            .unwrap());
+        let message = env
+            .call_method(exception, "getMessage", "()Ljava/lang/String;", &[])
+            .and_then(|message| message.l())
+            .unwrap();
+        let message = String::from(env.get_string(JString::from(message)).unwrap());
+
+        let location = concat!("panic 'boom' at ", file!(), ":");
+        assert!(message.starts_with(location), "{message}");
+        let (_, backtrace) = message
+            .split_once("\nRust backtrace:\n")
+            .expect("no backtrace");
+        assert!(
+            backtrace.contains("test_panic_hook_backtrace"),
+            "{backtrace}"
+        );
+    });
+}
@@ -0,0 +1,28 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The report written by the `build.rs` lists what wasn't generated as declared.
+
+use std::{fs, path::Path};
+
+#[test]
+fn test_report_lists_renames() {
+    let report = fs::read_to_string(Path::new(env!("OUT_DIR")).join("jaffi_report.json"))
+        .expect("no report");
+
+    for expected in [
+        r#"{"class": "net/bluejekyll/RustKeywords", "member": "crate", "kind": "renamed", "reason": "crate is not a valid identifier, the method is named r_crate"}"#,
+        r#"{"class": "net/bluejekyll/NativePrimitives", "member": "voidLong", "kind": "renamed", "reason": "overloaded, the method is named void_long_ji"}"#,
+        r#"{"class": "net/bluejekyll/AbstractNatives", "member": "<init>", "kind": "skipped", "reason": "abstract classes can't be constructed"}"#,
+        r#"{"class": "net/bluejekyll/NativeAnnotations", "member": "skipped", "kind": "skipped", "reason": "skipped by @JaffiSkip"}"#,
+    ] {
+        assert!(
+            report.contains(expected),
+            "{expected} is not in the report:\n{report}"
+        );
+    }
+}
@@ -0,0 +1,16 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Local wrappers must not escape their thread, the global wrappers are for sharing.
+
+#[test]
+fn test_send_sync() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/global_is_send_sync.rs");
+    t.compile_fail("tests/ui/local_is_not_send.rs");
+    t.compile_fail("tests/ui/local_is_not_sync.rs");
+}
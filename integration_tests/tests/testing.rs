@@ -0,0 +1,761 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The generated wrappers called from Rust, in the JVM embedded by `jaffi_support::testing`.
+
+use std::{
+    collections::HashSet,
+    future::Future,
+    io::Write,
+    panic::AssertUnwindSafe,
+    pin::pin,
+    str::FromStr,
+    sync::{mpsc, Arc},
+    task::{Context, Poll, Wake},
+    thread::{self, Thread},
+};
+
+use jaffi_integration_tests::net_bluejekyll::{
+    native_fallbacks, IOExceptionErr, NetBluejekyllConnection, NetBluejekyllCountListener,
+    NetBluejekyllDoctor, NetBluejekyllDocument, NetBluejekyllDraft, NetBluejekyllFruit,
+    NetBluejekyllFruitEnum, NetBluejekyllLateBound, NetBluejekyllLedger, NetBluejekyllNativeEnums,
+    NetBluejekyllNativeFallbacksGlobal, NetBluejekyllNotifier, NetBluejekyllNotifierGlobal,
+    NetBluejekyllSomethingException, StaticNetBluejekyllLateBound, StaticNetBluejekyllLedger,
+    GENERATED_BY, GENERATED_FROM, GENERATED_OPTIONS, JAFFI_REGISTRY, NET_BLUEJEKYLL_DOCTOR_CLASS,
+    NET_BLUEJEKYLL_LATE_BOUND_CLASS,
+};
+use jaffi_support::{
+    cache::{load_all, ClassCache, FieldIdCache, StaticFieldIdCache},
+    dispatch::Dispatcher,
+    exceptions::{
+        jdk_exception_name, AnyThrowable, Error, Exception, IOException, IllegalArgumentException,
+        IllegalStateException, NullPointerException, UnsupportedOperationException,
+    },
+    io::{JOutputStream, JavaOutputStream},
+    jni::{
+        errors::Error as JniError,
+        objects::{JObject, JString, JThrowable, JValue},
+        signature::JavaType,
+        JNIEnv,
+    },
+    pool::{CallError, JavaPool},
+    reflect::{BindingError, LateBoundMethod},
+    scratch,
+    string::{to_rust_string_with, StringStrategy},
+    testing::with_env,
+    FromRustToJava, JavaInt, Throwable, TryFromJavaValue, TryIntoJavaValue,
+};
+
+/// The classes compiled by the build script
+const CLASSPATH: &[&str] = &[concat!(env!("OUT_DIR"), "/java/classes")];
+
+#[test]
+fn test_enum_wrapper() {
+    with_env(CLASSPATH, |env| {
+        let fruit = NetBluejekyllFruit::from_enum_name(env, "BANANA").expect("valueOf failed");
+        assert_eq!(fruit.enum_name(env).unwrap(), "BANANA");
+        assert_eq!(
+            fruit.enum_ordinal(env).unwrap(),
+            NetBluejekyllFruitEnum::Banana.ordinal()
+        );
+    });
+}
+
+#[test]
+fn test_enum_conversions() {
+    with_env(CLASSPATH, |env| {
+        // the constant is read from its static field
+        let cherry =
+            NetBluejekyllFruit::from_rust_enum(env, NetBluejekyllFruitEnum::Cherry).unwrap();
+        assert_eq!(
+            cherry.rust_enum(env).unwrap(),
+            Some(NetBluejekyllFruitEnum::Cherry)
+        );
+
+        let banana = TryIntoJavaValue::<NetBluejekyllFruit>::try_into_java_value("BANANA", env)
+            .and_then(|banana| banana.l())
+            .map(NetBluejekyllFruit::from)
+            .unwrap();
+        assert_eq!(banana.enum_name(env).unwrap(), "BANANA");
+
+        // the IllegalArgumentException of valueOf is returned instead of a panic
+        let durian = TryIntoJavaValue::<NetBluejekyllFruit>::try_into_java_value("DURIAN", env);
+        assert!(matches!(durian, Err(JniError::JavaException)));
+        env.exception_clear().unwrap();
+    });
+}
+
+#[test]
+fn test_rejected_enum_arguments() {
+    with_env(CLASSPATH, |env| {
+        let enums = NetBluejekyllNativeEnums::new_1net_bluejekyll_native_enums(env);
+        assert_eq!(
+            enums.describe(env, NetBluejekyllFruitEnum::Banana, "CHERRY"),
+            "BANANA:1,CHERRY:2"
+        );
+
+        // Java isn't called, the wrapper returns the default with the IllegalArgumentException of valueOf pending
+        assert_eq!(
+            enums.describe(env, "DURIAN", NetBluejekyllFruitEnum::Apple),
+            ""
+        );
+        let (exception, message) = take_exception_message(env);
+        assert!(env
+            .is_instance_of(exception, "java/lang/IllegalArgumentException")
+            .unwrap());
+        assert!(message.unwrap().contains("DURIAN"));
+
+        // a rejected argument of a constructor is a `null` object
+        let connection = NetBluejekyllConnection::new_1net_bluejekyll_connection(
+            env,
+            "localhost",
+            80,
+            false,
+            1000,
+            "DURIAN",
+        );
+        assert!(connection.is_null());
+        let (exception, _) = take_exception_message(env);
+        assert!(env
+            .is_instance_of(exception, "java/lang/IllegalArgumentException")
+            .unwrap());
+    });
+}
+
+#[test]
+fn test_identity_methods() {
+    with_env(CLASSPATH, |env| {
+        let apple = NetBluejekyllFruit::from_enum_name(env, "APPLE").unwrap();
+        let banana = NetBluejekyllFruit::from_enum_name(env, "BANANA").unwrap();
+        // a second local reference to the constant, with a different pointer
+        let apple2 = NetBluejekyllFruit::from(env.new_local_ref::<JObject>(*apple).unwrap());
+        assert_ne!(apple.into_inner(), apple2.into_inner());
+
+        assert!(apple.is_same_object(env, &apple2).unwrap());
+        assert!(!apple.is_same_object(env, &banana).unwrap());
+        assert_eq!(
+            apple.identity_hash_code(env).unwrap(),
+            apple2.identity_hash_code(env).unwrap()
+        );
+
+        let fruits = [apple, banana, apple2]
+            .iter()
+            .map(|fruit| fruit.identity(env).unwrap())
+            .collect::<HashSet<_>>();
+        assert_eq!(fruits.len(), 2);
+    });
+}
+
+#[test]
+fn test_static_free_functions() {
+    with_env(CLASSPATH, |env| {
+        assert_eq!(native_fallbacks::describe(env, 7), "java 7");
+    });
+}
+
+#[test]
+fn test_listener() {
+    with_env(CLASSPATH, |env| {
+        let notifier = NetBluejekyllNotifier::new_1net_bluejekyll_notifier(env);
+        let listener =
+            NetBluejekyllCountListener::from_fn(env, |_env, name, count| format!("{name} {count}"))
+                .expect("no listener");
+        let listener = listener.unregister_on_drop(env, move |env, listener| {
+            notifier.remove_listener(env, listener.into())
+        });
+
+        notifier.add_listener(env, listener.listener().get(env).expect("no proxy"));
+        assert_eq!(notifier.fire(env, "apples", 3), "apples 3");
+        assert_eq!(notifier.listener_count(env), 1);
+
+        // the proxy implements the methods of Object with its identity
+        let proxy: JObject<'_> = listener.listener().get(env).expect("no proxy");
+        let same = env
+            .call_method(
+                proxy,
+                "equals",
+                "(Ljava/lang/Object;)Z",
+                &[JValue::Object(proxy)],
+            )
+            .and_then(|equal| equal.z())
+            .unwrap();
+        assert!(same);
+
+        drop(listener);
+        assert_eq!(notifier.listener_count(env), 0);
+    });
+}
+
+#[test]
+fn test_try_from_jvalue() {
+    with_env(CLASSPATH, |env| {
+        assert_eq!(
+            <i32 as TryFromJavaValue<JavaInt>>::try_from_jvalue(env, JValue::Int(7)).unwrap(),
+            7
+        );
+        assert!(<i32 as TryFromJavaValue<JavaInt>>::try_from_jvalue(env, JValue::Bool(1)).is_err());
+        assert!(
+            <String as TryFromJavaValue<jaffi_support::jni::objects::JString>>::try_from_jvalue(
+                env,
+                JValue::Long(1)
+            )
+            .is_err()
+        );
+    });
+}
+
+#[test]
+#[should_panic(expected = "Java exception is pending")]
+fn test_pending_exception() {
+    with_env(CLASSPATH, |env| {
+        // valueOf throws IllegalArgumentException
+        assert!(NetBluejekyllFruit::from_enum_name(env, "DURIAN").is_err());
+    });
+}
+
+#[test]
+fn test_exception_object() {
+    with_env(CLASSPATH, |env| {
+        let something =
+            NetBluejekyllSomethingException::new_1net_bluejekyll_something_exception(env);
+        let exception = Exception::<AnyThrowable>::catch(env, JThrowable::from(*something))
+            .unwrap_or_else(|_| panic!("any Throwable is caught"));
+        let object: NetBluejekyllSomethingException<'_> =
+            exception.object().expect("a SomethingException");
+        assert!(env.is_same_object(*object, *something).unwrap());
+
+        // the class of the wrapper is checked, any Throwable is caught
+        let error = env
+            .new_object("java/lang/Error", "()V", &[])
+            .expect("no Error");
+        let exception = Exception::<AnyThrowable>::catch(env, JThrowable::from(error))
+            .unwrap_or_else(|_| panic!("any Throwable is caught"));
+        match exception.object::<NetBluejekyllSomethingException<'_>>() {
+            Err(JniError::WrongJValueType(class, _)) => {
+                assert_eq!(class, "net/bluejekyll/SomethingException")
+            }
+            result => panic!("expected the class to be checked: {:?}", result.map(|_| ())),
+        }
+    });
+}
+
+/// Takes the pending exception, and returns its message
+fn take_exception_message<'j>(env: JNIEnv<'j>) -> (JThrowable<'j>, Option<String>) {
+    let exception = env.exception_occurred().expect("no exception");
+    assert!(!exception.is_null(), "nothing was thrown");
+    env.exception_clear().unwrap();
+
+    let message = env
+        .call_method(exception, "getMessage", "()Ljava/lang/String;", &[])
+        .and_then(|message| message.l())
+        .unwrap();
+    let message =
+        (!message.is_null()).then(|| String::from(env.get_string(JString::from(message)).unwrap()));
+    (exception, message)
+}
+
+#[test]
+fn test_jdk_exceptions() {
+    with_env(CLASSPATH, |env| {
+        fn check<T: Throwable>(env: JNIEnv<'_>, exception: T, class: &str) {
+            exception.throw(env, "thrown").unwrap();
+            let (thrown, message) = take_exception_message(env);
+            assert!(env.is_instance_of(thrown, class).unwrap(), "{class}");
+            assert_eq!(message.as_deref(), Some("thrown"));
+            assert!(T::catch(env, thrown).is_ok(), "{class}");
+            assert_eq!(
+                jdk_exception_name(class),
+                Some(class.rsplit('/').next().unwrap())
+            );
+        }
+
+        check(
+            env,
+            IllegalArgumentException,
+            "java/lang/IllegalArgumentException",
+        );
+        check(
+            env,
+            IllegalStateException,
+            "java/lang/IllegalStateException",
+        );
+        check(env, IOException, "java/io/IOException");
+        check(env, NullPointerException, "java/lang/NullPointerException");
+        check(
+            env,
+            UnsupportedOperationException,
+            "java/lang/UnsupportedOperationException",
+        );
+        assert_eq!(jdk_exception_name("java/lang/Exception"), None);
+
+        // subclasses are caught, other classes and null aren't
+        let not_found = env
+            .new_object("java/io/FileNotFoundException", "()V", &[])
+            .map(JThrowable::from)
+            .unwrap();
+        assert!(IOException::catch(env, not_found).is_ok());
+        assert!(IllegalStateException::catch(env, not_found).is_err());
+        assert!(IOException::catch(env, JThrowable::from(JObject::null())).is_err());
+
+        // the markers convert into the exception sets of the generated code, with an empty message
+        let error = Error::<IOExceptionErr>::from(IOException);
+        assert!(matches!(error.kind(), IOExceptionErr::IOException(_)));
+        assert_eq!(error.msg(), "");
+
+        // `?` on `std::io::Error` throws an `IOException` with the message of the error
+        let error = Error::<IOExceptionErr>::from(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "no such file",
+        ));
+        assert!(matches!(error.kind(), IOExceptionErr::IOException(_)));
+        assert_eq!(error.msg(), "no such file");
+        assert!(error.to_string().ends_with("IOExceptionErr: no such file"));
+        error.throw(env).unwrap();
+        let (thrown, message) = take_exception_message(env);
+        assert!(env.is_instance_of(thrown, "java/io/IOException").unwrap());
+        assert_eq!(message.as_deref(), Some("no such file"));
+    });
+}
+
+#[test]
+fn test_pending_exception_check() {
+    with_env(CLASSPATH, |env| {
+        let doctor = NetBluejekyllDoctor::new_1net_bluejekyll_doctor(env);
+        env.throw_new("java/lang/IllegalStateException", "not handled")
+            .unwrap();
+
+        // the wrapper panics instead of calling into Java, the exception is left pending
+        let panic = std::panic::catch_unwind(AssertUnwindSafe(|| doctor.title(env)))
+            .expect_err("called with a pending exception");
+        let message = jaffi_support::exceptions::get_panic_message(&*panic);
+        assert!(message.starts_with("exception pending on calling net.bluejekyll."));
+        assert!(message.contains(".title()Ljava/lang/String;"));
+        assert!(env.exception_check().unwrap());
+        env.exception_clear().unwrap();
+    });
+}
+
+#[test]
+fn test_late_bound() {
+    with_env(CLASSPATH, |env| {
+        NetBluejekyllLateBound::bind(env).expect("not bound");
+
+        let late_bound = NetBluejekyllLateBound::new_1net_bluejekyll_late_bound(env, 21);
+        assert_eq!(late_bound.twice(env), 42);
+        assert_eq!(late_bound.version(env), "1.0");
+
+        // the overloads with the name are listed, read with reflection
+        let missing = [LateBoundMethod::new(
+            "net/bluejekyll/LateBound",
+            "twice",
+            "(I)I",
+            false,
+        )];
+        match jaffi_support::reflect::bind(env, &NET_BLUEJEKYLL_LATE_BOUND_CLASS, &missing) {
+            Err(BindingError::Method { candidates, .. }) => {
+                assert_eq!(candidates, ["public int net.bluejekyll.LateBound.twice()"])
+            }
+            result => panic!("expected a missing method: {result:?}"),
+        }
+    });
+}
+
+#[test]
+fn test_package_private() {
+    with_env(CLASSPATH, |env| {
+        let ledger = NetBluejekyllLedger::new_1net_bluejekyll_ledger(env);
+        ledger.deposit(env, 20);
+        ledger.deposit(env, 22);
+        assert_eq!(ledger.balance(env), 42);
+        assert_eq!(ledger.currency(env), "EUR");
+    });
+}
+
+#[test]
+fn test_scratch_buffers() {
+    with_env(CLASSPATH, |env| {
+        scratch::release();
+
+        let doctor = NetBluejekyllDoctor::new_1net_bluejekyll_doctor(env);
+        for _ in 0..10 {
+            assert_eq!(doctor.greet(env, "Rose"), "hello Rose");
+        }
+        // one buffer of each type is reused by the calls
+        assert!(scratch::retained_bytes() > 0);
+
+        // nul and the supplementary characters are encoded differently in modified UTF-8
+        for string in [
+            "",
+            "ascii",
+            "nul \0 in the middle",
+            "\u{1F980} crab",
+            "\u{e9}t\u{e9}",
+        ] {
+            let java = scratch::new_string(env, string).expect("no String");
+            let rust = String::from(env.get_string(java).expect("no chars"));
+            assert_eq!(rust, string);
+        }
+
+        // a method signature isn't the type of a return value
+        let greet = env
+            .get_method_id(
+                "net/bluejekyll/Doctor",
+                "greet",
+                "(Ljava/lang/String;)Ljava/lang/String;",
+            )
+            .expect("no greet");
+        let ret = JavaType::from_str("()V").expect("not a signature");
+        // Safety: the return type is rejected before the method is called without its argument
+        match unsafe { scratch::call_method_unchecked(env, doctor.into(), greet, ret, &[]) } {
+            Err(JniError::WrongJValueType(..)) => (),
+            result => panic!("expected the return type to be rejected: {result:?}"),
+        }
+
+        scratch::release();
+        assert_eq!(scratch::retained_bytes(), 0);
+    });
+}
+
+/// Creates a Java String from UTF-16, which can contain unpaired surrogates unlike a Rust `str`
+fn new_utf16_string<'j>(env: JNIEnv<'j>, chars: &[u16]) -> JString<'j> {
+    let raw_env = env.get_native_interface();
+    // Safety: the chars are copied into the new String
+    let string = unsafe {
+        let new_string = (**raw_env).NewString.expect("NewString missing");
+        new_string(raw_env, chars.as_ptr(), chars.len() as i32)
+    };
+    assert!(!string.is_null(), "no String");
+    JString::from(string)
+}
+
+#[test]
+fn test_string_strategies() {
+    const STRATEGIES: [StringStrategy; 3] = [
+        StringStrategy::Utf16,
+        StringStrategy::Utf16Lossy,
+        StringStrategy::ModifiedUtf8,
+    ];
+
+    with_env(CLASSPATH, |env| {
+        // nul is encoded as two bytes in modified UTF-8, and a surrogate pair as two 3 byte sequences
+        for string in [
+            "",
+            "ascii",
+            "nul \0 in the middle\0",
+            "\u{1F980} crab \u{10FFFF}",
+            "\u{e9}t\u{e9}",
+        ] {
+            let from_rust = JString::rust_to_java(string, env);
+            let from_utf16 = new_utf16_string(env, &string.encode_utf16().collect::<Vec<_>>());
+            for strategy in STRATEGIES {
+                assert_eq!(to_rust_string_with(env, from_rust, strategy), string);
+                assert_eq!(to_rust_string_with(env, from_utf16, strategy), string);
+            }
+        }
+
+        // an unpaired high and an unpaired low surrogate
+        for (chars, lossy) in [
+            (&[0x61, 0xD83D, 0x62][..], "a\u{FFFD}b"),
+            (&[0x61, 0xDC00][..], "a\u{FFFD}"),
+        ] {
+            let java = new_utf16_string(env, chars);
+            assert_eq!(
+                to_rust_string_with(env, java, StringStrategy::Utf16Lossy),
+                lossy
+            );
+
+            // each byte of the 3 byte sequence of the surrogate is invalid UTF-8
+            assert_eq!(
+                to_rust_string_with(env, java, StringStrategy::ModifiedUtf8),
+                lossy.replace('\u{FFFD}', "\u{FFFD}\u{FFFD}\u{FFFD}")
+            );
+
+            let strict = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                to_rust_string_with(env, java, StringStrategy::Utf16)
+            }));
+            assert!(strict.is_err(), "the surrogate is unpaired");
+        }
+    });
+}
+
+#[test]
+fn test_class_cache() {
+    static STRING: ClassCache = ClassCache::new("java/lang/String");
+    static MISSING: ClassCache = ClassCache::new("net/bluejekyll/Missing");
+
+    with_env(CLASSPATH, |env| {
+        assert_eq!(STRING.class(), "java/lang/String");
+        let string = env.find_class("java/lang/String").unwrap();
+
+        // the class outlives the local frame of the lookup, each get is a new local reference to it
+        env.push_local_frame(4).unwrap();
+        let first = STRING.get(env).expect("no String");
+        assert!(env.is_same_object(*first, *string).unwrap());
+        env.pop_local_frame(JObject::null()).unwrap();
+        let cached = STRING.get(env).expect("no String");
+        assert!(env.is_same_object(*cached, *string).unwrap());
+
+        // a missing class is an error with the pending NoClassDefFoundError, and is looked up again on the next call
+        assert!(MISSING.get(env).is_err());
+        assert!(env.exception_check().unwrap());
+        env.exception_clear().unwrap();
+        assert_eq!(MISSING.class(), "net/bluejekyll/Missing");
+
+        // load_all skips the missing classes, without leaving an exception pending
+        load_all(env, &[&STRING, &MISSING]);
+        assert!(!env.exception_check().unwrap());
+    });
+}
+
+#[test]
+fn test_field_id_cache() {
+    static PARTICLE: ClassCache = ClassCache::new("net/bluejekyll/Particle");
+    static FRUIT: ClassCache = ClassCache::new("net/bluejekyll/Fruit");
+    static BUFFER: FieldIdCache =
+        FieldIdCache::new("net/bluejekyll/Particle", "buffer", "Ljava/nio/ByteBuffer;");
+    static BUFFER_IN: FieldIdCache =
+        FieldIdCache::new("net/bluejekyll/Particle", "buffer", "Ljava/nio/ByteBuffer;");
+    static MISSING: FieldIdCache = FieldIdCache::new(
+        "net/bluejekyll/Particle",
+        "missing",
+        "Ljava/nio/ByteBuffer;",
+    );
+    static APPLE: StaticFieldIdCache =
+        StaticFieldIdCache::new("net/bluejekyll/Fruit", "APPLE", "Lnet/bluejekyll/Fruit;");
+
+    with_env(CLASSPATH, |env| {
+        assert_eq!(BUFFER.signature(), "Ljava/nio/ByteBuffer;");
+        let particle = env.find_class("net/bluejekyll/Particle").unwrap();
+        let buffer = env
+            .get_field_id(particle, "buffer", "Ljava/nio/ByteBuffer;")
+            .unwrap()
+            .into_inner();
+
+        // the id of the lookup, and the same id from the cache on the next calls
+        for _ in 0..2 {
+            assert_eq!(BUFFER.get(env).expect("no buffer").into_inner(), buffer);
+            assert_eq!(
+                BUFFER_IN
+                    .get_in(env, &PARTICLE)
+                    .expect("no buffer")
+                    .into_inner(),
+                buffer
+            );
+        }
+
+        // a missing field is an error with the pending NoSuchFieldError
+        assert!(MISSING.get_in(env, &PARTICLE).is_err());
+        let exception = env.exception_occurred().unwrap();
+        env.exception_clear().unwrap();
+        assert!(env
+            .is_instance_of(exception, "java/lang/NoSuchFieldError")
+            .unwrap());
+
+        // the constants of the enums
+        assert_eq!(APPLE.class(), "net/bluejekyll/Fruit");
+        assert_eq!(APPLE.signature(), "Lnet/bluejekyll/Fruit;");
+        let fruit = env.find_class("net/bluejekyll/Fruit").unwrap();
+        let apple = env
+            .get_static_field_id(fruit, "APPLE", "Lnet/bluejekyll/Fruit;")
+            .unwrap()
+            .into_inner();
+        for _ in 0..2 {
+            assert_eq!(
+                APPLE.get_in(env, &FRUIT).expect("no APPLE").into_inner(),
+                apple
+            );
+        }
+    });
+}
+
+#[test]
+fn test_java_module() {
+    with_env(CLASSPATH, |env| {
+        // Safety: the JVM of the tests supports JNI_VERSION_9
+        let module = unsafe { NET_BLUEJEKYLL_DOCTOR_CLASS.module(env) }.expect("no module");
+        // the classes on the classpath are in the unnamed module
+        let named = env
+            .call_method(module, "isNamed", "()Z", &[])
+            .and_then(|named| named.z())
+            .expect("not a Module");
+        assert!(!named);
+    });
+}
+
+#[test]
+fn test_provenance() {
+    assert!(GENERATED_BY.starts_with("jaffi "));
+    let (_, fingerprint) = GENERATED_FROM
+        .iter()
+        .find(|(class, _)| *class == "net/bluejekyll/NativeStrings")
+        .expect("no NativeStrings");
+    assert_eq!(fingerprint.len(), 16);
+    assert!(GENERATED_OPTIONS.contains(&"time_conversions"));
+}
+
+#[test]
+fn test_output_stream_errors() {
+    with_env(CLASSPATH, |env| {
+        // writes to a pipe without a reader throw an IOException
+        let unconnected = env
+            .new_object("java/io/PipedOutputStream", "()V", &[])
+            .expect("no PipedOutputStream");
+
+        let mut stream = JavaOutputStream::new(env, unconnected.into());
+        stream.write_all(b"buffered").expect("not written yet");
+        let error = stream.into_stream().expect_err("the pipe isn't connected");
+        assert!(error.to_string().contains("java.io.IOException"));
+        assert!(!env.exception_check().unwrap());
+
+        // thrown again when the stream is passed to Java
+        let mut stream = JavaOutputStream::new(env, unconnected.into());
+        stream.write_all(b"buffered").expect("not written yet");
+        let _stream = JOutputStream::rust_to_java(stream, env);
+        assert!(env.exception_check().unwrap());
+        env.exception_clear().unwrap();
+    });
+}
+
+#[test]
+fn test_covariant_bridge() {
+    with_env(CLASSPATH, |env| {
+        let draft = NetBluejekyllDraft::new_1net_bluejekyll_draft(env);
+        let copy: NetBluejekyllDraft<'_> = draft.copy(env);
+        assert_eq!(copy.kind(env), "draft");
+
+        // the alias calls the bridge, which dispatches to the override
+        let document: NetBluejekyllDocument<'_> = draft.copy_bridge(env);
+        assert_eq!(document.kind(env), "draft");
+    });
+}
+
+#[test]
+fn test_default_methods() {
+    with_env(CLASSPATH, |env| {
+        let doctor = NetBluejekyllDoctor::new_1net_bluejekyll_doctor(env);
+        assert_eq!(doctor.title(env), "Dr. Who");
+        // the default method of a super interface
+        assert_eq!(doctor.greet(env, "Rose"), "hello Rose");
+        // the override of the class, not the default
+        assert_eq!(doctor.farewell(env), "allons-y");
+
+        let titled = doctor.as_net_bluejekyll_titled();
+        assert_eq!(titled.greet(env, "Martha"), "hello Martha");
+        assert_eq!(titled.farewell(env), "allons-y");
+    });
+}
+
+#[test]
+fn test_class_registry() {
+    // the library isn't loaded by the JVM, so JNI_OnLoad doesn't register the table
+    jaffi_support::class_registry::register(JAFFI_REGISTRY);
+
+    let draft = jaffi_support::registry()
+        .into_iter()
+        .find(|class| class.name == "net/bluejekyll/Draft")
+        .expect("Draft not registered");
+    assert_eq!(draft.super_class, Some("net/bluejekyll/Document"));
+    let copy = draft
+        .method("copy", "()Lnet/bluejekyll/Draft;")
+        .expect("no copy");
+    assert_eq!(copy.rust_name, "copy");
+    assert!(!copy.is_static && !copy.is_native);
+
+    let super_classes = jaffi_support::class_registry::super_classes("net/bluejekyll/Draft");
+    assert_eq!(super_classes[0].name, "net/bluejekyll/Document");
+
+    let doctor = jaffi_support::class_registry::find("net/bluejekyll/Doctor").expect("no Doctor");
+    assert_eq!(doctor.interfaces, ["net/bluejekyll/Titled"]);
+    assert!(doctor
+        .method("greet", "(Ljava/lang/String;)Ljava/lang/String;")
+        .is_some());
+
+    // the native methods are listed with the wrapper methods
+    let strings = jaffi_support::class_registry::find("net/bluejekyll/NativeStrings")
+        .expect("no NativeStrings");
+    let eat_string = strings
+        .method("eatString", "(Ljava/lang/String;)V")
+        .expect("no eatString");
+    assert!(eat_string.is_native);
+
+    jaffi_support::class_registry::unregister(JAFFI_REGISTRY);
+    assert!(jaffi_support::class_registry::find("net/bluejekyll/Draft").is_none());
+}
+
+/// Polls the future on this thread until it's ready, the pool's threads wake it
+fn block_on<F: Future>(future: F) -> F::Output {
+    struct Unpark(Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Arc::new(Unpark(thread::current())).into();
+    let mut cx = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+#[test]
+fn test_async_wrappers() {
+    let pool = with_env(CLASSPATH, |env| JavaPool::from_env(env, 2)).expect("no pool");
+
+    let notifier = block_on(NetBluejekyllNotifierGlobal::new_1net_bluejekyll_notifier_async(&pool))
+        .map(Arc::new)
+        .unwrap();
+    assert_eq!(
+        block_on(Arc::clone(&notifier).listener_count_async(&pool)),
+        Ok(0)
+    );
+    assert_eq!(
+        block_on(notifier.fire_async(&pool, "apples".to_string(), 3)),
+        Ok(String::new())
+    );
+    assert_eq!(
+        block_on(NetBluejekyllNativeFallbacksGlobal::describe_async(&pool, 7)),
+        Ok("java 7".to_string())
+    );
+
+    pool.shutdown();
+    assert_eq!(
+        block_on(NetBluejekyllNativeFallbacksGlobal::describe_async(&pool, 7)),
+        Err(CallError::ShutDown)
+    );
+}
+
+#[test]
+fn test_dispatcher() {
+    let dispatcher = Dispatcher::<i32>::new();
+    assert!(dispatcher.send(0).is_err());
+
+    let (results, received) = mpsc::channel();
+    with_env(CLASSPATH, |env| {
+        dispatcher
+            .start(env, move |env, command| {
+                // the worker calls Java with its own env
+                results
+                    .send(native_fallbacks::describe(env, command))
+                    .unwrap();
+            })
+            .expect("not started");
+    });
+
+    dispatcher.send(1).unwrap();
+    dispatcher.send(2).unwrap();
+    // the queued commands are handled before the worker exits
+    dispatcher.shutdown();
+    assert_eq!(received.iter().collect::<Vec<_>>(), ["java 1", "java 2"]);
+    assert!(!dispatcher.is_running());
+    assert!(dispatcher.send(3).is_err());
+}
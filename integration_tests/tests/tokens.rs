@@ -0,0 +1,514 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The tokens are generated from the classes compiled by the `build.rs`, without writing any files.
+//!
+//! Only the options which the `build.rs` doesn't use are tested here, the code generated with the others is compiled into the
+//!   library and tested in the JVM by `testing.rs`, and by the compile tests of `compile.rs`.
+
+use std::{borrow::Cow, path::Path};
+
+use jaffi::{
+    model::{NativeClass, Wrapper},
+    Backend, GeneratedTokens, Jaffi, JniVersion, PendingExceptionChecks, Prelude, PrimitiveTypes,
+};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+/// Builds a `Jaffi` for the classes compiled by the `build.rs`, with the options of the builder, e.g. `byte_vec_args(true)`
+macro_rules! jaffi {
+    ($($option:ident($($value:expr),*)),* $(,)?) => {
+        Jaffi::builder()
+            .classpath(vec![Cow::from(Path::new(env!("OUT_DIR")).join("java/classes"))])
+            $(.$option($($value),*))*
+            .build()
+    };
+}
+
+/// The code generated for the native class, e.g. `net.bluejekyll.NativeBind`
+fn native_class(tokens: &GeneratedTokens, class: &str) -> String {
+    tokens
+        .native_class(class)
+        .unwrap_or_else(|| panic!("no native class {class}"))
+        .to_string()
+}
+
+/// The code generated for the wrapped class, e.g. `net.bluejekyll.ParentClass`
+fn wrapper(tokens: &GeneratedTokens, class: &str) -> String {
+    tokens
+        .wrapper(class)
+        .unwrap_or_else(|| panic!("no wrapper {class}"))
+        .to_string()
+}
+
+/// Asserts the `expected` tokens are in the `generated` code
+#[track_caller]
+fn assert_generated(generated: &str, expected: TokenStream) {
+    let expected = expected.to_string();
+    assert!(
+        generated.contains(&expected),
+        "`{expected}` is not in the generated code:\n{generated}"
+    );
+}
+
+/// Asserts the `unexpected` tokens aren't in the `generated` code
+#[track_caller]
+fn assert_not_generated(generated: &str, unexpected: TokenStream) {
+    let unexpected = unexpected.to_string();
+    assert!(
+        !generated.contains(&unexpected),
+        "`{unexpected}` is in the generated code:\n{generated}"
+    );
+}
+
+/// The position of the `expected` tokens in the `generated` code
+#[track_caller]
+fn position(generated: &str, expected: TokenStream) -> usize {
+    let expected = expected.to_string();
+    generated
+        .find(&expected)
+        .unwrap_or_else(|| panic!("`{expected}` is not in the generated code:\n{generated}"))
+}
+
+fn quote_all(tokens: &GeneratedTokens) -> String {
+    let mut all = tokens.support().clone();
+    all.extend(tokens.wrappers().map(|(_, t)| t.clone()));
+    all.extend(tokens.native_classes().map(|(_, t)| t.clone()));
+    all.to_string()
+}
+
+#[test]
+fn test_generate_class_tokens() {
+    let jaffi = jaffi!(
+        output_dir(Path::new("/nonexistent")),
+        native_classes(vec![Cow::from("net.bluejekyll.NativeStrings")]),
+        classes_to_wrap(vec![Cow::from("net.bluejekyll.ParentClass")]),
+    );
+
+    let tokens = jaffi.generate_class_tokens().expect("failed to generate");
+    assert_generated(&tokens.support().to_string(), quote!(JNI_OnLoad));
+
+    let native = native_class(&tokens, "net.bluejekyll.NativeStrings");
+    assert_generated(&native, quote!(trait NativeStringsRs));
+    assert_not_generated(&native, quote!(JNI_OnLoad));
+
+    assert_generated(
+        &wrapper(&tokens, "net.bluejekyll.ParentClass"),
+        quote!(NetBluejekyllParentClass),
+    );
+    assert!(tokens
+        .wrappers()
+        .any(|(class, _)| class == "net/bluejekyll/ParentClass"));
+
+    // the whole file is the same as the classes together
+    let all = jaffi.generate_tokens().expect("failed to generate");
+    assert_eq!(all.to_string(), quote_all(&tokens));
+}
+
+#[test]
+fn test_native_method_suffix() {
+    let tokens = jaffi!(
+        native_classes(vec![Cow::from("net.bluejekyll.NativeBind")]),
+        native_method_suffix(Cow::from("Native")),
+    )
+    .generate_class_tokens()
+    .expect("failed to generate");
+    let native = native_class(&tokens, "net.bluejekyll.NativeBind");
+
+    // the trait methods are stripped, the exports keep the Java names
+    assert_generated(
+        &native,
+        quote!(fn add(&self, class: NetBluejekyllNativeBindClass<'j>, arg0: i32, arg1: i32) -> i32),
+    );
+    assert_generated(
+        &native,
+        quote!(fn greet(&self, this: NetBluejekyllNativeBind<'j>, arg0: String) -> String),
+    );
+    assert_not_generated(&native, quote!(fn add_native));
+    assert_generated(&native, quote!(Java_net_bluejekyll_NativeBind_addNative));
+}
+
+#[test]
+fn test_local_capacities() {
+    let tokens = jaffi!(
+        native_classes(vec![Cow::from("net.bluejekyll.NativeBind")]),
+        local_capacities(vec![
+            (Cow::from("net.bluejekyll.NativeBind"), 8),
+            (Cow::from("net.bluejekyll.NativeBind#greetNative"), 32),
+        ]),
+    )
+    .generate_class_tokens()
+    .expect("failed to generate");
+    let native = native_class(&tokens, "net.bluejekyll.NativeBind");
+
+    // the method overrides the class
+    assert_generated(&native, quote!(ensure_local_capacity(8i32)));
+    assert_generated(&native, quote!(ensure_local_capacity(32i32)));
+    assert_eq!(native.matches("ensure_local_capacity").count(), 2);
+}
+
+#[test]
+fn test_byte_vec_args() {
+    let tokens = jaffi!(
+        native_classes(vec![Cow::from("net.bluejekyll.NativeArrays")]),
+        byte_vec_args(true),
+    )
+    .generate_class_tokens()
+    .expect("failed to generate");
+    let native = native_class(&tokens, "net.bluejekyll.NativeArrays");
+
+    // only the arguments are copied, the results are still arrays
+    assert_generated(
+        &native,
+        quote!(fn send_bytes(&self, class: NetBluejekyllNativeArraysClass<'j>, arg0: Vec<u8>)),
+    );
+    assert_generated(&native, quote!(-> jaffi_support::arrays::JavaByteArray<'j>));
+
+    // the copies need the array_helpers feature of jaffi_support
+    assert_generated(
+        &tokens.support().to_string(),
+        quote!(jaffi_support::array_helpers!(required "byte_vec_args")),
+    );
+}
+
+#[test]
+fn test_varargs_array_helpers() {
+    let tokens = jaffi!(native_classes(vec![Cow::from(
+        "net.bluejekyll.NativeVarargs"
+    )]))
+    .generate_class_tokens()
+    .expect("failed to generate");
+
+    // the varargs wrappers are left out when jaffi_support is built without the array_helpers feature
+    let all = quote_all(&tokens);
+    assert!(
+        position(&all, quote!(jaffi_support::array_helpers!))
+            < position(&all, quote!(fn sum_varargs))
+    );
+    assert_not_generated(&tokens.support().to_string(), quote!(required));
+}
+
+#[test]
+fn test_lifetime_name_and_inner_module() {
+    let all = jaffi!(
+        native_classes(vec![Cow::from("net.bluejekyll.NativeBind")]),
+        lifetime_name(Cow::from("jni")),
+        inner_module(Cow::from("generated")),
+    )
+    .generate_tokens()
+    .expect("failed to generate")
+    .to_string();
+
+    assert_generated(&all, quote!(NetBluejekyllNativeBind<'jni>));
+    assert!(!all.contains("'j "));
+    assert!(all.starts_with("mod generated {"));
+    assert_generated(
+        &all,
+        quote!(
+            pub use self::generated::*;
+        ),
+    );
+    assert_generated(
+        &all,
+        quote!(
+            use super::super::NativeBindRsImpl;
+        ),
+    );
+}
+
+/// Adds a marker trait, implemented by each wrapper, and a constant for each native class
+struct MarkerBackend;
+
+impl Backend for MarkerBackend {
+    fn render_support(&self, tokens: TokenStream) -> TokenStream {
+        quote! {
+            #tokens
+            pub trait JavaMarker {}
+        }
+    }
+
+    fn render_wrapper(&self, wrapper: &Wrapper, tokens: TokenStream) -> TokenStream {
+        let rust_name = format_ident!("{}", wrapper.rust_name);
+        quote! {
+            #tokens
+            impl<'j> JavaMarker for #rust_name<'j> {}
+        }
+    }
+
+    fn render_native_class(&self, class: &NativeClass, tokens: TokenStream) -> TokenStream {
+        let class_name = &class.class;
+        quote! {
+            #tokens
+            pub const NATIVE_CLASS: &str = #class_name;
+        }
+    }
+}
+
+#[test]
+fn test_backend() {
+    let tokens = jaffi!(
+        native_classes(vec![Cow::from("net.bluejekyll.NativeBind")]),
+        classes_to_wrap(vec![Cow::from("net.bluejekyll.ParentClass")]),
+        backend(&MarkerBackend),
+        lifetime_name(Cow::from("jni")),
+    )
+    .generate_class_tokens()
+    .expect("failed to generate");
+
+    assert!(tokens.support().to_string().ends_with(
+        &quote!(
+            pub trait JavaMarker {}
+        )
+        .to_string()
+    ));
+
+    // the lifetime of the backend's code is renamed as well
+    assert!(wrapper(&tokens, "net.bluejekyll.ParentClass").ends_with(
+        &quote!(
+            impl<'jni> JavaMarker for NetBluejekyllParentClass<'jni> {}
+        )
+        .to_string()
+    ));
+    assert!(
+        native_class(&tokens, "net.bluejekyll.NativeBind").ends_with(
+            &quote!(
+                pub const NATIVE_CLASS: &str = "net/bluejekyll/NativeBind";
+            )
+            .to_string()
+        )
+    );
+}
+
+#[test]
+fn test_unchecked_exceptions() {
+    let tokens = jaffi!(
+        native_classes(vec![Cow::from("net.bluejekyll.NativeFallbacks")]),
+        classes_to_wrap(vec![Cow::from("net.bluejekyll.ParentClass")]),
+        java_fallback_impls(vec![Cow::from("net.bluejekyll.NativeFallbacks")]),
+        unchecked_exceptions(true),
+    )
+    .generate_class_tokens()
+    .expect("failed to generate");
+
+    // methods without declared exceptions also catch them
+    assert_generated(
+        &wrapper(&tokens, "net.bluejekyll.ParentClass"),
+        quote!(Exception::<'j, jaffi_support::exceptions::AnyThrowable>),
+    );
+    assert_generated(
+        &native_class(&tokens, "net.bluejekyll.NativeFallbacks"),
+        quote!(unwrap_or_else),
+    );
+}
+
+#[test]
+fn test_primitive_types() {
+    let tokens = jaffi!(
+        native_classes(vec![Cow::from("net.bluejekyll.NativePrimitives")]),
+        primitive_types(PrimitiveTypes {
+            utf16_chars: true,
+            numeric_booleans: true,
+            ..Default::default()
+        }),
+    )
+    .generate_class_tokens()
+    .expect("failed to generate");
+    let native = native_class(&tokens, "net.bluejekyll.NativePrimitives");
+
+    assert_generated(
+        &native,
+        quote!(fn echo_char(&self, this: NetBluejekyllNativePrimitives<'j>, arg0: u16) -> u16),
+    );
+    // boxed primitives are unchanged
+    assert_generated(&native, quote!(arg0: Option<bool>));
+}
+
+#[test]
+fn test_prelude_without_support() {
+    let support = jaffi!(
+        native_classes(vec![Cow::from("net.bluejekyll.Exceptions")]),
+        prelude(Prelude {
+            support: false,
+            ..Default::default()
+        }),
+    )
+    .generate_class_tokens()
+    .expect("failed to generate")
+    .support()
+    .to_string();
+
+    assert_generated(
+        &support,
+        quote!(
+            pub use super::ExceptionsRs;
+        ),
+    );
+    assert_generated(
+        &support,
+        quote!(
+            pub use jaffi_support::exceptions::IOException;
+        ),
+    );
+    // the braces of the `use` aren't balanced
+    assert!(!support.contains("pub use jaffi_support :: {"));
+}
+
+#[test]
+fn test_pending_exception_checks() {
+    let exceptions = |checks| {
+        let tokens = jaffi!(
+            native_classes(vec![]),
+            classes_to_wrap(vec![Cow::from("net.bluejekyll.Exceptions")]),
+            pending_exception_checks(checks),
+        )
+        .generate_class_tokens()
+        .expect("failed to generate");
+        wrapper(&tokens, "net.bluejekyll.Exceptions")
+    };
+
+    // the declared exception is caught, so the pending exception is returned as the `Err`
+    let always = exceptions(PendingExceptionChecks::Always);
+    assert_generated(
+        &always,
+        quote!(match jaffi_support::exceptions::check_pending_exception(env, "net.bluejekyll.Exceptions.iAlwaysThrow()V", true)),
+    );
+    assert_generated(
+        &always,
+        quote!(check_pending_exception(
+            env,
+            "net.bluejekyll.Exceptions.<init>()V",
+            false
+        )),
+    );
+
+    // the library of the integration tests is built with the debug checks
+    assert_not_generated(
+        &exceptions(PendingExceptionChecks::Never),
+        quote!(check_pending_exception),
+    );
+}
+
+#[test]
+fn test_dispatcher_order() {
+    let support = jaffi!(
+        native_classes(vec![Cow::from("net.bluejekyll.NativeDispatch")]),
+        dispatcher((
+            Cow::from("crate::Command"),
+            Cow::from("crate::handle_command")
+        )),
+    )
+    .generate_class_tokens()
+    .expect("failed to generate")
+    .support()
+    .to_string();
+
+    // the strategies are set before the handler can convert any Strings
+    assert!(
+        position(&support, quote!(string::set_strategy))
+            < position(&support, quote!(JAFFI_DISPATCHER.start))
+    );
+    assert_generated(
+        &support,
+        quote!(JAFFI_DISPATCHER.shutdown(); jaffi_support::global::shutdown();),
+    );
+}
+
+#[test]
+fn test_jni_version() {
+    let doctor = |jni_version: JniVersion| {
+        let tokens = jaffi!(
+            native_classes(vec![]),
+            classes_to_wrap(vec![Cow::from("net.bluejekyll.Doctor")]),
+            jni_version(jni_version),
+        )
+        .generate_class_tokens()
+        .expect("failed to generate");
+        (
+            tokens.support().to_string(),
+            wrapper(&tokens, "net.bluejekyll.Doctor"),
+        )
+    };
+
+    let (support, doctor_1_8) = doctor(JniVersion::V1_8);
+    assert_generated(&support, quote!(jaffi_support::jni::sys::JNI_VERSION_1_8));
+    assert_not_generated(&doctor_1_8, quote!(fn java_module));
+
+    // `GetModule` is only generated for JVMs which support it
+    let (support, doctor_9) = doctor(JniVersion::V9);
+    assert_generated(&support, quote!(jaffi_support::jni::sys::JNI_VERSION_9));
+    assert_generated(
+        &doctor_9,
+        quote!(pub fn java_module(env: JNIEnv<'j>) -> Result<JObject<'j>, JniError>),
+    );
+}
+
+#[test]
+fn test_android_panic_hook() {
+    let support = jaffi!(
+        native_classes(vec![Cow::from("net.bluejekyll.NativeDispatch")]),
+        android(true),
+    )
+    .generate_class_tokens()
+    .expect("failed to generate")
+    .support()
+    .to_string();
+
+    assert_generated(
+        &support,
+        quote!(jaffi_support::android::register_panic_hook_with(
+            vm,
+            PANIC_MAPPING,
+            env!("CARGO_PKG_NAME")
+        )),
+    );
+}
+
+#[test]
+fn test_invalid_options() {
+    let error = |jaffi: Jaffi<'_>| {
+        jaffi
+            .generate_class_tokens()
+            .err()
+            .expect("the options are invalid")
+            .to_string()
+    };
+    let native_bind = || vec![Cow::from("net.bluejekyll.NativeBind")];
+
+    assert!(error(jaffi!(
+        native_classes(native_bind()),
+        local_capacities(vec![(Cow::from("net.bluejekyll.NativeBind"), 0)]),
+    ))
+    .contains("invalid local capacity of net.bluejekyll.NativeBind: 0"));
+    assert!(error(jaffi!(
+        native_classes(native_bind()),
+        lifetime_name(Cow::from("static")),
+    ))
+    .contains("invalid lifetime_name static"));
+    assert!(error(jaffi!(
+        native_classes(native_bind()),
+        dispatcher((
+            Cow::from("crate::Command("),
+            Cow::from("crate::handle_command")
+        )),
+    ))
+    .contains("invalid dispatcher"));
+    // the crate's own JNI_OnUnload would conflict with an exported unload hook
+    assert!(error(jaffi!(
+        native_classes(native_bind()),
+        on_load_name(Cow::from("my_on_load")),
+    ))
+    .contains("requires an on_unload_name"));
+    // the declaration has no descriptor
+    assert!(error(jaffi!(
+        native_classes(vec![]),
+        late_bound_classes(vec![(
+            Cow::from("com.example.Sdk"),
+            vec![Cow::from("<init>()V"), Cow::from("version")],
+        )]),
+    ))
+    .contains("invalid late_bound_classes method version"));
+}
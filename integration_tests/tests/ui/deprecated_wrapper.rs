@@ -0,0 +1,14 @@
+#![deny(deprecated)]
+
+use jaffi_integration_tests::net_bluejekyll::{NetBluejekyllLegacy, NetBluejekyllParentClass};
+use jaffi_support::jni::JNIEnv;
+
+// the note of the method has the version of the Java annotation
+fn call_grandpa(env: JNIEnv<'_>, parent: NetBluejekyllParentClass<'_>) -> i32 {
+    parent.call_1grandpa(env, 1)
+}
+
+fn main() {
+    let _ = std::mem::size_of::<NetBluejekyllLegacy<'static>>();
+    let _ = call_grandpa;
+}
@@ -0,0 +1,58 @@
+use jaffi_integration_tests::net_bluejekyll::{
+    net::bluejekyll::NativeStrings,
+    prelude::{ExceptionsRs as _, NetBluejekyllExceptions},
+    NetBluejekyllDoctor, NetBluejekyllDocument, NetBluejekyllDraft, NetBluejekyllLedger,
+    NetBluejekyllNativeStrings, NetBluejekyllNotifierGlobal, StaticNetBluejekyllLedger,
+};
+use jaffi_support::{
+    jni::JNIEnv,
+    pool::{JavaCall, JavaPool},
+};
+
+// the classes of the package modules are aliases of the wrappers
+fn package_modules(strings: NativeStrings<'_>) -> NetBluejekyllNativeStrings<'_> {
+    strings
+}
+
+// the prelude has what the implementations of the native traits need
+fn prelude(this: NetBluejekyllExceptions<'_>) -> NetBluejekyllExceptions<'_> {
+    this
+}
+
+// the package-private methods of the Ledger, and the free function of the static one, are wrapped with package_private_classes
+fn package_private(env: JNIEnv<'_>, ledger: NetBluejekyllLedger<'_>) -> (i32, String) {
+    ledger.deposit(env, 42);
+    (ledger.balance(env), ledger.currency(env))
+}
+
+// the covariant override returns the most-derived wrapper, the bridge returns the overridden one
+fn covariant_bridge<'j>(
+    env: JNIEnv<'j>,
+    draft: NetBluejekyllDraft<'j>,
+) -> (NetBluejekyllDraft<'j>, NetBluejekyllDocument<'j>) {
+    (draft.copy(env), draft.copy_bridge(env))
+}
+
+// the default methods of the interfaces are wrapped on the class
+fn default_methods(env: JNIEnv<'_>, doctor: NetBluejekyllDoctor<'_>) -> (String, String) {
+    (doctor.title(env), doctor.greet(env, "Rose"))
+}
+
+// the arguments of the async wrappers are sent to a thread of the pool
+fn async_wrappers(
+    notifier: std::sync::Arc<NetBluejekyllNotifierGlobal>,
+    pool: &JavaPool,
+) -> JavaCall<String> {
+    notifier.fire_async(pool, "apples".to_string(), 3)
+}
+
+fn main() {
+    let _ = (
+        package_modules,
+        prelude,
+        package_private,
+        covariant_bridge,
+        default_methods,
+        async_wrappers,
+    );
+}
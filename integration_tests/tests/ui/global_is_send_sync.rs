@@ -0,0 +1,7 @@
+use jaffi_integration_tests::net_bluejekyll::NetBluejekyllNativeStringsGlobal;
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+fn main() {
+    assert_send_sync::<NetBluejekyllNativeStringsGlobal>();
+}
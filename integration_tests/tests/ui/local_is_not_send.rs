@@ -0,0 +1,7 @@
+use jaffi_integration_tests::net_bluejekyll::NetBluejekyllNativeStrings;
+
+fn assert_send<T: Send>() {}
+
+fn main() {
+    assert_send::<NetBluejekyllNativeStrings<'static>>();
+}
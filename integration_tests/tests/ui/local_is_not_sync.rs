@@ -0,0 +1,7 @@
+use jaffi_integration_tests::net_bluejekyll::NetBluejekyllNativeStrings;
+
+fn assert_sync<T: Sync>() {}
+
+fn main() {
+    assert_sync::<NetBluejekyllNativeStrings<'static>>();
+}
@@ -0,0 +1,12 @@
+use jaffi_integration_tests::net_bluejekyll::NetBluejekyllLedger;
+use jaffi_support::jni::JNIEnv;
+
+// protected and private methods are never wrapped, even with package_private_classes
+fn private_methods(env: JNIEnv<'_>, ledger: NetBluejekyllLedger<'_>) {
+    ledger.audit(env);
+    ledger.reset(env);
+}
+
+fn main() {
+    let _ = private_methods;
+}
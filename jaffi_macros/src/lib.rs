@@ -0,0 +1,185 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The `bind!` macro, generating the bindings at macro expansion time instead of in a `build.rs`
+
+#![warn(
+    clippy::default_trait_access,
+    clippy::dbg_macro,
+    clippy::print_stdout,
+    clippy::unimplemented,
+    missing_copy_implementations,
+    missing_docs,
+    non_snake_case,
+    non_upper_case_globals,
+    rust_2018_idioms,
+    unreachable_pub
+)]
+
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+};
+
+use jaffi::Jaffi;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    bracketed,
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    punctuated::Punctuated,
+    token, Ident, LitStr, Token,
+};
+
+/// The arguments of `bind!`, each is a string or a list of strings
+struct BindArgs {
+    classpath: Vec<LitStr>,
+    native: Vec<LitStr>,
+    wrap: Vec<LitStr>,
+}
+
+impl Parse for BindArgs {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let mut args = Self {
+            classpath: Vec::new(),
+            native: Vec::new(),
+            wrap: Vec::new(),
+        };
+
+        while !input.is_empty() {
+            let key = input.parse::<Ident>()?;
+            input.parse::<Token![=]>()?;
+
+            let values = if input.peek(token::Bracket) {
+                let content;
+                bracketed!(content in input);
+                Punctuated::<LitStr, Token![,]>::parse_terminated(&content)?
+                    .into_iter()
+                    .collect()
+            } else {
+                vec![input.parse::<LitStr>()?]
+            };
+
+            match key.to_string().as_str() {
+                "classpath" => args.classpath.extend(values),
+                "native" => args.native.extend(values),
+                "wrap" => args.wrap.extend(values),
+                _ => {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        "expected one of `classpath`, `native` or `wrap`",
+                    ))
+                }
+            }
+
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        if args.native.is_empty() {
+            return Err(input.error("`native` classes are required"));
+        }
+
+        Ok(args)
+    }
+}
+
+/// Expands `${VAR}` environment variables, relative paths are relative to the `CARGO_MANIFEST_DIR`
+fn resolve_path(path: &LitStr) -> syn::Result<PathBuf> {
+    let mut resolved = String::new();
+    let mut rest = path.value();
+
+    while let Some(start) = rest.find("${") {
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| syn::Error::new(path.span(), "unclosed `${`"))?;
+        let var = &rest[start + 2..start + end];
+        let value = std::env::var(var).map_err(|_| {
+            syn::Error::new(path.span(), format!("environment variable not set: {var}"))
+        })?;
+
+        resolved.push_str(&rest[..start]);
+        resolved.push_str(&value);
+        rest = rest[start + end + 1..].to_string();
+    }
+    resolved.push_str(&rest);
+
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    Ok(Path::new(&manifest_dir).join(resolved))
+}
+
+fn expand(args: BindArgs) -> syn::Result<TokenStream> {
+    let mut classpath = args
+        .classpath
+        .iter()
+        .map(resolve_path)
+        .collect::<syn::Result<Vec<_>>>()?;
+    if classpath.is_empty() {
+        classpath.push(PathBuf::from(
+            std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default(),
+        ));
+    }
+    let native = args.native.iter().map(LitStr::value).collect::<Vec<_>>();
+    let wrap = args.wrap.iter().map(LitStr::value).collect::<Vec<_>>();
+
+    let jaffi = Jaffi::builder()
+        .classpath(classpath.iter().map(|p| Cow::from(p.as_path())).collect())
+        .native_classes(native.iter().map(|c| Cow::from(c.as_str())).collect())
+        .classes_to_wrap(wrap.iter().map(|c| Cow::from(c.as_str())).collect())
+        .build();
+
+    let tokens = jaffi
+        .generate_tokens()
+        .map_err(|e| syn::Error::new(args.native[0].span(), e))?;
+
+    // the class files are included so that the bindings are regenerated when they change
+    let class_files = native
+        .iter()
+        .chain(wrap.iter())
+        .filter_map(|class| {
+            let class = Path::new(&class.replace('.', "/")).with_extension("class");
+            classpath
+                .iter()
+                .map(|dir| dir.join(&class))
+                .find(|path| path.is_file())
+        })
+        .map(|path| path.display().to_string());
+
+    Ok(quote! {
+        #(const _: &[u8] = include_bytes!(#class_files);)*
+
+        #tokens
+    })
+}
+
+/// Generates the bindings for the `native` classes in place, the same code that `Jaffi::generate` writes to a file
+///
+/// ```ignore
+/// jaffi_macros::bind!(
+///     classpath = "${OUT_DIR}/java/classes",
+///     native = ["net.bluejekyll.NativeStrings"],
+///     wrap = ["net.bluejekyll.ParentClass"],
+/// );
+/// ```
+///
+/// * `classpath` - directories with the class files, relative to the `CARGO_MANIFEST_DIR`, `${VAR}` is replaced with the
+///   environment variable, defaults to the `CARGO_MANIFEST_DIR`
+/// * `native` - classes with native methods (specified as java class names, i.e. `java.lang.Object`) to generate bindings for
+/// * `wrap` - classes that wrappers will be generated for
+///
+/// The crate needs a dependency on `jaffi_support`. The class files of the `native` and `wrap` classes are tracked, so the
+///   bindings are regenerated when they are recompiled.
+#[proc_macro]
+pub fn bind(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let args = parse_macro_input!(input as BindArgs);
+
+    expand(args)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
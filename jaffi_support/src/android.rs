@@ -0,0 +1,66 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Android support, panics are logged to logcat with `__android_log_write`, used by the `android` profile of the generator.
+//!
+//! On other targets the log is written to stderr, so that the same code can be tested on the host.
+
+use std::panic::{self, PanicHookInfo};
+
+use jni::JavaVM;
+
+use crate::exceptions::{self, PanicMapping};
+
+/// `ANDROID_LOG_ERROR` from `android/log.h`
+const ANDROID_LOG_ERROR: i32 = 6;
+
+#[cfg(target_os = "android")]
+#[link(name = "log")]
+extern "C" {
+    fn __android_log_write(
+        prio: i32,
+        tag: *const std::ffi::c_char,
+        text: *const std::ffi::c_char,
+    ) -> i32;
+}
+
+/// Writes the message to the Android log at the error priority
+pub fn log_error(tag: &str, msg: &str) {
+    write(ANDROID_LOG_ERROR, tag, msg)
+}
+
+#[cfg(target_os = "android")]
+fn write(prio: i32, tag: &str, msg: &str) {
+    use std::ffi::CString;
+
+    // interior NULs would truncate the message, replace them rather than dropping it
+    let tag = CString::new(tag.replace('\0', " ")).expect("NULs were replaced");
+    let msg = CString::new(msg.replace('\0', " ")).expect("NULs were replaced");
+
+    // Safety: both strings are valid and NUL terminated for the duration of the call
+    unsafe {
+        __android_log_write(prio, tag.as_ptr(), msg.as_ptr());
+    }
+}
+
+#[cfg(not(target_os = "android"))]
+fn write(_prio: i32, tag: &str, msg: &str) {
+    eprintln!("E/{tag}: {msg}");
+}
+
+/// See [`exceptions::register_panic_hook_with`], the panic is also logged with the `tag`
+///
+/// Panics on threads that are not attached to the JVM can't be thrown, logcat is the only place they are reported.
+pub fn register_panic_hook_with(vm: JavaVM, mapping: PanicMapping, tag: &'static str) {
+    exceptions::register_panic_hook_with(vm, mapping);
+
+    let throw_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |panic_info: &PanicHookInfo| {
+        log_error(tag, &panic_info.to_string());
+        throw_hook(panic_info);
+    }));
+}
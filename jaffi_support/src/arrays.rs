@@ -5,6 +5,11 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
+#[cfg(feature = "array_helpers")]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use core::{fmt, marker::PhantomData};
+
 use jni::objects::{AutoArray, JByteBuffer};
 
 use super::*;
@@ -62,6 +67,40 @@ impl<'j> FromRustToJava<'j, Self> for JavaByteArray<'j> {
     }
 }
 
+/// Passed through as is, e.g. to the wrappers of varargs methods
+impl<'j> FromRustToJava<'j, Self> for UnsupportedArray<'j> {
+    fn rust_to_java(rust: Self, _env: JNIEnv<'j>) -> Self {
+        rust
+    }
+}
+
+impl<'j> Deref for UnsupportedArray<'j> {
+    type Target = JObject<'j>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'j> From<JObject<'j>> for UnsupportedArray<'j> {
+    fn from(jobject: JObject<'j>) -> Self {
+        Self(jobject)
+    }
+}
+
+/// Copies the bytes out of the array, used for the `byte_vec_args` of native methods
+#[cfg(feature = "array_helpers")]
+impl<'j> FromJavaToRust<'j, JavaByteArray<'j>> for Vec<u8> {
+    fn java_to_rust(java: JavaByteArray<'j>, env: JNIEnv<'j>) -> Self {
+        if java.is_null() {
+            return Vec::new();
+        }
+
+        env.convert_byte_array(*java.0)
+            .expect("could not copy the byte array")
+    }
+}
+
 impl<'j> From<JObject<'j>> for JavaByteArray<'j> {
     fn from(jobject: JObject<'j>) -> Self {
         Self(jobject)
@@ -91,8 +130,242 @@ impl<'s: 'j, 'j> Deref for JavaByteArrayRef<'s, 'j> {
         let len = self.0.size().expect("len not available on array") as usize;
         let data = self.0.as_ptr() as *const u8;
 
-        unsafe { std::slice::from_raw_parts(data, len) }
+        unsafe { core::slice::from_raw_parts(data, len) }
+    }
+}
+
+// Object arrays, the generated wrappers of their elements need `std`
+
+/// The generated wrappers of Java objects, the elements of a [`JavaObjectArray`]
+#[cfg(feature = "std")]
+pub trait ArrayElement<'j>: From<JObject<'j>> + Into<JObject<'j>> {
+    /// The cached class of the wrapper, the element type of new arrays
+    fn class_cache() -> &'static crate::cache::ClassCache;
+}
+
+/// A single dimension array of the objects of a generated wrapper, e.g. `Foo[]` is `JavaObjectArray<'j, Foo<'j>>`
+///
+/// The elements are wrapped as they are read, `null` elements are wrappers of `null`.
+#[repr(transparent)]
+#[cfg(feature = "std")]
+pub struct JavaObjectArray<'j, T>(JObject<'j>, PhantomData<T>);
+
+#[cfg(feature = "std")]
+impl<'j, T: ArrayElement<'j>> JavaObjectArray<'j, T> {
+    /// Creates a new array containing the `elements`
+    #[cfg(feature = "array_helpers")]
+    pub fn new(
+        env: JNIEnv<'j>,
+        elements: impl IntoIterator<Item = T>,
+    ) -> Result<Self, jni::errors::Error> {
+        let elements = elements.into_iter().map(Into::into).collect::<Vec<_>>();
+        let class = T::class_cache().get(env)?;
+        let array =
+            env.new_object_array(elements.len() as jni::sys::jsize, class, JObject::null())?;
+        for (i, element) in elements.into_iter().enumerate() {
+            env.set_object_array_element(array, i as jni::sys::jsize, element)?;
+        }
+
+        Ok(Self(JObject::from(array), PhantomData))
+    }
+
+    /// The number of elements in the array
+    pub fn len(&self, env: JNIEnv<'j>) -> Result<usize, jni::errors::Error> {
+        env.get_array_length(*self.0).map(|len| len as usize)
+    }
+
+    /// Returns true if the array has no elements
+    pub fn is_empty(&self, env: JNIEnv<'j>) -> Result<bool, jni::errors::Error> {
+        self.len(env).map(|len| len == 0)
     }
+
+    /// Returns the element at the `index`, a new local reference
+    pub fn get(&self, env: JNIEnv<'j>, index: usize) -> Result<T, jni::errors::Error> {
+        env.get_object_array_element(*self.0, index as jni::sys::jsize)
+            .map(T::from)
+    }
+
+    /// Replaces the element at the `index`
+    pub fn set(&self, env: JNIEnv<'j>, index: usize, element: T) -> Result<(), jni::errors::Error> {
+        env.set_object_array_element(*self.0, index as jni::sys::jsize, element.into())
+    }
+
+    /// Returns all the elements, each is a new local reference
+    #[cfg(feature = "array_helpers")]
+    pub fn to_vec(&self, env: JNIEnv<'j>) -> Result<Vec<T>, jni::errors::Error> {
+        (0..self.len(env)?).map(|i| self.get(env, i)).collect()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'j, T> Clone for JavaObjectArray<'j, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'j, T> Copy for JavaObjectArray<'j, T> {}
+
+#[cfg(feature = "std")]
+impl<'j, T> fmt::Debug for JavaObjectArray<'j, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("JavaObjectArray").field(&self.0).finish()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'j, T: 'j> FromJavaToRust<'j, Self> for JavaObjectArray<'j, T> {
+    fn java_to_rust(java: Self, _env: JNIEnv<'j>) -> Self {
+        java
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'j, T: 'j> FromRustToJava<'j, Self> for JavaObjectArray<'j, T> {
+    fn rust_to_java(rust: Self, _env: JNIEnv<'j>) -> Self {
+        rust
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'j, T> Deref for JavaObjectArray<'j, T> {
+    type Target = JObject<'j>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'j, T> From<JObject<'j>> for JavaObjectArray<'j, T> {
+    fn from(jobject: JObject<'j>) -> Self {
+        Self(jobject, PhantomData)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'j, T> From<JavaObjectArray<'j, T>> for JObject<'j> {
+    fn from(jarray: JavaObjectArray<'j, T>) -> Self {
+        jarray.0
+    }
+}
+
+// Varargs support, the generated wrappers of varargs methods need the `array_helpers` feature
+
+/// Used by the generated code, expands to the items with the `array_helpers` feature, and to nothing without it
+///
+/// `array_helpers!(required "option")` is a compile error without the feature, for the generator options that need it, e.g.
+///   `byte_vec_args`.
+#[cfg(feature = "array_helpers")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! array_helpers {
+    (required $option:literal) => {};
+    ($($item:tt)*) => {
+        $($item)*
+    };
+}
+
+/// Used by the generated code, expands to the items with the `array_helpers` feature, and to nothing without it
+#[cfg(not(feature = "array_helpers"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! array_helpers {
+    (required $option:literal) => {
+        compile_error!(concat!(
+            "the ",
+            $option,
+            " option of the generator needs the array_helpers feature of jaffi_support"
+        ));
+    };
+    ($($item:tt)*) => {};
+}
+
+/// Primitive element types of the arrays built for Java varargs, `int...` is built from `i32`s
+#[cfg(feature = "array_helpers")]
+pub trait VarargsElement: Sized {
+    /// Creates a new Java array containing the `elements`
+    fn new_array<'j>(env: JNIEnv<'j>, elements: &[Self])
+        -> Result<JObject<'j>, jni::errors::Error>;
+}
+
+#[cfg(feature = "array_helpers")]
+macro_rules! varargs_element {
+    ($rtype: ty, $new_array: ident, $set_region: ident) => {
+        impl VarargsElement for $rtype {
+            fn new_array<'j>(
+                env: JNIEnv<'j>,
+                elements: &[Self],
+            ) -> Result<JObject<'j>, jni::errors::Error> {
+                let array = env.$new_array(elements.len() as jni::sys::jsize)?;
+                env.$set_region(array, 0, elements)?;
+                Ok(JObject::from(array))
+            }
+        }
+    };
+}
+
+#[cfg(feature = "array_helpers")]
+varargs_element!(i8, new_byte_array, set_byte_array_region);
+#[cfg(feature = "array_helpers")]
+varargs_element!(i16, new_short_array, set_short_array_region);
+#[cfg(feature = "array_helpers")]
+varargs_element!(i32, new_int_array, set_int_array_region);
+#[cfg(feature = "array_helpers")]
+varargs_element!(i64, new_long_array, set_long_array_region);
+#[cfg(feature = "array_helpers")]
+varargs_element!(f32, new_float_array, set_float_array_region);
+#[cfg(feature = "array_helpers")]
+varargs_element!(f64, new_double_array, set_double_array_region);
+
+#[cfg(feature = "array_helpers")]
+impl VarargsElement for bool {
+    fn new_array<'j>(
+        env: JNIEnv<'j>,
+        elements: &[Self],
+    ) -> Result<JObject<'j>, jni::errors::Error> {
+        let elements = elements
+            .iter()
+            .map(|b| *b as jni::sys::jboolean)
+            .collect::<Vec<_>>();
+        let array = env.new_boolean_array(elements.len() as jni::sys::jsize)?;
+        env.set_boolean_array_region(array, 0, &elements)?;
+        Ok(JObject::from(array))
+    }
+}
+
+/// See `JavaChar`, chars outside of the basic multilingual plane are converted with the `chars::CharStrategy`
+#[cfg(feature = "array_helpers")]
+impl VarargsElement for char {
+    fn new_array<'j>(
+        env: JNIEnv<'j>,
+        elements: &[Self],
+    ) -> Result<JObject<'j>, jni::errors::Error> {
+        let elements = elements
+            .iter()
+            .map(|ch| crate::chars::to_java_char(*ch))
+            .collect::<Vec<_>>();
+        let array = env.new_char_array(elements.len() as jni::sys::jsize)?;
+        env.set_char_array_region(array, 0, &elements)?;
+        Ok(JObject::from(array))
+    }
+}
+
+/// Creates a new Java array of the `class`, e.g. `java/lang/String`, containing the `elements`, for object varargs
+#[cfg(feature = "array_helpers")]
+pub fn new_object_array<'j>(
+    env: JNIEnv<'j>,
+    class: &str,
+    elements: Vec<JObject<'j>>,
+) -> Result<JObject<'j>, jni::errors::Error> {
+    let class = crate::class_loader::find_class(env, class)?;
+    let array = env.new_object_array(elements.len() as jni::sys::jsize, class, JObject::null())?;
+    for (i, element) in elements.into_iter().enumerate() {
+        env.set_object_array_element(array, i as jni::sys::jsize, element)?;
+    }
+
+    Ok(JObject::from(array))
 }
 
 // ByteBuffer support
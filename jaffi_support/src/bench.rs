@@ -0,0 +1,230 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Calls for the criterion benchmarks generated with the `bench_filename` option of the generator.
+//!
+//! The benchmarks measure the cost of crossing the boundary between Rust and Java. Each native method is called from Rust
+//!   through the JVM with a [`NativeCall`], and each wrapper of a Java method is called directly, with synthetic arguments:
+//!   zeros for the primitives, a short String and arrays of [`ARRAY_LEN`] elements. Calls that throw or panic with these
+//!   arguments are skipped, see [`try_call`].
+
+use std::{
+    ffi::c_void,
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+};
+
+use jni::{
+    objects::{JClass, JMethodID, JObject, JStaticMethodID, JValue},
+    signature::{JavaType, Primitive, TypeSignature},
+    JNIEnv, NativeMethod,
+};
+
+use crate::{exceptions::get_panic_message, harness::escape};
+
+/// The number of elements of the synthetic array arguments
+pub const ARRAY_LEN: i32 = 16;
+
+/// The synthetic String arguments
+pub const STRING: &str = "jaffi";
+
+/// The cdylib implementing the native methods
+///
+/// `System.load` called from Rust has no Java caller, so the JVM doesn't link the native methods of the classpath against the
+///   library, each [`NativeCall`] registers its export with `RegisterNatives` instead.
+pub struct Library(libloading::Library);
+
+impl Library {
+    /// Loads the library into the JVM, which runs its `JNI_OnLoad`, and opens it to look up the exports
+    pub fn load(env: JNIEnv<'_>, library: &Path) -> Result<Self, String> {
+        crate::testing::load_library(env, library)
+            .map_err(|e| format!("could not load {}: {e}", library.display()))?;
+        let library = unsafe { libloading::Library::new(library) }
+            .map_err(|e| format!("could not open {}: {e}", library.display()))?;
+        Ok(Self(library))
+    }
+
+    /// The export of the native method, by its short or long JNI name
+    fn export(&self, class: &str, name: &str, signature: &str) -> Result<*mut c_void, String> {
+        let short = format!("Java_{}_{}", escape(class), escape(name));
+        let params = signature
+            .strip_prefix('(')
+            .and_then(|d| d.split_once(')'))
+            .map_or("", |(params, _)| params);
+        let long = format!("{short}__{}", escape(params));
+
+        [&short, &long]
+            .iter()
+            .find_map(|symbol| unsafe { self.0.get::<*mut c_void>(symbol.as_bytes()).ok() })
+            .map(|symbol| *symbol)
+            .ok_or_else(|| format!("neither {short} nor {long} is exported"))
+    }
+}
+
+enum MethodId<'j> {
+    Static(JStaticMethodID<'j>),
+    Instance(JMethodID<'j>),
+}
+
+/// A native method with synthetic arguments, called through the JVM like a call from Java
+pub struct NativeCall<'j> {
+    receiver: JObject<'j>,
+    method_id: MethodId<'j>,
+    ret: JavaType,
+    args: Vec<JValue<'j>>,
+}
+
+impl<'j> NativeCall<'j> {
+    /// Looks up the method and creates its arguments, instance methods are called on an object created with the no-argument
+    ///   constructor
+    ///
+    /// The method is called once, an error is returned if it can't be called with synthetic arguments, e.g. if it takes an
+    ///   object other than a String, or if it throws.
+    ///
+    /// * `library` - the library exporting the native method
+    /// * `class` - the class descriptor, e.g. `net/bluejekyll/NativeStrings`
+    /// * `name` - the name of the native method
+    /// * `signature` - the method signature, e.g. `(Ljava/lang/String;)V`
+    /// * `is_static` - a static method, called on the class
+    pub fn new(
+        env: JNIEnv<'j>,
+        library: &Library,
+        class: &str,
+        name: &str,
+        signature: &str,
+        is_static: bool,
+    ) -> Result<Self, String> {
+        let fn_ptr = library.export(class, name, signature)?;
+        let TypeSignature { args, ret } =
+            TypeSignature::from_str(signature).map_err(|e| e.to_string())?;
+        let args = args
+            .iter()
+            .map(|arg| synthetic_arg(env, arg))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let call = try_call(env, || -> Result<Self, jni::errors::Error> {
+            let class = crate::class_loader::find_class(env, class)?;
+            env.register_native_methods(
+                class,
+                &[NativeMethod {
+                    name: name.into(),
+                    sig: signature.into(),
+                    fn_ptr,
+                }],
+            )?;
+            if is_static {
+                let method_id = env.get_static_method_id(class, name, signature)?;
+                Ok(Self {
+                    receiver: JObject::from(class),
+                    method_id: MethodId::Static(method_id),
+                    ret,
+                    args,
+                })
+            } else {
+                let method_id = env.get_method_id(class, name, signature)?;
+                let receiver = env.new_object(class, "()V", &[])?;
+                Ok(Self {
+                    receiver,
+                    method_id: MethodId::Instance(method_id),
+                    ret,
+                    args,
+                })
+            }
+        })?
+        .map_err(|e| e.to_string())?;
+
+        try_call(env, || call.invoke(env))?.map_err(|e| e.to_string())?;
+        Ok(call)
+    }
+
+    /// Calls the method, the result is discarded
+    pub fn call(&self, env: JNIEnv<'j>) {
+        if self.invoke(env).is_err() {
+            env.exception_clear().ok();
+        }
+    }
+
+    fn invoke(&self, env: JNIEnv<'j>) -> Result<(), jni::errors::Error> {
+        let result = match self.method_id {
+            MethodId::Static(method_id) => env.call_static_method_unchecked(
+                JClass::from(self.receiver),
+                method_id,
+                self.ret.clone(),
+                &self.args,
+            ),
+            MethodId::Instance(method_id) => {
+                env.call_method_unchecked(self.receiver, method_id, self.ret.clone(), &self.args)
+            }
+        }?;
+
+        if let JValue::Object(object) = result {
+            env.delete_local_ref(object)?;
+        }
+        Ok(())
+    }
+}
+
+/// The synthetic argument of the type, an error for objects other than String and arrays of primitives
+fn synthetic_arg<'j>(env: JNIEnv<'j>, ty: &JavaType) -> Result<JValue<'j>, String> {
+    let arg = match ty {
+        JavaType::Primitive(primitive) => match primitive {
+            Primitive::Boolean => JValue::Bool(jni::sys::JNI_FALSE),
+            Primitive::Byte => JValue::Byte(0),
+            Primitive::Char => JValue::Char(0),
+            Primitive::Short => JValue::Short(0),
+            Primitive::Int => JValue::Int(0),
+            Primitive::Long => JValue::Long(0),
+            Primitive::Float => JValue::Float(0.0),
+            Primitive::Double => JValue::Double(0.0),
+            Primitive::Void => return Err("void argument".to_string()),
+        },
+        JavaType::Object(class) if class == "java/lang/String" => env
+            .new_string(STRING)
+            .map(|string| JValue::Object(string.into()))
+            .map_err(|e| e.to_string())?,
+        JavaType::Array(element) => {
+            let array = match &**element {
+                JavaType::Primitive(Primitive::Boolean) => env.new_boolean_array(ARRAY_LEN),
+                JavaType::Primitive(Primitive::Byte) => env.new_byte_array(ARRAY_LEN),
+                JavaType::Primitive(Primitive::Char) => env.new_char_array(ARRAY_LEN),
+                JavaType::Primitive(Primitive::Short) => env.new_short_array(ARRAY_LEN),
+                JavaType::Primitive(Primitive::Int) => env.new_int_array(ARRAY_LEN),
+                JavaType::Primitive(Primitive::Long) => env.new_long_array(ARRAY_LEN),
+                JavaType::Primitive(Primitive::Float) => env.new_float_array(ARRAY_LEN),
+                JavaType::Primitive(Primitive::Double) => env.new_double_array(ARRAY_LEN),
+                ty => return Err(format!("unsupported argument type {ty}[]")),
+            };
+            array
+                .map(|array| JValue::Object(JObject::from(array)))
+                .map_err(|e| e.to_string())?
+        }
+        ty => return Err(format!("unsupported argument type {ty}")),
+    };
+
+    Ok(arg)
+}
+
+/// Calls `f` once, to check that the call can be benchmarked
+///
+/// A panic, or an exception left pending by `f`, is cleared and returned as the error.
+pub fn try_call<R>(env: JNIEnv<'_>, f: impl FnOnce() -> R) -> Result<R, String> {
+    let result = panic::catch_unwind(AssertUnwindSafe(f))
+        .map_err(|payload| format!("panicked: {}", get_panic_message(&*payload)));
+
+    if env.exception_check().unwrap_or(false) {
+        let exception = env.exception_occurred().map_err(|e| e.to_string())?;
+        env.exception_clear().map_err(|e| e.to_string())?;
+        let message = env
+            .call_method(exception, "toString", "()Ljava/lang/String;", &[])
+            .and_then(|message| message.l())
+            .and_then(|message| env.get_string(message.into()).map(String::from))
+            .unwrap_or_else(|e| e.to_string());
+        return Err(format!("threw {message}"));
+    }
+
+    result
+}
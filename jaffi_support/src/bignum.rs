@@ -0,0 +1,161 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! `java.math.BigInteger` and `java.math.BigDecimal`, converted to and from `num_bigint::BigInt` and `bigdecimal::BigDecimal`.
+//!
+//! Requires the `bignum` feature, the generator uses these when `bignum_conversions` is enabled in the builder.
+
+use std::ops::Deref;
+
+use jni::{
+    objects::{JObject, JValue},
+    signature::{JavaType, Primitive},
+    JNIEnv,
+};
+
+pub use bigdecimal::BigDecimal;
+pub use num_bigint::BigInt;
+
+use crate::{cache::MethodIdCache, FromJavaToRust, FromRustToJava};
+
+macro_rules! bignum_object {
+    ($(#[$doc:meta])* $jtype: ident) => {
+        $(#[$doc])*
+        #[repr(transparent)]
+        #[derive(Clone, Copy, Debug)]
+        pub struct $jtype<'j>(JObject<'j>);
+
+        impl<'j> Deref for $jtype<'j> {
+            type Target = JObject<'j>;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl<'j> From<JObject<'j>> for $jtype<'j> {
+            fn from(obj: JObject<'j>) -> Self {
+                Self(obj)
+            }
+        }
+
+        impl<'j> From<$jtype<'j>> for JObject<'j> {
+            fn from(obj: $jtype<'j>) -> Self {
+                obj.0
+            }
+        }
+    };
+}
+
+bignum_object!(
+    /// A `java.math.BigInteger`, converted to and from `BigInt`
+    JBigInteger
+);
+bignum_object!(
+    /// A `java.math.BigDecimal`, converted to and from `BigDecimal`
+    JBigDecimal
+);
+
+static TO_BYTE_ARRAY: MethodIdCache =
+    MethodIdCache::new("java/math/BigInteger", "toByteArray", "()[B");
+static UNSCALED_VALUE: MethodIdCache = MethodIdCache::new(
+    "java/math/BigDecimal",
+    "unscaledValue",
+    "()Ljava/math/BigInteger;",
+);
+static SCALE: MethodIdCache = MethodIdCache::new("java/math/BigDecimal", "scale", "()I");
+
+/// Converted through the two's-complement bytes of `toByteArray`
+impl<'j> FromJavaToRust<'j, JBigInteger<'j>> for BigInt {
+    fn java_to_rust(java: JBigInteger<'j>, env: JNIEnv<'j>) -> Self {
+        assert!(!java.is_null(), "null BigInteger passed to Rust");
+
+        let bytes = TO_BYTE_ARRAY
+            .get(env)
+            .and_then(|method| {
+                env.call_method_unchecked(
+                    java.0,
+                    method,
+                    JavaType::Array(Box::new(JavaType::Primitive(Primitive::Byte))),
+                    &[],
+                )
+            })
+            .and_then(|v| v.l())
+            .and_then(|bytes| env.convert_byte_array(bytes.into_inner()))
+            .expect("couldn't call a standard method in Java");
+
+        BigInt::from_signed_bytes_be(&bytes)
+    }
+}
+
+impl<'j> FromRustToJava<'j, BigInt> for JBigInteger<'j> {
+    fn rust_to_java(rust: BigInt, env: JNIEnv<'j>) -> Self {
+        let bytes = env
+            .byte_array_from_slice(&rust.to_signed_bytes_be())
+            .expect("couldn't allocate a byte array");
+
+        let integer = env
+            .new_object(
+                "java/math/BigInteger",
+                "([B)V",
+                &[JValue::Object(JObject::from(bytes))],
+            )
+            .expect("couldn't construct a BigInteger");
+
+        Self(integer)
+    }
+}
+
+impl<'j> FromJavaToRust<'j, JBigDecimal<'j>> for BigDecimal {
+    fn java_to_rust(java: JBigDecimal<'j>, env: JNIEnv<'j>) -> Self {
+        assert!(!java.is_null(), "null BigDecimal passed to Rust");
+
+        let unscaled = UNSCALED_VALUE
+            .get(env)
+            .and_then(|method| {
+                env.call_method_unchecked(
+                    java.0,
+                    method,
+                    JavaType::Object("java/math/BigInteger".to_string()),
+                    &[],
+                )
+            })
+            .and_then(|v| v.l())
+            .expect("couldn't call a standard method in Java");
+        let scale = SCALE
+            .get(env)
+            .and_then(|method| {
+                env.call_method_unchecked(java.0, method, JavaType::Primitive(Primitive::Int), &[])
+            })
+            .and_then(|v| v.i())
+            .expect("couldn't call a standard method in Java");
+
+        let unscaled = BigInt::java_to_rust(JBigInteger(unscaled), env);
+        BigDecimal::new(unscaled, i64::from(scale))
+    }
+}
+
+/// # Panics
+///
+/// If the scale of the decimal doesn't fit in the `int` scale of `BigDecimal`
+impl<'j> FromRustToJava<'j, BigDecimal> for JBigDecimal<'j> {
+    fn rust_to_java(rust: BigDecimal, env: JNIEnv<'j>) -> Self {
+        let (unscaled, scale) = rust.into_bigint_and_exponent();
+        let scale = i32::try_from(scale).expect("BigDecimal scale is too large for Java");
+        let unscaled = JBigInteger::rust_to_java(unscaled, env);
+
+        let decimal = env
+            .new_object(
+                "java/math/BigDecimal",
+                "(Ljava/math/BigInteger;I)V",
+                &[JValue::Object(unscaled.0), JValue::Int(scale)],
+            )
+            .expect("couldn't construct a BigDecimal");
+
+        Self(decimal)
+    }
+}
@@ -0,0 +1,190 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Boxed primitives, e.g. `java.lang.Integer`, converted to and from `Option`s of the Rust primitive, `null` is `None`.
+
+use std::ops::Deref;
+
+use jni::{
+    objects::{JObject, JValue},
+    signature::{JavaType, Primitive},
+    JNIEnv,
+};
+
+use crate::{
+    cache::{MethodIdCache, StaticMethodIdCache},
+    FromJavaToRust, FromRustToJava,
+};
+
+macro_rules! boxed_primitive {
+    (
+        $(#[$doc:meta])*
+        $jtype: ident,
+        $rtype: ty,
+        class = $class: literal,
+        value = ($value_method: literal, $value_sig: literal, $primitive: ident, $jval_func: ident),
+        value_of = $value_of_sig: literal,
+        to_rust = |$j: ident| $to_rust: expr,
+        to_java = |$r: ident| $to_java: expr,
+    ) => {
+        $(#[$doc])*
+        #[repr(transparent)]
+        #[derive(Clone, Copy, Debug)]
+        pub struct $jtype<'j>(JObject<'j>);
+
+        impl<'j> Deref for $jtype<'j> {
+            type Target = JObject<'j>;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl<'j> From<JObject<'j>> for $jtype<'j> {
+            fn from(obj: JObject<'j>) -> Self {
+                Self(obj)
+            }
+        }
+
+        impl<'j> From<$jtype<'j>> for JObject<'j> {
+            fn from(obj: $jtype<'j>) -> Self {
+                obj.0
+            }
+        }
+
+        impl<'j> FromJavaToRust<'j, $jtype<'j>> for Option<$rtype> {
+            fn java_to_rust(java: $jtype<'j>, env: JNIEnv<'j>) -> Self {
+                static VALUE: MethodIdCache = MethodIdCache::new($class, $value_method, $value_sig);
+
+                if java.is_null() {
+                    return None;
+                }
+
+                let method = VALUE.get(env).expect("couldn't find a standard method in Java");
+                let $j = env
+                    .call_method_unchecked(java.0, method, JavaType::Primitive(Primitive::$primitive), &[])
+                    .and_then(|v| v.$jval_func())
+                    .expect("couldn't call a standard method in Java");
+
+                Some($to_rust)
+            }
+        }
+
+        impl<'j> FromRustToJava<'j, Option<$rtype>> for $jtype<'j> {
+            fn rust_to_java(rust: Option<$rtype>, env: JNIEnv<'j>) -> Self {
+                static VALUE_OF: StaticMethodIdCache =
+                    StaticMethodIdCache::new($class, "valueOf", $value_of_sig);
+
+                let $r = match rust {
+                    Some(rust) => rust,
+                    None => return Self(JObject::null()),
+                };
+
+                let method = VALUE_OF.get(env).expect("couldn't find a standard method in Java");
+                let boxed = env
+                    .call_static_method_unchecked(
+                        VALUE_OF.class(),
+                        method,
+                        JavaType::Object(VALUE_OF.class().to_string()),
+                        &[$to_java],
+                    )
+                    .and_then(|v| v.l())
+                    .expect("couldn't call a standard method in Java");
+
+                Self(boxed)
+            }
+        }
+    };
+}
+
+boxed_primitive!(
+    /// A `java.lang.Boolean`
+    JBoolean,
+    bool,
+    class = "java/lang/Boolean",
+    value = ("booleanValue", "()Z", Boolean, z),
+    value_of = "(Z)Ljava/lang/Boolean;",
+    to_rust = |v| v,
+    to_java = |v| JValue::Bool(v.into()),
+);
+
+boxed_primitive!(
+    /// A `java.lang.Byte`
+    JByte,
+    i8,
+    class = "java/lang/Byte",
+    value = ("byteValue", "()B", Byte, b),
+    value_of = "(B)Ljava/lang/Byte;",
+    to_rust = |v| v,
+    to_java = |v| JValue::Byte(v),
+);
+
+boxed_primitive!(
+    /// A `java.lang.Character`, chars outside the basic multilingual plane can not be represented, see `chars::CharStrategy`
+    JCharacter,
+    char,
+    class = "java/lang/Character",
+    value = ("charValue", "()C", Char, c),
+    value_of = "(C)Ljava/lang/Character;",
+    to_rust = |v| crate::chars::to_rust_char(v),
+    to_java = |v| JValue::Char(crate::chars::to_java_char(v)),
+);
+
+boxed_primitive!(
+    /// A `java.lang.Double`
+    JDouble,
+    f64,
+    class = "java/lang/Double",
+    value = ("doubleValue", "()D", Double, d),
+    value_of = "(D)Ljava/lang/Double;",
+    to_rust = |v| v,
+    to_java = |v| JValue::Double(v),
+);
+
+boxed_primitive!(
+    /// A `java.lang.Float`
+    JFloat,
+    f32,
+    class = "java/lang/Float",
+    value = ("floatValue", "()F", Float, f),
+    value_of = "(F)Ljava/lang/Float;",
+    to_rust = |v| v,
+    to_java = |v| JValue::Float(v),
+);
+
+boxed_primitive!(
+    /// A `java.lang.Integer`
+    JInteger,
+    i32,
+    class = "java/lang/Integer",
+    value = ("intValue", "()I", Int, i),
+    value_of = "(I)Ljava/lang/Integer;",
+    to_rust = |v| v,
+    to_java = |v| JValue::Int(v),
+);
+
+boxed_primitive!(
+    /// A `java.lang.Long`
+    JLong,
+    i64,
+    class = "java/lang/Long",
+    value = ("longValue", "()J", Long, j),
+    value_of = "(J)Ljava/lang/Long;",
+    to_rust = |v| v,
+    to_java = |v| JValue::Long(v),
+);
+
+boxed_primitive!(
+    /// A `java.lang.Short`
+    JShort,
+    i16,
+    class = "java/lang/Short",
+    value = ("shortValue", "()S", Short, s),
+    value_of = "(S)Ljava/lang/Short;",
+    to_rust = |v| v,
+    to_java = |v| JValue::Short(v),
+);
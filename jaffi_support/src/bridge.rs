@@ -0,0 +1,35 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Rust domain types used in place of the wrappers of Java classes, for the `bridges` of the builder.
+
+use jni::JNIEnv;
+
+/// Converts between a Rust type and the generated wrapper `J` of a Java class
+///
+/// For each of the `bridges`, the generator implements `FromJavaToRust` and `FromRustToJava` with this, and the Rust type is used
+///   in the signatures of the native traits and the wrapper methods instead of the wrapper. The conversions are infallible like the
+///   other conversions at the boundary, a Java exception should be left pending and the conversion should panic.
+///
+/// ```ignore
+/// impl<'j> JavaBridge<'j, NetBluejekyllMoney<'j>> for Money {
+///     fn from_java(env: JNIEnv<'j>, java: NetBluejekyllMoney<'j>) -> Self {
+///         Money { cents: java.get_cents(env), currency: java.get_currency(env) }
+///     }
+///
+///     fn to_java(self, env: JNIEnv<'j>) -> NetBluejekyllMoney<'j> {
+///         NetBluejekyllMoney::new_1net_bluejekyll_money(env, self.cents, self.currency)
+///     }
+/// }
+/// ```
+pub trait JavaBridge<'j, J>: Sized {
+    /// Converts the Java object, which may be `null` if the Java method allows it, into the Rust type
+    fn from_java(env: JNIEnv<'j>, java: J) -> Self;
+
+    /// Converts the Rust type into a new Java object
+    fn to_java(self, env: JNIEnv<'j>) -> J;
+}
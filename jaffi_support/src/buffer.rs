@@ -0,0 +1,222 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Zero-copy views of direct `java.nio.ByteBuffer`s as `#[repr(C)]` Rust structs.
+//!
+//! The fields are read and written in the memory of the buffer, without a JNI call per field. The wrappers of the `buffer_classes`
+//!   of the generator get the unsafe `buffer_view` and `buffer_view_mut` for the `ByteBuffer` field of the class. Java must use the
+//!   native byte order for the buffer, `ByteBuffer.order(ByteOrder.nativeOrder())`. The views aren't tracked, so the caller must
+//!   ensure that a mutable view is the only access to the buffer while it's in use, from Rust or from Java.
+
+use core::{
+    fmt,
+    marker::PhantomData,
+    mem,
+    ops::{Deref, DerefMut},
+    ptr::NonNull,
+};
+
+use jni::{objects::JByteBuffer, JNIEnv};
+
+/// Plain data that can be laid out in a direct `ByteBuffer`
+///
+/// # Safety
+///
+/// The type must be `#[repr(C)]` or `#[repr(transparent)]`, have no padding, and be valid for any bit pattern, i.e. only contain
+///   integers, floats and arrays or other `BufferStruct`s of those. `bool` and `char` are not valid for all bit patterns.
+pub unsafe trait BufferStruct: Copy + 'static {}
+
+macro_rules! buffer_struct {
+    ($($ty: ty),*) => {
+        $(
+            unsafe impl BufferStruct for $ty {}
+        )*
+    };
+}
+
+buffer_struct!(i8, u8, i16, u16, i32, u32, i64, u64, f32, f64);
+
+unsafe impl<T: BufferStruct, const N: usize> BufferStruct for [T; N] {}
+
+/// The reasons a `ByteBuffer` can't be viewed as a struct
+#[derive(Debug)]
+pub enum BufferError {
+    /// The buffer isn't direct, e.g. it was created with `ByteBuffer.allocate` instead of `ByteBuffer.allocateDirect`
+    NotDirect,
+    /// The capacity of the buffer is smaller than the struct
+    TooSmall {
+        /// The capacity of the buffer
+        capacity: usize,
+        /// The size of the struct
+        required: usize,
+    },
+    /// The address of the buffer isn't aligned for the struct
+    Misaligned {
+        /// The address of the buffer
+        address: usize,
+        /// The alignment of the struct
+        align: usize,
+    },
+    /// A JNI call failed, e.g. the buffer is `null`
+    Jni(jni::errors::Error),
+}
+
+impl fmt::Display for BufferError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotDirect => write!(f, "the ByteBuffer is not direct"),
+            Self::TooSmall { capacity, required } => write!(
+                f,
+                "the ByteBuffer has a capacity of {capacity} bytes, {required} are required"
+            ),
+            Self::Misaligned { address, align } => write!(
+                f,
+                "the ByteBuffer address {address:#x} is not aligned to {align} bytes"
+            ),
+            Self::Jni(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl core::error::Error for BufferError {}
+
+impl From<jni::errors::Error> for BufferError {
+    fn from(e: jni::errors::Error) -> Self {
+        Self::Jni(e)
+    }
+}
+
+/// A read-only view of the struct at the start of a direct `ByteBuffer`
+pub struct BufferView<'j, T> {
+    data: NonNull<T>,
+    buffer: JByteBuffer<'j>,
+    _marker: PhantomData<&'j T>,
+}
+
+impl<'j, T: BufferStruct> BufferView<'j, T> {
+    /// The buffer the struct is in
+    pub fn buffer(&self) -> JByteBuffer<'j> {
+        self.buffer
+    }
+}
+
+impl<T> Deref for BufferView<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { self.data.as_ref() }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for BufferView<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// A mutable view of the struct at the start of a direct `ByteBuffer`, writes are visible to Java
+pub struct BufferViewMut<'j, T> {
+    data: NonNull<T>,
+    buffer: JByteBuffer<'j>,
+    _marker: PhantomData<&'j mut T>,
+}
+
+impl<'j, T: BufferStruct> BufferViewMut<'j, T> {
+    /// The buffer the struct is in
+    pub fn buffer(&self) -> JByteBuffer<'j> {
+        self.buffer
+    }
+}
+
+impl<T> Deref for BufferViewMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { self.data.as_ref() }
+    }
+}
+
+impl<T> DerefMut for BufferViewMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { self.data.as_mut() }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for BufferViewMut<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// Views the start of the direct `buffer` as a `T`
+///
+/// The buffer must be at least as large as `T`, and its address aligned for `T`, `ByteBuffer.allocateDirect` is aligned to 8 bytes
+///   on common JVMs.
+///
+/// # Safety
+///
+/// Neither Rust, through a [`BufferViewMut`] of the same buffer, nor Java may write to the memory of the buffer while the view is in use.
+pub unsafe fn view<'j, T: BufferStruct>(
+    env: JNIEnv<'j>,
+    buffer: JByteBuffer<'j>,
+) -> Result<BufferView<'j, T>, BufferError> {
+    let data = struct_ptr(env, buffer)?;
+    Ok(BufferView {
+        data,
+        buffer,
+        _marker: PhantomData,
+    })
+}
+
+/// Views the start of the direct `buffer` as a mutable `T`, see [`view`]
+///
+/// # Safety
+///
+/// There must not be other views of the buffer, mutable or not, while the mutable view is in use, and Java may neither read nor write
+///   the memory of the buffer in that time.
+pub unsafe fn view_mut<'j, T: BufferStruct>(
+    env: JNIEnv<'j>,
+    buffer: JByteBuffer<'j>,
+) -> Result<BufferViewMut<'j, T>, BufferError> {
+    let data = struct_ptr(env, buffer)?;
+    Ok(BufferViewMut {
+        data,
+        buffer,
+        _marker: PhantomData,
+    })
+}
+
+fn struct_ptr<T: BufferStruct>(
+    env: JNIEnv<'_>,
+    buffer: JByteBuffer<'_>,
+) -> Result<NonNull<T>, BufferError> {
+    if buffer.is_null() {
+        return Err(jni::errors::Error::NullPtr("buffer").into());
+    }
+
+    let bytes = match env.get_direct_buffer_address(buffer) {
+        Ok(bytes) => bytes,
+        Err(jni::errors::Error::NullPtr(_)) => return Err(BufferError::NotDirect),
+        Err(e) => return Err(e.into()),
+    };
+
+    let required = mem::size_of::<T>();
+    if bytes.len() < required {
+        return Err(BufferError::TooSmall {
+            capacity: bytes.len(),
+            required,
+        });
+    }
+
+    let address = bytes.as_mut_ptr() as usize;
+    let align = mem::align_of::<T>();
+    if !address.is_multiple_of(align) {
+        return Err(BufferError::Misaligned { address, align });
+    }
+
+    NonNull::new(bytes.as_mut_ptr().cast()).ok_or(BufferError::NotDirect)
+}
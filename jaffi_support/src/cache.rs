@@ -0,0 +1,377 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Lazily resolved caches for JNI ids and classes, generated code stores these in `static`s to skip the by-name lookups.
+
+use std::{
+    ptr,
+    sync::{
+        atomic::{AtomicPtr, Ordering},
+        Mutex,
+    },
+};
+
+use jni::{
+    objects::{JClass, JFieldID, JMethodID, JObject, JStaticFieldID, JStaticMethodID},
+    sys::{_jfieldID, _jmethodID},
+    JNIEnv,
+};
+
+use crate::global::RegisteredGlobal;
+
+/// A cached class, held as a global reference.
+///
+/// `FindClass` uses the class loader of the calling Java method, on threads attached from Rust that is the system class loader,
+///   which may not see the application classes. The generated `JNI_OnLoad` loads all the bound classes with [`load_all`], while
+///   the library's class loader is in use. The global reference is released by [`crate::global::shutdown`].
+pub struct ClassCache {
+    class: &'static str,
+    global: Mutex<Option<RegisteredGlobal>>,
+}
+
+impl ClassCache {
+    /// Create a new cache for the class, this does not perform the lookup.
+    ///
+    /// # Arguments
+    ///
+    /// * `class` - the class descriptor, e.g. `java/lang/String`
+    pub const fn new(class: &'static str) -> Self {
+        Self {
+            class,
+            global: Mutex::new(None),
+        }
+    }
+
+    /// Returns a new local reference to the cached class, looking it up if it hasn't been loaded or was released by an unload
+    pub fn get<'j>(&self, env: JNIEnv<'j>) -> Result<JClass<'j>, jni::errors::Error> {
+        if let Some(global) = &*self.lock() {
+            if let Some(local) = global.local(env)? {
+                return Ok(JClass::from(local));
+            }
+        }
+
+        // not locked during the lookup, the class initializer may call back into native code which uses this cache
+        let class = crate::class_loader::find_class(env, self.class)?;
+
+        let mut global = self.lock();
+        if !global.as_ref().is_some_and(RegisteredGlobal::is_live) {
+            // fails only after the shutdown, the class is then looked up on each call
+            *global = RegisteredGlobal::new(env, *class).ok();
+        }
+
+        Ok(class)
+    }
+
+    /// The class descriptor, e.g. `java/lang/String`
+    pub fn class(&self) -> &'static str {
+        self.class
+    }
+
+    /// Returns the `java.lang.Module` of the class, with the `GetModule` function of the JNI
+    ///
+    /// # Safety
+    ///
+    /// `GetModule` was added in `JNI_VERSION_9`, the library must request that or a newer version from `JNI_OnLoad`. The
+    ///   generated code only calls this when the `jni_version` of the builder is `JniVersion::V9` or newer.
+    pub unsafe fn module<'j>(&self, env: JNIEnv<'j>) -> Result<JObject<'j>, jni::errors::Error> {
+        let class = self.get(env)?;
+        let raw_env = env.get_native_interface();
+        let get_module = (**raw_env)
+            .GetModule
+            .ok_or(jni::errors::Error::JNIEnvMethodNotFound("GetModule"))?;
+        let module = get_module(raw_env, class.into_inner());
+        env.delete_local_ref(*class)?;
+
+        if module.is_null() {
+            return Err(jni::errors::Error::NullPtr("GetModule"));
+        }
+        Ok(JObject::from(module))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<RegisteredGlobal>> {
+        self.global
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Loads all the classes into their caches, classes which can't be found are left to be looked up on first use
+pub fn load_all(env: JNIEnv<'_>, caches: &[&ClassCache]) {
+    for cache in caches {
+        match cache.get(env) {
+            Ok(class) => {
+                env.delete_local_ref(*class).ok();
+            }
+            Err(_) => {
+                env.exception_clear().ok();
+            }
+        }
+    }
+}
+
+/// A cached method id for an instance method.
+///
+/// Method ids remain valid for as long as the declaring class is loaded. Racing lookups are benign, each will resolve to the same id.
+pub struct MethodIdCache {
+    class: &'static str,
+    name: &'static str,
+    signature: &'static str,
+    id: AtomicPtr<_jmethodID>,
+}
+
+impl MethodIdCache {
+    /// Create a new cache for the method, this does not perform the lookup.
+    ///
+    /// # Arguments
+    ///
+    /// * `class` - the class descriptor, e.g. `java/lang/Enum`
+    /// * `name` - the name of the method, e.g. `ordinal`
+    /// * `signature` - the method signature, e.g. `()I`
+    pub const fn new(class: &'static str, name: &'static str, signature: &'static str) -> Self {
+        Self {
+            class,
+            name,
+            signature,
+            id: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    /// Returns the cached method id, looking it up on first use
+    pub fn get<'j>(&self, env: JNIEnv<'j>) -> Result<JMethodID<'j>, jni::errors::Error> {
+        let id = self.id.load(Ordering::Acquire);
+        if !id.is_null() {
+            return Ok(JMethodID::from(id));
+        }
+
+        let class = crate::class_loader::find_class(env, self.class)?;
+        let id = env.get_method_id(class, self.name, self.signature);
+        env.delete_local_ref(*class)?;
+        let id = id?;
+        self.id.store(id.into_inner(), Ordering::Release);
+        Ok(id)
+    }
+
+    /// Returns the cached method id, looking it up in the cached `class` on first use
+    pub fn get_in<'j>(
+        &self,
+        env: JNIEnv<'j>,
+        class: &ClassCache,
+    ) -> Result<JMethodID<'j>, jni::errors::Error> {
+        let id = self.id.load(Ordering::Acquire);
+        if !id.is_null() {
+            return Ok(JMethodID::from(id));
+        }
+
+        let class = class.get(env)?;
+        let id = env.get_method_id(class, self.name, self.signature);
+        env.delete_local_ref(*class)?;
+        let id = id?;
+        self.id.store(id.into_inner(), Ordering::Release);
+        Ok(id)
+    }
+
+    /// The method signature, e.g. `()I`
+    pub fn signature(&self) -> &'static str {
+        self.signature
+    }
+}
+
+/// A cached method id for a static method.
+///
+/// See [`MethodIdCache`] for the validity of the cached id.
+pub struct StaticMethodIdCache {
+    class: &'static str,
+    name: &'static str,
+    signature: &'static str,
+    id: AtomicPtr<_jmethodID>,
+}
+
+impl StaticMethodIdCache {
+    /// Create a new cache for the static method, this does not perform the lookup.
+    ///
+    /// # Arguments
+    ///
+    /// * `class` - the class descriptor, e.g. `java/lang/String`
+    /// * `name` - the name of the method, e.g. `valueOf`
+    /// * `signature` - the method signature, e.g. `(I)Ljava/lang/String;`
+    pub const fn new(class: &'static str, name: &'static str, signature: &'static str) -> Self {
+        Self {
+            class,
+            name,
+            signature,
+            id: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    /// Returns the cached method id, looking it up on first use
+    pub fn get<'j>(&self, env: JNIEnv<'j>) -> Result<JStaticMethodID<'j>, jni::errors::Error> {
+        let id = self.id.load(Ordering::Acquire);
+        if !id.is_null() {
+            return Ok(JStaticMethodID::from(id));
+        }
+
+        let class = crate::class_loader::find_class(env, self.class)?;
+        let id = env.get_static_method_id(class, self.name, self.signature);
+        env.delete_local_ref(*class)?;
+        let id = id?;
+        self.id.store(id.into_inner(), Ordering::Release);
+        Ok(id)
+    }
+
+    /// Returns the cached method id, looking it up in the cached `class` on first use
+    pub fn get_in<'j>(
+        &self,
+        env: JNIEnv<'j>,
+        class: &ClassCache,
+    ) -> Result<JStaticMethodID<'j>, jni::errors::Error> {
+        let id = self.id.load(Ordering::Acquire);
+        if !id.is_null() {
+            return Ok(JStaticMethodID::from(id));
+        }
+
+        let class = class.get(env)?;
+        let id = env.get_static_method_id(class, self.name, self.signature);
+        env.delete_local_ref(*class)?;
+        let id = id?;
+        self.id.store(id.into_inner(), Ordering::Release);
+        Ok(id)
+    }
+
+    /// The class descriptor which declares the method
+    pub fn class(&self) -> &'static str {
+        self.class
+    }
+
+    /// The method signature, e.g. `(I)Ljava/lang/String;`
+    pub fn signature(&self) -> &'static str {
+        self.signature
+    }
+}
+
+/// A cached field id for an instance field, for the generated field accessors.
+///
+/// See [`MethodIdCache`] for the validity of the cached id.
+pub struct FieldIdCache {
+    class: &'static str,
+    name: &'static str,
+    signature: &'static str,
+    id: AtomicPtr<_jfieldID>,
+}
+
+impl FieldIdCache {
+    /// Create a new cache for the field, this does not perform the lookup.
+    ///
+    /// # Arguments
+    ///
+    /// * `class` - the class descriptor which declares the field, e.g. `net/bluejekyll/Particle`
+    /// * `name` - the name of the field, e.g. `buffer`
+    /// * `signature` - the type signature of the field, e.g. `Ljava/nio/ByteBuffer;`
+    pub const fn new(class: &'static str, name: &'static str, signature: &'static str) -> Self {
+        Self {
+            class,
+            name,
+            signature,
+            id: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    /// Returns the cached field id, looking it up on first use
+    pub fn get<'j>(&self, env: JNIEnv<'j>) -> Result<JFieldID<'j>, jni::errors::Error> {
+        let id = self.id.load(Ordering::Acquire);
+        if !id.is_null() {
+            return Ok(JFieldID::from(id));
+        }
+
+        let class = crate::class_loader::find_class(env, self.class)?;
+        let id = env.get_field_id(class, self.name, self.signature);
+        env.delete_local_ref(*class)?;
+        let id = id?;
+        self.id.store(id.into_inner(), Ordering::Release);
+        Ok(id)
+    }
+
+    /// Returns the cached field id, looking it up in the cached `class` on first use
+    pub fn get_in<'j>(
+        &self,
+        env: JNIEnv<'j>,
+        class: &ClassCache,
+    ) -> Result<JFieldID<'j>, jni::errors::Error> {
+        let id = self.id.load(Ordering::Acquire);
+        if !id.is_null() {
+            return Ok(JFieldID::from(id));
+        }
+
+        let class = class.get(env)?;
+        let id = env.get_field_id(class, self.name, self.signature);
+        env.delete_local_ref(*class)?;
+        let id = id?;
+        self.id.store(id.into_inner(), Ordering::Release);
+        Ok(id)
+    }
+
+    /// The type signature of the field, e.g. `Ljava/nio/ByteBuffer;`
+    pub fn signature(&self) -> &'static str {
+        self.signature
+    }
+}
+
+/// A cached field id for a static field, e.g. the constants of an enum.
+///
+/// See [`MethodIdCache`] for the validity of the cached id.
+pub struct StaticFieldIdCache {
+    class: &'static str,
+    name: &'static str,
+    signature: &'static str,
+    id: AtomicPtr<_jfieldID>,
+}
+
+impl StaticFieldIdCache {
+    /// Create a new cache for the static field, this does not perform the lookup.
+    ///
+    /// # Arguments
+    ///
+    /// * `class` - the class descriptor which declares the field, e.g. `net/bluejekyll/Fruit`
+    /// * `name` - the name of the field, e.g. `APPLE`
+    /// * `signature` - the type signature of the field, e.g. `Lnet/bluejekyll/Fruit;`
+    pub const fn new(class: &'static str, name: &'static str, signature: &'static str) -> Self {
+        Self {
+            class,
+            name,
+            signature,
+            id: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    /// Returns the cached field id, looking it up in the cached `class` on first use
+    pub fn get_in<'j>(
+        &self,
+        env: JNIEnv<'j>,
+        class: &ClassCache,
+    ) -> Result<JStaticFieldID<'j>, jni::errors::Error> {
+        let id = self.id.load(Ordering::Acquire);
+        if !id.is_null() {
+            return Ok(JStaticFieldID::from(id));
+        }
+
+        let class = class.get(env)?;
+        let id = env.get_static_field_id(class, self.name, self.signature);
+        env.delete_local_ref(*class)?;
+        let id = id?;
+        self.id.store(id.into_inner(), Ordering::Release);
+        Ok(id)
+    }
+
+    /// The class descriptor which declares the field
+    pub fn class(&self) -> &'static str {
+        self.class
+    }
+
+    /// The type signature of the field, e.g. `Lnet/bluejekyll/Fruit;`
+    pub fn signature(&self) -> &'static str {
+        self.signature
+    }
+}
@@ -0,0 +1,171 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Cancellation of long-running Rust computations from Java, e.g. background tasks on Android.
+//!
+//! A [`Cancellation`] is polled by the computation, with [`Cancellation::is_cancelled`] or [`Cancellation::check`], or awaited
+//!   with [`Cancellation::cancelled`] in a future. Java refers to it by a handle, a `long` from [`Cancellation::register`],
+//!   and requests the cancellation with [`cancel`]. With the `cancel_handles` option of the generator, a
+//!   `static native boolean cancel(long handle)` or `static native void cancel(long handle)` of the class calls [`cancel`] by
+//!   default. The computation [`release`]s the handle when it's done, later cancellations of the handle do nothing.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, AtomicI64, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Waker},
+};
+
+static NEXT_HANDLE: AtomicI64 = AtomicI64::new(1);
+static HANDLES: Mutex<Option<HashMap<i64, Cancellation>>> = Mutex::new(None);
+
+/// A token to request the cancellation of a computation, the clones share the cancellation
+#[derive(Clone, Default)]
+pub struct Cancellation {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    wakers: Mutex<Vec<Waker>>,
+}
+
+impl Cancellation {
+    /// A token that isn't cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests the cancellation, and wakes the futures of [`Self::cancelled`]
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+
+        let wakers = std::mem::take(
+            &mut *self
+                .inner
+                .wakers
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        );
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    /// `true` once the cancellation has been requested
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// An error once the cancellation has been requested, to return early with `?`
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// A future that completes once the cancellation has been requested, e.g. to select on with the computation
+    pub fn cancelled(&self) -> WaitCancelled {
+        WaitCancelled(self.clone())
+    }
+
+    /// Registers the token, and returns the handle for Java to cancel it with [`cancel`]
+    ///
+    /// The token stays registered until it's [`release`]d.
+    pub fn register(&self) -> i64 {
+        let handle = NEXT_HANDLE.fetch_add(1, Ordering::Relaxed);
+        handles(|handles| handles.insert(handle, self.clone()));
+        handle
+    }
+}
+
+impl fmt::Debug for Cancellation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cancellation")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+fn handles<R>(f: impl FnOnce(&mut HashMap<i64, Cancellation>) -> R) -> R {
+    let mut handles = HANDLES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(handles.get_or_insert_with(HashMap::new))
+}
+
+/// Cancels the token of the handle, `false` if the handle isn't registered, e.g. it was already released
+pub fn cancel(handle: i64) -> bool {
+    match get(handle) {
+        Some(cancellation) => {
+            cancellation.cancel();
+            true
+        }
+        None => false,
+    }
+}
+
+/// The token of the handle, `None` if it isn't registered
+pub fn get(handle: i64) -> Option<Cancellation> {
+    handles(|handles| handles.get(&handle).cloned())
+}
+
+/// Unregisters the handle, and returns its token
+pub fn release(handle: i64) -> Option<Cancellation> {
+    handles(|handles| handles.remove(&handle))
+}
+
+/// The error of [`Cancellation::check`], the computation was cancelled
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the computation was cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// The future of [`Cancellation::cancelled`]
+#[derive(Debug)]
+pub struct WaitCancelled(Cancellation);
+
+impl Future for WaitCancelled {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0.is_cancelled() {
+            return Poll::Ready(());
+        }
+
+        let mut wakers = self
+            .0
+            .inner
+            .wakers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        // checked again with the lock held, `cancel` takes the wakers after setting the flag
+        if self.0.is_cancelled() {
+            return Poll::Ready(());
+        }
+        if !wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+            wakers.push(cx.waker().clone());
+        }
+
+        Poll::Pending
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Conversions between Java chars and Rust chars.
+//!
+//! A Java `char` is a UTF-16 code unit, a surrogate is not a valid Rust `char`, and a Rust `char` outside of the basic
+//!   multilingual plane doesn't fit in a Java `char`. The strategy is process wide, the generated `JNI_OnLoad` sets the one
+//!   configured in the builder.
+
+use core::{
+    fmt,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+use jni::sys::jchar;
+
+/// The strategy used to convert chars that can't be represented on the other side
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[repr(u8)]
+pub enum CharStrategy {
+    /// Surrogates and chars outside of the basic multilingual plane are replaced with `U+FFFD`
+    #[default]
+    Lossy = 0,
+    /// Panics on surrogates and chars outside of the basic multilingual plane, thrown to Java from native methods
+    Checked = 1,
+}
+
+static STRATEGY: AtomicU8 = AtomicU8::new(CharStrategy::Lossy as u8);
+
+impl CharStrategy {
+    fn from_u8(strategy: u8) -> Self {
+        match strategy {
+            1 => Self::Checked,
+            _ => Self::Lossy,
+        }
+    }
+}
+
+/// Returns the strategy used when converting chars
+pub fn strategy() -> CharStrategy {
+    CharStrategy::from_u8(STRATEGY.load(Ordering::Relaxed))
+}
+
+/// Sets the strategy used when converting chars, this is called from the generated `JNI_OnLoad`
+pub fn set_strategy(strategy: CharStrategy) {
+    STRATEGY.store(strategy as u8, Ordering::Relaxed)
+}
+
+/// A char that can't be converted
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CharError {
+    /// The Java char is half of a surrogate pair
+    Surrogate(jchar),
+    /// The Rust char is outside of the basic multilingual plane
+    OutsideBmp(char),
+}
+
+impl fmt::Display for CharError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Surrogate(ch) => write!(f, "the Java char {ch:#06x} is a surrogate"),
+            Self::OutsideBmp(ch) => write!(
+                f,
+                "the char {:?} ({:#x}) is outside of the basic multilingual plane",
+                ch, *ch as u32
+            ),
+        }
+    }
+}
+
+impl core::error::Error for CharError {}
+
+/// Converts the Java char, an error if it's a surrogate
+pub fn try_to_rust_char(ch: jchar) -> Result<char, CharError> {
+    char::from_u32(u32::from(ch)).ok_or(CharError::Surrogate(ch))
+}
+
+/// Converts the Rust char, an error if it's outside of the basic multilingual plane
+pub fn try_to_java_char(ch: char) -> Result<jchar, CharError> {
+    jchar::try_from(u32::from(ch)).map_err(|_| CharError::OutsideBmp(ch))
+}
+
+/// Converts the Java char with the current [`strategy`]
+///
+/// # Panics
+///
+/// If the char is a surrogate and the strategy is `CharStrategy::Checked`
+pub fn to_rust_char(ch: jchar) -> char {
+    match strategy() {
+        CharStrategy::Lossy => try_to_rust_char(ch).unwrap_or(char::REPLACEMENT_CHARACTER),
+        CharStrategy::Checked => try_to_rust_char(ch).unwrap_or_else(|e| panic!("{e}")),
+    }
+}
+
+/// Converts the Rust char with the current [`strategy`]
+///
+/// # Panics
+///
+/// If the char is outside of the basic multilingual plane and the strategy is `CharStrategy::Checked`
+pub fn to_java_char(ch: char) -> jchar {
+    match strategy() {
+        CharStrategy::Lossy => try_to_java_char(ch).unwrap_or(char::REPLACEMENT_CHARACTER as jchar),
+        CharStrategy::Checked => try_to_java_char(ch).unwrap_or_else(|e| panic!("{e}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_try_conversions() {
+        assert_eq!(try_to_rust_char(0x41), Ok('A'));
+        assert_eq!(try_to_rust_char(0xd83d), Err(CharError::Surrogate(0xd83d)));
+        assert_eq!(try_to_java_char('é'), Ok(0xe9));
+        assert_eq!(try_to_java_char('🦀'), Err(CharError::OutsideBmp('🦀')));
+    }
+
+    #[test]
+    fn test_lossy() {
+        // the default, the strategy isn't changed by the tests
+        assert_eq!(to_rust_char(0xdc00), char::REPLACEMENT_CHARACTER);
+        assert_eq!(to_java_char('🦀'), 0xfffd);
+        assert_eq!(to_java_char('A'), 0x41);
+    }
+}
@@ -0,0 +1,99 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The class loader of the application, for looking up classes from threads attached from Rust.
+//!
+//! `FindClass` uses the class loader of the Java method on top of the stack. Threads attached from Rust have no Java frames, so it
+//!   falls back to the system class loader, which doesn't know the application classes on Android. The generated `JNI_OnLoad`
+//!   captures the loader of the native classes with [`capture`], and all the classes of the generated code are then looked up
+//!   with [`find_class`].
+//!
+//! Without the `std` feature no loader is captured, the classes are always looked up with `FindClass`.
+
+#[cfg(feature = "std")]
+use std::sync::{Mutex, MutexGuard};
+
+#[cfg(feature = "std")]
+use jni::objects::{JObject, JValue};
+use jni::{objects::JClass, JNIEnv};
+
+#[cfg(feature = "std")]
+use crate::global::RegisteredGlobal;
+
+/// The captured loader, released by [`crate::global::shutdown`]
+#[cfg(feature = "std")]
+static LOADER: Mutex<Option<RegisteredGlobal>> = Mutex::new(None);
+
+#[cfg(feature = "std")]
+fn loader() -> MutexGuard<'static, Option<RegisteredGlobal>> {
+    LOADER
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Captures the class loader that loaded the `class`, e.g. `net/bluejekyll/NativeStrings`, replacing any earlier loader
+///
+/// This must be called from a Java thread that can find the class, e.g. in `JNI_OnLoad`.
+#[cfg(feature = "std")]
+pub fn capture(env: JNIEnv<'_>, class: &str) -> Result<(), jni::errors::Error> {
+    let class = env.find_class(class)?;
+    let loader = env
+        .call_method(class, "getClassLoader", "()Ljava/lang/ClassLoader;", &[])?
+        .l()?;
+    env.delete_local_ref(*class)?;
+
+    set(env, loader)
+}
+
+/// Sets the class loader used by [`find_class`], e.g. the `getClassLoader()` of an Android `Context`
+///
+/// A `null` loader, that of the bootstrap classes, resets the lookups to `FindClass`.
+#[cfg(feature = "std")]
+pub fn set(env: JNIEnv<'_>, loader: JObject<'_>) -> Result<(), jni::errors::Error> {
+    let loader = if loader.is_null() {
+        None
+    } else {
+        Some(RegisteredGlobal::new(env, loader)?)
+    };
+
+    *self::loader() = loader;
+    Ok(())
+}
+
+/// Looks up the class, e.g. `java/lang/String`, with the captured class loader, or with `FindClass` if none was captured
+///
+/// Array classes, e.g. `[I`, are always looked up with `FindClass`.
+#[cfg(feature = "std")]
+pub fn find_class<'j>(env: JNIEnv<'j>, class: &str) -> Result<JClass<'j>, jni::errors::Error> {
+    // not locked during the lookup, the class initializer may call back into native code
+    let loader = match &*loader() {
+        Some(global) if !class.starts_with('[') => global.local(env)?,
+        _ => None,
+    };
+    let loader = match loader {
+        Some(loader) => loader,
+        None => return env.find_class(class),
+    };
+
+    let name = env.new_string(class.replace('/', "."))?;
+    let found = env.call_method(
+        loader,
+        "loadClass",
+        "(Ljava/lang/String;)Ljava/lang/Class;",
+        &[JValue::Object(*name)],
+    );
+    env.delete_local_ref(*name)?;
+    env.delete_local_ref(loader)?;
+
+    found?.l().map(JClass::from)
+}
+
+/// See [`find_class`], the class is looked up with `FindClass` without the `std` feature
+#[cfg(not(feature = "std"))]
+pub fn find_class<'j>(env: JNIEnv<'j>, class: &str) -> Result<JClass<'j>, jni::errors::Error> {
+    env.find_class(class)
+}
@@ -0,0 +1,127 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The classes bound by the generated code, with their superclass, interfaces and methods, for debugging and tools.
+//!
+//! With the `class_registry` option of the generator, the bindings have a static `JAFFI_REGISTRY` table of [`BoundClass`]es,
+//!   which the generated `JNI_OnLoad` passes to [`register`], and `JNI_OnUnload` to [`unregister`]. [`registry`] returns the
+//!   classes of all the loaded libraries. Tests that don't load the library register the table themselves.
+//!
+//! ```
+//! use jaffi_support::class_registry::{self, BoundClass, BoundMethod};
+//!
+//! static CLASSES: &[BoundClass] = &[BoundClass {
+//!     name: "net/bluejekyll/Draft",
+//!     super_class: Some("net/bluejekyll/Document"),
+//!     interfaces: &[],
+//!     methods: &[BoundMethod {
+//!         name: "copy",
+//!         signature: "()Lnet/bluejekyll/Draft;",
+//!         rust_name: "copy",
+//!         is_static: false,
+//!         is_native: false,
+//!     }],
+//! }];
+//!
+//! class_registry::register(CLASSES);
+//! let draft = class_registry::find("net/bluejekyll/Draft").expect("not registered");
+//! assert!(draft.method("copy", "()Lnet/bluejekyll/Draft;").is_some());
+//! class_registry::unregister(CLASSES);
+//! ```
+
+use std::{
+    ptr,
+    sync::{Mutex, MutexGuard},
+};
+
+/// A class bound by the generated code
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BoundClass {
+    /// The class descriptor, e.g. `net/bluejekyll/Draft`
+    pub name: &'static str,
+    /// The class descriptor of the superclass, `java/lang/Object` for interfaces, `None` only for `java/lang/Object` itself
+    pub super_class: Option<&'static str>,
+    /// The class descriptors of the interfaces the class implements, or the interface extends, as declared
+    pub interfaces: &'static [&'static str],
+    /// The bound methods, the wrapper methods and the native methods
+    pub methods: &'static [BoundMethod],
+}
+
+impl BoundClass {
+    /// The bound method with the name and signature
+    pub fn method(&self, name: &str, signature: &str) -> Option<&'static BoundMethod> {
+        self.methods
+            .iter()
+            .find(|method| method.name == name && method.signature == signature)
+    }
+}
+
+/// A method bound by the generated code
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BoundMethod {
+    /// The name in Java, `<init>` for constructors
+    pub name: &'static str,
+    /// The method signature, e.g. `(I)Ljava/lang/String;`
+    pub signature: &'static str,
+    /// The name of the Rust method, of the wrapper or the native trait
+    pub rust_name: &'static str,
+    /// A `static` method
+    pub is_static: bool,
+    /// A `native` method, implemented in Rust
+    pub is_native: bool,
+}
+
+static TABLES: Mutex<Vec<&'static [BoundClass]>> = Mutex::new(Vec::new());
+
+fn tables() -> MutexGuard<'static, Vec<&'static [BoundClass]>> {
+    // the tables are only pushed and removed, a panic can't leave them in a bad state
+    TABLES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Adds the table of the bindings of a library, registering the same table again has no effect
+pub fn register(classes: &'static [BoundClass]) {
+    let mut tables = tables();
+    if !tables.iter().any(|table| ptr::eq(*table, classes)) {
+        tables.push(classes);
+    }
+}
+
+/// Removes the table, before the library with the static is unloaded
+pub fn unregister(classes: &'static [BoundClass]) {
+    tables().retain(|table| !ptr::eq(*table, classes));
+}
+
+/// The classes of all the registered tables, in the order they were registered
+pub fn registry() -> Vec<&'static BoundClass> {
+    tables().iter().flat_map(|table| table.iter()).collect()
+}
+
+/// The registered class with the descriptor, e.g. `net/bluejekyll/Draft`, the first if more than one library binds it
+pub fn find(class: &str) -> Option<&'static BoundClass> {
+    tables()
+        .iter()
+        .flat_map(|table| table.iter())
+        .find(|bound| bound.name == class)
+}
+
+/// The registered superclasses of the class, nearest first, up to the first that isn't registered
+pub fn super_classes(class: &str) -> Vec<&'static BoundClass> {
+    let mut super_classes = Vec::<&'static BoundClass>::new();
+    let mut next = find(class).and_then(|bound| bound.super_class);
+    while let Some(bound) = next.and_then(find) {
+        // a cycle can only come from a hand-written table
+        if super_classes.iter().any(|seen| ptr::eq(*seen, bound)) {
+            break;
+        }
+        super_classes.push(bound);
+        next = bound.super_class;
+    }
+
+    super_classes
+}
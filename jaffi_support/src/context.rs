@@ -0,0 +1,59 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The context of a call to a native method, passed to the implementations of the classes in `context_impls` in the builder.
+
+use std::ops::Deref;
+
+use jni::{errors::Error, objects::JString, strings::JNIString, JNIEnv};
+
+/// The `JNIEnv` and the object, or class for static methods, that a native method was called on
+///
+/// This derefs to the `JNIEnv`, so all of its methods are available.
+#[derive(Clone, Copy)]
+pub struct CallCtx<'j, T> {
+    env: JNIEnv<'j>,
+    this: T,
+}
+
+impl<'j, T: Copy> CallCtx<'j, T> {
+    /// Used by the generated native methods
+    pub fn new(env: JNIEnv<'j>, this: T) -> Self {
+        Self { env, this }
+    }
+
+    /// The environment of the call
+    pub fn env(&self) -> JNIEnv<'j> {
+        self.env
+    }
+
+    /// The object the method was called on, or the class for static methods
+    pub fn this(&self) -> T {
+        self.this
+    }
+
+    /// Throws a new exception of the class, e.g. `java/lang/IllegalArgumentException`, with the message
+    ///
+    /// The exception is thrown when the native method returns, so return right after this, the returned value is ignored by Java.
+    pub fn throw(&self, class: &str, msg: impl Into<JNIString>) -> Result<(), Error> {
+        crate::class_loader::find_class(self.env, class)
+            .and_then(|class| self.env.throw_new(class, msg))
+    }
+
+    /// Creates a new Java String
+    pub fn new_string(&self, string: impl Into<JNIString>) -> Result<JString<'j>, Error> {
+        self.env.new_string(string)
+    }
+}
+
+impl<'j, T> Deref for CallCtx<'j, T> {
+    type Target = JNIEnv<'j>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.env
+    }
+}
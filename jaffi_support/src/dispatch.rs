@@ -0,0 +1,205 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Typed commands from native methods, handled in order on a worker thread attached to the JVM.
+//!
+//! A native method that starts work outliving the call sends a command with [`Dispatcher::send`] and returns to Java. The
+//!   handler runs on the worker with its own `JNIEnv`, so the command holds owned Rust values and the `register_global`
+//!   references of the Java objects, e.g. a callback interface, which the handler turns back into wrappers with `local` to post
+//!   the result to Java. The `dispatcher` option of the generator starts a [`Dispatcher`] in `JNI_OnLoad`, shuts it down in
+//!   `JNI_OnUnload`, and generates a `dispatch` function for sending the commands.
+//!
+//! ```no_run
+//! # use jaffi_support::{dispatch::Dispatcher, jni::JNIEnv};
+//! enum Command {
+//!     Log(String),
+//! }
+//!
+//! static DISPATCHER: Dispatcher<Command> = Dispatcher::new();
+//!
+//! fn on_load(env: JNIEnv<'_>) {
+//!     DISPATCHER
+//!         .start(env, |_env, command| match command {
+//!             Command::Log(message) => println!("{message}"),
+//!         })
+//!         .expect("no dispatcher");
+//! }
+//!
+//! fn from_native_method() {
+//!     DISPATCHER
+//!         .send(Command::Log("sent from Java".to_string()))
+//!         .expect("not started");
+//! }
+//! ```
+
+use std::{
+    fmt, io,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        mpsc::{self, SendError},
+        Mutex, MutexGuard,
+    },
+    thread::{self, JoinHandle},
+};
+
+use jni::{objects::JObject, JNIEnv, JavaVM};
+
+use crate::{exceptions::get_panic_message, frame::DEFAULT_CAPACITY};
+
+struct Worker<C> {
+    sender: mpsc::Sender<C>,
+    thread: JoinHandle<()>,
+}
+
+/// A queue of commands, handled in the order they were sent by the handler on the worker thread
+///
+/// The dispatcher is created stopped, so that it can be a `static`, and [`start`](Self::start)ed with the handler. Each command
+///   is handled in its own local frame, the local references the handler creates are released after it. A panic or an exception
+///   in the handler is reported to stderr, and the worker continues with the next command.
+pub struct Dispatcher<C> {
+    worker: Mutex<Option<Worker<C>>>,
+}
+
+impl<C: Send + 'static> Dispatcher<C> {
+    /// A stopped dispatcher, commands can't be sent until it's started
+    pub const fn new() -> Self {
+        Self {
+            worker: Mutex::new(None),
+        }
+    }
+
+    fn worker(&self) -> MutexGuard<'_, Option<Worker<C>>> {
+        // the worker is only replaced under the lock, a panic can't leave it in a bad state
+        self.worker
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Starts the worker thread, attached to the JVM of the env as a daemon, which calls `handler` with each command
+    ///
+    /// A dispatcher that's running is shut down first, after its queued commands are handled.
+    pub fn start<H>(&self, env: JNIEnv<'_>, handler: H) -> Result<(), DispatchError>
+    where
+        H: for<'j> FnMut(JNIEnv<'j>, C) + Send + 'static,
+    {
+        let vm = env.get_java_vm().map_err(DispatchError::Jni)?;
+        self.shutdown();
+
+        let (sender, receiver) = mpsc::channel();
+        let thread = thread::Builder::new()
+            .name("jaffi-dispatcher".to_string())
+            .spawn(move || run(&vm, &receiver, handler))
+            .map_err(DispatchError::Spawn)?;
+
+        *self.worker() = Some(Worker { sender, thread });
+        Ok(())
+    }
+
+    /// Queues the command for the handler, the command is returned in the error if the dispatcher isn't running
+    pub fn send(&self, command: C) -> Result<(), SendError<C>> {
+        match &*self.worker() {
+            Some(worker) => worker.sender.send(command),
+            None => Err(SendError(command)),
+        }
+    }
+
+    /// `true` if the dispatcher was started and hasn't been shut down
+    pub fn is_running(&self) -> bool {
+        self.worker().is_some()
+    }
+
+    /// Stops accepting commands, and waits for the worker to handle the commands that are already queued
+    ///
+    /// Don't call this from the handler, the worker would wait for itself.
+    pub fn shutdown(&self) {
+        let worker = self.worker().take();
+        if let Some(Worker { sender, thread }) = worker {
+            // the worker exits when the channel is closed and empty
+            drop(sender);
+            if thread.join().is_err() {
+                eprintln!("jaffi dispatcher thread panicked");
+            }
+        }
+    }
+}
+
+impl<C: Send + 'static> Default for Dispatcher<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C> fmt::Debug for Dispatcher<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let running = self
+            .worker
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .is_some();
+        f.debug_struct("Dispatcher")
+            .field("running", &running)
+            .finish()
+    }
+}
+
+/// The loop of the worker thread
+fn run<C, H>(vm: &JavaVM, receiver: &mpsc::Receiver<C>, mut handler: H)
+where
+    H: for<'j> FnMut(JNIEnv<'j>, C),
+{
+    let env = match vm.attach_current_thread_as_daemon() {
+        Ok(env) => env,
+        Err(e) => {
+            // the queued commands are dropped with the receiver
+            eprintln!("jaffi dispatcher thread couldn't attach to the JVM: {e}");
+            return;
+        }
+    };
+
+    for command in receiver {
+        if env.push_local_frame(DEFAULT_CAPACITY).is_err() {
+            eprintln!(
+                "jaffi dispatcher thread couldn't push a local frame, the command is dropped"
+            );
+            continue;
+        }
+
+        if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| handler(env, command))) {
+            eprintln!(
+                "jaffi dispatcher handler panicked: {}",
+                get_panic_message(&*payload)
+            );
+        }
+
+        // there is no Java caller to throw to on this thread
+        if env.exception_check().unwrap_or(false) {
+            env.exception_describe().ok();
+            env.exception_clear().ok();
+        }
+        env.pop_local_frame(JObject::null()).ok();
+    }
+}
+
+/// The dispatcher couldn't be started
+#[derive(Debug)]
+pub enum DispatchError {
+    /// The `JavaVM` couldn't be read from the env
+    Jni(jni::errors::Error),
+    /// The worker thread couldn't be spawned
+    Spawn(io::Error),
+}
+
+impl fmt::Display for DispatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Jni(e) => write!(f, "couldn't get the JavaVM: {e}"),
+            Self::Spawn(e) => write!(f, "couldn't spawn the dispatcher thread: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for DispatchError {}
@@ -5,11 +5,16 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
-use std::{
-    any::Any,
+use alloc::{
     borrow::Cow,
-    fmt,
-    panic::{self, PanicInfo, UnwindSafe},
+    format,
+    string::{String, ToString},
+};
+use core::{any::Any, fmt, panic::UnwindSafe};
+#[cfg(feature = "panic_hook")]
+use std::{
+    backtrace::{Backtrace, BacktraceStatus},
+    panic::PanicHookInfo,
 };
 
 use jni::{
@@ -19,6 +24,8 @@ use jni::{
     JNIEnv, JavaVM,
 };
 
+#[cfg(feature = "std")]
+use crate::arrays::ArrayElement;
 use crate::NullObject;
 
 pub fn get_panic_message(message: &'_ (dyn Any + Send)) -> Cow<'_, str> {
@@ -37,32 +44,145 @@ pub fn get_panic_message(message: &'_ (dyn Any + Send)) -> Cow<'_, str> {
     }
 }
 
+/// Converts the payload of a panic into the message of the thrown exception
+pub type PanicMessageFn = fn(&(dyn Any + Send)) -> Cow<'_, str>;
+
+/// Configures the exception that panics are converted to
+#[derive(Clone, Copy)]
+pub struct PanicMapping {
+    /// The exception class to throw, e.g. `java/lang/RuntimeException`, it must have a constructor taking a `String`
+    pub class: &'static str,
+    /// Produces the message from the panic payload
+    pub message: PanicMessageFn,
+}
+
+impl PanicMapping {
+    /// Panics are thrown as `java/lang/RuntimeException`
+    pub const DEFAULT: Self = Self {
+        class: "java/lang/RuntimeException",
+        message: get_panic_message,
+    };
+}
+
+impl Default for PanicMapping {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
 /// This panic hook can add a bit more information than the catch_unwind, which doesn't get the full panic_info
+///
+/// The thrown `RuntimeException` has the location of the panic, and the Rust backtrace if backtraces are enabled.
+///   Without the `panic_hook` feature this does nothing, the exception only has the panic message.
 pub fn register_panic_hook(vm: JavaVM) {
-    panic::set_hook(Box::new(move |panic_info: &PanicInfo| {
-        let env = vm.get_env().expect("not called in a JVM context");
+    register_panic_hook_with(vm, PanicMapping::DEFAULT)
+}
+
+/// See [`register_panic_hook`], panics are thrown as configured in the `mapping`
+#[cfg(feature = "panic_hook")]
+pub fn register_panic_hook_with(vm: JavaVM, mapping: PanicMapping) {
+    std::panic::set_hook(Box::new(move |panic_info: &PanicHookInfo| {
+        // threads that are not attached, e.g. spawned by Rust, have no Java caller to throw to
+        let env = match vm.get_env() {
+            Ok(env) => env,
+            Err(_) => return,
+        };
 
         // we don't want to overwrite an existing exception...
         if !env.exception_check().unwrap_or(true) {
-            let msg = get_panic_message(panic_info.payload());
+            let msg = (mapping.message)(panic_info.payload());
             let (file, line, column) = panic_info
                 .location()
                 .map(|l| (l.file(), l.line(), l.column()))
                 .unwrap_or_default();
 
-            let msg = format!("panic '{msg}' at {file}:{line}:{column}");
-            env.throw_new("java/lang/RuntimeException", msg)
+            let mut msg = format!("panic '{msg}' at {file}:{line}:{column}");
+
+            // only captured when enabled with RUST_BACKTRACE or RUST_LIB_BACKTRACE
+            let backtrace = Backtrace::capture();
+            if let BacktraceStatus::Captured = backtrace.status() {
+                msg.push_str(&format!("\nRust backtrace:\n{backtrace}"));
+            }
+
+            crate::class_loader::find_class(env, mapping.class)
+                .and_then(|class| env.throw_new(class, msg))
                 .expect("failed to throw exception");
         }
     }));
 }
 
+/// See [`register_panic_hook`], the default panic hook is kept without the `panic_hook` feature
+#[cfg(not(feature = "panic_hook"))]
+pub fn register_panic_hook_with(_vm: JavaVM, _mapping: PanicMapping) {}
+
+/// Throws a `java/lang/ClassCastException` for a value of the wrong type, then panics
+///
+/// The panic is caught by [`catch_panic_and_throw`], which doesn't overwrite the pending `ClassCastException`.
+#[track_caller]
+pub fn throw_class_cast(env: JNIEnv<'_>, error: jni::errors::Error) -> ! {
+    if !env.exception_check().unwrap_or(true) {
+        // the panic below is reported either way
+        let _ = crate::class_loader::find_class(env, "java/lang/ClassCastException")
+            .and_then(|class| env.throw_new(class, error.to_string()));
+    }
+
+    panic!("wrong type conversion, {error}")
+}
+
+/// Throws a `java/lang/IllegalArgumentException` for an argument of a wrapper that couldn't be converted to Java, unless the
+///   conversion left an exception pending, e.g. the `IllegalArgumentException` of `valueOf` for the name of an enum constant
+///
+/// The wrapper then returns without calling Java, and the exception is thrown to the Java caller of the native method.
+pub fn throw_illegal_argument(env: JNIEnv<'_>, error: jni::errors::Error) {
+    if !env.exception_check().unwrap_or(true) {
+        // if this fails, another exception is pending
+        let _ = crate::class_loader::find_class(env, "java/lang/IllegalArgumentException")
+            .and_then(|class| env.throw_new(class, error.to_string()));
+    }
+}
+
+/// Checks that no exception is pending before the wrapper of `method`, e.g. `net.bluejekyll.Foo.bar(I)V`, calls into Java
+///
+/// Calling into Java with a pending exception is undefined behavior in JNI. If the wrapper catches exceptions, `catches`, the
+///   pending exception is returned as a `JavaException` error, which the wrapper handles as if the method threw it. Otherwise
+///   this panics, a panic in a native method leaves the pending exception to be thrown in Java.
+#[track_caller]
+pub fn check_pending_exception(
+    env: JNIEnv<'_>,
+    method: &str,
+    catches: bool,
+) -> Result<(), jni::errors::Error> {
+    if !env.exception_check().unwrap_or(false) {
+        return Ok(());
+    }
+
+    if catches {
+        Err(jni::errors::Error::JavaException)
+    } else {
+        panic!(
+            "exception pending on calling {method}, it must be handled or returned to Java first"
+        )
+    }
+}
+
 /// Catches and potential panics, and then converts them to a RuntimeException in Java.
 ///
+/// Without the `catch_panic` feature the panic isn't caught, and unwinding out of the native method aborts the process.
+///
 /// * `R` - must implement `Default` in order to allow the (unused) default return value in the case of an exception
 pub fn catch_panic_and_throw<F: FnOnce() -> R + UnwindSafe, R: NullObject>(
     env: JNIEnv<'_>,
     f: F,
+) -> R {
+    catch_panic_and_throw_with(env, &PanicMapping::DEFAULT, f)
+}
+
+/// See [`catch_panic_and_throw`], panics are thrown as configured in the `mapping`
+#[cfg(feature = "catch_panic")]
+pub fn catch_panic_and_throw_with<F: FnOnce() -> R + UnwindSafe, R: NullObject>(
+    env: JNIEnv<'_>,
+    mapping: &PanicMapping,
+    f: F,
 ) -> R {
     let result = std::panic::catch_unwind(f);
 
@@ -71,10 +191,11 @@ pub fn catch_panic_and_throw<F: FnOnce() -> R + UnwindSafe, R: NullObject>(
         Err(e) => {
             // we don't want to overwrite an existing exception...
             if !env.exception_check().unwrap_or(true) {
-                let msg = get_panic_message(&e);
+                let msg = (mapping.message)(&*e);
 
                 let msg = format!("panic '{msg}'");
-                env.throw_new("java/lang/RuntimeException", msg)
+                crate::class_loader::find_class(env, mapping.class)
+                    .and_then(|class| env.throw_new(class, msg))
                     .expect("failed to throw exception");
             }
             R::null()
@@ -82,6 +203,16 @@ pub fn catch_panic_and_throw<F: FnOnce() -> R + UnwindSafe, R: NullObject>(
     }
 }
 
+/// See [`catch_panic_and_throw`], `f` is called directly without the `catch_panic` feature
+#[cfg(not(feature = "catch_panic"))]
+pub fn catch_panic_and_throw_with<F: FnOnce() -> R + UnwindSafe, R: NullObject>(
+    _env: JNIEnv<'_>,
+    _mapping: &PanicMapping,
+    f: F,
+) -> R {
+    f()
+}
+
 pub trait Throwable: Sized {
     /// Throw a new exception.
     #[track_caller]
@@ -91,6 +222,8 @@ pub trait Throwable: Sized {
     fn catch<'j>(_env: JNIEnv<'j>, exception: JThrowable<'j>) -> Result<Self, JThrowable<'j>>;
 }
 
+/// Any `java/lang/Throwable`, e.g. the exceptions caught with the `unchecked_exceptions` option of the generator
+#[derive(Clone, Copy, Debug)]
 pub struct AnyThrowable;
 
 impl Throwable for AnyThrowable {
@@ -121,6 +254,99 @@ impl<E: Throwable> Error<E> {
     pub fn throw(&self, env: JNIEnv<'_>) -> Result<(), jni::errors::Error> {
         <E as Throwable>::throw(&self.kind, env, &self.msg)
     }
+
+    /// The kind of exception that will be thrown
+    pub fn kind(&self) -> &E {
+        &self.kind
+    }
+
+    /// The message for the exception
+    pub fn msg(&self) -> &str {
+        &self.msg
+    }
+}
+
+/// Allows `Err(IllegalArgumentException.into())`, or any exception which is a member of the exception set `E`
+impl<T: Throwable, E: Throwable + From<T>> From<T> for Error<E> {
+    fn from(kind: T) -> Self {
+        Self::new(E::from(kind), "")
+    }
+}
+
+impl<E: Throwable> fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", core::any::type_name::<E>(), self.msg)
+    }
+}
+
+impl<E: Throwable> fmt::Debug for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        <Self as fmt::Display>::fmt(self, f)
+    }
+}
+
+impl<E: Throwable> core::error::Error for Error<E> {}
+
+macro_rules! jdk_exception {
+    ($name: ident, $class: literal) => {
+        #[doc = concat!("The JDK exception `", $class, "`")]
+        #[derive(Clone, Copy, Debug)]
+        pub struct $name;
+
+        impl Throwable for $name {
+            #[track_caller]
+            fn throw<S: Into<JNIString>>(
+                &self,
+                env: JNIEnv<'_>,
+                msg: S,
+            ) -> Result<(), jni::errors::Error> {
+                env.throw_new($class, msg)
+            }
+
+            fn catch<'j>(
+                env: JNIEnv<'j>,
+                exception: JThrowable<'j>,
+            ) -> Result<Self, JThrowable<'j>> {
+                if !exception.is_null() && env.is_instance_of(exception, $class).unwrap_or(false) {
+                    Ok(Self)
+                } else {
+                    Err(exception)
+                }
+            }
+        }
+    };
+}
+
+jdk_exception!(
+    IllegalArgumentException,
+    "java/lang/IllegalArgumentException"
+);
+jdk_exception!(IllegalStateException, "java/lang/IllegalStateException");
+jdk_exception!(IOException, "java/io/IOException");
+jdk_exception!(NullPointerException, "java/lang/NullPointerException");
+jdk_exception!(
+    UnsupportedOperationException,
+    "java/lang/UnsupportedOperationException"
+);
+
+/// Allows `?` on `std::io::Error` in natives declared `throws IOException`, the message is the `Display` of the error
+#[cfg(feature = "std")]
+impl<E: Throwable + From<IOException>> From<std::io::Error> for Error<E> {
+    fn from(error: std::io::Error) -> Self {
+        Self::new(E::from(IOException), error.to_string())
+    }
+}
+
+/// Returns the path in this module of the exception type for the JDK exception class, e.g. `java/io/IOException`
+pub fn jdk_exception_name(class: &str) -> Option<&'static str> {
+    match class {
+        "java/lang/IllegalArgumentException" => Some("IllegalArgumentException"),
+        "java/lang/IllegalStateException" => Some("IllegalStateException"),
+        "java/io/IOException" => Some("IOException"),
+        "java/lang/NullPointerException" => Some("NullPointerException"),
+        "java/lang/UnsupportedOperationException" => Some("UnsupportedOperationException"),
+        _ => None,
+    }
 }
 
 /// A type that represents a known Exception type from Java.
@@ -141,6 +367,25 @@ impl<'j, T: Throwable + Copy> Exception<'j, T> {
 }
 
 impl<'j, T: Throwable> Exception<'j, T> {
+    /// Returns the exception as the wrapper type `W`, e.g. the generated wrapper for the exception class.
+    ///
+    /// The exception is checked to be an instance of the class of `W`, `T` may stand for several exception classes. The
+    ///   generated wrappers of exception classes also convert `From` the `Exception` of their class without the check.
+    #[cfg(feature = "std")]
+    pub fn object<W: ArrayElement<'j>>(&self) -> Result<W, jni::errors::Error> {
+        let class = W::class_cache().get(self.env)?;
+        let is_instance = self.env.is_instance_of(self.exception, class);
+        self.env.delete_local_ref(*class)?;
+
+        if !is_instance? {
+            return Err(jni::errors::Error::WrongJValueType(
+                W::class_cache().class(),
+                "another Throwable",
+            ));
+        }
+        Ok(W::from(JObject::from(self.exception)))
+    }
+
     /// Throw a new exception.
     #[track_caller]
     pub fn throw<S: Into<JNIString>>(
@@ -161,6 +406,18 @@ impl<'j, T: Throwable> Exception<'j, T> {
             throwable,
         })
     }
+
+    /// Narrows the exception to `U`, e.g. an `Exception<AnyThrowable>` to a specific exception, returns this exception if it isn't a `U`
+    pub fn downcast<U: Throwable>(self) -> Result<Exception<'j, U>, Self> {
+        match U::catch(self.env, self.exception) {
+            Ok(throwable) => Ok(Exception {
+                env: self.env,
+                exception: self.exception,
+                throwable,
+            }),
+            Err(_) => Err(self),
+        }
+    }
 }
 
 impl<'j, T: Throwable> fmt::Display for Exception<'j, T> {
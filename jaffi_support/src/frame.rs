@@ -0,0 +1,134 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Local reference frames, for calling into Java in loops without leaking local references.
+//!
+//! The generated wrapper methods use [`with_frame`] when `local_frames` is enabled in the builder.
+
+use alloc::string::String;
+
+use jni::{
+    objects::{JObject, JValue},
+    JNIEnv,
+};
+
+/// The capacity of the frames pushed by the generated wrapper methods, the JVM grows frames as needed
+pub const DEFAULT_CAPACITY: i32 = 16;
+
+/// Results that can be returned out of a local frame
+///
+/// At most one local reference survives the frame, it's moved into the outer frame when the frame is popped.
+pub trait FrameResult<'j> {
+    /// The local reference to keep, `null` if there isn't one
+    fn kept(&self) -> JObject<'j>;
+
+    /// Replaces the kept reference with the one that's valid in the outer frame
+    fn with_kept(self, kept: JObject<'j>) -> Self;
+}
+
+impl<'j> FrameResult<'j> for JObject<'j> {
+    fn kept(&self) -> JObject<'j> {
+        *self
+    }
+
+    fn with_kept(self, kept: JObject<'j>) -> Self {
+        kept
+    }
+}
+
+impl<'j> FrameResult<'j> for JValue<'j> {
+    fn kept(&self) -> JObject<'j> {
+        match self {
+            JValue::Object(obj) => *obj,
+            _ => JObject::null(),
+        }
+    }
+
+    fn with_kept(self, kept: JObject<'j>) -> Self {
+        match self {
+            JValue::Object(_) => JValue::Object(kept),
+            value => value,
+        }
+    }
+}
+
+/// The error must not hold local references, they are released with the frame
+impl<'j, T: FrameResult<'j>, E> FrameResult<'j> for Result<T, E> {
+    fn kept(&self) -> JObject<'j> {
+        match self {
+            Ok(value) => value.kept(),
+            Err(_) => JObject::null(),
+        }
+    }
+
+    fn with_kept(self, kept: JObject<'j>) -> Self {
+        self.map(|value| value.with_kept(kept))
+    }
+}
+
+/// `None` keeps no reference
+impl<'j, T: FrameResult<'j>> FrameResult<'j> for Option<T> {
+    fn kept(&self) -> JObject<'j> {
+        self.as_ref().map_or_else(JObject::null, FrameResult::kept)
+    }
+
+    fn with_kept(self, kept: JObject<'j>) -> Self {
+        self.map(|value| value.with_kept(kept))
+    }
+}
+
+macro_rules! no_local_refs {
+    ($($rtype: ty),*) => {
+        $(
+            impl<'j> FrameResult<'j> for $rtype {
+                fn kept(&self) -> JObject<'j> {
+                    JObject::null()
+                }
+
+                fn with_kept(self, _kept: JObject<'j>) -> Self {
+                    self
+                }
+            }
+        )*
+    };
+}
+
+no_local_refs!((), bool, i8, char, i16, i32, i64, f32, f64, String);
+
+/// Pops the frame if `f` panics
+pub(crate) struct FrameGuard<'j>(pub(crate) JNIEnv<'j>);
+
+impl Drop for FrameGuard<'_> {
+    fn drop(&mut self) {
+        self.0.pop_local_frame(JObject::null()).ok();
+    }
+}
+
+/// Runs `f` in a new local frame, the local references created in `f` are released when it returns
+///
+/// The reference in the result, if any, is kept, see [`FrameResult`]. Pending exceptions are not affected by the frame.
+///
+/// # Panics
+///
+/// If the frame can't be allocated
+pub fn with_frame<'j, R, F>(env: JNIEnv<'j>, capacity: i32, f: F) -> R
+where
+    R: FrameResult<'j>,
+    F: FnOnce() -> R,
+{
+    env.push_local_frame(capacity)
+        .expect("couldn't push a local frame");
+
+    let guard = FrameGuard(env);
+    let result = f();
+    core::mem::forget(guard);
+
+    let kept = env
+        .pop_local_frame(result.kept())
+        .expect("couldn't pop the local frame");
+    result.with_kept(kept)
+}
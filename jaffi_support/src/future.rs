@@ -0,0 +1,244 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Native methods returning `java.util.concurrent.CompletableFuture` are implemented with Rust futures.
+//!
+//! The future returned from Rust is spawned on the [`Executor`] registered with [`set_executor`], usually from the
+//!   `user_on_load_fn`. When it resolves the thread is attached to the JVM and the `CompletableFuture` is completed,
+//!   a panic in the future completes it exceptionally with a `RuntimeException`.
+
+use std::{
+    future::Future,
+    ops::Deref,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    sync::{Arc, RwLock},
+    task::{Context, Poll},
+    thread,
+};
+
+use jni::{
+    objects::{JObject, JValue},
+    JNIEnv, JavaVM,
+};
+
+use crate::{
+    boxed::{JBoolean, JByte, JCharacter, JDouble, JFloat, JInteger, JLong, JShort},
+    exceptions::get_panic_message,
+    global::RegisteredGlobal,
+    FromRustToJava,
+};
+
+/// The future returned from Rust for a `CompletableFuture`, e.g. `Box::pin(async move { ... })`
+pub type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send + 'static>>;
+
+/// Runs the futures of native methods to completion, e.g. by spawning them on a runtime
+pub trait Executor: Send + Sync + 'static {
+    /// Spawns the future, it must be polled to completion
+    fn spawn(&self, future: BoxFuture<()>);
+}
+
+impl<F> Executor for F
+where
+    F: Fn(BoxFuture<()>) + Send + Sync + 'static,
+{
+    fn spawn(&self, future: BoxFuture<()>) {
+        self(future)
+    }
+}
+
+static EXECUTOR: RwLock<Option<Arc<dyn Executor>>> = RwLock::new(None);
+
+/// Sets the executor for the futures of native methods, replacing any previous executor
+pub fn set_executor<E: Executor>(executor: E) {
+    *EXECUTOR
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Arc::new(executor));
+}
+
+/// Removes the executor, e.g. from the `user_on_unload_fn`
+pub fn clear_executor() {
+    *EXECUTOR
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+}
+
+fn executor() -> Option<Arc<dyn Executor>> {
+    EXECUTOR
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+/// The results of futures that can complete a `CompletableFuture`
+///
+/// The conversion happens on the executor's thread, so these must be `Send` Rust types.
+pub trait ToJavaObject: Send + 'static {
+    /// Converts the value into the Java object, `null` for `None` and `()`
+    fn to_java_object<'a>(self, env: JNIEnv<'a>) -> JObject<'a>;
+}
+
+/// `CompletableFuture<Void>` completes with `null`
+impl ToJavaObject for () {
+    fn to_java_object<'a>(self, _env: JNIEnv<'a>) -> JObject<'a> {
+        JObject::null()
+    }
+}
+
+impl ToJavaObject for String {
+    fn to_java_object<'a>(self, env: JNIEnv<'a>) -> JObject<'a> {
+        let string = jni::objects::JString::rust_to_java(self, env);
+        string.into()
+    }
+}
+
+macro_rules! boxed_to_java_object {
+    ($jtype: ident, $rtype: ty) => {
+        impl ToJavaObject for Option<$rtype> {
+            fn to_java_object<'a>(self, env: JNIEnv<'a>) -> JObject<'a> {
+                $jtype::rust_to_java(self, env).into()
+            }
+        }
+    };
+}
+
+boxed_to_java_object!(JBoolean, bool);
+boxed_to_java_object!(JByte, i8);
+boxed_to_java_object!(JCharacter, char);
+boxed_to_java_object!(JDouble, f64);
+boxed_to_java_object!(JFloat, f32);
+boxed_to_java_object!(JInteger, i32);
+boxed_to_java_object!(JLong, i64);
+boxed_to_java_object!(JShort, i16);
+
+/// A `java.util.concurrent.CompletableFuture` returned from a native method
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug)]
+pub struct JCompletableFuture<'j>(JObject<'j>);
+
+impl<'j> Deref for JCompletableFuture<'j> {
+    type Target = JObject<'j>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'j> From<JObject<'j>> for JCompletableFuture<'j> {
+    fn from(obj: JObject<'j>) -> Self {
+        Self(obj)
+    }
+}
+
+impl<'j> From<JCompletableFuture<'j>> for JObject<'j> {
+    fn from(obj: JCompletableFuture<'j>) -> Self {
+        obj.0
+    }
+}
+
+/// Spawns the future on the executor, and returns the `CompletableFuture` that it will complete
+///
+/// # Panics
+///
+/// If no executor has been set with [`set_executor`]
+impl<'j, T: ToJavaObject> FromRustToJava<'j, BoxFuture<T>> for JCompletableFuture<'j> {
+    fn rust_to_java(rust: BoxFuture<T>, env: JNIEnv<'j>) -> Self {
+        let executor = executor().expect(
+            "no executor for futures, call jaffi_support::future::set_executor from the user_on_load_fn",
+        );
+
+        let completable = env
+            .new_object("java/util/concurrent/CompletableFuture", "()V", &[])
+            .expect("couldn't construct a CompletableFuture");
+        // released when the library is unloaded, the future is then not completed
+        let global =
+            RegisteredGlobal::new(env, completable).expect("couldn't create a global reference");
+        let vm = env.get_java_vm().expect("couldn't get the JavaVM");
+
+        executor.spawn(Box::pin(async move {
+            let result = CatchUnwind(rust).await;
+            complete(&vm, &global, result);
+        }));
+
+        Self(completable)
+    }
+}
+
+/// Completes the `CompletableFuture` from the executor's thread
+fn complete<T: ToJavaObject>(
+    vm: &JavaVM,
+    completable: &RegisteredGlobal,
+    result: thread::Result<T>,
+) {
+    // executor threads stay attached, this is cheap after the first call on a thread
+    let env = vm
+        .attach_current_thread_as_daemon()
+        .expect("couldn't attach the thread to the JVM");
+
+    let completed = env.with_local_frame(16, || {
+        let Some(completable) = completable.local(env)? else {
+            return Ok(JObject::null());
+        };
+
+        match result {
+            Ok(value) => {
+                let value = value.to_java_object(env);
+                env.call_method(
+                    completable,
+                    "complete",
+                    "(Ljava/lang/Object;)Z",
+                    &[JValue::Object(value)],
+                )?;
+            }
+            Err(payload) => {
+                // the panic hook has already thrown if the thread was attached when it panicked
+                let exception = if env.exception_check()? {
+                    let exception = env.exception_occurred()?;
+                    env.exception_clear()?;
+                    JObject::from(exception)
+                } else {
+                    let msg = env.new_string(get_panic_message(&*payload))?;
+                    env.new_object(
+                        "java/lang/RuntimeException",
+                        "(Ljava/lang/String;)V",
+                        &[JValue::Object(msg.into())],
+                    )?
+                };
+                env.call_method(
+                    completable,
+                    "completeExceptionally",
+                    "(Ljava/lang/Throwable;)Z",
+                    &[JValue::Object(exception)],
+                )?;
+            }
+        }
+
+        Ok(JObject::null())
+    });
+
+    // there is no Java caller to throw to on this thread
+    if completed.is_err() && env.exception_check().unwrap_or(false) {
+        env.exception_describe().ok();
+        env.exception_clear().ok();
+    }
+}
+
+/// Catches panics while polling the future
+struct CatchUnwind<F>(F);
+
+impl<F: Future + Unpin> Future for CatchUnwind<F> {
+    type Output = thread::Result<F::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let inner = &mut self.0;
+        match panic::catch_unwind(AssertUnwindSafe(|| Pin::new(inner).poll(cx))) {
+            Ok(Poll::Pending) => Poll::Pending,
+            Ok(Poll::Ready(value)) => Poll::Ready(Ok(value)),
+            Err(payload) => Poll::Ready(Err(payload)),
+        }
+    }
+}
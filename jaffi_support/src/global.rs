@@ -0,0 +1,126 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! A registry of the global references held from Rust.
+//!
+//! The generated `JNI_OnUnload` calls [`shutdown`], which releases every registered reference while the VM is still
+//!   valid. Any [`RegisteredGlobal`] that outlives the shutdown becomes inert, dropping it will not touch the VM.
+
+use std::{
+    collections::BTreeMap,
+    sync::{Mutex, MutexGuard},
+};
+
+use jni::{
+    objects::{GlobalRef, JObject},
+    JNIEnv,
+};
+
+struct Registry {
+    next_id: u64,
+    refs: BTreeMap<u64, GlobalRef>,
+    shutdown: bool,
+}
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+    next_id: 0,
+    refs: BTreeMap::new(),
+    shutdown: false,
+});
+
+fn registry() -> MutexGuard<'static, Registry> {
+    // a panic while holding the lock can't leave the map in a bad state
+    REGISTRY
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// An owned global reference, tracked in the registry so that it can be released when the library is unloaded.
+///
+/// This is `Send + Sync`, it only holds a key into the registry.
+#[derive(Debug)]
+pub struct RegisteredGlobal {
+    id: u64,
+}
+
+// the generated global wrappers rely on this
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<RegisteredGlobal>();
+};
+
+impl RegisteredGlobal {
+    /// Create a new global reference to `obj` and register it
+    pub fn new(env: JNIEnv<'_>, obj: JObject<'_>) -> Result<Self, jni::errors::Error> {
+        let global = env.new_global_ref(obj)?;
+
+        let mut registry = registry();
+        if registry.shutdown {
+            drop(registry);
+            drop(global);
+            return Err(jni::errors::Error::NullPtr(
+                "global reference registry is shut down",
+            ));
+        }
+
+        let id = registry.next_id;
+        registry.next_id += 1;
+        registry.refs.insert(id, global);
+
+        Ok(Self { id })
+    }
+
+    /// Returns a new local reference to the object, `None` if the registry has been shut down
+    pub fn local<'j>(&self, env: JNIEnv<'j>) -> Result<Option<JObject<'j>>, jni::errors::Error> {
+        let registry = registry();
+        let global = match registry.refs.get(&self.id) {
+            Some(global) => global,
+            None => return Ok(None),
+        };
+
+        // the global is valid while the registry is locked, the new local reference is owned by `env`
+        let obj = JObject::from(global.as_obj().into_inner());
+        env.new_local_ref::<JObject<'_>>(obj).map(Some)
+    }
+
+    /// Returns true if the reference has not yet been released by a [`shutdown`]
+    pub fn is_live(&self) -> bool {
+        registry().refs.contains_key(&self.id)
+    }
+}
+
+impl Drop for RegisteredGlobal {
+    fn drop(&mut self) {
+        // take the reference out of the registry before releasing it, the release may need to attach the thread
+        let global = registry().refs.remove(&self.id);
+        drop(global);
+    }
+}
+
+/// Returns the number of registered global references that are still live
+pub fn live_count() -> usize {
+    registry().refs.len()
+}
+
+/// Allows references to be registered again, called by the generated `JNI_OnLoad`
+pub fn startup() {
+    registry().shutdown = false;
+}
+
+/// Releases all the registered references and stops accepting new ones.
+///
+/// This must be called while the VM is still valid, the generated `JNI_OnUnload` does this. It's safe to call this more than once.
+pub fn shutdown() {
+    let refs = {
+        let mut registry = registry();
+        registry.shutdown = true;
+        std::mem::take(&mut registry.refs)
+    };
+
+    // released outside of the lock
+    drop(refs);
+}
@@ -0,0 +1,310 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Smoke tests of the bindings, used by the test module generated with the `test_harness` option of the generator.
+//!
+//! The JVM is that of [`crate::testing`], started in the test process. The native methods of each bound class are read with reflection, and their JNI names are looked up in the cdylib. This
+//!   catches bindings that are out of date with the classes, and export names that the JVM would not link.
+
+use std::{
+    ffi::c_void,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use jni::{
+    objects::{JClass, JObject, JString, JValue},
+    sys::jobjectArray,
+    JNIEnv,
+};
+
+use crate::testing::java_vm;
+
+/// The `ACC_NATIVE` modifier of `java.lang.reflect.Modifier`
+const NATIVE_MODIFIER: i32 = 0x0100;
+
+/// A class with native methods, and the methods that bindings were generated for
+pub struct NativeClass {
+    /// The class descriptor, e.g. `net/bluejekyll/NativeStrings`
+    pub class: &'static str,
+    /// The name and descriptor of each native method, e.g. `("eatString", "(Ljava/lang/String;)V")`
+    pub methods: &'static [(&'static str, &'static str)],
+}
+
+/// Checks that the native methods of the classes match the bindings, and that each links against the library
+///
+/// Returns all the mismatches and missing exports as the error.
+///
+/// * `classpath` - the classpath of the JVM, used when it's started by the first call
+/// * `library` - the cdylib with the exports, see [`default_library`]
+/// * `classes` - the classes with the native methods of the bindings
+pub fn check_native_methods(
+    classpath: &[&str],
+    library: &Path,
+    classes: &[NativeClass],
+) -> Result<(), String> {
+    let vm = java_vm(classpath)?;
+    let env = vm
+        .attach_current_thread_permanently()
+        .map_err(|e| format!("could not attach to the JVM: {e}"))?;
+
+    // the library is not loaded into the JVM, so its JNI_OnLoad doesn't run
+    let library = unsafe { libloading::Library::new(library) }
+        .map_err(|e| format!("could not load {}: {e}", library.display()))?;
+
+    let mut errors = String::new();
+    for class in classes {
+        let methods = match native_methods(env, classpath, class.class) {
+            Ok(methods) => methods,
+            Err(e) => {
+                env.exception_clear().ok();
+                writeln!(errors, "{}: could not read the class, {e}", class.class).unwrap();
+                continue;
+            }
+        };
+
+        for (name, descriptor) in &methods {
+            if !class
+                .methods
+                .iter()
+                .any(|(n, d)| n == name && d == descriptor)
+            {
+                writeln!(
+                    errors,
+                    "{}.{name}{descriptor}: no binding was generated, regenerate the bindings",
+                    class.class
+                )
+                .unwrap();
+                continue;
+            }
+
+            let short = format!("Java_{}_{}", escape(class.class), escape(name));
+            let params = descriptor
+                .strip_prefix('(')
+                .and_then(|d| d.split_once(')'))
+                .map_or("", |(params, _)| params);
+            let long = format!("{short}__{}", escape(params));
+
+            let linked = [&short, &long]
+                .iter()
+                .any(|symbol| unsafe { library.get::<*const c_void>(symbol.as_bytes()).is_ok() });
+            if !linked {
+                writeln!(
+                    errors,
+                    "{}.{name}{descriptor}: neither {short} nor {long} is exported",
+                    class.class
+                )
+                .unwrap();
+            }
+        }
+
+        for (name, descriptor) in class.methods {
+            if !methods.iter().any(|(n, d)| n == name && d == descriptor) {
+                writeln!(
+                    errors,
+                    "{}.{name}{descriptor}: the native method no longer exists, regenerate the bindings",
+                    class.class
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// The cdylib of the crate, `JAFFI_TEST_LIBRARY` or the library next to the `deps` directory of the test executable
+///
+/// * `crate_name` - the name of the library crate, i.e. `env!("CARGO_CRATE_NAME")`
+pub fn default_library(crate_name: &str) -> PathBuf {
+    if let Some(library) = std::env::var_os("JAFFI_TEST_LIBRARY") {
+        return PathBuf::from(library);
+    }
+
+    let file_name = format!(
+        "{}{crate_name}{}",
+        std::env::consts::DLL_PREFIX,
+        std::env::consts::DLL_SUFFIX
+    );
+    let exe = std::env::current_exe().unwrap_or_default();
+    let target_dir = exe
+        .parent()
+        .and_then(Path::parent)
+        .unwrap_or_else(|| Path::new("."));
+    target_dir.join(file_name)
+}
+
+/// The name and descriptor of each native method declared by the class, the class is not initialized
+fn native_methods(
+    env: JNIEnv<'_>,
+    classpath: &[&str],
+    class: &str,
+) -> Result<Vec<(String, String)>, jni::errors::Error> {
+    let class = load_class(env, classpath, class)?;
+
+    let methods = env
+        .call_method(
+            class,
+            "getDeclaredMethods",
+            "()[Ljava/lang/reflect/Method;",
+            &[],
+        )?
+        .l()?
+        .into_inner() as jobjectArray;
+
+    let mut native_methods = Vec::new();
+    for i in 0..env.get_array_length(methods)? {
+        let method = env.get_object_array_element(methods, i)?;
+        let modifiers = env.call_method(method, "getModifiers", "()I", &[])?.i()?;
+        if modifiers & NATIVE_MODIFIER == 0 {
+            continue;
+        }
+
+        let name = env
+            .call_method(method, "getName", "()Ljava/lang/String;", &[])?
+            .l()?;
+        let name = String::from(env.get_string(JString::from(name))?);
+
+        let params = env
+            .call_method(method, "getParameterTypes", "()[Ljava/lang/Class;", &[])?
+            .l()?
+            .into_inner() as jobjectArray;
+        let mut descriptor = String::from("(");
+        for j in 0..env.get_array_length(params)? {
+            let param = env.get_object_array_element(params, j)?;
+            descriptor.push_str(&type_descriptor(env, param.into())?);
+        }
+        descriptor.push(')');
+
+        let ret = env
+            .call_method(method, "getReturnType", "()Ljava/lang/Class;", &[])?
+            .l()?;
+        descriptor.push_str(&type_descriptor(env, ret.into())?);
+
+        native_methods.push((name, descriptor));
+        env.delete_local_ref(method)?;
+    }
+
+    Ok(native_methods)
+}
+
+/// Loads the class without initializing it
+///
+/// `Class.forName` finds the class file with the path encoding of the platform, which depends on the locale, so the class files
+///   of non-ASCII names are read here and defined in a class loader of their own, whose parent is the system class loader.
+///   Classes that aren't in a directory of the classpath, e.g. in a jar, are loaded with `Class.forName`.
+fn load_class<'j>(
+    env: JNIEnv<'j>,
+    classpath: &[&str],
+    class: &str,
+) -> Result<JClass<'j>, jni::errors::Error> {
+    let class_file = classpath
+        .iter()
+        .find_map(|dir| fs::read(Path::new(dir).join(format!("{class}.class"))).ok());
+    if let Some(class_file) = class_file {
+        let urls = env.new_object_array(0, "java/net/URL", JObject::null())?;
+        let loader = env.new_object(
+            "java/net/URLClassLoader",
+            "([Ljava/net/URL;)V",
+            &[JValue::Object(urls.into())],
+        )?;
+        return env.define_class(class, loader, &class_file);
+    }
+
+    let loader = env
+        .call_static_method(
+            "java/lang/ClassLoader",
+            "getSystemClassLoader",
+            "()Ljava/lang/ClassLoader;",
+            &[],
+        )?
+        .l()?;
+    let name = env.new_string(class.replace('/', "."))?;
+    env.call_static_method(
+        "java/lang/Class",
+        "forName",
+        "(Ljava/lang/String;ZLjava/lang/ClassLoader;)Ljava/lang/Class;",
+        &[
+            JValue::Object(*name),
+            JValue::Bool(0),
+            JValue::Object(loader),
+        ],
+    )?
+    .l()
+    .map(JClass::from)
+}
+
+/// The descriptor of the class, e.g. `I` or `Ljava/lang/String;`
+fn type_descriptor(env: JNIEnv<'_>, class: JClass<'_>) -> Result<String, jni::errors::Error> {
+    let name = env
+        .call_method(class, "getName", "()Ljava/lang/String;", &[])?
+        .l()?;
+    let name = String::from(env.get_string(JString::from(name))?);
+
+    let is_primitive = env.call_method(class, "isPrimitive", "()Z", &[])?.z()?;
+    let is_array = env.call_method(class, "isArray", "()Z", &[])?.z()?;
+    env.delete_local_ref(JObject::from(class))?;
+
+    let descriptor = match name.as_str() {
+        _ if is_array => name.replace('.', "/"),
+        "boolean" if is_primitive => "Z".to_string(),
+        "byte" if is_primitive => "B".to_string(),
+        "char" if is_primitive => "C".to_string(),
+        "short" if is_primitive => "S".to_string(),
+        "int" if is_primitive => "I".to_string(),
+        "long" if is_primitive => "J".to_string(),
+        "float" if is_primitive => "F".to_string(),
+        "double" if is_primitive => "D".to_string(),
+        "void" if is_primitive => "V".to_string(),
+        _ => format!("L{};", name.replace('.', "/")),
+    };
+
+    Ok(descriptor)
+}
+
+/// Escapes the name for a JNI export name, as specified by the JNI, independent of the escaping of the generator
+pub(crate) fn escape(name: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for ch in name.chars() {
+        match ch {
+            '/' => escaped.push('_'),
+            '_' => escaped.push_str("_1"),
+            ';' => escaped.push_str("_2"),
+            '[' => escaped.push_str("_3"),
+            ch if ch.is_ascii_alphanumeric() => escaped.push(ch),
+            ch => {
+                let mut utf16 = [0; 2];
+                for unit in ch.encode_utf16(&mut utf16) {
+                    write!(escaped, "_0{unit:04x}").unwrap();
+                }
+            }
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            escape("net/bluejekyll/Outer$Inner"),
+            "net_bluejekyll_Outer_00024Inner"
+        );
+        assert_eq!(escape("eat_String"), "eat_1String");
+        assert_eq!(escape("[Ljava/lang/String;I"), "_3Ljava_lang_String_2I");
+        assert_eq!(escape("i❤🦀"), "i_02764_0d83e_0dd80");
+    }
+}
@@ -0,0 +1,126 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The identity of Java objects, the equivalent of `==` and `System.identityHashCode` in Java.
+//!
+//! Different references to the same object, e.g. the local references of two calls, don't have the same pointer, so handles
+//!   can't be compared by their raw value. [`Identity`] holds the env to implement `Eq` and `Hash` with `IsSameObject`, e.g. to
+//!   deduplicate objects in a `HashSet`. The generated wrappers get `is_same_object`, `identity_hash_code` and `identity`
+//!   methods with the `identity_methods` option of the generator.
+
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+use jni::{
+    errors::Error,
+    objects::{JObject, JValue},
+    signature::{JavaType, Primitive},
+    JNIEnv,
+};
+
+use crate::cache::{ClassCache, StaticMethodIdCache};
+
+static SYSTEM: ClassCache = ClassCache::new("java/lang/System");
+static IDENTITY_HASH_CODE: StaticMethodIdCache = StaticMethodIdCache::new(
+    "java/lang/System",
+    "identityHashCode",
+    "(Ljava/lang/Object;)I",
+);
+
+/// `true` if both references are to the same object, or both are `null`, like `==` in Java
+pub fn is_same_object<'j>(
+    env: JNIEnv<'j>,
+    obj: JObject<'j>,
+    other: JObject<'j>,
+) -> Result<bool, Error> {
+    env.is_same_object(obj, other)
+}
+
+/// The `System.identityHashCode` of the object, `0` for `null`
+pub fn identity_hash_code<'j>(env: JNIEnv<'j>, obj: JObject<'j>) -> Result<i32, Error> {
+    if obj.is_null() {
+        return Ok(0);
+    }
+
+    let method_id = IDENTITY_HASH_CODE.get_in(env, &SYSTEM)?;
+    let class = SYSTEM.get(env)?;
+    let hash = env
+        .call_static_method_unchecked(
+            class,
+            method_id,
+            JavaType::Primitive(Primitive::Int),
+            &[JValue::Object(obj)],
+        )
+        .and_then(|hash| hash.i());
+    env.delete_local_ref(*class)?;
+
+    hash
+}
+
+/// A reference compared and hashed by the identity of the object, rather than by the pointer of the reference
+///
+/// The identity hash code is read once, on creation. Comparisons call `IsSameObject`, a failed call is treated as not equal.
+#[derive(Clone, Copy)]
+pub struct Identity<'j, T> {
+    env: JNIEnv<'j>,
+    obj: T,
+    hash_code: i32,
+}
+
+impl<'j, T> Identity<'j, T>
+where
+    T: Copy + Into<JObject<'j>>,
+{
+    /// Reads the identity hash code of the object
+    pub fn new(env: JNIEnv<'j>, obj: T) -> Result<Self, Error> {
+        let hash_code = identity_hash_code(env, obj.into())?;
+        Ok(Self {
+            env,
+            obj,
+            hash_code,
+        })
+    }
+
+    /// The reference to the object
+    pub fn get(&self) -> T {
+        self.obj
+    }
+
+    /// The `System.identityHashCode` of the object
+    pub fn hash_code(&self) -> i32 {
+        self.hash_code
+    }
+}
+
+impl<'j, T> PartialEq for Identity<'j, T>
+where
+    T: Copy + Into<JObject<'j>>,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.hash_code == other.hash_code
+            && is_same_object(self.env, self.obj.into(), other.obj.into()).unwrap_or(false)
+    }
+}
+
+impl<'j, T> Eq for Identity<'j, T> where T: Copy + Into<JObject<'j>> {}
+
+impl<T> Hash for Identity<'_, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.hash_code.hash(state);
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Identity<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Identity")
+            .field("obj", &self.obj)
+            .field("hash_code", &self.hash_code)
+            .finish()
+    }
+}
@@ -0,0 +1,344 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! `java.io.InputStream` and `java.io.OutputStream` adapted to `std::io::Read` and `std::io::Write`.
+//!
+//! The bytes are copied through a single reused `byte[]`, exceptions thrown by the streams are returned as `std::io::Error`s.
+
+use std::{fmt, io, ops::Deref, slice};
+
+use jni::{
+    objects::{JObject, JValue},
+    signature::{JavaType, Primitive},
+    sys::{jbyte, jbyteArray},
+    JNIEnv,
+};
+
+use crate::{cache::MethodIdCache, FromJavaToRust, FromRustToJava};
+
+/// The size of the `byte[]` used to copy to and from the streams
+pub const BUFFER_SIZE: usize = 8 * 1024;
+
+static READ: MethodIdCache = MethodIdCache::new("java/io/InputStream", "read", "([BII)I");
+static WRITE: MethodIdCache = MethodIdCache::new("java/io/OutputStream", "write", "([BII)V");
+static FLUSH: MethodIdCache = MethodIdCache::new("java/io/OutputStream", "flush", "()V");
+
+macro_rules! stream_object {
+    ($(#[$doc:meta])* $jtype: ident) => {
+        $(#[$doc])*
+        #[repr(transparent)]
+        #[derive(Clone, Copy, Debug)]
+        pub struct $jtype<'j>(JObject<'j>);
+
+        impl<'j> Deref for $jtype<'j> {
+            type Target = JObject<'j>;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl<'j> From<JObject<'j>> for $jtype<'j> {
+            fn from(obj: JObject<'j>) -> Self {
+                Self(obj)
+            }
+        }
+
+        impl<'j> From<$jtype<'j>> for JObject<'j> {
+            fn from(obj: $jtype<'j>) -> Self {
+                obj.0
+            }
+        }
+    };
+}
+
+stream_object!(
+    /// A `java.io.InputStream`, see [`JavaInputStream`]
+    JInputStream
+);
+stream_object!(
+    /// A `java.io.OutputStream`, see [`JavaOutputStream`]
+    JOutputStream
+);
+
+/// Clears the pending Java exception and converts it into an `io::Error` with the `toString` of the exception
+fn take_exception(env: JNIEnv<'_>, error: jni::errors::Error) -> io::Error {
+    if !matches!(error, jni::errors::Error::JavaException) {
+        return io::Error::other(error.to_string());
+    }
+
+    let exception = match env.exception_occurred().and_then(|exception| {
+        env.exception_clear()?;
+        Ok(exception)
+    }) {
+        Ok(exception) => exception,
+        Err(e) => return io::Error::other(e.to_string()),
+    };
+
+    let message = crate::call_string_method(&env, exception.into(), "toString")
+        .ok()
+        .flatten()
+        .map(String::from)
+        .unwrap_or_else(|| "unknown Java exception".to_string());
+
+    io::Error::other(message)
+}
+
+/// Allocates the `byte[]` used for copying
+fn new_buffer(env: JNIEnv<'_>) -> io::Result<jbyteArray> {
+    env.new_byte_array(BUFFER_SIZE as i32)
+        .map_err(|e| take_exception(env, e))
+}
+
+/// A `java.io.InputStream` as `std::io::Read`, passed to native methods for `InputStream` arguments
+pub struct JavaInputStream<'j> {
+    env: JNIEnv<'j>,
+    stream: JInputStream<'j>,
+    buffer: Option<jbyteArray>,
+}
+
+impl<'j> JavaInputStream<'j> {
+    /// Adapts the stream, the `byte[]` for reading is allocated on the first read
+    ///
+    /// # Panics
+    ///
+    /// If the stream is `null`
+    pub fn new(env: JNIEnv<'j>, stream: JInputStream<'j>) -> Self {
+        assert!(!stream.is_null(), "null InputStream passed to Rust");
+
+        Self {
+            env,
+            stream,
+            buffer: None,
+        }
+    }
+
+    /// The Java stream
+    pub fn as_stream(&self) -> JInputStream<'j> {
+        self.stream
+    }
+}
+
+impl io::Read for JavaInputStream<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let env = self.env;
+        let buffer = match self.buffer {
+            Some(buffer) => buffer,
+            None => *self.buffer.insert(new_buffer(env)?),
+        };
+
+        let len = buf.len().min(BUFFER_SIZE);
+        let read = READ
+            .get(env)
+            .and_then(|method| {
+                env.call_method_unchecked(
+                    self.stream.0,
+                    method,
+                    JavaType::Primitive(Primitive::Int),
+                    &[
+                        JValue::Object(JObject::from(buffer)),
+                        JValue::Int(0),
+                        JValue::Int(len as i32),
+                    ],
+                )
+            })
+            .and_then(|v| v.i())
+            .map_err(|e| take_exception(env, e))?;
+
+        // -1 is the end of the stream
+        if read <= 0 {
+            return Ok(0);
+        }
+
+        let read = read as usize;
+        // Safety: u8 and jbyte have the same layout, and read is at most len
+        let region = unsafe { slice::from_raw_parts_mut(buf.as_mut_ptr() as *mut jbyte, read) };
+        env.get_byte_array_region(buffer, 0, region)
+            .map_err(|e| take_exception(env, e))?;
+
+        Ok(read)
+    }
+}
+
+impl fmt::Debug for JavaInputStream<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JavaInputStream")
+            .field("stream", &self.stream)
+            .finish()
+    }
+}
+
+impl Drop for JavaInputStream<'_> {
+    fn drop(&mut self) {
+        if let Some(buffer) = self.buffer.take() {
+            self.env.delete_local_ref(JObject::from(buffer)).ok();
+        }
+    }
+}
+
+impl<'j> FromJavaToRust<'j, JInputStream<'j>> for JavaInputStream<'j> {
+    fn java_to_rust(java: JInputStream<'j>, env: JNIEnv<'j>) -> Self {
+        JavaInputStream::new(env, java)
+    }
+}
+
+impl<'j> FromRustToJava<'j, JavaInputStream<'j>> for JInputStream<'j> {
+    fn rust_to_java(rust: JavaInputStream<'j>, _env: JNIEnv<'j>) -> Self {
+        rust.stream
+    }
+}
+
+/// A `java.io.OutputStream` as `std::io::Write`, passed to native methods for `OutputStream` arguments
+///
+/// Writes are buffered in Rust, and written to the stream when the buffer is full, on `flush`, or on drop.
+///   Like `std::io::BufWriter`, errors on drop are ignored, call `flush` to see them.
+pub struct JavaOutputStream<'j> {
+    env: JNIEnv<'j>,
+    stream: JOutputStream<'j>,
+    buffer: Option<jbyteArray>,
+    pending: Vec<u8>,
+}
+
+impl<'j> JavaOutputStream<'j> {
+    /// Adapts the stream, the `byte[]` for writing is allocated on the first write to the stream
+    ///
+    /// # Panics
+    ///
+    /// If the stream is `null`
+    pub fn new(env: JNIEnv<'j>, stream: JOutputStream<'j>) -> Self {
+        assert!(!stream.is_null(), "null OutputStream passed to Rust");
+
+        Self {
+            env,
+            stream,
+            buffer: None,
+            pending: Vec::new(),
+        }
+    }
+
+    /// The Java stream, bytes that are still buffered have not been written to it
+    pub fn as_stream(&self) -> JOutputStream<'j> {
+        self.stream
+    }
+
+    /// Writes the buffered bytes to the stream and returns it, without calling `flush` on the stream
+    pub fn into_stream(mut self) -> io::Result<JOutputStream<'j>> {
+        self.write_pending()?;
+        Ok(self.stream)
+    }
+
+    /// Writes the buffered bytes to the stream, without calling `flush` on the stream
+    fn write_pending(&mut self) -> io::Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        let env = self.env;
+        let buffer = match self.buffer {
+            Some(buffer) => buffer,
+            None => *self.buffer.insert(new_buffer(env)?),
+        };
+
+        let result = self.pending.chunks(BUFFER_SIZE).try_for_each(|chunk| {
+            // Safety: u8 and jbyte have the same layout
+            let region =
+                unsafe { slice::from_raw_parts(chunk.as_ptr() as *const jbyte, chunk.len()) };
+            env.set_byte_array_region(buffer, 0, region)?;
+
+            let method = WRITE.get(env)?;
+            env.call_method_unchecked(
+                self.stream.0,
+                method,
+                JavaType::Primitive(Primitive::Void),
+                &[
+                    JValue::Object(JObject::from(buffer)),
+                    JValue::Int(0),
+                    JValue::Int(chunk.len() as i32),
+                ],
+            )?;
+
+            Ok(())
+        });
+
+        // the bytes are dropped on errors, it's unknown how much the stream accepted
+        self.pending.clear();
+        result.map_err(|e| take_exception(env, e))
+    }
+}
+
+impl io::Write for JavaOutputStream<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        if self.pending.len() >= BUFFER_SIZE {
+            self.write_pending()?;
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_pending()?;
+
+        let env = self.env;
+        FLUSH
+            .get(env)
+            .and_then(|method| {
+                env.call_method_unchecked(
+                    self.stream.0,
+                    method,
+                    JavaType::Primitive(Primitive::Void),
+                    &[],
+                )
+            })
+            .map_err(|e| take_exception(env, e))?;
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for JavaOutputStream<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JavaOutputStream")
+            .field("stream", &self.stream)
+            .field("pending", &self.pending.len())
+            .finish()
+    }
+}
+
+impl Drop for JavaOutputStream<'_> {
+    fn drop(&mut self) {
+        self.write_pending().ok();
+
+        if let Some(buffer) = self.buffer.take() {
+            self.env.delete_local_ref(JObject::from(buffer)).ok();
+        }
+    }
+}
+
+impl<'j> FromJavaToRust<'j, JOutputStream<'j>> for JavaOutputStream<'j> {
+    fn java_to_rust(java: JOutputStream<'j>, env: JNIEnv<'j>) -> Self {
+        JavaOutputStream::new(env, java)
+    }
+}
+
+/// The buffered bytes are written to the stream before it's passed to Java, use [`JavaOutputStream::into_stream`] to see the
+///   errors in Rust
+///
+/// If the bytes can't be written an `IOException` is thrown, it's pending when the stream is passed to Java.
+impl<'j> FromRustToJava<'j, JavaOutputStream<'j>> for JOutputStream<'j> {
+    fn rust_to_java(rust: JavaOutputStream<'j>, env: JNIEnv<'j>) -> Self {
+        let stream = rust.stream;
+        if let Err(e) = rust.into_stream() {
+            env.throw_new("java/io/IOException", e.to_string()).ok();
+        }
+        stream
+    }
+}
@@ -5,16 +5,80 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
-use std::{borrow::Cow, ops::Deref};
+#![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
+use alloc::{borrow::Cow, boxed::Box, string::String};
+use core::ops::Deref;
+
+#[cfg(feature = "android")]
+pub mod android;
 pub mod arrays;
+#[cfg(feature = "bench")]
+pub mod bench;
+#[cfg(feature = "bignum")]
+pub mod bignum;
+#[cfg(feature = "std")]
+pub mod boxed;
+pub mod bridge;
+pub mod buffer;
+#[cfg(feature = "std")]
+pub mod cache;
+#[cfg(feature = "std")]
+pub mod cancel;
+pub mod chars;
+pub mod class_loader;
+#[cfg(feature = "std")]
+pub mod class_registry;
+#[cfg(feature = "std")]
+pub mod context;
+#[cfg(feature = "dispatch")]
+pub mod dispatch;
 pub mod exceptions;
-
+pub mod frame;
+#[cfg(feature = "std")]
+pub mod future;
+#[cfg(feature = "std")]
+pub mod global;
+#[cfg(feature = "harness")]
+pub mod harness;
+#[cfg(feature = "std")]
+pub mod identity;
+#[cfg(feature = "std")]
+pub mod io;
+#[cfg(feature = "std")]
+pub mod listener;
+pub mod monitor;
+#[cfg(feature = "std")]
+pub mod optional;
+#[cfg(feature = "pool")]
+pub mod pool;
+#[cfg(feature = "std")]
+pub mod reflect;
+#[cfg(feature = "scratch")]
+pub mod scratch;
+pub mod string;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "time")]
+pub mod time;
+#[cfg(feature = "tracing")]
+pub mod tracing;
+#[cfg(feature = "uuid")]
+pub mod uuid;
+pub mod validate;
+
+pub use bridge::JavaBridge;
+#[cfg(feature = "std")]
+pub use cancel::Cancellation;
+#[cfg(feature = "std")]
+pub use class_registry::registry;
 pub use exceptions::{Error, Exception, Throwable};
 pub use jni;
 
 use jni::{
-    objects::{JClass, JObject, JString, JValue},
+    objects::{JClass, JObject, JString, JThrowable, JValue},
     strings::{JNIString, JavaStr},
     JNIEnv,
 };
@@ -27,7 +91,7 @@ pub(crate) fn get_class_name<'j>(
     let name = name.l()?;
     let name = JString::from(name);
     let name = env.get_string(name)?;
-    Ok(Cow::from(&name).to_string())
+    Ok(Cow::from(&name).into_owned())
 }
 
 fn call_string_method<'j, 'l: 'j>(
@@ -64,36 +128,95 @@ pub trait FromRustToJava<'j, R> {
 #[repr(transparent)]
 pub struct JavaByte(pub jni::sys::jbyte);
 
+/// The bits are reinterpreted, i.e. `-1` is `255`, use `u8::try_from` to reject negative bytes
 impl FromJavaToRust<'_, JavaByte> for u8 {
     fn java_to_rust(java: JavaByte, _env: JNIEnv<'_>) -> Self {
         java.0 as u8
     }
 }
 
+/// The bits are reinterpreted, i.e. `255` is `-1`, use `JavaByte::try_from` to reject values above `i8::MAX`
 impl FromRustToJava<'_, u8> for JavaByte {
     fn rust_to_java(rust: u8, _env: JNIEnv<'_>) -> Self {
         JavaByte(rust as jni::sys::jbyte)
     }
 }
 
+impl TryFrom<JavaByte> for u8 {
+    type Error = core::num::TryFromIntError;
+
+    fn try_from(java: JavaByte) -> Result<Self, Self::Error> {
+        u8::try_from(java.0)
+    }
+}
+
+impl TryFrom<u8> for JavaByte {
+    type Error = core::num::TryFromIntError;
+
+    fn try_from(rust: u8) -> Result<Self, Self::Error> {
+        jni::sys::jbyte::try_from(rust).map(JavaByte)
+    }
+}
+
+/// `byte` is signed in Java, the generated code uses `i8`
+impl FromJavaToRust<'_, JavaByte> for i8 {
+    fn java_to_rust(java: JavaByte, _env: JNIEnv<'_>) -> Self {
+        java.0
+    }
+}
+
+impl FromRustToJava<'_, i8> for JavaByte {
+    fn rust_to_java(rust: i8, _env: JNIEnv<'_>) -> Self {
+        JavaByte(rust)
+    }
+}
+
 /// Char
 ///
 /// Chars are generally going to be bad from Rust to Java, always best to just use Strings.
-/// jchar is just a u16, which can't encode the same space as Rust...
+/// jchar is just a u16, which can't encode the same space as Rust, see `chars::CharStrategy` for the chars that don't convert.
 #[derive(Clone, Copy, Debug, Default)]
 #[repr(transparent)]
 pub struct JavaChar(pub jni::sys::jchar);
 
 impl FromJavaToRust<'_, JavaChar> for char {
     fn java_to_rust(java: JavaChar, _env: JNIEnv<'_>) -> Self {
-        let ch = java.0 as u32;
-        unsafe { char::from_u32_unchecked(ch) }
+        chars::to_rust_char(java.0)
     }
 }
 
 impl FromRustToJava<'_, char> for JavaChar {
     fn rust_to_java(rust: char, _env: JNIEnv<'_>) -> Self {
-        JavaChar(rust as u32 as u16)
+        JavaChar(chars::to_java_char(rust))
+    }
+}
+
+/// The UTF-16 code unit, unchanged
+impl FromJavaToRust<'_, JavaChar> for u16 {
+    fn java_to_rust(java: JavaChar, _env: JNIEnv<'_>) -> Self {
+        java.0
+    }
+}
+
+impl FromRustToJava<'_, u16> for JavaChar {
+    fn rust_to_java(rust: u16, _env: JNIEnv<'_>) -> Self {
+        JavaChar(rust)
+    }
+}
+
+impl TryFrom<JavaChar> for char {
+    type Error = chars::CharError;
+
+    fn try_from(java: JavaChar) -> Result<Self, Self::Error> {
+        chars::try_to_rust_char(java.0)
+    }
+}
+
+impl TryFrom<char> for JavaChar {
+    type Error = chars::CharError;
+
+    fn try_from(rust: char) -> Result<Self, Self::Error> {
+        chars::try_to_java_char(rust).map(JavaChar)
     }
 }
 
@@ -203,6 +326,19 @@ impl FromRustToJava<'_, bool> for JavaBoolean {
     }
 }
 
+/// The `jboolean`, any value other than `0` is `true` in Java
+impl FromJavaToRust<'_, JavaBoolean> for u8 {
+    fn java_to_rust(java: JavaBoolean, _env: JNIEnv<'_>) -> Self {
+        java.0
+    }
+}
+
+impl FromRustToJava<'_, u8> for JavaBoolean {
+    fn rust_to_java(rust: u8, _env: JNIEnv<'_>) -> Self {
+        JavaBoolean(rust)
+    }
+}
+
 /// Void
 #[derive(Clone, Copy, Debug, Default)]
 #[repr(transparent)]
@@ -218,41 +354,45 @@ impl FromRustToJava<'_, ()> for JavaVoid {
     }
 }
 
-/// Strings
-impl<'j, J> FromJavaToRust<'j, J> for String
-where
-    J: 'j + Deref<Target = JObject<'j>>,
-{
-    // TODO: there's probably a somewhat cheaper option to reduce all the allocations here.
-    fn java_to_rust(java: J, env: JNIEnv<'j>) -> Self {
-        // We're going to have Java properly return utf-8 bytes from a String rather than the BS that is the "reduced utf-8" in JNI
-        let utf8_arg = env
-            .new_string("UTF-8")
-            .expect("Java couldn't allocate a simple string");
+/// Strings, converted with the [`string::strategy`]
+impl<'j> FromJavaToRust<'j, JString<'j>> for String {
+    fn java_to_rust(java: JString<'j>, env: JNIEnv<'j>) -> Self {
+        string::to_rust_string(env, java)
+    }
+}
 
-        // TODO: cache the method_id...
-        let byte_array = env
-            .call_method(
-                *java,
-                "getBytes",
-                "(Ljava/lang/String;)[B",
-                &[JValue::Object(utf8_arg.into())],
-            )
-            .expect("couldn't call a standard method in Java");
-        let byte_array = byte_array
-            .l()
-            .expect("should have been a JObject of a byte array");
+/// Nullable Strings, `None` for `null`
+impl<'j> FromJavaToRust<'j, JString<'j>> for Option<String> {
+    fn java_to_rust(java: JString<'j>, env: JNIEnv<'j>) -> Self {
+        if java.is_null() {
+            None
+        } else {
+            Some(string::to_rust_string(env, java))
+        }
+    }
+}
 
-        let bytes = env
-            .convert_byte_array(*byte_array)
-            .expect("the byte_array from previous call was bad");
+macro_rules! identity_object {
+    ($jtype: ident) => {
+        impl<'j> FromJavaToRust<'j, $jtype<'j>> for $jtype<'j> {
+            fn java_to_rust(java: $jtype<'j>, _env: JNIEnv<'j>) -> Self {
+                java
+            }
+        }
 
-        // Java should really not have returned bad UTF-8
-        unsafe { String::from_utf8_unchecked(bytes) }
-    }
+        impl<'j> FromRustToJava<'j, $jtype<'j>> for $jtype<'j> {
+            fn rust_to_java(rust: $jtype<'j>, _env: JNIEnv<'j>) -> Self {
+                rust
+            }
+        }
+    };
 }
 
-trait KnownString: Into<JNIString> {}
+identity_object!(JClass);
+identity_object!(JObject);
+identity_object!(JThrowable);
+
+pub(crate) trait KnownString: Into<JNIString> + AsRef<str> {}
 
 impl KnownString for String {}
 impl KnownString for &'_ str {}
@@ -264,14 +404,35 @@ where
     S: KnownString,
 {
     fn rust_to_java(rust: S, env: JNIEnv<'j>) -> Self {
+        // the modified UTF-8 is encoded in a buffer of the thread, instead of a new CString
+        #[cfg(feature = "scratch")]
+        let string = scratch::new_string(env, rust.as_ref());
+
         // There's basically no "cheap" way to do this
-        env.new_string(rust).expect("bad string sent to Java")
+        #[cfg(not(feature = "scratch"))]
+        let string = env.new_string(rust);
+
+        string.expect("bad string sent to Java")
+    }
+}
+
+/// Nullable Strings, `null` for `None`
+impl<'j, S> FromRustToJava<'j, Option<S>> for JString<'j>
+where
+    S: KnownString,
+{
+    fn rust_to_java(rust: Option<S>, env: JNIEnv<'j>) -> Self {
+        match rust {
+            Some(rust) => Self::rust_to_java(rust, env),
+            None => JString::from(JObject::null()),
+        }
     }
 }
 
 /// Convert from a JValue (return type in Java) into the Rust type
 ///
-/// This is infallible because the generated code using it should "know" that the type is already correct
+/// This is infallible because the generated code using it should "know" that the type is already correct,
+///   see [`TryFromJavaValue`] for the fallible version
 pub trait FromJavaValue<'j, J>: Sized {
     fn from_jvalue(env: JNIEnv<'j>, jvalue: JValue<'j>) -> Self;
 }
@@ -300,7 +461,9 @@ macro_rules! from_java_value {
 }
 
 from_java_value!(JavaByte, u8, b);
+from_java_value!(JavaByte, i8, b);
 from_java_value!(JavaChar, char, c);
+from_java_value!(JavaChar, u16, c);
 from_java_value!(JavaDouble, f64, d);
 from_java_value!(JavaFloat, f32, f);
 from_java_value!(JavaInt, i32, i);
@@ -308,6 +471,95 @@ from_java_value!(JavaLong, i64, j);
 from_java_value!(JavaShort, i16, s);
 from_java_value!(JavaVoid, (), v);
 
+/// `JValue::z` has already converted the `jboolean`
+impl<'j> FromJavaValue<'j, JavaBoolean> for bool {
+    fn from_jvalue(_env: JNIEnv<'j>, jvalue: JValue<'j>) -> Self {
+        jvalue.z().expect("wrong type conversion")
+    }
+}
+
+impl<'j> FromJavaValue<'j, JavaBoolean> for u8 {
+    fn from_jvalue(_env: JNIEnv<'j>, jvalue: JValue<'j>) -> Self {
+        u8::from(jvalue.z().expect("wrong type conversion"))
+    }
+}
+
+/// Convert from a JValue into the Rust type, an error if the value isn't of the Java type `J`
+///
+/// The fallible version of [`FromJavaValue`], e.g. for a JValue returned by a method with a different signature than expected
+pub trait TryFromJavaValue<'j, J>: Sized {
+    fn try_from_jvalue(env: JNIEnv<'j>, jvalue: JValue<'j>) -> Result<Self, jni::errors::Error>;
+}
+
+impl<'j, T, J> TryFromJavaValue<'j, J> for T
+where
+    T: FromJavaToRust<'j, J>,
+    J: 'j,
+    J: From<JObject<'j>>,
+{
+    fn try_from_jvalue(env: JNIEnv<'j>, jvalue: JValue<'j>) -> Result<Self, jni::errors::Error> {
+        let object = jvalue.l()?;
+        Ok(Self::java_to_rust(object.into(), env))
+    }
+}
+
+macro_rules! try_from_java_value {
+    ($jtype: ident, $rtype:ty, $jval_func: ident) => {
+        impl<'j> TryFromJavaValue<'j, $jtype> for $rtype {
+            fn try_from_jvalue(
+                env: JNIEnv<'j>,
+                jvalue: JValue<'j>,
+            ) -> Result<Self, jni::errors::Error> {
+                let t = $jtype(jvalue.$jval_func()?);
+                Ok(Self::java_to_rust(t, env))
+            }
+        }
+    };
+}
+
+try_from_java_value!(JavaByte, u8, b);
+try_from_java_value!(JavaByte, i8, b);
+try_from_java_value!(JavaChar, char, c);
+try_from_java_value!(JavaChar, u16, c);
+try_from_java_value!(JavaDouble, f64, d);
+try_from_java_value!(JavaFloat, f32, f);
+try_from_java_value!(JavaInt, i32, i);
+try_from_java_value!(JavaLong, i64, j);
+try_from_java_value!(JavaShort, i16, s);
+try_from_java_value!(JavaVoid, (), v);
+
+impl<'j> TryFromJavaValue<'j, JavaBoolean> for bool {
+    fn try_from_jvalue(_env: JNIEnv<'j>, jvalue: JValue<'j>) -> Result<Self, jni::errors::Error> {
+        jvalue.z()
+    }
+}
+
+impl<'j> TryFromJavaValue<'j, JavaBoolean> for u8 {
+    fn try_from_jvalue(_env: JNIEnv<'j>, jvalue: JValue<'j>) -> Result<Self, jni::errors::Error> {
+        jvalue.z().map(u8::from)
+    }
+}
+
+/// Converts the result of a call made by the generated wrappers
+///
+/// In debug builds, or with the `checked_values` feature, a value of the wrong type throws a `java/lang/ClassCastException` and
+///   panics, so the exception is thrown to the Java caller of the native method. Otherwise this is [`FromJavaValue`], which aborts
+///   on a mismatch.
+#[track_caller]
+pub fn from_jvalue_checked<'j, J, T>(env: JNIEnv<'j>, jvalue: JValue<'j>) -> T
+where
+    T: FromJavaValue<'j, J> + TryFromJavaValue<'j, J>,
+{
+    if cfg!(any(debug_assertions, feature = "checked_values")) {
+        match T::try_from_jvalue(env, jvalue) {
+            Ok(value) => value,
+            Err(e) => exceptions::throw_class_cast(env, e),
+        }
+    } else {
+        T::from_jvalue(env, jvalue)
+    }
+}
+
 /// Convert from Rust type into JValue
 pub trait IntoJavaValue<'j, J: 'j> {
     fn into_java_value(self, env: JNIEnv<'j>) -> JValue<'j>;
@@ -326,6 +578,23 @@ where
     }
 }
 
+/// Convert from Rust type into JValue, an error if Java rejects the value
+///
+/// The fallible version of [`IntoJavaValue`], e.g. for the name of an enum constant, which `valueOf` may not know
+pub trait TryIntoJavaValue<'j, J: 'j> {
+    fn try_into_java_value(self, env: JNIEnv<'j>) -> Result<JValue<'j>, jni::errors::Error>;
+}
+
+impl<'j, J, R> TryIntoJavaValue<'j, J> for R
+where
+    J: 'j,
+    R: IntoJavaValue<'j, J>,
+{
+    fn try_into_java_value(self, env: JNIEnv<'j>) -> Result<JValue<'j>, jni::errors::Error> {
+        Ok(self.into_java_value(env))
+    }
+}
+
 macro_rules! into_java_value {
     ($jtype: ident, $rtype:ty) => {
         impl IntoJavaValue<'_, $jtype> for $rtype {
@@ -338,7 +607,9 @@ macro_rules! into_java_value {
 }
 
 into_java_value!(JavaByte, u8);
+into_java_value!(JavaByte, i8);
 into_java_value!(JavaChar, char);
+into_java_value!(JavaChar, u16);
 into_java_value!(JavaDouble, f64);
 into_java_value!(JavaFloat, f32);
 into_java_value!(JavaInt, i32);
@@ -346,6 +617,18 @@ into_java_value!(JavaLong, i64);
 into_java_value!(JavaShort, i16);
 into_java_value!(JavaVoid, ());
 
+impl IntoJavaValue<'_, JavaBoolean> for bool {
+    fn into_java_value(self, env: JNIEnv<'_>) -> JValue<'_> {
+        JValue::Bool(JavaBoolean::rust_to_java(self, env).0)
+    }
+}
+
+impl IntoJavaValue<'_, JavaBoolean> for u8 {
+    fn into_java_value(self, env: JNIEnv<'_>) -> JValue<'_> {
+        JValue::Bool(JavaBoolean::rust_to_java(self, env).0)
+    }
+}
+
 macro_rules! java_primitive {
     ($jtype: ty) => {
         impl JavaPrimitive for $jtype {}
@@ -375,6 +658,7 @@ macro_rules! null_object {
     };
 }
 
+null_object!(JavaBoolean);
 null_object!(JavaByte);
 null_object!(JavaChar);
 null_object!(JavaDouble);
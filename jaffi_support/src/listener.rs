@@ -0,0 +1,421 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Java listeners, i.e. interfaces with a single abstract method, implemented by Rust closures.
+//!
+//! A [`Listener`] is a `java.lang.reflect.Proxy` of the interface, whose `InvocationHandler` calls the closure. The handler is
+//!   a small class defined on first use with `DefineClass`, in a class loader of its own for each library, so no Java code needs
+//!   to be shipped; this isn't supported on Android. The proxy is kept with a registered global reference, which is released
+//!   when the library is unloaded, pass it to the Java `addListener` with [`Listener::get`]. Dropping the [`Listener`] releases
+//!   the closure, later calls from Java throw an `IllegalStateException`. Use [`Listener::unregister_on_drop`] to also remove it
+//!   from Java when the guard is dropped. The `listener_interfaces` option of the generator adds `from_fn` to the wrappers of the
+//!   interfaces, which converts the arguments and the result of the closure.
+
+use std::{
+    collections::HashMap,
+    ffi::c_void,
+    fmt,
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use jni::{
+    errors::Error,
+    objects::{JClass, JObject, JValue},
+    sys::{jobject, jobjectArray},
+    JNIEnv, NativeMethod,
+};
+
+use crate::{exceptions::catch_panic_and_throw, global::RegisteredGlobal};
+
+/// The closure of a listener, called with the arguments of the interface method, the primitives are boxed
+///
+/// It returns the result of the method, boxed for primitives and `null` for `void`.
+pub type ListenerFn =
+    dyn for<'a> Fn(JNIEnv<'a>, &[JObject<'a>]) -> Result<JObject<'a>, Error> + Send + Sync;
+
+const HANDLER_CLASS: &str = "net/bluejekyll/jaffi/ListenerHandler";
+const INVOKE_SIG: &str =
+    "(Ljava/lang/Object;Ljava/lang/reflect/Method;[Ljava/lang/Object;)Ljava/lang/Object;";
+
+static NEXT_ID: AtomicI64 = AtomicI64::new(1);
+static LISTENERS: Mutex<Option<HashMap<i64, Arc<ListenerFn>>>> = Mutex::new(None);
+static HANDLER: Mutex<Option<RegisteredGlobal>> = Mutex::new(None);
+
+fn listeners<R>(f: impl FnOnce(&mut HashMap<i64, Arc<ListenerFn>>) -> R) -> R {
+    let mut listeners = LISTENERS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(listeners.get_or_insert_with(HashMap::new))
+}
+
+/// A Java proxy of an interface implemented by a Rust closure
+///
+/// The closure is released on drop, the proxy then throws an `IllegalStateException` when it's called.
+pub struct Listener {
+    id: i64,
+    proxy: RegisteredGlobal,
+}
+
+impl Listener {
+    /// Implements the interface, specified as a class descriptor, i.e. `java/lang/Runnable`, with the closure
+    ///
+    /// The closure is called for all the methods other than `equals`, `hashCode` and `toString`, which are implemented with the
+    ///   identity of the proxy.
+    pub fn new<F>(env: JNIEnv<'_>, interface: &str, f: F) -> Result<Self, Error>
+    where
+        F: for<'a> Fn(JNIEnv<'a>, &[JObject<'a>]) -> Result<JObject<'a>, Error>
+            + Send
+            + Sync
+            + 'static,
+    {
+        let handler_class = handler_class(env)?;
+        let interface = crate::class_loader::find_class(env, interface)?;
+
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        listeners(|listeners| listeners.insert(id, Arc::new(f)));
+
+        let handler = env.new_object(handler_class, "(J)V", &[JValue::Long(id)]);
+        let proxy = handler.and_then(|handler| {
+            let loader = env
+                .call_method(interface, "getClassLoader", "()Ljava/lang/ClassLoader;", &[])?
+                .l()?;
+            let interfaces = env.new_object_array(1, "java/lang/Class", interface)?;
+            env.call_static_method(
+                "java/lang/reflect/Proxy",
+                "newProxyInstance",
+                "(Ljava/lang/ClassLoader;[Ljava/lang/Class;Ljava/lang/reflect/InvocationHandler;)Ljava/lang/Object;",
+                &[
+                    JValue::Object(loader),
+                    JValue::Object(interfaces.into()),
+                    JValue::Object(handler),
+                ],
+            )?
+            .l()
+        });
+
+        match proxy.and_then(|proxy| RegisteredGlobal::new(env, proxy)) {
+            Ok(proxy) => Ok(Self { id, proxy }),
+            Err(e) => {
+                listeners(|listeners| listeners.remove(&id));
+                Err(e)
+            }
+        }
+    }
+
+    /// A new local reference to the proxy, as the wrapper of the interface, e.g. to pass to the method registering the listener
+    ///
+    /// This fails after the library has been unloaded, the proxy is then released.
+    pub fn get<'j, T: From<JObject<'j>>>(&self, env: JNIEnv<'j>) -> Result<T, Error> {
+        self.proxy
+            .local(env)?
+            .map(T::from)
+            .ok_or(Error::NullPtr("the proxy of the listener is released"))
+    }
+
+    /// A guard that calls `unregister` with the proxy when it's dropped, e.g. to call the Java `removeListener`, and then
+    ///   releases the closure
+    pub fn unregister_on_drop<'j, F>(self, env: JNIEnv<'j>, unregister: F) -> ListenerGuard<'j, F>
+    where
+        F: FnOnce(JNIEnv<'j>, JObject<'j>),
+    {
+        ListenerGuard {
+            env,
+            listener: self,
+            unregister: Some(unregister),
+        }
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        listeners(|listeners| listeners.remove(&self.id));
+    }
+}
+
+impl fmt::Debug for Listener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Listener").field("id", &self.id).finish()
+    }
+}
+
+/// Unregisters the [`Listener`] when it's dropped, see [`Listener::unregister_on_drop`]
+pub struct ListenerGuard<'j, F>
+where
+    F: FnOnce(JNIEnv<'j>, JObject<'j>),
+{
+    env: JNIEnv<'j>,
+    listener: Listener,
+    unregister: Option<F>,
+}
+
+impl<'j, F> ListenerGuard<'j, F>
+where
+    F: FnOnce(JNIEnv<'j>, JObject<'j>),
+{
+    /// The listener, e.g. to pass to the method registering it
+    pub fn listener(&self) -> &Listener {
+        &self.listener
+    }
+}
+
+impl<'j, F> Drop for ListenerGuard<'j, F>
+where
+    F: FnOnce(JNIEnv<'j>, JObject<'j>),
+{
+    fn drop(&mut self) {
+        let Some(unregister) = self.unregister.take() else {
+            return;
+        };
+
+        // a local reference of the env's frame, the global reference is deleted with the listener
+        if let Ok(proxy) = self.listener.get(self.env) {
+            unregister(self.env, proxy);
+        }
+    }
+}
+
+/// The handler class, defined and registered on first use, and again after the library is loaded again
+fn handler_class<'j>(env: JNIEnv<'j>) -> Result<JClass<'j>, Error> {
+    let mut handler = HANDLER
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(handler) = &*handler {
+        if let Some(class) = handler.local(env)? {
+            return Ok(JClass::from(class));
+        }
+    }
+
+    // each load of each library defines the class in a new loader, the native method of a defined class is never replaced
+    let urls = env.new_object_array(0, "java/net/URL", JObject::null())?;
+    let loader = env.new_object(
+        "java/net/URLClassLoader",
+        "([Ljava/net/URL;)V",
+        &[JValue::Object(urls.into())],
+    )?;
+    let class = env.define_class(HANDLER_CLASS, loader, &handler_class_file())?;
+    env.register_native_methods(
+        class,
+        &[NativeMethod {
+            name: "invoke".into(),
+            sig: INVOKE_SIG.into(),
+            fn_ptr: invoke as *mut c_void,
+        }],
+    )?;
+
+    *handler = Some(RegisteredGlobal::new(env, *class)?);
+    Ok(class)
+}
+
+/// `ListenerHandler.invoke`, calls the closure of the `id` of the handler
+extern "system" fn invoke<'j>(
+    env: JNIEnv<'j>,
+    this: JObject<'j>,
+    proxy: JObject<'j>,
+    method: JObject<'j>,
+    args: jobjectArray,
+) -> jobject {
+    catch_panic_and_throw(env, || {
+        let result = call_listener(env, this, proxy, method, args);
+        match result {
+            Ok(result) => result,
+            Err(Error::JavaException) => JObject::null(),
+            Err(e) => {
+                env.throw_new("java/lang/RuntimeException", e.to_string())
+                    .expect("failed to throw exception");
+                JObject::null()
+            }
+        }
+    })
+    .into_inner()
+}
+
+fn call_listener<'j>(
+    env: JNIEnv<'j>,
+    this: JObject<'j>,
+    proxy: JObject<'j>,
+    method: JObject<'j>,
+    args: jobjectArray,
+) -> Result<JObject<'j>, Error> {
+    let name = env
+        .call_method(method, "getName", "()Ljava/lang/String;", &[])?
+        .l()?;
+    let name = String::from(env.get_string(name.into())?);
+    let declaring_class = env
+        .call_method(method, "getDeclaringClass", "()Ljava/lang/Class;", &[])?
+        .l()?;
+
+    let args = if args.is_null() {
+        Vec::new()
+    } else {
+        (0..env.get_array_length(args)?)
+            .map(|i| env.get_object_array_element(args, i))
+            .collect::<Result<Vec<_>, _>>()?
+    };
+
+    // the methods of Object are called on the proxy as well
+    let object_class = env.find_class("java/lang/Object")?;
+    if env.is_same_object(declaring_class, object_class)? {
+        return match (name.as_str(), args.as_slice()) {
+            ("equals", [other]) => {
+                box_value(env, JValue::Bool(env.is_same_object(proxy, *other)?.into()))
+            }
+            ("hashCode", []) => box_value(
+                env,
+                JValue::Int(crate::identity::identity_hash_code(env, proxy)?),
+            ),
+            ("toString", []) => {
+                let hash_code = crate::identity::identity_hash_code(env, proxy)?;
+                Ok(env
+                    .new_string(format!("RustListener@{hash_code:x}"))?
+                    .into())
+            }
+            _ => Err(Error::MethodNotFound {
+                name,
+                sig: String::new(),
+            }),
+        };
+    }
+
+    let id = env.get_field(this, "id", "J")?.j()?;
+    let Some(listener) = listeners(|listeners| listeners.get(&id).cloned()) else {
+        env.throw_new(
+            "java/lang/IllegalStateException",
+            format!("the Rust listener was dropped, {name} can't be called"),
+        )?;
+        return Err(Error::JavaException);
+    };
+
+    listener(env, &args)
+}
+
+/// Unboxes an argument of the closure of a listener, for the type of the descriptor, i.e. `I` for an `int`
+///
+/// Objects and arrays are returned as is.
+pub fn unbox<'a>(env: JNIEnv<'a>, obj: JObject<'a>, descriptor: &str) -> Result<JValue<'a>, Error> {
+    let (method, sig) = match descriptor {
+        "Z" => ("booleanValue", "()Z"),
+        "B" => ("byteValue", "()B"),
+        "C" => ("charValue", "()C"),
+        "S" => ("shortValue", "()S"),
+        "I" => ("intValue", "()I"),
+        "J" => ("longValue", "()J"),
+        "F" => ("floatValue", "()F"),
+        "D" => ("doubleValue", "()D"),
+        _ => return Ok(JValue::Object(obj)),
+    };
+
+    env.call_method(obj, method, sig, &[])
+}
+
+/// Boxes the result of the closure of a listener, `null` for `void`
+pub fn box_value<'a>(env: JNIEnv<'a>, value: JValue<'a>) -> Result<JObject<'a>, Error> {
+    let (class, sig) = match value {
+        JValue::Object(obj) => return Ok(obj),
+        JValue::Void => return Ok(JObject::null()),
+        JValue::Bool(_) => ("java/lang/Boolean", "(Z)Ljava/lang/Boolean;"),
+        JValue::Byte(_) => ("java/lang/Byte", "(B)Ljava/lang/Byte;"),
+        JValue::Char(_) => ("java/lang/Character", "(C)Ljava/lang/Character;"),
+        JValue::Short(_) => ("java/lang/Short", "(S)Ljava/lang/Short;"),
+        JValue::Int(_) => ("java/lang/Integer", "(I)Ljava/lang/Integer;"),
+        JValue::Long(_) => ("java/lang/Long", "(J)Ljava/lang/Long;"),
+        JValue::Float(_) => ("java/lang/Float", "(F)Ljava/lang/Float;"),
+        JValue::Double(_) => ("java/lang/Double", "(D)Ljava/lang/Double;"),
+    };
+
+    env.call_static_method(class, "valueOf", sig, &[value])?.l()
+}
+
+/// The class file of `ListenerHandler`, a `final` `InvocationHandler` with a `long id`, and the native `invoke`
+///
+/// ```java
+/// public final class ListenerHandler implements InvocationHandler {
+///     private final long id;
+///
+///     public ListenerHandler(long id) { this.id = id; }
+///
+///     public native Object invoke(Object proxy, Method method, Object[] args);
+/// }
+/// ```
+fn handler_class_file() -> Vec<u8> {
+    fn utf8(class: &mut Vec<u8>, s: &str) {
+        class.push(1);
+        class.extend((s.len() as u16).to_be_bytes());
+        class.extend(s.as_bytes());
+    }
+    fn u16s(class: &mut Vec<u8>, values: &[u16]) {
+        for value in values {
+            class.extend(value.to_be_bytes());
+        }
+    }
+
+    let mut class = vec![0xCA, 0xFE, 0xBA, 0xBE];
+    // Java 6, the last version without a `StackMapTable`
+    u16s(&mut class, &[0, 50]);
+
+    // the constant pool
+    u16s(&mut class, &[19]);
+    utf8(&mut class, HANDLER_CLASS); // 1
+    class.push(7); // 2, this class
+    u16s(&mut class, &[1]);
+    utf8(&mut class, "java/lang/Object"); // 3
+    class.push(7); // 4, super class
+    u16s(&mut class, &[3]);
+    utf8(&mut class, "java/lang/reflect/InvocationHandler"); // 5
+    class.push(7); // 6, interface
+    u16s(&mut class, &[5]);
+    utf8(&mut class, "id"); // 7
+    utf8(&mut class, "J"); // 8
+    utf8(&mut class, "<init>"); // 9
+    utf8(&mut class, "(J)V"); // 10
+    utf8(&mut class, "()V"); // 11
+    class.push(12); // 12, Object.<init>()V
+    u16s(&mut class, &[9, 11]);
+    class.push(10); // 13, Methodref Object.<init>
+    u16s(&mut class, &[4, 12]);
+    class.push(12); // 14, id:J
+    u16s(&mut class, &[7, 8]);
+    class.push(9); // 15, Fieldref id
+    u16s(&mut class, &[2, 14]);
+    utf8(&mut class, "Code"); // 16
+    utf8(&mut class, "invoke"); // 17
+    utf8(&mut class, INVOKE_SIG); // 18
+
+    // public final super, this, super, one interface
+    u16s(&mut class, &[0x0031, 2, 4, 1, 6]);
+
+    // private final long id
+    u16s(&mut class, &[1, 0x0012, 7, 8, 0]);
+
+    // the constructor and invoke
+    u16s(&mut class, &[2]);
+    let code = [
+        0x2a, // aload_0
+        0xb7, 0x00, 0x0d, // invokespecial Object.<init>
+        0x2a, // aload_0
+        0x1f, // lload_1
+        0xb5, 0x00, 0x0f, // putfield id
+        0xb1, // return
+    ];
+    u16s(&mut class, &[0x0001, 9, 10, 1, 16]);
+    class.extend((12 + code.len() as u32).to_be_bytes());
+    // max_stack, max_locals
+    u16s(&mut class, &[3, 3]);
+    class.extend((code.len() as u32).to_be_bytes());
+    class.extend(code);
+    // no exception table or attributes
+    u16s(&mut class, &[0, 0]);
+
+    // public native invoke
+    u16s(&mut class, &[0x0101, 17, 18, 0]);
+
+    // no class attributes
+    u16s(&mut class, &[0]);
+    class
+}
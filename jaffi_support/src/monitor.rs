@@ -0,0 +1,27 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Monitors of Java objects, the equivalent of `synchronized (obj) { ... }` blocks in Java.
+//!
+//! The generated object wrappers have a `lock` method that returns a [`MonitorGuard`].
+
+use jni::{errors::Error, objects::JObject, JNIEnv};
+
+/// Holds the monitor of a Java object, the monitor is exited when this is dropped, see `JNIEnv::lock_obj`
+///
+/// Monitors are reentrant, and are held by the current thread, the guard is neither `Send` nor `Sync`.
+pub use jni::MonitorGuard;
+
+/// Enters the monitor of the object, blocking until it's available
+///
+/// # Panics
+///
+/// If the object is `null`
+pub fn lock<'j>(env: JNIEnv<'j>, obj: JObject<'j>) -> Result<MonitorGuard<'j>, Error> {
+    assert!(!obj.is_null(), "null object locked from Rust");
+    env.lock_obj(obj)
+}
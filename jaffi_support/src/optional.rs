@@ -0,0 +1,149 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! `java.util.Optional`, converted to and from Rust `Option`s
+
+use std::{marker::PhantomData, ops::Deref};
+
+use jni::{
+    objects::{JObject, JValue},
+    signature::{JavaType, Primitive},
+    JNIEnv,
+};
+
+use crate::{
+    cache::{MethodIdCache, StaticMethodIdCache},
+    FromJavaToRust, FromRustToJava,
+};
+
+static IS_PRESENT: MethodIdCache = MethodIdCache::new("java/util/Optional", "isPresent", "()Z");
+static GET: MethodIdCache = MethodIdCache::new("java/util/Optional", "get", "()Ljava/lang/Object;");
+static EMPTY: StaticMethodIdCache =
+    StaticMethodIdCache::new("java/util/Optional", "empty", "()Ljava/util/Optional;");
+static OF_NULLABLE: StaticMethodIdCache = StaticMethodIdCache::new(
+    "java/util/Optional",
+    "ofNullable",
+    "(Ljava/lang/Object;)Ljava/util/Optional;",
+);
+
+/// A `java.util.Optional` of the Java type `J`, the type comes from the generic signature of the method
+#[repr(transparent)]
+pub struct JOptional<'j, J> {
+    obj: JObject<'j>,
+    element: PhantomData<J>,
+}
+
+impl<'j, J> Clone for JOptional<'j, J> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'j, J> Copy for JOptional<'j, J> {}
+
+impl<'j, J> Deref for JOptional<'j, J> {
+    type Target = JObject<'j>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.obj
+    }
+}
+
+impl<'j, J> From<JObject<'j>> for JOptional<'j, J> {
+    fn from(obj: JObject<'j>) -> Self {
+        Self {
+            obj,
+            element: PhantomData,
+        }
+    }
+}
+
+impl<'j, J> From<JOptional<'j, J>> for JObject<'j> {
+    fn from(optional: JOptional<'j, J>) -> Self {
+        optional.obj
+    }
+}
+
+/// A `null` Optional is also `None`
+impl<'j, J, T> FromJavaToRust<'j, JOptional<'j, J>> for Option<T>
+where
+    J: 'j + From<JObject<'j>>,
+    T: FromJavaToRust<'j, J>,
+{
+    fn java_to_rust(java: JOptional<'j, J>, env: JNIEnv<'j>) -> Self {
+        if java.is_null() {
+            return None;
+        }
+
+        let is_present = IS_PRESENT
+            .get(env)
+            .and_then(|method| {
+                env.call_method_unchecked(
+                    java.obj,
+                    method,
+                    JavaType::Primitive(Primitive::Boolean),
+                    &[],
+                )
+            })
+            .and_then(|v| v.z())
+            .expect("couldn't call a standard method in Java");
+
+        if !is_present {
+            return None;
+        }
+
+        let element = GET
+            .get(env)
+            .and_then(|method| {
+                env.call_method_unchecked(
+                    java.obj,
+                    method,
+                    JavaType::Object("java/lang/Object".to_string()),
+                    &[],
+                )
+            })
+            .and_then(|v| v.l())
+            .expect("couldn't call a standard method in Java");
+
+        Some(T::java_to_rust(J::from(element), env))
+    }
+}
+
+impl<'j, J, T> FromRustToJava<'j, Option<T>> for JOptional<'j, J>
+where
+    J: FromRustToJava<'j, T> + Into<JObject<'j>>,
+{
+    fn rust_to_java(rust: Option<T>, env: JNIEnv<'j>) -> Self {
+        let optional = match rust {
+            Some(rust) => {
+                let element: JObject<'j> = J::rust_to_java(rust, env).into();
+                OF_NULLABLE.get(env).and_then(|method| {
+                    env.call_static_method_unchecked(
+                        OF_NULLABLE.class(),
+                        method,
+                        JavaType::Object(OF_NULLABLE.class().to_string()),
+                        &[JValue::Object(element)],
+                    )
+                })
+            }
+            None => EMPTY.get(env).and_then(|method| {
+                env.call_static_method_unchecked(
+                    EMPTY.class(),
+                    method,
+                    JavaType::Object(EMPTY.class().to_string()),
+                    &[],
+                )
+            }),
+        };
+
+        let optional = optional
+            .and_then(|v| v.l())
+            .expect("couldn't call a standard method in Java");
+
+        Self::from(optional)
+    }
+}
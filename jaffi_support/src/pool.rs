@@ -0,0 +1,313 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Calls into Java from async Rust, on a pool of threads attached to the JVM.
+//!
+//! Java calls block the thread, and the `JNIEnv` can't be sent between threads, so an async task can't call Java without stalling
+//!   its executor. [`JavaPool::call`] runs a closure with the `JNIEnv` of one of the pool's threads and returns a [`JavaCall`]
+//!   future of its result. The generated `_async` methods of the global wrappers, from the `async_wrappers` option, use this.
+//!
+//! ```no_run
+//! # use jaffi_support::{jni::JNIEnv, pool::JavaPool};
+//! # async fn example(env: JNIEnv<'_>) {
+//! let pool = JavaPool::from_env(env, 4).expect("no pool");
+//! let version = pool
+//!     .call(|env| {
+//!         let version = env.new_string("java.version").unwrap();
+//!         let version = env
+//!             .call_static_method(
+//!                 "java/lang/System",
+//!                 "getProperty",
+//!                 "(Ljava/lang/String;)Ljava/lang/String;",
+//!                 &[version.into()],
+//!             )
+//!             .and_then(|version| version.l())
+//!             .unwrap();
+//!         String::from(env.get_string(version.into()).unwrap())
+//!     })
+//!     .await;
+//! # }
+//! ```
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    future::Future,
+    io,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    task::{Context, Poll, Waker},
+    thread,
+};
+
+use jni::{objects::JObject, JNIEnv, JavaVM};
+
+use crate::{exceptions::get_panic_message, frame::DEFAULT_CAPACITY};
+
+type Job = Box<dyn for<'j> FnOnce(JNIEnv<'j>) + Send + 'static>;
+
+struct Queue {
+    jobs: VecDeque<Job>,
+    shutdown: bool,
+}
+
+struct Shared {
+    queue: Mutex<Queue>,
+    available: Condvar,
+}
+
+impl Shared {
+    fn queue(&self) -> MutexGuard<'_, Queue> {
+        // the jobs run outside of the lock, a panic can't leave the queue in a bad state
+        self.queue
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// A pool of threads attached to the JVM, running the Java calls of async Rust code
+///
+/// The threads are attached as daemons, so they don't keep the JVM from exiting. Each call runs in its own local frame, the local
+///   references it creates are released when it returns. Dropping the pool lets the threads finish the queued calls and exit.
+pub struct JavaPool {
+    shared: Arc<Shared>,
+}
+
+impl JavaPool {
+    /// Starts `threads` threads, at least one, attached to the JVM
+    pub fn new(vm: JavaVM, threads: usize) -> io::Result<Self> {
+        let vm = Arc::new(vm);
+        let shared = Arc::new(Shared {
+            queue: Mutex::new(Queue {
+                jobs: VecDeque::new(),
+                shutdown: false,
+            }),
+            available: Condvar::new(),
+        });
+
+        for i in 0..threads.max(1) {
+            let vm = Arc::clone(&vm);
+            let shared = Arc::clone(&shared);
+            thread::Builder::new()
+                .name(format!("jaffi-pool-{i}"))
+                .spawn(move || run(&vm, &shared))?;
+        }
+
+        Ok(Self { shared })
+    }
+
+    /// Starts the pool with the `JavaVM` of the env, e.g. from the `user_on_load_fn`
+    pub fn from_env(env: JNIEnv<'_>, threads: usize) -> Result<Self, PoolError> {
+        let vm = env.get_java_vm().map_err(PoolError::Jni)?;
+        Self::new(vm, threads).map_err(PoolError::Spawn)
+    }
+
+    /// Runs `f` on one of the pool's threads, the returned future resolves to its result
+    ///
+    /// The result must not hold local references, they are released when `f` returns, use `register_global` to return objects.
+    pub fn call<T, F>(&self, f: F) -> JavaCall<T>
+    where
+        T: Send + 'static,
+        F: for<'j> FnOnce(JNIEnv<'j>) -> T + Send + 'static,
+    {
+        let slot = Arc::new(Mutex::new(Slot {
+            result: None,
+            waker: None,
+        }));
+        let completer = Completer(Arc::clone(&slot));
+
+        let mut queue = self.shared.queue();
+        if queue.shutdown {
+            drop(queue);
+            completer.complete(Err(CallError::ShutDown));
+        } else {
+            queue.jobs.push_back(Box::new(move |env| {
+                let result = panic::catch_unwind(AssertUnwindSafe(|| f(env))).map_err(|payload| {
+                    CallError::Panicked(get_panic_message(&*payload).into_owned())
+                });
+                completer.complete(result);
+            }));
+            drop(queue);
+            self.shared.available.notify_one();
+        }
+
+        JavaCall { slot }
+    }
+
+    /// Stops accepting calls, the threads exit after the calls that are already queued, this doesn't wait for them
+    pub fn shutdown(&self) {
+        self.shared.queue().shutdown = true;
+        self.shared.available.notify_all();
+    }
+}
+
+impl Drop for JavaPool {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+impl fmt::Debug for JavaPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let queue = self.shared.queue();
+        f.debug_struct("JavaPool")
+            .field("queued", &queue.jobs.len())
+            .field("shutdown", &queue.shutdown)
+            .finish()
+    }
+}
+
+/// The loop of each thread of the pool
+fn run(vm: &JavaVM, shared: &Shared) {
+    let env = match vm.attach_current_thread_as_daemon() {
+        Ok(env) => env,
+        Err(e) => {
+            // the other threads may still be able to attach
+            eprintln!("jaffi pool thread couldn't attach to the JVM: {e}");
+            return;
+        }
+    };
+
+    loop {
+        let job = {
+            let mut queue = shared.queue();
+            loop {
+                if let Some(job) = queue.jobs.pop_front() {
+                    break job;
+                }
+                if queue.shutdown {
+                    return;
+                }
+                queue = shared
+                    .available
+                    .wait(queue)
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+            }
+        };
+
+        if env.push_local_frame(DEFAULT_CAPACITY).is_err() {
+            // the job is dropped, its call completes with `ShutDown`
+            eprintln!("jaffi pool thread couldn't push a local frame");
+            continue;
+        }
+
+        job(env);
+
+        // there is no Java caller to throw to on this thread, e.g. after a panic in a wrapper
+        if env.exception_check().unwrap_or(false) {
+            env.exception_describe().ok();
+            env.exception_clear().ok();
+        }
+        env.pop_local_frame(JObject::null()).ok();
+    }
+}
+
+struct Slot<T> {
+    result: Option<Result<T, CallError>>,
+    waker: Option<Waker>,
+}
+
+/// Completes the call, with `ShutDown` if the job is dropped without running
+struct Completer<T>(Arc<Mutex<Slot<T>>>);
+
+impl<T> Completer<T> {
+    fn complete(self, result: Result<T, CallError>) {
+        Self::set(&self.0, result);
+        std::mem::forget(self);
+    }
+
+    fn set(slot: &Mutex<Slot<T>>, result: Result<T, CallError>) {
+        let waker = {
+            let mut slot = slot.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            slot.result = Some(result);
+            slot.waker.take()
+        };
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Drop for Completer<T> {
+    fn drop(&mut self) {
+        Self::set(&self.0, Err(CallError::ShutDown));
+    }
+}
+
+/// The future of a call on a [`JavaPool`]
+///
+/// Dropping it doesn't cancel the call, the result is discarded.
+#[must_use = "the result of the call is only available from the future"]
+pub struct JavaCall<T> {
+    slot: Arc<Mutex<Slot<T>>>,
+}
+
+impl<T> Future for JavaCall<T> {
+    type Output = Result<T, CallError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut slot = self
+            .slot
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        match slot.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                slot.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for JavaCall<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JavaCall").finish_non_exhaustive()
+    }
+}
+
+/// The call didn't return a result
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CallError {
+    /// The call panicked, e.g. on an uncaught Java exception, with the panic message
+    Panicked(String),
+    /// The pool was shut down before the call ran
+    ShutDown,
+}
+
+impl fmt::Display for CallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Panicked(msg) => write!(f, "the Java call panicked: {msg}"),
+            Self::ShutDown => write!(f, "the Java pool was shut down"),
+        }
+    }
+}
+
+impl std::error::Error for CallError {}
+
+/// The pool couldn't be started
+#[derive(Debug)]
+pub enum PoolError {
+    /// The `JavaVM` couldn't be read from the env
+    Jni(jni::errors::Error),
+    /// A thread couldn't be spawned
+    Spawn(io::Error),
+}
+
+impl fmt::Display for PoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Jni(e) => write!(f, "couldn't get the JavaVM: {e}"),
+            Self::Spawn(e) => write!(f, "couldn't spawn a pool thread: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for PoolError {}
@@ -0,0 +1,261 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Methods bound at run time, for classes whose class files aren't available when the bindings are generated.
+//!
+//! The wrappers of the `late_bound_classes` are generated from the declared names and signatures, and look the methods up on
+//!   first use with [`LateBoundMethod`]. A missing class or method is only found at run time, so the generated `bind` checks
+//!   all of them with [`bind`], and the error lists the overloads that Java has, read with reflection.
+
+use std::{
+    fmt, ptr,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+use jni::{
+    objects::{JClass, JMethodID, JObject, JStaticMethodID, JString},
+    sys::_jmethodID,
+    JNIEnv,
+};
+
+use crate::cache::ClassCache;
+
+/// A method of a late-bound class, looked up by name and signature on first use and then cached
+///
+/// Unlike [`crate::cache::MethodIdCache`], a failed lookup clears the `NoSuchMethodError` and returns a [`BindingError`].
+pub struct LateBoundMethod {
+    class: &'static str,
+    name: &'static str,
+    signature: &'static str,
+    is_static: bool,
+    id: AtomicPtr<_jmethodID>,
+}
+
+impl LateBoundMethod {
+    /// Create a new cache for the method, this does not perform the lookup.
+    ///
+    /// # Arguments
+    ///
+    /// * `class` - the class descriptor, e.g. `net/bluejekyll/LateBound`
+    /// * `name` - the name of the method, `<init>` for constructors
+    /// * `signature` - the method signature, e.g. `()I`
+    /// * `is_static` - a static method, looked up with `GetStaticMethodID`
+    pub const fn new(
+        class: &'static str,
+        name: &'static str,
+        signature: &'static str,
+        is_static: bool,
+    ) -> Self {
+        Self {
+            class,
+            name,
+            signature,
+            is_static,
+            id: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    /// Returns the cached id of the instance method or constructor, looking it up in the cached `class` on first use
+    pub fn get_in<'j>(
+        &self,
+        env: JNIEnv<'j>,
+        class: &ClassCache,
+    ) -> Result<JMethodID<'j>, BindingError> {
+        self.lookup(env, class).map(JMethodID::from)
+    }
+
+    /// Returns the cached id of the static method, looking it up in the cached `class` on first use
+    pub fn get_static_in<'j>(
+        &self,
+        env: JNIEnv<'j>,
+        class: &ClassCache,
+    ) -> Result<JStaticMethodID<'j>, BindingError> {
+        self.lookup(env, class).map(JStaticMethodID::from)
+    }
+
+    fn lookup(&self, env: JNIEnv<'_>, class: &ClassCache) -> Result<*mut _jmethodID, BindingError> {
+        let id = self.id.load(Ordering::Acquire);
+        if !id.is_null() {
+            return Ok(id);
+        }
+
+        let class = find_class(env, class)?;
+        let id = if self.is_static {
+            env.get_static_method_id(class, self.name, self.signature)
+                .map(JStaticMethodID::into_inner)
+        } else {
+            env.get_method_id(class, self.name, self.signature)
+                .map(JMethodID::into_inner)
+        };
+
+        let id = match id {
+            Ok(id) => id,
+            Err(jni::errors::Error::MethodNotFound { .. })
+            | Err(jni::errors::Error::JavaException) => {
+                env.exception_clear().map_err(BindingError::Jni)?;
+                let candidates = candidates(env, class, self.name).map_err(BindingError::Jni)?;
+                env.delete_local_ref(*class).ok();
+                return Err(BindingError::Method {
+                    class: self.class.to_string(),
+                    name: self.name.to_string(),
+                    signature: self.signature.to_string(),
+                    candidates,
+                });
+            }
+            Err(e) => return Err(BindingError::Jni(e)),
+        };
+        env.delete_local_ref(*class).map_err(BindingError::Jni)?;
+
+        self.id.store(id, Ordering::Release);
+        Ok(id)
+    }
+
+    /// The class descriptor, e.g. `net/bluejekyll/LateBound`
+    pub fn class(&self) -> &'static str {
+        self.class
+    }
+
+    /// The name of the method, `<init>` for constructors
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The method signature, e.g. `()I`
+    pub fn signature(&self) -> &'static str {
+        self.signature
+    }
+}
+
+/// Looks up the class and each of the methods, returning the first that can't be bound
+///
+/// The generated `bind` of a late-bound wrapper calls this with all the declared methods.
+pub fn bind(
+    env: JNIEnv<'_>,
+    class: &ClassCache,
+    methods: &[LateBoundMethod],
+) -> Result<(), BindingError> {
+    let local = find_class(env, class)?;
+    env.delete_local_ref(*local).map_err(BindingError::Jni)?;
+
+    for method in methods {
+        method.lookup(env, class)?;
+    }
+
+    Ok(())
+}
+
+/// Looks up the class, clearing the `ClassNotFoundException` or `NoClassDefFoundError` if it's missing
+fn find_class<'j>(env: JNIEnv<'j>, class: &ClassCache) -> Result<JClass<'j>, BindingError> {
+    match class.get(env) {
+        Ok(class) => Ok(class),
+        Err(jni::errors::Error::JavaException) => {
+            let exception = env.exception_occurred().map_err(BindingError::Jni)?;
+            env.exception_clear().map_err(BindingError::Jni)?;
+            let message = to_string(env, exception.into()).map_err(BindingError::Jni)?;
+            env.delete_local_ref(*exception).ok();
+            Err(BindingError::Class {
+                class: class.class().to_string(),
+                message,
+            })
+        }
+        Err(e) => Err(BindingError::Jni(e)),
+    }
+}
+
+/// The `toString` of the public methods, or constructors for `<init>`, with the name, read with reflection
+fn candidates(
+    env: JNIEnv<'_>,
+    class: JClass<'_>,
+    name: &str,
+) -> Result<Vec<String>, jni::errors::Error> {
+    let mut candidates = Vec::new();
+    env.with_local_frame(16, || {
+        let (getter, signature) = if name == "<init>" {
+            ("getConstructors", "()[Ljava/lang/reflect/Constructor;")
+        } else {
+            ("getMethods", "()[Ljava/lang/reflect/Method;")
+        };
+        let members = env.call_method(class, getter, signature, &[])?.l()?;
+
+        for i in 0..env.get_array_length(*members)? {
+            let member = env.get_object_array_element(*members, i)?;
+            let member_name = env
+                .call_method(member, "getName", "()Ljava/lang/String;", &[])?
+                .l()?;
+            let member_name = String::from(env.get_string(member_name.into())?);
+            // constructors are named for their class
+            if name == "<init>" || member_name == name {
+                candidates.push(to_string(env, member)?);
+            }
+            env.delete_local_ref(member)?;
+        }
+
+        Ok(JObject::null())
+    })?;
+
+    Ok(candidates)
+}
+
+fn to_string(env: JNIEnv<'_>, obj: JObject<'_>) -> Result<String, jni::errors::Error> {
+    let string = env
+        .call_method(obj, "toString", "()Ljava/lang/String;", &[])?
+        .l()?;
+    let string = JString::from(string);
+    let rust = String::from(env.get_string(string)?);
+    env.delete_local_ref(*string)?;
+    Ok(rust)
+}
+
+/// A late-bound class or method that isn't available at run time
+#[derive(Debug)]
+pub enum BindingError {
+    /// The class wasn't found, with the message of the exception
+    Class {
+        /// The class descriptor
+        class: String,
+        /// The `toString` of the `ClassNotFoundException` or `NoClassDefFoundError`
+        message: String,
+    },
+    /// The class doesn't have the method
+    Method {
+        /// The class descriptor
+        class: String,
+        /// The name of the method
+        name: String,
+        /// The declared signature
+        signature: String,
+        /// The public methods, or constructors, with the name that the class does have, as their Java `toString`
+        candidates: Vec<String>,
+    },
+    /// The lookup failed otherwise
+    Jni(jni::errors::Error),
+}
+
+impl fmt::Display for BindingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Class { class, message } => {
+                write!(f, "late-bound class {class} not found: {message}")
+            }
+            Self::Method {
+                class,
+                name,
+                signature,
+                candidates,
+            } => {
+                write!(f, "late-bound method {class}.{name}{signature} not found")?;
+                if !candidates.is_empty() {
+                    write!(f, ", the class has: {}", candidates.join(", "))?;
+                }
+                Ok(())
+            }
+            Self::Jni(e) => write!(f, "late binding failed: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for BindingError {}
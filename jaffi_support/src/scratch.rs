@@ -0,0 +1,308 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Per-thread pools of the buffers used to convert the arguments of calls into Java, behind the `scratch` feature.
+//!
+//! `jni` allocates a `Vec<jvalue>` for the arguments of each call, and a `CString` of modified UTF-8 for each `String` sent to
+//!   Java. With the `scratch` feature the Strings sent to Java are encoded in a pooled buffer, and the wrapper methods generated
+//!   with the `scratch_buffers` option pass their arguments in a pooled `jvalue` array. A buffer is taken from the pool of the
+//!   thread for a call and returned after it, so a native method called back from Java takes another one. The buffers are freed
+//!   when the thread exits, or with [`release`].
+
+use std::{cell::RefCell, ffi::c_char, mem};
+
+use jni::{
+    errors::Error,
+    objects::{JClass, JMethodID, JObject, JStaticMethodID, JString, JValue},
+    signature::{JavaType, Primitive},
+    sys::jvalue,
+    JNIEnv,
+};
+
+/// Buffers with more capacity than this are freed instead of returned to the pool, in bytes
+pub const MAX_RETAINED_CAPACITY: usize = 64 * 1024;
+
+/// The buffers kept in the pool of a thread, per type, the depth of the nested calls between Java and Rust that get pooled
+///   buffers
+pub const MAX_RETAINED_BUFFERS: usize = 8;
+
+/// The buffers of one type of a thread
+struct Pool<T>(RefCell<Vec<Vec<T>>>);
+
+impl<T> Pool<T> {
+    const fn new() -> Self {
+        Self(RefCell::new(Vec::new()))
+    }
+
+    /// Calls `f` with an empty buffer from the pool, which is returned to it after
+    fn with<R>(&self, f: impl FnOnce(&mut Vec<T>) -> R) -> R {
+        let mut buffer = self.0.borrow_mut().pop().unwrap_or_default();
+        let result = f(&mut buffer);
+
+        // a buffer isn't returned if `f` panics, the pool is still consistent
+        buffer.clear();
+        let mut buffers = self.0.borrow_mut();
+        if buffer.capacity() * mem::size_of::<T>() <= MAX_RETAINED_CAPACITY
+            && buffers.len() < MAX_RETAINED_BUFFERS
+        {
+            buffers.push(buffer);
+        }
+        result
+    }
+
+    fn retained_bytes(&self) -> usize {
+        self.0
+            .borrow()
+            .iter()
+            .map(|buffer| buffer.capacity() * mem::size_of::<T>())
+            .sum()
+    }
+
+    fn release(&self) {
+        self.0.borrow_mut().clear();
+    }
+}
+
+thread_local! {
+    static UTF8: Pool<u8> = const { Pool::new() };
+    static JVALUES: Pool<jvalue> = const { Pool::new() };
+}
+
+/// The capacity of the buffers kept in the pools of the current thread, in bytes
+pub fn retained_bytes() -> usize {
+    UTF8.with(Pool::retained_bytes) + JVALUES.with(Pool::retained_bytes)
+}
+
+/// Frees the buffers of the current thread, e.g. after a burst of calls with large Strings on a long lived thread
+pub fn release() {
+    UTF8.with(Pool::release);
+    JVALUES.with(Pool::release);
+}
+
+/// Creates a Java String, encoding the modified UTF-8 for `NewStringUTF` in a pooled buffer
+pub fn new_string<'j>(env: JNIEnv<'j>, string: &str) -> Result<JString<'j>, Error> {
+    UTF8.with(|pool| {
+        pool.with(|buffer| {
+            encode_modified_utf8(string, buffer);
+
+            let raw_env = env.get_native_interface();
+            // Safety: the buffer is nul terminated modified UTF-8, which is copied into the new String
+            let raw_string = unsafe {
+                let new_string_utf = (**raw_env)
+                    .NewStringUTF
+                    .ok_or(Error::JNIEnvMethodNotFound("NewStringUTF"))?;
+                new_string_utf(raw_env, buffer.as_ptr() as *const c_char)
+            };
+
+            check_exception(env)?;
+            if raw_string.is_null() {
+                return Err(Error::NullPtr("NewStringUTF"));
+            }
+            Ok(JString::from(raw_string))
+        })
+    })
+}
+
+/// Appends the nul terminated modified UTF-8 of `string` to the buffer
+///
+/// Modified UTF-8 encodes nul as `0xC0 0x80`, and the supplementary characters as the 3 byte sequences of their surrogates.
+fn encode_modified_utf8(string: &str, buffer: &mut Vec<u8>) {
+    buffer.reserve(string.len() + 1);
+
+    // the 4 byte sequences of supplementary characters start with 0xF0 to 0xF4
+    if !string.bytes().any(|b| b == 0 || b >= 0xF0) {
+        buffer.extend_from_slice(string.as_bytes());
+    } else {
+        for c in string.chars() {
+            match c {
+                '\0' => buffer.extend_from_slice(&[0xC0, 0x80]),
+                c if c.len_utf16() == 2 => {
+                    for unit in c.encode_utf16(&mut [0; 2]) {
+                        buffer.extend_from_slice(&[
+                            0xE0 | (*unit >> 12) as u8,
+                            0x80 | ((*unit >> 6) & 0x3F) as u8,
+                            0x80 | (*unit & 0x3F) as u8,
+                        ]);
+                    }
+                }
+                c => buffer.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+            }
+        }
+    }
+
+    buffer.push(0);
+}
+
+/// Calls `f` with the arguments converted into a pooled `jvalue` array
+fn with_jvalues<'j, R>(args: &[JValue<'j>], f: impl FnOnce(*const jvalue) -> R) -> R {
+    JVALUES.with(|pool| {
+        pool.with(|buffer| {
+            buffer.extend(args.iter().map(|arg| arg.to_jni()));
+            f(buffer.as_ptr())
+        })
+    })
+}
+
+fn check_exception(env: JNIEnv<'_>) -> Result<(), Error> {
+    if env.exception_check()? {
+        Err(Error::JavaException)
+    } else {
+        Ok(())
+    }
+}
+
+/// Calls the `Call*MethodA` function of the JNI for the return type, the instance and static functions have the same signature
+macro_rules! call_typed {
+    ($env:ident, $ret:ident, $target:ident, $method_id:ident, $args:ident,
+     $object:ident, $boolean:ident, $byte:ident, $char:ident, $short:ident, $int:ident, $long:ident, $float:ident,
+     $double:ident, $void:ident) => {{
+        let raw_env = $env.get_native_interface();
+
+        macro_rules! call {
+            ($name:ident) => {
+                (**raw_env)
+                    .$name
+                    .ok_or(Error::JNIEnvMethodNotFound(stringify!($name)))?(
+                    raw_env, $target, $method_id, $args,
+                )
+            };
+        }
+
+        // Safety: the caller guarantees the method id is of the class of the target, and the arguments have the types of its signature
+        let value = unsafe {
+            match $ret {
+                JavaType::Object(_) | JavaType::Array(_) => JValue::Object(JObject::from(call!($object))),
+                JavaType::Method(_) => return Err(Error::WrongJValueType("a return type", "method")),
+                JavaType::Primitive(primitive) => match primitive {
+                    Primitive::Boolean => JValue::Bool(call!($boolean)),
+                    Primitive::Byte => JValue::Byte(call!($byte)),
+                    Primitive::Char => JValue::Char(call!($char)),
+                    Primitive::Short => JValue::Short(call!($short)),
+                    Primitive::Int => JValue::Int(call!($int)),
+                    Primitive::Long => JValue::Long(call!($long)),
+                    Primitive::Float => JValue::Float(call!($float)),
+                    Primitive::Double => JValue::Double(call!($double)),
+                    Primitive::Void => {
+                        call!($void);
+                        JValue::Void
+                    }
+                },
+            }
+        };
+
+        check_exception($env)?;
+        Ok(value)
+    }};
+}
+
+/// `JNIEnv::call_method_unchecked`, with the arguments passed in a pooled `jvalue` array
+///
+/// # Safety
+///
+/// `method_id` must be of a method of the class of `obj`, or of one of its super classes or interfaces. `ret` must be the return
+///   type of the method, and `args` must have the number and types of its parameters. The JNI doesn't check these, calling a
+///   method with a mismatched id or arguments is undefined behavior.
+pub unsafe fn call_method_unchecked<'j>(
+    env: JNIEnv<'j>,
+    obj: JObject<'j>,
+    method_id: JMethodID<'j>,
+    ret: JavaType,
+    args: &[JValue<'j>],
+) -> Result<JValue<'j>, Error> {
+    let obj = obj.into_inner();
+    let method_id = method_id.into_inner();
+
+    with_jvalues(args, |args| {
+        call_typed!(
+            env,
+            ret,
+            obj,
+            method_id,
+            args,
+            CallObjectMethodA,
+            CallBooleanMethodA,
+            CallByteMethodA,
+            CallCharMethodA,
+            CallShortMethodA,
+            CallIntMethodA,
+            CallLongMethodA,
+            CallFloatMethodA,
+            CallDoubleMethodA,
+            CallVoidMethodA
+        )
+    })
+}
+
+/// `JNIEnv::call_static_method_unchecked`, with the arguments passed in a pooled `jvalue` array
+///
+/// # Safety
+///
+/// `method_id` must be of a static method of `class`. `ret` must be the return type of the method, and `args` must have the
+///   number and types of its parameters, as with [`call_method_unchecked`].
+pub unsafe fn call_static_method_unchecked<'j>(
+    env: JNIEnv<'j>,
+    class: JClass<'j>,
+    method_id: JStaticMethodID<'j>,
+    ret: JavaType,
+    args: &[JValue<'j>],
+) -> Result<JValue<'j>, Error> {
+    let class = class.into_inner();
+    let method_id = method_id.into_inner();
+
+    with_jvalues(args, |args| {
+        call_typed!(
+            env,
+            ret,
+            class,
+            method_id,
+            args,
+            CallStaticObjectMethodA,
+            CallStaticBooleanMethodA,
+            CallStaticByteMethodA,
+            CallStaticCharMethodA,
+            CallStaticShortMethodA,
+            CallStaticIntMethodA,
+            CallStaticLongMethodA,
+            CallStaticFloatMethodA,
+            CallStaticDoubleMethodA,
+            CallStaticVoidMethodA
+        )
+    })
+}
+
+/// `JNIEnv::new_object_unchecked`, with the arguments passed in a pooled `jvalue` array
+///
+/// # Safety
+///
+/// `ctor_id` must be of a constructor of `class`, and `args` must have the number and types of its parameters, as with
+///   [`call_method_unchecked`].
+pub unsafe fn new_object_unchecked<'j>(
+    env: JNIEnv<'j>,
+    class: JClass<'j>,
+    ctor_id: JMethodID<'j>,
+    args: &[JValue<'j>],
+) -> Result<JObject<'j>, Error> {
+    let class = class.into_inner();
+    let ctor_id = ctor_id.into_inner();
+
+    with_jvalues(args, |args| {
+        let raw_env = env.get_native_interface();
+        // Safety: the caller guarantees the constructor id is of the class, and the arguments have the types of its signature
+        let obj = unsafe {
+            let new_object = (**raw_env)
+                .NewObjectA
+                .ok_or(Error::JNIEnvMethodNotFound("NewObjectA"))?;
+            new_object(raw_env, class, ctor_id, args)
+        };
+
+        check_exception(env)?;
+        if obj.is_null() {
+            return Err(Error::NullPtr("NewObjectA"));
+        }
+        Ok(JObject::from(obj))
+    })
+}
@@ -0,0 +1,416 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Conversions of Java Strings into Rust.
+//!
+//! The strategy is process wide, the generated `JNI_OnLoad` sets the one configured in the builder. Without the
+//!   `string_strategies` feature only [`StringStrategy::ModifiedUtf8`] is available, and the configured strategy is ignored.
+//!   The `Interned` Strings need the `std` feature.
+
+use alloc::string::String;
+#[cfg(feature = "string_strategies")]
+use core::sync::atomic::{AtomicU8, Ordering};
+use core::{ffi::c_char, fmt, ops::Deref, ptr, slice};
+#[cfg(feature = "std")]
+use std::{collections::HashMap, sync::Mutex};
+
+#[cfg(feature = "string_strategies")]
+use jni::sys::jchar;
+use jni::{
+    objects::{JObject, JString, JValue},
+    strings::JavaStr,
+    JNIEnv,
+};
+
+#[cfg(feature = "std")]
+use crate::global::RegisteredGlobal;
+use crate::{FromJavaToRust, FromRustToJava, KnownString};
+
+/// The strategy used to convert Java Strings into Rust Strings
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[repr(u8)]
+pub enum StringStrategy {
+    /// Read the UTF-16 chars directly with `GetStringCritical`, unpaired surrogates are replaced with `U+FFFD`
+    #[default]
+    Utf16Lossy = 0,
+    /// Read the UTF-16 chars directly with `GetStringCritical`, panics on unpaired surrogates
+    Utf16 = 1,
+    /// Read the "modified UTF-8" from `GetStringUTFChars` and decode it, this is slower but handles all valid Strings
+    ModifiedUtf8 = 2,
+}
+
+#[cfg(feature = "string_strategies")]
+static STRATEGY: AtomicU8 = AtomicU8::new(StringStrategy::Utf16Lossy as u8);
+
+#[cfg(feature = "string_strategies")]
+impl StringStrategy {
+    fn from_u8(strategy: u8) -> Self {
+        match strategy {
+            1 => Self::Utf16,
+            2 => Self::ModifiedUtf8,
+            _ => Self::Utf16Lossy,
+        }
+    }
+}
+
+/// Returns the strategy used when converting Java Strings
+#[cfg(feature = "string_strategies")]
+pub fn strategy() -> StringStrategy {
+    StringStrategy::from_u8(STRATEGY.load(Ordering::Relaxed))
+}
+
+/// Returns the strategy used when converting Java Strings, always `ModifiedUtf8` without the `string_strategies` feature
+#[cfg(not(feature = "string_strategies"))]
+pub fn strategy() -> StringStrategy {
+    StringStrategy::ModifiedUtf8
+}
+
+/// Sets the strategy used when converting Java Strings, this is called from the generated `JNI_OnLoad`
+#[cfg(feature = "string_strategies")]
+pub fn set_strategy(strategy: StringStrategy) {
+    STRATEGY.store(strategy as u8, Ordering::Relaxed)
+}
+
+/// Ignored without the `string_strategies` feature, Strings are always converted from modified UTF-8
+#[cfg(not(feature = "string_strategies"))]
+pub fn set_strategy(_strategy: StringStrategy) {}
+
+/// Converts the Java String with the current [`strategy`]
+///
+/// # Panics
+///
+/// If the string is `null`, or the JVM fails to return the chars
+pub fn to_rust_string<'j>(env: JNIEnv<'j>, string: JString<'j>) -> String {
+    to_rust_string_with(env, string, strategy())
+}
+
+/// Converts the Java String with the `strategy`, the UTF-16 strategies are converted from modified UTF-8 without the
+///   `string_strategies` feature
+///
+/// # Panics
+///
+/// If the string is `null`, or the JVM fails to return the chars
+pub fn to_rust_string_with<'j>(
+    env: JNIEnv<'j>,
+    string: JString<'j>,
+    strategy: StringStrategy,
+) -> String {
+    assert!(!string.is_null(), "null String passed to Rust");
+
+    #[cfg(feature = "string_strategies")]
+    match strategy {
+        StringStrategy::Utf16Lossy => return with_utf16(env, string, String::from_utf16_lossy),
+        // the error is checked after the critical region is released
+        StringStrategy::Utf16 => {
+            return with_utf16(env, string, String::from_utf16)
+                .expect("String contains an unpaired surrogate")
+        }
+        StringStrategy::ModifiedUtf8 => (),
+    }
+    #[cfg(not(feature = "string_strategies"))]
+    let _ = strategy;
+
+    let java_str: JavaStr<'j, '_> = env
+        .get_string(string)
+        .expect("couldn't get the chars of the String");
+    String::from(java_str)
+}
+
+/// Calls `f` with the chars of the String, no JNI calls may be made in `f` and it must not panic
+#[cfg(feature = "string_strategies")]
+fn with_utf16<'j, T, F: FnOnce(&[jchar]) -> T>(env: JNIEnv<'j>, string: JString<'j>, f: F) -> T {
+    let raw_env = env.get_native_interface();
+    let raw_string = string.into_inner();
+
+    // Safety: the env and string are valid for 'j, and the critical region is released before returning.
+    //   `f` does not call back into the JVM.
+    unsafe {
+        let functions = &**raw_env;
+        let get_length = functions.GetStringLength.expect("GetStringLength missing");
+        let get_critical = functions
+            .GetStringCritical
+            .expect("GetStringCritical missing");
+        let release_critical = functions
+            .ReleaseStringCritical
+            .expect("ReleaseStringCritical missing");
+
+        let len = get_length(raw_env, raw_string) as usize;
+        let chars = get_critical(raw_env, raw_string, ptr::null_mut());
+        assert!(!chars.is_null(), "couldn't get the chars of the String");
+
+        let chars_slice = if len == 0 {
+            &[]
+        } else {
+            slice::from_raw_parts(chars, len)
+        };
+
+        let result = f(chars_slice);
+        release_critical(raw_env, raw_string, chars);
+        result
+    }
+}
+
+/// A Java String borrowed for the duration of a native call, see [`JavaString::as_str`]
+///
+/// This is generated for `String` arguments of native methods when `borrowed_string_args` is enabled in the builder.
+pub struct JavaString<'j> {
+    env: JNIEnv<'j>,
+    string: JString<'j>,
+    // the modified UTF-8 from GetStringUTFChars, released on drop
+    utf_chars: *const c_char,
+    len: usize,
+    // only allocated if the modified UTF-8 is not also valid UTF-8
+    owned: Option<String>,
+}
+
+impl<'j> JavaString<'j> {
+    /// Borrows the chars of the String from the JVM
+    ///
+    /// # Panics
+    ///
+    /// If the string is `null`, or the JVM fails to return the chars
+    pub fn new(env: JNIEnv<'j>, string: JString<'j>) -> Self {
+        assert!(!string.is_null(), "null String passed to Rust");
+
+        let raw_env = env.get_native_interface();
+        let raw_string = string.into_inner();
+
+        // Safety: the env and string are valid for 'j, the chars are released on drop
+        let (utf_chars, len) = unsafe {
+            let functions = &**raw_env;
+            let get_utf_chars = functions
+                .GetStringUTFChars
+                .expect("GetStringUTFChars missing");
+            let get_utf_length = functions
+                .GetStringUTFLength
+                .expect("GetStringUTFLength missing");
+
+            let len = get_utf_length(raw_env, raw_string) as usize;
+            let utf_chars = get_utf_chars(raw_env, raw_string, ptr::null_mut());
+            assert!(!utf_chars.is_null(), "couldn't get the chars of the String");
+
+            (utf_chars, len)
+        };
+
+        let mut java_string = Self {
+            env,
+            string,
+            utf_chars,
+            len,
+            owned: None,
+        };
+
+        // modified UTF-8 is standard UTF-8 unless there are nul or supplementary chars, which are encoded differently
+        if !java_string.is_standard_utf8() {
+            java_string.owned = Some(to_rust_string_with(
+                env,
+                string,
+                StringStrategy::ModifiedUtf8,
+            ));
+        }
+
+        java_string
+    }
+
+    fn utf_bytes(&self) -> &[u8] {
+        if self.len == 0 {
+            return &[];
+        }
+
+        // Safety: the chars are valid until drop
+        unsafe { slice::from_raw_parts(self.utf_chars as *const u8, self.len) }
+    }
+
+    /// The overlong nul and the surrogate pairs of modified UTF-8 are both rejected as invalid UTF-8
+    fn is_standard_utf8(&self) -> bool {
+        core::str::from_utf8(self.utf_bytes()).is_ok()
+    }
+
+    /// The String, this only allocates if the String contains nul or supplementary characters
+    pub fn as_str(&self) -> &str {
+        if let Some(owned) = &self.owned {
+            return owned;
+        }
+
+        // Safety: checked in new that these are valid UTF-8
+        unsafe { core::str::from_utf8_unchecked(self.utf_bytes()) }
+    }
+
+    /// The Java String object
+    pub fn as_jstring(&self) -> JString<'j> {
+        self.string
+    }
+}
+
+impl Deref for JavaString<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for JavaString<'_> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Debug for JavaString<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for JavaString<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl Drop for JavaString<'_> {
+    fn drop(&mut self) {
+        let raw_env = self.env.get_native_interface();
+
+        // Safety: the chars were returned from GetStringUTFChars for this string
+        unsafe {
+            if let Some(release) = (**raw_env).ReleaseStringUTFChars {
+                release(raw_env, self.string.into_inner(), self.utf_chars);
+            }
+        }
+    }
+}
+
+impl<'j> FromJavaToRust<'j, JString<'j>> for JavaString<'j> {
+    fn java_to_rust(java: JString<'j>, env: JNIEnv<'j>) -> Self {
+        JavaString::new(env, java)
+    }
+}
+
+/// A constant Rust string that is created in Java once, and then passed as a new local reference to the cached String
+///
+/// Pass these to wrapper methods generated with `interned_string_args`, e.g. `obj.put(env, Interned("key"), value)`.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Interned(pub &'static str);
+
+/// The interned Strings, as global references that are released when the library is unloaded
+#[cfg(feature = "std")]
+static INTERNED: Mutex<Option<HashMap<&'static str, RegisteredGlobal>>> = Mutex::new(None);
+
+/// Returns a local reference to the Java String for `string`, creating it on first use
+///
+/// The String is kept as a global reference, it is created again if the global references were released by an unload.
+#[cfg(feature = "std")]
+pub fn intern<'j>(
+    env: JNIEnv<'j>,
+    string: &'static str,
+) -> Result<JString<'j>, jni::errors::Error> {
+    let mut interned = INTERNED
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let interned = interned.get_or_insert_with(HashMap::new);
+
+    if let Some(global) = interned.get(string) {
+        if let Some(local) = global.local(env)? {
+            return Ok(JString::from(local));
+        }
+    }
+
+    let local = env.new_string(string)?;
+    interned.insert(string, RegisteredGlobal::new(env, *local)?);
+    Ok(local)
+}
+
+#[cfg(feature = "std")]
+impl<'j> FromRustToJava<'j, Interned> for JString<'j> {
+    fn rust_to_java(rust: Interned, env: JNIEnv<'j>) -> Self {
+        intern(env, rust.0).expect("bad string sent to Java")
+    }
+}
+
+macro_rules! string_object {
+    ($(#[$doc:meta])* $jtype: ident) => {
+        $(#[$doc])*
+        #[repr(transparent)]
+        #[derive(Clone, Copy, Debug)]
+        pub struct $jtype<'j>(JObject<'j>);
+
+        impl<'j> Deref for $jtype<'j> {
+            type Target = JObject<'j>;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl<'j> From<JObject<'j>> for $jtype<'j> {
+            fn from(obj: JObject<'j>) -> Self {
+                Self(obj)
+            }
+        }
+
+        impl<'j> From<$jtype<'j>> for JObject<'j> {
+            fn from(obj: $jtype<'j>) -> Self {
+                obj.0
+            }
+        }
+
+        /// Converted with `toString()`
+        impl<'j> FromJavaToRust<'j, $jtype<'j>> for String {
+            fn java_to_rust(java: $jtype<'j>, env: JNIEnv<'j>) -> Self {
+                assert!(!java.is_null(), "null String passed to Rust");
+
+                let string = env
+                    .call_method(java.0, "toString", "()Ljava/lang/String;", &[])
+                    .and_then(|s| s.l())
+                    .expect("couldn't call a standard method in Java");
+
+                to_rust_string(env, JString::from(string))
+            }
+        }
+    };
+}
+
+string_object!(
+    /// A `java.lang.CharSequence`, converted to and from Rust Strings
+    JCharSequence
+);
+string_object!(
+    /// A `java.lang.StringBuilder`, converted to and from Rust Strings
+    JStringBuilder
+);
+
+/// Passed to Java as a `java.lang.String`, which is a `CharSequence`
+impl<'j, S> FromRustToJava<'j, S> for JCharSequence<'j>
+where
+    S: KnownString,
+{
+    fn rust_to_java(rust: S, env: JNIEnv<'j>) -> Self {
+        let string = JString::rust_to_java(rust, env);
+        Self(*string)
+    }
+}
+
+/// Passed to Java as a new `java.lang.StringBuilder`
+impl<'j, S> FromRustToJava<'j, S> for JStringBuilder<'j>
+where
+    S: KnownString,
+{
+    fn rust_to_java(rust: S, env: JNIEnv<'j>) -> Self {
+        let string = JString::rust_to_java(rust, env);
+        let builder = env
+            .new_object(
+                "java/lang/StringBuilder",
+                "(Ljava/lang/String;)V",
+                &[JValue::Object(*string)],
+            )
+            .expect("couldn't construct a StringBuilder");
+
+        Self(builder)
+    }
+}
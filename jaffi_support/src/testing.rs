@@ -0,0 +1,173 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! An embedded JVM for the Rust-side tests of the generated wrappers.
+//!
+//! The JVM is started in the test process with the invocation API, `libjvm` is found from `JAVA_HOME` or the `java` on the
+//!   `PATH`. Only one JVM can be created in a process, so the classpath of the first call is used by all the tests.
+//!
+//! ```no_run
+//! jaffi_support::testing::with_env(&["target/classes"], |env| {
+//!     let string = env.new_string("hello").unwrap();
+//!     assert_eq!(String::from(env.get_string(string).unwrap()), "hello");
+//! });
+//! ```
+
+use std::{
+    ffi::{c_void, CString},
+    path::{Path, PathBuf},
+    ptr,
+    sync::OnceLock,
+};
+
+use jni::{
+    objects::{JObject, JValue},
+    sys::{jint, JavaVMInitArgs, JavaVMOption, JNI_OK, JNI_VERSION_1_8},
+    JNIEnv, JavaVM,
+};
+
+use crate::frame::FrameGuard;
+
+/// The capacity of the local frame of [`with_env`], more references can be created, this is only a hint to the JVM
+const FRAME_CAPACITY: i32 = 64;
+
+/// The JVM of the process, started by the first call with the classpath, later calls return the same JVM
+///
+/// * `classpath` - the directories and jars of the classes, e.g. `target/classes`
+pub fn java_vm(classpath: &[&str]) -> Result<&'static JavaVM, String> {
+    static VM: OnceLock<Result<JavaVM, String>> = OnceLock::new();
+
+    VM.get_or_init(|| create_java_vm(classpath))
+        .as_ref()
+        .map_err(Clone::clone)
+}
+
+/// Runs `f` with the env of the current thread, attached to the JVM of [`java_vm`]
+///
+/// The local references created in `f` are released when it returns.
+///
+/// # Panics
+///
+/// If the JVM can't be started, or if `f` leaves a Java exception pending, the exception is printed to stderr and cleared
+pub fn with_env<R, F>(classpath: &[&str], f: F) -> R
+where
+    F: FnOnce(JNIEnv<'_>) -> R,
+{
+    let vm = java_vm(classpath).unwrap_or_else(|e| panic!("could not start the JVM: {e}"));
+    let guard = vm
+        .attach_current_thread()
+        .unwrap_or_else(|e| panic!("could not attach to the JVM: {e}"));
+    let env = *guard;
+
+    env.push_local_frame(FRAME_CAPACITY)
+        .expect("couldn't push a local frame");
+    let frame = FrameGuard(env);
+    let result = f(env);
+
+    if env.exception_check().unwrap_or(false) {
+        env.exception_describe().ok();
+        env.exception_clear().ok();
+        panic!("a Java exception is pending after the test");
+    }
+
+    drop(frame);
+    result
+}
+
+/// Loads the cdylib into the JVM with `System.load`, so that its `JNI_OnLoad` runs and the native methods link
+///
+/// The library is loaded by the class loader of the caller, which for threads attached from Rust is the system class loader.
+///   See `harness::default_library` for the path of the crate's cdylib.
+pub fn load_library(env: JNIEnv<'_>, library: &Path) -> Result<(), jni::errors::Error> {
+    let library = library
+        .canonicalize()
+        .unwrap_or_else(|_| library.to_path_buf());
+    let path = env.new_string(library.to_string_lossy())?;
+    let result = env.call_static_method(
+        "java/lang/System",
+        "load",
+        "(Ljava/lang/String;)V",
+        &[JValue::Object(*path)],
+    );
+    env.delete_local_ref(JObject::from(path))?;
+
+    result.map(drop)
+}
+
+fn create_java_vm(classpath: &[&str]) -> Result<JavaVM, String> {
+    type CreateJavaVm = unsafe extern "system" fn(
+        *mut *mut jni::sys::JavaVM,
+        *mut *mut c_void,
+        *mut c_void,
+    ) -> jint;
+
+    let libjvm = find_libjvm().ok_or("could not find libjvm, set JAVA_HOME")?;
+    let libjvm = unsafe { libloading::Library::new(&libjvm) }
+        .map_err(|e| format!("could not load {}: {e}", libjvm.display()))?;
+
+    let separator = if cfg!(windows) { ";" } else { ":" };
+    let classpath = CString::new(format!("-Djava.class.path={}", classpath.join(separator)))
+        .map_err(|e| e.to_string())?;
+    let mut options = [JavaVMOption {
+        optionString: classpath.as_ptr() as *mut _,
+        extraInfo: ptr::null_mut(),
+    }];
+    let mut args = JavaVMInitArgs {
+        version: JNI_VERSION_1_8,
+        nOptions: options.len() as jint,
+        options: options.as_mut_ptr(),
+        ignoreUnrecognized: 0,
+    };
+
+    let mut vm = ptr::null_mut();
+    let mut env = ptr::null_mut();
+    let result = unsafe {
+        let create = libjvm
+            .get::<CreateJavaVm>(b"JNI_CreateJavaVM")
+            .map_err(|e| e.to_string())?;
+        create(&mut vm, &mut env, &mut args as *mut _ as *mut c_void)
+    };
+    if result != JNI_OK {
+        return Err(format!("JNI_CreateJavaVM failed: {result}"));
+    }
+
+    // the JVM can't be unloaded
+    std::mem::forget(libjvm);
+    unsafe { JavaVM::from_raw(vm) }.map_err(|e| e.to_string())
+}
+
+/// `libjvm` in the `JAVA_HOME`, or in the home of the `java` found on the `PATH`
+fn find_libjvm() -> Option<PathBuf> {
+    let java_home = std::env::var_os("JAVA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| {
+            let java = if cfg!(windows) { "java.exe" } else { "java" };
+            let path = std::env::var_os("PATH")?;
+            let java = std::env::split_paths(&path)
+                .map(|dir| dir.join(java))
+                .find(|java| java.is_file())?;
+
+            // bin/java, usually a symlink
+            let java = java.canonicalize().ok()?;
+            java.parent()?.parent().map(Path::to_path_buf)
+        })?;
+
+    let library = format!(
+        "{}jvm{}",
+        std::env::consts::DLL_PREFIX,
+        std::env::consts::DLL_SUFFIX
+    );
+    [
+        "lib/server",
+        "jre/lib/server",
+        "bin/server",
+        "jre/lib/amd64/server",
+    ]
+    .iter()
+    .map(|dir| java_home.join(dir).join(&library))
+    .find(|path| path.is_file())
+}
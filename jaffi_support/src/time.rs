@@ -0,0 +1,235 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! `java.time.Instant`, `java.time.Duration` and `java.util.Date`, converted to and from `std::time` types.
+//!
+//! Requires the `time` feature, the generator uses these when `time_conversions` is enabled in the builder.
+
+use std::{
+    ops::Deref,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use jni::{
+    objects::{JObject, JValue},
+    signature::{JavaType, Primitive},
+    JNIEnv,
+};
+
+use crate::{
+    cache::{MethodIdCache, StaticMethodIdCache},
+    FromJavaToRust, FromRustToJava,
+};
+
+macro_rules! time_object {
+    ($(#[$doc:meta])* $jtype: ident) => {
+        $(#[$doc])*
+        #[repr(transparent)]
+        #[derive(Clone, Copy, Debug)]
+        pub struct $jtype<'j>(JObject<'j>);
+
+        impl<'j> Deref for $jtype<'j> {
+            type Target = JObject<'j>;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl<'j> From<JObject<'j>> for $jtype<'j> {
+            fn from(obj: JObject<'j>) -> Self {
+                Self(obj)
+            }
+        }
+
+        impl<'j> From<$jtype<'j>> for JObject<'j> {
+            fn from(obj: $jtype<'j>) -> Self {
+                obj.0
+            }
+        }
+    };
+}
+
+time_object!(
+    /// A `java.time.Instant`, converted to and from `SystemTime`
+    JInstant
+);
+time_object!(
+    /// A `java.time.Duration`, converted to and from `Duration`
+    JDuration
+);
+time_object!(
+    /// A `java.util.Date`, converted to and from `SystemTime` with millisecond precision
+    JDate
+);
+
+fn call_long<'j>(env: JNIEnv<'j>, obj: JObject<'j>, method: &MethodIdCache) -> i64 {
+    method
+        .get(env)
+        .and_then(|id| {
+            env.call_method_unchecked(obj, id, JavaType::Primitive(Primitive::Long), &[])
+        })
+        .and_then(|v| v.j())
+        .expect("couldn't call a standard method in Java")
+}
+
+fn call_int<'j>(env: JNIEnv<'j>, obj: JObject<'j>, method: &MethodIdCache) -> i32 {
+    method
+        .get(env)
+        .and_then(|id| env.call_method_unchecked(obj, id, JavaType::Primitive(Primitive::Int), &[]))
+        .and_then(|v| v.i())
+        .expect("couldn't call a standard method in Java")
+}
+
+fn call_of_seconds<'j>(
+    env: JNIEnv<'j>,
+    method: &StaticMethodIdCache,
+    seconds: i64,
+    nanos: i64,
+) -> JObject<'j> {
+    method
+        .get(env)
+        .and_then(|id| {
+            env.call_static_method_unchecked(
+                method.class(),
+                id,
+                JavaType::Object(method.class().to_string()),
+                &[JValue::Long(seconds), JValue::Long(nanos)],
+            )
+        })
+        .and_then(|v| v.l())
+        .expect("couldn't call a standard method in Java")
+}
+
+/// Seconds and nanos relative to the epoch, the nanos are always positive as in `java.time`
+fn from_epoch(time: SystemTime) -> (i64, i64) {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(after) => (after.as_secs() as i64, i64::from(after.subsec_nanos())),
+        Err(before) => {
+            let before = before.duration();
+            let seconds = -(before.as_secs() as i64);
+            match before.subsec_nanos() {
+                0 => (seconds, 0),
+                nanos => (seconds - 1, i64::from(1_000_000_000 - nanos)),
+            }
+        }
+    }
+}
+
+fn to_epoch(seconds: i64, nanos: u32) -> SystemTime {
+    if seconds >= 0 {
+        UNIX_EPOCH + Duration::new(seconds as u64, nanos)
+    } else {
+        UNIX_EPOCH - Duration::from_secs(seconds.unsigned_abs())
+            + Duration::from_nanos(u64::from(nanos))
+    }
+}
+
+static INSTANT_EPOCH_SECOND: MethodIdCache =
+    MethodIdCache::new("java/time/Instant", "getEpochSecond", "()J");
+static INSTANT_NANO: MethodIdCache = MethodIdCache::new("java/time/Instant", "getNano", "()I");
+static INSTANT_OF_EPOCH_SECOND: StaticMethodIdCache = StaticMethodIdCache::new(
+    "java/time/Instant",
+    "ofEpochSecond",
+    "(JJ)Ljava/time/Instant;",
+);
+
+impl<'j> FromJavaToRust<'j, JInstant<'j>> for SystemTime {
+    fn java_to_rust(java: JInstant<'j>, env: JNIEnv<'j>) -> Self {
+        assert!(!java.is_null(), "null Instant passed to Rust");
+
+        let seconds = call_long(env, java.0, &INSTANT_EPOCH_SECOND);
+        let nanos = call_int(env, java.0, &INSTANT_NANO);
+
+        to_epoch(seconds, nanos as u32)
+    }
+}
+
+impl<'j> FromRustToJava<'j, SystemTime> for JInstant<'j> {
+    fn rust_to_java(rust: SystemTime, env: JNIEnv<'j>) -> Self {
+        let (seconds, nanos) = from_epoch(rust);
+        Self(call_of_seconds(
+            env,
+            &INSTANT_OF_EPOCH_SECOND,
+            seconds,
+            nanos,
+        ))
+    }
+}
+
+static DURATION_SECONDS: MethodIdCache =
+    MethodIdCache::new("java/time/Duration", "getSeconds", "()J");
+static DURATION_NANO: MethodIdCache = MethodIdCache::new("java/time/Duration", "getNano", "()I");
+static DURATION_OF_SECONDS: StaticMethodIdCache = StaticMethodIdCache::new(
+    "java/time/Duration",
+    "ofSeconds",
+    "(JJ)Ljava/time/Duration;",
+);
+
+/// # Panics
+///
+/// If the Duration is negative, `std::time::Duration` is unsigned
+impl<'j> FromJavaToRust<'j, JDuration<'j>> for Duration {
+    fn java_to_rust(java: JDuration<'j>, env: JNIEnv<'j>) -> Self {
+        assert!(!java.is_null(), "null Duration passed to Rust");
+
+        let seconds = call_long(env, java.0, &DURATION_SECONDS);
+        let nanos = call_int(env, java.0, &DURATION_NANO);
+
+        let seconds = u64::try_from(seconds).expect("negative Duration passed to Rust");
+        Duration::new(seconds, nanos as u32)
+    }
+}
+
+impl<'j> FromRustToJava<'j, Duration> for JDuration<'j> {
+    fn rust_to_java(rust: Duration, env: JNIEnv<'j>) -> Self {
+        let seconds = i64::try_from(rust.as_secs()).expect("Duration is too long for Java");
+        let nanos = i64::from(rust.subsec_nanos());
+        Self(call_of_seconds(env, &DURATION_OF_SECONDS, seconds, nanos))
+    }
+}
+
+static DATE_GET_TIME: MethodIdCache = MethodIdCache::new("java/util/Date", "getTime", "()J");
+
+impl<'j> FromJavaToRust<'j, JDate<'j>> for SystemTime {
+    fn java_to_rust(java: JDate<'j>, env: JNIEnv<'j>) -> Self {
+        assert!(!java.is_null(), "null Date passed to Rust");
+
+        let millis = call_long(env, java.0, &DATE_GET_TIME);
+        let seconds = millis.div_euclid(1000);
+        let nanos = millis.rem_euclid(1000) as u32 * 1_000_000;
+
+        to_epoch(seconds, nanos)
+    }
+}
+
+/// The time is truncated to milliseconds
+impl<'j> FromRustToJava<'j, SystemTime> for JDate<'j> {
+    fn rust_to_java(rust: SystemTime, env: JNIEnv<'j>) -> Self {
+        let (seconds, nanos) = from_epoch(rust);
+        let millis = seconds * 1000 + nanos / 1_000_000;
+
+        let date = env
+            .new_object("java/util/Date", "(J)V", &[JValue::Long(millis)])
+            .expect("couldn't construct a Date");
+
+        Self(date)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_epoch_round_trip() {
+        for (seconds, nanos) in [(0, 0), (1, 5), (-1, 0), (-2, 999_999_999), (-1, 1)] {
+            let time = to_epoch(seconds, nanos as u32);
+            assert_eq!(from_epoch(time), (seconds, nanos));
+        }
+    }
+}
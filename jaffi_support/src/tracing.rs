@@ -0,0 +1,31 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! `tracing` spans for the calls between Rust and Java, used by the `tracing_spans` option of the generator.
+//!
+//! Native functions called from Java enter a `jni_native` span, wrapper methods calling into Java a `jni_call` span, both
+//!   at the `TRACE` level with the `jaffi` target. Nested spans show the mixed Rust and Java stack.
+
+pub use ::tracing::span::EnteredSpan;
+
+/// Enters the span of a native method called from Java, e.g. `net.bluejekyll.NativeStrings`, `eatString` and `(Ljava/lang/String;)V`
+pub fn native_span(
+    class: &'static str,
+    method: &'static str,
+    signature: &'static str,
+) -> EnteredSpan {
+    ::tracing::trace_span!(target: "jaffi", "jni_native", class, method, signature).entered()
+}
+
+/// Enters the span of a call into a Java method from a wrapper
+pub fn wrapper_span(
+    class: &'static str,
+    method: &'static str,
+    signature: &'static str,
+) -> EnteredSpan {
+    ::tracing::trace_span!(target: "jaffi", "jni_call", class, method, signature).entered()
+}
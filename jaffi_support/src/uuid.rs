@@ -0,0 +1,89 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! `java.util.UUID`, converted to and from `uuid::Uuid`.
+//!
+//! Requires the `uuid` feature, the generator uses these when `uuid_conversions` is enabled in the builder.
+
+use std::ops::Deref;
+
+use jni::{
+    objects::{JObject, JValue},
+    signature::{JavaType, Primitive},
+    JNIEnv,
+};
+
+pub use ::uuid::Uuid;
+
+use crate::{cache::MethodIdCache, FromJavaToRust, FromRustToJava};
+
+static MOST_SIGNIFICANT_BITS: MethodIdCache =
+    MethodIdCache::new("java/util/UUID", "getMostSignificantBits", "()J");
+static LEAST_SIGNIFICANT_BITS: MethodIdCache =
+    MethodIdCache::new("java/util/UUID", "getLeastSignificantBits", "()J");
+
+/// A `java.util.UUID`, converted to and from `Uuid`
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug)]
+pub struct JUuid<'j>(JObject<'j>);
+
+impl<'j> Deref for JUuid<'j> {
+    type Target = JObject<'j>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'j> From<JObject<'j>> for JUuid<'j> {
+    fn from(obj: JObject<'j>) -> Self {
+        Self(obj)
+    }
+}
+
+impl<'j> From<JUuid<'j>> for JObject<'j> {
+    fn from(obj: JUuid<'j>) -> Self {
+        obj.0
+    }
+}
+
+fn call_bits<'j>(env: JNIEnv<'j>, obj: JObject<'j>, method: &MethodIdCache) -> u64 {
+    method
+        .get(env)
+        .and_then(|id| {
+            env.call_method_unchecked(obj, id, JavaType::Primitive(Primitive::Long), &[])
+        })
+        .and_then(|v| v.j())
+        .expect("couldn't call a standard method in Java") as u64
+}
+
+impl<'j> FromJavaToRust<'j, JUuid<'j>> for Uuid {
+    fn java_to_rust(java: JUuid<'j>, env: JNIEnv<'j>) -> Self {
+        assert!(!java.is_null(), "null UUID passed to Rust");
+
+        let most = call_bits(env, java.0, &MOST_SIGNIFICANT_BITS);
+        let least = call_bits(env, java.0, &LEAST_SIGNIFICANT_BITS);
+
+        Uuid::from_u64_pair(most, least)
+    }
+}
+
+impl<'j> FromRustToJava<'j, Uuid> for JUuid<'j> {
+    fn rust_to_java(rust: Uuid, env: JNIEnv<'j>) -> Self {
+        let (most, least) = rust.as_u64_pair();
+
+        let uuid = env
+            .new_object(
+                "java/util/UUID",
+                "(JJ)V",
+                &[JValue::Long(most as i64), JValue::Long(least as i64)],
+            )
+            .expect("couldn't construct a UUID");
+
+        Self(uuid)
+    }
+}
@@ -0,0 +1,102 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Checks of the arguments of native methods, called by the exported functions generated with the `validate_args` option.
+//!
+//! The checks are only generated with `debug_assertions`. An invalid argument is thrown as a `java/lang/IllegalArgumentException`,
+//!   and the function returns to Java without calling the implementation, instead of failing later in the conversion or corrupting
+//!   state in the native code.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+
+use jni::{objects::JObject, JNIEnv};
+
+/// `false` if an exception is pending in the native method, the exception is left pending, to be thrown in Java on return
+///
+/// The exception can be from the caller, which must not call into Java with a pending exception, or from the conversion of the
+///   arguments. The implementation isn't called either way.
+pub fn no_exception_pending(env: JNIEnv<'_>) -> bool {
+    !env.exception_check().unwrap_or(true)
+}
+
+/// Checks a reference argument of the native `method`, throwing an `IllegalArgumentException` and returning `false` if it's invalid
+///
+/// * `argument` - the description of the argument in the message, e.g. ``argument 0 `name` ``
+/// * `nullable` - `null` is a valid value, i.e. the Rust type is an `Option`
+/// * `descriptor` - the field descriptor of the parameter, arrays are checked to be an instance of it, e.g. that an `int[][]`
+///   parameter isn't passed an `int[]`
+pub fn argument(
+    env: JNIEnv<'_>,
+    method: &str,
+    argument: &str,
+    obj: JObject<'_>,
+    nullable: bool,
+    descriptor: &str,
+) -> bool {
+    if obj.is_null() {
+        if nullable {
+            return true;
+        }
+
+        throw_illegal_argument(env, format!("{argument} of {method} is null"));
+        return false;
+    }
+
+    if !descriptor.starts_with('[') {
+        return true;
+    }
+
+    let class = match crate::class_loader::find_class(env, descriptor) {
+        Ok(class) => class,
+        Err(_) => {
+            // the array class isn't visible to the class loader, it can't be checked
+            let _ = env.exception_clear();
+            return true;
+        }
+    };
+
+    match env.is_instance_of(obj, class) {
+        Ok(true) => true,
+        Ok(false) => {
+            let actual = class_name(env, obj).unwrap_or_else(|| "another type".to_string());
+            throw_illegal_argument(
+                env,
+                format!("{argument} of {method} is a {actual}, expected {descriptor}"),
+            );
+            false
+        }
+        Err(_) => {
+            let _ = env.exception_clear();
+            true
+        }
+    }
+}
+
+/// The class name of the object, e.g. `[I`, `None` if it can't be read
+fn class_name(env: JNIEnv<'_>, obj: JObject<'_>) -> Option<String> {
+    let class = env.get_object_class(obj).ok()?;
+    let name = env
+        .call_method(*class, "getName", "()Ljava/lang/String;", &[])
+        .and_then(|name| name.l());
+
+    match name {
+        Ok(name) => env.get_string(name.into()).ok().map(String::from),
+        Err(_) => {
+            let _ = env.exception_clear();
+            None
+        }
+    }
+}
+
+fn throw_illegal_argument(env: JNIEnv<'_>, message: String) {
+    // an OutOfMemoryError from the lookup is left pending
+    let _ = crate::class_loader::find_class(env, "java/lang/IllegalArgumentException")
+        .and_then(|class| env.throw_new(class, message));
+}
@@ -0,0 +1,34 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Custom rendering of the generated code
+
+use proc_macro2::TokenStream;
+
+use crate::model::{NativeClass, Wrapper};
+
+/// Renders the generated code, each method gets the default rendering and returns the code to generate in its place
+///
+/// Backends can add items, e.g. boilerplate or trait impls, rewrite the tokens, or replace them entirely, with the model of the
+///   class to generate from. All the methods return the default rendering unchanged by default. The code uses the `'j` lifetime,
+///   the `lifetime_name` is applied after the backend.
+pub trait Backend {
+    /// The imports, exception types, `JNI_OnLoad` and `JNI_OnUnload` shared by all the classes
+    fn render_support(&self, tokens: TokenStream) -> TokenStream {
+        tokens
+    }
+
+    /// The wrapper struct of a class, with its methods, enum, mirror or sealed enum
+    fn render_wrapper(&self, _wrapper: &Wrapper, tokens: TokenStream) -> TokenStream {
+        tokens
+    }
+
+    /// The `*Rs` trait of a class with native methods, and the exported `extern` functions
+    fn render_native_class(&self, _class: &NativeClass, tokens: TokenStream) -> TokenStream {
+        tokens
+    }
+}
@@ -0,0 +1,262 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Criterion benchmarks of the calls between Rust and Java, included in a bench target of the crate
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+use crate::model::{JavaType, Method, Model, Wrapper};
+
+/// Generates the source of the `jaffi_benches` function, benchmarking each native method and each wrapper method
+///
+/// * `bindings` - the path of the generated code from the bench target, e.g. `my_crate::bindings`
+/// * `library_name` - the name of the cdylib implementing the native methods
+/// * `classpath` - the absolute classpath of the JVM started in the benchmark
+/// * `unchecked_exceptions` - the wrappers of constructors return a `Result`
+pub(crate) fn generate_benches(
+    model: &Model,
+    bindings: &TokenStream,
+    library_name: &str,
+    classpath: &[String],
+    unchecked_exceptions: bool,
+) -> String {
+    let natives = model.native_classes.iter().flat_map(|class| {
+        let class_name = &class.class;
+        class.methods.iter().map(move |method| {
+            let name = &method.name;
+            let signature = &method.descriptor;
+            let is_static = method.is_static;
+            quote! { (#class_name, #name, #signature, #is_static) }
+        })
+    });
+    let wrappers = model
+        .wrappers
+        .iter()
+        .map(|wrapper| generate_wrapper_benches(wrapper, bindings, unchecked_exceptions));
+
+    let benches = quote! {
+        /// The classpath the bindings were generated from
+        const JAFFI_CLASSPATH: &[&str] = &[#(#classpath),*];
+
+        /// Benchmarks each native method, called from Rust through the JVM, and each wrapper of a Java method, with synthetic arguments
+        ///
+        /// The methods that can't be called with the arguments of `jaffi_support::bench` are skipped.
+        #[allow(deprecated, clippy::let_unit_value)]
+        pub fn jaffi_benches(c: &mut criterion::Criterion) {
+            jaffi_support::testing::with_env(JAFFI_CLASSPATH, |env| {
+                let library = jaffi_support::harness::default_library(#library_name);
+                let library = jaffi_support::bench::Library::load(env, &library)
+                    .unwrap_or_else(|e| panic!("{e}"));
+
+                let mut group = c.benchmark_group("jaffi_native");
+                for (class, name, signature, is_static) in [#(#natives),*] {
+                    let bench = format!("{}.{name}{signature}", class.replace('/', "."));
+                    match jaffi_support::bench::NativeCall::new(env, &library, class, name, signature, is_static) {
+                        Ok(call) => {
+                            group.bench_function(bench, |b| b.iter(|| call.call(env)));
+                        }
+                        Err(e) => eprintln!("skipping {bench}: {e}"),
+                    }
+                }
+                group.finish();
+
+                let bytes = jaffi_support::arrays::JavaByteArray::new(env, &[0; jaffi_support::bench::ARRAY_LEN as usize])
+                    .expect("could not create the byte array");
+                let mut group = c.benchmark_group("jaffi_wrapper");
+                #(#wrappers)*
+                group.finish();
+            });
+        }
+    };
+
+    format!("// DO NOT EDIT THIS FILE - it is generated by jaffi\n{benches}")
+}
+
+/// The benchmarks of the methods of the wrapper, instance methods are called on an object made with the no-argument constructor
+fn generate_wrapper_benches(
+    wrapper: &Wrapper,
+    bindings: &TokenStream,
+    unchecked_exceptions: bool,
+) -> TokenStream {
+    let obj_name = format_ident!("{}", wrapper.rust_name);
+    let static_trait_name = format_ident!("Static{}", wrapper.rust_name);
+    let class = wrapper.class.replace('/', ".");
+
+    // the native methods are benchmarked through the JVM
+    let methods = wrapper
+        .methods
+        .iter()
+        .filter(|method| !method.is_native && !method.is_constructor && !method.is_bridge)
+        .filter_map(|method| Some((method, synthetic_args(method)?)))
+        .collect::<Vec<_>>();
+
+    let benches = methods
+        .iter()
+        .map(|(method, args)| {
+            let rust_method_name = format_ident!("{}", method.rust_name);
+            let bench = format!("{class}.{}{}", method.name, method.descriptor);
+            let skipped = format!("skipping {bench}: {{e}}");
+            let call = if method.is_static {
+                quote! {
+                    <#bindings::#obj_name as #bindings::#static_trait_name>::#rust_method_name(
+                        &#bindings::#obj_name::from(jaffi_support::jni::objects::JObject::null()),
+                        env,
+                        #(#args),*
+                    )
+                }
+            } else {
+                quote! { receiver.#rust_method_name(env, #(#args),*) }
+            };
+
+            let bench_call = quote! {
+                {
+                    let call = || {
+                        let _ = #call;
+                    };
+                    match jaffi_support::bench::try_call(env, call) {
+                        Ok(()) => {
+                            group.bench_function(#bench, |b| {
+                                b.iter(|| jaffi_support::frame::with_frame(env, jaffi_support::frame::DEFAULT_CAPACITY, call))
+                            });
+                        }
+                        Err(e) => eprintln!(#skipped),
+                    }
+                }
+            };
+
+            (method.is_static, bench_call)
+        })
+        .collect::<Vec<_>>();
+
+    let static_benches = benches
+        .iter()
+        .filter(|(is_static, _)| *is_static)
+        .map(|(_, bench)| bench);
+    let instance_benches = benches
+        .iter()
+        .filter(|(is_static, _)| !*is_static)
+        .map(|(_, bench)| bench)
+        .collect::<Vec<_>>();
+
+    let constructor = wrapper
+        .methods
+        .iter()
+        .find(|method| method.is_constructor && method.arguments.is_empty());
+    let instance_benches = match constructor {
+        _ if instance_benches.is_empty() => quote! {},
+        Some(constructor) => {
+            let skipped = format!("skipping the methods of {class}: {{e}}");
+            let rust_method_name = format_ident!("{}", constructor.rust_name);
+            let expect = (unchecked_exceptions || !constructor.exceptions.is_empty())
+                .then(|| quote! { .expect("the constructor threw") });
+
+            quote! {
+                match jaffi_support::bench::try_call(env, || #bindings::#obj_name::#rust_method_name(env) #expect) {
+                    Ok(receiver) => {
+                        #(#instance_benches)*
+                    }
+                    Err(e) => eprintln!(#skipped),
+                }
+            }
+        }
+        None => {
+            let skipped =
+                format!("skipping the methods of {class}: no constructor without arguments");
+            quote! { eprintln!(#skipped); }
+        }
+    };
+
+    quote! {
+        #(#static_benches)*
+        #instance_benches
+    }
+}
+
+/// The synthetic arguments of the wrapper method, `None` if an argument isn't a primitive, String or `byte[]`
+fn synthetic_args(method: &Method) -> Option<Vec<TokenStream>> {
+    method
+        .arguments
+        .iter()
+        .map(|arg| match &arg.ty {
+            JavaType::Boolean
+            | JavaType::Byte
+            | JavaType::Char
+            | JavaType::Short
+            | JavaType::Int
+            | JavaType::Long
+            | JavaType::Float
+            | JavaType::Double => matches!(
+                arg.rust_type.as_str(),
+                "bool" | "i8" | "u8" | "char" | "u16" | "i16" | "i32" | "i64" | "f32" | "f64"
+            )
+            .then(|| quote! { Default::default() }),
+            JavaType::Object(class) if class == "java/lang/String" => {
+                Some(quote! { jaffi_support::bench::STRING })
+            }
+            JavaType::Array(element) if **element == JavaType::Byte => Some(quote! { bytes }),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Argument;
+
+    fn method(arguments: &[(JavaType, &str)]) -> Method {
+        Method {
+            name: "m".to_string(),
+            rust_name: "m".to_string(),
+            descriptor: "()V".to_string(),
+            is_static: true,
+            is_native: false,
+            is_constructor: false,
+            arguments: arguments
+                .iter()
+                .map(|(ty, rust_type)| Argument {
+                    name: "arg".to_string(),
+                    java_name: None,
+                    ty: ty.clone(),
+                    rust_type: rust_type.to_string(),
+                })
+                .collect(),
+            result: None,
+            rust_result: "()".to_string(),
+            exceptions: vec![],
+            export_names: vec![],
+            deprecated: None,
+            is_bridge: false,
+        }
+    }
+
+    #[test]
+    fn test_synthetic_args() {
+        let string = JavaType::Object("java/lang/String".to_string());
+        let bytes = JavaType::Array(Box::new(JavaType::Byte));
+        let args = synthetic_args(&method(&[
+            (JavaType::Int, "i32"),
+            (string, "impl IntoJavaValue"),
+            (bytes, "JavaByteArray"),
+        ]))
+        .expect("synthetic args");
+        assert_eq!(
+            args.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "Default :: default ()",
+                "jaffi_support :: bench :: STRING",
+                "bytes"
+            ]
+        );
+
+        // enums and boxed primitives
+        assert!(synthetic_args(&method(&[(JavaType::Int, "Fruit")])).is_none());
+        let list = JavaType::Object("java/util/List".to_string());
+        assert!(synthetic_args(&method(&[(list, "JObject")])).is_none());
+    }
+}
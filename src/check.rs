@@ -0,0 +1,158 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Diffs of the generated files against the existing ones, for `Jaffi::check`
+
+use std::{
+    ffi::OsString,
+    fmt::Write,
+    io::Write as _,
+    process::{Command, Stdio},
+    str::FromStr,
+};
+
+use proc_macro2::TokenStream;
+
+/// Lines of context around the difference
+const CONTEXT_LINES: usize = 3;
+/// Tokens of context around the difference, tokens are much shorter than lines
+const CONTEXT_TOKENS: usize = 12;
+/// Differences longer than this are truncated
+const MAX_CHANGED: usize = 40;
+
+/// A diff of the first difference of the lines, `None` if they are the same
+pub(crate) fn diff_lines(expected: &str, found: &str) -> Option<String> {
+    let expected = expected.lines().collect::<Vec<_>>();
+    let found = found.lines().collect::<Vec<_>>();
+
+    hunk(&expected, &found, CONTEXT_LINES, "line", "\n")
+}
+
+/// A diff of the first difference of the Rust tokens, `None` if they are the same
+///
+/// Both are parsed as a `TokenStream`, so that the whitespace and formatting are ignored
+pub(crate) fn diff_tokens(expected: &str, found: &str) -> Option<String> {
+    let normalize =
+        |s: &str| TokenStream::from_str(s).map_or_else(|_| s.to_string(), |t| t.to_string());
+    let expected = normalize(expected);
+    let found = normalize(found);
+
+    let expected = expected.split_whitespace().collect::<Vec<_>>();
+    let found = found.split_whitespace().collect::<Vec<_>>();
+
+    hunk(&expected, &found, CONTEXT_TOKENS, "token", " ")
+}
+
+/// Formats the Rust source with `rustfmt`, or `$RUSTFMT`, `None` if it isn't installed or fails
+///
+/// Files are usually formatted after being generated, `rustfmt` also reorders the `use` lists, which changes the tokens
+pub(crate) fn rustfmt(source: &str) -> Option<String> {
+    let rustfmt = std::env::var_os("RUSTFMT").unwrap_or_else(|| OsString::from("rustfmt"));
+    let mut child = Command::new(rustfmt)
+        .args(["--edition", "2021", "--emit", "stdout"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    // the pipe is closed when stdin is dropped, so that rustfmt finishes
+    child.stdin.take()?.write_all(source.as_bytes()).ok()?;
+    let output = child.wait_with_output().ok()?;
+
+    if output.status.success() {
+        String::from_utf8(output.stdout).ok()
+    } else {
+        None
+    }
+}
+
+/// Renders the changed range, between the common prefix and suffix, `-` is `found` and `+` is `expected`
+fn hunk(
+    expected: &[&str],
+    found: &[&str],
+    context: usize,
+    unit: &str,
+    separator: &str,
+) -> Option<String> {
+    if expected == found {
+        return None;
+    }
+
+    let prefix = expected
+        .iter()
+        .zip(found)
+        .take_while(|(e, f)| e == f)
+        .count();
+    let suffix = expected[prefix..]
+        .iter()
+        .rev()
+        .zip(found[prefix..].iter().rev())
+        .take_while(|(e, f)| e == f)
+        .count();
+
+    let mut diff = format!("@@ {unit} {} @@", prefix + 1);
+    let mut write_part = |sign: char, part: &[&str]| {
+        if part.is_empty() {
+            return;
+        }
+
+        let truncated = part.len() > MAX_CHANGED;
+        let part = &part[..part.len().min(MAX_CHANGED)];
+        if separator == "\n" {
+            for item in part {
+                write!(diff, "\n{sign} {item}").unwrap();
+            }
+            if truncated {
+                write!(diff, "\n{sign} ...").unwrap();
+            }
+        } else {
+            write!(diff, "\n{sign} {}", part.join(separator)).unwrap();
+            if truncated {
+                diff.push_str(" ...");
+            }
+        }
+    };
+
+    write_part(' ', &expected[prefix.saturating_sub(context)..prefix]);
+    write_part('-', &found[prefix..found.len() - suffix]);
+    write_part('+', &expected[prefix..expected.len() - suffix]);
+    let after = expected.len() - suffix;
+    write_part(' ', &expected[after..expected.len().min(after + context)]);
+
+    Some(diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lines() {
+        assert_eq!(diff_lines("a\nb\n", "a\nb\n"), None);
+        assert_eq!(
+            diff_lines("a\nb\nc\nd\ne\nf\n", "a\nb\nc\nX\ne\nf\n").as_deref(),
+            Some("@@ line 4 @@\n  a\n  b\n  c\n- X\n+ d\n  e\n  f")
+        );
+        assert_eq!(
+            diff_lines("a\nb\n", "").as_deref(),
+            Some("@@ line 1 @@\n+ a\n+ b")
+        );
+    }
+
+    #[test]
+    fn test_diff_tokens_ignores_formatting() {
+        assert_eq!(
+            diff_tokens("fn f() { g(1, 2); }", "fn f() {\n    g(1, 2);\n}\n"),
+            None
+        );
+        assert_eq!(
+            diff_tokens("fn f() { g(1, 2); }", "fn f() {\n    g(1, 3);\n}\n").as_deref(),
+            Some("@@ token 8 @@\n  fn f () { g (1 ,\n- 3)\n+ 2)\n  ; }")
+        );
+    }
+}
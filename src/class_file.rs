@@ -0,0 +1,252 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The raw class file format, to check the version before parsing, to drop the attributes for `lenient_attributes`, and to
+//!   declare the methods of the `late_bound_classes`
+
+/// The attributes that are read from the classes, the others can be dropped
+pub(crate) const USED_ATTRIBUTES: &[&str] = &[
+    "Code",
+    "ConstantValue",
+    "Exceptions",
+    "MethodParameters",
+    "PermittedSubclasses",
+    "Record",
+    "Signature",
+];
+
+/// The major version of Java 1.1, the release of later versions is the major version minus this
+const JAVA_1_1_MAJOR: u16 = 45;
+
+/// The `(major, minor)` version of the class file, `None` if it isn't a class file
+pub(crate) fn class_version(bytes: &[u8]) -> Option<(u16, u16)> {
+    if bytes.get(..4)? != [0xCA, 0xFE, 0xBA, 0xBE] {
+        return None;
+    }
+
+    let mut ix = 4;
+    let minor = read_u2(bytes, &mut ix)?;
+    let major = read_u2(bytes, &mut ix)?;
+    Some((major, minor))
+}
+
+/// The Java release of the major version, e.g. `17` for `61`, and `4` for Java 1.4
+pub(crate) fn java_release(major: u16) -> u16 {
+    major.saturating_sub(JAVA_1_1_MAJOR - 1).max(1)
+}
+
+/// Copies the class file without the attributes of the class, fields and methods for which `keep` returns false
+///
+/// Returns `None` if the class file can't be read, e.g. the constant pool has an unknown tag
+pub(crate) fn strip_attributes(bytes: &[u8], keep: impl Fn(&[u8]) -> bool) -> Option<Vec<u8>> {
+    let mut ix = 8;
+    let pool_count = read_u2(bytes, &mut ix)?;
+
+    // the names of the attributes are indexes into the constant pool, only the utf8 entries are needed
+    let mut utf8 = vec![None; usize::from(pool_count)];
+    let mut index = 1;
+    while index < pool_count {
+        let tag = *bytes.get(ix)?;
+        ix += 1;
+        match tag {
+            1 => {
+                let len = usize::from(read_u2(bytes, &mut ix)?);
+                utf8[usize::from(index)] = Some(bytes.get(ix..ix + len)?);
+                ix += len;
+            }
+            3 | 4 | 9 | 10 | 11 | 12 | 17 | 18 => ix += 4,
+            // longs and doubles take two entries
+            5 | 6 => {
+                ix += 8;
+                index += 1;
+            }
+            7 | 8 | 16 | 19 | 20 => ix += 2,
+            15 => ix += 3,
+            _ => return None,
+        }
+        index += 1;
+    }
+
+    let mut stripped = bytes.get(..ix)?.to_vec();
+
+    let copy = |stripped: &mut Vec<u8>, ix: &mut usize, len: usize| -> Option<()> {
+        stripped.extend_from_slice(bytes.get(*ix..*ix + len)?);
+        *ix += len;
+        Some(())
+    };
+    let copy_attributes = |stripped: &mut Vec<u8>, ix: &mut usize| -> Option<()> {
+        let count = read_u2(bytes, ix)?;
+        let mut kept = Vec::new();
+        let mut kept_count = 0_u16;
+        for _ in 0..count {
+            let start = *ix;
+            let name = read_u2(bytes, ix)?;
+            let len = read_u4(bytes, ix)? as usize;
+            *ix += len;
+
+            let name = utf8.get(usize::from(name)).copied().flatten()?;
+            if keep(name) {
+                kept.extend_from_slice(bytes.get(start..*ix)?);
+                kept_count += 1;
+            }
+        }
+
+        stripped.extend_from_slice(&kept_count.to_be_bytes());
+        stripped.extend_from_slice(&kept);
+        Some(())
+    };
+
+    // access flags, this class and super class
+    copy(&mut stripped, &mut ix, 6)?;
+    let interfaces = usize::from(read_u2(bytes, &mut ix)?);
+    ix -= 2;
+    copy(&mut stripped, &mut ix, 2 + 2 * interfaces)?;
+
+    // fields and then methods, both have the access flags, name and descriptor before their attributes
+    for _ in 0..2 {
+        let members = read_u2(bytes, &mut ix)?;
+        stripped.extend_from_slice(&members.to_be_bytes());
+        for _ in 0..members {
+            copy(&mut stripped, &mut ix, 6)?;
+            copy_attributes(&mut stripped, &mut ix)?;
+        }
+    }
+
+    copy_attributes(&mut stripped, &mut ix)?;
+    Some(stripped)
+}
+
+/// A class file of a `public class` with the `public` methods, without code, for the declared methods of the `late_bound_classes`
+///
+/// * `methods` - whether the method is `static`, its name, `<init>` for constructors, and its descriptor
+pub(crate) fn synthesize(class: &str, methods: &[(bool, &str, &str)]) -> Vec<u8> {
+    // the constant pool is the class, the super class, and the name and descriptor of each method
+    let mut pool = Vec::<u8>::new();
+    let mut pool_count = 1_u16;
+    let mut utf8 = |pool: &mut Vec<u8>, value: &str| -> u16 {
+        pool.push(1);
+        pool.extend_from_slice(&(value.len() as u16).to_be_bytes());
+        pool.extend_from_slice(value.as_bytes());
+        pool_count += 1;
+        pool_count - 1
+    };
+
+    let this_name = utf8(&mut pool, class);
+    let super_name = utf8(&mut pool, "java/lang/Object");
+    let members = methods
+        .iter()
+        .map(|(is_static, name, descriptor)| {
+            let access: u16 = if *is_static { 0x0009 } else { 0x0001 };
+            (access, utf8(&mut pool, name), utf8(&mut pool, descriptor))
+        })
+        .collect::<Vec<_>>();
+    let this_class = pool_count;
+    let super_class = pool_count + 1;
+    for name in [this_name, super_name] {
+        pool.push(7);
+        pool.extend_from_slice(&name.to_be_bytes());
+    }
+
+    // Java 8
+    let mut bytes = vec![0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 52];
+    bytes.extend_from_slice(&(super_class + 1).to_be_bytes());
+    bytes.extend_from_slice(&pool);
+    // public and super, no interfaces or fields
+    bytes.extend_from_slice(&[0, 0x21]);
+    bytes.extend_from_slice(&this_class.to_be_bytes());
+    bytes.extend_from_slice(&super_class.to_be_bytes());
+    bytes.extend_from_slice(&[0, 0, 0, 0]);
+
+    bytes.extend_from_slice(&(members.len() as u16).to_be_bytes());
+    for (access, name, descriptor) in members {
+        bytes.extend_from_slice(&access.to_be_bytes());
+        bytes.extend_from_slice(&name.to_be_bytes());
+        bytes.extend_from_slice(&descriptor.to_be_bytes());
+        bytes.extend_from_slice(&[0, 0]);
+    }
+
+    // no attributes of the class
+    bytes.extend_from_slice(&[0, 0]);
+    bytes
+}
+
+fn read_u2(bytes: &[u8], ix: &mut usize) -> Option<u16> {
+    let value = bytes.get(*ix..*ix + 2)?;
+    *ix += 2;
+    Some(u16::from_be_bytes([value[0], value[1]]))
+}
+
+fn read_u4(bytes: &[u8], ix: &mut usize) -> Option<u32> {
+    let value = bytes.get(*ix..*ix + 4)?;
+    *ix += 4;
+    Some(u32::from_be_bytes([value[0], value[1], value[2], value[3]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `public class A` of Java 17, with a `SourceFile` attribute that is one byte short
+    fn class_with_bad_source_file() -> Vec<u8> {
+        let mut bytes = vec![0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 61, 0, 6];
+        for utf8 in [&b"A"[..], b"java/lang/Object", b"SourceFile"] {
+            bytes.push(1);
+            bytes.extend_from_slice(&(utf8.len() as u16).to_be_bytes());
+            bytes.extend_from_slice(utf8);
+        }
+        bytes.extend_from_slice(&[7, 0, 1, 7, 0, 2]);
+        // public, this is #4, super is #5, no interfaces, fields or methods
+        bytes.extend_from_slice(&[0, 0x21, 0, 4, 0, 5, 0, 0, 0, 0, 0, 0]);
+        bytes.extend_from_slice(&[0, 1, 0, 3, 0, 0, 0, 1, 0]);
+        bytes
+    }
+
+    #[test]
+    fn test_class_version() {
+        assert_eq!(class_version(&class_with_bad_source_file()), Some((61, 0)));
+        assert_eq!(class_version(b"not a class"), None);
+        assert_eq!(java_release(61), 17);
+        assert_eq!(java_release(52), 8);
+    }
+
+    #[test]
+    fn test_synthesize() {
+        let bytes = synthesize(
+            "net/bluejekyll/LateBound",
+            &[
+                (false, "<init>", "(I)V"),
+                (true, "version", "()Ljava/lang/String;"),
+            ],
+        );
+        assert_eq!(class_version(&bytes), Some((52, 0)));
+
+        let class_file = cafebabe::parse_class(&bytes).unwrap();
+        assert_eq!(class_file.this_class, "net/bluejekyll/LateBound");
+        assert_eq!(class_file.super_class.as_deref(), Some("java/lang/Object"));
+        assert_eq!(class_file.methods.len(), 2);
+        assert_eq!(class_file.methods[1].name, "version");
+        assert_eq!(
+            class_file.methods[1].descriptor.to_string(),
+            "()Ljava/lang/String;"
+        );
+        assert!(class_file.methods[1]
+            .access_flags
+            .contains(cafebabe::MethodAccessFlags::STATIC));
+    }
+
+    #[test]
+    fn test_strip_attributes() {
+        let bytes = class_with_bad_source_file();
+        assert!(cafebabe::parse_class(&bytes).is_err());
+
+        let stripped = strip_attributes(&bytes, |name| name != b"SourceFile").unwrap();
+        assert_eq!(stripped.len(), bytes.len() - 7);
+        let class_file = cafebabe::parse_class(&stripped).unwrap();
+        assert_eq!(class_file.this_class, "A");
+        assert!(class_file.attributes.is_empty());
+    }
+}
@@ -9,7 +9,7 @@
 
 #![deny(missing_docs)]
 
-use std::fmt;
+use std::{fmt, path::PathBuf};
 
 use enum_as_inner::EnumAsInner;
 use thiserror::Error;
@@ -30,9 +30,94 @@ pub enum ErrorKind {
     #[error("{0}")]
     Io(#[from] std::io::Error),
 
+    /// A generated file is missing or out of date, from `Jaffi::check`
+    #[error("{} is out of date, regenerate it:\n{diff}", path.display())]
+    Stale {
+        /// The path of the generated file
+        path: std::path::PathBuf,
+        /// The first difference, `-` is the existing file and `+` the generated one
+        diff: String,
+    },
+
     /// An error occurred with the cafebabe library
     #[error("{0}")]
     Cafebabe(#[from] cafebabe::ParseError),
+
+    /// A class was not found in any directory of the classpath
+    #[error(
+        "could not find class {class} in the classpath [{}]{}",
+        display_paths(classpath),
+        required_by.as_ref().map(|by| format!(", required by {by}")).unwrap_or_default()
+    )]
+    ClassNotFound {
+        /// The class, e.g. `net/bluejekyll/NativeStrings`
+        class: String,
+        /// The class that required it, e.g. the sealed class of a permitted subclass, `None` if it was listed in the builder
+        required_by: Option<String>,
+        /// The directories that were searched
+        classpath: Vec<PathBuf>,
+    },
+
+    /// A class file could not be read or parsed
+    #[error("could not read class file {}: {source}", path.display())]
+    ClassFile {
+        /// The path of the class file
+        path: PathBuf,
+        /// The error reading or parsing it
+        source: Error,
+    },
+
+    /// A class file is newer than the `max_java_release`
+    #[error(
+        "{} has class version {major}.{minor}, of Java {java_release}, newer than the max_java_release {max_java_release}",
+        path.display()
+    )]
+    UnsupportedVersion {
+        /// The path of the class file
+        path: PathBuf,
+        /// The major version of the class file, e.g. `61`
+        major: u16,
+        /// The minor version of the class file
+        minor: u16,
+        /// The Java release of the major version, e.g. `17`
+        java_release: u16,
+        /// The configured maximum
+        max_java_release: u16,
+    },
+
+    /// The overrides file could not be read or parsed
+    #[error("could not read the overrides file {}: {message}", path.display())]
+    Overrides {
+        /// The path of the overrides file
+        path: PathBuf,
+        /// The error reading or parsing it
+        message: String,
+    },
+
+    /// An error generating the bindings of a class, or of one of its methods
+    #[error(
+        "{class}{}{}: {source}",
+        method.as_ref().map(|method| format!(".{method}")).unwrap_or_default(),
+        descriptor.as_deref().unwrap_or_default()
+    )]
+    Class {
+        /// The class being generated, e.g. `net/bluejekyll/NativeStrings`
+        class: String,
+        /// The method, if the error is about one
+        method: Option<String>,
+        /// The descriptor of the method, e.g. `(I)V`
+        descriptor: Option<String>,
+        /// The error
+        source: Error,
+    },
+}
+
+fn display_paths(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 /// The error type for errors that get returned in the crate
@@ -50,6 +135,46 @@ impl Error {
     }
 }
 
+/// Adds the class, and method, being generated to errors
+pub(crate) trait ClassContext<T> {
+    /// The error happened while generating the `class`, e.g. `net/bluejekyll/NativeStrings`
+    fn in_class(self, class: &str) -> Result<T, Error>;
+
+    /// The error happened while generating the `method` of the `class`
+    fn in_method(self, class: &str, method: &str, descriptor: &str) -> Result<T, Error>;
+}
+
+impl<T, E: Into<Error>> ClassContext<T> for Result<T, E> {
+    fn in_class(self, class: &str) -> Result<T, Error> {
+        self.map_err(|e| {
+            let e = e.into();
+            match &*e.kind {
+                // already has the context, possibly with the method
+                ErrorKind::Class { class: c, .. } if c == class => e,
+                _ => ErrorKind::Class {
+                    class: class.to_string(),
+                    method: None,
+                    descriptor: None,
+                    source: e,
+                }
+                .into(),
+            }
+        })
+    }
+
+    fn in_method(self, class: &str, method: &str, descriptor: &str) -> Result<T, Error> {
+        self.map_err(|e| {
+            ErrorKind::Class {
+                class: class.to_string(),
+                method: Some(method.to_string()),
+                descriptor: Some(descriptor.to_string()),
+                source: e.into(),
+            }
+            .into()
+        })
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         fmt::Display::fmt(&self.kind, f)
@@ -86,9 +211,3 @@ impl From<Error> for String {
         e.to_string()
     }
 }
-
-/// A trait marking a type which implements From<Error> and
-/// std::error::Error types as well as Clone + Send
-pub(crate) trait FromError: From<Error> + std::error::Error + Clone {}
-
-impl<E> FromError for E where E: From<Error> + std::error::Error + Clone {}
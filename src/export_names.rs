@@ -0,0 +1,24 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The names of the exported native functions
+
+/// Which JNI names the native functions are exported as
+///
+/// The JVM links the short name, `Java_{class}_{method}`, first and falls back to the long name with the mangled argument descriptor,
+///   `Java_{class}_{method}__{args}`. Overloaded methods are always exported with the long name.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[non_exhaustive]
+pub enum ExportNames {
+    /// The short name, unless the method is overloaded
+    #[default]
+    Short,
+    /// Always the long name
+    Long,
+    /// Both names for methods that aren't overloaded, the long name delegates to the short one
+    Both,
+}
@@ -0,0 +1,171 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! A C header declaring the exported native functions, in the same format as `javac -h`
+
+use std::fmt::Write;
+
+use crate::template::{ClassFfi, JavaDesc};
+
+/// The C type of a field descriptor, e.g. `jint` for `I`, returns the type and the rest of the descriptor
+///
+/// `is_throwable` decides if a class, e.g. `java/lang/Exception`, is a `Throwable`, those are declared as `jthrowable`
+fn c_type<'d>(descriptor: &'d str, is_throwable: &dyn Fn(&str) -> bool) -> (&'static str, &'d str) {
+    let mut chars = descriptor.chars();
+    let ty = match chars.next() {
+        Some('Z') => "jboolean",
+        Some('B') => "jbyte",
+        Some('C') => "jchar",
+        Some('S') => "jshort",
+        Some('I') => "jint",
+        Some('J') => "jlong",
+        Some('F') => "jfloat",
+        Some('D') => "jdouble",
+        Some('V') => "void",
+        Some('L') => {
+            let (class, rest) = chars
+                .as_str()
+                .split_once(';')
+                .expect("object descriptor is missing the ';'");
+            let ty = match class {
+                "java/lang/String" => "jstring",
+                "java/lang/Class" => "jclass",
+                class if is_throwable(class) => "jthrowable",
+                _ => "jobject",
+            };
+            return (ty, rest);
+        }
+        Some('[') => {
+            let (element, rest) = c_type(chars.as_str(), is_throwable);
+            let ty = match element {
+                "jboolean" => "jbooleanArray",
+                "jbyte" => "jbyteArray",
+                "jchar" => "jcharArray",
+                "jshort" => "jshortArray",
+                "jint" => "jintArray",
+                "jlong" => "jlongArray",
+                "jfloat" => "jfloatArray",
+                "jdouble" => "jdoubleArray",
+                _ => "jobjectArray",
+            };
+            return (ty, rest);
+        }
+        ch => panic!("unexpected character in descriptor: {ch:?}"),
+    };
+
+    (ty, chars.as_str())
+}
+
+/// The C types of the arguments and the result of a method descriptor
+fn c_signature(
+    descriptor: &str,
+    is_throwable: &dyn Fn(&str) -> bool,
+) -> (Vec<&'static str>, &'static str) {
+    let (mut arguments, result) = descriptor
+        .strip_prefix('(')
+        .and_then(|descriptor| descriptor.split_once(')'))
+        .expect("method descriptor is missing the parentheses");
+
+    let mut types = Vec::new();
+    while !arguments.is_empty() {
+        let (ty, rest) = c_type(arguments, is_throwable);
+        types.push(ty);
+        arguments = rest;
+    }
+
+    (types, c_type(result, is_throwable).0)
+}
+
+/// Declares every exported function of the native classes, `guard` is used for the include guard
+pub(crate) fn generate_header(
+    class_ffis: &[ClassFfi],
+    guard: &str,
+    is_throwable: &dyn Fn(&str) -> bool,
+) -> String {
+    let mut header = String::new();
+
+    writeln!(
+        header,
+        "/* DO NOT EDIT THIS FILE - it is generated by jaffi */"
+    )
+    .unwrap();
+    writeln!(header, "#include <jni.h>").unwrap();
+    writeln!(header, "/* Native functions implemented in Rust */").unwrap();
+    writeln!(header).unwrap();
+    writeln!(header, "#ifndef _Included_{guard}").unwrap();
+    writeln!(header, "#define _Included_{guard}").unwrap();
+    writeln!(header, "#ifdef __cplusplus").unwrap();
+    writeln!(header, "extern \"C\" {{").unwrap();
+    writeln!(header, "#endif").unwrap();
+
+    for class_ffi in class_ffis {
+        for func in &class_ffi.functions {
+            let (arguments, result) = c_signature(func.signature.as_str(), is_throwable);
+            let this = if func.is_static { "jclass" } else { "jobject" };
+            let arguments = ["JNIEnv *", this]
+                .into_iter()
+                .chain(arguments)
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            for export_name in &func.fn_export_ffi_names {
+                writeln!(header, "/*").unwrap();
+                writeln!(
+                    header,
+                    " * Class:     {}",
+                    JavaDesc::from(class_ffi.class_name.as_str()).binary_name()
+                )
+                .unwrap();
+                writeln!(header, " * Method:    {}", func.name).unwrap();
+                writeln!(header, " * Signature: {}", func.signature).unwrap();
+                writeln!(header, " */").unwrap();
+                writeln!(header, "JNIEXPORT {result} JNICALL {export_name}").unwrap();
+                writeln!(header, "  ({arguments});").unwrap();
+                writeln!(header).unwrap();
+            }
+        }
+    }
+
+    writeln!(header, "#ifdef __cplusplus").unwrap();
+    writeln!(header, "}}").unwrap();
+    writeln!(header, "#endif").unwrap();
+    writeln!(header, "#endif").unwrap();
+
+    header
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_c_signature() {
+        let is_throwable = |class: &str| class.ends_with("Exception");
+
+        assert_eq!(c_signature("()V", &is_throwable), (vec![], "void"));
+        assert_eq!(
+            c_signature(
+                "(ZLjava/lang/String;[[I[JLjava/lang/Class;)Lp/SomeException;",
+                &is_throwable
+            ),
+            (
+                vec![
+                    "jboolean",
+                    "jstring",
+                    "jobjectArray",
+                    "jlongArray",
+                    "jclass"
+                ],
+                "jthrowable"
+            )
+        );
+        assert_eq!(
+            c_signature("([Ljava/lang/String;Ljava/util/List;)[D", &is_throwable),
+            (vec!["jobjectArray", "jobject"], "jdoubleArray")
+        );
+    }
+}
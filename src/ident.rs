@@ -5,6 +5,8 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
+use std::{borrow::Cow, fmt::Write};
+
 use proc_macro2::Ident;
 use quote::format_ident;
 
@@ -21,8 +23,9 @@ pub(crate) fn contains_keyword(s: &str) -> bool {
     KEYWORDS.contains(&s)
 }
 
+/// Illegal words, and names starting with a digit, e.g. from a Java name starting with an escaped character
 pub(crate) fn is_illegal(s: &str) -> bool {
-    ILLEGAL_WORDS.contains(&s)
+    ILLEGAL_WORDS.contains(&s) || s.starts_with(|ch: char| ch.is_ascii_digit())
 }
 
 pub(crate) fn make_ident(ident: &str) -> Ident {
@@ -36,3 +39,28 @@ pub(crate) fn make_ident(ident: &str) -> Ident {
         format_ident!("{ident}")
     }
 }
+
+/// Escapes the characters of a Java name that aren't ASCII alphanumerics, `/`, `$`, `.` or `_` as in the JNI names, e.g. `_000fc`
+///   for `ü` or `_0002d` for `-`, so that the Rust names derived from it are ASCII identifiers
+///
+/// Characters outside of the basic multilingual plane are escaped as their surrogate pairs.
+pub(crate) fn escape_non_ascii(name: &str) -> Cow<'_, str> {
+    let is_kept = |ch: char| ch.is_ascii_alphanumeric() || matches!(ch, '/' | '$' | '.' | '_');
+    if name.chars().all(is_kept) {
+        return Cow::Borrowed(name);
+    }
+
+    let mut escaped = String::with_capacity(name.len());
+    for ch in name.chars() {
+        if is_kept(ch) {
+            escaped.push(ch);
+            continue;
+        }
+
+        for unit in ch.encode_utf16(&mut [0; 2]) {
+            write!(escaped, "_0{unit:04x}").expect("write to String failed");
+        }
+    }
+
+    Cow::Owned(escaped)
+}
@@ -0,0 +1,117 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The JNI version requested from the JVM
+
+use std::fmt;
+
+use jaffi_support::jni::sys::{
+    jint, JNI_VERSION_10, JNI_VERSION_19, JNI_VERSION_1_6, JNI_VERSION_1_8, JNI_VERSION_20,
+    JNI_VERSION_21, JNI_VERSION_9,
+};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+
+/// The JNI version returned from the generated `JNI_OnLoad`, the JVM will refuse to load the library if it doesn't support the version
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[non_exhaustive]
+pub enum JniVersion {
+    /// `JNI_VERSION_1_6`, Java 6 and Android
+    V1_6,
+    /// `JNI_VERSION_1_8`, Java 8
+    #[default]
+    V1_8,
+    /// `JNI_VERSION_9`, Java 9
+    V9,
+    /// `JNI_VERSION_10`, Java 10
+    V10,
+    /// `JNI_VERSION_19`, Java 19
+    V19,
+    /// `JNI_VERSION_20`, Java 20
+    V20,
+    /// `JNI_VERSION_21`, Java 21
+    V21,
+}
+
+impl JniVersion {
+    /// The value of the version constant, as defined in `jni.h`
+    pub fn as_jint(self) -> jint {
+        match self {
+            Self::V1_6 => JNI_VERSION_1_6,
+            Self::V1_8 => JNI_VERSION_1_8,
+            Self::V9 => JNI_VERSION_9,
+            Self::V10 => JNI_VERSION_10,
+            Self::V19 => JNI_VERSION_19,
+            Self::V20 => JNI_VERSION_20,
+            Self::V21 => JNI_VERSION_21,
+        }
+    }
+
+    /// `GetModule` was added to the JNI functions in `JNI_VERSION_9`
+    pub(crate) fn has_modules(self) -> bool {
+        self >= Self::V9
+    }
+}
+
+impl fmt::Display for JniVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let version = match self {
+            Self::V1_6 => "JNI_VERSION_1_6",
+            Self::V1_8 => "JNI_VERSION_1_8",
+            Self::V9 => "JNI_VERSION_9",
+            Self::V10 => "JNI_VERSION_10",
+            Self::V19 => "JNI_VERSION_19",
+            Self::V20 => "JNI_VERSION_20",
+            Self::V21 => "JNI_VERSION_21",
+        };
+
+        f.write_str(version)
+    }
+}
+
+/// The path of the version constant in the generated code, e.g. `jaffi_support::jni::sys::JNI_VERSION_1_8`
+impl ToTokens for JniVersion {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let constant = format_ident!("{self}");
+        tokens.extend(quote! { jaffi_support::jni::sys::#constant })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_as_jint() {
+        assert_eq!(JniVersion::V1_6.as_jint(), 0x0001_0006);
+        assert_eq!(JniVersion::V1_8.as_jint(), 0x0001_0008);
+        assert_eq!(JniVersion::V9.as_jint(), 0x0009_0000);
+        assert_eq!(JniVersion::V10.as_jint(), 0x000a_0000);
+        assert_eq!(JniVersion::V19.as_jint(), 0x0013_0000);
+        assert_eq!(JniVersion::V20.as_jint(), 0x0014_0000);
+        assert_eq!(JniVersion::V21.as_jint(), 0x0015_0000);
+    }
+
+    #[test]
+    fn test_to_tokens() {
+        assert_eq!(
+            JniVersion::V1_6.to_token_stream().to_string(),
+            "jaffi_support :: jni :: sys :: JNI_VERSION_1_6"
+        );
+        assert_eq!(
+            JniVersion::V21.to_token_stream().to_string(),
+            "jaffi_support :: jni :: sys :: JNI_VERSION_21"
+        );
+    }
+
+    #[test]
+    fn test_has_modules() {
+        assert!(!JniVersion::V1_8.has_modules());
+        assert!(JniVersion::V9.has_modules());
+        assert!(JniVersion::V21.has_modules());
+    }
+}
@@ -20,32 +20,87 @@
     unreachable_pub
 )]
 
+mod backend;
+mod bench;
+mod check;
+mod class_file;
 mod error;
+mod export_names;
+mod header;
 mod ident;
+mod jni_version;
+mod loader;
+pub mod model;
+mod overrides;
+mod pending_exceptions;
+mod prelude;
+mod primitive_types;
+mod provenance;
+mod provider;
+mod report;
+mod signature;
 mod template;
+mod tokens;
+mod verify;
 
+pub use backend::Backend;
 pub use error::{Error, ErrorKind};
+pub use export_names::ExportNames;
+pub use jni_version::JniVersion;
+pub use pending_exceptions::PendingExceptionChecks;
+pub use prelude::Prelude;
+pub use primitive_types::PrimitiveTypes;
+pub use provider::ClassProvider;
+pub use report::{Report, ReportEntry, ReportKind};
+pub use tokens::GeneratedTokens;
 
 use std::{
     borrow::Cow,
-    collections::{BTreeSet, HashMap, HashSet},
+    collections::{BTreeSet, HashMap, HashSet, VecDeque},
     fs::File,
-    io::{Read, Write},
+    io::Read,
+    mem,
     path::{Path, PathBuf},
+    str::FromStr,
+    time::Instant,
 };
 
-use cafebabe::{attributes::AttributeData, ClassFile, MethodAccessFlags, MethodInfo, ParseOptions};
+use cafebabe::{
+    attributes::{AnnotationElementValue, AttributeData, AttributeInfo},
+    constant_pool::LiteralConstant,
+    descriptor::{BaseType, FieldType, ReturnDescriptor, Ty},
+    ClassAccessFlags, ClassFile, FieldAccessFlags, MethodAccessFlags, MethodInfo, ParseOptions,
+};
 use heck::{ToSnakeCase, ToUpperCamelCase};
-use quote::format_ident;
+use jaffi_support::{chars::CharStrategy, string::StringStrategy};
+use log::{Level, LevelFilter};
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, ToTokens};
 use template::{
-    Arg, ClassFfi, Function, JniAbi, JniType, Object, ObjectType, Return, RustTypeName,
+    Arg, BufferBacking, ClassFfi, Constant, ConstantValue, Function, JniAbi, JniType, Mirror,
+    MirrorProperty, Object, ObjectType, Return, RustTypeName,
 };
 use typed_builder::TypedBuilder;
 
-use crate::template::{BaseJniTy, FuncAbi, JavaDesc};
+use crate::{
+    error::ClassContext,
+    ident::{contains_keyword, escape_non_ascii, is_illegal, make_ident},
+    model::Model,
+    overrides::{ArrayStrategy, MethodOverride, Overrides},
+    template::{BaseJniTy, FfiConfig, FuncAbi, JavaDesc, MethodDesc},
+};
 
 pub use jaffi_support;
 
+/// Logs to the `jaffi` target, if the level is enabled by the `log_level` of the builder
+macro_rules! jaffi_log {
+    ($jaffi:expr, $level:expr, $($arg:tt)+) => {
+        if $level <= $jaffi.log_level {
+            log::log!(target: "jaffi", $level, $($arg)+);
+        }
+    };
+}
+
 /// A utility for generating Rust FFI implementations from Java class files that contain `native` functions.
 #[derive(TypedBuilder)]
 pub struct Jaffi<'a> {
@@ -57,24 +112,554 @@ pub struct Jaffi<'a> {
     /// Name of the target jaffi file, defaults to "generated_jaffi.rs"
     #[builder(default=Path::new("generated_jaffi.rs"))]
     output_filename: &'a Path,
+    /// Name of a C header to generate in the `output_dir`, declaring the exported native functions like `javac -h` does
+    #[builder(default=None, setter(strip_option))]
+    header_filename: Option<&'a Path>,
+    /// Name of a JSON file to write the `Report` of `generate` to, in the `output_dir`
+    #[builder(default=None, setter(strip_option))]
+    report_filename: Option<&'a Path>,
+    /// Name of a Rust file to generate in the `output_dir` with criterion benchmarks of the native methods and of the wrappers
+    ///
+    /// The file defines `jaffi_benches(&mut criterion::Criterion)`, include it in a bench target with `criterion` as a dependency.
+    ///   The native methods are called through the JVM and the wrappers directly, with synthetic arguments, to measure the cost
+    ///   of crossing the boundary. This requires the `bench` feature of `jaffi_support`
+    #[builder(default=None, setter(strip_option))]
+    bench_filename: Option<&'a Path>,
+    /// The path of the generated code from the bench target of the `bench_filename`, e.g. `my_crate::bindings`, defaults to the
+    ///   `library_name`
+    #[builder(default=None, setter(strip_option))]
+    bench_bindings: Option<Cow<'a, str>>,
+    /// A Java class (specified as a java class name, i.e. `com.example.NativeLoader`) to generate, that loads the library with `System.loadLibrary`
+    ///
+    /// The source is written to the `output_dir`, in the directory of its package, with javadoc listing the native classes.
+    #[builder(default=None, setter(strip_option))]
+    loader_class: Option<Cow<'a, str>>,
+    /// The name of the library loaded by the `loader_class`, defaults to the `CARGO_PKG_NAME` with `-` replaced by `_`
+    #[builder(default=None, setter(strip_option))]
+    library_name: Option<Cow<'a, str>>,
     /// Used like ClassPath in Java, defaults to `.` if empty
     classpath: Vec<Cow<'a, Path>>,
+    /// Supplies the classes that aren't in the `classpath`, e.g. from jars or embedded resources
+    #[builder(default=None, setter(strip_option))]
+    class_provider: Option<&'a dyn ClassProvider>,
+    /// Renders the generated code in place of the default rendering of the support code, the wrappers and the native classes
+    #[builder(default=None, setter(strip_option))]
+    backend: Option<&'a dyn Backend>,
+    /// The Java release, e.g. `17`, that the classes of multi-release classpath directories are selected for
+    ///
+    /// Directories with `Multi-Release: true` in their `META-INF/MANIFEST.MF`, i.e. extracted multi-release jars, have
+    ///   variants of classes in `META-INF/versions/{release}`. The newest variant up to this release is read, defaults to
+    ///   only reading the base classes.
+    #[builder(default=None, setter(strip_option))]
+    java_release: Option<u16>,
+    /// The newest Java release, e.g. `17`, of the class files that are read, newer classes are an `ErrorKind::UnsupportedVersion`
+    ///
+    /// Defaults to reading all versions, parse errors of classes name their version.
+    #[builder(default=None, setter(strip_option))]
+    max_java_release: Option<u16>,
+    /// Classes that can't be parsed are parsed again without the attributes that aren't used, and then without any attributes
+    ///
+    /// Newer class files can have attributes, or versions of them, that the parser doesn't support. Dropped attributes are
+    ///   logged as warnings.
+    #[builder(default = false)]
+    lenient_attributes: bool,
+    /// Classes that are referenced by the wrapped classes, but missing from the classpath or unreadable, get opaque wrappers
+    ///   without methods instead of failing the generation, defaults to `false`
+    ///
+    /// Use this for classes that are only available as class files, whose dependencies aren't. Each degraded class is logged as a
+    ///   warning, and is `Downgraded` in the `Report`. The native classes, and the classes listed in the builder, are still required.
+    #[builder(default = false)]
+    lenient_missing_classes: bool,
     /// List of classes with native methods (specified as java class names, i.e. `java.lang.Object`) to generate bindings for
     native_classes: Vec<Cow<'a, str>>,
     /// List of classes that wrappers will be generated for
     #[builder(default=Vec::new())]
     classes_to_wrap: Vec<Cow<'a, str>>,
+    /// The exception class (specified as a java class name, i.e. `java.lang.RuntimeException`) that panics are thrown as, defaults to `java.lang.RuntimeException`
+    ///
+    /// The class must have a constructor that takes a single `String` message
+    #[builder(default=Cow::Borrowed("java.lang.RuntimeException"))]
+    panic_exception: Cow<'a, str>,
+    /// Path to a function, `fn(&(dyn Any + Send)) -> Cow<'_, str>`, that converts the panic payload into the exception message,
+    ///   defaults to `jaffi_support::exceptions::get_panic_message`
+    #[builder(default=None, setter(strip_option))]
+    panic_message_fn: Option<Cow<'a, str>>,
+    /// Path to a function, `fn(&JavaVM)`, that will be called from the generated `JNI_OnLoad`
+    #[builder(default=None, setter(strip_option))]
+    user_on_load_fn: Option<Cow<'a, str>>,
+    /// Path to a function, `fn(&JavaVM)`, that will be called from the generated `JNI_OnUnload`
+    ///
+    /// This is called before the registered global references are released
+    #[builder(default=None, setter(strip_option))]
+    user_on_unload_fn: Option<Cow<'a, str>>,
+    /// The path of a command type and of its handler, `fn(JNIEnv<'_>, Command)`, e.g. `("crate::Command", "crate::handle_command")`
+    ///
+    /// Generates a `JAFFI_DISPATCHER`, which `JNI_OnLoad` starts with the handler, and a `dispatch` function, which native
+    ///   methods call to send commands to the handler. It handles them in order on a worker thread attached to the JVM, e.g. to
+    ///   post results to a Java callback interface after the native method returned. `JNI_OnUnload` waits for the queued
+    ///   commands before the global references are released. This requires the `dispatch` feature of `jaffi_support`
+    #[builder(default=None, setter(strip_option))]
+    dispatcher: Option<(Cow<'a, str>, Cow<'a, str>)>,
+    /// Path to a function, `fn(class: &'static str, method: &'static str)`, that will be called on entering each exported native function
+    ///
+    /// The class is the java class name, i.e. `net.bluejekyll.NativeStrings`, and the method the Java name. It is called before
+    ///   the arguments are converted, e.g. to enter a `tracing` span.
+    #[builder(default=None, setter(strip_option))]
+    native_enter_fn: Option<Cow<'a, str>>,
+    /// Path to a function, `fn(class: &'static str, method: &'static str, elapsed: std::time::Duration)`, that will be called
+    ///   on leaving each exported native function
+    ///
+    /// It is called after the result is converted to Java, also if the implementation returned an error or panicked.
+    #[builder(default=None, setter(strip_option))]
+    native_exit_fn: Option<Cow<'a, str>>,
+    /// The JNI version returned from the generated `JNI_OnLoad`, defaults to `JNI_VERSION_1_8`, or `JNI_VERSION_1_6` for `android`
+    #[builder(default=None, setter(strip_option))]
+    jni_version: Option<JniVersion>,
+    /// Generate for Android, defaults to `false`
+    ///
+    /// `JNI_OnLoad` requests `JNI_VERSION_1_6`, the only version ART supports, so a newer `jni_version` is an error, as are the
+    ///   `listener_interfaces`, the `test_harness` and the `bench_filename`, which need APIs that ART lacks. Panics are logged to logcat before being thrown, this requires the `android` feature of `jaffi_support`.
+    #[builder(default = false)]
+    android: bool,
+    /// Generate the load hook as `pub fn {on_load_name}(vm: JavaVM) -> jint` instead of exporting `JNI_OnLoad`
+    ///
+    /// Use this when the crate defines its own `JNI_OnLoad`, which should then call the generated function.
+    #[builder(default=None, setter(strip_option))]
+    on_load_name: Option<Cow<'a, str>>,
+    /// Generate the unload hook as `pub fn {on_unload_name}(vm: JavaVM)` instead of exporting `JNI_OnUnload`
+    ///
+    /// Required with `on_load_name`, the crate's own `JNI_OnUnload` should then call the generated function.
+    #[builder(default=None, setter(strip_option))]
+    on_unload_name: Option<Cow<'a, str>>,
+    /// The strategy for converting Java Strings to Rust, set in the generated `JNI_OnLoad`, defaults to `StringStrategy::Utf16Lossy`
+    #[builder(default)]
+    string_strategy: StringStrategy,
+    /// The strategy for Java chars that are surrogates and Rust chars outside of the basic multilingual plane, set in the generated
+    ///   `JNI_OnLoad`, defaults to `CharStrategy::Lossy`
+    #[builder(default)]
+    char_strategy: CharStrategy,
+    /// The Rust types of `byte`, `char` and `boolean` in the native traits, wrapper methods and mirrors, defaults to `i8`, `char`
+    ///   and `bool`
+    #[builder(default)]
+    primitive_types: PrimitiveTypes,
+    /// `String` arguments of native methods are passed as `jaffi_support::string::JavaString`, borrowing the chars from the JVM, defaults to `false`
+    #[builder(default = false)]
+    borrowed_string_args: bool,
+    /// `byte[]` arguments of native methods are copied into a `Vec<u8>`, instead of passed as `jaffi_support::arrays::JavaByteArray`,
+    ///   defaults to `false`
+    ///
+    /// A `null` array is an empty `Vec`. Wrapper methods with `byte[]` arguments always get a `{method}_bytes` method taking `&[u8]`.
+    #[builder(default = false)]
+    byte_vec_args: bool,
+    /// `java.time.Instant`, `java.time.Duration` and `java.util.Date` are converted to `std::time::SystemTime` and `std::time::Duration`, defaults to `false`
+    ///
+    /// This requires the `time` feature of `jaffi_support`
+    #[builder(default = false)]
+    time_conversions: bool,
+    /// `java.util.UUID` is converted to `uuid::Uuid`, defaults to `false`
+    ///
+    /// This requires the `uuid` feature of `jaffi_support`
+    #[builder(default = false)]
+    uuid_conversions: bool,
+    /// `java.math.BigInteger` and `java.math.BigDecimal` are converted to `num_bigint::BigInt` and `bigdecimal::BigDecimal`, defaults to `false`
+    ///
+    /// This requires the `bignum` feature of `jaffi_support`
+    #[builder(default = false)]
+    bignum_conversions: bool,
+    /// Generated wrapper methods call into Java in a new local frame, releasing the local references of the converted arguments, defaults to `false`
+    ///
+    /// Use this when the wrappers are called in loops from native methods, see `jaffi_support::frame::with_frame`
+    #[builder(default = false)]
+    local_frames: bool,
+    /// Generated wrapper methods look up the method id once, and call Java with the `_unchecked` functions, defaults to `false`
+    ///
+    /// This skips the lookup by name and signature, and the checks of the arguments, on each call. The arguments are converted
+    ///   to the types of the signature by the wrapper, use this for wrappers that are called in tight loops.
+    #[builder(default = false)]
+    cached_method_ids: bool,
+    /// Generated wrapper methods pass their arguments to Java in a `jvalue` array pooled per thread, defaults to `false`
+    ///
+    /// This requires the `scratch` feature of `jaffi_support`, which also pools the buffers of the Strings sent to Java. The method
+    ///   ids are cached as with `cached_method_ids`, use this for wrappers called at a high frequency, see `jaffi_support::scratch`.
+    #[builder(default = false)]
+    scratch_buffers: bool,
+    /// `String` arguments of generated wrapper methods take any `impl IntoJavaValue<'j, JString<'j>>`, defaults to `false`
+    ///
+    /// This includes `String`, `&str` and `jaffi_support::string::Interned`, constants that are created in Java once and cached.
+    #[builder(default = false)]
+    interned_string_args: bool,
+    /// The global references of the wrappers get `_async` variants of the methods, returning futures, defaults to `false`
+    ///
+    /// The variants take a `jaffi_support::pool::JavaPool` and call the wrapper method on one of its threads, for async Rust code
+    ///   that calls blocking Java methods. Only the methods with arguments and results that are `Send`, e.g. primitives and `String`,
+    ///   get variants, constructors return the global reference. This requires the `pool` feature of `jaffi_support`
+    #[builder(default = false)]
+    async_wrappers: bool,
+    /// Exported native functions and wrapper methods enter a `tracing` span for each call, defaults to `false`
+    ///
+    /// The spans are at the `TRACE` level, with the class, method and signature. This requires the `tracing` feature of `jaffi_support`
+    #[builder(default = false)]
+    tracing_spans: bool,
+    /// Wrappers get `is_same_object`, `identity_hash_code` and `identity`, to compare and hash objects by their identity, defaults to `false`
+    ///
+    /// Different references to the same object don't have the same pointer, `identity` returns a `jaffi_support::identity::Identity`
+    ///   that implements `Eq` and `Hash` with `IsSameObject` and `System.identityHashCode`. The methods are prefixed with `object_`
+    ///   if the class has a method with the same name.
+    #[builder(default = false)]
+    identity_methods: bool,
+    /// All the wrapper methods return `Result<T, Exception<AnyThrowable>>`, to handle unchecked exceptions in Rust, defaults to `false`
+    ///
+    /// By default only methods that declare exceptions return a `Result`, any other exception is left pending in the JVM. With this
+    ///   option every exception is caught, and can be narrowed with `Exception::downcast`.
+    #[builder(default = false)]
+    unchecked_exceptions: bool,
+    /// Generates a `#[cfg(test)]` module with a smoke test of the native methods, defaults to `false`
+    ///
+    /// The test starts a JVM, with `libjvm` from `JAVA_HOME`, reads the native methods of each class with reflection, and checks
+    ///   that the bindings are up to date and that each method links against the cdylib of the crate, or `JAFFI_TEST_LIBRARY`.
+    ///   This requires the `harness` feature of `jaffi_support`
+    #[builder(default = false)]
+    test_harness: bool,
+    /// Generates a `JAFFI_REGISTRY` table of the bound classes, with their superclass, interfaces and methods, defaults to `false`
+    ///
+    /// `JNI_OnLoad` registers the table with `jaffi_support::class_registry`, so that `jaffi_support::registry()` lists the
+    ///   classes at run time, e.g. for debugging or a dynamic dispatch layer. `JNI_OnUnload` removes it.
+    #[builder(default = false)]
+    class_registry: bool,
+    /// The exported native functions validate their arguments in debug builds, defaults to `false`
+    ///
+    /// With `debug_assertions`, the functions check that no exception is pending, that reference arguments aren't `null`, unless
+    ///   the Rust type is an `Option`, and that arrays have the dimensions of the parameter, before calling the implementation.
+    ///   An invalid argument is thrown as a `java/lang/IllegalArgumentException` naming the method and the argument
+    #[builder(default = false)]
+    validate_args: bool,
+    /// Native classes (specified as java class names) whose implementation is registered once, instead of constructed with `from_env` for each call
+    ///
+    /// The generated trait is object safe, with the `JNIEnv` passed to each method, and a `register_{trait}` function stores the `Box<dyn Trait>`.
+    ///   Use this for stateful implementations, registering them from the `user_on_load_fn`.
+    #[builder(default=Vec::new())]
+    registered_impls: Vec<Cow<'a, str>>,
+    /// Native classes (specified as java class names) whose implementation methods get a `jaffi_support::context::CallCtx`,
+    ///   bundling the `JNIEnv` with the object, or class for static methods, in place of the `this` or `class` argument
+    #[builder(default=Vec::new())]
+    context_impls: Vec<Cow<'a, str>>,
+    /// Native classes (specified as java class names) whose native methods with a Java fallback get a default implementation calling it
+    ///
+    /// The fallback is the public method with the same signature, named without the `native_method_prefix` or `native_method_suffix`,
+    ///   or without a `native` prefix or `Native` suffix if those aren't set, e.g. `foo` for `nativeFoo` or `fooNative`.
+    ///   Implementations without a `CallCtx` or registration provide the `JNIEnv` with `jni_env`.
+    #[builder(default=Vec::new())]
+    java_fallback_impls: Vec<Cow<'a, str>>,
+    /// Packages (specified as java package names, i.e. `net.bluejekyll.aggregate`) whose native classes are implemented with one
+    ///   trait, instead of a trait for each class
+    ///
+    /// The trait is named for the package, e.g. `NetBluejekyllAggregateRs`, and implemented by `NetBluejekyllAggregateRsImpl`. The
+    ///   methods are prefixed with the snake case of their class, e.g. `native_adder_add` for `NativeAdder.add`. Classes in the
+    ///   `registered_impls` or `context_impls` keep their own traits.
+    #[builder(default=Vec::new())]
+    aggregate_packages: Vec<Cow<'a, str>>,
+    /// Native classes (specified as java class names) whose `static native boolean cancel(long handle)` or
+    ///   `static native void cancel(long handle)` gets a default implementation cancelling the handle
+    ///
+    /// The handles are from `jaffi_support::Cancellation::register`, so that Java can request the cancellation of a long-running
+    ///   Rust computation with the `Cancellation`. The result is `false` if the handle was already released.
+    #[builder(default=Vec::new())]
+    cancel_handles: Vec<Cow<'a, str>>,
+    /// Native methods, specified as `{class}#{method}`, i.e. `net.bluejekyll.NativeArrays#sumArray`, or a java class name for all
+    ///   its native methods, that ensure the capacity for local references with `EnsureLocalCapacity` before converting the arguments
+    ///
+    /// Use this for native methods that create more than the 16 local references the JVM guarantees. If the capacity can't be
+    ///   ensured an `OutOfMemoryError` is thrown without calling the implementation. A method overrides its class.
+    #[builder(default=Vec::new())]
+    local_capacities: Vec<(Cow<'a, str>, i32)>,
+    /// A TOML file, with the extension `toml`, or a JSON file of per-method overrides of the generation
+    ///
+    /// The overrides are keyed by the java class name and then by the method name, for all its overloads, or the name with the
+    ///   descriptor, e.g. `sumArray([I)I`. They can `rename` the Rust method, force a `Result` with `result`, pass the `String`
+    ///   arguments at the `nullable` indexes as `Option<String>`, choose how the `byte[]` arguments of a native method are passed
+    ///   with `arrays`, `"borrowed"` or `"vec"`, or `skip` the method. Add the file to `cargo:rerun-if-changed`.
+    #[builder(default=None, setter(strip_option))]
+    overrides_file: Option<&'a Path>,
+    /// Java beans (specified as java class names) that get a plain Rust struct mirror, `{Wrapper}Bean`, with `from_java` and `to_java` conversions
+    ///
+    /// The properties are the public getters, `getX` or `isX`, with a matching setter, `setX`. Property types must be primitives,
+    ///   boxed primitives or `String`s, which are `Option<String>`. The bean must have a public no-argument constructor.
+    ///
+    /// Records get a `{Wrapper}Record` mirror without being listed, `to_java` creates them with the canonical constructor.
+    #[builder(default=Vec::new())]
+    bean_classes: Vec<Cow<'a, str>>,
+    /// Interfaces (specified as java class names) with a single abstract method, i.e. listeners, whose wrappers get `from_fn`
+    ///
+    /// `from_fn` implements the interface with a Rust closure, taking and returning the Rust types of the method, and returns a
+    ///   `jaffi_support::listener::Listener`. The interfaces are wrapped as well. Interfaces without a single abstract method are
+    ///   listed as skipped in the report.
+    #[builder(default=Vec::new())]
+    listener_interfaces: Vec<Cow<'a, str>>,
+    /// The mirrors of the `bean_classes`, and of records, derive `serde::Serialize` and `serde::Deserialize`, the properties keep
+    ///   their Java names, defaults to `false`
+    ///
+    /// This requires a dependency on `serde`, with the `derive` feature
+    #[builder(default = false)]
+    bean_serde: bool,
+    /// Java classes (specified as java class names) backed by a direct `java.nio.ByteBuffer` field, with the path of the `#[repr(C)]`
+    ///   Rust struct laid out in the buffer, e.g. `("net.bluejekyll.Particle", "crate::Particle")`
+    ///
+    /// The wrappers get `buffer_view` and `buffer_view_mut`, which map the buffer onto the struct without a JNI call per field. The
+    ///   class must have exactly one instance field of type `ByteBuffer`, and the struct must implement `jaffi_support::buffer::BufferStruct`.
+    #[builder(default=Vec::new())]
+    buffer_classes: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+    /// Java classes (specified as java class names) with the path of a Rust type used in their place in the generated signatures,
+    ///   e.g. `("net.bluejekyll.Money", "crate::Money")`
+    ///
+    /// The Rust type must implement `jaffi_support::JavaBridge` for the wrapper of the class, the native traits and the wrapper
+    ///   methods then take and return the Rust type, converting it at the boundary. The classes are wrapped, the methods of a
+    ///   bridged class keep its wrapper, so that the conversions can call them.
+    #[builder(default=Vec::new())]
+    bridges: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+    /// Java classes (specified as java class names) that are only available at run time, with the methods to wrap, e.g.
+    ///   `("com.example.Sdk", vec!["<init>(I)V", "version()Ljava/lang/String;", "static parse(Ljava/lang/String;)I"])`
+    ///
+    /// The class files of these classes aren't read, the wrappers are generated from the declared names and descriptors, prefixed
+    ///   with `static ` for static methods. The wrapper methods look up the methods with `jaffi_support::reflect` on first use,
+    ///   and `bind` checks that the class and all the declared methods are available.
+    #[builder(default=Vec::new())]
+    late_bound_classes: Vec<(Cow<'a, str>, Vec<Cow<'a, str>>)>,
+    /// Java classes (specified as java class names) whose package-private methods are wrapped as well, e.g. for test harnesses
+    ///   or migrations
+    ///
+    /// JNI doesn't check the access of methods, so the wrappers call these like the public methods, without `setAccessible` or a
+    ///   `MethodHandles.Lookup`. The docs of the wrapper methods mark them as package-private, they aren't part of the API of the
+    ///   class and can change without notice. Private and protected methods are never wrapped. The classes are wrapped as well.
+    #[builder(default=Vec::new())]
+    package_private_classes: Vec<Cow<'a, str>>,
+    /// The JNI names native functions are exported as, defaults to `ExportNames::Short`
+    #[builder(default)]
+    export_names: ExportNames,
+    /// When the wrapper methods check for a pending exception before calling into Java, defaults to
+    ///   `PendingExceptionChecks::Never`
+    ///
+    /// A wrapper that catches exceptions returns the pending exception as its `Err`, the others panic, naming the method, instead
+    ///   of calling into Java with the exception pending
+    #[builder(default)]
+    pending_exception_checks: PendingExceptionChecks,
+    /// A prefix stripped from the Rust names of native methods, e.g. `native` for `nativeFoo`, which is implemented as `foo`
+    ///
+    /// Only stripped when followed by an upper case letter, the exported JNI names are unchanged
+    #[builder(default=None, setter(strip_option))]
+    native_method_prefix: Option<Cow<'a, str>>,
+    /// A suffix stripped from the Rust names of native methods, e.g. `Native` for `fooNative`, which is implemented as `foo`
+    #[builder(default=None, setter(strip_option))]
+    native_method_suffix: Option<Cow<'a, str>>,
+    /// The most verbose level of the `log` records of the generation, defaults to `LevelFilter::Info`
+    ///
+    /// The records go to the logger of the build script, e.g. `env_logger`, nothing is printed without one. `Info` logs a summary,
+    ///   `Debug` each class with the time it took, and `Trace` the methods read from each class.
+    #[builder(default = LevelFilter::Info)]
+    log_level: LevelFilter,
+    /// The name of the lifetime of the JNI references in the generated code, without the `'`, defaults to `j`
+    ///
+    /// The `*Rs` traits and the wrappers are generic over this lifetime, e.g. `jni` generates `NetBluejekyllNativeStrings<'jni>`.
+    #[builder(default=None, setter(strip_option))]
+    lifetime_name: Option<Cow<'a, str>>,
+    /// Wraps the generated code in a private module with this name, and re-exports its public items with a glob
+    ///
+    /// The imports of the generated code then don't collide with the items of the module the file is `include!`d into, and
+    ///   items of that module take precedence over generated items with the same name. The `*RsImpl`s are still in that module.
+    #[builder(default=None, setter(strip_option))]
+    inner_module: Option<Cow<'a, str>>,
+    /// The wrappers and native classes are generated in nested modules mirroring their Java packages, e.g.
+    ///   `net::bluejekyll`, defaults to `false`
+    ///
+    /// The items are also re-exported at the top, so the flat names keep working. In its package module an item prefixed with the
+    ///   package also gets an alias without it, e.g. `net::bluejekyll::NativeStrings` for `NetBluejekyllNativeStrings`, unless that
+    ///   name is already used. The `*RsImpl`s are still in the module including the generated code.
+    #[builder(default = false)]
+    package_modules: bool,
+    /// Generates a `prelude` module re-exporting the items an implementation of the native traits typically needs
+    ///
+    /// `Prelude` selects the native traits, the wrappers, the exceptions and the types of `jaffi_support` and `jni`, e.g.
+    ///   `prelude(Prelude::default())` for all of them. Implementations then only need `use generated::prelude::*;`.
+    #[builder(default=None, setter(strip_option))]
+    prelude: Option<Prelude>,
 }
 
 impl<'a> Jaffi<'a> {
     /// Generate the rust FFI files based on the configured inputs
-    pub fn generate(&self) -> Result<(), Error> {
+    ///
+    /// The returned `Report` lists the methods and types that were skipped, downgraded or renamed
+    pub fn generate(&self) -> Result<Report, Error> {
+        let (files, report) = self.render()?;
+
+        for (path, contents) in files {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, contents)?;
+        }
+
+        Ok(report)
+    }
+
+    /// Generates the files in memory and compares them to the existing files, without writing anything
+    ///
+    /// Returns an `ErrorKind::Stale` error, with a diff of the first difference, if a file is missing or out of date.
+    ///   Rust files are compared by their tokens, and also formatted with `rustfmt` if it's installed, so formatting them
+    ///   doesn't make them stale.
+    ///   Use this in CI for projects that commit the generated bindings.
+    pub fn check(&self) -> Result<Report, Error> {
+        let (files, report) = self.render()?;
+
+        for (path, expected) in files {
+            let found = match std::fs::read_to_string(&path) {
+                Ok(found) => found,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+                Err(e) => return Err(e.into()),
+            };
+
+            let diff = if path.extension().is_some_and(|ext| ext == "rs") {
+                check::diff_tokens(&expected, &found).and_then(|diff| {
+                    match check::rustfmt(&expected) {
+                        Some(formatted) => check::diff_tokens(&formatted, &found),
+                        None => Some(diff),
+                    }
+                })
+            } else {
+                check::diff_lines(&expected, &found)
+            };
+
+            if let Some(diff) = diff {
+                return Err(ErrorKind::Stale { path, diff }.into());
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Generate the Rust FFI code, without writing any files
+    ///
+    /// This is the content of the `output_filename` written by `generate`, for tools that post-process or embed it
+    pub fn generate_tokens(&self) -> Result<TokenStream, Error> {
+        Ok(self.generate_class_tokens()?.to_token_stream())
+    }
+
+    /// Generate the Rust FFI code split up by class, without writing any files
+    pub fn generate_class_tokens(&self) -> Result<GeneratedTokens, Error> {
+        Ok(self.prepare()?.0)
+    }
+
+    /// Reads the classes into the model of the classes, methods and types that code is generated for, see `model::Visitor`
+    pub fn model(&self) -> Result<Model, Error> {
+        Ok(self.prepare()?.0.model)
+    }
+
+    /// Renders all the files, returned with their paths in the `output_dir`
+    fn render(&self) -> Result<(Vec<(PathBuf, String)>, Report), Error> {
+        let (tokens, class_ffis) = self.prepare()?;
+        let mut files = Vec::new();
+
+        // render the file
+        let output_dir = self.output_dir;
+
+        if let Some(header_filename) = self.header_filename {
+            let guard = header_filename
+                .file_stem()
+                .map(|stem| stem.to_string_lossy())
+                .unwrap_or_default()
+                .replace(|ch: char| !ch.is_ascii_alphanumeric(), "_");
+            let header =
+                header::generate_header(&class_ffis, &guard, &|class| self.is_throwable(class));
+            files.push((output_dir.join(header_filename), header));
+        }
+
+        if let Some(loader_class) = &self.loader_class {
+            let library_name = self.library_name("loader_class")?;
+            let loader = loader::generate_loader(&class_ffis, loader_class, &library_name);
+            let loader_file = output_dir.join(class_to_path(loader_class).with_extension("java"));
+            files.push((loader_file, loader));
+        }
+
+        // we always generate to the same file name, the provenance is in comments at the top
+        let rust_file = output_dir.join(self.output_filename);
+        let rust = format!(
+            "{}{}",
+            tokens.provenance.to_comment(),
+            tokens.to_token_stream()
+        );
+        files.push((rust_file, rust));
+
+        if let Some(report_filename) = self.report_filename {
+            files.push((output_dir.join(report_filename), tokens.report.to_json()));
+        }
+
+        if let Some(bench_filename) = self.bench_filename {
+            let library_name = self.library_name("bench_filename")?;
+            let bindings = parse_fn_path("bench_bindings", &self.bench_bindings)?
+                .unwrap_or_else(|| make_ident(&library_name).into_token_stream());
+            // the benchmarks run in another directory than the build script
+            let current_dir = std::env::current_dir().unwrap_or_default();
+            let classpath = self
+                .classpath()
+                .map(|path| current_dir.join(path).display().to_string())
+                .collect::<Vec<_>>();
+
+            let benches = bench::generate_benches(
+                &tokens.model,
+                &bindings,
+                &library_name,
+                &classpath,
+                self.unchecked_exceptions,
+            );
+            files.push((output_dir.join(bench_filename), benches));
+        }
+
+        Ok((files, tokens.report))
+    }
+
+    /// The `library_name`, or the `CARGO_PKG_NAME` with `-` replaced by `_`, the `option` needing it is named in the error
+    fn library_name(&self, option: &str) -> Result<String, Error> {
+        match &self.library_name {
+            Some(library_name) => Ok(library_name.to_string()),
+            None => Ok(std::env::var("CARGO_PKG_NAME")
+                .map_err(|_| format!("library_name is required for the {option} outside of cargo"))?
+                .replace('-', "_")),
+        }
+    }
+
+    /// Reads the classes and generates the code, the native classes are also returned for the header and loader
+    fn prepare(&self) -> Result<(GeneratedTokens, Vec<ClassFfi>), Error> {
+        let start = Instant::now();
+        let mut report = Report::default();
+
+        self.check_android()?;
+        if let Some((spec, capacity)) = self.local_capacities.iter().find(|(_, c)| *c <= 0) {
+            return Err(format!(
+                "invalid local capacity of {spec}: {capacity}, it must be positive"
+            )
+            .into());
+        }
+
+        let overrides = match self.overrides_file {
+            Some(path) => Overrides::read(path)?,
+            None => Overrides::default(),
+        };
+        for (class, methods) in &self.late_bound_classes {
+            for method in methods {
+                parse_late_bound_method(method).in_class(class)?;
+            }
+        }
+
         // shared buffer for classes that are read into memory
         let mut class_ffis = Vec::<ClassFfi>::new();
         let mut argument_types = HashSet::<JavaDesc>::new();
         argument_types.extend(
             self.classes_to_wrap
                 .iter()
+                .chain(self.bean_classes.iter())
+                .chain(self.listener_interfaces.iter())
+                .chain(self.buffer_classes.iter().map(|(class, _)| class))
+                .chain(self.bridges.iter().map(|(class, _)| class))
+                .chain(self.late_bound_classes.iter().map(|(class, _)| class))
+                .chain(self.package_private_classes.iter())
                 .map(|s| JavaDesc::from(s as &str)),
         );
 
@@ -84,25 +669,52 @@ impl<'a> Jaffi<'a> {
             .iter()
             .map(|s| JavaDesc::from(s as &str))
             .collect::<Vec<_>>();
-        let classes = self.search_classpath(&native_classes)?;
+        let classes = native_classes
+            .iter()
+            .map(|class| self.search_classpath(class, None))
+            .collect::<Result<Vec<_>, _>>()?;
 
         let mut class_buf = Vec::<u8>::new();
         for class in classes {
-            let class_file = self.read_class(&class, &mut class_buf)?;
-
-            let (class_ffi, objects) = self.generate_native_impls(class_file)?;
+            let class_start = Instant::now();
+            let (class_file, _) = self.read_class(class, &mut class_buf)?;
+            let class_name = class_file.this_class.to_string();
+            let version = (class_file.major_version, class_file.minor_version);
+
+            let (class_ffi, objects) = self
+                .generate_native_impls(class_file, &overrides, &mut report)
+                .in_class(&class_name)?;
+            jaffi_log!(
+                self,
+                Level::Debug,
+                "native class {class_name}, version {}.{}, in {:?}",
+                version.0,
+                version.1,
+                class_start.elapsed()
+            );
             class_ffis.extend(class_ffi);
             argument_types.extend(objects);
         }
 
-        // create the wrapper types
-        let objects = self.generate_support_types(argument_types)?;
+        // fail here rather than with an UnsatisfiedLinkError at runtime
+        verify::verify_export_names(&class_ffis)?;
 
-        // render the file
-        let output_dir = self.output_dir;
+        // exceptions thrown from the native methods are wrapped so that their payloads are accessible
+        let exception_types = class_ffis
+            .iter()
+            .flat_map(|o| o.functions.iter())
+            .flat_map(|f| f.exceptions.iter())
+            .filter(|e| ObjectType::from(*e).as_object().is_some())
+            .cloned()
+            .collect::<HashSet<_>>();
 
-        // we always generate to the same file name
-        let rust_file = output_dir.join(&self.output_filename);
+        // create the wrapper types
+        let mut objects =
+            self.generate_support_types(argument_types, exception_types, &overrides, &mut report)?;
+        self.aggregate_native_classes(&mut class_ffis);
+        self.find_java_fallbacks(&mut class_ffis, &objects, &mut report);
+        self.find_cancel_handles(&mut class_ffis);
+        add_native_factories(&class_ffis, &mut objects);
 
         // collect all the exception types
         let exceptions = objects
@@ -130,83 +742,525 @@ impl<'a> Jaffi<'a> {
             .cloned()
             .collect();
 
-        let ffi_tokens = template::generate_java_ffi(objects, class_ffis, exceptions);
-        let rendered = ffi_tokens.to_string();
+        let lifetime_name = self.lifetime_name()?;
+        let config = FfiConfig {
+            panic_exception: JavaDesc::from(&self.panic_exception as &str),
+            panic_message_fn: parse_fn_path("panic_message_fn", &self.panic_message_fn)?,
+            user_on_load_fn: parse_fn_path("user_on_load_fn", &self.user_on_load_fn)?,
+            user_on_unload_fn: parse_fn_path("user_on_unload_fn", &self.user_on_unload_fn)?,
+            dispatcher: self
+                .dispatcher
+                .as_ref()
+                .map(|(command, handler)| {
+                    TokenStream::from_str(command)
+                        .and_then(|command| Ok((command, TokenStream::from_str(handler)?)))
+                        .map_err(|e| Error::from(format!("invalid dispatcher: {e}")))
+                })
+                .transpose()?,
+            native_enter_fn: parse_fn_path("native_enter_fn", &self.native_enter_fn)?,
+            native_exit_fn: parse_fn_path("native_exit_fn", &self.native_exit_fn)?,
+            jni_version: self.jni_version()?,
+            on_load_name: self.on_load_name.as_deref().map(make_ident),
+            on_unload_name: self.on_unload_name()?,
+            string_strategy: self.string_strategy,
+            char_strategy: self.char_strategy,
+            primitive_types: self.primitive_types,
+            local_frames: self.local_frames,
+            cached_method_ids: self.cached_method_ids,
+            scratch_buffers: self.scratch_buffers,
+            interned_string_args: self.interned_string_args,
+            tracing_spans: self.tracing_spans,
+            test_harness_classpath: self.test_harness.then(|| {
+                // the tests run in another directory than the build script
+                let current_dir = std::env::current_dir().unwrap_or_default();
+                self.classpath()
+                    .map(|path| current_dir.join(path).display().to_string())
+                    .collect()
+            }),
+            class_registry: self.class_registry,
+            validate_args: self.validate_args,
+            pending_exception_checks: self.pending_exception_checks,
+            android: self.android,
+            bean_serde: self.bean_serde,
+            identity_methods: self.identity_methods,
+            unchecked_exceptions: self.unchecked_exceptions,
+            inner_module: self.inner_module.as_deref().map(make_ident),
+            package_modules: self.package_modules,
+            async_wrappers: self.async_wrappers,
+            prelude: self.prelude,
+            options: self.generation_options(),
+        };
+
+        let object_count = objects.len();
+        let model = Model::new(&class_ffis, &objects);
+        let mut tokens = template::generate_java_ffi(objects, &class_ffis, exceptions, &config);
+        if let Some(backend) = self.backend {
+            render_backend(backend, &model, &mut tokens);
+        }
+        if let Some(lifetime_name) = &lifetime_name {
+            tokens.support =
+                template::rename_lifetime(mem::take(&mut tokens.support), lifetime_name);
+            for class_tokens in tokens
+                .wrappers
+                .values_mut()
+                .chain(tokens.native_classes.values_mut())
+            {
+                *class_tokens = template::rename_lifetime(mem::take(class_tokens), lifetime_name);
+            }
+        }
+        jaffi_log!(
+            self,
+            Level::Info,
+            "generated {} native classes and {object_count} wrappers in {:?}",
+            class_ffis.len(),
+            start.elapsed()
+        );
+        report.finish();
+        tokens.report = report;
+        tokens.model = model;
+
+        Ok((tokens, class_ffis))
+    }
+
+    /// The options that change the generated code, e.g. `time_conversions`, for the provenance of the generated file
+    ///
+    /// Paths and the options that only affect the other files or the logging aren't included.
+    fn generation_options(&self) -> Vec<String> {
+        let flags = [
+            ("lenient_attributes", self.lenient_attributes),
+            ("lenient_missing_classes", self.lenient_missing_classes),
+            ("android", self.android),
+            ("borrowed_string_args", self.borrowed_string_args),
+            ("byte_vec_args", self.byte_vec_args),
+            ("time_conversions", self.time_conversions),
+            ("uuid_conversions", self.uuid_conversions),
+            ("bignum_conversions", self.bignum_conversions),
+            ("local_frames", self.local_frames),
+            ("cached_method_ids", self.cached_method_ids),
+            ("scratch_buffers", self.scratch_buffers),
+            ("interned_string_args", self.interned_string_args),
+            ("tracing_spans", self.tracing_spans),
+            ("async_wrappers", self.async_wrappers),
+            ("identity_methods", self.identity_methods),
+            ("unchecked_exceptions", self.unchecked_exceptions),
+            ("test_harness", self.test_harness),
+            ("class_registry", self.class_registry),
+            ("validate_args", self.validate_args),
+            ("bean_serde", self.bean_serde),
+            ("unsigned_bytes", self.primitive_types.unsigned_bytes),
+            ("utf16_chars", self.primitive_types.utf16_chars),
+            ("numeric_booleans", self.primitive_types.numeric_booleans),
+            ("package_modules", self.package_modules),
+        ];
+        let lists = [
+            ("registered_impls", &self.registered_impls),
+            ("context_impls", &self.context_impls),
+            ("java_fallback_impls", &self.java_fallback_impls),
+            ("aggregate_packages", &self.aggregate_packages),
+            ("cancel_handles", &self.cancel_handles),
+            ("bean_classes", &self.bean_classes),
+            ("listener_interfaces", &self.listener_interfaces),
+            ("package_private_classes", &self.package_private_classes),
+        ];
+        let pairs = [
+            ("buffer_classes", &self.buffer_classes),
+            ("bridges", &self.bridges),
+        ];
+
+        let mut options = vec![
+            format!("string_strategy={:?}", self.string_strategy),
+            format!("char_strategy={:?}", self.char_strategy),
+            format!("export_names={:?}", self.export_names),
+            format!(
+                "pending_exception_checks={:?}",
+                self.pending_exception_checks
+            ),
+        ];
+        options.extend(self.prelude.map(|prelude| format!("prelude={prelude:?}")));
+        options.extend(self.jni_version.map(|v| format!("jni_version={v}")));
+        options.extend(
+            flags
+                .into_iter()
+                .filter(|(_, enabled)| *enabled)
+                .map(|(name, _)| name.to_string()),
+        );
+        options.extend(
+            lists
+                .into_iter()
+                .filter(|(_, list)| !list.is_empty())
+                .map(|(name, list)| format!("{name}={}", list.join(","))),
+        );
+        options.extend(pairs.into_iter().filter(|(_, list)| !list.is_empty()).map(
+            |(name, list)| {
+                let list = list
+                    .iter()
+                    .map(|(class, rust)| format!("{class}:{rust}"))
+                    .collect::<Vec<_>>();
+                format!("{name}={}", list.join(","))
+            },
+        ));
+        options.extend(
+            self.late_bound_classes.iter().map(|(class, methods)| {
+                format!("late_bound_classes={class}:{}", methods.join(","))
+            }),
+        );
+        options.extend(
+            self.overrides_file
+                .map(|path| format!("overrides_file={}", path.display())),
+        );
+
+        options
+    }
+
+    /// Returns the path to the class file, or an error listing the classpath if it isn't found
+    ///
+    /// * `required_by` - the class that required this one, `None` if it was listed in the builder
+    fn search_classpath(
+        &self,
+        class: &JavaDesc,
+        required_by: Option<&JavaDesc>,
+    ) -> Result<ClassSource, Error> {
+        self.find_class(class).ok_or_else(|| {
+            ErrorKind::ClassNotFound {
+                class: class.to_string(),
+                required_by: required_by.map(ToString::to_string),
+                classpath: self.classpath().map(Path::to_path_buf).collect(),
+            }
+            .into()
+        })
+    }
+
+    /// The classpath, `.` if none was configured
+    fn classpath(&self) -> impl Iterator<Item = &Path> + '_ {
+        let default_classpath = self.classpath.is_empty().then(|| Path::new("."));
+        default_classpath
+            .into_iter()
+            .chain(self.classpath.iter().map(|path| &**path))
+    }
+
+    /// Returns the class file if it is in the classpath, or supplied by the `class_provider`
+    ///
+    /// The `late_bound_classes` aren't looked up, their class files are synthesized from the declared methods.
+    fn find_class(&self, class: &JavaDesc) -> Option<ClassSource> {
+        // the module descriptors of modular jars are not classes
+        if class.class_name() == "module-info" {
+            return None;
+        }
+
+        if let Some((_, methods)) = self
+            .late_bound_classes
+            .iter()
+            .find(|(late_bound, _)| JavaDesc::from(&**late_bound) == *class)
+        {
+            // the declarations are checked by `late_bound_methods` before the generation
+            let methods = methods
+                .iter()
+                .filter_map(|method| parse_late_bound_method(method).ok())
+                .collect::<Vec<_>>();
+            return Some(ClassSource::Provided {
+                class: class.clone(),
+                bytes: class_file::synthesize(class.as_str(), &methods),
+            });
+        }
+
+        let class_path = class_to_path(class.as_str());
+
+        #[allow(clippy::unimplemented)]
+        for classpath in self.classpath() {
+            if classpath.is_dir() {
+                if let Some(path) = self.lookup_from_path(classpath, &class_path) {
+                    return Some(ClassSource::File(path));
+                }
+            } else if classpath.is_file()
+                && classpath.extension().unwrap_or_default() == "jar"
+                && self.class_provider.is_none()
+            {
+                unimplemented!("jar files for classpath not yet supported, use a class_provider")
+            }
+        }
+
+        let bytes = self.class_provider?.load(class.as_str())?;
+        Some(ClassSource::Provided {
+            class: class.clone(),
+            bytes,
+        })
+    }
+
+    /// Returns the path of the class in the classpath directory, the variant for the `java_release` in multi-release directories
+    fn lookup_from_path(&self, classpath: &Path, class: &Path) -> Option<PathBuf> {
+        if let Some(java_release) = self.java_release.filter(|_| is_multi_release(classpath)) {
+            // the versioned directories start with Java 9, the newest variant up to the release is used, like the JVM
+            let versions = classpath.join("META-INF").join("versions");
+            for release in (9..=java_release).rev() {
+                let path = versions.join(release.to_string()).join(class);
+                if path.is_file() {
+                    return Some(path);
+                }
+            }
+        }
 
-        let mut rust_file = File::create(rust_file)?;
-        rust_file.write_all(rendered.as_bytes())?;
+        let path = classpath.join(class);
+        path.is_file().then_some(path)
+    }
 
-        Ok(())
+    /// The version returned from `JNI_OnLoad`, Android only supports `JNI_VERSION_1_6`
+    fn jni_version(&self) -> Result<JniVersion, Error> {
+        match self.jni_version {
+            Some(jni_version) if self.android && jni_version > JniVersion::V1_6 => Err(format!(
+                "android only supports JNI_VERSION_1_6, but jni_version is {jni_version}"
+            )
+            .into()),
+            Some(jni_version) => Ok(jni_version),
+            None if self.android => Ok(JniVersion::V1_6),
+            None => Ok(JniVersion::default()),
+        }
     }
 
-    fn search_classpath(&self, classes: &[JavaDesc]) -> Result<Vec<PathBuf>, Error> {
-        let default_classpath = &[Cow::Borrowed(Path::new("."))] as &[_];
-        let classpath = if self.classpath.is_empty() {
-            default_classpath
+    /// The options that don't work on ART are errors with `android`
+    ///
+    /// The handler classes of `listener_interfaces` are JVM class files defined with `DefineClass` in a `URLClassLoader`, ART only
+    ///   loads dex files. The `test_harness` and the `bench_filename` start a JVM with the invocation API, which apps can't use.
+    fn check_android(&self) -> Result<(), Error> {
+        if !self.android {
+            return Ok(());
+        }
+
+        let unsupported = [
+            ("listener_interfaces", !self.listener_interfaces.is_empty()),
+            ("test_harness", self.test_harness),
+            ("bench_filename", self.bench_filename.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(option, enabled)| enabled.then_some(option))
+        .collect::<Vec<_>>();
+
+        if unsupported.is_empty() {
+            Ok(())
         } else {
-            self.classpath.as_slice()
+            Err(format!("android doesn't support {}", unsupported.join(", ")).into())
+        }
+    }
+
+    /// The `on_unload_name`, the unload hook can't be exported when the crate defines its own `JNI_OnLoad`
+    fn on_unload_name(&self) -> Result<Option<Ident>, Error> {
+        match (&self.on_load_name, &self.on_unload_name) {
+            (Some(on_load_name), None) => Err(format!(
+                "on_load_name {on_load_name} requires an on_unload_name, for the crate's JNI_OnUnload to call"
+            )
+            .into()),
+            (_, on_unload_name) => Ok(on_unload_name.as_deref().map(make_ident)),
+        }
+    }
+
+    /// The `lifetime_name`, `None` for the default `'j`
+    fn lifetime_name(&self) -> Result<Option<Ident>, Error> {
+        let Some(name) = self.lifetime_name.as_deref() else {
+            return Ok(None);
         };
+        let name = name.strip_prefix('\'').unwrap_or(name);
+
+        // `'s` is also used by the generated code
+        let is_valid = name
+            .chars()
+            .next()
+            .is_some_and(|ch| ch.is_ascii_alphabetic() || ch == '_')
+            && name
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+            && !contains_keyword(name)
+            && !is_illegal(name)
+            && name != "s";
+        if !is_valid {
+            return Err(format!(
+                "invalid lifetime_name {name}, it must be an identifier other than a keyword or `s`"
+            )
+            .into());
+        }
 
-        // create all the classes
-        let mut found_classes = Vec::new();
-        for class in classes {
-            let class = class_to_path(class.as_str());
+        Ok((name != "j").then(|| format_ident!("{name}")))
+    }
 
-            let mut found_class = false;
+    /// Logs a warning, and reports the class as downgraded to a wrapper without methods, for `lenient_missing_classes`
+    fn degrade(&self, class: &JavaDesc, reason: &str, report: &mut Report) {
+        jaffi_log!(
+            self,
+            Level::Warn,
+            "{class} is {reason}, the wrapper has no methods"
+        );
+        report.push(
+            ReportKind::Downgraded,
+            class.as_str(),
+            None,
+            format!("{reason}, the wrapper has no methods"),
+        );
+    }
 
-            #[allow(clippy::unimplemented)]
-            'search: for classpath in classpath {
-                if classpath.is_dir() && lookup_from_path(&*classpath, &class) {
-                    found_class = true;
-                    found_classes.push(classpath.join(&class));
-                    break 'search;
-                } else if classpath.is_file() && classpath.extension().unwrap_or_default() == "jar"
-                {
-                    unimplemented!("jar files for classpath not yet supported")
-                } else {
-                    continue 'search;
-                };
+    /// Follows the superclasses of the class in the classpath, to decide if it's a `java.lang.Throwable`
+    ///
+    /// The JDK isn't in the classpath, so classes outside of it are only known to be throwable if they extend one of
+    ///   the `java.lang` exception base classes
+    fn is_throwable(&self, class: &str) -> bool {
+        let mut class_buf = Vec::<u8>::new();
+        let mut class = JavaDesc::from(class).as_str().to_string();
+
+        loop {
+            if matches!(
+                class.as_str(),
+                "java/lang/Throwable"
+                    | "java/lang/Exception"
+                    | "java/lang/Error"
+                    | "java/lang/RuntimeException"
+            ) {
+                return true;
             }
 
-            // couldn't find the class
-            if !found_class {
-                return Err(
-                    format!("could not find class in classpath: {}", class.display()).into(),
-                );
+            let Some(source) = self.find_class(&JavaDesc::from(class.as_str())) else {
+                return false;
+            };
+            let Ok((class_file, _)) = self.read_class(source, &mut class_buf) else {
+                return false;
+            };
+            let Some(super_class) = class_file.super_class else {
+                return false;
+            };
+
+            class = super_class.to_string();
+        }
+    }
+
+    /// The interfaces of the class and their super interfaces that are in the classpath, nearest first
+    ///
+    /// Like the superclasses in `is_throwable`, the interfaces of the JDK aren't in the classpath and are skipped.
+    fn super_interfaces(&self, class_file: &ClassFile<'_>) -> Vec<JavaDesc> {
+        let mut class_buf = Vec::<u8>::new();
+        let mut interfaces = Vec::<JavaDesc>::new();
+        let mut search = class_file
+            .interfaces
+            .iter()
+            .map(|interface| JavaDesc::from(&**interface))
+            .collect::<VecDeque<_>>();
+
+        while let Some(interface) = search.pop_front() {
+            if interfaces.contains(&interface) {
+                continue;
             }
+            let Some(source) = self.find_class(&interface) else {
+                continue;
+            };
+            let Ok((class_file, _)) = self.read_class(source, &mut class_buf) else {
+                continue;
+            };
+
+            search.extend(
+                class_file
+                    .interfaces
+                    .iter()
+                    .map(|interface| JavaDesc::from(&**interface)),
+            );
+            interfaces.push(interface);
         }
 
-        Ok(found_classes)
+        interfaces
     }
 
     /// # Arguments
-    /// * `path` - path to the classfile
+    /// * `source` - the classfile, from `find_class`
     /// * `class_buf` - temporary buffer to use for the parsing, this will be cleared before use
-    fn read_class(&self, path: &Path, class_buf: &'a mut Vec<u8>) -> Result<ClassFile<'a>, Error> {
+    ///
+    /// Returns the class with the fingerprint of the class file
+    fn read_class(
+        &self,
+        source: ClassSource,
+        class_buf: &'a mut Vec<u8>,
+    ) -> Result<(ClassFile<'a>, u64), Error> {
         class_buf.clear();
 
-        if !path.exists() {
-            return Err(Error::from(format!("file not found: {}", path.display())));
+        // provided classes are reported by their path relative to the classpath
+        let path = match &source {
+            ClassSource::File(path) => path.clone(),
+            ClassSource::Provided { class, .. } => class_to_path(class.as_str()),
+        };
+        let class_file_error = |source: Error| -> Error {
+            ErrorKind::ClassFile {
+                path: path.clone(),
+                source,
+            }
+            .into()
+        };
+
+        match source {
+            ClassSource::File(path) => {
+                if !path.exists() {
+                    return Err(class_file_error("file not found".into()));
+                }
+
+                File::open(&path)
+                    .and_then(|mut file| file.read_to_end(class_buf))
+                    .map_err(|e| class_file_error(e.into()))?;
+            }
+            ClassSource::Provided { bytes, .. } => *class_buf = bytes,
         }
 
-        let mut file = File::open(path)?;
-        file.read_to_end(class_buf)?;
+        let fingerprint = provenance::fingerprint(class_buf);
+        let (major, minor) = class_file::class_version(class_buf)
+            .ok_or_else(|| class_file_error("not a class file".into()))?;
+        let java_release = class_file::java_release(major);
+        if let Some(max_java_release) = self.max_java_release.filter(|max| java_release > *max) {
+            return Err(ErrorKind::UnsupportedVersion {
+                path,
+                major,
+                minor,
+                java_release,
+                max_java_release,
+            }
+            .into());
+        }
 
         let mut opts = ParseOptions::default();
         opts.parse_bytecode(false);
-        cafebabe::parse_class_with_options(class_buf, &opts).map_err(Into::into)
+
+        if self.lenient_attributes && cafebabe::parse_class_with_options(class_buf, &opts).is_err()
+        {
+            let used = |name: &[u8]| {
+                class_file::USED_ATTRIBUTES
+                    .iter()
+                    .any(|used| used.as_bytes() == name)
+            };
+            let stripped = [
+                class_file::strip_attributes(class_buf, used),
+                class_file::strip_attributes(class_buf, |_| false),
+            ]
+            .into_iter()
+            .flatten()
+            .find(|stripped| cafebabe::parse_class_with_options(stripped, &opts).is_ok());
+
+            if let Some(stripped) = stripped {
+                jaffi_log!(
+                    self,
+                    Level::Warn,
+                    "dropped the attributes that could not be parsed from {}",
+                    path.display()
+                );
+                *class_buf = stripped;
+            }
+        }
+
+        cafebabe::parse_class_with_options(class_buf, &opts)
+            .map(|class_file| (class_file, fingerprint))
+            .map_err(|e| {
+                class_file_error(
+                    format!("{e}, class version {major}.{minor} of Java {java_release}").into(),
+                )
+            })
     }
 
     /// Returns list of Support types needed as interfaces in the ABI interfaces
     fn generate_native_impls(
         &self,
         class_file: ClassFile<'_>,
+        overrides: &Overrides,
+        report: &mut Report,
     ) -> Result<(Option<ClassFfi>, HashSet<JavaDesc>), Error> {
-        eprintln!(
-            "Generating native implementations for: {}, version: {}.{}",
-            class_file.this_class, class_file.major_version, class_file.minor_version
-        );
-
         let native_methods = class_file
             .methods
             .iter()
@@ -218,39 +1272,121 @@ impl<'a> Jaffi<'a> {
             return Ok((None, HashSet::new()));
         }
 
+        // javac rejects these, but other compilers could emit them and the JVM would never link them
+        if class_file
+            .access_flags
+            .contains(ClassAccessFlags::INTERFACE)
+        {
+            return Err("native methods are not allowed in interfaces".into());
+        }
+
         // get all the function information
         let (functions, argument_objects) =
-            self.extract_function_info(&class_file, native_methods)?;
-
-        let trait_name = Path::new(&*class_file.this_class)
-            .file_name()
-            .expect("no file component")
-            .to_string_lossy()
-            .to_string()
-            + "Rs";
+            self.extract_function_info(&class_file, native_methods, overrides, report)?;
+
+        // nested classes and names that aren't ASCII need to be made into identifiers, e.g. `ShapeCircleRs` for `Shape$Circle`
+        let simple_name = JavaDesc::from(&*class_file.this_class)
+            .class_name()
+            .to_string();
+        let trait_name = if simple_name.chars().all(|ch| ch.is_ascii_alphanumeric()) {
+            simple_name
+        } else {
+            escape_non_ascii(&simple_name).to_upper_camel_case()
+        } + "Rs";
         let trait_impl = format!("{trait_name}Impl");
+        let registered_impl = self
+            .registered_impls
+            .iter()
+            .any(|class| JavaDesc::from(&**class) == JavaDesc::from(&*class_file.this_class));
+        let context_impl = self
+            .context_impls
+            .iter()
+            .any(|class| JavaDesc::from(&**class) == JavaDesc::from(&*class_file.this_class));
 
         // build up the rendering information.
         let class_ffi = template::ClassFfi {
             class_name: class_file.this_class.to_string(),
             trait_name,
             trait_impl,
+            registered_impl,
+            context_impl,
+            aggregated: false,
             functions,
         };
 
         Ok((Some(class_ffi), argument_objects))
     }
 
-    fn generate_support_types(&self, mut types: HashSet<JavaDesc>) -> Result<Vec<Object>, Error> {
+    /// # Arguments
+    ///
+    /// * `types` - all the types that need wrappers
+    /// * `exception_types` - exceptions which will have their methods wrapped if they are found in the classpath
+    /// * `overrides` - the per-method overrides of the wrapper methods
+    /// * `report` - the downgraded and skipped types and methods are added to this
+    fn generate_support_types(
+        &self,
+        mut types: HashSet<JavaDesc>,
+        mut exception_types: HashSet<JavaDesc>,
+        overrides: &Overrides,
+        report: &mut Report,
+    ) -> Result<Vec<Object>, Error> {
+        types.extend(exception_types.iter().cloned());
+
         let mut search_object_types = types.iter().cloned().collect::<Vec<_>>();
         let mut objects = Vec::<Object>::with_capacity(search_object_types.len());
         let mut already_generated = HashSet::<JavaDesc>::new();
-        let classes_to_wrap = self
+        let mut classes_to_wrap = self
             .classes_to_wrap
             .iter()
             .chain(self.native_classes.iter())
+            .chain(self.bean_classes.iter())
+            .chain(self.listener_interfaces.iter())
+            .chain(self.buffer_classes.iter().map(|(class, _)| class))
+            .chain(self.bridges.iter().map(|(class, _)| class))
+            .chain(self.late_bound_classes.iter().map(|(class, _)| class))
+            .chain(self.package_private_classes.iter())
+            .map(|s| JavaDesc::from(&**s))
+            .collect::<HashSet<_>>();
+        let listener_interfaces = self
+            .listener_interfaces
+            .iter()
             .map(|s| JavaDesc::from(&**s))
             .collect::<HashSet<_>>();
+        let bean_classes = self
+            .bean_classes
+            .iter()
+            .map(|s| JavaDesc::from(&**s))
+            .collect::<HashSet<_>>();
+        let package_private_classes = self
+            .package_private_classes
+            .iter()
+            .map(|s| JavaDesc::from(&**s))
+            .collect::<HashSet<_>>();
+        let buffer_classes = self
+            .buffer_classes
+            .iter()
+            .map(|(class, rust_struct)| {
+                let rust_struct = TokenStream::from_str(rust_struct)
+                    .map_err(|e| format!("invalid buffer_classes struct for {class}: {e}"))?;
+                Ok((JavaDesc::from(&**class), rust_struct))
+            })
+            .collect::<Result<HashMap<_, _>, Error>>()?;
+        let bridges = self
+            .bridges
+            .iter()
+            .map(|(class, rust_ty)| {
+                let rust_ty = TokenStream::from_str(rust_ty)
+                    .map_err(|e| format!("invalid bridges type for {class}: {e}"))?;
+                Ok((JavaDesc::from(&**class), rust_ty))
+            })
+            .collect::<Result<HashMap<_, _>, Error>>()?;
+        let late_bound_classes = self
+            .late_bound_classes
+            .iter()
+            .map(|(class, _)| JavaDesc::from(&**class))
+            .collect::<HashSet<_>>();
+        // the classes that weren't listed in the builder, by the class that required them
+        let mut required_by = HashMap::<JavaDesc, JavaDesc>::new();
 
         let mut class_buf = Vec::<u8>::new();
         while let Some(object_desc) = search_object_types.pop() {
@@ -260,61 +1396,256 @@ impl<'a> Jaffi<'a> {
                 already_generated.insert(object_desc.clone());
             }
 
+            let object_start = Instant::now();
             let wrap_methods = classes_to_wrap.contains(&object_desc);
+            let wrap_exception = !wrap_methods && exception_types.contains(&object_desc);
             let mut object = Object::from(ObjectType::from(&object_desc));
 
-            if wrap_methods {
-                let class = self.search_classpath(&[object_desc.clone()])?;
+            // only the classes listed in the builder are required with `lenient_missing_classes`
+            let required = !self.lenient_missing_classes
+                || (wrap_methods && !required_by.contains_key(&object_desc));
+            let mut degraded = false;
 
-                for obj_path in class {
-                    let class_file = self.read_class(&obj_path, &mut class_buf)?;
+            // classes that aren't wrapped are still inspected when available, e.g. to discover enums
+            let class = if wrap_methods && required {
+                Some(self.search_classpath(&object_desc, required_by.get(&object_desc))?)
+            } else {
+                self.find_class(&object_desc)
+            };
+
+            let class_file = match class {
+                Some(source) => match self.read_class(source, &mut class_buf) {
+                    Ok((class_file, fingerprint)) => {
+                        object.fingerprint = Some(fingerprint);
+                        Some(class_file)
+                    }
+                    Err(e) if !required => {
+                        self.degrade(&object_desc, &e.to_string(), report);
+                        degraded = true;
+                        None
+                    }
+                    Err(e) => return Err(e),
+                },
+                None if wrap_methods => {
+                    let reason = match required_by.get(&object_desc) {
+                        Some(required_by) => {
+                            format!("not found in the classpath, required by {required_by}")
+                        }
+                        None => "not found in the classpath".to_string(),
+                    };
+                    self.degrade(&object_desc, &reason, report);
+                    degraded = true;
+                    None
+                }
+                None => None,
+            };
 
-                    // collect public and non-native methods
-                    let public_methods = class_file
-                        .methods
+            if let Some(class_file) = &class_file {
+                object.deprecated = extract_deprecation(&class_file.attributes);
+                object.super_class = class_file
+                    .super_class
+                    .as_ref()
+                    .map(|super_class| JavaDesc::from(&**super_class));
+                object.declared_interfaces = class_file
+                    .interfaces
+                    .iter()
+                    .map(|interface| JavaDesc::from(&**interface))
+                    .collect();
+                if class_file.access_flags.contains(ClassAccessFlags::ENUM) {
+                    object.enum_constants = class_file
+                        .fields
                         .iter()
-                        .filter(|method_info| {
-                            !method_info.access_flags.contains(MethodAccessFlags::NATIVE)
-                                && method_info.access_flags.contains(MethodAccessFlags::PUBLIC)
-                        })
-                        .collect::<Vec<_>>();
-
-                    let (functions, new_types) =
-                        self.extract_function_info(&class_file, public_methods)?;
-
-                    // add any types to generate that we haven't seen before
-                    for ty in new_types {
-                        if !types.contains(&ty) {
-                            types.insert(ty.clone());
-                            search_object_types.push(ty);
+                        .filter(|field| field.access_flags.contains(FieldAccessFlags::ENUM))
+                        .map(|field| field.name.to_string())
+                        .collect();
+                }
+            }
+
+            if let (true, Some(class_file)) = (wrap_methods, &class_file) {
+                // the permitted subclasses of sealed classes are wrapped as well, to be matched in `classify`
+                object.permitted_subclasses = class_file
+                    .attributes
+                    .iter()
+                    .find_map(|attribute| {
+                        if let AttributeData::PermittedSubclasses(subclasses) = &attribute.data {
+                            Some(subclasses.iter().map(|s| JavaDesc::from(&**s)).collect())
+                        } else {
+                            None
                         }
+                    })
+                    .unwrap_or_default();
+
+                for subclass in &object.permitted_subclasses {
+                    classes_to_wrap.insert(subclass.clone());
+                    required_by
+                        .entry(subclass.clone())
+                        .or_insert_with(|| object_desc.clone());
+                    if types.insert(subclass.clone()) {
+                        search_object_types.push(subclass.clone());
                     }
+                }
+            }
 
-                    // find all interfaces this type supports
-                    for interface in class_file
-                        .super_class
-                        .iter()
-                        .chain(class_file.interfaces.iter())
+            if let Some(class_file) = &class_file {
+                if bean_classes.contains(&object_desc) {
+                    object.mirror = Some(Mirror::Bean(
+                        extract_bean_properties(class_file).in_class(object_desc.as_str())?,
+                    ));
+                } else {
+                    object.mirror =
+                        extract_record_components(class_file, report).map(Mirror::Record);
+                }
+            }
+
+            if let (Some(rust_struct), Some(class_file)) =
+                (buffer_classes.get(&object_desc), &class_file)
+            {
+                object.buffer = Some(BufferBacking {
+                    field: extract_buffer_field(class_file).in_class(object_desc.as_str())?,
+                    rust_struct: rust_struct.clone(),
+                });
+            }
+
+            object.bridge = bridges.get(&object_desc).cloned();
+
+            if !degraded && !wrap_methods && !wrap_exception && object.enum_constants.is_empty() {
+                let reason = if class_file.is_some() {
+                    "not in classes_to_wrap, the wrapper has no methods"
+                } else {
+                    "not found in the classpath, the wrapper has no methods"
+                };
+                report.push(ReportKind::Downgraded, object_desc.as_str(), None, reason);
+            }
+
+            if let (true, Some(class_file)) = (wrap_methods || wrap_exception, class_file) {
+                // abstract classes and interfaces can't be constructed
+                let can_construct = !class_file
+                    .access_flags
+                    .intersects(ClassAccessFlags::ABSTRACT | ClassAccessFlags::INTERFACE);
+
+                // collect public and non-native methods, and the package-private ones of the package_private_classes
+                let package_private = package_private_classes.contains(&object_desc);
+                let mut public_methods = class_file
+                    .methods
+                    .iter()
+                    .filter(|method_info| {
+                        !method_info.access_flags.contains(MethodAccessFlags::NATIVE)
+                            && (method_info.access_flags.contains(MethodAccessFlags::PUBLIC)
+                                || package_private && is_package_private(method_info))
+                            && (can_construct || method_info.name != "<init>")
+                    })
+                    .collect::<Vec<_>>();
+
+                // the default methods of the interfaces are called on the object, unless the class overrides them
+                let interfaces = self.super_interfaces(&class_file);
+                let mut interface_bufs = vec![Vec::<u8>::new(); interfaces.len()];
+                let interface_files = interface_bufs
+                    .iter_mut()
+                    .zip(&interfaces)
+                    .filter_map(|(interface_buf, interface)| {
+                        let source = self.find_class(interface)?;
+                        self.read_class(source, interface_buf).ok()
+                    })
+                    .map(|(interface_file, _)| interface_file)
+                    .collect::<Vec<_>>();
+                for method_info in interface_files
+                    .iter()
+                    .flat_map(|interface| &interface.methods)
+                {
+                    let is_default = method_info.access_flags.contains(MethodAccessFlags::PUBLIC)
+                        && !method_info
+                            .access_flags
+                            .intersects(MethodAccessFlags::ABSTRACT | MethodAccessFlags::STATIC);
+                    let declared = |declared: &MethodInfo<'_>| {
+                        declared.name == method_info.name
+                            && declared.descriptor == method_info.descriptor
+                    };
+                    if is_default
+                        && !class_file.methods.iter().any(declared)
+                        && !public_methods.iter().any(|method| declared(method))
                     {
-                        // we're only going to generate types that have been explicitly been asked for,
-                        //   or those that appear in args, that's what's in the hash_map. So unlike above
-                        //   we won't add to the types hashmap
-                        let interface = JavaDesc::from(interface as &str);
-                        if types.contains(&interface) {
-                            search_object_types.push(interface.clone());
-                            object
-                                .interfaces
-                                .push(RustTypeName::from(interface.as_str().to_upper_camel_case()));
-                        }
+                        public_methods.push(method_info);
+                    }
+                }
+
+                if !can_construct
+                    && class_file.methods.iter().any(|method_info| {
+                        method_info.name == "<init>"
+                            && method_info.access_flags.contains(MethodAccessFlags::PUBLIC)
+                    })
+                {
+                    report.push(
+                        ReportKind::Skipped,
+                        &class_file.this_class,
+                        Some("<init>"),
+                        "abstract classes can't be constructed",
+                    );
+                }
+
+                let (mut functions, new_types) = self
+                    .extract_function_info(&class_file, public_methods, overrides, report)
+                    .in_class(object_desc.as_str())?;
+                if late_bound_classes.contains(&object_desc) {
+                    for function in &mut functions {
+                        function.late_bound = true;
                     }
+                }
 
-                    // add the function to the methods in the object
-                    object.methods.extend(functions.into_iter());
+                // exceptions thrown by these methods will be wrapped as well
+                for exception in functions.iter().flat_map(|f| f.exceptions.iter()) {
+                    if ObjectType::from(exception).as_object().is_some()
+                        && exception_types.insert(exception.clone())
+                    {
+                        types.insert(exception.clone());
+                        search_object_types.push(exception.clone());
+                    }
+                }
+
+                // add any types to generate that we haven't seen before
+                for ty in new_types {
+                    if !types.contains(&ty) {
+                        types.insert(ty.clone());
+                        search_object_types.push(ty);
+                    }
                 }
+
+                // find all interfaces this type supports
+                for interface in class_file
+                    .super_class
+                    .iter()
+                    .chain(class_file.interfaces.iter())
+                {
+                    // we're only going to generate types that have been explicitly been asked for,
+                    //   or those that appear in args, that's what's in the hash_map. So unlike above
+                    //   we won't add to the types hashmap
+                    let interface = JavaDesc::from(interface as &str);
+                    if types.contains(&interface) {
+                        search_object_types.push(interface.clone());
+                        object
+                            .interfaces
+                            .push(RustTypeName::from(interface.to_rust_type_name()));
+                    }
+                }
+
+                if listener_interfaces.contains(&object_desc) {
+                    object.listener = find_listener_method(&class_file, &functions, report);
+                }
+
+                // add the function to the methods in the object
+                object.methods.extend(functions);
+                object.constants = extract_constants(&class_file, report);
             }
+            jaffi_log!(
+                self,
+                Level::Debug,
+                "wrapper {object_desc} in {:?}",
+                object_start.elapsed()
+            );
             objects.push(object);
         }
 
+        // the types were discovered in the order of a HashSet, sort them so the generated file is reproducible
+        objects.sort_by(|a, b| a.java_name.cmp(&b.java_name));
         Ok(objects)
     }
 
@@ -325,26 +1656,69 @@ impl<'a> Jaffi<'a> {
         &self,
         class_file: &ClassFile<'_>,
         methods: Vec<&MethodInfo<'_>>,
+        overrides: &Overrides,
+        report: &mut Report,
     ) -> Result<(Vec<Function>, HashSet<JavaDesc>), Error> {
-        eprintln!(
-            "Extracting function information for: {}, version: {}.{}",
-            class_file.this_class, class_file.major_version, class_file.minor_version
+        jaffi_log!(
+            self,
+            Level::Trace,
+            "methods of {}: {}",
+            class_file.this_class,
+            methods
+                .iter()
+                .map(|method| method.name.as_ref())
+                .collect::<Vec<_>>()
+                .join(", ")
         );
 
-        let method_names = methods.iter().fold(HashMap::new(), |mut map, method| {
-            // TODO: figure out how to dedup this code...
-            let method_name = if method.name == "<init>" {
-                Cow::from(format!("new_{}", class_file.this_class))
-            } else {
-                method.name.clone()
-            };
+        // a covariant override has a bridge with the return type of the overridden method, its wrapper is an alias of the override
+        let bridges = methods
+            .iter()
+            .filter(|bridge| {
+                bridge.access_flags.contains(MethodAccessFlags::BRIDGE)
+                    && methods.iter().any(|method| {
+                        !method.access_flags.contains(MethodAccessFlags::BRIDGE)
+                            && method.name == bridge.name
+                            && method.descriptor.parameters == bridge.descriptor.parameters
+                    })
+            })
+            .map(|bridge| (bridge.name.clone(), bridge.descriptor.to_string()))
+            .collect::<HashSet<_>>();
+        let is_bridge = |method: &MethodInfo<'_>| {
+            bridges.contains(&(method.name.clone(), method.descriptor.to_string()))
+        };
 
-            *map.entry(method_name).or_insert(0) += 1;
-            map
-        });
+        let method_names = methods.iter().filter(|method| !is_bridge(method)).fold(
+            HashMap::new(),
+            |mut map, method| {
+                // TODO: figure out how to dedup this code...
+                let method_name = if method.name == "<init>" {
+                    Cow::from(format!("new_{}", class_file.this_class))
+                } else {
+                    method.name.clone()
+                };
+
+                *map.entry(method_name).or_insert(0) += 1;
+                map
+            },
+        );
 
         let mut rust_method_names: HashMap<String, usize> = HashMap::new();
 
+        // overrides that don't match a method are most likely misspelled
+        for key in overrides.method_keys(&class_file.this_class) {
+            if !methods.iter().any(|method| {
+                key == method.name || key == format!("{}{}", method.name, method.descriptor)
+            }) {
+                jaffi_log!(
+                    self,
+                    Level::Warn,
+                    "the override of {}.{key} doesn't match a generated method",
+                    class_file.this_class
+                );
+            }
+        }
+
         // All objects needed to support calls into JNI from Java
         let mut argument_objects = HashSet::<JavaDesc>::new();
 
@@ -353,10 +1727,45 @@ impl<'a> Jaffi<'a> {
         let this_class = ObjectType::Object(this_class_desc.clone());
         argument_objects.insert(this_class_desc.clone());
 
+        // the conversions of a bridged class call its wrapper methods, so they aren't bridged
+        let bridges = self
+            .bridges
+            .iter()
+            .map(|(class, rust_ty)| (JavaDesc::from(&**class), rust_ty.to_string()))
+            .filter(|(class, _)| *class != this_class_desc)
+            .collect::<HashMap<_, _>>();
+
         // build up the function definitions
         let mut functions = Vec::new();
         for (index, method) in methods.into_iter().enumerate() {
-            let descriptor = JavaDesc::from(method.descriptor.to_string());
+            let descriptor =
+                MethodDesc::parse(&method.descriptor.to_string()).ok_or_else(|| {
+                    ErrorKind::Msg(format!(
+                        "invalid descriptor of {}.{}: {}",
+                        class_file.this_class, method.name, method.descriptor
+                    ))
+                })?;
+
+            // the overrides file takes precedence over the annotations in the class file
+            let file_override =
+                overrides.get(&class_file.this_class, &method.name, descriptor.as_str());
+            let method_override =
+                MethodOverride::merge(file_override, MethodOverride::from_annotations(method));
+            let method_override = method_override.as_ref();
+            if method_override.is_some_and(|o| o.skip) {
+                let reason = if file_override.is_some_and(|o| o.skip) {
+                    "skipped by the overrides"
+                } else {
+                    "skipped by @JaffiSkip"
+                };
+                report.push(
+                    ReportKind::Skipped,
+                    &class_file.this_class,
+                    Some(&method.name),
+                    reason,
+                );
+                continue;
+            }
 
             let is_constructor = method.name == "<init>";
             let is_native = method.access_flags.contains(MethodAccessFlags::NATIVE);
@@ -366,14 +1775,14 @@ impl<'a> Jaffi<'a> {
             let class_ffi_name = this_class.to_jni_class_name();
             let object_ffi_name = this_class.to_jni_type_name();
 
-            let arg_types = method
+            let mut arg_types = method
                 .descriptor
                 .parameters
                 .iter()
                 .map(JniType::from_java)
                 .collect::<Vec<_>>();
 
-            let result = if !is_constructor {
+            let mut result = if !is_constructor {
                 Return::from_java(&method.descriptor.result)
             } else {
                 Return::Val(JniType::Ty(BaseJniTy::Jobject(ObjectType::from(
@@ -381,23 +1790,162 @@ impl<'a> Jaffi<'a> {
                 ))))
             };
 
+            if is_native {
+                if let Return::Val(ty) = &mut result {
+                    ty.use_future_bridge();
+                }
+            }
+
+            // the element types of Optionals and CompletableFutures are only in the generic signature
+            let type_args = method
+                .attributes
+                .iter()
+                .find_map(|attribute| {
+                    if let AttributeData::Signature(signature) = &attribute.data {
+                        Some(signature)
+                    } else {
+                        None
+                    }
+                })
+                .and_then(|signature| signature::parse_method_signature(signature))
+                .filter(|type_args| type_args.parameters.len() == arg_types.len());
+            if let Some(type_args) = type_args {
+                for (ty, type_arg) in arg_types.iter_mut().zip(type_args.parameters) {
+                    ty.set_element_type(type_arg);
+                }
+                if let Return::Val(ty) = &mut result {
+                    ty.set_element_type(type_args.result);
+                }
+            }
+
+            // wrappers of varargs methods also get a method taking the elements of the last argument
+            let mut varargs =
+                if !is_native && method.access_flags.contains(MethodAccessFlags::VARARGS) {
+                    arg_types.last().and_then(JniType::varargs_element)
+                } else {
+                    None
+                };
+
+            for ty in arg_types
+                .iter_mut()
+                .chain(result.as_val_mut())
+                .chain(varargs.as_mut())
+            {
+                if self.time_conversions {
+                    ty.use_time_conversions();
+                }
+                if self.uuid_conversions {
+                    ty.use_uuid_conversions();
+                }
+                if self.bignum_conversions {
+                    ty.use_bignum_conversions();
+                }
+                ty.use_bridges(&bridges);
+            }
+
+            for (i, ty) in arg_types.iter().enumerate() {
+                if ty.is_unsupported_array() {
+                    report.push(
+                        ReportKind::Downgraded,
+                        &class_file.this_class,
+                        Some(&method.name),
+                        format!(
+                            "argument {i}, {}, is an UnsupportedArray",
+                            method.descriptor.parameters[i]
+                        ),
+                    );
+                }
+            }
+            if let (Return::Val(ty), ReturnDescriptor::Return(field_type)) =
+                (&result, &method.descriptor.result)
+            {
+                if ty.is_unsupported_array() {
+                    report.push(
+                        ReportKind::Downgraded,
+                        &class_file.this_class,
+                        Some(&method.name),
+                        format!("the result, {field_type}, is an UnsupportedArray"),
+                    );
+                }
+            }
+
             // Collect the Objects that need to be supported for returns and argument lists
-            for ty in arg_types.iter().chain(result.as_val().into_iter()) {
+            for ty in arg_types
+                .iter()
+                .chain(result.as_val())
+                .chain(varargs.as_ref())
+            {
+                if let Some(obj) = ty.object_array_element() {
+                    argument_objects.insert(obj.clone());
+                    continue;
+                }
+
                 match ty {
-                    JniType::Ty(BaseJniTy::Jobject(ObjectType::Object(obj))) => {
-                        argument_objects.insert(obj.clone())
+                    JniType::Ty(BaseJniTy::Jobject(
+                        ObjectType::Object(obj) | ObjectType::Bridge(obj, _),
+                    )) => argument_objects.insert(obj.clone()),
+                    JniType::Ty(BaseJniTy::Jobject(ObjectType::Optional(element))) => {
+                        if let ObjectType::Object(obj) | ObjectType::Bridge(obj, _) = &**element {
+                            argument_objects.insert(obj.clone())
+                        } else {
+                            continue;
+                        }
                     }
                     _ => continue,
                 };
             }
 
+            let nullable = method_override
+                .map(|o| o.nullable.as_slice())
+                .unwrap_or_default();
+            for &i in nullable {
+                // other references keep their type, `validate_args` accepts `null` for them
+                if !matches!(
+                    arg_types.get(i),
+                    Some(JniType::Ty(BaseJniTy::Jobject(_)) | JniType::Jarray(_))
+                ) {
+                    jaffi_log!(
+                        self,
+                        Level::Warn,
+                        "the override of {}.{} marks argument {i} nullable, which isn't a reference",
+                        class_file.this_class,
+                        method.name
+                    );
+                }
+            }
+
+            let mut java_names = parameter_names(method, arg_types.len(), is_static).into_iter();
+            let borrow_strings = is_native && self.borrowed_string_args;
+            let byte_vecs = is_native
+                && method_override
+                    .and_then(|o| o.arrays)
+                    .map_or(self.byte_vec_args, |arrays| arrays == ArrayStrategy::Vec);
             let arguments = arg_types
                 .into_iter()
                 .enumerate()
                 .map(move |(i, ty)| Arg {
                     name: format_ident!("arg{i}"),
+                    java_name: java_names.next().flatten(),
+                    nullable: nullable.contains(&i)
+                        || matches!(
+                            ty,
+                            JniType::Ty(BaseJniTy::Jobject(
+                                ObjectType::Boxed(_) | ObjectType::Optional(_)
+                            ))
+                        ),
                     ty: ty.to_jni_type_name(),
-                    rs_ty: ty.to_rs_type_name(),
+                    rs_ty: match ty {
+                        JniType::Ty(BaseJniTy::Jobject(ObjectType::JString))
+                            if nullable.contains(&i) =>
+                        {
+                            "Option<String>".into()
+                        }
+                        JniType::Ty(BaseJniTy::Jobject(ObjectType::JString)) if borrow_strings => {
+                            "jaffi_support::string::JavaString<'j>".into()
+                        }
+                        ty if byte_vecs && ty.is_byte_array() => "Vec<u8>".into(),
+                        _ => ty.to_rs_type_name_with(self.primitive_types),
+                    },
                 })
                 .collect();
 
@@ -406,37 +1954,96 @@ impl<'a> Jaffi<'a> {
             } else {
                 method.name.clone()
             };
-            let fn_ffi_name = if *method_names
+            let is_bridge = is_bridge(method);
+            let is_overloaded = method_names
                 .get(&method_name)
-                .expect("should have been added above")
-                > 1
-            {
+                .is_some_and(|count| *count > 1);
+            let short_ffi_name = FuncAbi::from(JniAbi::from(method_name));
+            let long_ffi_name = short_ffi_name.clone().with_descriptor(&descriptor);
+            let fn_ffi_name = if is_overloaded {
                 // need to long abi name
-                FuncAbi::from(JniAbi::from(method_name)).with_descriptor(&descriptor)
+                long_ffi_name.clone()
             } else {
                 // short is ok (faster lookup in dynamic linking)
-                FuncAbi::from(JniAbi::from(method_name))
+                short_ffi_name
+            };
+
+            // the first name is the implementation, any others delegate to it
+            let this_object = this_class
+                .as_object()
+                .expect("this should have been a custom object");
+            let fn_export_ffi_names = match self.export_names {
+                ExportNames::Long => vec![long_ffi_name.with_class(this_object)],
+                ExportNames::Both if !is_overloaded => vec![
+                    fn_ffi_name.with_class(this_object),
+                    long_ffi_name.with_class(this_object),
+                ],
+                _ => vec![fn_ffi_name.with_class(this_object)],
             };
-            let fn_export_ffi_name = fn_ffi_name.with_class(
-                this_class
-                    .as_object()
-                    .expect("this should have been a custom object"),
-            );
 
             // dedup the rust method names
-            let rust_method_name: String = fn_ffi_name.to_string().to_snake_case();
-            let rust_method_name = if *rust_method_names
+            let renamed = method_override.and_then(|o| o.rename.as_deref());
+            let rust_method_name = match renamed {
+                Some(rename) => rename.to_string(),
+                None if is_native => self
+                    .strip_native_method_name(&fn_ffi_name.to_string())
+                    .to_snake_case(),
+                None => fn_ffi_name.to_string().to_snake_case(),
+            };
+            let rust_method_name = if is_bridge {
+                format!("{rust_method_name}_bridge")
+            } else {
+                rust_method_name
+            };
+            let is_duplicate = *rust_method_names
                 .entry(rust_method_name.clone())
                 .and_modify(|i| *i += 1)
                 .or_default()
-                == 0
-            {
+                != 0;
+            let rust_method_name = if !is_duplicate {
                 rust_method_name
             } else {
                 // we're going to add the index into the list of methods from the Class file, hopefully this is consistently ordered with the Code?
                 //  otherwise this will create confusing results when the classfile changes after Java recompilation...
                 format!("{rust_method_name}_{index}")
             };
+
+            let java_method_name = if is_native {
+                self.strip_native_method_name(&method.name)
+            } else {
+                method.name.to_string()
+            };
+            if (renamed.is_some() || !is_constructor)
+                && rust_method_name != java_method_name.to_snake_case()
+            {
+                let reason = if is_bridge {
+                    format!("bridge of a covariant override, the method is a hidden alias named {rust_method_name}")
+                } else if is_duplicate {
+                    format!("the name is already used, the method is named {rust_method_name}")
+                } else if file_override.is_some_and(|o| o.rename.is_some()) {
+                    format!("renamed by the overrides, the method is named {rust_method_name}")
+                } else if renamed.is_some() {
+                    format!("renamed by @JaffiRename, the method is named {rust_method_name}")
+                } else if is_overloaded {
+                    format!("overloaded, the method is named {rust_method_name}")
+                } else {
+                    format!("escaped as in the JNI name, the method is named {rust_method_name}")
+                };
+                report.push(
+                    ReportKind::Renamed,
+                    &class_file.this_class,
+                    Some(&method.name),
+                    reason,
+                );
+            }
+            if crate::ident::is_illegal(&rust_method_name) {
+                report.push(
+                    ReportKind::Renamed,
+                    &class_file.this_class,
+                    Some(&method.name),
+                    format!("{rust_method_name} is not a valid identifier, the method is named r_{rust_method_name}"),
+                );
+            }
             let rust_method_name = FuncAbi::from_raw(rust_method_name);
 
             // get the exceptions from the method
@@ -452,15 +2059,21 @@ impl<'a> Jaffi<'a> {
                 })
                 .flatten()
                 .collect();
-            let exceptions = exceptions
+            let mut exceptions = exceptions
                 .into_iter()
                 .map(|s| JavaDesc::from(s.to_string()))
                 .collect::<BTreeSet<_>>();
 
+            // a forced `Result` lets native methods throw, and wrapper methods catch all exceptions
+            let force_result = method_override.is_some_and(|o| o.result);
+            if force_result && is_native && exceptions.is_empty() {
+                exceptions.insert(JavaDesc::from("java/lang/RuntimeException"));
+            }
+
             let function = Function {
                 name: method.name.to_string(),
                 object_java_desc,
-                fn_export_ffi_name,
+                fn_export_ffi_names,
                 class_ffi_name,
                 object_ffi_name,
                 rust_method_name,
@@ -470,8 +2083,22 @@ impl<'a> Jaffi<'a> {
                 is_native,
                 arguments,
                 result: result.to_jni_type_name(),
-                rs_result: result.to_rs_type_name(),
+                rs_result: result.to_rs_type_name_with(self.primitive_types),
                 exceptions,
+                java_fallback: None,
+                varargs,
+                native_factory: false,
+                cancel_handle: false,
+                catch_all: force_result && !is_native,
+                deprecated: extract_deprecation(&method.attributes),
+                late_bound: false,
+                bridge: is_bridge,
+                package_private: !is_native && is_package_private(method),
+                local_capacity: if is_native {
+                    self.local_capacity(&class_file.this_class, &method.name)
+                } else {
+                    None
+                },
             };
 
             functions.push(function);
@@ -479,17 +2106,710 @@ impl<'a> Jaffi<'a> {
 
         Ok((functions, argument_objects))
     }
+
+    /// Sets the `java_fallback` of the native methods of the `java_fallback_impls` to the wrapper method of the fallback
+    fn find_java_fallbacks(
+        &self,
+        class_ffis: &mut [ClassFfi],
+        objects: &[Object],
+        report: &mut Report,
+    ) {
+        for class_ffi in class_ffis {
+            let class = JavaDesc::from(class_ffi.class_name.as_str());
+            if !self
+                .java_fallback_impls
+                .iter()
+                .any(|fallback_class| JavaDesc::from(&**fallback_class) == class)
+            {
+                continue;
+            }
+
+            let Some(object) = objects.iter().find(|object| object.java_name == class) else {
+                continue;
+            };
+
+            for func in &mut class_ffi.functions {
+                let fallback_name = self.java_fallback_name(&func.name);
+                let Some(fallback) = object.methods.iter().find(|method| {
+                    !method.is_native
+                        && !method.is_constructor
+                        && method.name == fallback_name
+                        && method.signature == func.signature
+                        && method.is_static == func.is_static
+                }) else {
+                    continue;
+                };
+
+                // the arguments are passed through and the result returned as is
+                let same_types = fallback.rs_result == func.rs_result
+                    && fallback
+                        .arguments
+                        .iter()
+                        .zip(&func.arguments)
+                        .all(|(fallback_arg, arg)| fallback_arg.rs_ty == arg.rs_ty);
+                if !same_types
+                    || !func.exceptions.is_empty()
+                    || !fallback.exceptions.is_empty()
+                    || fallback.catch_all
+                {
+                    report.push(
+                        ReportKind::Skipped,
+                        &class_ffi.class_name,
+                        Some(&func.name),
+                        format!("the Java fallback {fallback_name} can't be called with the types of the native method, or declares exceptions"),
+                    );
+                    continue;
+                }
+
+                func.java_fallback = Some(fallback.rust_method_name.clone());
+            }
+        }
+    }
+
+    /// Moves the native classes of the `aggregate_packages` to the trait of their package, prefixing the methods with the class
+    fn aggregate_native_classes(&self, class_ffis: &mut [ClassFfi]) {
+        for class_ffi in class_ffis {
+            if class_ffi.registered_impl || class_ffi.context_impl {
+                continue;
+            }
+
+            let class = JavaDesc::from(class_ffi.class_name.as_str());
+            let Some((package, class_name)) = class.as_str().rsplit_once('/') else {
+                continue;
+            };
+            if !self
+                .aggregate_packages
+                .iter()
+                .any(|aggregate| aggregate.replace('.', "/") == package)
+            {
+                continue;
+            }
+
+            class_ffi.trait_name = format!("{}Rs", escape_non_ascii(package).to_upper_camel_case());
+            class_ffi.trait_impl = format!("{}Impl", class_ffi.trait_name);
+            class_ffi.aggregated = true;
+
+            let prefix = escape_non_ascii(class_name)
+                .replace('$', "_")
+                .to_snake_case();
+            for func in &mut class_ffi.functions {
+                let name = func.rust_method_name.for_rust_ident().to_string();
+                let name = name.trim_start_matches("r#");
+                func.rust_method_name = FuncAbi::from_raw(format!("{prefix}_{name}"));
+            }
+        }
+    }
+
+    /// Sets the `cancel_handle` of the `static native cancel(long)` methods of the `cancel_handles`
+    fn find_cancel_handles(&self, class_ffis: &mut [ClassFfi]) {
+        for class_ffi in class_ffis {
+            let class = JavaDesc::from(class_ffi.class_name.as_str());
+            if !self
+                .cancel_handles
+                .iter()
+                .any(|cancel_class| JavaDesc::from(&**cancel_class) == class)
+            {
+                continue;
+            }
+
+            for func in &mut class_ffi.functions {
+                func.cancel_handle = func.is_static
+                    && func.name == "cancel"
+                    && matches!(func.signature.as_str(), "(J)V" | "(J)Z");
+            }
+        }
+    }
+
+    /// The name of the Java fallback of a native method, without the prefix or suffix
+    fn java_fallback_name(&self, name: &str) -> String {
+        let stripped = if self.native_method_prefix.is_some() || self.native_method_suffix.is_some()
+        {
+            self.strip_native_method_name(name)
+        } else {
+            strip_method_name(name, Some("native"), Some("Native"))
+        };
+
+        // a stripped prefix leaves the name capitalized, i.e. `Foo` for `nativeFoo`
+        let mut chars = stripped.chars();
+        chars
+            .next()
+            .map(|first| first.to_lowercase().chain(chars).collect())
+            .unwrap_or_default()
+    }
+
+    /// The `local_capacities` of a native method, the method is more specific than its class
+    fn local_capacity(&self, class: &str, method: &str) -> Option<i32> {
+        let class = JavaDesc::from(class);
+        let capacity_of = |for_method: bool| {
+            self.local_capacities
+                .iter()
+                .find(|(spec, _)| match spec.split_once('#') {
+                    Some((spec_class, spec_method)) => {
+                        for_method && spec_method == method && JavaDesc::from(spec_class) == class
+                    }
+                    None => !for_method && JavaDesc::from(&**spec) == class,
+                })
+                .map(|(_, capacity)| *capacity)
+        };
+
+        capacity_of(true).or_else(|| capacity_of(false))
+    }
+
+    /// Strips the `native_method_prefix` and `native_method_suffix`
+    fn strip_native_method_name(&self, name: &str) -> String {
+        strip_method_name(
+            name,
+            self.native_method_prefix.as_deref(),
+            self.native_method_suffix.as_deref(),
+        )
+    }
+}
+
+/// Strips the `prefix`, if followed by an upper case letter, and the `suffix`, the descriptor of long JNI names, after `__`, is kept
+fn strip_method_name(name: &str, prefix: Option<&str>, suffix: Option<&str>) -> String {
+    let (mut stripped, descriptor) = name
+        .split_once("__")
+        .map_or((name, None), |(name, descriptor)| (name, Some(descriptor)));
+
+    if let Some(rest) = prefix.and_then(|prefix| stripped.strip_prefix(prefix)) {
+        if rest.starts_with(|ch: char| ch.is_ascii_uppercase()) {
+            stripped = rest;
+        }
+    }
+
+    if let Some(rest) = suffix.and_then(|suffix| stripped.strip_suffix(suffix)) {
+        if !rest.is_empty() {
+            stripped = rest;
+        }
+    }
+
+    match descriptor {
+        Some(descriptor) => format!("{stripped}__{descriptor}"),
+        None => stripped.to_string(),
+    }
+}
+
+/// A class file found by `find_class`
+#[derive(Debug)]
+enum ClassSource {
+    /// In a directory of the classpath
+    File(PathBuf),
+    /// Supplied by the `class_provider`
+    Provided { class: JavaDesc, bytes: Vec<u8> },
+}
+
+/// Parses a declared method of the `late_bound_classes`, e.g. `static parse(Ljava/lang/String;)I`, into whether it's static,
+///   its name and its descriptor
+fn parse_late_bound_method(method: &str) -> Result<(bool, &str, &str), Error> {
+    let (is_static, declaration) = match method.strip_prefix("static ") {
+        Some(declaration) => (true, declaration.trim_start()),
+        None => (false, method),
+    };
+    let invalid = |reason: &str| -> Error {
+        format!("invalid late_bound_classes method {method}: {reason}").into()
+    };
+
+    let Some(start) = declaration.find('(') else {
+        return Err(invalid(
+            "expected a name followed by a descriptor, e.g. `size()I`",
+        ));
+    };
+    let (name, descriptor) = declaration.split_at(start);
+    let Some((_, result)) = model::parse_method_descriptor(descriptor) else {
+        return Err(invalid("the descriptor isn't valid"));
+    };
+
+    if name == "<init>" {
+        if is_static || result.is_some() {
+            return Err(invalid("constructors aren't static and return void"));
+        }
+    } else if name.is_empty() || name.contains(['<', '>', '.', ';', '[', '/']) {
+        return Err(invalid("the name isn't a valid Java method name"));
+    }
+
+    Ok((is_static, name, descriptor))
 }
 
 fn class_to_path(name: &str) -> PathBuf {
-    let name = name.replace('.', "/");
-    PathBuf::from(name).with_extension("class")
+    let name = JavaDesc::from(name);
+    PathBuf::from(name.as_str()).with_extension("class")
+}
+
+/// Adds the static native methods returning their own class to the wrapper of the class, as `new_from_{method}`
+/// The single abstract method of a listener interface, the methods of `Object` don't count
+fn find_listener_method(
+    class_file: &ClassFile<'_>,
+    functions: &[Function],
+    report: &mut Report,
+) -> Option<Function> {
+    let is_interface = class_file
+        .access_flags
+        .contains(ClassAccessFlags::INTERFACE);
+    let abstract_methods = class_file
+        .methods
+        .iter()
+        .filter(|method| {
+            method.access_flags.contains(MethodAccessFlags::ABSTRACT)
+                && !matches!(
+                    (&*method.name, &*method.descriptor.to_string()),
+                    ("equals", "(Ljava/lang/Object;)Z")
+                        | ("hashCode", "()I")
+                        | ("toString", "()Ljava/lang/String;")
+                )
+        })
+        .collect::<Vec<_>>();
+
+    let listener = match abstract_methods.as_slice() {
+        [method] if is_interface => functions.iter().find(|func| {
+            func.name == method.name && func.signature.as_str() == method.descriptor.to_string()
+        }),
+        _ => None,
+    };
+    if listener.is_none() {
+        report.push(
+            ReportKind::Skipped,
+            &class_file.this_class,
+            None,
+            "not an interface with a single abstract method, no listener is generated",
+        );
+    }
+
+    listener.cloned()
+}
+
+/// The names of the parameters, from the `MethodParameters` attribute, or the `LocalVariableTable` of the code
+///
+/// The names are `None` if the class file doesn't have them, or if they don't match the `count` of the parameters, e.g. for
+///   the synthetic parameters of inner classes.
+fn parameter_names(method: &MethodInfo<'_>, count: usize, is_static: bool) -> Vec<Option<String>> {
+    let parameters = method.attributes.iter().find_map(|attribute| {
+        if let AttributeData::MethodParameters(parameters) = &attribute.data {
+            Some(parameters)
+        } else {
+            None
+        }
+    });
+    if let Some(parameters) = parameters.filter(|parameters| parameters.len() == count) {
+        return parameters
+            .iter()
+            .map(|parameter| parameter.name.as_ref().map(|name| name.to_string()))
+            .collect();
+    }
+
+    let locals = method
+        .attributes
+        .iter()
+        .filter_map(|attribute| {
+            if let AttributeData::Code(code) = &attribute.data {
+                Some(code)
+            } else {
+                None
+            }
+        })
+        .flat_map(|code| code.attributes.iter())
+        .filter_map(|attribute| {
+            if let AttributeData::LocalVariableTable(locals) = &attribute.data {
+                Some(locals)
+            } else {
+                None
+            }
+        })
+        .flatten()
+        .collect::<Vec<_>>();
+
+    // the parameters are the first locals, after `this`, `long` and `double` take two slots
+    let mut slot = u16::from(!is_static);
+    method
+        .descriptor
+        .parameters
+        .iter()
+        .map(|parameter| {
+            let name = locals
+                .iter()
+                .find(|local| local.index == slot && local.start_pc == 0)
+                .map(|local| local.name.to_string());
+            slot += match parameter {
+                FieldType::Ty(Ty::Base(BaseType::Long | BaseType::Double)) => 2,
+                _ => 1,
+            };
+            name
+        })
+        .collect()
+}
+
+/// Neither public, protected nor private, only accessible in the package of the class
+fn is_package_private(method: &MethodInfo<'_>) -> bool {
+    !method.access_flags.intersects(
+        MethodAccessFlags::PUBLIC | MethodAccessFlags::PROTECTED | MethodAccessFlags::PRIVATE,
+    )
+}
+
+fn add_native_factories(class_ffis: &[ClassFfi], objects: &mut [Object]) {
+    for class_ffi in class_ffis {
+        let class = JavaDesc::from(class_ffi.class_name.as_str());
+        let Some(object) = objects.iter_mut().find(|object| object.java_name == class) else {
+            continue;
+        };
+
+        let factories = class_ffi
+            .functions
+            .iter()
+            .filter(|func| func.is_static && func.rs_result == object.obj_name)
+            .map(|func| {
+                let rust_method_name = func.rust_method_name.for_rust_ident().to_string();
+                Function {
+                    rust_method_name: FuncAbi::from_raw(format!(
+                        "new_from_{}",
+                        rust_method_name.trim_start_matches("r#")
+                    )),
+                    java_fallback: None,
+                    local_capacity: None,
+                    varargs: None,
+                    native_factory: true,
+                    cancel_handle: false,
+                    catch_all: false,
+                    ..func.clone()
+                }
+            })
+            .collect::<Vec<_>>();
+        object.methods.extend(factories);
+    }
+}
+
+/// Replaces the tokens of the support code and of each class with the rendering of the backend
+fn render_backend(backend: &dyn Backend, model: &Model, tokens: &mut GeneratedTokens) {
+    tokens.support = backend.render_support(mem::take(&mut tokens.support));
+    for wrapper in &model.wrappers {
+        if let Some(class_tokens) = tokens.wrappers.get_mut(&wrapper.class) {
+            *class_tokens = backend.render_wrapper(wrapper, mem::take(class_tokens));
+        }
+    }
+    for class in &model.native_classes {
+        if let Some(class_tokens) = tokens.native_classes.get_mut(&class.class) {
+            *class_tokens = backend.render_native_class(class, mem::take(class_tokens));
+        }
+    }
+}
+
+/// `true` if the manifest of the classpath directory has `Multi-Release: true`
+fn is_multi_release(classpath: &Path) -> bool {
+    let manifest = classpath.join("META-INF").join("MANIFEST.MF");
+    let Ok(manifest) = std::fs::read_to_string(manifest) else {
+        return false;
+    };
+
+    manifest.lines().any(|line| {
+        line.split_once(':').is_some_and(|(key, value)| {
+            key.trim() == "Multi-Release" && value.trim().eq_ignore_ascii_case("true")
+        })
+    })
+}
+
+/// Primitives, boxed primitives and `String`s can be copied into the Rust mirrors of beans and records
+fn is_mirror_type(ty: &JniType) -> bool {
+    matches!(
+        ty,
+        JniType::Ty(
+            BaseJniTy::Jbyte
+                | BaseJniTy::Jchar
+                | BaseJniTy::Jdouble
+                | BaseJniTy::Jfloat
+                | BaseJniTy::Jint
+                | BaseJniTy::Jlong
+                | BaseJniTy::Jshort
+                | BaseJniTy::Jboolean
+                | BaseJniTy::Jobject(ObjectType::JString | ObjectType::Boxed(_))
+        )
+    )
+}
+
+/// The note of the `#[deprecated]` of a `@Deprecated` class or method, `None` if it isn't deprecated
+///
+/// The `since` and `forRemoval` of the annotation are added to the note, e.g. `deprecated in Java since 9, for removal`. Classes
+///   compiled by older compilers, or deprecated only with the javadoc tag, just have the `Deprecated` attribute.
+fn extract_deprecation(attributes: &[AttributeInfo<'_>]) -> Option<String> {
+    let annotation = attributes
+        .iter()
+        .filter_map(|attribute| {
+            if let AttributeData::RuntimeVisibleAnnotations(annotations) = &attribute.data {
+                Some(annotations)
+            } else {
+                None
+            }
+        })
+        .flatten()
+        .find(|annotation| annotation.type_descriptor == "Ljava/lang/Deprecated;");
+
+    let Some(annotation) = annotation else {
+        return attributes
+            .iter()
+            .any(|attribute| matches!(attribute.data, AttributeData::Deprecated))
+            .then(|| "deprecated in Java".to_string());
+    };
+
+    let mut note = "deprecated in Java".to_string();
+    for element in &annotation.elements {
+        match (&*element.name, &element.value) {
+            ("since", AnnotationElementValue::StringConstant(since)) if !since.is_empty() => {
+                note.push_str(&format!(" since {since}"))
+            }
+            ("forRemoval", AnnotationElementValue::BooleanConstant(1)) => {
+                note.push_str(", for removal")
+            }
+            _ => (),
+        }
+    }
+
+    Some(note)
+}
+
+/// The components of a record, `None` if the class isn't a record, or a component can't be mirrored
+fn extract_record_components(
+    class_file: &ClassFile<'_>,
+    report: &mut Report,
+) -> Option<Vec<MirrorProperty>> {
+    let components = class_file.attributes.iter().find_map(|attribute| {
+        if let AttributeData::Record(components) = &attribute.data {
+            Some(components)
+        } else {
+            None
+        }
+    })?;
+
+    let mut properties = Vec::with_capacity(components.len());
+    for component in components {
+        // the accessor has the name of the component
+        let field_type =
+            class_file
+                .methods
+                .iter()
+                .find_map(|method| match &method.descriptor.result {
+                    ReturnDescriptor::Return(field_type)
+                        if method.name == component.name
+                            && method.descriptor.parameters.is_empty()
+                            && method.access_flags.contains(MethodAccessFlags::PUBLIC) =>
+                    {
+                        Some(field_type)
+                    }
+                    _ => None,
+                })?;
+
+        let ty = JniType::from_java(field_type);
+        if !is_mirror_type(&ty) {
+            report.push(
+                ReportKind::Skipped,
+                &class_file.this_class,
+                Some(&component.name),
+                format!(
+                    "unsupported type of the component, {}, the record has no Rust mirror",
+                    component.descriptor
+                ),
+            );
+            return None;
+        }
+
+        properties.push(MirrorProperty {
+            name: component.name.to_string(),
+            getter: component.name.to_string(),
+            setter: None,
+            descriptor: component.descriptor.to_string(),
+            ty,
+        });
+    }
+
+    Some(properties)
 }
 
-fn lookup_from_path(classpath: &Path, class: &Path) -> bool {
-    let path = classpath.join(class);
+/// The properties of a bean, the getters with matching setters, in the order of the getters
+fn extract_bean_properties(class_file: &ClassFile<'_>) -> Result<Vec<MirrorProperty>, Error> {
+    let public_methods = class_file
+        .methods
+        .iter()
+        .filter(|method| {
+            method.access_flags.contains(MethodAccessFlags::PUBLIC)
+                && !method.access_flags.contains(MethodAccessFlags::STATIC)
+        })
+        .collect::<Vec<_>>();
+
+    let constructable = !class_file
+        .access_flags
+        .intersects(ClassAccessFlags::ABSTRACT | ClassAccessFlags::INTERFACE)
+        && public_methods
+            .iter()
+            .any(|method| method.name == "<init>" && method.descriptor.parameters.is_empty());
+    if !constructable {
+        return Err("bean has no public no-argument constructor".into());
+    }
+
+    let mut properties = Vec::new();
+    for getter in &public_methods {
+        let ReturnDescriptor::Return(field_type) = &getter.descriptor.result else {
+            continue;
+        };
+        if !getter.descriptor.parameters.is_empty() {
+            continue;
+        }
+
+        let property = match (
+            getter.name.strip_prefix("get"),
+            getter.name.strip_prefix("is"),
+        ) {
+            (Some(property), _) => property,
+            (None, Some(property))
+                if matches!(field_type, FieldType::Ty(Ty::Base(BaseType::Boolean))) =>
+            {
+                property
+            }
+            _ => continue,
+        };
+        if property.is_empty() || getter.name == "getClass" {
+            continue;
+        }
+
+        let setter = format!("set{property}");
+        let has_setter = public_methods.iter().any(|method| {
+            method.name == setter
+                && method.descriptor.parameters.as_slice() == std::slice::from_ref(field_type)
+                && matches!(method.descriptor.result, ReturnDescriptor::Void)
+        });
+        if !has_setter {
+            continue;
+        }
+
+        // the same as java.beans.Introspector.decapitalize, `URL` stays `URL`, `Name` is `name`
+        let mut chars = property.chars();
+        let name = match (chars.next(), chars.next()) {
+            (Some(first), Some(second)) if first.is_uppercase() && second.is_uppercase() => {
+                property.to_string()
+            }
+            (Some(first), _) => first
+                .to_lowercase()
+                .chain(property.chars().skip(1))
+                .collect(),
+            (None, _) => continue,
+        };
+
+        let ty = JniType::from_java(field_type);
+        if !is_mirror_type(&ty) {
+            return Err(format!(
+                "unsupported type of bean property {name}: {field_type}"
+            ))
+            .in_method(
+                &class_file.this_class,
+                &getter.name,
+                &getter.descriptor.to_string(),
+            );
+        }
+
+        properties.push(MirrorProperty {
+            name,
+            getter: getter.name.to_string(),
+            setter: Some(setter),
+            descriptor: field_type.to_string(),
+            ty,
+        });
+    }
 
-    path.is_file()
+    Ok(properties)
+}
+
+/// Collects the public `static final` fields that have a constant value in the class file
+/// The name of the only instance field of type `java.nio.ByteBuffer`, for the `buffer_classes`
+fn extract_buffer_field(class_file: &ClassFile<'_>) -> Result<String, Error> {
+    let mut fields = class_file.fields.iter().filter(|field| {
+        !field.access_flags.contains(FieldAccessFlags::STATIC)
+            && matches!(&field.descriptor, FieldType::Ty(Ty::Object(obj)) if obj == "java/nio/ByteBuffer")
+    });
+
+    match (fields.next(), fields.next()) {
+        (Some(field), None) => Ok(field.name.to_string()),
+        (None, _) => Err("buffer class has no ByteBuffer instance field".into()),
+        (Some(_), Some(_)) => {
+            Err("buffer class has more than one ByteBuffer instance field".into())
+        }
+    }
+}
+
+fn extract_constants(class_file: &ClassFile<'_>, report: &mut Report) -> Vec<Constant> {
+    let constant_flags =
+        FieldAccessFlags::PUBLIC | FieldAccessFlags::STATIC | FieldAccessFlags::FINAL;
+
+    class_file
+        .fields
+        .iter()
+        .filter(|field| field.access_flags.contains(constant_flags))
+        .filter_map(|field| {
+            let literal = field.attributes.iter().find_map(|attribute| {
+                if let AttributeData::ConstantValue(literal) = &attribute.data {
+                    Some(literal)
+                } else {
+                    None
+                }
+            })?;
+
+            // booleans, bytes, chars and shorts are all stored as ints
+            let value = match (&field.descriptor, literal) {
+                (FieldType::Ty(Ty::Base(BaseType::Boolean)), LiteralConstant::Integer(v)) => {
+                    Some(ConstantValue::Boolean(*v != 0))
+                }
+                (FieldType::Ty(Ty::Base(BaseType::Byte)), LiteralConstant::Integer(v)) => {
+                    Some(ConstantValue::Byte(*v as i8))
+                }
+                (FieldType::Ty(Ty::Base(BaseType::Char)), LiteralConstant::Integer(v)) => {
+                    // unpaired surrogates are not valid Rust chars
+                    char::from_u32(*v as u32).map(ConstantValue::Char)
+                }
+                (FieldType::Ty(Ty::Base(BaseType::Short)), LiteralConstant::Integer(v)) => {
+                    Some(ConstantValue::Short(*v as i16))
+                }
+                (FieldType::Ty(Ty::Base(BaseType::Int)), LiteralConstant::Integer(v)) => {
+                    Some(ConstantValue::Int(*v))
+                }
+                (FieldType::Ty(Ty::Base(BaseType::Long)), LiteralConstant::Long(v)) => {
+                    Some(ConstantValue::Long(*v))
+                }
+                (FieldType::Ty(Ty::Base(BaseType::Float)), LiteralConstant::Float(v)) => {
+                    Some(ConstantValue::Float(*v))
+                }
+                (FieldType::Ty(Ty::Base(BaseType::Double)), LiteralConstant::Double(v)) => {
+                    Some(ConstantValue::Double(*v))
+                }
+                (FieldType::Ty(Ty::Object(class)), LiteralConstant::String(v))
+                    if class == "java/lang/String" =>
+                {
+                    Some(ConstantValue::String(v.to_string()))
+                }
+                _ => None,
+            };
+
+            // strings that are not valid in Rust, e.g. with unpaired surrogates, are skipped
+            let Some(value) = value else {
+                report.push(
+                    ReportKind::Skipped,
+                    &class_file.this_class,
+                    Some(&field.name),
+                    format!(
+                        "the constant value of {} is not valid in Rust",
+                        field.descriptor
+                    ),
+                );
+                return None;
+            };
+
+            Some(Constant {
+                name: field.name.to_string(),
+                value,
+            })
+        })
+        .collect()
+}
+
+/// Parses the path to a Rust function from the builder `option`
+fn parse_fn_path(option: &str, path: &Option<Cow<'_, str>>) -> Result<Option<TokenStream>, Error> {
+    path.as_deref()
+        .map(TokenStream::from_str)
+        .transpose()
+        .map_err(|e| format!("invalid {option}: {e}").into())
 }
 
 #[cfg(test)]
@@ -501,15 +2821,290 @@ mod tests {
         assert_eq!(JniAbi::from("p.q.r.A").to_string(), "p_q_r_A");
         assert_eq!(
             FuncAbi::from(JniAbi::from("f"))
-                .with_descriptor(&JavaDesc::from("(ILjava.lang.String;)D"))
+                .with_descriptor(&MethodDesc::parse("(ILjava/lang/String;)D").unwrap())
                 .with_class(&JavaDesc::from("p.q.r.A"))
                 .to_string(),
             "Java_p_q_r_A_f__ILjava_lang_String_2"
         );
     }
 
+    #[test]
+    fn test_parse_late_bound_method() {
+        assert_eq!(
+            parse_late_bound_method("static parse(Ljava/lang/String;)I").unwrap(),
+            (true, "parse", "(Ljava/lang/String;)I")
+        );
+        assert_eq!(
+            parse_late_bound_method("<init>(I)V").unwrap(),
+            (false, "<init>", "(I)V")
+        );
+        assert!(parse_late_bound_method("size").is_err());
+        assert!(parse_late_bound_method("size(I").is_err());
+        assert!(parse_late_bound_method("static <init>()V").is_err());
+        assert!(parse_late_bound_method("java.lang.size()I").is_err());
+    }
+
+    #[test]
+    fn test_allow_deprecated() {
+        let tokens = template::allow_deprecated(quote::quote! {
+            const _: () = { assert!(true) };
+            /// Foo
+            pub struct Foo(u8);
+            impl Foo {}
+        });
+
+        assert_eq!(
+            tokens.to_string(),
+            quote::quote! {
+                #[allow(deprecated)]
+                const _: () = { assert!(true) };
+                #[allow(deprecated)]
+                /// Foo
+                pub struct Foo(u8);
+                #[allow(deprecated)]
+                impl Foo {}
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_descriptors() {
+        assert!(MethodDesc::parse("(ILjava.lang.String;)D").is_none());
+        assert!(MethodDesc::parse("java/lang/String").is_none());
+
+        let descriptor = MethodDesc::parse("(ILjava/lang/String;)D").unwrap();
+        assert_eq!(descriptor.parameter_descriptors(), "ILjava/lang/String;");
+        assert_eq!(JavaDesc::from("p.Outer$Inner").as_str(), "p/Outer$Inner");
+        assert_eq!(
+            JavaDesc::from("p/Outer$Inner").binary_name(),
+            "p.Outer$Inner"
+        );
+    }
+
     #[test]
     fn test_escape_name_unicode() {
-        assert_eq!(JniAbi::from("i❤'🦀").to_string(), "i_02764_027_01f980");
+        // the code units are padded to four digits, the crab is escaped as its surrogate pair
+        assert_eq!(
+            JniAbi::from("i❤'🦀").to_string(),
+            "i_02764_00027_0d83e_0dd80"
+        );
+        assert_eq!(
+            FuncAbi::from(JniAbi::from("grüße"))
+                .with_class(&JavaDesc::from("p.Outer$Inner_𝒜"))
+                .to_string(),
+            "Java_p_Outer_00024Inner_1_0d835_0dc9c_gr_000fc_000dfe"
+        );
+        assert_eq!(
+            JavaDesc::from("p.Outer$Ünïcode").to_rust_type_name(),
+            "POuter000dcn000efcode"
+        );
+    }
+
+    #[test]
+    fn test_strip_native_method_name() {
+        let jaffi = Jaffi::builder()
+            .classpath(vec![])
+            .native_classes(vec![])
+            .native_method_prefix(Cow::from("native"))
+            .native_method_suffix(Cow::from("Native"))
+            .build();
+
+        assert_eq!(jaffi.strip_native_method_name("nativeFoo"), "Foo");
+        assert_eq!(jaffi.strip_native_method_name("fooNative"), "foo");
+        assert_eq!(jaffi.strip_native_method_name("voidNative__JI"), "void__JI");
+        assert_eq!(jaffi.strip_native_method_name("natives"), "natives");
+        assert_eq!(jaffi.strip_native_method_name("Native"), "Native");
+    }
+
+    #[test]
+    fn test_android_jni_version() {
+        let jaffi = |android, jni_version| Jaffi {
+            jni_version,
+            android,
+            ..Jaffi::builder()
+                .classpath(vec![])
+                .native_classes(vec![])
+                .build()
+        };
+
+        assert_eq!(jaffi(false, None).jni_version().unwrap(), JniVersion::V1_8);
+        assert_eq!(jaffi(true, None).jni_version().unwrap(), JniVersion::V1_6);
+        assert_eq!(
+            jaffi(true, Some(JniVersion::V1_6)).jni_version().unwrap(),
+            JniVersion::V1_6
+        );
+        assert!(jaffi(true, Some(JniVersion::V9)).jni_version().is_err());
+    }
+
+    #[test]
+    fn test_android_unsupported_options() {
+        let jaffi = Jaffi::builder()
+            .classpath(vec![])
+            .native_classes(vec![])
+            .android(true)
+            .listener_interfaces(vec![Cow::from("net.bluejekyll.CountListener")])
+            .test_harness(true)
+            .build();
+
+        let error = jaffi
+            .generate()
+            .expect_err("listeners aren't supported on ART");
+        assert_eq!(
+            error.to_string(),
+            "android doesn't support listener_interfaces, test_harness"
+        );
+
+        // the same options are accepted without android, and android without them
+        let jaffi = Jaffi {
+            android: false,
+            ..jaffi
+        };
+        assert!(jaffi.check_android().is_ok());
+        let jaffi = Jaffi {
+            android: true,
+            listener_interfaces: vec![],
+            test_harness: false,
+            ..jaffi
+        };
+        assert!(jaffi.check_android().is_ok());
+    }
+
+    #[test]
+    fn test_class_not_found_error() {
+        let jaffi = Jaffi::builder()
+            .classpath(vec![Cow::from(Path::new("missing/classes"))])
+            .native_classes(vec![])
+            .build();
+
+        let error = jaffi
+            .search_classpath(
+                &JavaDesc::from("p.Circle"),
+                Some(&JavaDesc::from("p.Shape")),
+            )
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "could not find class p/Circle in the classpath [missing/classes], required by p/Shape"
+        );
+
+        let error = Err::<(), _>("unsupported")
+            .in_class("p/Shape")
+            .in_class("p/Shape")
+            .unwrap_err();
+        assert_eq!(error.to_string(), "p/Shape: unsupported");
+    }
+
+    #[test]
+    fn test_class_provider() {
+        let provider = |class: &str| (class == "p/A").then(|| b"not a class".to_vec());
+        let jaffi = Jaffi::builder()
+            .classpath(vec![])
+            .native_classes(vec![])
+            .class_provider(&provider)
+            .build();
+
+        assert!(jaffi.find_class(&JavaDesc::from("p.B")).is_none());
+
+        let source = jaffi.find_class(&JavaDesc::from("p.A")).unwrap();
+        let mut class_buf = Vec::new();
+        let error = jaffi.read_class(source, &mut class_buf).unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("could not read class file p/A.class: "));
+    }
+
+    #[test]
+    fn test_lenient_missing_classes() {
+        let provider = |class: &str| (class == "p/A").then(|| b"not a class".to_vec());
+        let jaffi = |lenient_missing_classes| Jaffi {
+            lenient_missing_classes,
+            ..Jaffi::builder()
+                .classpath(vec![Cow::from(Path::new("missing/classes"))])
+                .native_classes(vec![])
+                .class_provider(&provider)
+                .build()
+        };
+        let types = || HashSet::from([JavaDesc::from("p.A"), JavaDesc::from("p.B")]);
+
+        let mut report = Report::default();
+        assert!(jaffi(false)
+            .generate_support_types(types(), HashSet::new(), &Overrides::default(), &mut report)
+            .is_err());
+
+        let mut report = Report::default();
+        let objects = jaffi(true)
+            .generate_support_types(types(), HashSet::new(), &Overrides::default(), &mut report)
+            .unwrap();
+        assert_eq!(objects.len(), 2);
+        assert!(objects.iter().all(|object| object.methods.is_empty()));
+
+        let reasons = report
+            .entries()
+            .iter()
+            .map(|entry| (entry.class.as_str(), entry.kind, entry.reason.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(reasons.len(), 2);
+        assert!(reasons.iter().any(|(class, kind, reason)| *class == "p/A"
+            && *kind == ReportKind::Downgraded
+            && reason.starts_with("could not read class file p/A.class: ")));
+        assert!(reasons.contains(&(
+            "p/B",
+            ReportKind::Downgraded,
+            "not found in the classpath, the wrapper has no methods"
+        )));
+    }
+
+    #[test]
+    fn test_multi_release_classpath() {
+        let classpath =
+            std::env::temp_dir().join(format!("jaffi_multi_release_{}", std::process::id()));
+        let versioned = classpath.join("META-INF/versions/11/p");
+        std::fs::create_dir_all(&versioned).unwrap();
+        std::fs::create_dir_all(classpath.join("p")).unwrap();
+        std::fs::write(
+            classpath.join("META-INF/MANIFEST.MF"),
+            "Manifest-Version: 1.0\nMulti-Release: true\n",
+        )
+        .unwrap();
+        std::fs::write(classpath.join("p/A.class"), b"").unwrap();
+        std::fs::write(versioned.join("A.class"), b"").unwrap();
+        std::fs::write(classpath.join("module-info.class"), b"").unwrap();
+
+        let jaffi = |java_release| Jaffi {
+            java_release,
+            ..Jaffi::builder()
+                .classpath(vec![Cow::from(classpath.as_path())])
+                .native_classes(vec![])
+                .build()
+        };
+        let found = |java_release| match jaffi(java_release).find_class(&JavaDesc::from("p.A")) {
+            Some(ClassSource::File(path)) => path.strip_prefix(&classpath).unwrap().to_path_buf(),
+            source => panic!("not found in the classpath: {source:?}"),
+        };
+
+        assert_eq!(found(None), Path::new("p/A.class"));
+        assert_eq!(found(Some(8)), Path::new("p/A.class"));
+        assert_eq!(found(Some(17)), Path::new("META-INF/versions/11/p/A.class"));
+        assert!(jaffi(None)
+            .find_class(&JavaDesc::from("module-info"))
+            .is_none());
+
+        std::fs::remove_dir_all(&classpath).unwrap();
+    }
+
+    #[test]
+    fn test_boxed_type_names() {
+        use quote::ToTokens;
+
+        let integer = ObjectType::from(JavaDesc::from("java.lang.Integer"));
+        assert_eq!(
+            integer.to_rs_type_name().to_token_stream().to_string(),
+            "Option < i32 >"
+        );
+        assert_eq!(
+            integer.to_jni_type_name().to_token_stream().to_string(),
+            "jaffi_support :: boxed :: JInteger < 'j >"
+        );
     }
 }
@@ -0,0 +1,103 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! A Java class that loads the Rust library, for the Java code using the native classes
+
+use std::fmt::Write;
+
+use crate::template::ClassFfi;
+
+/// Generates the source of the loader class, `loader_class` is the Java class name, i.e. `net.bluejekyll.NativeLoader`
+pub(crate) fn generate_loader(
+    class_ffis: &[ClassFfi],
+    loader_class: &str,
+    library_name: &str,
+) -> String {
+    let (package, class) = loader_class
+        .rsplit_once('.')
+        .map_or((None, loader_class), |(package, class)| {
+            (Some(package), class)
+        });
+
+    let mut loader = String::new();
+
+    writeln!(
+        loader,
+        "// DO NOT EDIT THIS FILE - it is generated by jaffi"
+    )
+    .unwrap();
+    if let Some(package) = package {
+        writeln!(loader, "package {package};").unwrap();
+    }
+    writeln!(loader).unwrap();
+    writeln!(loader, "/**").unwrap();
+    writeln!(
+        loader,
+        " * Loads the native library, {{@code {library_name}}}, implementing the native methods of these classes:"
+    )
+    .unwrap();
+    writeln!(loader, " * <ul>").unwrap();
+    for class_ffi in class_ffis {
+        let mut methods = Vec::<&str>::new();
+        for func in &class_ffi.functions {
+            if !methods.contains(&func.name.as_str()) {
+                methods.push(&func.name);
+            }
+        }
+        let methods = methods
+            .iter()
+            .map(|method| format!("{{@code {method}}}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        // a '$' is either a nested class, linked with '.', or part of the name, so those aren't linked
+        let native_class = class_ffi.class_name.replace('/', ".");
+        if native_class.contains('$') {
+            writeln!(loader, " * <li>{{@code {native_class}}}: {methods}</li>").unwrap();
+        } else {
+            writeln!(loader, " * <li>{{@link {native_class}}}: {methods}</li>").unwrap();
+        }
+    }
+    writeln!(loader, " * </ul>").unwrap();
+    writeln!(loader, " *").unwrap();
+    writeln!(
+        loader,
+        " * <p>Call {{@link #load()}} before the first call to any of the native methods.</p>"
+    )
+    .unwrap();
+    writeln!(loader, " */").unwrap();
+    writeln!(loader, "public final class {class} {{").unwrap();
+    writeln!(
+        loader,
+        "    /** The name of the library, as passed to {{@link System#loadLibrary(String)}} */"
+    )
+    .unwrap();
+    writeln!(
+        loader,
+        "    public static final String LIBRARY_NAME = \"{library_name}\";"
+    )
+    .unwrap();
+    writeln!(loader).unwrap();
+    writeln!(loader, "    private static boolean loaded = false;").unwrap();
+    writeln!(loader).unwrap();
+    writeln!(loader, "    private {class}() {{}}").unwrap();
+    writeln!(loader).unwrap();
+    writeln!(
+        loader,
+        "    /** Loads the library with {{@link System#loadLibrary(String)}}, only the first call loads it */"
+    )
+    .unwrap();
+    writeln!(loader, "    public static synchronized void load() {{").unwrap();
+    writeln!(loader, "        if (!loaded) {{").unwrap();
+    writeln!(loader, "            System.loadLibrary(LIBRARY_NAME);").unwrap();
+    writeln!(loader, "            loaded = true;").unwrap();
+    writeln!(loader, "        }}").unwrap();
+    writeln!(loader, "    }}").unwrap();
+    writeln!(loader, "}}").unwrap();
+
+    loader
+}
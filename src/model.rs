@@ -0,0 +1,344 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The classes, methods and types read from the class files, for tools that consume the analysis rather than the generated code
+//!
+//! The model is independent of the code generation, new fields and variants are added without a breaking change, so the types
+//!   are `#[non_exhaustive]`. Use `Jaffi::model` to get it, and [`Model::visit`] to walk it with a [`Visitor`].
+
+use crate::template::{ClassFfi, Function, JavaDesc, Object};
+
+/// A Java type, from a field or method descriptor
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum JavaType {
+    /// `boolean`
+    Boolean,
+    /// `byte`
+    Byte,
+    /// `char`
+    Char,
+    /// `short`
+    Short,
+    /// `int`
+    Int,
+    /// `long`
+    Long,
+    /// `float`
+    Float,
+    /// `double`
+    Double,
+    /// A class or interface, e.g. `java/lang/String`
+    Object(String),
+    /// An array of the element type
+    Array(Box<JavaType>),
+}
+
+impl JavaType {
+    /// Parses a field descriptor, e.g. `[Ljava/lang/String;`, `None` if it isn't a valid descriptor
+    pub fn from_descriptor(descriptor: &str) -> Option<Self> {
+        match parse_type(descriptor)? {
+            (ty, "") => Some(ty),
+            _ => None,
+        }
+    }
+
+    /// The field descriptor, e.g. `[Ljava/lang/String;`
+    pub fn descriptor(&self) -> String {
+        match self {
+            Self::Boolean => "Z".to_string(),
+            Self::Byte => "B".to_string(),
+            Self::Char => "C".to_string(),
+            Self::Short => "S".to_string(),
+            Self::Int => "I".to_string(),
+            Self::Long => "J".to_string(),
+            Self::Float => "F".to_string(),
+            Self::Double => "D".to_string(),
+            Self::Object(class) => format!("L{class};"),
+            Self::Array(element) => format!("[{}", element.descriptor()),
+        }
+    }
+}
+
+/// An internal name of a class, e.g. `java/lang/String`, the binary name `java.lang.String` isn't valid in descriptors
+pub(crate) fn is_internal_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.split('/').all(|segment| !segment.is_empty())
+        && !name.contains(['.', ';', '['])
+}
+
+/// Parses the type at the start of the descriptor, returns it with the rest of the descriptor
+fn parse_type(descriptor: &str) -> Option<(JavaType, &str)> {
+    let mut chars = descriptor.chars();
+    let ty = match chars.next()? {
+        'Z' => JavaType::Boolean,
+        'B' => JavaType::Byte,
+        'C' => JavaType::Char,
+        'S' => JavaType::Short,
+        'I' => JavaType::Int,
+        'J' => JavaType::Long,
+        'F' => JavaType::Float,
+        'D' => JavaType::Double,
+        'L' => {
+            let (class, rest) = chars.as_str().split_once(';')?;
+            if !is_internal_name(class) {
+                return None;
+            }
+            return Some((JavaType::Object(class.to_string()), rest));
+        }
+        '[' => {
+            let (element, rest) = parse_type(chars.as_str())?;
+            return Some((JavaType::Array(Box::new(element)), rest));
+        }
+        _ => return None,
+    };
+
+    Some((ty, chars.as_str()))
+}
+
+/// Parses a method descriptor into the parameter types and the result type, `None` for `void`
+pub(crate) fn parse_method_descriptor(
+    descriptor: &str,
+) -> Option<(Vec<JavaType>, Option<JavaType>)> {
+    let mut rest = descriptor.strip_prefix('(')?;
+    let mut parameters = Vec::new();
+    while !rest.starts_with(')') {
+        let (ty, next) = parse_type(rest)?;
+        parameters.push(ty);
+        rest = next;
+    }
+
+    let result = match &rest[1..] {
+        "V" => None,
+        result => Some(JavaType::from_descriptor(result)?),
+    };
+
+    Some((parameters, result))
+}
+
+/// All the classes that code is generated for
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Model {
+    /// The classes with native methods, in the order of the `native_classes`
+    pub native_classes: Vec<NativeClass>,
+    /// The classes that wrappers are generated for, sorted by class
+    pub wrappers: Vec<Wrapper>,
+}
+
+impl Model {
+    /// Calls the visitor for each native class and wrapper, followed by each of their methods
+    pub fn visit<V: Visitor + ?Sized>(&self, visitor: &mut V) {
+        for class in &self.native_classes {
+            visitor.visit_native_class(class);
+            for method in &class.methods {
+                visitor.visit_method(&class.class, method);
+            }
+        }
+
+        for wrapper in &self.wrappers {
+            visitor.visit_wrapper(wrapper);
+            for method in &wrapper.methods {
+                visitor.visit_method(&wrapper.class, method);
+            }
+        }
+    }
+
+    pub(crate) fn new(class_ffis: &[ClassFfi], objects: &[Object]) -> Self {
+        Self {
+            native_classes: class_ffis.iter().map(NativeClass::from).collect(),
+            wrappers: objects.iter().map(Wrapper::from).collect(),
+        }
+    }
+}
+
+/// Receives the parts of the [`Model`] from [`Model::visit`], all the methods do nothing by default
+pub trait Visitor {
+    /// A class with native methods
+    fn visit_native_class(&mut self, _class: &NativeClass) {}
+
+    /// A class with a generated wrapper
+    fn visit_wrapper(&mut self, _wrapper: &Wrapper) {}
+
+    /// A method of the native class or wrapper, specified as a class descriptor, i.e. `java/lang/Object`
+    fn visit_method(&mut self, _class: &str, _method: &Method) {}
+}
+
+/// A class with native methods, implemented by a `*RsImpl`
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct NativeClass {
+    /// The class descriptor, i.e. `net/bluejekyll/NativeStrings`
+    pub class: String,
+    /// The name of the generated trait, e.g. `NativeStringsRs`
+    pub trait_name: String,
+    /// The name of the implementation of the trait, e.g. `NativeStringsRsImpl`
+    pub impl_name: String,
+    /// The native methods
+    pub methods: Vec<Method>,
+}
+
+impl From<&ClassFfi> for NativeClass {
+    fn from(class: &ClassFfi) -> Self {
+        Self {
+            class: JavaDesc::from(&*class.class_name).to_string(),
+            trait_name: class.trait_name.clone(),
+            impl_name: class.trait_impl.clone(),
+            methods: class.functions.iter().map(Method::from).collect(),
+        }
+    }
+}
+
+/// A class with a generated wrapper, for calling its public methods from Rust
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Wrapper {
+    /// The class descriptor, i.e. `net/bluejekyll/ParentClass`
+    pub class: String,
+    /// The name of the wrapper, e.g. `NetBluejekyllParentClass`
+    pub rust_name: String,
+    /// The public methods, empty if the class isn't wrapped, e.g. if it was only referenced
+    pub methods: Vec<Method>,
+    /// The names of the constants, in ordinal order, if this is an enum
+    pub enum_constants: Vec<String>,
+    /// The permitted subclasses, as class descriptors, if this is a sealed class or interface
+    pub permitted_subclasses: Vec<String>,
+    /// The note of the `#[deprecated]` of the wrapper, if the class is `@Deprecated`
+    pub deprecated: Option<String>,
+}
+
+impl From<&Object> for Wrapper {
+    fn from(object: &Object) -> Self {
+        Self {
+            class: object.java_name.to_string(),
+            rust_name: object.obj_name.no_lifetime().to_string(),
+            methods: object.methods.iter().map(Method::from).collect(),
+            enum_constants: object.enum_constants.clone(),
+            permitted_subclasses: object
+                .permitted_subclasses
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            deprecated: object.deprecated.clone(),
+        }
+    }
+}
+
+/// A method of a native class or wrapper
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Method {
+    /// The name in Java, `<init>` for constructors
+    pub name: String,
+    /// The name of the Rust method
+    pub rust_name: String,
+    /// The method descriptor, e.g. `(ILjava/lang/String;)V`
+    pub descriptor: String,
+    /// A `static` method
+    pub is_static: bool,
+    /// A `native` method, implemented in Rust
+    pub is_native: bool,
+    /// A constructor
+    pub is_constructor: bool,
+    /// The parameters, in order
+    pub arguments: Vec<Argument>,
+    /// The Java result type, `None` for `void` and constructors
+    pub result: Option<JavaType>,
+    /// The name of the Rust result type, without its path, e.g. `String`
+    pub rust_result: String,
+    /// The declared exceptions, as class descriptors
+    pub exceptions: Vec<String>,
+    /// The names the native method is exported as, the first is the implementation, empty for other methods
+    pub export_names: Vec<String>,
+    /// The note of the `#[deprecated]` of the wrapper method, if the method is `@Deprecated`
+    pub deprecated: Option<String>,
+    /// The bridge of a covariant override, the wrapper method is a hidden alias returning the type of the overridden method
+    pub is_bridge: bool,
+}
+
+impl From<&Function> for Method {
+    fn from(function: &Function) -> Self {
+        let descriptor = function.signature.as_str();
+        let (parameters, result) = parse_method_descriptor(descriptor).unwrap_or_default();
+
+        Self {
+            name: function.name.clone(),
+            rust_name: function.rust_method_name.for_rust_ident().to_string(),
+            descriptor: descriptor.to_string(),
+            is_static: function.is_static,
+            is_native: function.is_native,
+            is_constructor: function.is_constructor,
+            arguments: function
+                .arguments
+                .iter()
+                .zip(parameters)
+                .map(|(arg, ty)| Argument {
+                    name: arg.name.to_string(),
+                    java_name: arg.java_name.clone(),
+                    ty,
+                    rust_type: arg.rs_ty.to_string(),
+                })
+                .collect(),
+            result,
+            rust_result: function.rs_result.to_string(),
+            exceptions: function
+                .exceptions
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            export_names: function
+                .fn_export_ffi_names
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            deprecated: function.deprecated.clone(),
+            is_bridge: function.bridge,
+        }
+    }
+}
+
+/// A parameter of a method
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Argument {
+    /// The name of the Rust argument, e.g. `arg0`
+    pub name: String,
+    /// The name of the Java parameter, if the class was compiled with `-parameters` or `-g`
+    pub java_name: Option<String>,
+    /// The Java type
+    pub ty: JavaType,
+    /// The name of the Rust type, without its path, e.g. `String`
+    pub rust_type: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_method_descriptor() {
+        assert_eq!(
+            parse_method_descriptor("(I[[Ljava/lang/String;Z)J"),
+            Some((
+                vec![
+                    JavaType::Int,
+                    JavaType::Array(Box::new(JavaType::Array(Box::new(JavaType::Object(
+                        "java/lang/String".to_string()
+                    ))))),
+                    JavaType::Boolean,
+                ],
+                Some(JavaType::Long)
+            ))
+        );
+        assert_eq!(parse_method_descriptor("()V"), Some((vec![], None)));
+        assert_eq!(parse_method_descriptor("(L;)V"), None);
+        assert_eq!(parse_method_descriptor("(Ljava.lang.String;)V"), None);
+        assert_eq!(parse_method_descriptor("(Ljava//String;)V"), None);
+        assert_eq!(JavaType::from_descriptor("IJ"), None);
+        assert_eq!(JavaType::from_descriptor("[B").unwrap().descriptor(), "[B");
+    }
+}
@@ -0,0 +1,271 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Per-method overrides of the generation, read from a TOML or JSON file, or from the annotations of the methods
+
+use std::{collections::HashMap, path::Path};
+
+use cafebabe::{
+    attributes::{Annotation, AnnotationElementValue, AttributeData},
+    MethodInfo,
+};
+use serde::Deserialize;
+
+use crate::{
+    error::{Error, ErrorKind},
+    template::JavaDesc,
+};
+
+/// The overrides of the methods, keyed by the java class name and then by the method
+///
+/// The method is either its name, for all its overloads, or its name followed by the descriptor, e.g. `sumArray([I)I`, which
+///   takes precedence. Constructors are named `<init>`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(transparent)]
+pub(crate) struct Overrides {
+    classes: HashMap<String, HashMap<String, MethodOverride>>,
+}
+
+/// The changes to the generation of a method
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct MethodOverride {
+    /// The Rust name of the method, in place of the snake case of the Java name
+    pub(crate) rename: Option<String>,
+    /// Returns a `Result`, even if the method doesn't declare exceptions
+    #[serde(default)]
+    pub(crate) result: bool,
+    /// The indexes of the `String` arguments that are passed as `Option<String>`, `None` for `null`
+    ///
+    /// Other reference arguments keep their type, the checks of `validate_args` accept `null` for them
+    #[serde(default)]
+    pub(crate) nullable: Vec<usize>,
+    /// How the `byte[]` arguments of a native method are passed, in place of the `byte_vec_args` of the builder
+    pub(crate) arrays: Option<ArrayStrategy>,
+    /// Nothing is generated for the method
+    #[serde(default)]
+    pub(crate) skip: bool,
+}
+
+/// How the `byte[]` arguments of native methods are passed
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ArrayStrategy {
+    /// As `jaffi_support::arrays::JavaByteArray`, borrowing the elements from the JVM
+    Borrowed,
+    /// Copied into a `Vec<u8>`
+    Vec,
+}
+
+impl MethodOverride {
+    /// The override from the `@JaffiSkip`, `@JaffiRename` and `@JaffiNullable` annotations of the method, `None` if it has none
+    ///
+    /// The annotations are matched by their simple name, in any package, with any retention. `@JaffiRename` takes the Rust
+    ///   name as its `value`, and `@JaffiNullable` marks `String` parameters.
+    pub(crate) fn from_annotations(method: &MethodInfo<'_>) -> Option<Self> {
+        let mut method_override = Self::default();
+        let mut annotated = false;
+        for annotation in annotations(method) {
+            match simple_name(annotation) {
+                "JaffiSkip" => method_override.skip = true,
+                "JaffiRename" => {
+                    method_override.rename = annotation.elements.iter().find_map(|element| {
+                        match (&*element.name, &element.value) {
+                            ("value", AnnotationElementValue::StringConstant(rename)) => {
+                                Some(rename.to_string())
+                            }
+                            _ => None,
+                        }
+                    })
+                }
+                _ => continue,
+            }
+            annotated = true;
+        }
+
+        for (index, annotations) in parameter_annotations(method).into_iter().enumerate() {
+            if annotations
+                .iter()
+                .any(|annotation| simple_name(annotation) == "JaffiNullable")
+            {
+                method_override.nullable.push(index);
+                annotated = true;
+            }
+        }
+
+        annotated.then_some(method_override)
+    }
+
+    /// The override of the file, with the changes of the annotations it doesn't make
+    pub(crate) fn merge(file: Option<&Self>, annotations: Option<Self>) -> Option<Self> {
+        let Some(file) = file else {
+            return annotations;
+        };
+        let mut merged = file.clone();
+        if let Some(annotations) = annotations {
+            merged.rename = merged.rename.or(annotations.rename);
+            merged.skip |= annotations.skip;
+            merged.nullable.extend(annotations.nullable);
+            merged.nullable.sort_unstable();
+            merged.nullable.dedup();
+        }
+
+        Some(merged)
+    }
+}
+
+/// The annotations of the method, visible and invisible at runtime
+fn annotations<'m>(method: &'m MethodInfo<'_>) -> impl Iterator<Item = &'m Annotation<'m>> {
+    method
+        .attributes
+        .iter()
+        .filter_map(|attribute| match &attribute.data {
+            AttributeData::RuntimeVisibleAnnotations(annotations)
+            | AttributeData::RuntimeInvisibleAnnotations(annotations) => Some(annotations),
+            _ => None,
+        })
+        .flatten()
+}
+
+/// The annotations of each parameter of the method, visible and invisible at runtime
+fn parameter_annotations<'m>(method: &'m MethodInfo<'_>) -> Vec<Vec<&'m Annotation<'m>>> {
+    let mut parameters = Vec::<Vec<_>>::new();
+    for attribute in &method.attributes {
+        let (AttributeData::RuntimeVisibleParameterAnnotations(annotations)
+        | AttributeData::RuntimeInvisibleParameterAnnotations(annotations)) = &attribute.data
+        else {
+            continue;
+        };
+
+        parameters.resize_with(parameters.len().max(annotations.len()), Vec::new);
+        for (parameter, annotations) in parameters.iter_mut().zip(annotations) {
+            parameter.extend(&annotations.annotations);
+        }
+    }
+
+    parameters
+}
+
+/// The simple name of the annotation type, e.g. `JaffiSkip` for `Lcom/example/JaffiSkip;`
+fn simple_name<'a>(annotation: &'a Annotation<'_>) -> &'a str {
+    annotation
+        .type_descriptor
+        .trim_end_matches(';')
+        .rsplit(['/', '$'])
+        .next()
+        .unwrap_or_default()
+}
+
+impl Overrides {
+    /// Reads the overrides from the file, TOML if the extension is `toml`, otherwise JSON
+    pub(crate) fn read(path: &Path) -> Result<Self, Error> {
+        let error = |message: String| ErrorKind::Overrides {
+            path: path.to_path_buf(),
+            message,
+        };
+
+        let contents = std::fs::read_to_string(path).map_err(|e| error(e.to_string()))?;
+        let overrides = if path.extension().is_some_and(|ext| ext == "toml") {
+            toml::from_str(&contents).map_err(|e| error(e.to_string()))?
+        } else {
+            serde_json::from_str(&contents).map_err(|e| error(e.to_string()))?
+        };
+
+        Ok(overrides)
+    }
+
+    /// The override of the method of the class, e.g. `net/bluejekyll/NativeArrays`, the descriptor takes precedence over the name
+    pub(crate) fn get(&self, class: &str, name: &str, descriptor: &str) -> Option<&MethodOverride> {
+        let methods = self.methods(class)?;
+        methods
+            .get(&format!("{name}{descriptor}"))
+            .or_else(|| methods.get(name))
+    }
+
+    /// The methods of the class that have overrides, as they are keyed in the file
+    pub(crate) fn method_keys(&self, class: &str) -> impl Iterator<Item = &str> {
+        self.methods(class)
+            .into_iter()
+            .flat_map(|methods| methods.keys().map(String::as_str))
+    }
+
+    fn methods(&self, class: &str) -> Option<&HashMap<String, MethodOverride>> {
+        self.classes
+            .iter()
+            .find(|(name, _)| JavaDesc::from(name.as_str()).as_str() == class)
+            .map(|(_, methods)| methods)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get() {
+        let toml: Overrides = toml::from_str(
+            r#"
+            ["net.bluejekyll.NativeArrays"]
+            sumArray = { rename = "sum" }
+            "sumArray([I)I" = { result = true, arrays = "vec" }
+            "#,
+        )
+        .unwrap();
+        let json: Overrides = serde_json::from_str(
+            r#"{"net.bluejekyll.NativeArrays": {"sumArray": {"rename": "sum"}, "sumArray([I)I": {"result": true, "arrays": "vec"}}}"#,
+        )
+        .unwrap();
+
+        for overrides in [toml, json] {
+            let exact = overrides
+                .get("net/bluejekyll/NativeArrays", "sumArray", "([I)I")
+                .unwrap();
+            assert!(exact.result);
+            assert_eq!(exact.arrays, Some(ArrayStrategy::Vec));
+            assert!(exact.rename.is_none());
+
+            let by_name = overrides
+                .get("net/bluejekyll/NativeArrays", "sumArray", "([B)I")
+                .unwrap();
+            assert_eq!(by_name.rename.as_deref(), Some("sum"));
+            assert!(!by_name.result);
+
+            assert!(overrides
+                .get("net/bluejekyll/NativeStrings", "sumArray", "([I)I")
+                .is_none());
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        let file = MethodOverride {
+            rename: Some("sum".to_string()),
+            nullable: vec![1],
+            ..Default::default()
+        };
+        let annotations = MethodOverride {
+            rename: Some("add".to_string()),
+            nullable: vec![0, 1],
+            skip: true,
+            ..Default::default()
+        };
+
+        let merged = MethodOverride::merge(Some(&file), Some(annotations.clone())).unwrap();
+        assert_eq!(merged.rename.as_deref(), Some("sum"));
+        assert_eq!(merged.nullable, [0, 1]);
+        assert!(merged.skip);
+
+        let merged = MethodOverride::merge(None, Some(annotations)).unwrap();
+        assert_eq!(merged.rename.as_deref(), Some("add"));
+        assert!(MethodOverride::merge(None, None).is_none());
+    }
+
+    #[test]
+    fn test_unknown_field() {
+        assert!(toml::from_str::<Overrides>("[\"p.A\"]\nfoo = { renamed = \"bar\" }").is_err());
+    }
+}
@@ -0,0 +1,24 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The checks for a pending exception before the wrapper methods call into Java
+
+/// When the wrapper methods check for a pending exception before calling into Java
+///
+/// Calling into Java with a pending exception is undefined behavior in JNI, e.g. after a wrapper call that threw wasn't handled.
+///   Wrappers that catch exceptions return the pending exception as their `Err`, the others panic without making the call.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[non_exhaustive]
+pub enum PendingExceptionChecks {
+    /// The wrappers don't check
+    #[default]
+    Never,
+    /// The wrappers check in builds with `debug_assertions`
+    Debug,
+    /// The wrappers always check
+    Always,
+}
@@ -0,0 +1,34 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The items re-exported from the generated `prelude` module
+
+/// The items re-exported from the generated `prelude` module, the default is all of them
+///
+/// e.g. `Prelude { wrappers: false, ..Default::default() }`
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Prelude {
+    /// The `*Rs` traits of the native classes
+    pub native_traits: bool,
+    /// The wrappers, with their `*Class` types and the Rust enums of Java enums
+    pub wrappers: bool,
+    /// The exception types and the enums of the exceptions declared by the methods
+    pub exceptions: bool,
+    /// `JNIEnv`, `JObject`, `JClass` and `JString`, and `Exception`, `Error` and the conversion traits of `jaffi_support`
+    pub support: bool,
+}
+
+impl Default for Prelude {
+    fn default() -> Self {
+        Self {
+            native_traits: true,
+            wrappers: true,
+            exceptions: true,
+            support: true,
+        }
+    }
+}
@@ -0,0 +1,21 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The Rust types of the Java primitives
+
+/// The Rust types of the Java primitives that have more than one reasonable mapping, the defaults are `i8`, `char` and `bool`
+///
+/// e.g. `PrimitiveTypes { unsigned_bytes: true, ..Default::default() }`
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct PrimitiveTypes {
+    /// `byte` is `u8`, the bits are reinterpreted, i.e. `-1` is `255`
+    pub unsigned_bytes: bool,
+    /// `char` is the UTF-16 code unit `u16`, surrogates are passed unchanged instead of with the `char_strategy`
+    pub utf16_chars: bool,
+    /// `boolean` is the `jboolean` `u8`, `0` or `1`
+    pub numeric_booleans: bool,
+}
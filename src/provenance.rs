@@ -0,0 +1,117 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The provenance of the generated bindings, the jaffi version, the fingerprints of the class files and the options
+
+use std::fmt::Write;
+
+use proc_macro2::{Literal, TokenStream};
+use quote::quote;
+
+/// The version of jaffi, e.g. `jaffi 0.2.0`
+const GENERATED_BY: &str = concat!("jaffi ", env!("CARGO_PKG_VERSION"));
+
+/// The 64 bit FNV-1a hash of the bytes, stable across builds and platforms
+pub(crate) fn fingerprint(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// The inputs of the generation, written to the header of the generated file and as constants
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Provenance {
+    /// The classes, i.e. `java/lang/Object`, with the fingerprints of their class files, sorted by class
+    pub(crate) classes: Vec<(String, u64)>,
+    /// The options of the builder that change the generated code, e.g. `time_conversions`
+    pub(crate) options: Vec<String>,
+}
+
+impl Provenance {
+    /// The fingerprint of all the classes and options, changes if any of them does
+    fn fingerprint(&self) -> u64 {
+        let mut inputs = String::new();
+        for (class, fingerprint) in &self.classes {
+            writeln!(inputs, "{class} {fingerprint:016x}").expect("writing to a String");
+        }
+        for option in &self.options {
+            writeln!(inputs, "{option}").expect("writing to a String");
+        }
+
+        fingerprint(inputs.as_bytes())
+    }
+
+    /// The comments at the top of the generated file
+    pub(crate) fn to_comment(&self) -> String {
+        let mut comment = format!("// Generated by {GENERATED_BY}, do not edit\n//\n");
+        writeln!(comment, "// fingerprint: {:016x}", self.fingerprint())
+            .expect("writing to a String");
+        writeln!(comment, "// options: {}", self.options.join(", ")).expect("writing to a String");
+        comment.push_str("// classes:\n");
+        for (class, fingerprint) in &self.classes {
+            writeln!(comment, "//   {class} {fingerprint:016x}").expect("writing to a String");
+        }
+        comment.push('\n');
+
+        comment
+    }
+
+    /// The `GENERATED_BY`, `GENERATED_FROM` and `GENERATED_OPTIONS` constants
+    pub(crate) fn to_tokens(&self) -> TokenStream {
+        let classes = self.classes.iter().map(|(class, _)| class);
+        let fingerprints = self
+            .classes
+            .iter()
+            .map(|(_, fingerprint)| format!("{fingerprint:016x}"));
+        let options = &self.options;
+        // the constants are only in the library if they are used, the static is kept for audits of the binary
+        let provenance = Literal::byte_string(
+            format!("{GENERATED_BY} {:016x}\0", self.fingerprint()).as_bytes(),
+        );
+
+        quote! {
+            /// The version of jaffi that generated these bindings, e.g. `jaffi 0.2.0`
+            pub const GENERATED_BY: &str = #GENERATED_BY;
+
+            /// The classes these bindings were generated from, with the FNV-1a fingerprints of their class files
+            pub const GENERATED_FROM: &[(&str, &str)] = &[#((#classes, #fingerprints)),*];
+
+            /// The options of the builder that changed the generated code
+            pub const GENERATED_OPTIONS: &[&str] = &[#(#options),*];
+
+            /// `GENERATED_BY` with the fingerprint of all the inputs, found in the library with e.g. `strings`
+            #[used]
+            static JAFFI_PROVENANCE: &[u8] = #provenance;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint() {
+        // the published test vectors of FNV-1a
+        assert_eq!(fingerprint(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fingerprint(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fingerprint(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn test_comment() {
+        let provenance = Provenance {
+            classes: vec![("net/bluejekyll/Foo".to_string(), 0xff)],
+            options: vec!["time_conversions".to_string()],
+        };
+
+        let comment = provenance.to_comment();
+        assert!(comment.starts_with(&format!("// Generated by {GENERATED_BY}")));
+        assert!(comment.contains("// options: time_conversions\n"));
+        assert!(comment.contains("//   net/bluejekyll/Foo 00000000000000ff\n"));
+    }
+}
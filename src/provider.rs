@@ -0,0 +1,28 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Class files from other sources than the directories of the classpath
+
+/// Supplies class files, e.g. from jars, artifact stores, virtual filesystems of build systems or embedded resources
+///
+/// The directories of the classpath are searched first, the provider is asked for the classes that aren't found there.
+///   Closures, `Fn(&str) -> Option<Vec<u8>>`, are providers.
+pub trait ClassProvider {
+    /// Returns the content of the class file, `None` if the provider doesn't have the class
+    ///
+    /// * `class` - the class descriptor, e.g. `java/lang/String`
+    fn load(&self, class: &str) -> Option<Vec<u8>>;
+}
+
+impl<F> ClassProvider for F
+where
+    F: Fn(&str) -> Option<Vec<u8>>,
+{
+    fn load(&self, class: &str) -> Option<Vec<u8>> {
+        self(class)
+    }
+}
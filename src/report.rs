@@ -0,0 +1,184 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! A report of the Java constructs that were not generated as declared, to audit the coverage of the bindings
+
+use std::fmt::{self, Write};
+
+/// What happened to a method, type or constant during generation
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum ReportKind {
+    /// Nothing was generated for it
+    Skipped,
+    /// A less specific type was generated, e.g. `UnsupportedArray` or a wrapper without methods
+    Downgraded,
+    /// The Rust name differs from the Java name, beyond the change of case
+    Renamed,
+}
+
+impl ReportKind {
+    /// The lower case name, as used in the JSON
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Skipped => "skipped",
+            Self::Downgraded => "downgraded",
+            Self::Renamed => "renamed",
+        }
+    }
+}
+
+impl fmt::Display for ReportKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A single method, type or constant that was not generated as declared
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub struct ReportEntry {
+    /// The class, e.g. `net/bluejekyll/NativeArrays`
+    pub class: String,
+    /// The method, field or component of the class, `None` if the entry is about the class itself
+    pub member: Option<String>,
+    /// What happened to it
+    pub kind: ReportKind,
+    /// Why it happened
+    pub reason: String,
+}
+
+impl fmt::Display for ReportEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.kind, self.class)?;
+        if let Some(member) = &self.member {
+            write!(f, ".{member}")?;
+        }
+        write!(f, ": {}", self.reason)
+    }
+}
+
+/// The report returned from `Jaffi::generate`, the entries are sorted by class and member
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    entries: Vec<ReportEntry>,
+}
+
+impl Report {
+    /// All the entries
+    pub fn entries(&self) -> &[ReportEntry] {
+        &self.entries
+    }
+
+    /// `true` if everything was generated as declared
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The entries for a class, e.g. `net/bluejekyll/NativeArrays`
+    pub fn for_class<'r>(&'r self, class: &'r str) -> impl Iterator<Item = &'r ReportEntry> + 'r {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.class == class)
+    }
+
+    pub(crate) fn push(
+        &mut self,
+        kind: ReportKind,
+        class: &str,
+        member: Option<&str>,
+        reason: impl Into<String>,
+    ) {
+        self.entries.push(ReportEntry {
+            class: class.to_string(),
+            member: member.map(ToString::to_string),
+            kind,
+            reason: reason.into(),
+        });
+    }
+
+    /// Sorts and dedups the entries, classes are visited in the order of a HashSet
+    pub(crate) fn finish(&mut self) {
+        self.entries.sort();
+        self.entries.dedup();
+    }
+
+    /// The entries as a JSON array of objects with `class`, `member`, `kind` and `reason`
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("[");
+
+        for (i, entry) in self.entries.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+
+            json.push_str("\n  {\"class\": ");
+            write_json_string(&mut json, &entry.class);
+            json.push_str(", \"member\": ");
+            match &entry.member {
+                Some(member) => write_json_string(&mut json, member),
+                None => json.push_str("null"),
+            }
+            json.push_str(", \"kind\": ");
+            write_json_string(&mut json, entry.kind.as_str());
+            json.push_str(", \"reason\": ");
+            write_json_string(&mut json, &entry.reason);
+            json.push('}');
+        }
+
+        if !self.entries.is_empty() {
+            json.push('\n');
+        }
+        json.push_str("]\n");
+        json
+    }
+}
+
+fn write_json_string(json: &mut String, s: &str) {
+    json.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            ch if ch.is_control() => write!(json, "\\u{:04x}", ch as u32).unwrap(),
+            ch => json.push(ch),
+        }
+    }
+    json.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_json() {
+        let mut report = Report::default();
+        assert_eq!(report.to_json(), "[]\n");
+
+        report.push(
+            ReportKind::Renamed,
+            "p/B",
+            Some("self"),
+            "\"self\" is not a valid identifier",
+        );
+        report.push(ReportKind::Skipped, "p/A", None, "tab\there");
+        report.finish();
+
+        assert_eq!(
+            report.to_json(),
+            r#"[
+  {"class": "p/A", "member": null, "kind": "skipped", "reason": "tab\there"},
+  {"class": "p/B", "member": "self", "kind": "renamed", "reason": "\"self\" is not a valid identifier"}
+]
+"#
+        );
+    }
+}
@@ -0,0 +1,177 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Minimal parsing of the generic `Signature` attribute of methods, the descriptor has the erased types.
+
+/// The type arguments of the parameters and result of a method, e.g. `(Ljava/util/Optional<Ljava/lang/String;>;)V`
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct MethodTypeArgs {
+    /// The class of the first type argument of each parameter, `None` if there is no type argument, or it's not a class
+    pub(crate) parameters: Vec<Option<String>>,
+    /// The class of the first type argument of the result
+    pub(crate) result: Option<String>,
+}
+
+/// A parsed type in the signature
+struct SigType {
+    /// The class name, `None` for primitives, arrays and type variables
+    class: Option<String>,
+    /// The class of the first type argument
+    type_arg: Option<String>,
+}
+
+impl SigType {
+    fn other() -> Self {
+        Self {
+            class: None,
+            type_arg: None,
+        }
+    }
+}
+
+/// Parses the generic signature of a method, returns `None` if the signature is not understood
+pub(crate) fn parse_method_signature(signature: &str) -> Option<MethodTypeArgs> {
+    let mut chars = signature.as_bytes();
+
+    // skip the type parameters of the method
+    if chars.first() == Some(&b'<') {
+        chars = skip_balanced(chars)?;
+    }
+
+    chars = chars.strip_prefix(b"(")?;
+
+    let mut parameters = Vec::new();
+    while chars.first() != Some(&b')') {
+        let (ty, rest) = parse_type(chars)?;
+        parameters.push(ty.type_arg);
+        chars = rest;
+    }
+    chars = &chars[1..];
+
+    let result = if chars.first() == Some(&b'V') {
+        None
+    } else {
+        parse_type(chars)?.0.type_arg
+    };
+
+    Some(MethodTypeArgs { parameters, result })
+}
+
+/// Skips the `<...>` at the start of chars
+fn skip_balanced(chars: &[u8]) -> Option<&[u8]> {
+    let mut depth = 0usize;
+    for (i, c) in chars.iter().enumerate() {
+        match c {
+            b'<' => depth += 1,
+            b'>' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&chars[i + 1..]);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    None
+}
+
+fn parse_type(chars: &[u8]) -> Option<(SigType, &[u8])> {
+    match chars.first()? {
+        b'B' | b'C' | b'D' | b'F' | b'I' | b'J' | b'S' | b'Z' => {
+            Some((SigType::other(), &chars[1..]))
+        }
+        b'[' => parse_type(&chars[1..]).map(|(_, rest)| (SigType::other(), rest)),
+        b'T' => {
+            let end = chars.iter().position(|c| *c == b';')?;
+            Some((SigType::other(), &chars[end + 1..]))
+        }
+        b'L' => parse_class_type(&chars[1..]),
+        _ => None,
+    }
+}
+
+/// Parses the class type after the `L`, including the terminating `;`
+fn parse_class_type(mut chars: &[u8]) -> Option<(SigType, &[u8])> {
+    let mut class = String::new();
+    let mut type_arg = None;
+
+    loop {
+        match chars.first()? {
+            b';' => {
+                return Some((
+                    SigType {
+                        class: Some(class),
+                        type_arg,
+                    },
+                    &chars[1..],
+                ))
+            }
+            b'<' => {
+                chars = &chars[1..];
+                let mut first = true;
+                while chars.first()? != &b'>' {
+                    let arg = match chars.first()? {
+                        b'*' => {
+                            chars = &chars[1..];
+                            None
+                        }
+                        b'+' | b'-' => {
+                            let (ty, rest) = parse_type(&chars[1..])?;
+                            chars = rest;
+                            ty.class
+                        }
+                        _ => {
+                            let (ty, rest) = parse_type(chars)?;
+                            chars = rest;
+                            ty.class
+                        }
+                    };
+
+                    if first {
+                        type_arg = arg;
+                        first = false;
+                    }
+                }
+                chars = &chars[1..];
+            }
+            // inner classes of generic classes, e.g. `Lp/Outer<TT;>.Inner;`
+            b'.' => {
+                class.push('$');
+                type_arg = None;
+                chars = &chars[1..];
+            }
+            _ => {
+                // the delimiters are all ascii, so this is on a char boundary
+                let end = chars.iter().position(|c| matches!(c, b';' | b'<' | b'.'))?;
+                class.push_str(std::str::from_utf8(&chars[..end]).ok()?);
+                chars = &chars[end..];
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_method_signature() {
+        let args = parse_method_signature(
+            "<T:Ljava/lang/Object;>(ILjava/util/Optional<Ljava/lang/String;>;[TT;Ljava/util/Map<**>;)Ljava/util/Optional<+Lp/A<TT;>;>;",
+        )
+        .expect("failed to parse");
+
+        assert_eq!(
+            args,
+            MethodTypeArgs {
+                parameters: vec![None, Some("java/lang/String".to_string()), None, None],
+                result: Some("p/A".to_string()),
+            }
+        );
+    }
+}
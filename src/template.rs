@@ -6,65 +6,82 @@
 // copied, modified, or distributed except according to those terms.
 
 use std::{
-    collections::{BTreeSet, HashSet},
-    fmt,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fmt::{self, Write},
 };
 
 use cafebabe::descriptor::{BaseType, FieldType, ReturnDescriptor, Ty};
 use enum_as_inner::EnumAsInner;
-use heck::{ToSnakeCase, ToUpperCamelCase};
+use heck::{ToShoutySnakeCase, ToSnakeCase, ToUpperCamelCase};
 use jaffi_support::{
-    JavaBoolean, JavaByte, JavaChar, JavaDouble, JavaFloat, JavaInt, JavaLong, JavaShort, JavaVoid,
+    chars::CharStrategy, string::StringStrategy, JavaBoolean, JavaByte, JavaChar, JavaDouble, JavaFloat, JavaInt,
+    JavaLong, JavaShort, JavaVoid,
 };
-use proc_macro2::{Ident, TokenStream};
+use proc_macro2::{Delimiter, Group, Ident, Spacing, TokenStream, TokenTree};
 use quote::{format_ident, quote, ToTokens, TokenStreamExt};
 
-use crate::ident::make_ident;
+use crate::{
+    ident::{escape_non_ascii, make_ident},
+    model::{parse_method_descriptor, JavaType, Model},
+    provenance::Provenance,
+    report::Report,
+    tokens::{package_path, GeneratedTokens},
+    JniVersion, PendingExceptionChecks, Prelude, PrimitiveTypes,
+};
 
-fn generate_function(func: &Function) -> TokenStream {
+fn generate_function(
+    func: &Function,
+    enum_types: &HashSet<RustTypeName>,
+    config: &FfiConfig,
+) -> TokenStream {
     let name = &func.name;
     let jni_sig = &func.signature;
-    let java_doc = format!("A wrapper for the java function `{name}{jni_sig}`");
+    let java_doc = if func.native_factory {
+        format!("Creates the object with the native factory `{name}{jni_sig}`, implemented in Rust")
+    } else {
+        format!("A wrapper for the java function `{name}{jni_sig}`")
+    };
     let rust_method_name = func.rust_method_name.for_rust_ident();
-    let add_pub = if !func.is_static {
+    let add_pub = if !func.is_static || func.native_factory {
         quote! {pub}
     } else {
         quote! {}
     };
-    let amp_self = if !func.is_constructor {
+    let amp_self = if !func.is_constructor && !func.native_factory {
         quote! {&self,}
     } else {
         quote! {}
     };
-    let arguments = func
-        .arguments
-        .iter()
-        .map(|arg| (&arg.name, &arg.rs_ty))
-        .map(|(name, rs_ty)| quote! { #name: #rs_ty })
-        .collect::<Vec<_>>();
-    let exception_name = exception_name_from_set(&func.exceptions);
+    let jstring = ObjectType::JString.to_jni_type_name();
+    let arguments = wrapper_arguments(func, enum_types, config);
+    let exception_name = wrapper_exception(func, config);
     let return_err = quote!{ Exception::<'j, #exception_name> };
     let rs_result = &func.rs_result;
-    let rs_result_sig = if !func.exceptions.is_empty() {
-        quote!{ Result<#rs_result, #return_err> }
-    } else {
-        quote!{ #rs_result }
-    };
+    let rs_result_sig = wrapper_result(func, config);
     let result = &func.result;
+    // `JObject` derefs to the raw pointer, not to a `JObject`, so it doesn't get the `IntoJavaValue` of the other objects
+    let jobject = ObjectType::JObject.to_jni_type_name();
     let to_jvalue_args= func
         .arguments
         .iter()
         .map(|arg| (&arg.name, &arg.rs_ty, &arg.ty))
-        .map(|(name, rs_ty, ty)| 
+        .map(|(name, rs_ty, ty)| if enum_types.contains(ty) {
+            // converted beforehand, Java can reject the name of a constant
+            quote!{ #name }
+        } else if config.interned_string_args && *ty == jstring {
+            quote!{ IntoJavaValue::<'j, #ty>::into_java_value(#name, env) }
+        } else if *ty == jobject {
+            quote!{ JValue::Object(#name) }
+        } else {
             quote!{ <#rs_ty as IntoJavaValue<'j, #ty>>::into_java_value(#name, env) }
-        )
+        })
         .collect::<Vec<_>>();
     let object_java_desc = &func.object_java_desc.0;
-    let signature = &func.signature.0;
+    let signature = func.signature.as_str();
     let name = &func.name;
     let from_java_value =
-        quote! { <#rs_result as FromJavaValue<#result>>::from_jvalue(env, jvalue) };
-    let exception_handler = if !func.exceptions.is_empty() { 
+        quote! { jaffi_support::from_jvalue_checked::<#result, #rs_result>(env, jvalue) };
+    let exception_handler = if exception_name.is_some() { 
         quote!{
             Err(jni::errors::Error::JavaException) => {
                 let throwable = match env.exception_occurred() {
@@ -84,28 +101,33 @@ fn generate_function(func: &Function) -> TokenStream {
     } else {
         quote!{}
     };
-    let ok_return = if !func.exceptions.is_empty() {
+    let ok_return = if exception_name.is_some() {
         quote!{ let rust_value = Ok(rust_value); }
     } else {
         quote!{}
     };
-    let method_call = if func.is_constructor {
+    let class_cache = class_cache_ident(object_java_desc);
+    let method_call = if func.late_bound {
+        late_bound_method_call(func, config.scratch_buffers)
+    } else if config.cached_method_ids || config.scratch_buffers {
+        cached_method_call(func, config.scratch_buffers)
+    } else if func.is_constructor {
         quote! {
-            env.new_object(
-                #object_java_desc,
+            #class_cache.get(env).and_then(|class| env.new_object(
+                class,
                 #signature,
                 args
-            )
+            ))
             .map(JValue::from)
         }
     } else if func.is_static {
         quote! {
-            env.call_static_method(
-                #object_java_desc,
+            #class_cache.get(env).and_then(|class| env.call_static_method(
+                class,
                 #name,
                 #signature,
                 args
-            )
+            ))
         }
     } else {
         quote! {
@@ -118,27 +140,117 @@ fn generate_function(func: &Function) -> TokenStream {
         }
     };
 
+    // an enum that can't be converted throws an IllegalArgumentException, Java isn't called and the result is `None`
+    let enum_conversions = func
+        .arguments
+        .iter()
+        .filter(|arg| enum_types.contains(&arg.ty))
+        .map(|Arg { name, ty, .. }| {
+            quote! {
+                let #name = match jaffi_support::TryIntoJavaValue::<'j, #ty>::try_into_java_value(#name, env) {
+                    Ok(value) => value,
+                    Err(e) => {
+                        jaffi_support::exceptions::throw_illegal_argument(env, e);
+                        break 'call Ok(None);
+                    }
+                };
+            }
+        })
+        .collect::<Vec<_>>();
+    let call_body = quote! {
+        {
+            let args: &[JValue<'j>] = &[
+                #(#to_jvalue_args),*
+            ];
+
+            #method_call
+        }
+    };
+    let (call_body, call_result, thrown) = if enum_conversions.is_empty() {
+        (call_body, quote! { Result<JValue, _> }, None)
+    } else {
+        let call_body = quote! {
+            {
+                'call: {
+                    #(#enum_conversions)*
+                    Result::map(#call_body, Some)
+                }
+            }
+        };
+        let thrown_default = thrown_default(func);
+        let thrown = quote! {
+            // an argument was rejected, the exception is thrown to the Java caller
+            Ok(None) => #thrown_default,
+        };
+
+        (call_body, quote! { Result<Option<JValue>, _> }, Some(thrown))
+    };
+    // the arguments converted to Java are released with the frame, an object result is kept
+    let call_body = if config.local_frames {
+        quote! {
+            jaffi_support::frame::with_frame(env, jaffi_support::frame::DEFAULT_CAPACITY, || #call_body)
+        }
+    } else {
+        call_body
+    };
+    // a pending exception is handled as if the method threw it, the arguments aren't converted
+    let catches = exception_name.is_some();
+    let method = format!("{}.{name}{signature}", func.object_java_desc.binary_name());
+    let check =
+        quote! { jaffi_support::exceptions::check_pending_exception(env, #method, #catches) };
+    let pending_exception = match config.pending_exception_checks {
+        PendingExceptionChecks::Never => None,
+        PendingExceptionChecks::Debug => {
+            Some(quote! { if cfg!(debug_assertions) { #check } else { Ok(()) } })
+        }
+        PendingExceptionChecks::Always => Some(check),
+    };
+    let call_body = match pending_exception {
+        Some(pending_exception) => quote! {
+            match #pending_exception {
+                Err(e) => Err(e),
+                Ok(()) => #call_body,
+            }
+        },
+        None => call_body,
+    };
+    let varargs_method = func
+        .varargs
+        .as_ref()
+        .filter(|_| !func.native_factory)
+        .map(|element| generate_varargs_function(func, element, config));
+    let bytes_method = generate_bytes_function(func, config).filter(|_| !func.native_factory);
+    let span = if config.tracing_spans {
+        let class = func.object_java_desc.binary_name();
+        quote! { let _span = jaffi_support::tracing::wrapper_span(#class, #name, #signature); }
+    } else {
+        quote! {}
+    };
+    let deprecated = method_attributes(func);
+    let ok_jvalue = if thrown.is_some() {
+        quote! { Ok(Some(jvalue)) }
+    } else {
+        quote! { Ok(jvalue) }
+    };
+
     quote! {
         #[doc = #java_doc]
         ///
         /// # Arguments
         ///
         /// * `env` - this should be the same JNIEnv "owning" this object
+        #deprecated
         #add_pub fn #rust_method_name(
             #amp_self
             env: JNIEnv<'j>,
             #(#arguments),*
         ) -> #rs_result_sig {
-            let args: &[JValue<'j>] = &[
-                #(#to_jvalue_args),*
-            ];
-
-            let rust_value: Result<JValue, _> = {
-                #method_call
-            };
+            #span
+            let rust_value: #call_result = #call_body;
 
             let rust_value = match rust_value {
-                Ok(jvalue) => #from_java_value,
+                #ok_jvalue => #from_java_value,
+                #thrown
                 #exception_handler
                 Err(e) => {
                     panic!("error call_method, {e}")
@@ -148,462 +260,2880 @@ fn generate_function(func: &Function) -> TokenStream {
             #ok_return
             rust_value 
         }
+
+        #varargs_method
+        #bytes_method
     }
 }
 
-fn generate_struct(obj: &Object) -> TokenStream {
-    let class_name = &obj.class_name;
-    let static_java_doc = format!(
-        "Wrapper for the static methods of Java class `{}`",
-        obj.java_name
-    );
-    let obj_name = &obj.obj_name;
-    let java_doc = format!(
-        "Wrapper for the public methods of Java class `{}`",
-        obj.java_name
+/// `from_fn` of a listener interface, implementing the single abstract method `func` with a closure
+fn generate_listener(func: &Function) -> TokenStream {
+    let interface = func.object_java_desc.as_str();
+    let name = &func.name;
+    let from_fn_doc = format!(
+        "Implements `{}` with the closure, which is called for `{name}`, see `jaffi_support::listener::Listener`",
+        func.object_java_desc.binary_name()
     );
-    let static_trait_name = &obj.static_trait_name;
-    let java_name = obj.java_name.as_str();
+    // the closure is called in the frames of the Java calls, the types get the lifetime of the bound, or an inferred one
+    let call_lifetime = format_ident!("l");
+    let inferred = format_ident!("_");
+    let (parameters, result) = func.signature.types();
 
-    let interfaces = obj
-        .interfaces
+    let names = func.arguments.iter().map(|arg| &arg.name).collect::<Vec<_>>();
+    let bound_args = func
+        .arguments
         .iter()
-        .map(|interface| {
-            let interface = interface.no_lifetime();
-            let as_interface = format_ident!("as_{}", interface.to_string().to_snake_case());
+        .map(|arg| rename_lifetime(arg.rs_ty.to_token_stream(), &call_lifetime));
+    let bound_result = rename_lifetime(func.rs_result.to_token_stream(), &call_lifetime);
+    let conversions = func.arguments.iter().zip(parameters).map(|(arg, parameter)| {
+        let name = &arg.name;
+        let rs_ty = rename_lifetime(arg.rs_ty.to_token_stream(), &inferred);
+        let ty = rename_lifetime(arg.ty.to_token_stream(), &inferred);
+        let descriptor = parameter.descriptor();
+        quote! {
+            let #name = <#rs_ty as jaffi_support::FromJavaValue<#ty>>::from_jvalue(
+                env,
+                jaffi_support::listener::unbox(env, *#name, #descriptor)?,
+            );
+        }
+    });
+    let call = if result.is_some() {
+        let rs_result = rename_lifetime(func.rs_result.to_token_stream(), &inferred);
+        let result = rename_lifetime(func.result.to_token_stream(), &inferred);
+        quote! {
+            let result = f(env, #(#names),*);
+            jaffi_support::listener::box_value(env, <#rs_result as IntoJavaValue<#result>>::into_java_value(result, env))
+        }
+    } else {
+        quote! {
+            f(env, #(#names),*);
+            Ok(JObject::null())
+        }
+    };
 
-            quote! {
-                pub fn #as_interface(&self) -> #interface {
-                    #interface(self.0)
-                }
-            }
-        })
-        .collect::<TokenStream>();
+    quote! {
+        #[doc = #from_fn_doc]
+        ///
+        /// The closure is released when the `Listener` is dropped.
+        pub fn from_fn<F>(env: JNIEnv<'j>, f: F) -> Result<jaffi_support::listener::Listener, JniError>
+        where
+            F: for<'l> Fn(JNIEnv<'l>, #(#bound_args),*) -> #bound_result + Send + Sync + 'static,
+        {
+            jaffi_support::listener::Listener::new(env, #interface, move |env, args| {
+                let [#(#names),*] = args else {
+                    return Err(JniError::WrongJValueType("the arguments of", #name));
+                };
+                #(#conversions)*
 
-    let methods = obj
-        .methods
-        .iter()
-        .filter(|f| !f.is_static)
-        .map(generate_function)
-        .collect::<TokenStream>();
-    let static_methods = obj
-        .methods
-        .iter()
-        .filter(|f| f.is_static)
-        .map(generate_function)
-        .collect::<TokenStream>();
+                #call
+            })
+        }
+    }
+}
 
-    quote! {
-        #[doc = #static_java_doc]
-        #[derive(Clone, Copy, Debug)]
-        #[repr(transparent)]
-        pub struct #class_name (JClass<'j>);
+/// The type of the exceptions caught by the wrapper method of `func`, `None` if exceptions aren't caught
+///
+/// With `unchecked_exceptions`, or the `result` of the overrides, all exceptions are caught as `AnyThrowable`, otherwise only the
+///   declared exceptions are.
+fn wrapper_exception(func: &Function, config: &FfiConfig) -> Option<TokenStream> {
+    if config.unchecked_exceptions || func.catch_all {
+        Some(quote! { jaffi_support::exceptions::AnyThrowable })
+    } else if !func.exceptions.is_empty() {
+        let exception_name = exception_name_from_set(&func.exceptions);
+        Some(quote! { #exception_name })
+    } else {
+        None
+    }
+}
 
-        impl<'j> #static_trait_name for #class_name {}
+/// The result of the wrapper method of `func`, a `Result` if it catches exceptions
+fn wrapper_result(func: &Function, config: &FfiConfig) -> TokenStream {
+    let rs_result = &func.rs_result;
+    match wrapper_exception(func, config) {
+        Some(exception_name) => quote! { Result<#rs_result, Exception::<'j, #exception_name>> },
+        None => quote! { #rs_result },
+    }
+}
 
-        impl<'j> #class_name {
-            fn java_class_desc() -> &'static str {
-                #java_name
+/// The arguments of the wrapper method of `func`
+fn wrapper_arguments(
+    func: &Function,
+    enum_types: &HashSet<RustTypeName>,
+    config: &FfiConfig,
+) -> Vec<TokenStream> {
+    // Strings can also be passed as an `Interned` constant with `interned_string_args`
+    let jstring = ObjectType::JString.to_jni_type_name();
+    let is_interned = |arg: &Arg| config.interned_string_args && arg.ty == jstring;
+    // enums can be passed as the wrapper, the generated Rust enum, or the name of the constant
+    func.arguments
+        .iter()
+        .map(|arg| {
+            let name = &arg.name;
+            let rs_ty = &arg.rs_ty;
+            let ty = &arg.ty;
+            if enum_types.contains(&arg.ty) {
+                quote! { #name: impl jaffi_support::TryIntoJavaValue<'j, #rs_ty> }
+            } else if is_interned(arg) {
+                quote! { #name: impl IntoJavaValue<'j, #ty> }
+            } else {
+                quote! { #name: #rs_ty }
             }
-        }
+        })
+        .collect()
+}
 
-        impl<'j> std::ops::Deref for #class_name  {
-            type Target = JClass<'j>;
+/// The `#[deprecated]` of the wrapper of a `@Deprecated` class or method
+fn deprecated_attribute(deprecated: &Option<String>) -> Option<TokenStream> {
+    deprecated
+        .as_ref()
+        .map(|note| quote! { #[deprecated(note = #note)] })
+}
 
-            fn deref(&self) -> &Self::Target {
-                &self.0
-            }
+/// The `#[deprecated]` of the wrapper methods of a `@Deprecated` method, the `#[doc(hidden)]` of those of a bridge, and the
+///   note of those of a package-private method
+fn method_attributes(func: &Function) -> TokenStream {
+    let deprecated = deprecated_attribute(&func.deprecated);
+    let hidden = func.bridge.then(|| quote! { #[doc(hidden)] });
+    let package_private = func.package_private.then(|| {
+        quote! {
+            ///
+            /// **Package-private** in Java, called without the access checks of Java. It isn't part of the API of the class.
         }
+    });
+    quote! { #package_private #deprecated #hidden }
+}
 
-        impl<'j> FromJavaToRust<'j, #class_name> for #class_name {
-            fn java_to_rust(java: #class_name, _env: JNIEnv<'j>) -> Self {
-                java
-            }
+/// Adds `#[allow(deprecated)]` to each item, the generated code uses the deprecated wrappers and methods itself
+///
+/// An item ends with a `;` or with a `{...}` that isn't followed by a `;`, e.g. the initializer of a `const`.
+pub(crate) fn allow_deprecated(tokens: TokenStream) -> TokenStream {
+    let mut allowed = TokenStream::new();
+    let mut tokens = tokens.into_iter().peekable();
+    let mut item_start = true;
+    while let Some(token) = tokens.next() {
+        if item_start {
+            allowed.extend(quote! { #[allow(deprecated)] });
         }
 
-        impl<'j> FromRustToJava<'j, #class_name> for #class_name {
-            fn rust_to_java(rust: #class_name, _env: JNIEnv<'j>) -> Self {
-                rust
+        item_start = match &token {
+            TokenTree::Punct(punct) => punct.as_char() == ';',
+            TokenTree::Group(group) => {
+                group.delimiter() == Delimiter::Brace
+                    && !matches!(tokens.peek(), Some(TokenTree::Punct(punct)) if punct.as_char() == ';')
             }
-        }
-
-        #[doc = #java_doc]
-        #[derive(Clone, Copy, Debug)]
-        #[repr(transparent)]
-        pub struct #obj_name(JObject<'j>);
-
-        impl<'j> #static_trait_name for #obj_name {}
+            _ => false,
+        };
+        allowed.append(token);
+    }
 
-        impl<'j> #obj_name {
-            /// Returns the type name in java, e.g. `Object` is `"java/lang/Object"`
-            pub fn java_class_desc() -> &'static str {
-                #java_name
-            }
+    allowed
+}
 
-            #interfaces
+/// A module of free functions for the static methods of the class, which call the static trait without a `JClass`
+fn generate_static_module(
+    obj: &Object,
+    module: &Ident,
+    enum_types: &HashSet<RustTypeName>,
+    config: &FfiConfig,
+) -> Option<TokenStream> {
+    let static_methods = obj
+        .methods
+        .iter()
+        .filter(|f| f.is_static && !f.is_constructor && !f.native_factory)
+        .collect::<Vec<_>>();
+    if static_methods.is_empty() {
+        return None;
+    }
 
-            #methods
-        }
+    let module_doc = format!(
+        "The static methods of Java class `{}`, as free functions",
+        obj.java_name
+    );
+    let class_name = obj.class_name.no_lifetime();
+    let static_trait_name = obj.static_trait_name.no_lifetime();
 
-        pub trait #static_trait_name {
-            #static_methods
-        }
+    let functions = static_methods
+        .into_iter()
+        .map(|func| {
+            let name = &func.name;
+            let jni_sig = &func.signature;
+            let java_doc = format!("A wrapper for the java function `{name}{jni_sig}`, see `{static_trait_name}`");
+            let rust_method_name = func.rust_method_name.for_rust_ident();
+            let arguments = wrapper_arguments(func, enum_types, config);
+            let arg_names = func.arguments.iter().map(|arg| &arg.name);
 
-        impl<'j> std::ops::Deref for #obj_name {
-            type Target = JObject<'j>;
+            let rs_result_sig = wrapper_result(func, config);
+            let deprecated = method_attributes(func);
 
-            fn deref(&self) -> &Self::Target {
-                &self.0
+            quote! {
+                #[doc = #java_doc]
+                #deprecated
+                pub fn #rust_method_name<'j>(
+                    env: JNIEnv<'j>,
+                    #(#arguments),*
+                ) -> #rs_result_sig {
+                    // the static methods use the cached class, not the receiver
+                    let class = #class_name(JClass::from(JObject::null()));
+                    #static_trait_name::#rust_method_name(&class, env, #(#arg_names),*)
+                }
             }
-        }
+        })
+        .collect::<TokenStream>();
 
-        impl<'j> From<#obj_name> for JObject<'j> {
-            fn from(obj: #obj_name) -> Self {
-                obj.0
-            }
-        }
+    Some(quote! {
+        #[doc = #module_doc]
+        #[allow(unused_imports)]
+        pub mod #module {
+            use super::*;
 
-        impl<'j> From<JObject<'j>> for #obj_name {
-            fn from(obj: JObject<'j>) -> Self {
-                Self(obj)
-            }
+            #functions
         }
+    })
+}
 
-        impl<'j> FromJavaToRust<'j, #obj_name> for #obj_name {
-            fn java_to_rust(java: #obj_name, _env: JNIEnv<'j>) -> Self  {
-                java
-            }
-        }
+/// `bind` of the wrapper of a class of the `late_bound_classes`, which looks up the class and all the declared methods
+fn generate_late_bind(obj: &Object, class_cache: &Ident) -> Option<TokenStream> {
+    if !obj.methods.iter().any(|f| f.late_bound) {
+        return None;
+    }
 
-        impl<'j> FromRustToJava<'j, #obj_name> for #obj_name {
-            fn rust_to_java(rust: #obj_name, _env: JNIEnv<'j>) -> Self {
-                rust
-            }
+    // a Java method named `bind` takes precedence, like `lock`
+    let bind = if obj
+        .methods
+        .iter()
+        .any(|f| f.rust_method_name.for_rust_ident() == "bind")
+    {
+        format_ident!("late_bind")
+    } else {
+        format_ident!("bind")
+    };
+    let java_name = obj.java_name.as_str();
+    let methods = obj.methods.iter().filter(|f| f.late_bound).map(|f| {
+        let name = &f.name;
+        let signature = f.signature.as_str();
+        let is_static = f.is_static;
+        quote! { jaffi_support::reflect::LateBoundMethod::new(#java_name, #name, #signature, #is_static) }
+    });
+
+    Some(quote! {
+        /// Looks up the late-bound class and all its declared methods, the wrapper methods panic if they can't be bound
+        pub fn #bind(env: JNIEnv<'j>) -> Result<(), jaffi_support::reflect::BindingError> {
+            jaffi_support::reflect::bind(env, &#class_cache, &[#(#methods),*])
         }
-
-    }
+    })
 }
 
-/// Takes a set of exceptions to produce a type to represent the name
-fn exception_name_from_set(exceptions: &BTreeSet<JavaDesc>) -> Ident {
-    let mut name = String::new();
-    for ex in exceptions {
-        name.push_str(ex.class_name());
+/// The `_async` methods of the global wrapper of `obj`, calling the wrapper methods on a `jaffi_support::pool::JavaPool`
+///
+/// Only the methods without caught exceptions, and with arguments and results that are `Send`, get variants. Constructors and
+///   native factories return the global wrapper of the new object.
+fn generate_async_methods(obj: &Object, config: &FfiConfig) -> TokenStream {
+    if !config.async_wrappers {
+        return TokenStream::new();
     }
 
-    name.push_str("Err");
-
-    make_ident(&name)
-}
-
-fn generate_exceptions(exception_sets: HashSet<BTreeSet<JavaDesc>>) -> TokenStream {
-    let mut tokens = TokenStream::new();
+    let obj_name = obj.obj_name.no_lifetime();
+    let class_name = obj.class_name.no_lifetime();
+    let static_trait_name = obj.static_trait_name.no_lifetime();
+    // `local` is taken, and a Java method could already be named like the variant of another one
+    let mut names = HashSet::from([format_ident!("local")]);
 
-    // First generate all the Exception types that wrap the Java Exceptions
-    let exception_types = exception_sets
+    obj.methods
         .iter()
-        .flat_map(|s| s.iter())
-        .collect::<HashSet<_>>();
-    for exception in exception_types {
-        let ex_ident = make_ident(exception.class_name());
-        let ex_class_name = format!("{exception}");
-        let doc_str = 
-        format!("An opaque type that represents the exception object `{exception}` from Java");
-
-        tokens.extend(quote!{
-            #[doc = #doc_str]
-            #[derive(Copy, Clone)]
-            pub struct #ex_ident;
+        .filter(|f| {
+            let is_new = f.is_constructor || f.native_factory;
+            wrapper_exception(f, config).is_none()
+                && f.arguments.iter().all(|arg| arg.rs_ty.is_sendable())
+                && (is_new || f.rs_result.is_sendable())
+        })
+        .filter_map(|func| {
+            let async_name = make_ident(&format!("{}_async", func.rust_method_name.0 .0.to_snake_case()));
+            names.insert(async_name.clone()).then_some((func, async_name))
+        })
+        .map(|(func, async_name)| {
+            let name = &func.name;
+            let jni_sig = &func.signature;
+            let rust_method_name = func.rust_method_name.for_rust_ident();
+            let arg_names = func.arguments.iter().map(|arg| &arg.name).collect::<Vec<_>>();
+            let arg_types = func.arguments.iter().map(|arg| &arg.rs_ty);
+            let deprecated = method_attributes(func);
+            let rs_result = &func.rs_result;
 
-            impl jaffi_support::Throwable for #ex_ident {
-                #[track_caller]
-                fn throw<'j, S: Into<JNIString>>(&self, env: JNIEnv<'j>, msg: S) -> Result<(), JniError> {
-                    env.throw_new(#ex_class_name, msg)
+            if func.is_constructor || func.native_factory {
+                let java_doc = format!("Calls the constructor `{name}{jni_sig}` on a thread of the pool, returning the global reference to the new object");
+                quote! {
+                    #[doc = #java_doc]
+                    #deprecated
+                    pub fn #async_name(pool: &jaffi_support::pool::JavaPool, #(#arg_names: #arg_types),*) -> jaffi_support::pool::JavaCall<Self> {
+                        pool.call(move |env| {
+                            #obj_name::#rust_method_name(env, #(#arg_names),*)
+                                .register_global(env)
+                                .expect("error new_global_ref")
+                        })
+                    }
                 }
-
-                fn catch<'j>(env: JNIEnv<'j>, throwable: JThrowable<'j>) -> Result<Self, JThrowable<'j>> { 
-                    if !throwable.is_null() && env.is_instance_of(throwable, #ex_class_name).expect("could not check instance_of") {
-                        Ok(Self)
-                    } else {
-                        Err(throwable)
+            } else if func.is_static {
+                let java_doc = format!("Calls the static method `{name}{jni_sig}` on a thread of the pool");
+                quote! {
+                    #[doc = #java_doc]
+                    #deprecated
+                    pub fn #async_name(pool: &jaffi_support::pool::JavaPool, #(#arg_names: #arg_types),*) -> jaffi_support::pool::JavaCall<#rs_result> {
+                        pool.call(move |env| {
+                            // the static methods use the cached class, not the receiver
+                            let class = #class_name(JClass::from(JObject::null()));
+                            #static_trait_name::#rust_method_name(&class, env, #(#arg_names),*)
+                        })
                     }
                 }
-            }
-        });
-    }
-
-    // Now Generate the return type name for the combined exceptions
-    for exception_set in &exception_sets {
-        let exception = exception_name_from_set(exception_set);
-        // the enum variants
-        let ex_variants = exception_sets
-            .iter()
-            .flat_map(|s| s.iter())
-            .map(|d| make_ident(d.class_name()))
-            .map(|i| quote! { #i(#i)})
-            .collect::<Vec<_>>();
-        let ex_variant_names = exception_sets
-            .iter()
-            .flat_map(|s| s.iter())
-            .map(|d| make_ident(d.class_name()))
-            .map(|i| quote! { #i })
-            .collect::<Vec<_>>();
-
-        tokens.extend(quote!{
-            #[derive(Copy, Clone)]
-            pub enum #exception {
-                #(#ex_variants),*
-            }
-
-            impl jaffi_support::Throwable for #exception {
-                #[track_caller]
-                fn throw<'j, S: Into<JNIString>>(&self, env: JNIEnv<'j>, msg: S) -> Result<(), JniError> {
-                    match self {
-                        #(Self::#ex_variant_names(ex) => ex.throw(env, msg)),*
-                    }
-                }
-
-                fn catch<'j>(env: JNIEnv<'j>, throwable: JThrowable<'j>) -> Result<Self, JThrowable<'j>> { 
-                    const ALL_EXCEPTIONS: &[#exception]  = &[#(#exception::#ex_variants),*] as &[_];
-                    for exception in ALL_EXCEPTIONS {
-                        match exception {
-                            #(v @ Self::#ex_variant_names(_e) => {
-                                if let Ok(_e) = #ex_variant_names::catch(env, throwable) {
-                                    return Ok(*v);
-                                }
-                            })*
-                        }
+            } else {
+                let java_doc = format!("Calls `{name}{jni_sig}` on a thread of the pool, the call panics if the library has been unloaded");
+                quote! {
+                    #[doc = #java_doc]
+                    #deprecated
+                    pub fn #async_name(self: std::sync::Arc<Self>, pool: &jaffi_support::pool::JavaPool, #(#arg_names: #arg_types),*) -> jaffi_support::pool::JavaCall<#rs_result> {
+                        pool.call(move |env| {
+                            let obj = self
+                                .local(env)
+                                .expect("error new_local_ref")
+                                .expect("the library has been unloaded");
+                            obj.#rust_method_name(env, #(#arg_names),*)
+                        })
                     }
-
-                    Err(throwable)
                 }
             }
         })
-    }
-
-    tokens
+        .collect()
 }
 
-fn generate_class_ffi(class_ffi: &ClassFfi) -> TokenStream {
-    let trait_impl = make_ident(&class_ffi.trait_impl);
-    let trait_name = make_ident(&class_ffi.trait_name);
-    let doc_str = format!(
-        "Implement this with `super::{trait_impl}` to support native methods from `{}`",
-        class_ffi.class_name
-    );
+/// A method taking `&[u8]` for the `byte[]` arguments, which calls the wrapper method of `func` with new Java arrays
+fn generate_bytes_function(func: &Function, config: &FfiConfig) -> Option<TokenStream> {
+    let byte_array = RustTypeName::from("jaffi_support::arrays::JavaByteArray<'j>");
+    if !func.arguments.iter().any(|arg| arg.ty == byte_array) {
+        return None;
+    }
 
-    let trait_functions = class_ffi
-        .functions
-        .iter()
-        .map(|func| {
-            let name = &func.name;
-            let jni_sig = &func.signature;
-            let java_doc = format!("Implementation for the method `{name}{jni_sig}`");
-            let rust_method_name = func.rust_method_name.for_rust_ident();
-            let class_ffi_name = &func.class_ffi_name;
-            let object_ffi_name = &func.object_ffi_name;
-            let class_or_this = if func.is_static {
-                quote! { class: #class_ffi_name  }
-            } else {
-                quote! { this: #object_ffi_name  }
-            };
-            let arguments = func
-                .arguments
-                .iter()
-                .map(|arg| (&arg.name, &arg.rs_ty))
-                .map(|(name, rs_ty)| quote! { #name: #rs_ty })
-                .collect::<Vec<_>>();
-            let rs_result = &func.rs_result;
+    let name = &func.name;
+    let jni_sig = &func.signature;
+    let java_doc = format!("A wrapper for the java function `{name}{jni_sig}`, the `byte[]` arguments are copied from slices");
+    let rust_method_name = func.rust_method_name.for_rust_ident();
+    let bytes_method_name = format_ident!("{}_bytes", rust_method_name.to_string().trim_start_matches("r#"));
+    let add_pub = if !func.is_static {
+        quote! {pub}
+    } else {
+        quote! {}
+    };
+    let (amp_self, receiver) = if func.is_constructor {
+        (quote! {}, quote! { Self:: })
+    } else {
+        (quote! {&self,}, quote! { self. })
+    };
 
-            let rs_result = if !func.exceptions.is_empty() {
-                let exception_name = exception_name_from_set(&func.exceptions);
-                quote! { Result<#rs_result, jaffi_support::Error<#exception_name>> }
+    let arguments = func
+        .arguments
+        .iter()
+        .map(|arg| {
+            let name = &arg.name;
+            let rs_ty = &arg.rs_ty;
+            if arg.ty == byte_array {
+                quote! { #name: &[u8] }
             } else {
-                quote! { #rs_result }
-            };
-
+                quote! { #name: #rs_ty }
+            }
+        })
+        .collect::<Vec<_>>();
+    let to_arrays = func
+        .arguments
+        .iter()
+        .filter(|arg| arg.ty == byte_array)
+        .map(|arg| {
+            let name = &arg.name;
             quote! {
-                #[doc = #java_doc]
-                fn #rust_method_name(
-                    &self,
-                    #class_or_this,
-                    #(#arguments),*
-                ) -> #rs_result;
+                let #name = jaffi_support::arrays::JavaByteArray::new(env, #name)
+                    .expect("error creating the byte array");
             }
         })
         .collect::<TokenStream>();
+    let arg_names = func.arguments.iter().map(|arg| &arg.name);
 
-    let extern_functions = class_ffi
-        .functions
+    let rs_result_sig = wrapper_result(func, config);
+    let deprecated = method_attributes(func);
+
+    Some(quote! {
+        #[doc = #java_doc]
+        ///
+        /// # Arguments
+        ///
+        /// * `env` - this should be the same JNIEnv "owning" this object
+        #deprecated
+        #add_pub fn #bytes_method_name(
+            #amp_self
+            env: JNIEnv<'j>,
+            #(#arguments),*
+        ) -> #rs_result_sig {
+            #to_arrays
+
+            #receiver #rust_method_name(env, #(#arg_names),*)
+        }
+    })
+}
+
+/// A method collecting the varargs from an iterator into the Java array, which calls the wrapper method of `func`
+fn generate_varargs_function(func: &Function, element: &JniType, config: &FfiConfig) -> TokenStream {
+    let name = &func.name;
+    let jni_sig = &func.signature;
+    let java_doc = format!("A wrapper for the java function `{name}{jni_sig}`, the varargs are collected into the Java array");
+    let rust_method_name = func.rust_method_name.for_rust_ident();
+    let varargs_method_name = format_ident!("{}_varargs", rust_method_name.to_string().trim_start_matches("r#"));
+    let add_pub = if !func.is_static {
+        quote! {pub}
+    } else {
+        quote! {}
+    };
+    let (amp_self, receiver) = if func.is_constructor {
+        (quote! {}, quote! { Self:: })
+    } else {
+        (quote! {&self,}, quote! { self. })
+    };
+
+    let (fixed, last) = func.arguments.split_at(func.arguments.len() - 1);
+    let fixed_args = fixed
         .iter()
-        .map(|func| {
-            let signature = &func.signature.0;
-            let object_name = &func.object_java_desc;
-            let name = &func.name;
-            let fn_doc = format!("Java native `{object_name}.{name}{signature}`.");
-            let fn_export_ffi_name = make_ident(&func.fn_export_ffi_name.0 .0);
-            let class_ffi_name = &func.class_ffi_name;
-            let object_ffi_name = &func.object_ffi_name;
-            let class_or_this = if func.is_static {
-                quote! { class: #class_ffi_name  }
-            } else {
-                quote! { this: #object_ffi_name  }
-            };
-            let arguments = func
-                .arguments
-                .iter()
-                .map(|arg| (&arg.name, &arg.ty))
-                .map(|(name, ty)| quote! { #name: #ty })
-                .collect::<Vec<_>>();
-            let result = &func.result;
-            let args_to_rust = func
-                .arguments
-                .iter()
-                .map(|arg| (&arg.name, &arg.rs_ty))
-                .map(|(name, rs_ty)| {
-                    quote! {
-                        let #name = <#rs_ty>::java_to_rust(#name, env);
-                    }
-                })
-                .collect::<Vec<_>>();
-            let rust_method_name = func.rust_method_name.for_rust_ident();
-            let call_class_or_this = if func.is_static {
-                format_ident!("class")
+        .map(|arg| {
+            let name = &arg.name;
+            let rs_ty = &arg.rs_ty;
+            quote! { #name: #rs_ty }
+        })
+        .collect::<Vec<_>>();
+    let fixed_names = fixed.iter().map(|arg| &arg.name).collect::<Vec<_>>();
+    let array_ty = &last[0].rs_ty;
+
+    let element_ty = element.to_jni_type_name();
+    let element_rs_ty = element.to_rs_type_name();
+    // `JObject` derefs to the raw pointer, so `Object...` elements are passed as they are
+    let new_array = match element {
+        JniType::Ty(BaseJniTy::Jobject(obj)) => {
+            let class = obj.as_descriptor().0;
+            let to_object = if *obj == ObjectType::JObject {
+                quote! { varargs.into_iter().collect() }
             } else {
-                format_ident!("this")
-            };
-            let args_call = func
-                .arguments
-                .iter()
-                .map(|arg| &arg.name)
-                .map(|name| quote! {#name})
-                .collect::<Vec<_>>();
-
-            let handle_err = if !func.exceptions.is_empty() {
                 quote! {
-                    let result = match result {
-                        Err(e) => {
-                            e.throw(env).expect("failed to throw exception");
-                            return NullObject::null();
-                        }
-                        Ok(r) => r,
-                    };
+                    varargs
+                        .into_iter()
+                        .map(|element| {
+                            <#element_rs_ty as IntoJavaValue<'j, #element_ty>>::into_java_value(element, env)
+                                .l()
+                                .expect("varargs element is not an object")
+                        })
+                        .collect()
                 }
-            } else {
-                quote! {}
             };
 
-            quote! {
-                #[doc = #fn_doc]
-                ///
-                /// This will be linked into the Java Object at runtime via the `ld_library_path` rules in Java.
-                #[no_mangle]
-                #[allow(improper_ctypes_definitions)]
-                pub extern "system" fn #fn_export_ffi_name<'j>(
-                    env: JNIEnv<'j>,
-                    #class_or_this,
-                    #(#arguments),*
-                ) -> #result {
-                    let myself = #trait_impl::from_env(env);
+            quote! { jaffi_support::arrays::new_object_array(env, #class, #to_object) }
+        }
+        _ => quote! {
+            <#element_rs_ty as jaffi_support::arrays::VarargsElement>::new_array(
+                env,
+                &varargs.into_iter().collect::<Vec<_>>(),
+            )
+        },
+    };
 
-                    #(#args_to_rust)*
+    let rs_result_sig = wrapper_result(func, config);
+    let deprecated = method_attributes(func);
 
-                    exceptions::catch_panic_and_throw(env, || {
-                        let result = myself.#rust_method_name (
-                            #call_class_or_this,
-                            #(#args_call),*
-                        );
+    // the arrays are built with the `array_helpers` of jaffi_support, which may be built without them
+    quote! {
+        jaffi_support::array_helpers! {
+            #[doc = #java_doc]
+            ///
+            /// # Arguments
+            ///
+            /// * `env` - this should be the same JNIEnv "owning" this object
+            #deprecated
+            #add_pub fn #varargs_method_name(
+                #amp_self
+                env: JNIEnv<'j>,
+                #(#fixed_args,)*
+                varargs: impl IntoIterator<Item = #element_rs_ty>,
+            ) -> #rs_result_sig {
+                let varargs = #new_array.expect("error creating the varargs array");
+
+                #receiver #rust_method_name(env, #(#fixed_names,)* <#array_ty>::from(varargs))
+            }
+        }
+    }
+}
 
-                        #handle_err
+/// Calls the method by a cached method id, without checking the arguments against the signature, see `cached_method_ids`
+fn cached_method_call(func: &Function, scratch: bool) -> TokenStream {
+    let object_java_desc = &func.object_java_desc.0;
+    let signature = func.signature.as_str();
+    let name = &func.name;
+    let ret = return_java_type(func.signature.types().1);
+    let class_cache = class_cache_ident(object_java_desc);
+    let (new_object, call_static, call_method) = unchecked_calls(scratch, &ret);
 
-                        <#result>::rust_to_java(result, env)
+    if func.is_constructor {
+        quote! {
+            {
+                static METHOD_ID: jaffi_support::cache::MethodIdCache =
+                    jaffi_support::cache::MethodIdCache::new(#object_java_desc, "<init>", #signature);
+
+                METHOD_ID
+                    .get_in(env, &#class_cache)
+                    .and_then(|method_id| {
+                        let class = #class_cache.get(env)?;
+                        #new_object
                     })
-                }
+                    .map(JValue::from)
             }
-        })
-        .collect::<TokenStream>();
+        }
+    } else if func.is_static {
+        quote! {
+            {
+                static METHOD_ID: jaffi_support::cache::StaticMethodIdCache =
+                    jaffi_support::cache::StaticMethodIdCache::new(#object_java_desc, #name, #signature);
 
-    // let exception_sets = class_ffi.functions.iter().map(|f| &f.exceptions).collect::<HashSet<_>>().into_iter().map(exception_name_from_set).map(|i| quote!{ #i }).collect::<Vec<_>>();
-    // let trait_exception_type = if !exception_sets.is_empty() {
-    //     quote!{
-    //         type Error: #(Into<#exception_sets>)+*;
-    //     }
-    // } else {
-    //     quote!{}
-    // };
+                METHOD_ID.get_in(env, &#class_cache).and_then(|method_id| {
+                    let class = #class_cache.get(env)?;
+                    #call_static
+                })
+            }
+        }
+    } else {
+        quote! {
+            {
+                static METHOD_ID: jaffi_support::cache::MethodIdCache =
+                    jaffi_support::cache::MethodIdCache::new(#object_java_desc, #name, #signature);
 
-    quote! {
-        // This is the trait developers must implement
-        use super::#trait_impl;
+                METHOD_ID
+                    .get_in(env, &#class_cache)
+                    .and_then(|method_id| #call_method)
+            }
+        }
+    }
+}
 
-        #[doc = #doc_str]
-        pub trait #trait_name<'j> {
-            //#trait_exception_type
+/// The call of a method of the `late_bound_classes`, looked up by name and signature on first use, and then cached
+///
+/// The lookup panics if the class or method isn't available, `bind` checks them beforehand.
+fn late_bound_method_call(func: &Function, scratch: bool) -> TokenStream {
+    let object_java_desc = &func.object_java_desc.0;
+    let signature = func.signature.as_str();
+    let name = &func.name;
+    let is_static = func.is_static;
+    let ret = return_java_type(func.signature.types().1);
+    let class_cache = class_cache_ident(object_java_desc);
+    let (new_object, call_static, call_method) = unchecked_calls(scratch, &ret);
+    let method = quote! {
+        static METHOD: jaffi_support::reflect::LateBoundMethod =
+            jaffi_support::reflect::LateBoundMethod::new(#object_java_desc, #name, #signature, #is_static);
+    };
+
+    if func.is_constructor {
+        quote! {
+            {
+                #method
+                let method_id = METHOD.get_in(env, &#class_cache).unwrap_or_else(|e| panic!("{e}"));
+                #class_cache
+                    .get(env)
+                    .and_then(|class| #new_object)
+                    .map(JValue::from)
+            }
+        }
+    } else if func.is_static {
+        quote! {
+            {
+                #method
+                let method_id = METHOD.get_static_in(env, &#class_cache).unwrap_or_else(|e| panic!("{e}"));
+                #class_cache
+                    .get(env)
+                    .and_then(|class| #call_static)
+            }
+        }
+    } else {
+        quote! {
+            {
+                #method
+                let method_id = METHOD.get_in(env, &#class_cache).unwrap_or_else(|e| panic!("{e}"));
+                #call_method
+            }
+        }
+    }
+}
+
+/// The calls of `new_object_unchecked`, `call_static_method_unchecked` and `call_method_unchecked`, by the cached method id
+///
+/// With `scratch_buffers` these are the functions of `jaffi_support::scratch`, passing the arguments in a pooled `jvalue` array.
+///   They are unsafe, the wrappers are safe as the ids and arguments are generated from the descriptor of the method.
+fn unchecked_calls(scratch: bool, ret: &TokenStream) -> (TokenStream, TokenStream, TokenStream) {
+    if scratch {
+        // Safety: the method id is looked up by the name and descriptor of the method in the class of the wrapper, and the
+        //   arguments and return type are converted to the types of the descriptor
+        (
+            quote! { unsafe { jaffi_support::scratch::new_object_unchecked(env, class, method_id, args) } },
+            quote! { unsafe { jaffi_support::scratch::call_static_method_unchecked(env, class, method_id, #ret, args) } },
+            quote! { unsafe { jaffi_support::scratch::call_method_unchecked(env, self.0, method_id, #ret, args) } },
+        )
+    } else {
+        (
+            quote! { env.new_object_unchecked(class, method_id, args) },
+            quote! { env.call_static_method_unchecked(class, method_id, #ret, args) },
+            quote! { env.call_method_unchecked(self.0, method_id, #ret, args) },
+        )
+    }
+}
+
+/// Compile time assertions that the types of the extern functions have the layout of the JNI types in the descriptors,
+///   the wrappers rely on `#[repr(transparent)]` to be passed by value across the FFI boundary
+fn generate_abi_assertions(class_ffi: &ClassFfi) -> TokenStream {
+    fn sys_type(ty: &JavaType) -> Ident {
+        match ty {
+            JavaType::Boolean => format_ident!("jboolean"),
+            JavaType::Byte => format_ident!("jbyte"),
+            JavaType::Char => format_ident!("jchar"),
+            JavaType::Short => format_ident!("jshort"),
+            JavaType::Int => format_ident!("jint"),
+            JavaType::Long => format_ident!("jlong"),
+            JavaType::Float => format_ident!("jfloat"),
+            JavaType::Double => format_ident!("jdouble"),
+            JavaType::Object(_) | JavaType::Array(_) => format_ident!("jobject"),
+        }
+    }
+
+    // keyed by the rendered type, each type is only asserted once
+    let mut types = BTreeMap::<String, (TokenStream, Ident)>::new();
+    let mut add = |ty: &RustTypeName, sys: Ident| {
+        // there's no `'j` in a const context
+        let ty = rename_lifetime(ty.to_token_stream(), &format_ident!("static"));
+        types.entry(ty.to_string()).or_insert((ty, sys));
+    };
+
+    for func in &class_ffi.functions {
+        let (parameters, result) = func.signature.types();
+
+        if func.is_static {
+            add(&func.class_ffi_name, format_ident!("jclass"));
+        } else {
+            add(&func.object_ffi_name, format_ident!("jobject"));
+        }
+        for (arg, parameter) in func.arguments.iter().zip(parameters) {
+            add(&arg.ty, sys_type(parameter));
+        }
+        if let Some(result) = result {
+            add(&func.result, sys_type(result));
+        }
+    }
+
+    let assertions = types.into_values().map(|(ty, sys)| {
+        quote! {
+            assert!(std::mem::size_of::<#ty>() == std::mem::size_of::<jni::sys::#sys>());
+            assert!(std::mem::align_of::<#ty>() == std::mem::align_of::<jni::sys::#sys>());
+        }
+    });
+
+    quote! {
+        const _: () = {
+            #(#assertions)*
+        };
+    }
+}
+
+/// The `JavaType` of the result of the method, which selects the `Call*MethodA` function
+fn return_java_type(result: Option<&JavaType>) -> TokenStream {
+    let primitive = match result {
+        Some(JavaType::Boolean) => quote! { Boolean },
+        Some(JavaType::Byte) => quote! { Byte },
+        Some(JavaType::Char) => quote! { Char },
+        Some(JavaType::Short) => quote! { Short },
+        Some(JavaType::Int) => quote! { Int },
+        Some(JavaType::Long) => quote! { Long },
+        Some(JavaType::Float) => quote! { Float },
+        Some(JavaType::Double) => quote! { Double },
+        None => quote! { Void },
+        // the class of objects and arrays isn't used by the unchecked calls, an empty name doesn't allocate
+        Some(_) => return quote! { jni::signature::JavaType::Object(String::new()) },
+    };
+
+    quote! { jni::signature::JavaType::Primitive(jni::signature::Primitive::#primitive) }
+}
+
+/// The result of a wrapper that threw an `IllegalArgumentException` for an argument, instead of calling Java
+///
+/// It's the default of the Java result, `0`, `false` or `null`, converted like a result of the method. Strings are empty, as
+///   `null` isn't converted to them.
+fn thrown_default(func: &Function) -> TokenStream {
+    let jvalue = match func.signature.types().1 {
+        // constructors return the new object
+        _ if func.is_constructor => quote! { JValue::Object(JObject::null()) },
+        Some(JavaType::Boolean) => quote! { JValue::Bool(0) },
+        Some(JavaType::Byte) => quote! { JValue::Byte(0) },
+        Some(JavaType::Char) => quote! { JValue::Char(0) },
+        Some(JavaType::Short) => quote! { JValue::Short(0) },
+        Some(JavaType::Int) => quote! { JValue::Int(0) },
+        Some(JavaType::Long) => quote! { JValue::Long(0) },
+        Some(JavaType::Float) => quote! { JValue::Float(0.0) },
+        Some(JavaType::Double) => quote! { JValue::Double(0.0) },
+        None => quote! { JValue::Void },
+        Some(_) if func.rs_result.is_string() => return quote! { String::new() },
+        Some(_) => quote! { JValue::Object(JObject::null()) },
+    };
+    let result = &func.result;
+    let rs_result = &func.rs_result;
+
+    quote! { jaffi_support::from_jvalue_checked::<#result, #rs_result>(env, #jvalue) }
+}
+
+/// The `static` `ClassCache` of the class, e.g. `NET_BLUEJEKYLL_NATIVE_STRINGS_CLASS` for `net/bluejekyll/NativeStrings`
+fn class_cache_ident(java_desc: &str) -> Ident {
+    let name = escape_non_ascii(java_desc).replace(['/', '$'], "_").to_shouty_snake_case();
+    format_ident!("{name}_CLASS")
+}
+
+fn generate_struct(
+    obj: &Object,
+    enum_types: &HashSet<RustTypeName>,
+    config: &FfiConfig,
+) -> TokenStream {
+    let class_name = &obj.class_name;
+    let static_java_doc = format!(
+        "Wrapper for the static methods of Java class `{}`",
+        obj.java_name
+    );
+    let obj_name = &obj.obj_name;
+    let java_doc = format!(
+        "Wrapper for the public methods of Java class `{}`",
+        obj.java_name
+    );
+    let global_name = obj.obj_name.no_lifetime().append("Global");
+    let global_doc = format!(
+        "A global reference to an instance of Java class `{}`, see `{}::register_global`",
+        obj.java_name,
+        obj.obj_name.no_lifetime()
+    );
+    let static_trait_name = &obj.static_trait_name;
+    let java_name = obj.java_name.as_str();
+    let class_cache = class_cache_ident(java_name);
+    let class_cache_doc = format!("The cached class `{java_name}`, loaded in `JNI_OnLoad`");
+    let deprecated = deprecated_attribute(&obj.deprecated);
+
+    let interfaces = obj
+        .interfaces
+        .iter()
+        .map(|interface| {
+            let interface = interface.no_lifetime();
+            let as_interface = format_ident!("as_{}", interface.to_string().to_snake_case());
+
+            quote! {
+                pub fn #as_interface(&self) -> #interface<'j> {
+                    #interface(self.0)
+                }
+            }
+        })
+        .collect::<TokenStream>();
+
+    let methods = obj
+        .methods
+        .iter()
+        .filter(|f| !f.is_static || f.native_factory)
+        .map(|f| generate_function(f, enum_types, config))
+        .collect::<TokenStream>();
+    let static_methods = obj
+        .methods
+        .iter()
+        .filter(|f| f.is_static && !f.native_factory)
+        .map(|f| generate_function(f, enum_types, config))
+        .collect::<TokenStream>();
+    let java_enum = generate_enum(obj);
+
+    // the descriptors used by the wrapper methods, for mixing in calls with jni directly
+    let descriptors = obj
+        .methods
+        .iter()
+        .map(|f| {
+            let rust_name = f.rust_method_name.0 .0.to_shouty_snake_case();
+            let name_const = format_ident!("{rust_name}_NAME");
+            let sig_const = format_ident!("{rust_name}_SIG");
+            let name = &f.name;
+            let signature = f.signature.as_str();
+            let name_doc = format!("The name of the Java method wrapped by `{}`", f.rust_method_name.for_rust_ident());
+            let sig_doc = format!("The signature of the Java method wrapped by `{}`", f.rust_method_name.for_rust_ident());
+
+            quote! {
+                #[doc = #name_doc]
+                pub const #name_const: &'static str = #name;
+                #[doc = #sig_doc]
+                pub const #sig_const: &'static str = #signature;
+            }
+        })
+        .collect::<TokenStream>();
+
+    // a Java method named `lock`, e.g. on `java.util.concurrent.locks.Lock`, takes precedence
+    let lock_name = if obj
+        .methods
+        .iter()
+        .any(|f| !f.is_static && f.rust_method_name.for_rust_ident() == "lock")
+    {
+        format_ident!("monitor_lock")
+    } else {
+        format_ident!("lock")
+    };
+
+    let identity_methods = config.identity_methods.then(|| {
+        // Java methods with the same names take precedence, like for `lock`
+        let method_name = |name: &str| {
+            if obj
+                .methods
+                .iter()
+                .any(|f| f.rust_method_name.for_rust_ident() == name)
+            {
+                format_ident!("object_{name}")
+            } else {
+                format_ident!("{name}")
+            }
+        };
+        let is_same_object = method_name("is_same_object");
+        let identity_hash_code = method_name("identity_hash_code");
+        let identity = method_name("identity");
+
+        quote! {
+            /// `true` if `other` references the same object, like `==` in Java, the references themselves may differ
+            pub fn #is_same_object(&self, env: JNIEnv<'j>, other: &Self) -> Result<bool, JniError> {
+                jaffi_support::identity::is_same_object(env, self.0, other.0)
+            }
+
+            /// The `System.identityHashCode` of the object, `0` for `null`
+            pub fn #identity_hash_code(&self, env: JNIEnv<'j>) -> Result<i32, JniError> {
+                jaffi_support::identity::identity_hash_code(env, self.0)
+            }
+
+            /// This object compared and hashed by its identity, e.g. to deduplicate objects in a `HashSet`
+            pub fn #identity(&self, env: JNIEnv<'j>) -> Result<jaffi_support::identity::Identity<'j, Self>, JniError> {
+                jaffi_support::identity::Identity::new(env, *self)
+            }
+        }
+    });
+
+    let listener = obj.listener.as_ref().map(generate_listener);
+    let late_bind = generate_late_bind(obj, &class_cache);
+    let async_methods = generate_async_methods(obj, config);
+
+    let buffer_views = obj.buffer.as_ref().map(|buffer| {
+        let field = buffer.field.as_str();
+        let rust_struct = &buffer.rust_struct;
+        let view_doc = format!("Views the `ByteBuffer` field `{field}` as `{rust_struct}`, without copying, see `jaffi_support::buffer::view`");
+        let view_mut_doc = format!("Views the `ByteBuffer` field `{field}` as a mutable `{rust_struct}`, see `jaffi_support::buffer::view_mut`");
+
+        // the field id is looked up once, the views are taken in hot loops
+        let get_buffer = quote! {
+            static FIELD_ID: jaffi_support::cache::FieldIdCache =
+                jaffi_support::cache::FieldIdCache::new(#java_name, #field, "Ljava/nio/ByteBuffer;");
+
+            let field_id = FIELD_ID.get_in(env, &#class_cache)?;
+            let buffer = env
+                .get_field_unchecked(self.0, field_id, jni::signature::JavaType::Object("java/nio/ByteBuffer".to_string()))?
+                .l()?;
+        };
+
+        quote! {
+            #[doc = #view_doc]
+            ///
+            /// # Safety
+            ///
+            /// Neither a mutable view of the buffer nor Java may write to the buffer while the view is in use.
+            pub unsafe fn buffer_view(&self, env: JNIEnv<'j>) -> Result<jaffi_support::buffer::BufferView<'j, #rust_struct>, jaffi_support::buffer::BufferError> {
+                #get_buffer
+                jaffi_support::buffer::view(env, buffer.into())
+            }
+
+            #[doc = #view_mut_doc]
+            ///
+            /// # Safety
+            ///
+            /// There must not be other views of the buffer while the mutable view is in use, and Java may not access the buffer in that time.
+            pub unsafe fn buffer_view_mut(&self, env: JNIEnv<'j>) -> Result<jaffi_support::buffer::BufferViewMut<'j, #rust_struct>, jaffi_support::buffer::BufferError> {
+                #get_buffer
+                jaffi_support::buffer::view_mut(env, buffer.into())
+            }
+        }
+    });
+
+    let bridge = obj.bridge.as_ref().map(|rust_ty| {
+        quote! {
+            impl<'j> FromJavaToRust<'j, #obj_name> for #rust_ty {
+                fn java_to_rust(java: #obj_name, env: JNIEnv<'j>) -> Self {
+                    <#rust_ty as jaffi_support::JavaBridge<'j, #obj_name>>::from_java(env, java)
+                }
+            }
+
+            impl<'j> FromRustToJava<'j, #rust_ty> for #obj_name {
+                fn rust_to_java(rust: #rust_ty, env: JNIEnv<'j>) -> Self {
+                    <#rust_ty as jaffi_support::JavaBridge<'j, #obj_name>>::to_java(rust, env)
+                }
+            }
+        }
+    });
+
+    let constants = obj
+        .constants
+        .iter()
+        .map(|constant| {
+            let name = make_ident(&constant.name);
+            let (ty, value) = constant.value.to_type_and_value();
+            let doc = format!("The constant `{}.{}`", obj.java_name, constant.name);
+
+            quote! {
+                #[doc = #doc]
+                #[allow(non_upper_case_globals)]
+                pub const #name: #ty = #value;
+            }
+        })
+        .collect::<TokenStream>();
+
+    // `GetModule` is only in the function table of JVMs which support `JNI_VERSION_9`
+    let java_module = config.jni_version.has_modules().then(|| {
+        quote! {
+            /// Returns the `java.lang.Module` of the class
+            pub fn java_module(env: JNIEnv<'j>) -> Result<JObject<'j>, JniError> {
+                // Safety: `JNI_OnLoad` requested a version with `GetModule`
+                unsafe { #class_cache.module(env) }
+            }
+        }
+    });
+
+    quote! {
+        #[doc = #class_cache_doc]
+        pub static #class_cache: jaffi_support::cache::ClassCache =
+            jaffi_support::cache::ClassCache::new(#java_name);
+
+        #[doc = #static_java_doc]
+        #deprecated
+        #[derive(Clone, Copy, Debug)]
+        #[repr(transparent)]
+        pub struct #class_name (JClass<'j>);
+
+        impl<'j> #static_trait_name for #class_name {}
+
+        impl<'j> #class_name {
+            fn java_class_desc() -> &'static str {
+                #java_name
+            }
+
+            #java_module
+
+            #constants
+        }
+
+        impl<'j> std::ops::Deref for #class_name  {
+            type Target = JClass<'j>;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl<'j> FromJavaToRust<'j, #class_name> for #class_name {
+            fn java_to_rust(java: #class_name, _env: JNIEnv<'j>) -> Self {
+                java
+            }
+        }
+
+        impl<'j> FromRustToJava<'j, #class_name> for #class_name {
+            fn rust_to_java(rust: #class_name, _env: JNIEnv<'j>) -> Self {
+                rust
+            }
+        }
+
+        #[doc = #java_doc]
+        ///
+        /// This is a local reference, only valid on the current thread until the native call returns, it is neither `Send` nor `Sync`.
+        ///   Use `register_global` to hold onto the object or to share it with other threads.
+        #deprecated
+        #[derive(Clone, Copy, Debug)]
+        #[repr(transparent)]
+        pub struct #obj_name(JObject<'j>);
+
+        impl<'j> #static_trait_name for #obj_name {}
+
+        impl<'j> #obj_name {
+            /// The type name in java, e.g. `Object` is `"java/lang/Object"`
+            pub const JAVA_CLASS_DESC: &'static str = #java_name;
+
+            #descriptors
+
+            /// Returns the type name in java, e.g. `Object` is `"java/lang/Object"`
+            pub fn java_class_desc() -> &'static str {
+                Self::JAVA_CLASS_DESC
+            }
+
+            /// Creates a global reference to this object, it will be released when the library is unloaded if not dropped earlier
+            pub fn register_global(&self, env: JNIEnv<'j>) -> Result<#global_name, JniError> {
+                jaffi_support::global::RegisteredGlobal::new(env, self.0).map(#global_name)
+            }
+
+            /// Enters the monitor of this object, like a `synchronized` block in Java, the monitor is exited when the guard is dropped
+            pub fn #lock_name(&self, env: JNIEnv<'j>) -> Result<jaffi_support::monitor::MonitorGuard<'j>, JniError> {
+                jaffi_support::monitor::lock(env, self.0)
+            }
+
+            #identity_methods
+
+            #late_bind
+
+            #listener
+
+            #interfaces
+
+            #buffer_views
+
+            #methods
+        }
+
+        pub trait #static_trait_name {
+            #static_methods
+        }
+
+        impl<'j> std::ops::Deref for #obj_name {
+            type Target = JObject<'j>;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl<'j> From<#obj_name> for JObject<'j> {
+            fn from(obj: #obj_name) -> Self {
+                obj.0
+            }
+        }
+
+        impl<'j> From<JObject<'j>> for #obj_name {
+            fn from(obj: JObject<'j>) -> Self {
+                Self(obj)
+            }
+        }
+
+        impl<'j> FromJavaToRust<'j, #obj_name> for #obj_name {
+            fn java_to_rust(java: #obj_name, _env: JNIEnv<'j>) -> Self  {
+                java
+            }
+        }
+
+        impl<'j> jaffi_support::arrays::ArrayElement<'j> for #obj_name {
+            fn class_cache() -> &'static jaffi_support::cache::ClassCache {
+                &#class_cache
+            }
+        }
+
+        #[doc = #global_doc]
+        ///
+        /// This is `Send` and `Sync`, use `local` to get a wrapper for calls on the current thread.
+        #deprecated
+        #[derive(Debug)]
+        pub struct #global_name(jaffi_support::global::RegisteredGlobal);
+
+        impl #global_name {
+            /// Returns a new local reference to the object, `None` if the library has been unloaded
+            pub fn local<'j>(&self, env: JNIEnv<'j>) -> Result<Option<#obj_name>, JniError> {
+                self.0.local(env).map(|obj| obj.map(<#obj_name>::from))
+            }
+
+            #async_methods
+        }
+
+        impl From<#global_name> for jaffi_support::global::RegisteredGlobal {
+            fn from(global: #global_name) -> Self {
+                global.0
+            }
+        }
+
+        impl<'j> FromRustToJava<'j, #obj_name> for #obj_name {
+            fn rust_to_java(rust: #obj_name, _env: JNIEnv<'j>) -> Self {
+                rust
+            }
+        }
+
+        #bridge
+
+        #java_enum
+    }
+}
+
+/// Generates the Rust enum and the conversions for Java enum classes, nothing is generated for other classes
+fn generate_enum(obj: &Object) -> TokenStream {
+    if obj.enum_constants.is_empty() {
+        return TokenStream::new();
+    }
+
+    let obj_name = &obj.obj_name;
+    let enum_name = obj.obj_name.no_lifetime().append("Enum");
+    let java_name = obj.java_name.as_str();
+    let enum_doc = format!(
+        "The constants of the Java enum `{java_name}`, variants are in the same order as the Java ordinals"
+    );
+    let value_of_sig = format!("(Ljava/lang/String;)L{java_name};");
+    let constant_sig = format!("L{java_name};");
+    let class_cache = class_cache_ident(java_name);
+
+    let variants = obj
+        .enum_constants
+        .iter()
+        .map(|c| make_ident(&c.to_upper_camel_case()))
+        .collect::<Vec<_>>();
+    let names = obj.enum_constants.iter().collect::<Vec<_>>();
+    let ordinals = (0..obj.enum_constants.len() as i32).collect::<Vec<_>>();
+    let len = obj.enum_constants.len();
+
+    quote! {
+        #[doc = #enum_doc]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub enum #enum_name {
+            #(#variants),*
+        }
+
+        impl #enum_name {
+            /// The name of the constant in Java
+            pub const fn name(&self) -> &'static str {
+                match self {
+                    #(Self::#variants => #names),*
+                }
+            }
+
+            /// The ordinal of the constant in Java
+            pub const fn ordinal(&self) -> i32 {
+                match self {
+                    #(Self::#variants => #ordinals),*
+                }
+            }
+
+            /// Returns the variant for the Java constant name
+            pub fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    #(#names => Some(Self::#variants),)*
+                    _ => None,
+                }
+            }
+
+            /// Returns the variant for the Java ordinal
+            pub const fn from_ordinal(ordinal: i32) -> Option<Self> {
+                match ordinal {
+                    #(#ordinals => Some(Self::#variants),)*
+                    _ => None,
+                }
+            }
+        }
+
+        impl<'j> #obj_name {
+            /// Returns the Java enum constant of the Rust enum, read from its cached static field
+            pub fn from_rust_enum(env: JNIEnv<'j>, rust: #enum_name) -> Result<Self, JniError> {
+                // in the order of the ordinals
+                static CONSTANTS: [jaffi_support::cache::StaticFieldIdCache; #len] = [
+                    #(jaffi_support::cache::StaticFieldIdCache::new(#java_name, #names, #constant_sig)),*
+                ];
+
+                let field_id = CONSTANTS[rust.ordinal() as usize].get_in(env, &#class_cache)?;
+                let class = #class_cache.get(env)?;
+                let constant = env.get_static_field_unchecked(class, field_id, jni::signature::JavaType::Object(String::new()));
+                env.delete_local_ref(*class)?;
+                constant?.l().map(Self)
+            }
+
+            /// Returns the Java enum constant for the name, via a cached `valueOf` lookup
+            pub fn from_enum_name(env: JNIEnv<'j>, name: &str) -> Result<Self, JniError> {
+                static VALUE_OF: jaffi_support::cache::StaticMethodIdCache =
+                    jaffi_support::cache::StaticMethodIdCache::new(#java_name, "valueOf", #value_of_sig);
+
+                let method_id = VALUE_OF.get_in(env, &#class_cache)?;
+                let name = env.new_string(name)?;
+                let class = #class_cache.get(env)?;
+
+                let constant = env.call_static_method_unchecked(
+                    class,
+                    method_id,
+                    jni::signature::JavaType::Object(#java_name.to_string()),
+                    &[JValue::Object(name.into())],
+                );
+                env.delete_local_ref(*class)?;
+                env.delete_local_ref(*name)?;
+                constant?.l().map(Self)
+            }
+
+            /// The `name()` of the Java enum constant
+            pub fn enum_name(&self, env: JNIEnv<'j>) -> Result<String, JniError> {
+                static NAME: jaffi_support::cache::MethodIdCache =
+                    jaffi_support::cache::MethodIdCache::new("java/lang/Enum", "name", "()Ljava/lang/String;");
+
+                let method_id = NAME.get(env)?;
+                let name = env.call_method_unchecked(
+                    self.0,
+                    method_id,
+                    jni::signature::JavaType::Object("java/lang/String".to_string()),
+                    &[],
+                )?;
+
+                <String as jaffi_support::TryFromJavaValue<jni::objects::JString<'j>>>::try_from_jvalue(env, name)
+            }
+
+            /// The `ordinal()` of the Java enum constant
+            pub fn enum_ordinal(&self, env: JNIEnv<'j>) -> Result<i32, JniError> {
+                static ORDINAL: jaffi_support::cache::MethodIdCache =
+                    jaffi_support::cache::MethodIdCache::new("java/lang/Enum", "ordinal", "()I");
+
+                let method_id = ORDINAL.get(env)?;
+                env.call_method_unchecked(
+                    self.0,
+                    method_id,
+                    jni::signature::JavaType::Primitive(jni::signature::Primitive::Int),
+                    &[],
+                )?
+                .i()
+            }
+
+            /// Converts to the Rust enum by way of the ordinal, `None` if this is `null` or an unknown constant
+            pub fn rust_enum(&self, env: JNIEnv<'j>) -> Result<Option<#enum_name>, JniError> {
+                if self.0.is_null() {
+                    return Ok(None);
+                }
+
+                self.enum_ordinal(env).map(#enum_name::from_ordinal)
+            }
+        }
+
+        impl<'j> jaffi_support::TryIntoJavaValue<'j, #obj_name> for #enum_name {
+            fn try_into_java_value(self, env: JNIEnv<'j>) -> Result<JValue<'j>, JniError> {
+                <#obj_name>::from_rust_enum(env, self).map(|constant| JValue::Object(constant.0))
+            }
+        }
+
+        /// `valueOf` throws an `IllegalArgumentException` for a name that isn't a constant, which is returned as the error
+        impl<'j, 's> jaffi_support::TryIntoJavaValue<'j, #obj_name> for &'s str {
+            fn try_into_java_value(self, env: JNIEnv<'j>) -> Result<JValue<'j>, JniError> {
+                <#obj_name>::from_enum_name(env, self).map(|constant| JValue::Object(constant.0))
+            }
+        }
+    }
+}
+
+/// Takes a set of exceptions to produce a type to represent the name
+fn exception_name_from_set(exceptions: &BTreeSet<JavaDesc>) -> Ident {
+    let mut name = String::new();
+    for ex in exceptions {
+        name.push_str(ex.class_name());
+    }
+
+    name.push_str("Err");
+
+    make_ident(&name)
+}
+
+/// The type used to represent the exception, the JDK exceptions from `jaffi_support` are used where available
+fn exception_type(exception: &JavaDesc) -> TokenStream {
+    if let Some(name) = jaffi_support::exceptions::jdk_exception_name(exception.as_str()) {
+        let name = format_ident!("{name}");
+        quote! { jaffi_support::exceptions::#name }
+    } else {
+        let name = make_ident(exception.class_name());
+        quote! { #name }
+    }
+}
+
+fn generate_exceptions(exception_sets: HashSet<BTreeSet<JavaDesc>>) -> TokenStream {
+    let mut tokens = TokenStream::new();
+
+    // sorted, so the generated file is reproducible
+    let exception_sets = exception_sets.into_iter().collect::<BTreeSet<_>>();
+
+    // First generate all the Exception types that wrap the Java Exceptions
+    let exception_types = exception_sets
+        .iter()
+        .flat_map(|s| s.iter())
+        .collect::<BTreeSet<_>>();
+    for exception in exception_types {
+        let ex_type = exception_type(exception);
+        let ex_object = ObjectType::from(exception);
+        let from_exception = if ex_object.as_object().is_some() {
+            let obj_name = ex_object.to_jni_type_name();
+            quote! {
+                impl<'j> From<Exception<'j, #ex_type>> for #obj_name {
+                    fn from(exception: Exception<'j, #ex_type>) -> Self {
+                        // `catch` checked the class of the exception
+                        Self::from(JObject::from(exception.exception()))
+                    }
+                }
+            }
+        } else {
+            quote! {}
+        };
+
+        // the JDK exceptions are already defined in jaffi_support
+        if jaffi_support::exceptions::jdk_exception_name(exception.as_str()).is_some() {
+            tokens.extend(from_exception);
+            continue;
+        }
+
+        let class_cache = class_cache_ident(exception.as_str());
+        let doc_str = 
+        format!("An opaque type that represents the exception object `{exception}` from Java");
+
+        tokens.extend(quote!{
+            #[doc = #doc_str]
+            #[derive(Copy, Clone)]
+            pub struct #ex_type;
+
+            impl jaffi_support::Throwable for #ex_type {
+                #[track_caller]
+                fn throw<'j, S: Into<JNIString>>(&self, env: JNIEnv<'j>, msg: S) -> Result<(), JniError> {
+                    let class = #class_cache.get(env)?;
+                    env.throw_new(class, msg)
+                }
+
+                fn catch<'j>(env: JNIEnv<'j>, throwable: JThrowable<'j>) -> Result<Self, JThrowable<'j>> { 
+                    if !throwable.is_null() && #class_cache.get(env).and_then(|class| env.is_instance_of(throwable, class)).expect("could not check instance_of") {
+                        Ok(Self)
+                    } else {
+                        Err(throwable)
+                    }
+                }
+            }
+
+            #from_exception
+        });
+    }
+
+    // Now Generate the return type name for the combined exceptions
+    for exception_set in &exception_sets {
+        let exception = exception_name_from_set(exception_set);
+        // the enum variants
+        let ex_variant_names = exception_set
+            .iter()
+            .map(|d| make_ident(d.class_name()))
+            .collect::<Vec<_>>();
+        let ex_types = exception_set
+            .iter()
+            .map(exception_type)
+            .collect::<Vec<_>>();
+
+        tokens.extend(quote!{
+            #[derive(Copy, Clone)]
+            pub enum #exception {
+                #(#ex_variant_names(#ex_types)),*
+            }
+
+            impl jaffi_support::Throwable for #exception {
+                #[track_caller]
+                fn throw<'j, S: Into<JNIString>>(&self, env: JNIEnv<'j>, msg: S) -> Result<(), JniError> {
+                    match self {
+                        #(Self::#ex_variant_names(ex) => ex.throw(env, msg)),*
+                    }
+                }
+
+                fn catch<'j>(env: JNIEnv<'j>, throwable: JThrowable<'j>) -> Result<Self, JThrowable<'j>> { 
+                    const ALL_EXCEPTIONS: &[#exception]  = &[#(#exception::#ex_variant_names(#ex_types)),*] as &[_];
+                    for exception in ALL_EXCEPTIONS {
+                        match exception {
+                            #(v @ Self::#ex_variant_names(_e) => {
+                                if let Ok(_e) = <#ex_types as jaffi_support::Throwable>::catch(env, throwable) {
+                                    return Ok(*v);
+                                }
+                            })*
+                        }
+                    }
+
+                    Err(throwable)
+                }
+            }
+
+            #(
+                impl From<#ex_types> for #exception {
+                    fn from(ex: #ex_types) -> Self {
+                        Self::#ex_variant_names(ex)
+                    }
+                }
+            )*
+        })
+    }
+
+    tokens
+}
+
+/// The methods of the `*Rs` trait of the class, with the defaults calling the Java fallbacks and cancelling handles
+fn generate_trait_functions(class_ffi: &ClassFfi, config: &FfiConfig) -> TokenStream {
+    class_ffi
+        .functions
+        .iter()
+        .map(|func| {
+            let name = &func.name;
+            let jni_sig = &func.signature;
+            let java_doc = format!("Implementation for the method `{name}{jni_sig}`");
+            let rust_method_name = func.rust_method_name.for_rust_ident();
+            let class_ffi_name = &func.class_ffi_name;
+            let object_ffi_name = &func.object_ffi_name;
+            let class_or_this = if class_ffi.context_impl {
+                let this_ty = if func.is_static { class_ffi_name } else { object_ffi_name };
+                quote! { ctx: jaffi_support::context::CallCtx<'j, #this_ty> }
+            } else if func.is_static {
+                quote! { class: #class_ffi_name  }
+            } else {
+                quote! { this: #object_ffi_name  }
+            };
+            // the context carries the env
+            let env_param = if class_ffi.registered_impl && !class_ffi.context_impl {
+                quote! { env: JNIEnv<'j>, }
+            } else {
+                quote! {}
+            };
+            let arguments = func
+                .arguments
+                .iter()
+                .map(|arg| (&arg.name, &arg.rs_ty))
+                .map(|(name, rs_ty)| quote! { #name: #rs_ty })
+                .collect::<Vec<_>>();
+            let rs_result = &func.rs_result;
+
+            let rs_result = if !func.exceptions.is_empty() {
+                let exception_name = exception_name_from_set(&func.exceptions);
+                quote! { Result<#rs_result, jaffi_support::Error<#exception_name>> }
+            } else {
+                quote! { #rs_result }
+            };
+
+            // the default of a cancel handle only uses the handle
+            let allow_unused = if func.cancel_handle {
+                quote! { #[allow(unused_variables)] }
+            } else {
+                quote! {}
+            };
+
+            // the default calls the Java fallback, with the env from the context, the registered method, or `jni_env`
+            let body = if let Some(fallback) = &func.java_fallback {
+                let fallback = fallback.for_rust_ident();
+                let arg_names = func.arguments.iter().map(|arg| &arg.name);
+                let (receiver, env) = if class_ffi.context_impl {
+                    (quote! { ctx.this() }, quote! { ctx.env() })
+                } else if func.is_static {
+                    (quote! { class }, quote! { self.jni_env() })
+                } else {
+                    (quote! { this }, quote! { self.jni_env() })
+                };
+                let env = if class_ffi.registered_impl && !class_ffi.context_impl {
+                    quote! { env }
+                } else {
+                    env
+                };
+
+                // with `unchecked_exceptions` the fallback catches the exceptions, which are rethrown by the panic handler
+                let unwrap = if config.unchecked_exceptions {
+                    quote! { .unwrap_or_else(|e| panic!("uncaught exception in the Java fallback: {e}")) }
+                } else {
+                    quote! {}
+                };
+
+                quote! {
+                    {
+                        #receiver.#fallback(#env, #(#arg_names),*)#unwrap
+                    }
+                }
+            } else if func.cancel_handle {
+                let handle = &func.arguments[0].name;
+                if func.signature.as_str() == "(J)V" {
+                    quote! { { jaffi_support::cancel::cancel(#handle); } }
+                } else {
+                    quote! { { jaffi_support::cancel::cancel(#handle) } }
+                }
+            } else {
+                quote! { ; }
+            };
+
+            if class_ffi.registered_impl {
+                quote! {
+                    #[doc = #java_doc]
+                    #allow_unused
+                    fn #rust_method_name<'j>(
+                        &self,
+                        #env_param
+                        #class_or_this,
+                        #(#arguments),*
+                    ) -> #rs_result #body
+                }
+            } else {
+                quote! {
+                    #[doc = #java_doc]
+                    #allow_unused
+                    fn #rust_method_name(
+                        &self,
+                        #class_or_this,
+                        #(#arguments),*
+                    ) -> #rs_result #body
+                }
+            }
+        })
+        .collect::<TokenStream>()
+}
+
+fn generate_class_ffi(class_ffi: &ClassFfi, config: &FfiConfig) -> TokenStream {
+    let trait_impl = make_ident(&class_ffi.trait_impl);
+    let trait_name = make_ident(&class_ffi.trait_name);
+    let doc_str = format!(
+        "Implement this with `super::{trait_impl}` to support native methods from `{}`",
+        class_ffi.class_name
+    );
+
+    let trait_functions = generate_trait_functions(class_ffi, config);
+
+    let registered_name = make_ident(&class_ffi.trait_name.to_shouty_snake_case());
+    let register_fn = format_ident!("register_{}", class_ffi.trait_name.to_snake_case());
+
+    let extern_functions = class_ffi
+        .functions
+        .iter()
+        .map(|func| {
+            let signature = func.signature.as_str();
+            let object_name = &func.object_java_desc;
+            let name = &func.name;
+            let fn_doc = format!("Java native `{object_name}.{name}{signature}`.");
+            let fn_export_ffi_name = make_ident(&func.fn_export_ffi_names[0].0 .0);
+            let class_ffi_name = &func.class_ffi_name;
+            let object_ffi_name = &func.object_ffi_name;
+            let class_or_this = if func.is_static {
+                quote! { class: #class_ffi_name  }
+            } else {
+                quote! { this: #object_ffi_name  }
+            };
+            let arguments = func
+                .arguments
+                .iter()
+                .map(|arg| (&arg.name, &arg.ty))
+                .map(|(name, ty)| quote! { #name: #ty })
+                .collect::<Vec<_>>();
+            let result = &func.result;
+            let args_to_rust = func
+                .arguments
+                .iter()
+                .map(|arg| (&arg.name, &arg.rs_ty))
+                .map(|(name, rs_ty)| {
+                    quote! {
+                        let #name = <#rs_ty>::java_to_rust(#name, env);
+                    }
+                })
+                .collect::<Vec<_>>();
+            let rust_method_name = func.rust_method_name.for_rust_ident();
+            let call_class_or_this = if func.is_static {
+                format_ident!("class")
+            } else {
+                format_ident!("this")
+            };
+            let args_call = func
+                .arguments
+                .iter()
+                .map(|arg| &arg.name)
+                .map(|name| quote! {#name})
+                .collect::<Vec<_>>();
+
+            let handle_err = if !func.exceptions.is_empty() {
+                quote! {
+                    let result = match result {
+                        Err(e) => {
+                            e.throw(env).expect("failed to throw exception");
+                            return NullObject::null();
+                        }
+                        Ok(r) => r,
+                    };
+                }
+            } else {
+                quote! {}
+            };
+
+            let call_class_or_this = if class_ffi.context_impl {
+                quote! { jaffi_support::context::CallCtx::new(env, #call_class_or_this) }
+            } else {
+                quote! { #call_class_or_this }
+            };
+
+            // a missing registration panics in the closure, so it's thrown to Java
+            let (get_myself, lookup_myself, env_arg) = if class_ffi.registered_impl {
+                let missing = format!(
+                    "no implementation of {} registered, call {register_fn} from the user_on_load_fn",
+                    class_ffi.trait_name
+                );
+                (
+                    quote! {},
+                    quote! { let myself = #registered_name.get().expect(#missing); },
+                    if class_ffi.context_impl { quote! {} } else { quote! { env, } },
+                )
+            } else {
+                (
+                    quote! { let myself = #trait_impl::from_env(env); },
+                    quote! {},
+                    quote! {},
+                )
+            };
+
+            // the hooks are given the java class name and the Java method name
+            let hook_class = object_name.binary_name();
+
+            // in debug builds, checked before the arguments are converted, and again before the implementation is called
+            let (validate_args, validate_call) = if config.validate_args {
+                let method = format!("{hook_class}.{name}{signature}");
+                let (parameters, _) = func.signature.types();
+                let checks = func
+                    .arguments
+                    .iter()
+                    .zip(parameters)
+                    .enumerate()
+                    .filter(|(_, (_, ty))| matches!(ty, JavaType::Object(_) | JavaType::Array(_)))
+                    .map(|(i, (arg, ty))| {
+                        let arg_name = &arg.name;
+                        let argument = match &arg.java_name {
+                            Some(java_name) => format!("argument {i} `{java_name}`"),
+                            None => format!("argument {i}"),
+                        };
+                        let nullable = arg.nullable;
+                        let descriptor = ty.descriptor();
+                        quote! {
+                            || !jaffi_support::validate::argument(env, #method, #argument, *#arg_name, #nullable, #descriptor)
+                        }
+                    });
+
+                (
+                    quote! {
+                        #[cfg(debug_assertions)]
+                        if !jaffi_support::validate::no_exception_pending(env) #(#checks)* {
+                            return NullObject::null();
+                        }
+                    },
+                    quote! {
+                        #[cfg(debug_assertions)]
+                        if !jaffi_support::validate::no_exception_pending(env) {
+                            return NullObject::null();
+                        }
+                    },
+                )
+            } else {
+                (quote! {}, quote! {})
+            };
+
+            let call = quote! {
+                exceptions::catch_panic_and_throw_with(env, &PANIC_MAPPING, || {
+                    #validate_call
+                    #lookup_myself
+
+                    let result = myself.#rust_method_name (
+                        #env_arg
+                        #call_class_or_this,
+                        #(#args_call),*
+                    );
+
+                    #handle_err
+
+                    <#result>::rust_to_java(result, env)
+                })
+            };
+
+            // the OutOfMemoryError is left pending for Java
+            let ensure_capacity = func.local_capacity.map(|capacity| {
+                quote! {
+                    if env.ensure_local_capacity(#capacity).is_err() {
+                        return NullObject::null();
+                    }
+                }
+            });
+            let span = config.tracing_spans.then(|| {
+                quote! { let _span = jaffi_support::tracing::native_span(#hook_class, #name, #signature); }
+            });
+            let enter = config.native_enter_fn.as_ref().map(|enter_fn| {
+                quote! { #enter_fn(#hook_class, #name); }
+            });
+            let (start, call) = if let Some(exit_fn) = &config.native_exit_fn {
+                (
+                    quote! { let start = std::time::Instant::now(); },
+                    quote! {
+                        let ret = #call;
+                        #exit_fn(#hook_class, #name, start.elapsed());
+                        ret
+                    },
+                )
+            } else {
+                (quote! {}, call)
+            };
+
+            let export = quote! {
+                #[doc = #fn_doc]
+                ///
+                /// This will be linked into the Java Object at runtime via the `ld_library_path` rules in Java.
+                #[no_mangle]
+                #[allow(improper_ctypes_definitions)]
+                pub extern "system" fn #fn_export_ffi_name<'j>(
+                    env: JNIEnv<'j>,
+                    #class_or_this,
+                    #(#arguments),*
+                ) -> #result {
+                    #span
+                    #validate_args
+                    #ensure_capacity
+                    #enter
+                    #start
+
+                    #get_myself
+
+                    #(#args_to_rust)*
+
+                    #call
+                }
+            };
+
+            let arg_names = func.arguments.iter().map(|arg| &arg.name).collect::<Vec<_>>();
+            let class_or_this_name = if func.is_static {
+                format_ident!("class")
+            } else {
+                format_ident!("this")
+            };
+            let other_exports = func.fn_export_ffi_names[1..]
+                .iter()
+                .map(|other| {
+                    let other = make_ident(&other.0 .0);
+                    let other_doc = format!("Java native `{object_name}.{name}{signature}`, exported with another name for `{fn_export_ffi_name}`.");
+
+                    quote! {
+                        #[doc = #other_doc]
+                        #[no_mangle]
+                        #[allow(improper_ctypes_definitions)]
+                        pub extern "system" fn #other<'j>(
+                            env: JNIEnv<'j>,
+                            #class_or_this,
+                            #(#arguments),*
+                        ) -> #result {
+                            #fn_export_ffi_name(env, #class_or_this_name, #(#arg_names),*)
+                        }
+                    }
+                })
+                .collect::<TokenStream>();
+
+            quote! {
+                #export
+                #other_exports
+            }
+        })
+        .collect::<TokenStream>();
+    let abi_assertions = generate_abi_assertions(class_ffi);
+    let extern_functions = quote! {
+        #extern_functions
+        #abi_assertions
+    };
+
+    // let exception_sets = class_ffi.functions.iter().map(|f| &f.exceptions).collect::<HashSet<_>>().into_iter().map(exception_name_from_set).map(|i| quote!{ #i }).collect::<Vec<_>>();
+    // let trait_exception_type = if !exception_sets.is_empty() {
+    //     quote!{
+    //         type Error: #(Into<#exception_sets>)+*;
+    //     }
+    // } else {
+    //     quote!{}
+    // };
+
+    // the trait is shared by the classes of the package, see `generate_aggregate_trait`
+    if class_ffi.aggregated {
+        return extern_functions;
+    }
+
+    if class_ffi.registered_impl {
+        let doc_str = format!(
+            "Register an implementation of this with `{register_fn}` to support native methods from `{}`",
+            class_ffi.class_name
+        );
+        let register_doc = format!(
+            "Registers the implementation of `{trait_name}`, usually from the `user_on_load_fn`, the implementation is kept for the life of the library"
+        );
+
+        return quote! {
+            #[doc = #doc_str]
+            pub trait #trait_name: Send + Sync + 'static {
+                #trait_functions
+            }
+
+            static #registered_name: std::sync::OnceLock<Box<dyn #trait_name>> = std::sync::OnceLock::new();
+
+            #[doc = #register_doc]
+            ///
+            /// # Returns
+            ///
+            /// The implementation is returned as the error if one was already registered
+            pub fn #register_fn(implementation: Box<dyn #trait_name>) -> Result<(), Box<dyn #trait_name>> {
+                #registered_name.set(implementation)
+            }
+
+            #extern_functions
+        };
+    }
+
+    // the default methods calling the Java fallbacks need the env captured in `from_env`
+    let has_fallback = class_ffi
+        .functions
+        .iter()
+        .any(|func| func.java_fallback.is_some());
+    let jni_env = if has_fallback && !class_ffi.context_impl && !class_ffi.registered_impl {
+        quote! {
+            /// The `JNIEnv` from `from_env`, for the default methods calling the Java fallbacks
+            fn jni_env(&self) -> JNIEnv<'j>;
+        }
+    } else {
+        quote! {}
+    };
+
+    let impl_path = impl_path(&trait_impl, &class_ffi.class_name, config);
+
+    quote! {
+        // This is the trait developers must implement
+        use #impl_path;
+
+        #[doc = #doc_str]
+        pub trait #trait_name<'j> {
+            //#trait_exception_type
+
+            /// Costruct this type from the Java object
+            ///
+            /// Implementations should consider storing both values as types on the implementation object
+            fn from_env(env: JNIEnv<'j>) -> Self;
+
+            #jni_env
+
+            #trait_functions
+        }
+
+        #extern_functions
+    }
+}
+
+/// The path of the `*RsImpl` of a native class, it is in the module including the generated code, outside of the inner module
+///   and the package modules
+fn impl_path(trait_impl: &Ident, class_name: &str, config: &FfiConfig) -> TokenStream {
+    let mut depth = 1;
+    if config.inner_module.is_some() {
+        depth += 1;
+    }
+    if config.package_modules {
+        depth += package_path(class_name).len();
+    }
+    let supers = std::iter::repeat_n(quote! { super:: }, depth);
+
+    quote! { #(#supers)* #trait_impl }
+}
+
+/// The trait of one of the `aggregate_packages`, with the methods of all its native classes
+fn generate_aggregate_trait(class_ffis: &[&ClassFfi], config: &FfiConfig) -> TokenStream {
+    let Some(first) = class_ffis.first() else {
+        return TokenStream::new();
+    };
+    let trait_impl = make_ident(&first.trait_impl);
+    let trait_name = make_ident(&first.trait_name);
+    let classes = class_ffis
+        .iter()
+        .map(|class_ffi| format!("`{}`", class_ffi.class_name))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let doc_str = format!(
+        "Implement this with `super::{trait_impl}` to support native methods from {classes}, the methods are prefixed with the class"
+    );
+
+    let trait_functions = class_ffis
+        .iter()
+        .map(|class_ffi| generate_trait_functions(class_ffi, config))
+        .collect::<TokenStream>();
+
+    let has_fallback = class_ffis
+        .iter()
+        .flat_map(|class_ffi| &class_ffi.functions)
+        .any(|func| func.java_fallback.is_some());
+    let jni_env = has_fallback.then(|| {
+        quote! {
+            /// The `JNIEnv` from `from_env`, for the default methods calling the Java fallbacks
+            fn jni_env(&self) -> JNIEnv<'j>;
+        }
+    });
+
+    let impl_path = impl_path(&trait_impl, &first.class_name, config);
+
+    quote! {
+        use #impl_path;
+
+        #[doc = #doc_str]
+        pub trait #trait_name<'j> {
+            /// Costruct this type from the Java object
+            ///
+            /// Implementations should consider storing both values as types on the implementation object
+            fn from_env(env: JNIEnv<'j>) -> Self;
+
+            #jni_env
+
+            #trait_functions
+        }
+    }
+}
+
+/// A plain Rust struct with the properties of a Java bean or the components of a record, with conversions from and to Java
+fn generate_mirror(obj: &Object, mirror: &Mirror, config: &FfiConfig) -> TokenStream {
+    let obj_name = &obj.obj_name;
+    let java_class_desc = obj.java_name.as_str();
+    let class_cache = class_cache_ident(java_class_desc);
+    let (mirror_name, mirror_doc, properties) = match mirror {
+        Mirror::Bean(properties) => (
+            obj.obj_name.no_lifetime().append("Bean"),
+            format!("A copy of the properties of the Java bean `{java_class_desc}`, read with `from_java` and written to a new bean with `to_java`"),
+            properties,
+        ),
+        Mirror::Record(components) => (
+            obj.obj_name.no_lifetime().append("Record"),
+            format!("A copy of the components of the Java record `{java_class_desc}`, read with `from_java` and written to a new record with `to_java`"),
+            components,
+        ),
+    };
+
+    let serde_derive = config.bean_serde.then(|| quote! { #[derive(serde::Serialize, serde::Deserialize)] });
+
+    let mut fields = TokenStream::new();
+    let mut reads = TokenStream::new();
+    let mut values = Vec::<TokenStream>::new();
+    for property in properties {
+        let field = make_ident(&property.name.to_snake_case());
+        let field_doc = match &property.setter {
+            Some(setter) => format!(
+                "The `{}` property, from `{}` and `{}`",
+                property.name, property.getter, setter
+            ),
+            None => format!("The `{}` component", property.name),
+        };
+        let serde_rename = (config.bean_serde && field != property.name).then(|| {
+            let name = &property.name;
+            quote! { #[serde(rename = #name)] }
+        });
+
+        let getter = &property.getter;
+        let getter_sig = format!("(){}", property.descriptor);
+
+        // Strings are optional, beans and records commonly have null properties
+        if let JniType::Ty(BaseJniTy::Jobject(ObjectType::JString)) = &property.ty {
+            fields.extend(quote! {
+                #[doc = #field_doc]
+                #serde_rename
+                pub #field: Option<String>,
+            });
+            reads.extend(quote! {
+                #field: {
+                    let string = env.call_method(obj.0, #getter, #getter_sig, &[])?.l()?;
+                    if string.is_null() {
+                        None
+                    } else {
+                        Some(String::java_to_rust(jni::objects::JString::from(string), env))
+                    }
+                },
+            });
+            values.push(quote! {
+                JValue::Object(match &self.#field {
+                    Some(string) => JObject::from(env.new_string(string)?),
+                    None => JObject::null(),
+                })
+            });
+        } else {
+            let ty = property.ty.to_jni_type_name();
+            let rs_ty = property.ty.to_rs_type_name_with(config.primitive_types);
+
+            fields.extend(quote! {
+                #[doc = #field_doc]
+                #serde_rename
+                pub #field: #rs_ty,
+            });
+            reads.extend(quote! {
+                #field: <#rs_ty as jaffi_support::TryFromJavaValue<#ty>>::try_from_jvalue(
+                    env,
+                    env.call_method(obj.0, #getter, #getter_sig, &[])?,
+                )?,
+            });
+            values.push(quote! {
+                <#rs_ty as IntoJavaValue<'j, #ty>>::into_java_value(self.#field, env)
+            });
+        }
+    }
+
+    let to_java = match mirror {
+        Mirror::Bean(properties) => {
+            let writes = properties.iter().zip(values).map(|(property, value)| {
+                let setter = property.setter.as_deref().expect("bean properties have setters");
+                let setter_sig = format!("({})V", property.descriptor);
+                quote! { env.call_method(bean, #setter, #setter_sig, &[#value])?; }
+            });
+
+            quote! {
+                /// Creates a new bean with its no-argument constructor, and writes the properties with the setters
+                pub fn to_java<'j>(&self, env: JNIEnv<'j>) -> Result<#obj_name, JniError> {
+                    let bean = env.new_object(#class_cache.get(env)?, "()V", &[])?;
+                    #(#writes)*
+                    Ok(bean.into())
+                }
+            }
+        }
+        Mirror::Record(components) => {
+            let constructor_sig = format!(
+                "({})V",
+                components
+                    .iter()
+                    .map(|component| component.descriptor.as_str())
+                    .collect::<String>()
+            );
+
+            quote! {
+                /// Creates a new record with its canonical constructor
+                pub fn to_java<'j>(&self, env: JNIEnv<'j>) -> Result<#obj_name, JniError> {
+                    let args: &[JValue<'j>] = &[#(#values),*];
+                    env.new_object(#class_cache.get(env)?, #constructor_sig, args).map(Into::into)
+                }
+            }
+        }
+    };
+
+    quote! {
+        #[doc = #mirror_doc]
+        #[derive(Clone, Debug, Default, PartialEq)]
+        #serde_derive
+        pub struct #mirror_name {
+            #fields
+        }
+
+        impl #mirror_name {
+            /// Reads the properties with the getters, or the components with the accessors
+            pub fn from_java<'j>(env: JNIEnv<'j>, obj: #obj_name) -> Result<Self, JniError> {
+                Ok(Self {
+                    #reads
+                })
+            }
+
+            #to_java
+        }
+    }
+}
+
+/// An enum of the permitted subclasses of a sealed class, with `classify` on the wrapper to match the object to one of them
+/// Constructors with at least this many arguments get a builder
+const CONSTRUCTOR_BUILDER_ARGS: usize = 5;
+
+/// A builder for the public constructor with the most arguments, if it has at least `CONSTRUCTOR_BUILDER_ARGS`
+///
+/// The builder is typed by which arguments are set, each one is set once and `build` is only available when all of them are.
+fn generate_builder(
+    obj: &Object,
+    enum_types: &HashSet<RustTypeName>,
+    config: &FfiConfig,
+) -> Option<TokenStream> {
+    let constructor = obj
+        .methods
+        .iter()
+        .filter(|f| f.is_constructor && !f.native_factory)
+        .filter(|f| f.arguments.len() >= CONSTRUCTOR_BUILDER_ARGS)
+        .reduce(|most, f| if f.arguments.len() > most.arguments.len() { f } else { most })?;
+
+    let obj_name = &obj.obj_name;
+    let builder_name = obj.obj_name.no_lifetime().append("Builder");
+    let java_class_desc = obj.java_name.as_str();
+    let builder_doc = format!(
+        "A builder of the Java class `{java_class_desc}`, with the constructor `<init>{}`",
+        constructor.signature
+    );
+    let rust_method_name = constructor.rust_method_name.for_rust_ident();
+    let rs_result = wrapper_result(constructor, config);
+
+    // enums are passed as any `TryIntoJavaValue` and interned strings as any `IntoJavaValue`, like to the wrapper
+    let jstring = ObjectType::JString.to_jni_type_name();
+    let is_interned = |arg: &Arg| config.interned_string_args && arg.ty == jstring;
+
+    let params = (0..constructor.arguments.len())
+        .map(|i| format_ident!("A{i}"))
+        .collect::<Vec<_>>();
+    let values = constructor
+        .arguments
+        .iter()
+        .map(|arg| &arg.name)
+        .collect::<Vec<_>>();
+    let unset = constructor.arguments.iter().map(|_| quote! { () });
+
+    let mut setters = TokenStream::new();
+    for (i, arg) in constructor.arguments.iter().enumerate() {
+        // the names of the parameters are only in classes compiled with `-parameters` or `-g`
+        let setter = match arg.java_name.as_deref().map(str::to_snake_case) {
+            Some(name) if name == "new" || name == "build" => format_ident!("{name}_arg"),
+            Some(name) => make_ident(&name),
+            None => arg.name.clone(),
+        };
+        let setter_doc = format!(
+            "Sets the argument {i}{} of the constructor",
+            arg.java_name
+                .as_ref()
+                .map(|name| format!(", `{name}`,"))
+                .unwrap_or_default()
+        );
+        let rs_ty = &arg.rs_ty;
+        let ty = &arg.ty;
+        let (generic, value_ty) = if enum_types.contains(&arg.ty) {
+            (quote! { T: jaffi_support::TryIntoJavaValue<'j, #ty>, }, quote! { T })
+        } else if is_interned(arg) {
+            (quote! { T: IntoJavaValue<'j, #ty>, }, quote! { T })
+        } else {
+            (quote! {}, quote! { #rs_ty })
+        };
+
+        let other_params = params
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(_, param)| param);
+        let before = params
+            .iter()
+            .enumerate()
+            .map(|(j, param)| if j == i { quote! { () } } else { quote! { #param } });
+        let after = params
+            .iter()
+            .enumerate()
+            .map(|(j, param)| if j == i { quote! { (#value_ty,) } } else { quote! { #param } });
+        let take = values
+            .iter()
+            .enumerate()
+            .map(|(j, value)| if j == i { quote! { _ } } else { quote! { #value } });
+        let put = values
+            .iter()
+            .enumerate()
+            .map(|(j, value)| if j == i { quote! { (#setter,) } } else { quote! { #value } });
+
+        setters.extend(quote! {
+            impl<'j, #(#other_params),*> #builder_name<'j, #(#before),*> {
+                #[doc = #setter_doc]
+                pub fn #setter<#generic>(self, #setter: #value_ty) -> #builder_name<'j, #(#after),*> {
+                    let (#(#take),*) = self.args;
+                    #builder_name {
+                        args: (#(#put),*),
+                        _lifetime: std::marker::PhantomData,
+                    }
+                }
+            }
+        });
+    }
+
+    let build_generics = constructor
+        .arguments
+        .iter()
+        .zip(&params)
+        .filter(|(arg, _)| enum_types.contains(&arg.ty) || is_interned(arg))
+        .map(|(arg, param)| {
+            let ty = &arg.ty;
+            if enum_types.contains(&arg.ty) {
+                quote! { #param: jaffi_support::TryIntoJavaValue<'j, #ty> }
+            } else {
+                quote! { #param: IntoJavaValue<'j, #ty> }
+            }
+        });
+    let set = constructor.arguments.iter().zip(&params).map(|(arg, param)| {
+        if enum_types.contains(&arg.ty) || is_interned(arg) {
+            quote! { (#param,) }
+        } else {
+            let rs_ty = &arg.rs_ty;
+            quote! { (#rs_ty,) }
+        }
+    });
+
+    Some(quote! {
+        #[doc = #builder_doc]
+        pub struct #builder_name<'j, #(#params = ()),*> {
+            args: (#(#params),*),
+            _lifetime: std::marker::PhantomData<&'j ()>,
+        }
+
+        impl<'j> #builder_name<'j> {
+            /// A builder without any of the arguments set
+            pub fn new() -> Self {
+                Self {
+                    args: (#(#unset),*),
+                    _lifetime: std::marker::PhantomData,
+                }
+            }
+        }
+
+        impl<'j> Default for #builder_name<'j> {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        #setters
+
+        impl<'j, #(#build_generics),*> #builder_name<'j, #(#set),*> {
+            /// Constructs the Java object with the arguments
+            pub fn build(self, env: JNIEnv<'j>) -> #rs_result {
+                let (#((#values,)),*) = self.args;
+                <#obj_name>::#rust_method_name(env, #(#values),*)
+            }
+        }
+    })
+}
+
+fn generate_sealed(obj: &Object) -> TokenStream {
+    let obj_name = &obj.obj_name;
+    let sealed_name = obj.obj_name.append("Sealed<'j>");
+    let sealed_type = sealed_name.no_lifetime();
+    let java_class_desc = obj.java_name.as_str();
+    let sealed_doc = format!(
+        "The permitted subclasses of the sealed `{java_class_desc}`, see `classify`"
+    );
+
+    let (variants, checks): (Vec<_>, Vec<_>) = obj
+        .permitted_subclasses
+        .iter()
+        .map(|subclass| {
+            // nested classes are named for the innermost class, e.g. `Circle` for `Shape$Circle`
+            let simple_name = subclass
+                .as_str()
+                .rsplit(['/', '$'])
+                .next()
+                .unwrap_or(subclass.as_str());
+            let variant = make_ident(&escape_non_ascii(simple_name).to_upper_camel_case());
+            let subclass_name = ObjectType::from(subclass.clone())
+                .to_jni_type_name()
+                .append("<'j>");
+            let subclass_desc = subclass.as_str();
+            let subclass_cache = class_cache_ident(subclass_desc);
+
+            let variant_doc = format!("An instance of `{subclass_desc}`");
+            let variant_def = quote! {
+                #[doc = #variant_doc]
+                #variant(#subclass_name),
+            };
+            let check = quote! {
+                if env.is_instance_of(self.0, #subclass_cache.get(env)?)? {
+                    return Ok(#sealed_type::#variant(self.0.into()));
+                }
+            };
+
+            (variant_def, check)
+        })
+        .unzip();
+
+    quote! {
+        #[doc = #sealed_doc]
+        #[derive(Clone, Copy, Debug)]
+        pub enum #sealed_name {
+            #(#variants)*
+        }
+
+        impl<'j> #obj_name {
+            /// Returns the permitted subclass this object is an instance of, for matching over the sealed hierarchy
+            ///
+            /// # Errors
+            ///
+            /// If the object is `null`
+            pub fn classify(&self, env: JNIEnv<'j>) -> Result<#sealed_name, JniError> {
+                // null is an instance of every class in JNI
+                if self.0.is_null() {
+                    return Err(JniError::NullPtr("classify"));
+                }
+
+                #(#checks)*
+
+                unreachable!("the JVM only allows the permitted subclasses to extend {}", #java_class_desc)
+            }
+        }
+    }
+}
+
+/// Options from the builder that change the generated code
+pub(crate) struct FfiConfig {
+    /// The exception class that panics are thrown as
+    pub(crate) panic_exception: JavaDesc,
+    /// Path to the function that converts the panic payload to the exception message
+    pub(crate) panic_message_fn: Option<TokenStream>,
+    /// Path to the function called from `JNI_OnLoad`
+    pub(crate) user_on_load_fn: Option<TokenStream>,
+    /// Path to the function called from `JNI_OnUnload`
+    pub(crate) user_on_unload_fn: Option<TokenStream>,
+    /// The paths of the command type and of the handler of the `JAFFI_DISPATCHER`
+    pub(crate) dispatcher: Option<(TokenStream, TokenStream)>,
+    /// Path to the function called on entering each exported native function
+    pub(crate) native_enter_fn: Option<TokenStream>,
+    /// Path to the function called on leaving each exported native function
+    pub(crate) native_exit_fn: Option<TokenStream>,
+    /// The version returned from `JNI_OnLoad`
+    pub(crate) jni_version: JniVersion,
+    /// The name of the load hook, when it should not be exported as `JNI_OnLoad`
+    pub(crate) on_load_name: Option<Ident>,
+    /// The name of the unload hook, when it should not be exported as `JNI_OnUnload`
+    pub(crate) on_unload_name: Option<Ident>,
+    /// The strategy set in `JNI_OnLoad` for converting Strings
+    pub(crate) string_strategy: StringStrategy,
+    /// The strategy set in `JNI_OnLoad` for converting chars
+    pub(crate) char_strategy: CharStrategy,
+    /// The Rust types of `byte`, `char` and `boolean`
+    pub(crate) primitive_types: PrimitiveTypes,
+    /// Wrapper methods call into Java in a new local frame
+    pub(crate) local_frames: bool,
+    /// `String` arguments of wrapper methods also take `Interned` constants
+    pub(crate) interned_string_args: bool,
+    /// Wrapper methods call Java by cached method ids
+    pub(crate) cached_method_ids: bool,
+    /// Wrapper methods call Java by cached method ids, with the arguments in a pooled `jvalue` array
+    pub(crate) scratch_buffers: bool,
+    /// Native functions and wrapper methods enter a `tracing` span
+    pub(crate) tracing_spans: bool,
+    /// The absolute classpath of the smoke test generated with `test_harness`, `None` if it's not generated
+    pub(crate) test_harness_classpath: Option<Vec<String>>,
+    /// The bound classes are listed in a `JAFFI_REGISTRY` table, registered with `jaffi_support::class_registry` on load
+    pub(crate) class_registry: bool,
+    /// The exported native functions validate their arguments with `jaffi_support::validate` in debug builds
+    pub(crate) validate_args: bool,
+    /// When the wrapper methods check for a pending exception before calling into Java
+    pub(crate) pending_exception_checks: PendingExceptionChecks,
+    /// The mirrors of beans and records derive `serde::Serialize` and `serde::Deserialize`
+    pub(crate) bean_serde: bool,
+    /// Wrappers get `is_same_object`, `identity_hash_code` and `identity`
+    pub(crate) identity_methods: bool,
+    /// Wrapper methods catch all exceptions, returning `Exception<AnyThrowable>`
+    pub(crate) unchecked_exceptions: bool,
+    /// Panics are logged to logcat
+    pub(crate) android: bool,
+    /// The module the generated code is wrapped in, re-exported with a glob
+    pub(crate) inner_module: Option<Ident>,
+    /// The classes are generated in modules mirroring their packages
+    pub(crate) package_modules: bool,
+    /// The global wrappers get `_async` methods calling Java on a `jaffi_support::pool::JavaPool`
+    pub(crate) async_wrappers: bool,
+    /// The items of the generated `prelude` module, `None` if it's not generated
+    pub(crate) prelude: Option<Prelude>,
+    /// The options of the builder that change the generated code, for the provenance
+    pub(crate) options: Vec<String>,
+}
+
+/// Generates the code for all the classes, the `report` is empty
+pub(crate) fn generate_java_ffi(
+    objects: Vec<Object>,
+    other_classes: &[ClassFfi],
+    exceptions: HashSet<BTreeSet<JavaDesc>>,
+    config: &FfiConfig,
+) -> GeneratedTokens {
+    let header = quote! {
+        use jaffi_support::{
+            exceptions,
+            Exception,
+            FromJavaToRust,
+            FromRustToJava,
+            IntoJavaValue,
+            NullObject,
+            jni::{
+                sys::jint,
+                JavaVM, JNIEnv,
+                objects::{JClass, JObject, JValue, JThrowable},
+                strings::JNIString,
+                errors::Error as JniError,
+                self,
+            }
+        };
+    };
+
+    let enum_types = objects
+        .iter()
+        .filter(|o| !o.enum_constants.is_empty())
+        .map(|o| o.obj_name.clone())
+        .collect::<HashSet<_>>();
+    let static_modules = static_module_names(&objects);
+    let mut provenance = Provenance {
+        classes: objects
+            .iter()
+            .filter_map(|o| Some((o.java_name.to_string(), o.fingerprint?)))
+            .collect(),
+        options: config.options.clone(),
+    };
+    provenance.classes.sort();
+    let generated_by = provenance.to_tokens();
+    let wrappers = objects
+        .iter()
+        .map(|o| {
+            let mut tokens = generate_struct(o, &enum_types, config);
+            if let Some(mirror) = &o.mirror {
+                tokens.extend(generate_mirror(o, mirror, config));
+            }
+            if !o.permitted_subclasses.is_empty() {
+                tokens.extend(generate_sealed(o));
+            }
+            tokens.extend(generate_builder(o, &enum_types, config));
+            if let Some(module) = static_modules.get(&o.java_name) {
+                tokens.extend(generate_static_module(o, module, &enum_types, config));
+            }
+            (o.java_name.as_str().to_string(), tokens)
+        })
+        .collect::<BTreeMap<_, _>>();
+    let mut native_classes = other_classes
+        .iter()
+        .map(|class_ffi| {
+            (
+                class_ffi.class_name.clone(),
+                generate_class_ffi(class_ffi, config),
+            )
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    // the trait of an aggregated package is generated with its first class
+    let mut aggregates = BTreeMap::<&str, Vec<&ClassFfi>>::new();
+    for class_ffi in other_classes.iter().filter(|class_ffi| class_ffi.aggregated) {
+        aggregates
+            .entry(&class_ffi.trait_name)
+            .or_default()
+            .push(class_ffi);
+    }
+    for mut class_ffis in aggregates.into_values() {
+        class_ffis.sort_by(|a, b| a.class_name.cmp(&b.class_name));
+        let aggregate_trait = generate_aggregate_trait(&class_ffis, config);
+        if let Some(tokens) = native_classes.get_mut(&class_ffis[0].class_name) {
+            tokens.extend(aggregate_trait);
+        }
+    }
+
+    let prelude = config
+        .prelude
+        .map(|prelude| generate_prelude(prelude, &objects, other_classes, &exceptions));
+    let exceptions = generate_exceptions(exceptions);
+
+    let panic_class = config.panic_exception.as_str();
+    let panic_message_fn = config
+        .panic_message_fn
+        .clone()
+        .unwrap_or_else(|| quote! { exceptions::get_panic_message });
+    let panic_mapping = quote! {
+        /// The exception thrown for panics in native methods
+        const PANIC_MAPPING: exceptions::PanicMapping = exceptions::PanicMapping {
+            class: #panic_class,
+            message: #panic_message_fn,
+        };
+    };
+
+    let user_on_load = config
+        .user_on_load_fn
+        .as_ref()
+        .map(|user_fn| quote! { #user_fn(&vm); });
+    let user_on_unload = config
+        .user_on_unload_fn
+        .as_ref()
+        .map(|user_fn| quote! { #user_fn(&vm); });
+    let unload_vm = if user_on_unload.is_some() {
+        format_ident!("vm")
+    } else {
+        format_ident!("_vm")
+    };
+
+    let jni_version = config.jni_version;
+    let jni_version_doc = format!("Requests `{}` from the JVM", config.jni_version);
+    let on_load_sig = if let Some(on_load_name) = &config.on_load_name {
+        quote! { pub fn #on_load_name(vm: JavaVM) -> jint }
+    } else {
+        quote! {
+            #[no_mangle]
+            pub extern "system" fn JNI_OnLoad(vm: JavaVM, _reserved: *const std::ffi::c_void) -> jint
+        }
+    };
+
+    let on_unload_sig = if let Some(on_unload_name) = &config.on_unload_name {
+        quote! { pub fn #on_unload_name(#unload_vm: JavaVM) }
+    } else {
+        quote! {
+            #[no_mangle]
+            pub extern "system" fn JNI_OnUnload(#unload_vm: JavaVM, _reserved: *const std::ffi::c_void)
+        }
+    };
+
+    let string_strategy = make_ident(&format!("{:?}", config.string_strategy));
+    let char_strategy = make_ident(&format!("{:?}", config.char_strategy));
+    let register_panic_hook = if config.android {
+        quote! { jaffi_support::android::register_panic_hook_with(vm, PANIC_MAPPING, env!("CARGO_PKG_NAME")); }
+    } else {
+        quote! { exceptions::register_panic_hook_with(vm, PANIC_MAPPING); }
+    };
+
+    // the classes are loaded while the library's class loader is in use, see `ClassCache`, sorted like the wrappers
+    let class_caches = wrappers
+        .keys()
+        .map(|java_name| class_cache_ident(java_name))
+        .collect::<Vec<_>>();
+    // the loader of the native classes is used for the lookups from threads attached from Rust
+    let capture_loader = native_classes.keys().next().map(|class| {
+        quote! {
+            if jaffi_support::class_loader::capture(env, #class).is_err() {
+                env.exception_clear().ok();
+            }
+        }
+    });
+    let load_classes = quote! {
+        if let Ok(env) = vm.get_env() {
+            #capture_loader
+            jaffi_support::cache::load_all(env, &[#(&#class_caches),*]);
+        }
+    };
+
+    let (class_registry, register_classes, unregister_classes) = if config.class_registry {
+        (
+            Some(generate_class_registry(&objects, other_classes)),
+            Some(quote! { jaffi_support::class_registry::register(JAFFI_REGISTRY); }),
+            Some(quote! { jaffi_support::class_registry::unregister(JAFFI_REGISTRY); }),
+        )
+    } else {
+        (None, None, None)
+    };
+
+    // started before the user_on_load_fn, which may send commands, and stopped before the global references are released
+    let (dispatcher, start_dispatcher, stop_dispatcher) = match &config.dispatcher {
+        Some((command, handler)) => {
+            let (command_name, handler_name) = (
+                command.to_string().replace(' ', ""),
+                handler.to_string().replace(' ', ""),
+            );
+            let static_doc = format!("The dispatcher of the `{command_name}`s, started in `JNI_OnLoad` with `{handler_name}`");
+            let dispatch_doc = format!("Sends the command to `{handler_name}`, which handles it on the worker thread of the `JAFFI_DISPATCHER`");
+            (
+                Some(quote! {
+                    #[doc = #static_doc]
+                    pub static JAFFI_DISPATCHER: jaffi_support::dispatch::Dispatcher<#command> =
+                        jaffi_support::dispatch::Dispatcher::new();
+
+                    #[doc = #dispatch_doc]
+                    ///
+                    /// The command is returned in the error if the dispatcher isn't running, e.g. while the library is unloaded.
+                    pub fn dispatch(command: #command) -> Result<(), std::sync::mpsc::SendError<#command>> {
+                        JAFFI_DISPATCHER.send(command)
+                    }
+                }),
+                // the library fails to load without its dispatcher, `JNI_OnUnload` isn't called after that
+                Some(quote! {
+                    let started = vm
+                        .get_env()
+                        .map_err(jaffi_support::dispatch::DispatchError::Jni)
+                        .and_then(|env| JAFFI_DISPATCHER.start(env, #handler));
+                    if let Err(e) = started {
+                        eprintln!("jaffi couldn't start the dispatcher: {e}");
+                        #unregister_classes
+                        jaffi_support::global::shutdown();
+                        return jaffi_support::jni::sys::JNI_ERR;
+                    }
+                }),
+                Some(quote! { JAFFI_DISPATCHER.shutdown(); }),
+            )
+        }
+        None => (None, None, None),
+    };
+
+    let onload = quote!{
+        /// Hook to setup panic_handler on the dynamic library load, etc.
+        ///
+        #[doc = #jni_version_doc]
+        #on_load_sig {
+            jaffi_support::global::startup();
+            // before any Strings or chars are converted, e.g. by the dispatcher or the user_on_load_fn
+            jaffi_support::string::set_strategy(jaffi_support::string::StringStrategy::#string_strategy);
+            jaffi_support::chars::set_strategy(jaffi_support::chars::CharStrategy::#char_strategy);
+            #register_classes
+            #load_classes
+            #start_dispatcher
+            #user_on_load
+            #register_panic_hook
+            #jni_version
+        }
+
+        /// Hook to release all registered global references before the library is unloaded
+        #on_unload_sig {
+            #stop_dispatcher
+            #user_on_unload
+            jaffi_support::global::shutdown();
+            #unregister_classes
+        }
+    };
+
+    // the bytes of the `byte_vec_args` are copied with the `array_helpers` of jaffi_support
+    let byte_vecs = other_classes
+        .iter()
+        .flat_map(|class_ffi| &class_ffi.functions)
+        .flat_map(|func| &func.arguments)
+        .any(|arg| arg.rs_ty.is_byte_vec())
+        .then(|| {
+            quote! {
+                const _: () = {
+                    jaffi_support::array_helpers!(required "byte_vec_args");
+                };
+            }
+        });
+
+    let test_harness = config
+        .test_harness_classpath
+        .as_ref()
+        .map(|classpath| generate_test_harness(classpath, other_classes));
+
+    let support = quote! {
+        #header
+
+        #generated_by
+
+        #panic_mapping
+
+        #exceptions
+
+        #byte_vecs
+
+        #onload
+
+        #dispatcher
+
+        #class_registry
+
+        #test_harness
+
+        #prelude
+    };
+
+    // the deprecated wrappers and methods only warn where they are used outside of the generated code
+    let (support, wrappers, native_classes) = if objects
+        .iter()
+        .flat_map(|o| o.methods.iter().map(|f| &f.deprecated).chain(Some(&o.deprecated)))
+        .any(Option::is_some)
+    {
+        let allow_all = |classes: BTreeMap<String, TokenStream>| {
+            classes
+                .into_iter()
+                .map(|(class, tokens)| (class, allow_deprecated(tokens)))
+                .collect::<BTreeMap<_, _>>()
+        };
+        (allow_deprecated(support), allow_all(wrappers), allow_all(native_classes))
+    } else {
+        (support, wrappers, native_classes)
+    };
+
+    GeneratedTokens {
+        support,
+        wrappers,
+        native_classes,
+        inner_module: config.inner_module.clone(),
+        package_modules: config.package_modules,
+        report: Report::default(),
+        model: Model::default(),
+        provenance,
+    }
+}
 
-            /// Costruct this type from the Java object
-            ///
-            /// Implementations should consider storing both values as types on the implementation object
-            fn from_env(env: JNIEnv<'j>) -> Self;
+/// The `prelude` module, re-exporting the selected items
+fn generate_prelude(
+    prelude: Prelude,
+    objects: &[Object],
+    class_ffis: &[ClassFfi],
+    exception_sets: &HashSet<BTreeSet<JavaDesc>>,
+) -> TokenStream {
+    // sorted and deduplicated, the classes of an aggregated package share the trait
+    let mut items = BTreeMap::<String, TokenStream>::new();
+    let mut add = |item: TokenStream| {
+        items.insert(item.to_string(), item);
+    };
 
-            #trait_functions
+    if prelude.native_traits {
+        for class_ffi in class_ffis {
+            let trait_name = make_ident(&class_ffi.trait_name);
+            add(quote! { super::#trait_name });
+        }
+    }
+    if prelude.wrappers {
+        for obj in objects {
+            let obj_name = obj.obj_name.no_lifetime();
+            let class_name = obj.class_name.no_lifetime();
+            add(quote! { super::#obj_name });
+            add(quote! { super::#class_name });
+            if !obj.enum_constants.is_empty() {
+                let enum_name = obj_name.append("Enum");
+                add(quote! { super::#enum_name });
+            }
+        }
+    }
+    if prelude.exceptions {
+        for exception_set in exception_sets {
+            let exception = exception_name_from_set(exception_set);
+            add(quote! { super::#exception });
+            for exception in exception_set {
+                let ex_type = exception_type(exception);
+                if jaffi_support::exceptions::jdk_exception_name(exception.as_str()).is_some() {
+                    add(ex_type);
+                } else {
+                    add(quote! { super::#ex_type });
+                }
+            }
+        }
+    }
+    let support = prelude.support.then(|| {
+        quote! {
+            pub use jaffi_support::{
+                jni::{
+                    objects::{JClass, JObject, JString},
+                    JNIEnv,
+                },
+                Error, Exception, FromJavaToRust, FromRustToJava, IntoJavaValue, NullObject, Throwable, TryIntoJavaValue,
+            };
         }
+    });
+    let items = items.into_values();
 
-        #extern_functions
+    quote! {
+        /// The native traits, wrappers, exceptions and support types selected in the builder, for `use prelude::*;`
+        pub mod prelude {
+            #(pub use #items;)*
+
+            #support
+        }
     }
 }
 
-pub(crate) fn generate_java_ffi(
-    objects: Vec<Object>,
-    other_classes: Vec<ClassFfi>,
-    exceptions: HashSet<BTreeSet<JavaDesc>>,
-) -> TokenStream {
-    let header = quote! {
-        use jaffi_support::{
-            exceptions,
-            Exception,
-            FromJavaToRust,
-            FromRustToJava,
-            FromJavaValue,
-            IntoJavaValue,
-            NullObject,
-            jni::{
-                sys::jint,
-                JavaVM, JNIEnv,
-                objects::{JClass, JObject, JValue, JThrowable},
-                strings::JNIString,
-                errors::Error as JniError,
-                self,
-            }
-        };
+/// The names of the modules of free functions, the snake case of the class name, e.g. `native_primitives`
+///
+/// Classes with the same name in different packages, or names that are used by the generated code, get the snake case of
+///   the wrapper, e.g. `net_bluejekyll_native_primitives`
+fn static_module_names(objects: &[Object]) -> HashMap<JavaDesc, Ident> {
+    const RESERVED: &[&str] = &["exceptions", "jni", "jaffi_support", "jaffi_test_harness"];
+
+    let short_name = |o: &Object| {
+        escape_non_ascii(o.java_name.class_name())
+            .replace('$', "_")
+            .to_snake_case()
     };
+    let mut counts = HashMap::<String, usize>::new();
+    for object in objects {
+        *counts.entry(short_name(object)).or_default() += 1;
+    }
 
-    let objects = objects.iter().map(generate_struct).collect::<TokenStream>();
-    let class_ffis = other_classes
+    objects
         .iter()
-        .map(generate_class_ffi)
-        .collect::<TokenStream>();
+        .map(|o| {
+            let name = short_name(o);
+            let name = if counts[&name] > 1 || RESERVED.contains(&name.as_str()) {
+                o.obj_name.no_lifetime().to_string().to_snake_case()
+            } else {
+                name
+            };
+            (o.java_name.clone(), make_ident(&name))
+        })
+        .collect()
+}
 
-    let exceptions = generate_exceptions(exceptions);
+/// Renames the `'j` lifetime of the generated code, e.g. to `'jni`
+pub(crate) fn rename_lifetime(tokens: TokenStream, name: &Ident) -> TokenStream {
+    let mut renamed = TokenStream::new();
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        match token {
+            TokenTree::Group(group) => {
+                let mut new_group =
+                    Group::new(group.delimiter(), rename_lifetime(group.stream(), name));
+                new_group.set_span(group.span());
+                renamed.append(new_group);
+            }
+            // a lifetime is a joint `'` followed by its name
+            TokenTree::Punct(punct) if punct.as_char() == '\'' && punct.spacing() == Spacing::Joint => {
+                renamed.append(punct);
+                if let Some(TokenTree::Ident(ident)) = tokens.peek() {
+                    if ident == "j" {
+                        renamed.append(Ident::new(&name.to_string(), ident.span()));
+                        tokens.next();
+                    }
+                }
+            }
+            token => renamed.append(token),
+        }
+    }
 
-    let onload = quote!{
-        /// Hook to setup panic_handler on the dynamic library load, etc.
-        #[no_mangle]
-        pub extern "system" fn JNI_OnLoad(vm: JavaVM, _reserved: *const std::ffi::c_void) -> jint {
-            exceptions::register_panic_hook(vm);
-            jni::sys::JNI_VERSION_1_8
+    renamed
+}
+
+/// A test that the native methods of the classes match the bindings, and link against the cdylib
+fn generate_test_harness(classpath: &[String], class_ffis: &[ClassFfi]) -> TokenStream {
+    let classes = class_ffis.iter().map(|class_ffi| {
+        let class = &class_ffi.class_name;
+        let names = class_ffi.functions.iter().map(|f| &f.name);
+        let signatures = class_ffi.functions.iter().map(|f| f.signature.as_str());
+
+        quote! {
+            jaffi_support::harness::NativeClass {
+                class: #class,
+                methods: &[#((#names, #signatures)),*],
+            }
         }
-    };
+    });
 
     quote! {
-        #header
+        #[cfg(test)]
+        mod jaffi_test_harness {
+            /// The classpath the bindings were generated from
+            const CLASSPATH: &[&str] = &[#(#classpath),*];
+
+            #[test]
+            fn native_methods_link() {
+                let classes = &[#(#classes),*];
+                let library = jaffi_support::harness::default_library(env!("CARGO_CRATE_NAME"));
+
+                if let Err(errors) = jaffi_support::harness::check_native_methods(CLASSPATH, &library, classes) {
+                    panic!("the native methods don't match the bindings, in {}:\n{errors}", library.display());
+                }
+            }
+        }
+    }
+}
 
-        #exceptions
+/// The `JAFFI_REGISTRY` table of the classes that were read from the classpath, with their wrapper and native methods
+fn generate_class_registry(objects: &[Object], class_ffis: &[ClassFfi]) -> TokenStream {
+    let classes = objects
+        .iter()
+        .filter(|object| object.fingerprint.is_some())
+        .map(|object| {
+            let name = object.java_name.as_str();
+            let super_class = match &object.super_class {
+                Some(super_class) => {
+                    let super_class = super_class.as_str();
+                    quote! { Some(#super_class) }
+                }
+                None => quote! { None },
+            };
+            let interfaces = object.declared_interfaces.iter().map(JavaDesc::as_str);
 
-        #objects
+            // the factories of the wrapper are the static native methods
+            let natives = class_ffis
+                .iter()
+                .filter(|class_ffi| class_ffi.class_name == name)
+                .flat_map(|class_ffi| &class_ffi.functions);
+            let methods = object
+                .methods
+                .iter()
+                .filter(|func| !func.native_factory)
+                .chain(natives)
+                .map(|func| {
+                    let name = &func.name;
+                    let signature = func.signature.as_str();
+                    let rust_name = func.rust_method_name.for_rust_ident().to_string();
+                    let is_static = func.is_static;
+                    let is_native = func.is_native;
 
-        #onload
+                    quote! {
+                        jaffi_support::class_registry::BoundMethod {
+                            name: #name,
+                            signature: #signature,
+                            rust_name: #rust_name,
+                            is_static: #is_static,
+                            is_native: #is_native,
+                        }
+                    }
+                });
+
+            quote! {
+                jaffi_support::class_registry::BoundClass {
+                    name: #name,
+                    super_class: #super_class,
+                    interfaces: &[#(#interfaces),*],
+                    methods: &[#(#methods),*],
+                }
+            }
+        });
 
-        #class_ffis
+    quote! {
+        /// The classes bound by this library, registered with `jaffi_support::class_registry` in `JNI_OnLoad`
+        pub static JAFFI_REGISTRY: &[jaffi_support::class_registry::BoundClass] = &[#(#classes),*];
     }
 }
 
@@ -611,18 +3141,26 @@ pub(crate) struct ClassFfi {
     pub(crate) class_name: String,
     pub(crate) trait_name: String,
     pub(crate) trait_impl: String,
+    /// The implementation is registered at load, instead of constructed with `from_env` for each call
+    pub(crate) registered_impl: bool,
+    /// The implementation gets a `CallCtx` instead of the `this` or `class`
+    pub(crate) context_impl: bool,
+    /// The class is implemented with the trait of its package, one of the `aggregate_packages`
+    pub(crate) aggregated: bool,
     pub(crate) functions: Vec<Function>,
 }
 
 #[allow(dead_code)]
+#[derive(Clone)]
 pub(crate) struct Function {
     pub(crate) name: String,
     pub(crate) object_java_desc: JavaDesc,
-    pub(crate) fn_export_ffi_name: ClassAndFuncAbi,
+    /// The names the function is exported as, the first is the implementation
+    pub(crate) fn_export_ffi_names: Vec<ClassAndFuncAbi>,
     pub(crate) class_ffi_name: RustTypeName,
     pub(crate) object_ffi_name: RustTypeName,
     pub(crate) rust_method_name: FuncAbi,
-    pub(crate) signature: JavaDesc,
+    pub(crate) signature: MethodDesc,
     pub(crate) is_static: bool,
     pub(crate) is_native: bool,
     pub(crate) is_constructor: bool,
@@ -630,10 +3168,35 @@ pub(crate) struct Function {
     pub(crate) result: RustTypeName,
     pub(crate) rs_result: RustTypeName,
     pub(crate) exceptions: BTreeSet<JavaDesc>,
+    /// The wrapper method of the Java fallback of a native method, the trait method gets a default calling it
+    pub(crate) java_fallback: Option<FuncAbi>,
+    /// The capacity of local references ensured before the native method converts its arguments
+    pub(crate) local_capacity: Option<i32>,
+    /// The element type of the varargs, the last argument, wrappers of these also get a method taking an iterator of the elements
+    pub(crate) varargs: Option<JniType>,
+    /// A static native method returning its own class, which the wrapper of the class calls as an associated function
+    pub(crate) native_factory: bool,
+    /// A `static native cancel(long)` of the `cancel_handles`, the trait method gets a default cancelling the handle
+    pub(crate) cancel_handle: bool,
+    /// The wrapper method catches all exceptions, as with `unchecked_exceptions`, from the `result` of the overrides
+    pub(crate) catch_all: bool,
+    /// The note of the `#[deprecated]` of the wrapper methods, if the Java method is `@Deprecated`
+    pub(crate) deprecated: Option<String>,
+    /// A declared method of the `late_bound_classes`, the wrapper looks it up with `jaffi_support::reflect`
+    pub(crate) late_bound: bool,
+    /// The bridge of a covariant override, its wrapper is a `#[doc(hidden)]` alias returning the type of the overridden method
+    pub(crate) bridge: bool,
+    /// A package-private method of the `package_private_classes`, the docs of the wrapper mark it
+    pub(crate) package_private: bool,
 }
 
+#[derive(Clone)]
 pub(crate) struct Arg {
     pub(crate) name: Ident,
+    /// The name of the parameter in Java, if the class file has it, i.e. compiled with `-parameters` or `-g`
+    pub(crate) java_name: Option<String>,
+    /// `null` is a valid value, marked `nullable` in the overrides, or converted to `None`, e.g. for boxed primitives
+    pub(crate) nullable: bool,
     pub(crate) ty: RustTypeName,
     pub(crate) rs_ty: RustTypeName,
 }
@@ -645,6 +3208,101 @@ pub(crate) struct Object {
     pub(crate) static_trait_name: RustTypeName,
     pub(crate) methods: Vec<Function>,
     pub(crate) interfaces: Vec<RustTypeName>,
+    /// The names of the constants, in ordinal order, if this is an enum
+    pub(crate) enum_constants: Vec<String>,
+    /// The `static final` fields with constant values
+    pub(crate) constants: Vec<Constant>,
+    /// A Rust struct mirroring the bean or record is generated
+    pub(crate) mirror: Option<Mirror>,
+    /// The permitted subclasses, if this is a sealed class or interface
+    pub(crate) permitted_subclasses: Vec<JavaDesc>,
+    /// The `ByteBuffer` field and the Rust struct laid out in it, if this is one of the `buffer_classes`
+    pub(crate) buffer: Option<BufferBacking>,
+    /// The single abstract method, if this is one of the `listener_interfaces`
+    pub(crate) listener: Option<Function>,
+    /// The path of the Rust type used in place of the wrapper, if this is one of the `bridges`
+    pub(crate) bridge: Option<TokenStream>,
+    /// The fingerprint of the class file, if it was read
+    pub(crate) fingerprint: Option<u64>,
+    /// The note of the `#[deprecated]` of the wrapper, if the Java class is `@Deprecated`
+    pub(crate) deprecated: Option<String>,
+    /// The superclass, if the class file was read and the class has one
+    pub(crate) super_class: Option<JavaDesc>,
+    /// The interfaces declared by the class file, whether or not they are wrapped
+    pub(crate) declared_interfaces: Vec<JavaDesc>,
+}
+
+/// The direct `ByteBuffer` field of a buffer-backed class, viewed as a `#[repr(C)]` Rust struct
+pub(crate) struct BufferBacking {
+    /// The name of the field, of type `java.nio.ByteBuffer`
+    pub(crate) field: String,
+    /// The path of the Rust struct, e.g. `crate::Particle`
+    pub(crate) rust_struct: TokenStream,
+}
+
+/// The properties of a bean or the components of a record, mirrored in a Rust struct
+pub(crate) enum Mirror {
+    /// Created with the no-argument constructor and written with the setters
+    Bean(Vec<MirrorProperty>),
+    /// Created with the canonical constructor
+    Record(Vec<MirrorProperty>),
+}
+
+/// A property of a bean, with a getter and a setter, or a component of a record, with an accessor
+pub(crate) struct MirrorProperty {
+    /// The name of the property, e.g. `firstName` for `getFirstName`
+    pub(crate) name: String,
+    pub(crate) getter: String,
+    pub(crate) setter: Option<String>,
+    /// The field descriptor of the property, e.g. `I`
+    pub(crate) descriptor: String,
+    pub(crate) ty: JniType,
+}
+
+/// A `static final` field initialized with a constant value in the class file
+pub(crate) struct Constant {
+    pub(crate) name: String,
+    pub(crate) value: ConstantValue,
+}
+
+/// The value of a constant, the type is from the descriptor of the field
+pub(crate) enum ConstantValue {
+    Boolean(bool),
+    Byte(i8),
+    Char(char),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(String),
+}
+
+impl ConstantValue {
+    /// Returns the Rust type and the value
+    fn to_type_and_value(&self) -> (TokenStream, TokenStream) {
+        match self {
+            Self::Boolean(v) => (quote! { bool }, quote! { #v }),
+            Self::Byte(v) => (quote! { i8 }, quote! { #v }),
+            Self::Char(v) => (quote! { char }, quote! { #v }),
+            Self::Short(v) => (quote! { i16 }, quote! { #v }),
+            Self::Int(v) => (quote! { i32 }, quote! { #v }),
+            Self::Long(v) => (quote! { i64 }, quote! { #v }),
+            Self::Float(v) if v.is_nan() => (quote! { f32 }, quote! { f32::NAN }),
+            Self::Float(v) if *v == f32::INFINITY => (quote! { f32 }, quote! { f32::INFINITY }),
+            Self::Float(v) if *v == f32::NEG_INFINITY => {
+                (quote! { f32 }, quote! { f32::NEG_INFINITY })
+            }
+            Self::Float(v) => (quote! { f32 }, quote! { #v }),
+            Self::Double(v) if v.is_nan() => (quote! { f64 }, quote! { f64::NAN }),
+            Self::Double(v) if *v == f64::INFINITY => (quote! { f64 }, quote! { f64::INFINITY }),
+            Self::Double(v) if *v == f64::NEG_INFINITY => {
+                (quote! { f64 }, quote! { f64::NEG_INFINITY })
+            }
+            Self::Double(v) => (quote! { f64 }, quote! { #v }),
+            Self::String(v) => (quote! { &'static str }, quote! { #v }),
+        }
+    }
 }
 
 impl From<ObjectType> for Object {
@@ -661,6 +3319,17 @@ impl From<ObjectType> for Object {
             static_trait_name,
             methods: Vec::new(),
             interfaces: Vec::new(),
+            enum_constants: Vec::new(),
+            constants: Vec::new(),
+            mirror: None,
+            permitted_subclasses: Vec::new(),
+            buffer: None,
+            listener: None,
+            bridge: None,
+            fingerprint: None,
+            deprecated: None,
+            super_class: None,
+            declared_interfaces: Vec::new(),
         }
     }
 }
@@ -686,10 +3355,10 @@ impl Return {
         }
     }
 
-    pub(crate) fn to_rs_type_name(&self) -> RustTypeName {
+    pub(crate) fn to_rs_type_name_with(&self, primitive_types: PrimitiveTypes) -> RustTypeName {
         match self {
             Self::Void => "()".into(),
-            Self::Val(ty) => ty.to_rs_type_name(),
+            Self::Val(ty) => ty.to_rs_type_name_with(primitive_types),
         }
     }
 }
@@ -760,6 +3429,112 @@ impl JniType {
         }
     }
 
+    /// The Rust type, with the choices of `primitive_types` for `byte`, `char` and `boolean`
+    pub(crate) fn to_rs_type_name_with(&self, primitive_types: PrimitiveTypes) -> RustTypeName {
+        match self {
+            Self::Ty(BaseJniTy::Jbyte) if primitive_types.unsigned_bytes => {
+                std::any::type_name::<u8>().into()
+            }
+            Self::Ty(BaseJniTy::Jchar) if primitive_types.utf16_chars => {
+                std::any::type_name::<u16>().into()
+            }
+            Self::Ty(BaseJniTy::Jboolean) if primitive_types.numeric_booleans => {
+                std::any::type_name::<u8>().into()
+            }
+            _ => self.to_rs_type_name(),
+        }
+    }
+
+    /// `byte[]`, passed as `JavaByteArray`
+    pub(crate) fn is_byte_array(&self) -> bool {
+        matches!(self, Self::Jarray(jarray) if jarray.dimensions == 1 && jarray.ty == BaseJniTy::Jbyte)
+    }
+
+    /// Arrays other than `byte[]` and the arrays of wrapped objects are passed as `UnsupportedArray`
+    pub(crate) fn is_unsupported_array(&self) -> bool {
+        matches!(self, Self::Jarray(jarray) if !jarray.is_supported())
+    }
+
+    /// The class of the elements of a single dimension object array, e.g. `Foo` of `Foo[]`, passed as `JavaObjectArray`
+    pub(crate) fn object_array_element(&self) -> Option<&JavaDesc> {
+        match self {
+            Self::Jarray(jarray) => jarray.object_element(),
+            _ => None,
+        }
+    }
+
+    /// The element type of a single dimension array, for the varargs of a method
+    pub(crate) fn varargs_element(&self) -> Option<Self> {
+        match self {
+            Self::Jarray(jarray) if jarray.dimensions == 1 => Some(Self::Ty(jarray.ty.clone())),
+            _ => None,
+        }
+    }
+
+    /// Sets the element type of an `Optional` or `CompletableFuture` from the generic signature, this does nothing for other types
+    pub(crate) fn set_element_type(&mut self, element: Option<String>) {
+        if let (
+            Self::Ty(BaseJniTy::Jobject(
+                ObjectType::Optional(ty) | ObjectType::CompletableFuture(ty),
+            )),
+            Some(element),
+        ) = (self, element)
+        {
+            **ty = ObjectType::from(JavaDesc::from(element));
+        }
+    }
+
+    /// Returns a `CompletableFuture` that is completed from a Rust future, for the results of native methods
+    pub(crate) fn use_future_bridge(&mut self) {
+        if let Self::Ty(BaseJniTy::Jobject(obj)) = self {
+            if obj.as_descriptor().as_str() == "java/util/concurrent/CompletableFuture" {
+                *obj = ObjectType::CompletableFuture(Box::new(ObjectType::JObject));
+            }
+        }
+    }
+
+    /// Converts `java.time.Instant`, `java.time.Duration` and `java.util.Date` objects, including `Optional` elements, to the `std::time` types
+    pub(crate) fn use_time_conversions(&mut self) {
+        self.replace_object(|desc| TimeType::from_descriptor(desc).map(ObjectType::Time));
+    }
+
+    /// Converts `java.util.UUID` objects, including `Optional` elements, to `uuid::Uuid`
+    pub(crate) fn use_uuid_conversions(&mut self) {
+        self.replace_object(|desc| (desc == "java/util/UUID").then_some(ObjectType::Uuid));
+    }
+
+    /// Converts `java.math.BigInteger` and `java.math.BigDecimal` objects, including `Optional` elements, to `BigInt` and `BigDecimal`
+    pub(crate) fn use_bignum_conversions(&mut self) {
+        self.replace_object(|desc| match desc {
+            "java/math/BigInteger" => Some(ObjectType::BigInteger),
+            "java/math/BigDecimal" => Some(ObjectType::BigDecimal),
+            _ => None,
+        });
+    }
+
+    /// Converts the objects of the `bridges`, including `Optional` elements, to their Rust types
+    pub(crate) fn use_bridges(&mut self, bridges: &HashMap<JavaDesc, String>) {
+        self.replace_object(|desc| {
+            let desc = JavaDesc::from(desc);
+            bridges
+                .get(&desc)
+                .map(|rust| ObjectType::Bridge(desc.clone(), rust.clone()))
+        });
+    }
+
+    /// Replaces the `Object`, or the `Object` element of an `Optional`, if `replace` returns a type for the descriptor
+    fn replace_object(&mut self, replace: impl FnOnce(&str) -> Option<ObjectType>) {
+        let obj = match self {
+            Self::Ty(BaseJniTy::Jobject(ObjectType::Optional(element))) => &mut **element,
+            Self::Ty(BaseJniTy::Jobject(obj)) => obj,
+            _ => return,
+        };
+
+        if let Some(replacement) = obj.as_object().and_then(|desc| replace(desc.as_str())) {
+            *obj = replacement;
+        }
+    }
+
     /// Takes the types from the class file and converts to Self.
     pub(crate) fn from_java(field_type: &FieldType<'_>) -> Self {
         fn base_jni_ty_from_java(ty: &Ty<'_>) -> BaseJniTy {
@@ -805,10 +3580,26 @@ impl JavaArray {
 
         match self.ty {
             BaseJniTy::Jbyte => "jaffi_support::arrays::JavaByteArray<'j>".into(),
+            BaseJniTy::Jobject(ref obj @ ObjectType::Object(_)) => {
+                RustTypeName::from("jaffi_support::arrays::JavaObjectArray<'j>")
+                    .with_generic(obj.to_jni_type_name())
+            }
             _ => "jaffi_support::arrays::UnsupportedArray<'j>".into(),
         }
     }
 
+    /// `byte[]` and the arrays of the wrappers of objects, e.g. `Foo[]`
+    fn is_supported(&self) -> bool {
+        self.dimensions == 1 && (self.ty == BaseJniTy::Jbyte || self.object_element().is_some())
+    }
+
+    fn object_element(&self) -> Option<&JavaDesc> {
+        match self.ty {
+            BaseJniTy::Jobject(ObjectType::Object(ref obj)) if self.dimensions == 1 => Some(obj),
+            _ => None,
+        }
+    }
+
     pub(crate) fn to_rs_type_name(&self) -> RustTypeName {
         self.to_jni_type_name()
     }
@@ -818,21 +3609,160 @@ impl JavaArray {
 pub(crate) enum ObjectType {
     JClass,
     JByteBuffer,
+    JCharSequence,
+    JInputStream,
     JObject,
+    JOutputStream,
     JString,
+    JStringBuilder,
     JThrowable,
+    Boxed(BoxedType),
+    /// `java.util.Optional`, the element type is from the generic signature, or `java.lang.Object`
+    Optional(Box<ObjectType>),
+    /// `java.util.concurrent.CompletableFuture` returned from native methods, the element type is from the generic signature
+    CompletableFuture(Box<ObjectType>),
+    /// Only used when `time_conversions` are enabled, otherwise these are `Object`s
+    Time(TimeType),
+    /// `java.util.UUID`, only used when `uuid_conversions` are enabled
+    Uuid,
+    /// `java.math.BigInteger`, only used when `bignum_conversions` are enabled
+    BigInteger,
+    /// `java.math.BigDecimal`, only used when `bignum_conversions` are enabled
+    BigDecimal,
+    /// A class of the `bridges`, converted to the path of the Rust type with `JavaBridge`
+    Bridge(JavaDesc, String),
     Object(JavaDesc),
 }
 
+/// `java.time` and `java.util.Date` types converted to `std::time` types
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
+pub(crate) enum TimeType {
+    Instant,
+    Duration,
+    Date,
+}
+
+impl TimeType {
+    fn from_descriptor(desc: &str) -> Option<Self> {
+        match desc {
+            "java/time/Instant" => Some(Self::Instant),
+            "java/time/Duration" => Some(Self::Duration),
+            "java/util/Date" => Some(Self::Date),
+            _ => None,
+        }
+    }
+
+    fn as_descriptor(self) -> JavaDesc {
+        match self {
+            Self::Instant => "java/time/Instant".into(),
+            Self::Duration => "java/time/Duration".into(),
+            Self::Date => "java/util/Date".into(),
+        }
+    }
+
+    fn to_jni_type_name(self) -> RustTypeName {
+        match self {
+            Self::Instant => "jaffi_support::time::JInstant<'j>".into(),
+            Self::Duration => "jaffi_support::time::JDuration<'j>".into(),
+            Self::Date => "jaffi_support::time::JDate<'j>".into(),
+        }
+    }
+
+    fn to_rs_type_name(self) -> RustTypeName {
+        match self {
+            Self::Instant | Self::Date => "std::time::SystemTime".into(),
+            Self::Duration => "std::time::Duration".into(),
+        }
+    }
+}
+
+/// The boxed primitives, e.g. `java.lang.Integer`, these are converted to `Option`s of the primitive
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
+pub(crate) enum BoxedType {
+    Boolean,
+    Byte,
+    Character,
+    Double,
+    Float,
+    Integer,
+    Long,
+    Short,
+}
+
+impl BoxedType {
+    const ALL: [Self; 8] = [
+        Self::Boolean,
+        Self::Byte,
+        Self::Character,
+        Self::Double,
+        Self::Float,
+        Self::Integer,
+        Self::Long,
+        Self::Short,
+    ];
+
+    fn class_name(self) -> &'static str {
+        match self {
+            Self::Boolean => "Boolean",
+            Self::Byte => "Byte",
+            Self::Character => "Character",
+            Self::Double => "Double",
+            Self::Float => "Float",
+            Self::Integer => "Integer",
+            Self::Long => "Long",
+            Self::Short => "Short",
+        }
+    }
+
+    fn from_descriptor(desc: &str) -> Option<Self> {
+        let class_name = desc.strip_prefix("java/lang/")?;
+        Self::ALL.into_iter().find(|b| b.class_name() == class_name)
+    }
+
+    fn as_descriptor(self) -> JavaDesc {
+        format!("java/lang/{}", self.class_name()).into()
+    }
+
+    fn to_jni_type_name(self) -> RustTypeName {
+        format!("jaffi_support::boxed::J{}<'j>", self.class_name()).into()
+    }
+
+    fn to_rs_type_name(self) -> RustTypeName {
+        let primitive = match self {
+            Self::Boolean => std::any::type_name::<bool>(),
+            Self::Byte => std::any::type_name::<i8>(),
+            Self::Character => std::any::type_name::<char>(),
+            Self::Double => std::any::type_name::<f64>(),
+            Self::Float => std::any::type_name::<f32>(),
+            Self::Integer => std::any::type_name::<i32>(),
+            Self::Long => std::any::type_name::<i64>(),
+            Self::Short => std::any::type_name::<i16>(),
+        };
+
+        format!("Option<{primitive}>").into()
+    }
+}
+
 impl ObjectType {
     pub(crate) fn as_descriptor(&self) -> JavaDesc {
         match self {
             Self::JClass => "java/lang/Class".into(),
             Self::JByteBuffer => "java/nio/ByteBuffer".into(),
+            Self::JCharSequence => "java/lang/CharSequence".into(),
+            Self::JInputStream => "java/io/InputStream".into(),
             Self::JObject => "java/lang/Object".into(),
+            Self::JOutputStream => "java/io/OutputStream".into(),
             Self::JString => "java/lang/String".into(),
+            Self::JStringBuilder => "java/lang/StringBuilder".into(),
             Self::JThrowable => "java/lang/Throwable".into(),
-            Self::Object(desc) => desc.clone(),
+            Self::Boxed(boxed) => boxed.as_descriptor(),
+            Self::Optional(_) => "java/util/Optional".into(),
+            Self::CompletableFuture(_) => "java/util/concurrent/CompletableFuture".into(),
+            Self::Time(time) => time.as_descriptor(),
+            Self::Uuid => "java/util/UUID".into(),
+            Self::BigInteger => "java/math/BigInteger".into(),
+            Self::BigDecimal => "java/math/BigDecimal".into(),
+            Self::Bridge(desc, _) | Self::Object(desc) => desc.clone(),
         }
     }
 
@@ -840,11 +3770,25 @@ impl ObjectType {
         match *self {
             Self::JClass => "jni::objects::JClass<'j>".into(),
             Self::JByteBuffer => "jni::objects::JByteBuffer<'j>".into(),
+            Self::JCharSequence => "jaffi_support::string::JCharSequence<'j>".into(),
+            Self::JInputStream => "jaffi_support::io::JInputStream<'j>".into(),
             Self::JObject => "jni::objects::JObject<'j>".into(),
+            Self::JOutputStream => "jaffi_support::io::JOutputStream<'j>".into(),
             Self::JString => "jni::objects::JString<'j>".into(),
+            Self::JStringBuilder => "jaffi_support::string::JStringBuilder<'j>".into(),
             Self::JThrowable => "jni::objects::JThrowable<'j>".into(),
-            Self::Object(ref obj) => {
-                RustTypeName::from(obj.escape_for_extern_fn().to_upper_camel_case()).append("<'j>")
+            Self::Boxed(boxed) => boxed.to_jni_type_name(),
+            Self::Optional(ref element) => {
+                RustTypeName::from("jaffi_support::optional::JOptional<'j>")
+                    .with_generic(element.to_jni_type_name())
+            }
+            Self::CompletableFuture(_) => "jaffi_support::future::JCompletableFuture<'j>".into(),
+            Self::Time(time) => time.to_jni_type_name(),
+            Self::Uuid => "jaffi_support::uuid::JUuid<'j>".into(),
+            Self::BigInteger => "jaffi_support::bignum::JBigInteger<'j>".into(),
+            Self::BigDecimal => "jaffi_support::bignum::JBigDecimal<'j>".into(),
+            Self::Bridge(ref obj, _) | Self::Object(ref obj) => {
+                RustTypeName::from(obj.to_rust_type_name()).append("<'j>")
             }
         }
     }
@@ -865,11 +3809,35 @@ impl ObjectType {
         match *self {
             Self::JClass => "jni::objects::JClass<'j>".into(),
             Self::JByteBuffer => "jni::objects::JByteBuffer<'j>".into(),
+            Self::JCharSequence => "String".into(),
+            Self::JInputStream => "jaffi_support::io::JavaInputStream<'j>".into(),
             Self::JObject => "jni::objects::JObject<'j>".into(),
+            Self::JOutputStream => "jaffi_support::io::JavaOutputStream<'j>".into(),
             Self::JString => "String".into(),
+            Self::JStringBuilder => "String".into(),
             Self::JThrowable => "jni::objects::JThrowable<'j>".into(),
+            Self::Boxed(boxed) => boxed.to_rs_type_name(),
+            Self::Optional(ref element) => {
+                RustTypeName::from("Option").with_generic(element.to_rs_type_name())
+            }
+            Self::CompletableFuture(ref element) => {
+                // `CompletableFuture<Void>` is completed with `()`
+                let output = match **element {
+                    Self::Object(ref obj) if obj.as_str() == "java/lang/Void" => {
+                        RustTypeName::unit()
+                    }
+                    _ => element.to_rs_type_name(),
+                };
+
+                RustTypeName::from("jaffi_support::future::BoxFuture").with_generic(output)
+            }
+            Self::Time(time) => time.to_rs_type_name(),
+            Self::Uuid => "jaffi_support::uuid::Uuid".into(),
+            Self::BigInteger => "jaffi_support::bignum::BigInt".into(),
+            Self::BigDecimal => "jaffi_support::bignum::BigDecimal".into(),
+            Self::Bridge(_, ref rust) => rust.as_str().into(),
             Self::Object(ref obj) => {
-                RustTypeName::from(obj.0.replace('/', "_").to_upper_camel_case()).append("<'j>")
+                RustTypeName::from(obj.to_rust_type_name()).append("<'j>")
             }
         }
     }
@@ -885,12 +3853,20 @@ impl<'o> From<&'o JavaDesc> for ObjectType {
     fn from(java_desc: &'o JavaDesc) -> Self {
         let path_name = java_desc.as_str();
         match path_name {
-            _ if &*path_name == "java/lang/Class" => Self::JClass,
-            _ if &*path_name == "java/nio/ByteBuffer" => Self::JByteBuffer,
-            _ if &*path_name == "java/lang/Object" => Self::JObject,
-            _ if &*path_name == "java/lang/String" => Self::JString,
-            _ if &*path_name == "java/lang/Throwable" => Self::JThrowable,
-            path_name => Self::Object(path_name.to_string().into()),
+            _ if path_name == "java/lang/Class" => Self::JClass,
+            _ if path_name == "java/nio/ByteBuffer" => Self::JByteBuffer,
+            _ if path_name == "java/lang/CharSequence" => Self::JCharSequence,
+            _ if path_name == "java/io/InputStream" => Self::JInputStream,
+            _ if path_name == "java/lang/Object" => Self::JObject,
+            _ if path_name == "java/io/OutputStream" => Self::JOutputStream,
+            _ if path_name == "java/lang/String" => Self::JString,
+            _ if path_name == "java/lang/StringBuilder" => Self::JStringBuilder,
+            _ if path_name == "java/lang/Throwable" => Self::JThrowable,
+            _ if path_name == "java/util/Optional" => Self::Optional(Box::new(Self::JObject)),
+            path_name => match BoxedType::from_descriptor(path_name) {
+                Some(boxed) => Self::Boxed(boxed),
+                None => Self::Object(path_name.to_string().into()),
+            },
         }
     }
 }
@@ -914,27 +3890,19 @@ pub(crate) struct JniAbi(String);
 impl FuncAbi {
     pub(crate) fn with_class(&self, class: &JavaDesc) -> ClassAndFuncAbi {
         let mut ffi_name = "Java_".to_string();
-        ffi_name.push_str(&class.escape_for_extern_fn());
+        ffi_name.push_str(&JniAbi::from(class.as_str()).0);
         ffi_name.push('_');
         ffi_name.push_str(&self.0 .0);
         ClassAndFuncAbi(JniAbi(ffi_name))
     }
 
-    pub(crate) fn with_descriptor(self, descriptor: &JavaDesc) -> Self {
-        // strip the '(', ')', and return from the descriptor
-        let descriptor = descriptor.0.strip_prefix('(').unwrap_or(&descriptor.0);
-        let descriptor = if let Some(pos) = descriptor.find(')') {
-            &descriptor[..pos]
-        } else {
-            descriptor
-        };
-
-        let abi_descriptor = JniAbi::from(descriptor);
+    pub(crate) fn with_descriptor(self, descriptor: &MethodDesc) -> Self {
+        let abi_descriptor = JniAbi::from(descriptor.parameter_descriptors().as_str());
 
         Self(JniAbi(format!("{self}__{abi_descriptor}")))
     }
 
-    fn for_rust_ident(&self) -> Ident {
+    pub(crate) fn for_rust_ident(&self) -> Ident {
         make_ident(&self.0 .0.to_snake_case())
     }
 
@@ -1021,11 +3989,10 @@ impl<S: AsRef<str>> From<S> for JniAbi {
                 ';' => abi_name.push_str("_2"),
                 '[' => abi_name.push_str("_3"),
                 _ if ch.is_ascii_alphanumeric() => abi_name.push(ch),
+                // each UTF-16 code unit is escaped, a surrogate pair as two
                 _ => {
-                    abi_name.push_str("_0");
-
-                    for c in ch.escape_unicode().skip(3).filter(|c| *c != '}') {
-                        abi_name.push(c);
+                    for unit in ch.encode_utf16(&mut [0; 2]) {
+                        write!(abi_name, "_0{unit:04x}").expect("write to String failed");
                     }
                 }
             }
@@ -1053,7 +4020,9 @@ impl fmt::Display for ClassAndFuncAbi {
     }
 }
 
-/// Descriptor in java, like `java.lang.String` or `(Ljava.lang.String;)J`
+/// The internal name of a class, like `java/lang/String`, the binary name `java.lang.String` is converted on creation
+///
+/// Method descriptors are [`MethodDesc`]s.
 #[derive(Clone, Debug, Hash, Eq, PartialEq, Ord, PartialOrd)]
 pub(crate) struct JavaDesc(String);
 
@@ -1062,15 +4031,23 @@ impl JavaDesc {
         &self.0
     }
 
-    pub(crate) fn escape_for_extern_fn(&self) -> String {
-        self.0.replace('/', "_")
+    /// The binary name, e.g. `java.lang.String` or `p.Outer$Inner`, as used by Java in e.g. `Class.getName`
+    pub(crate) fn binary_name(&self) -> String {
+        self.0.replace('/', ".")
+    }
+
+    /// The name of the Rust types of the class, e.g. `NetBluejekyllShapeCircle` for `net/bluejekyll/Shape$Circle`
+    ///
+    /// Characters that aren't ASCII alphanumerics are escaped, see `escape_non_ascii`.
+    pub(crate) fn to_rust_type_name(&self) -> String {
+        escape_non_ascii(&self.0).to_upper_camel_case()
     }
 
     /// Returns the final Class name, e.g. returns `String` for `java/lang/String`
     pub(crate) fn class_name(&self) -> &str {
         self.0
-            .split('/')
-            .last()
+            .rsplit('/')
+            .next()
             .expect("split should at least return empty string")
     }
 }
@@ -1093,12 +4070,56 @@ impl fmt::Display for JavaDesc {
     }
 }
 
+/// A method descriptor, like `(Ljava/lang/String;)J`, the class names are internal names
+///
+/// The descriptor is parsed once, on creation, with the parser of the [`model`](crate::model).
+#[derive(Clone, Debug, Hash, Eq, PartialEq)]
+pub(crate) struct MethodDesc {
+    descriptor: String,
+    parameters: Vec<JavaType>,
+    result: Option<JavaType>,
+}
+
+impl MethodDesc {
+    /// `None` if the descriptor isn't valid, e.g. `(Ljava.lang.String;)J` with a binary name
+    pub(crate) fn parse(descriptor: &str) -> Option<Self> {
+        let (parameters, result) = parse_method_descriptor(descriptor)?;
+        Some(Self {
+            descriptor: descriptor.to_string(),
+            parameters,
+            result,
+        })
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        &self.descriptor
+    }
+
+    /// The descriptors of the parameters, e.g. `ILjava/lang/String;` of `(ILjava/lang/String;)J`
+    pub(crate) fn parameter_descriptors(&self) -> String {
+        self.parameters.iter().map(JavaType::descriptor).collect()
+    }
+
+    /// The types of the parameters and the result, `None` for `void`
+    pub(crate) fn types(&self) -> (&[JavaType], Option<&JavaType>) {
+        (&self.parameters, self.result.as_ref())
+    }
+}
+
+impl fmt::Display for MethodDesc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.write_str(&self.descriptor)
+    }
+}
+
 /// Descriptor in java, like `java.lang.String` or `(Ljava.lang.String;)J`
 #[derive(Clone, Debug, Hash, Eq, PartialEq)]
 pub(crate) struct RustTypeName {
     path: Vec<Ident>,
     ty: Option<Ident>,
     lifetime: bool,
+    /// A type parameter, e.g. the `i32` of `Option<i32>`
+    generic: Option<Box<RustTypeName>>,
 }
 
 fn path_from_name(name: &str) -> (Vec<Ident>, &str) {
@@ -1106,7 +4127,13 @@ fn path_from_name(name: &str) -> (Vec<Ident>, &str) {
     let name = iter
         .next()
         .expect("even empty strings should return the empty string");
-    let path = iter.map(make_ident).collect();
+    // `crate`, `self` and `super` are valid at the start of paths, e.g. the `crate::Money` of the `bridges`
+    let path = iter
+        .map(|segment| match segment {
+            "crate" | "self" | "super" => format_ident!("{segment}"),
+            segment => make_ident(segment),
+        })
+        .collect();
 
     (path, name)
 }
@@ -1125,13 +4152,10 @@ impl RustTypeName {
                 path,
                 ty: Some(format_ident!("{}{}", ty, s)),
                 lifetime,
+                generic: self.generic.clone(),
             }
         } else {
-            Self {
-                path: Vec::new(),
-                ty: None,
-                lifetime: false,
-            }
+            Self::unit()
         }
     }
 
@@ -1148,21 +4172,74 @@ impl RustTypeName {
                 path,
                 ty: Some(format_ident!("{}{}", s, ty)),
                 lifetime,
+                generic: self.generic.clone(),
             }
         } else {
-            Self {
-                path: Vec::new(),
-                ty: None,
-                lifetime: false,
-            }
+            Self::unit()
         }
     }
 
+    /// Adds the type parameter, e.g. `Option` with `i32` is `Option<i32>`
+    pub(crate) fn with_generic(mut self, generic: RustTypeName) -> Self {
+        self.generic = Some(Box::new(generic));
+        self
+    }
+
     pub(crate) fn no_lifetime(&self) -> Self {
         Self {
             path: self.path.clone(),
             ty: self.ty.clone(),
             lifetime: false,
+            generic: self.generic.clone(),
+        }
+    }
+
+    /// `Vec<u8>`, the copied bytes of the `byte_vec_args`
+    pub(crate) fn is_byte_vec(&self) -> bool {
+        self.path.is_empty()
+            && self.ty.as_ref().is_some_and(|ty| ty == "Vec")
+            && self
+                .generic
+                .as_ref()
+                .is_some_and(|generic| generic.to_string() == "u8")
+    }
+
+    /// `String`, e.g. of a `java.lang.String` or a `java.lang.CharSequence`
+    pub(crate) fn is_string(&self) -> bool {
+        self.path.is_empty()
+            && self.generic.is_none()
+            && self.ty.as_ref().is_some_and(|ty| ty == "String")
+    }
+
+    /// An owned Rust value without references into the JVM, that the `_async` methods of the `async_wrappers` can send between threads
+    ///
+    /// Only the primitives, `String`, the `std::time` types and their `Option`s are, the other types, e.g. the `bridges`, may not be `Send`.
+    pub(crate) fn is_sendable(&self) -> bool {
+        let Some(ty) = &self.ty else {
+            return true;
+        };
+        if self.lifetime {
+            return false;
+        }
+
+        let path = self.path.iter().rev().map(ToString::to_string).collect::<Vec<_>>();
+        match (&path[..], &self.generic) {
+            ([], None) => matches!(
+                ty.to_string().as_str(),
+                "bool" | "i8" | "u8" | "char" | "u16" | "i16" | "i32" | "i64" | "f32" | "f64" | "String"
+            ),
+            ([], Some(generic)) => *ty == "Option" && generic.is_sendable(),
+            ([std, time], None) => std == "std" && time == "time",
+            _ => false,
+        }
+    }
+
+    fn unit() -> Self {
+        Self {
+            path: Vec::new(),
+            ty: None,
+            lifetime: false,
+            generic: None,
         }
     }
 }
@@ -1182,6 +4259,17 @@ impl From<String> for RustTypeName {
 
 impl From<&str> for RustTypeName {
     fn from(s: &str) -> Self {
+        // a type parameter, other than the lifetime, e.g. `Option<i32>`
+        if let Some((outer, generic)) = s
+            .strip_suffix('>')
+            .and_then(|s| s.split_once('<'))
+            .filter(|(_, generic)| *generic != "'j")
+        {
+            let mut outer = Self::from(outer);
+            outer.generic = Some(Box::new(Self::from(generic)));
+            return outer;
+        }
+
         let (path, s) = path_from_name(s);
         let (s, lifetime) = if s.ends_with("<'j>") {
             (s.trim_end_matches("<'j>"), true)
@@ -1190,16 +4278,13 @@ impl From<&str> for RustTypeName {
         };
 
         if s == "()" {
-            Self {
-                path: Vec::new(),
-                ty: None,
-                lifetime: false,
-            }
+            Self::unit()
         } else {
             Self {
                 path,
                 ty: Some(make_ident(s)),
                 lifetime,
+                generic: None,
             }
         }
     }
@@ -1208,7 +4293,11 @@ impl From<&str> for RustTypeName {
 impl fmt::Display for RustTypeName {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         if let Some(ty) = &self.ty {
-            write!(f, "{}", ty)
+            write!(f, "{}", ty)?;
+            if let Some(generic) = &self.generic {
+                write!(f, "<{}>", generic)?;
+            }
+            Ok(())
         } else {
             write!(f, "()")
         }
@@ -1219,7 +4308,13 @@ impl ToTokens for RustTypeName {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         if let Some(ty) = &self.ty {
             let name = ty;
-            let lifetime = if self.lifetime {
+            let lifetime = if let Some(generic) = &self.generic {
+                if self.lifetime {
+                    quote! {<'j, #generic>}
+                } else {
+                    quote! {<#generic>}
+                }
+            } else if self.lifetime {
                 quote! {<'j>}
             } else {
                 quote! {}
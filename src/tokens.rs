@@ -0,0 +1,390 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The generated code as `TokenStream`s, for tools that post-process or embed it
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use proc_macro2::{Delimiter, Ident, TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+
+use crate::{
+    ident::{escape_non_ascii, make_ident},
+    model::Model,
+    provenance::Provenance,
+    report::Report,
+    template::JavaDesc,
+};
+
+/// Names that a package module or the alias of an item would shadow, the imports of the generated code and the std prelude
+const RESERVED: &[&str] = &[
+    "exceptions",
+    "jni",
+    "jaffi_support",
+    "jaffi_test_harness",
+    "prelude",
+    "std",
+    "core",
+    "alloc",
+    "Exception",
+    "FromJavaToRust",
+    "FromRustToJava",
+    "IntoJavaValue",
+    "NullObject",
+    "JavaVM",
+    "JNIEnv",
+    "JClass",
+    "JObject",
+    "JValue",
+    "JThrowable",
+    "JNIString",
+    "JniError",
+    "AsMut",
+    "AsRef",
+    "Box",
+    "Clone",
+    "Copy",
+    "Default",
+    "DoubleEndedIterator",
+    "Drop",
+    "Eq",
+    "Err",
+    "ExactSizeIterator",
+    "Extend",
+    "Fn",
+    "FnMut",
+    "FnOnce",
+    "From",
+    "Into",
+    "IntoIterator",
+    "Iterator",
+    "None",
+    "Ok",
+    "Option",
+    "Ord",
+    "PartialEq",
+    "PartialOrd",
+    "Result",
+    "Send",
+    "Sized",
+    "Some",
+    "String",
+    "Sync",
+    "ToOwned",
+    "ToString",
+    "TryFrom",
+    "TryInto",
+    "Unpin",
+    "Vec",
+];
+
+/// The generated code, split up by class, returned from `Jaffi::generate_class_tokens`
+///
+/// The tokens of a single class refer to the imports, exceptions and other support items, they only compile together with the `support` tokens.
+///   `to_token_stream` returns everything, as written by `Jaffi::generate`, in the `inner_module` if that is set, and with the
+///   classes in their package modules with `package_modules`.
+pub struct GeneratedTokens {
+    pub(crate) support: TokenStream,
+    pub(crate) wrappers: BTreeMap<String, TokenStream>,
+    pub(crate) native_classes: BTreeMap<String, TokenStream>,
+    pub(crate) inner_module: Option<Ident>,
+    pub(crate) package_modules: bool,
+    pub(crate) report: Report,
+    pub(crate) model: Model,
+    pub(crate) provenance: Provenance,
+}
+
+impl GeneratedTokens {
+    /// The imports, exception types, `JNI_OnLoad` and `JNI_OnUnload` shared by all the classes
+    pub fn support(&self) -> &TokenStream {
+        &self.support
+    }
+
+    /// The wrapper of a class, specified as a java class name, i.e. `java.lang.Object`, including its enum, mirror or sealed enum
+    pub fn wrapper(&self, class: &str) -> Option<&TokenStream> {
+        self.wrappers.get(JavaDesc::from(class).as_str())
+    }
+
+    /// All the wrappers, by class, i.e. `java/lang/Object`
+    pub fn wrappers(&self) -> impl Iterator<Item = (&str, &TokenStream)> + '_ {
+        self.wrappers
+            .iter()
+            .map(|(class, tokens)| (class.as_str(), tokens))
+    }
+
+    /// The `*Rs` trait and the exported functions of a class with native methods, specified as a java class name
+    pub fn native_class(&self, class: &str) -> Option<&TokenStream> {
+        self.native_classes.get(JavaDesc::from(class).as_str())
+    }
+
+    /// All the classes with native methods, by class, i.e. `net/bluejekyll/NativeStrings`
+    pub fn native_classes(&self) -> impl Iterator<Item = (&str, &TokenStream)> + '_ {
+        self.native_classes
+            .iter()
+            .map(|(class, tokens)| (class.as_str(), tokens))
+    }
+
+    /// The methods and types that were skipped, downgraded or renamed
+    pub fn report(&self) -> &Report {
+        &self.report
+    }
+
+    /// The classes, methods and types the code was generated from
+    pub fn model(&self) -> &Model {
+        &self.model
+    }
+}
+
+impl ToTokens for GeneratedTokens {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let mut generated = self.support.clone();
+        if self.package_modules {
+            let classes = self.wrappers.iter().chain(&self.native_classes);
+            generated.extend(generate_package_modules(classes, &self.support));
+        } else {
+            generated.extend(self.wrappers.values().cloned());
+            generated.extend(self.native_classes.values().cloned());
+        }
+
+        match &self.inner_module {
+            Some(inner_module) => tokens.extend(quote! {
+                mod #inner_module {
+                    #generated
+                }
+
+                pub use self::#inner_module::*;
+            }),
+            None => tokens.extend(generated),
+        }
+    }
+}
+
+/// The modules of the package of a class, i.e. `net`, `bluejekyll` for `net/bluejekyll/NativeStrings`
+///
+/// Names that are used by the generated code get a `_` suffix, e.g. `jni_`.
+pub(crate) fn package_path(class: &str) -> Vec<Ident> {
+    let Some((package, _)) = class.rsplit_once('/') else {
+        return Vec::new();
+    };
+
+    package
+        .split('/')
+        .map(|segment| {
+            let name = escape_non_ascii(segment).replace('$', "_00024");
+            if RESERVED.contains(&name.as_str()) {
+                make_ident(&format!("{name}_"))
+            } else {
+                make_ident(&name)
+            }
+        })
+        .collect()
+}
+
+/// The public items declared at the top level of the tokens of a class, with their visibility, e.g. `pub(crate)`
+fn public_items(tokens: &TokenStream) -> Vec<(TokenStream, Ident)> {
+    const ITEMS: &[&str] = &[
+        "struct", "enum", "union", "trait", "fn", "mod", "static", "const", "type",
+    ];
+
+    let tokens = tokens.clone().into_iter().collect::<Vec<_>>();
+    let mut items = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        if !matches!(token, TokenTree::Ident(ident) if ident == "pub") {
+            continue;
+        }
+
+        let mut rest = tokens[i + 1..].iter().peekable();
+        let mut visibility = quote! { pub };
+        if let Some(TokenTree::Group(group)) = rest.peek() {
+            if group.delimiter() == Delimiter::Parenthesis {
+                visibility.extend(Some(TokenTree::Group(group.clone())));
+                rest.next();
+            }
+        }
+
+        // the qualifiers, e.g. `unsafe extern "system" fn` or `static mut`, are skipped up to the name
+        let mut is_item = false;
+        for token in rest {
+            match token {
+                TokenTree::Ident(ident) if ITEMS.iter().any(|item| ident == item) => is_item = true,
+                TokenTree::Ident(ident)
+                    if ident == "mut" || ident == "unsafe" || ident == "extern" => {}
+                TokenTree::Literal(_) => {}
+                TokenTree::Ident(name) if is_item && name != "_" => {
+                    items.push((visibility, name.clone()));
+                    break;
+                }
+                _ => break,
+            }
+        }
+    }
+
+    items
+}
+
+/// A module of a Java package, with the classes of the package and the modules of its subpackages
+#[derive(Default)]
+struct PackageModule {
+    package: String,
+    classes: TokenStream,
+    items: Vec<(TokenStream, Ident)>,
+    subpackages: BTreeMap<Ident, PackageModule>,
+}
+
+impl PackageModule {
+    /// The module, the items are re-exported with `self::{path}` from the top in `re_exports`
+    fn to_tokens(
+        &self,
+        name: &Ident,
+        path: &[&Ident],
+        used_names: &BTreeSet<String>,
+        re_exports: &mut TokenStream,
+    ) -> TokenStream {
+        // the classes refer to the support items and the other classes at the top
+        let supers = std::iter::repeat_n(quote! { super:: }, path.len());
+        let imports = (!self.classes.is_empty()).then(|| quote! { use #(#supers)* *; });
+        let doc = format!(
+            "The classes of the Java package `{}`",
+            self.package.replace('/', ".")
+        );
+
+        // the items prefixed with the package, i.e. `NetBluejekyll`, also get the short name here
+        let prefix = JavaDesc::from(self.package.as_str()).to_rust_type_name();
+        let aliases = self
+            .items
+            .iter()
+            .filter_map(|(_, item)| {
+                let item_name = item.to_string();
+                let alias = item_name.strip_prefix(&prefix)?;
+                (alias.starts_with(|ch: char| ch.is_ascii_uppercase())
+                    && !used_names.contains(alias)
+                    && !RESERVED.contains(&alias))
+                .then(|| {
+                    let alias = make_ident(alias);
+                    quote! {
+                        #[allow(deprecated)]
+                        pub use self::#item as #alias;
+                    }
+                })
+            })
+            .collect::<TokenStream>();
+
+        for (visibility, item) in &self.items {
+            // the deprecated items only warn where they are used
+            re_exports.extend(quote! {
+                #[allow(deprecated)]
+                #visibility use self::#(#path)::* :: #item;
+            });
+        }
+
+        let subpackages = self
+            .subpackages
+            .iter()
+            .map(|(name, module)| {
+                let mut path = path.to_vec();
+                path.push(name);
+                module.to_tokens(name, &path, used_names, re_exports)
+            })
+            .collect::<TokenStream>();
+        let classes = &self.classes;
+
+        quote! {
+            #[doc = #doc]
+            pub mod #name {
+                #imports
+
+                #aliases
+
+                #classes
+
+                #subpackages
+            }
+        }
+    }
+}
+
+/// The classes in modules of their packages, with their items re-exported at the top
+fn generate_package_modules<'c>(
+    classes: impl Iterator<Item = (&'c String, &'c TokenStream)>,
+    support: &TokenStream,
+) -> TokenStream {
+    let mut root = PackageModule::default();
+    let mut used_names = public_items(support)
+        .into_iter()
+        .map(|(_, item)| item.to_string())
+        .collect::<BTreeSet<_>>();
+
+    for (class, tokens) in classes {
+        let items = public_items(tokens);
+        used_names.extend(items.iter().map(|(_, item)| item.to_string()));
+
+        let mut module = &mut root;
+        for (name, segment) in package_path(class).into_iter().zip(class.split('/')) {
+            let package = match module.package.as_str() {
+                "" => segment.to_string(),
+                parent => format!("{parent}/{segment}"),
+            };
+            module = module.subpackages.entry(name).or_default();
+            module.package = package;
+        }
+        module.classes.extend(tokens.clone());
+        module.items.extend(items);
+    }
+
+    // classes in the default package stay at the top
+    let mut tokens = root.classes;
+    let mut re_exports = TokenStream::new();
+    for (name, module) in &root.subpackages {
+        tokens.extend(module.to_tokens(name, &[name], &used_names, &mut re_exports));
+    }
+    tokens.extend(re_exports);
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_package_path() {
+        let path = |class| {
+            package_path(class)
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(path("net/bluejekyll/Foo"), ["net", "bluejekyll"]);
+        assert_eq!(path("Foo"), Vec::<String>::new());
+        assert_eq!(path("jni/impl/Foo"), ["jni_", "r#impl"]);
+    }
+
+    #[test]
+    fn test_public_items() {
+        let tokens = quote! {
+            use super::Foo;
+            pub struct Foo<'j>(JObject<'j>);
+            impl<'j> Foo<'j> {
+                pub fn bar(&self) {}
+            }
+            pub(crate) static mut FOO_CLASS: u8 = 0;
+            #[no_mangle]
+            pub extern "system" fn Java_Foo_bar() {}
+            const _: () = ();
+        };
+
+        let items = public_items(&tokens)
+            .into_iter()
+            .map(|(visibility, item)| format!("{visibility} {item}"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            items,
+            ["pub Foo", "pub (crate) FOO_CLASS", "pub Java_Foo_bar"]
+        );
+    }
+}
@@ -0,0 +1,119 @@
+// Copyright 2022 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Verifies the exported names of native methods against the JNI name mangling rules.
+//!
+//! The names are derived again here, independently of the generator, so that a mangling mistake fails the build
+//!   instead of being found as an `UnsatisfiedLinkError` when Java first calls the method.
+
+use std::{collections::HashMap, fmt::Write};
+
+use crate::{
+    template::{ClassFfi, JavaDesc},
+    Error,
+};
+
+/// Mangles a class name, method name or argument descriptor, as specified in "Resolving Native Method Names" of the JNI spec
+fn mangle(name: &str) -> String {
+    let mut mangled = String::with_capacity(name.len());
+
+    // the escapes are of UTF-16 code units, characters outside the BMP are escaped as surrogate pairs
+    for unit in name.encode_utf16() {
+        match char::from_u32(u32::from(unit)) {
+            Some('/') => mangled.push('_'),
+            Some('_') => mangled.push_str("_1"),
+            Some(';') => mangled.push_str("_2"),
+            Some('[') => mangled.push_str("_3"),
+            Some(ch) if ch.is_ascii_alphanumeric() => mangled.push(ch),
+            _ => write!(mangled, "_0{unit:04x}").expect("write to String failed"),
+        }
+    }
+
+    mangled
+}
+
+/// `Java_{class}_{method}`
+fn short_name(class: &str, method: &str) -> String {
+    format!("Java_{}_{}", mangle(class), mangle(method))
+}
+
+/// `Java_{class}_{method}__{arguments}`
+fn long_name(class: &str, method: &str, descriptor: &str) -> String {
+    let arguments = descriptor
+        .strip_prefix('(')
+        .and_then(|descriptor| descriptor.split_once(')'))
+        .map(|(arguments, _result)| arguments)
+        .unwrap_or(descriptor);
+
+    format!("{}__{}", short_name(class, method), mangle(arguments))
+}
+
+/// Checks the export names of all the native methods, returning an error listing every name that the JVM would not link
+pub(crate) fn verify_export_names(class_ffis: &[ClassFfi]) -> Result<(), Error> {
+    let mut mismatches = String::new();
+
+    for class_ffi in class_ffis {
+        let class = JavaDesc::from(class_ffi.class_name.as_str());
+        let overloads = class_ffi.functions.iter().fold(
+            HashMap::<&str, usize>::new(),
+            |mut overloads, func| {
+                *overloads.entry(&func.name).or_default() += 1;
+                overloads
+            },
+        );
+
+        for func in &class_ffi.functions {
+            let short = short_name(class.as_str(), &func.name);
+            let long = long_name(class.as_str(), &func.name, func.signature.as_str());
+
+            // the JVM links the short name first, it would be linked for every overload
+            let expected = if overloads[func.name.as_str()] > 1 {
+                vec![long]
+            } else {
+                vec![short, long]
+            };
+
+            for generated in &func.fn_export_ffi_names {
+                let generated = generated.to_string();
+                if !expected.contains(&generated) {
+                    writeln!(
+                        mismatches,
+                        "  {class}.{}{}: generated `{generated}`, expected `{}`",
+                        func.name,
+                        func.signature,
+                        expected.join("` or `")
+                    )
+                    .expect("write to String failed");
+                }
+            }
+        }
+    }
+
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("native methods would fail to link, the generated export names are wrong:\n{mismatches}").into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mangle() {
+        assert_eq!(
+            long_name("p/q/r/A", "f", "(ILjava/lang/String;)D"),
+            "Java_p_q_r_A_f__ILjava_lang_String_2"
+        );
+        assert_eq!(
+            short_name("p/Outer$Inner_1", "get_[]"),
+            "Java_p_Outer_00024Inner_11_get_1_3_0005d"
+        );
+        assert_eq!(mangle("i❤'🦀"), "i_02764_00027_0d83e_0dd80");
+    }
+}